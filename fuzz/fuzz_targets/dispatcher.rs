@@ -0,0 +1,113 @@
+//! Feeds arbitrary instruction data to the deployed program's dispatcher
+//!
+//! Every input is sent as-is, discriminator included, against a fixed owner,
+//! their default vault, the System Program and the stats PDA, which is the
+//! account set most instructions start with; Deposit also gets the config PDA
+//! it takes next. The program runs as built by
+//! `cargo build-sbf`, so a malformed payload that makes it panic, e.g. on an
+//! out-of-bounds read or an overflow, fails the target; ordinary errors don't.
+//! State carries over between inputs, so deposits made by one input are there
+//! for the withdrawals of the next.
+#![no_main]
+
+use std::sync::Mutex;
+
+use blueshift_vault::testing::{load_vault_program_from, PROGRAM_PATH};
+use libfuzzer_sys::fuzz_target;
+use litesvm::LiteSVM;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+// System Program ID
+const SYSTEM_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("11111111111111111111111111111111");
+
+// Program ID from lib.rs
+const PROGRAM_ID: Pubkey = Pubkey::new_from_array(blueshift_vault::ID);
+
+struct Harness {
+    svm: LiteSVM,
+    owner: Keypair,
+    accounts: Vec<AccountMeta>,
+    config: Pubkey,
+}
+
+impl Harness {
+    fn new() -> Self {
+        let mut svm = LiteSVM::new();
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../");
+        load_vault_program_from(&mut svm, format!("{path}{PROGRAM_PATH}"))
+            .expect("Failed to load program");
+
+        let owner = Keypair::new_from_array([7u8; 32]);
+        let (vault, _bump) =
+            Pubkey::find_program_address(&[b"vault", owner.pubkey().as_ref()], &PROGRAM_ID);
+        let (stats, _bump) = Pubkey::find_program_address(&[b"stats"], &PROGRAM_ID);
+        let (config, _bump) = Pubkey::find_program_address(&[b"config"], &PROGRAM_ID);
+
+        let accounts = vec![
+            AccountMeta::new(owner.pubkey(), true), // owner (signer, writable)
+            AccountMeta::new(vault, false),         // vault (writable)
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system program
+            AccountMeta::new(stats, false),         // stats (writable)
+        ];
+
+        Self {
+            svm,
+            owner,
+            accounts,
+            config,
+        }
+    }
+
+    fn run(&mut self, data: &[u8]) {
+        // Keep the owner able to pay fees and fund deposits
+        if self.svm.get_balance(&self.owner.pubkey()).unwrap_or(0) < LAMPORTS_PER_SOL {
+            self.svm
+                .airdrop(&self.owner.pubkey(), 100 * LAMPORTS_PER_SOL)
+                .expect("Failed to airdrop");
+        }
+
+        let mut accounts = self.accounts.clone();
+        if data.first() == Some(blueshift_vault::Deposit::DISCRIMINATOR) {
+            accounts.push(AccountMeta::new_readonly(self.config, false)); // config
+        }
+
+        let instruction = Instruction {
+            program_id: PROGRAM_ID,
+            accounts,
+            data: data.to_vec(),
+        };
+
+        // A fresh blockhash keeps repeated inputs from being deduplicated
+        self.svm.expire_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&self.owner.pubkey()),
+            &[&self.owner],
+            self.svm.latest_blockhash(),
+        );
+
+        if let Err(failure) = self.svm.send_transaction(tx) {
+            assert!(
+                !failure.meta.logs.iter().any(|log| log.contains("panicked")),
+                "Program panicked on {data:?}: {:#?}",
+                failure.meta.logs
+            );
+        }
+    }
+}
+
+static HARNESS: Mutex<Option<Harness>> = Mutex::new(None);
+
+fuzz_target!(|data: &[u8]| {
+    HARNESS
+        .lock()
+        .unwrap()
+        .get_or_insert_with(Harness::new)
+        .run(data);
+});
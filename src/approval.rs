@@ -0,0 +1,119 @@
+use pinocchio::{
+    account_info::AccountInfo, pubkey::Pubkey, sysvars::instructions::Instructions, ProgramResult,
+};
+
+use crate::error::VaultError;
+
+/// Ed25519 signature verification precompile
+pub const ED25519_PROGRAM_ID: Pubkey =
+    pinocchio_pubkey::from_str("Ed25519SigVerify111111111111111111111111111");
+
+/// Size of a withdraw approval message
+pub const APPROVAL_MESSAGE_LEN: usize = 56;
+
+/// Size of a withdraw-all approval message that tips its relayer
+pub const TIP_APPROVAL_MESSAGE_LEN: usize = 88;
+
+/// Size of the precompile's data header, `[num_signatures: u8][padding: u8]`
+const ED25519_HEADER_LEN: usize = 2;
+
+/// Size of one signature's offsets in the precompile's data
+const ED25519_OFFSETS_LEN: usize = 14;
+
+/// Instruction index the precompile reads as "this instruction"
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// Returns the message an owner signs to approve withdrawing `amount` from
+/// `vault` until `expiry_ts`
+///
+/// Layout: `[vault: Pubkey][amount: u64][nonce: u64][expiry_ts: i64]`. Naming
+/// the vault keeps an approval from being replayed against another of the
+/// owner's vaults, whose nonces count separately, and the expiry keeps a relayer
+/// from sitting on an approval and submitting it much later.
+#[inline(always)]
+pub fn approval_message(
+    vault: &Pubkey,
+    amount: u64,
+    nonce: u64,
+    expiry_ts: i64,
+) -> [u8; APPROVAL_MESSAGE_LEN] {
+    let mut message = [0u8; APPROVAL_MESSAGE_LEN];
+    message[..32].copy_from_slice(vault);
+    message[32..40].copy_from_slice(&amount.to_le_bytes());
+    message[40..48].copy_from_slice(&nonce.to_le_bytes());
+    message[48..56].copy_from_slice(&expiry_ts.to_le_bytes());
+    message
+}
+
+/// Returns the message an owner signs to let `relayer` withdraw all of `vault`
+/// until `expiry_ts`, keeping `tip` lamports for itself
+///
+/// Layout: `[vault: Pubkey][tip: u64][nonce: u64][relayer: Pubkey][expiry_ts: i64]`.
+/// Naming the relayer keeps anyone else from submitting the approval to take
+/// the tip, the expiry works like `approval_message`'s, and the length keeps it
+/// from passing as one.
+#[inline(always)]
+pub fn tip_approval_message(
+    vault: &Pubkey,
+    tip: u64,
+    nonce: u64,
+    relayer: &Pubkey,
+    expiry_ts: i64,
+) -> [u8; TIP_APPROVAL_MESSAGE_LEN] {
+    let mut message = [0u8; TIP_APPROVAL_MESSAGE_LEN];
+    message[..32].copy_from_slice(vault);
+    message[32..40].copy_from_slice(&tip.to_le_bytes());
+    message[40..48].copy_from_slice(&nonce.to_le_bytes());
+    message[48..80].copy_from_slice(relayer);
+    message[80..88].copy_from_slice(&expiry_ts.to_le_bytes());
+    message
+}
+
+/// Checks that the instruction right before the current one verifies `signer`'s
+/// ed25519 signature over `message`
+///
+/// The precompile fails the whole transaction on a bad signature, so all that's
+/// left is to check which key and message it verified. It has to verify exactly
+/// one signature, with the key, signature and message all in its own data;
+/// anything else fails with `InvalidApproval`.
+pub fn check_ed25519_approval(
+    instructions_sysvar: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> ProgramResult {
+    let instructions = Instructions::try_from(instructions_sysvar)?;
+    let verify = instructions
+        .get_instruction_relative(-1)
+        .map_err(|_| VaultError::InvalidApproval)?;
+
+    if verify.get_program_id().ne(&ED25519_PROGRAM_ID) {
+        return Err(VaultError::InvalidApproval.into());
+    }
+
+    let data = verify.get_instruction_data();
+    if data.len() < ED25519_HEADER_LEN + ED25519_OFFSETS_LEN || data[0].ne(&1) {
+        return Err(VaultError::InvalidApproval.into());
+    }
+
+    // `[signature_offset][signature_ix][public_key_offset][public_key_ix]
+    // [message_offset][message_size][message_ix]`, all `u16`
+    let offsets = &data[ED25519_HEADER_LEN..ED25519_HEADER_LEN + ED25519_OFFSETS_LEN];
+    let field = |index: usize| u16::from_le_bytes([offsets[2 * index], offsets[2 * index + 1]]);
+    if [field(1), field(3), field(6)]
+        .iter()
+        .any(|instruction| instruction.ne(&CURRENT_INSTRUCTION))
+    {
+        return Err(VaultError::InvalidApproval.into());
+    }
+
+    let public_key_offset = field(2) as usize;
+    let (message_offset, message_size) = (field(4) as usize, field(5) as usize);
+
+    let verified_key = data.get(public_key_offset..public_key_offset + 32);
+    let verified_message = data.get(message_offset..message_offset + message_size);
+    if verified_key.ne(&Some(signer.as_ref())) || verified_message.ne(&Some(message)) {
+        return Err(VaultError::InvalidApproval.into());
+    }
+
+    Ok(())
+}
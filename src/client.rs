@@ -0,0 +1,582 @@
+//! Off-chain helpers for reading vaults, shared by tests and clients.
+
+extern crate alloc;
+
+use alloc::{string::String, vec, vec::Vec};
+
+use solana_account::Account;
+use solana_instruction::{AccountMeta, Instruction};
+use solana_pubkey::Pubkey;
+
+/// Program id as an off-chain [`Pubkey`]
+pub const PROGRAM_ID: Pubkey = Pubkey::new_from_array(crate::ID);
+
+/// Source of on-chain accounts, such as an RPC client or a LiteSVM instance.
+///
+/// Closures taking an address and returning the account implement it, so
+/// callers can adapt any client without a wrapper type:
+///
+/// ```ignore
+/// let balance = client::vault_balance(&|address: &Pubkey| svm.get_account(address), &owner);
+/// ```
+pub trait AccountFetcher {
+    /// Returns the account at `address`, or `None` if it does not exist
+    fn fetch_account(&self, address: &Pubkey) -> Option<Account>;
+}
+
+impl<F> AccountFetcher for F
+where
+    F: Fn(&Pubkey) -> Option<Account>,
+{
+    fn fetch_account(&self, address: &Pubkey) -> Option<Account> {
+        self(address)
+    }
+}
+
+/// Derives the vault PDA and its canonical bump for `owner` and vault `id`
+pub fn find_vault_address(owner: &Pubkey, id: u64) -> (Pubkey, u8) {
+    find_salted_vault_address(owner, id, &crate::pda::DEFAULT_SALT)
+}
+
+/// Derives the vault PDA and its canonical bump for `owner`, vault `id` and `salt`
+pub fn find_salted_vault_address(owner: &Pubkey, id: u64, salt: &[u8; 32]) -> (Pubkey, u8) {
+    let id = id.to_le_bytes();
+    Pubkey::find_program_address(
+        &[
+            crate::pda::VAULT_SEED,
+            owner.as_ref(),
+            crate::pda::id_seed(&id),
+            crate::pda::salt_seed(salt),
+        ],
+        &PROGRAM_ID,
+    )
+}
+
+/// Derives the vault PDA and its canonical bump for `owner`, vault `id` and an
+/// application-defined `seed` of up to 32 bytes, or `None` for a longer seed
+///
+/// Instructions address the vault by `pda::application_salt(seed)` as its salt.
+pub fn find_seeded_vault_address(owner: &Pubkey, id: u64, seed: &[u8]) -> Option<(Pubkey, u8)> {
+    let salt = crate::pda::application_salt(seed).ok()?;
+    Some(find_salted_vault_address(owner, id, &salt))
+}
+
+/// Derives the vault PDAs and canonical bumps of `owner`'s unsalted vaults
+/// `ids`, in order
+///
+/// Every address still needs its own bump search, so this saves the caller
+/// the loop and the allocations rather than any hashing.
+pub fn derive_vaults(owner: &Pubkey, ids: &[u64]) -> Vec<(Pubkey, u8)> {
+    let mut vaults = Vec::with_capacity(ids.len());
+    vaults.extend(ids.iter().map(|id| find_vault_address(owner, *id)));
+    vaults
+}
+
+/// Builds a Deposit of `amount` lamports into `owner`'s default vault
+pub fn deposit_instruction(owner: &Pubkey, amount: u64) -> Instruction {
+    let mut data = vec![*crate::Deposit::DISCRIMINATOR];
+    data.extend_from_slice(&amount.to_le_bytes());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vault_accounts(owner),
+        data,
+    }
+}
+
+/// Builds an OpenVault creating `owner`'s default vault with no flags or label
+pub fn open_vault_instruction(owner: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new(find_vault_address(owner, 0).0, false),
+            AccountMeta::new_readonly(Pubkey::new_from_array(pinocchio_system::ID), false),
+            AccountMeta::new(find_registry_address(owner).0, false),
+            AccountMeta::new(find_stats_address().0, false),
+            AccountMeta::new_readonly(find_config_address().0, false),
+        ],
+        data: vec![*crate::OpenVault::DISCRIMINATOR],
+    }
+}
+
+/// Builds [`open_vault_instruction`] followed by [`deposit_instruction`], so one
+/// transaction opens `owner`'s default vault and funds it with `amount` lamports
+///
+/// The transaction fails if the vault already exists; use
+/// [`deposit_instruction`] alone then.
+pub fn create_and_deposit(owner: &Pubkey, amount: u64) -> Vec<Instruction> {
+    vec![
+        open_vault_instruction(owner),
+        deposit_instruction(owner, amount),
+    ]
+}
+
+/// Builds a MigrateVault moving `owner`'s unsalted vault `old_id` to the
+/// unsalted address of `new_id`
+pub fn migrate_vault_instruction(owner: &Pubkey, old_id: u64, new_id: u64) -> Instruction {
+    // `[old_id][old_salt][new_id][new_salt]`
+    let mut data = vec![*crate::MigrateVault::DISCRIMINATOR];
+    data.extend_from_slice(&old_id.to_le_bytes());
+    data.extend_from_slice(&crate::pda::DEFAULT_SALT);
+    data.extend_from_slice(&new_id.to_le_bytes());
+    data.extend_from_slice(&crate::pda::DEFAULT_SALT);
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new(find_vault_address(owner, old_id).0, false),
+            AccountMeta::new(find_vault_address(owner, new_id).0, false),
+            AccountMeta::new_readonly(Pubkey::new_from_array(pinocchio_system::ID), false),
+            AccountMeta::new(find_registry_address(owner).0, false),
+        ],
+        data,
+    }
+}
+
+/// Builds the instructions that close `owner`'s unsalted vault `old_id` and
+/// open `new_id` in its place with the balance carried over, in the order one
+/// transaction has to send them
+///
+/// MigrateVault does both halves itself, so nothing is left half-moved if the
+/// transaction fails: the old vault stays as it was and the new address
+/// untouched. It fails if `new_id`'s vault already exists.
+pub fn migrate(owner: &Pubkey, old_id: u64, new_id: u64) -> Vec<Instruction> {
+    vec![migrate_vault_instruction(owner, old_id, new_id)]
+}
+
+/// Builds a Withdraw that closes `owner`'s default vault
+pub fn withdraw_instruction(owner: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vault_accounts(owner),
+        data: vec![*crate::Withdraw::DISCRIMINATOR],
+    }
+}
+
+/// Appends the checksum byte a program built with `verify-checksum` expects to
+/// `instruction`'s data
+pub fn with_checksum(mut instruction: Instruction) -> Instruction {
+    let checksum = crate::parse::checksum(&instruction.data);
+    instruction.data.push(checksum);
+    instruction
+}
+
+/// Builds a Withdraw that closes `owner`'s default vault, and succeeds without
+/// doing anything if the vault has no lamports, e.g. because it's already closed
+pub fn withdraw_idempotent_instruction(owner: &Pubkey) -> Instruction {
+    // `[id][close][salt][min_out][keep_alive][receipt][hook][idempotent]`
+    let mut data = vec![*crate::Withdraw::DISCRIMINATOR];
+    data.extend_from_slice(&crate::pda::DEFAULT_VAULT_ID);
+    data.push(1);
+    data.extend_from_slice(&crate::pda::DEFAULT_SALT);
+    data.extend_from_slice(&[0; 16]);
+    data.extend_from_slice(&[0, 0, 1]);
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vault_accounts(owner),
+        data,
+    }
+}
+
+/// Lamports in one SOL
+pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+/// Converts `sol` to lamports, rounding to the nearest lamport with halves
+/// rounded up, so amounts like `0.1` that floats can't represent exactly land
+/// on the lamport they name
+///
+/// Meant for amounts passed to the instruction builders, e.g.
+/// `deposit_instruction(&owner, sol(2.5))`.
+///
+/// # Panics
+///
+/// If `sol` is NaN, negative, or more than fits in a `u64` of lamports.
+pub fn sol_to_lamports(sol: f64) -> u64 {
+    assert!(
+        !sol.is_nan() && sol >= 0.0,
+        "SOL amount must be a non-negative number"
+    );
+
+    // `u64::MAX as f64` rounds up to 2^64, which itself doesn't fit
+    let lamports = sol * LAMPORTS_PER_SOL as f64;
+    assert!(lamports < u64::MAX as f64, "SOL amount overflows u64 lamports");
+
+    // Rounded by hand, since adding `0.5` up front would itself round at 2^53
+    let whole = lamports as u64;
+    if lamports - whole as f64 >= 0.5 {
+        whole + 1
+    } else {
+        whole
+    }
+}
+
+/// Shorthand for [`sol_to_lamports`]
+pub use self::sol_to_lamports as sol;
+
+/// Converts `lamports` to SOL; amounts up to 2^53 lamports convert back
+/// exactly with [`sol_to_lamports`]
+pub fn lamports_to_sol(lamports: u64) -> f64 {
+    lamports as f64 / LAMPORTS_PER_SOL as f64
+}
+
+/// Returns the most an owner holding `owner_balance` lamports can deposit while
+/// keeping `fee_estimate` for the transaction's fees and `rent` in their account
+///
+/// `rent` should cover the owner account's own rent-exempt minimum, plus the
+/// stats PDA's rent if the deposit may be the first. Returns `0` when the
+/// balance doesn't cover fees and rent, which the program rejects as a deposit.
+pub fn max_deposit_amount(owner_balance: u64, fee_estimate: u64, rent: u64) -> u64 {
+    owner_balance.saturating_sub(fee_estimate.saturating_add(rent))
+}
+
+/// Compute Budget program id
+pub const COMPUTE_BUDGET_PROGRAM_ID: Pubkey = Pubkey::new_from_array(pinocchio_pubkey::from_str(
+    "ComputeBudget111111111111111111111111111111",
+));
+
+/// Worst-case compute units of a Deposit, including creating the stats PDA.
+/// `tests/compute_tests.rs` fails once a Deposit grows past it.
+pub const DEPOSIT_COMPUTE_UNITS: u32 = 6_000;
+
+/// Worst-case compute units of a Withdraw.
+/// `tests/compute_tests.rs` fails once a Withdraw grows past it.
+pub const WITHDRAW_COMPUTE_UNITS: u32 = 8_000;
+
+/// Builds a Compute Budget `SetComputeUnitLimit` of `units` for the transaction
+pub fn set_compute_unit_limit_instruction(units: u32) -> Instruction {
+    // `[discriminator: u8][units: u32]`
+    let mut data = vec![2u8];
+    data.extend_from_slice(&units.to_le_bytes());
+
+    Instruction {
+        program_id: COMPUTE_BUDGET_PROGRAM_ID,
+        accounts: vec![],
+        data,
+    }
+}
+
+/// Builds [`deposit_instruction`] behind a compute unit limit sized for it, so
+/// the transaction doesn't fall back on the default limit
+pub fn deposit_with_budget(owner: &Pubkey, amount: u64) -> Vec<Instruction> {
+    vec![
+        set_compute_unit_limit_instruction(DEPOSIT_COMPUTE_UNITS),
+        deposit_instruction(owner, amount),
+    ]
+}
+
+/// Builds [`withdraw_instruction`] behind a compute unit limit sized for it
+pub fn withdraw_with_budget(owner: &Pubkey) -> Vec<Instruction> {
+    vec![
+        set_compute_unit_limit_instruction(WITHDRAW_COMPUTE_UNITS),
+        withdraw_instruction(owner),
+    ]
+}
+
+/// Accounts shared by Deposit and Withdraw on `owner`'s default vault
+///
+/// While the config has a hook program, callers have to append it.
+fn vault_accounts(owner: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(*owner, true),
+        AccountMeta::new(find_vault_address(owner, 0).0, false),
+        AccountMeta::new_readonly(Pubkey::new_from_array(pinocchio_system::ID), false),
+        AccountMeta::new(find_stats_address().0, false),
+        AccountMeta::new_readonly(find_config_address().0, false),
+    ]
+}
+
+/// Returns the lamport balance of `owner`'s default vault, or `None` if it
+/// does not exist (never funded, or closed by a withdraw)
+pub fn vault_balance<F: AccountFetcher>(fetcher: &F, owner: &Pubkey) -> Option<u64> {
+    let (vault, _) = find_vault_address(owner, 0);
+    fetcher
+        .fetch_account(&vault)
+        .map(|account| account.lamports)
+}
+
+/// Returns the label of the opened vault at `vault`, or `None` if it isn't an
+/// opened vault or its label isn't valid UTF-8
+pub fn vault_label<F: AccountFetcher>(fetcher: &F, vault: &Pubkey) -> Option<String> {
+    let account = fetcher.fetch_account(vault)?;
+    if account.owner != PROGRAM_ID {
+        return None;
+    }
+
+    let state = crate::state::VaultState::from_bytes(&account.data)?;
+    core::str::from_utf8(state.label()).ok().map(String::from)
+}
+
+/// Derives the metadata PDA and its canonical bump for `vault`
+pub fn find_metadata_address(vault: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[crate::pda::METADATA_SEED, vault.as_ref()], &PROGRAM_ID)
+}
+
+/// Derives the tombstone PDA and its canonical bump for `vault`
+pub fn find_tombstone_address(vault: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[crate::pda::TOMBSTONE_SEED, vault.as_ref()], &PROGRAM_ID)
+}
+
+/// Returns the metadata of the opened vault at `vault`, or `None` if it was
+/// never set
+pub fn fetch_vault_metadata<F: AccountFetcher>(
+    fetcher: &F,
+    vault: &Pubkey,
+) -> Option<crate::metadata::VaultMetadata> {
+    let (metadata, _) = find_metadata_address(vault);
+    let account = fetcher.fetch_account(&metadata)?;
+    if account.owner != PROGRAM_ID {
+        return None;
+    }
+
+    crate::metadata::VaultMetadata::from_bytes(&account.data).copied()
+}
+
+/// Returns the decoded state of `owner`'s unsalted vault `id`, or `None` if it
+/// isn't an opened vault or its data doesn't hold a supported `VaultState`
+pub fn fetch_vault_state<F: AccountFetcher>(
+    fetcher: &F,
+    owner: &Pubkey,
+    id: u64,
+) -> Option<crate::state::VaultState> {
+    let (vault, _) = find_vault_address(owner, id);
+    let account = fetcher.fetch_account(&vault)?;
+    if account.owner != PROGRAM_ID {
+        return None;
+    }
+
+    crate::state::VaultState::from_bytes(&account.data).copied()
+}
+
+/// Builds an ExportState reporting `vault`'s export, metadata included, from
+/// `offset` on
+pub fn export_state_instruction(vault: &Pubkey, offset: u16) -> Instruction {
+    let mut data = vec![*crate::ExportState::DISCRIMINATOR];
+    data.extend_from_slice(&offset.to_le_bytes());
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*vault, false),
+            AccountMeta::new_readonly(find_metadata_address(vault).0, false),
+        ],
+        data,
+    }
+}
+
+/// Decodes an ExportState export, the return data of each call concatenated,
+/// into the vault's state and metadata, or `None` if it isn't an export of
+/// the current format and layouts
+pub fn decode_state_export(
+    export: &[u8],
+) -> Option<(
+    crate::state::VaultState,
+    Option<crate::metadata::VaultMetadata>,
+)> {
+    let (format, export) = export.split_first()?;
+    if *format != crate::instructions::EXPORT_FORMAT {
+        return None;
+    }
+
+    fn section(export: &[u8]) -> Option<(&[u8], &[u8])> {
+        let len = u16::from_le_bytes(export.get(..2)?.try_into().ok()?) as usize;
+        export.get(2..)?.split_at_checked(len)
+    }
+    let (state, export) = section(export)?;
+    let (metadata, export) = section(export)?;
+    if !export.is_empty() {
+        return None;
+    }
+
+    let state = *crate::state::VaultState::from_bytes(state)?;
+    let metadata = if metadata.is_empty() {
+        None
+    } else {
+        Some(*crate::metadata::VaultMetadata::from_bytes(metadata)?)
+    };
+    Some((state, metadata))
+}
+
+/// Derives the vault registry PDA and its canonical bump for `owner`
+pub fn find_registry_address(owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[crate::pda::REGISTRY_SEED, owner.as_ref()], &PROGRAM_ID)
+}
+
+/// Returns every vault `owner` opened, in creation order, or `None` if the
+/// owner has no registry yet or its data is malformed
+pub fn vault_registry<F: AccountFetcher>(fetcher: &F, owner: &Pubkey) -> Option<Vec<Pubkey>> {
+    let (registry, _) = find_registry_address(owner);
+    let account = fetcher.fetch_account(&registry)?;
+    if account.owner != PROGRAM_ID {
+        return None;
+    }
+
+    let header = account.data.get(..crate::registry::RegistryHeader::LEN)?;
+    let count = u64::from_le_bytes(header[32..40].try_into().ok()?) as usize;
+    let entries = account
+        .data
+        .get(crate::registry::RegistryHeader::LEN..crate::registry::registry_len(count))?;
+
+    Some(
+        entries
+            .chunks_exact(32)
+            .map(|entry| Pubkey::new_from_array(entry.try_into().unwrap()))
+            .collect(),
+    )
+}
+
+/// Derives the tag index PDA and its canonical bump for `owner` and `tag`
+pub fn find_tag_index_address(owner: &Pubkey, tag: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[crate::pda::TAG_SEED, owner.as_ref(), &tag.to_le_bytes()],
+        &PROGRAM_ID,
+    )
+}
+
+/// Returns every vault `owner` tagged with `tag`, in tagging order, or `None`
+/// if nothing was tagged with it yet or the index data is malformed
+pub fn vaults_by_tag<F: AccountFetcher>(
+    fetcher: &F,
+    owner: &Pubkey,
+    tag: u32,
+) -> Option<Vec<Pubkey>> {
+    let (index, _) = find_tag_index_address(owner, tag);
+    let account = fetcher.fetch_account(&index)?;
+    if account.owner != PROGRAM_ID {
+        return None;
+    }
+
+    let header = account.data.get(..crate::tag::TagIndexHeader::LEN)?;
+    let count = u64::from_le_bytes(header[36..44].try_into().ok()?) as usize;
+    let entries = account
+        .data
+        .get(crate::tag::TagIndexHeader::LEN..crate::tag::tag_index_len(count))?;
+
+    Some(
+        entries
+            .chunks_exact(32)
+            .map(|entry| Pubkey::new_from_array(entry.try_into().unwrap()))
+            .collect(),
+    )
+}
+
+/// Derives the global stats PDA and its canonical bump
+pub fn find_stats_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[crate::pda::STATS_SEED], &PROGRAM_ID)
+}
+
+/// Upgradeable BPF loader, which owns the program's ProgramData account
+pub const BPF_LOADER_UPGRADEABLE_ID: Pubkey =
+    Pubkey::new_from_array(crate::pda::BPF_LOADER_UPGRADEABLE_ID);
+
+/// Derives the address of the program's ProgramData account, which Initialize
+/// reads the upgrade authority from
+pub fn find_program_data_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PROGRAM_ID.as_ref()], &BPF_LOADER_UPGRADEABLE_ID)
+}
+
+/// Derives the config PDA and its canonical bump
+pub fn find_config_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[crate::pda::CONFIG_SEED], &PROGRAM_ID)
+}
+
+/// Returns the protocol-wide stats, or `None` before the first vault operation
+pub fn global_stats<F: AccountFetcher>(fetcher: &F) -> Option<crate::stats::GlobalStats> {
+    let (stats, _) = find_stats_address();
+    let account = fetcher.fetch_account(&stats)?;
+    if account.owner != PROGRAM_ID {
+        return None;
+    }
+
+    crate::stats::GlobalStats::from_bytes(&account.data).copied()
+}
+
+/// Derives the reward pool PDA and its canonical bump
+pub fn find_reward_pool_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[crate::pda::REWARD_POOL_SEED], &PROGRAM_ID)
+}
+
+/// Returns the reward pool, or `None` before it was first funded
+pub fn reward_pool<F: AccountFetcher>(fetcher: &F) -> Option<crate::reward_pool::RewardPool> {
+    let (reward_pool, _) = find_reward_pool_address();
+    let account = fetcher.fetch_account(&reward_pool)?;
+    if account.owner != PROGRAM_ID {
+        return None;
+    }
+
+    crate::reward_pool::RewardPool::from_bytes(&account.data).copied()
+}
+
+/// A vault event decoded from a transaction's program logs
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VaultEvent {
+    /// `amount` lamports landed in one of `owner`'s vaults
+    Deposit { owner: Pubkey, amount: u64 },
+    /// `amount` lamports left one of `owner`'s vaults
+    Withdraw { owner: Pubkey, amount: u64 },
+}
+
+/// Decodes the `vault:deposit` and `vault:withdraw` events in `logs`, in order
+///
+/// Takes log messages as the RPC returns them (`Program log: ...`) or bare, in
+/// the `key=value` format or the JSON one of a `json-events` build. Unrelated
+/// and malformed lines are skipped.
+pub fn parse_events(logs: &[String]) -> Vec<VaultEvent> {
+    logs.iter().filter_map(|line| parse_event(line)).collect()
+}
+
+fn parse_event(line: &str) -> Option<VaultEvent> {
+    let line = line.strip_prefix("Program log: ").unwrap_or(line);
+    let (kind, owner, amount) = match line.strip_prefix('{') {
+        Some(json) => split_json_event(json)?,
+        None => split_event(line)?,
+    };
+
+    if owner.len() != 64 || !owner.bytes().all(|digit| digit.is_ascii_hexdigit()) {
+        return None;
+    }
+    let mut key = [0u8; 32];
+    for (byte, digits) in key.iter_mut().zip(owner.as_bytes().chunks_exact(2)) {
+        *byte = u8::from_str_radix(core::str::from_utf8(digits).ok()?, 16).ok()?;
+    }
+    let owner = Pubkey::new_from_array(key);
+
+    let amount = amount.parse().ok()?;
+
+    match kind {
+        crate::events::DEPOSIT_EVENT => Some(VaultEvent::Deposit { owner, amount }),
+        crate::events::WITHDRAW_EVENT => Some(VaultEvent::Withdraw { owner, amount }),
+        _ => None,
+    }
+}
+
+/// Splits `vault:<event> owner=<hex> amount=<lamports>` into its three values
+fn split_event(line: &str) -> Option<(&str, &str, &str)> {
+    let mut fields = line.split(' ');
+    let kind = fields.next()?;
+    let owner = fields.next()?.strip_prefix("owner=")?;
+    let amount = fields.next()?.strip_prefix("amount=")?;
+    if fields.next().is_some() {
+        return None;
+    }
+
+    Some((kind, owner, amount))
+}
+
+/// Splits `"event":"<event>","owner":"<hex>","amount":<lamports>}`, a JSON
+/// event without its opening brace, into its three values, the event name
+/// given its `vault:` prefix back
+fn split_json_event(json: &str) -> Option<(&'static str, &str, &str)> {
+    let (name, rest) = json.strip_prefix("\"event\":\"")?.split_once('"')?;
+    let (owner, rest) = rest.strip_prefix(",\"owner\":\"")?.split_once('"')?;
+    let amount = rest.strip_prefix(",\"amount\":")?.strip_suffix('}')?;
+
+    let kind = match name {
+        "deposit" => crate::events::DEPOSIT_EVENT,
+        "withdraw" => crate::events::WITHDRAW_EVENT,
+        _ => return None,
+    };
+
+    Some((kind, owner, amount))
+}
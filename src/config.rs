@@ -0,0 +1,480 @@
+use core::mem::size_of;
+
+use pinocchio::{
+    account_info::{AccountInfo, Ref, RefMut},
+    program_error::ProgramError,
+    pubkey::{create_program_address, Pubkey},
+    ProgramResult,
+};
+
+use crate::error::VaultError;
+use crate::logic::FeeRounding;
+use crate::parse::array_at;
+use crate::pda::{
+    find_config_address, find_program_data_address, BPF_LOADER_UPGRADEABLE_ID, CONFIG_SEED,
+};
+
+/// Protocol-wide settings
+///
+/// Lives in the `["config"]` PDA, created once by Initialize. The account that
+/// initializes it is recorded as the admin. Until then every setting reads as
+/// its default, so the protocol works without a config.
+#[repr(C)]
+pub struct Config {
+    admin: Pubkey,
+    referral_bps: [u8; 2],
+    price_oracle: Pubkey,
+    max_vaults_per_owner: [u8; 8],
+    max_deposit_per_tx: [u8; 8],
+    rent_subsidy: Pubkey,
+    dust_threshold: [u8; 8],
+    hook_program: Pubkey,
+    reuse_cooldown_secs: [u8; 8],
+    recovery_address: Pubkey,
+    paused: u8,
+    min_withdraw: [u8; 8],
+    pending_admin: Pubkey,
+    fee_rounding: u8,
+    bump: u8,
+    paused_at: [u8; 8],
+    withdraw_fee_bps: [u8; 2],
+    treasury: Pubkey,
+    fee_exempt_count: u8,
+    fee_exempt: [Pubkey; Config::MAX_FEE_EXEMPT],
+    recovery_inactivity_secs: [u8; 8],
+}
+
+impl Config {
+    pub const LEN: usize = size_of::<Self>();
+
+    /// `fee_rounding` value rounding fees down, leaving the dust to the owner
+    pub const FEE_ROUNDING_FAVOR_OWNER: u8 = 0;
+
+    /// `fee_rounding` value rounding fees up, sending the dust to the fee's recipient
+    pub const FEE_ROUNDING_FAVOR_TREASURY: u8 = 1;
+
+    /// Most owners the withdrawal fee can be waived for
+    pub const MAX_FEE_EXEMPT: usize = 4;
+
+    /// Seconds the program has to stay paused before EmergencyDrain can move
+    /// any vault, so owners see the pause coming and can still withdraw first
+    pub const EMERGENCY_DRAIN_DELAY_SECS: i64 = 3 * 86_400;
+
+    /// Reinterprets raw account data as the config, e.g. off-chain
+    #[inline(always)]
+    pub fn from_bytes(data: &[u8]) -> Option<&Self> {
+        if data.len().ne(&Self::LEN) {
+            return None;
+        }
+
+        Some(unsafe { &*(data.as_ptr() as *const Self) })
+    }
+
+    /// Borrows an initialized config
+    #[inline(always)]
+    pub fn load(account: &AccountInfo) -> Result<Ref<'_, Self>, ProgramError> {
+        Self::check(account)?;
+        Ok(Ref::map(account.try_borrow_data()?, |data| unsafe {
+            &*(data.as_ptr() as *const Self)
+        }))
+    }
+
+    /// Mutably borrows an initialized config
+    #[inline(always)]
+    pub fn load_mut(account: &AccountInfo) -> Result<RefMut<'_, Self>, ProgramError> {
+        Self::check(account)?;
+        Ok(RefMut::map(account.try_borrow_mut_data()?, |data| unsafe {
+            &mut *(data.as_mut_ptr() as *mut Self)
+        }))
+    }
+
+    #[inline(always)]
+    fn check(account: &AccountInfo) -> Result<(), ProgramError> {
+        if !account.is_owned_by(&crate::ID) {
+            return Err(VaultError::InvalidVaultOwner.into());
+        }
+
+        if account.data_len().ne(&Self::LEN) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn admin(&self) -> &Pubkey {
+        &self.admin
+    }
+
+    /// Share of each referred deposit paid to the referrer, in basis points
+    #[inline(always)]
+    pub fn referral_bps(&self) -> u16 {
+        u16::from_le_bytes(self.referral_bps)
+    }
+
+    /// Price account WithdrawIfPriceAbove reads, or the zero pubkey if none is configured
+    #[inline(always)]
+    pub fn price_oracle(&self) -> &Pubkey {
+        &self.price_oracle
+    }
+
+    /// Most vaults an owner's registry can list, or `0` for no limit
+    #[inline(always)]
+    pub fn max_vaults_per_owner(&self) -> u64 {
+        u64::from_le_bytes(self.max_vaults_per_owner)
+    }
+
+    /// Largest amount a single Deposit can move, or `0` for no limit
+    #[inline(always)]
+    pub fn max_deposit_per_tx(&self) -> u64 {
+        u64::from_le_bytes(self.max_deposit_per_tx)
+    }
+
+    /// Account that pays the rent of vaults opened by subsidized deposits, or
+    /// the zero pubkey if none is configured
+    #[inline(always)]
+    pub fn rent_subsidy(&self) -> &Pubkey {
+        &self.rent_subsidy
+    }
+
+    /// Balance below which a WithdrawAmount that opts in closes the vault
+    /// instead of leaving dust behind, or `0` for no auto-close
+    #[inline(always)]
+    pub fn dust_threshold(&self) -> u64 {
+        u64::from_le_bytes(self.dust_threshold)
+    }
+
+    /// Program invoked after each Deposit and Withdraw, or the zero pubkey if
+    /// none is configured
+    #[inline(always)]
+    pub fn hook_program(&self) -> &Pubkey {
+        &self.hook_program
+    }
+
+    /// Seconds a vault closed with a tombstone can't be deposited into again,
+    /// or `0` for no cooldown
+    #[inline(always)]
+    pub fn reuse_cooldown_secs(&self) -> u64 {
+        u64::from_le_bytes(self.reuse_cooldown_secs)
+    }
+
+    /// Account EmergencyDrain and AdminRecover move vault funds to, or the zero
+    /// pubkey if none is configured
+    #[inline(always)]
+    pub fn recovery_address(&self) -> &Pubkey {
+        &self.recovery_address
+    }
+
+    /// Seconds without owner activity before AdminRecover can sweep a vault, or
+    /// `0` if recovery is unavailable
+    #[inline(always)]
+    pub fn recovery_inactivity_secs(&self) -> u64 {
+        u64::from_le_bytes(self.recovery_inactivity_secs)
+    }
+
+    /// Whether the admin paused the program
+    #[inline(always)]
+    pub fn is_paused(&self) -> bool {
+        self.paused.ne(&0)
+    }
+
+    /// Smallest amount a WithdrawAmount can move short of draining the vault,
+    /// or `0` for no minimum
+    #[inline(always)]
+    pub fn min_withdraw(&self) -> u64 {
+        u64::from_le_bytes(self.min_withdraw)
+    }
+
+    /// Admin proposed by ProposeAdmin that hasn't accepted yet, or the zero
+    /// pubkey if no handoff is pending
+    #[inline(always)]
+    pub fn pending_admin(&self) -> &Pubkey {
+        &self.pending_admin
+    }
+
+    /// Direction fees round in, towards the owner unless set to favor the treasury
+    #[inline(always)]
+    pub fn fee_rounding(&self) -> FeeRounding {
+        match self.fee_rounding {
+            Self::FEE_ROUNDING_FAVOR_TREASURY => FeeRounding::FavorTreasury,
+            _ => FeeRounding::FavorOwner,
+        }
+    }
+
+    /// Canonical bump of the config PDA, recorded by Initialize
+    #[inline(always)]
+    pub fn bump(&self) -> u8 {
+        self.bump
+    }
+
+    #[inline(always)]
+    pub fn set_pending_admin(&mut self, pending_admin: &Pubkey) {
+        self.pending_admin = *pending_admin;
+    }
+
+    /// Hands the config over to the pending admin, clearing the proposal
+    #[inline(always)]
+    pub fn accept_pending_admin(&mut self) {
+        self.admin = self.pending_admin;
+        self.pending_admin = Pubkey::default();
+    }
+
+    /// Unix timestamp the program was last paused at, `0` while it isn't paused
+    #[inline(always)]
+    pub fn paused_at(&self) -> i64 {
+        i64::from_le_bytes(self.paused_at)
+    }
+
+    /// Whether the program has been paused for at least
+    /// `EMERGENCY_DRAIN_DELAY_SECS` at `now`
+    #[inline(always)]
+    pub fn is_drainable(&self, now: i64) -> bool {
+        self.is_paused()
+            && now >= self.paused_at().saturating_add(Self::EMERGENCY_DRAIN_DELAY_SECS)
+    }
+
+    /// Pauses the program at `now`, or unpauses it; pausing an already paused
+    /// program keeps its original pause time
+    #[inline(always)]
+    pub fn set_paused(&mut self, paused: bool, now: i64) {
+        if paused && !self.is_paused() {
+            self.paused_at = now.to_le_bytes();
+        } else if !paused {
+            self.paused_at = [0; 8];
+        }
+        self.paused = paused as u8;
+    }
+
+    /// Share of each withdrawal paid to the treasury, in basis points
+    #[inline(always)]
+    pub fn withdraw_fee_bps(&self) -> u16 {
+        u16::from_le_bytes(self.withdraw_fee_bps)
+    }
+
+    /// Account withdrawal fees go to, or the zero pubkey while there is no fee
+    #[inline(always)]
+    pub fn treasury(&self) -> &Pubkey {
+        &self.treasury
+    }
+
+    /// Owners whose withdrawals skip the fee
+    #[inline(always)]
+    pub fn fee_exempt(&self) -> &[Pubkey] {
+        &self.fee_exempt[..(self.fee_exempt_count as usize).min(Self::MAX_FEE_EXEMPT)]
+    }
+
+    /// Sets the withdrawal fee, its treasury and the owners exempt from it,
+    /// replacing any previous ones; `exempt` holds at most `MAX_FEE_EXEMPT` keys
+    #[inline(always)]
+    pub fn set_withdraw_fee(&mut self, fee_bps: u16, treasury: &Pubkey, exempt: &[Pubkey]) {
+        self.withdraw_fee_bps = fee_bps.to_le_bytes();
+        self.treasury = *treasury;
+        self.fee_exempt = [Pubkey::default(); Self::MAX_FEE_EXEMPT];
+        self.fee_exempt[..exempt.len()].copy_from_slice(exempt);
+        self.fee_exempt_count = exempt.len() as u8;
+    }
+
+    #[inline(always)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_inner(
+        &mut self,
+        admin: &Pubkey,
+        referral_bps: u16,
+        price_oracle: &Pubkey,
+        max_vaults_per_owner: u64,
+        max_deposit_per_tx: u64,
+        rent_subsidy: &Pubkey,
+        dust_threshold: u64,
+        hook_program: &Pubkey,
+        reuse_cooldown_secs: u64,
+        recovery_address: &Pubkey,
+        min_withdraw: u64,
+        fee_rounding: u8,
+        recovery_inactivity_secs: u64,
+        bump: u8,
+    ) {
+        self.admin = *admin;
+        self.referral_bps = referral_bps.to_le_bytes();
+        self.price_oracle = *price_oracle;
+        self.max_vaults_per_owner = max_vaults_per_owner.to_le_bytes();
+        self.max_deposit_per_tx = max_deposit_per_tx.to_le_bytes();
+        self.rent_subsidy = *rent_subsidy;
+        self.dust_threshold = dust_threshold.to_le_bytes();
+        self.hook_program = *hook_program;
+        self.reuse_cooldown_secs = reuse_cooldown_secs.to_le_bytes();
+        self.recovery_address = *recovery_address;
+        self.paused = 0;
+        self.paused_at = [0; 8];
+        self.min_withdraw = min_withdraw.to_le_bytes();
+        self.pending_admin = Pubkey::default();
+        self.fee_rounding = fee_rounding;
+        self.recovery_inactivity_secs = recovery_inactivity_secs.to_le_bytes();
+        self.bump = bump;
+    }
+}
+
+/// Checks that `config` is the config PDA, returning its canonical bump
+///
+/// An initialized config records its bump, so only the address is recomputed;
+/// the bump search runs just until Initialize creates the account.
+#[inline(always)]
+pub fn check_config_account(config: &AccountInfo) -> Result<u8, ProgramError> {
+    if config.is_owned_by(&crate::ID) {
+        let bump = Config::from_bytes(&config.try_borrow_data()?)
+            .ok_or(ProgramError::InvalidAccountOwner)?
+            .bump();
+        let config_key = create_program_address(&[CONFIG_SEED, &[bump]], &crate::ID)?;
+        if config.key().ne(&config_key) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        return Ok(bump);
+    }
+
+    let (config_key, bump) = find_config_address();
+    if config.key().ne(&config_key) {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+
+    Ok(bump)
+}
+
+/// Reads a setting off the initialized `config` with `read`, or returns the
+/// setting's default while the config isn't initialized
+///
+/// Every free accessor below goes through this, so the protocol works the same
+/// with no config as with one holding only defaults.
+#[inline(always)]
+pub fn load_or_default<T: Default>(
+    config: &AccountInfo,
+    read: impl FnOnce(&Config) -> T,
+) -> Result<T, ProgramError> {
+    if config.is_owned_by(&pinocchio_system::ID) {
+        return Ok(T::default());
+    }
+
+    Ok(read(&*Config::load(config)?))
+}
+
+/// Checks that `admin` signed and is the admin of the initialized `config`
+#[inline(always)]
+pub fn check_admin(config: &AccountInfo, admin: &AccountInfo) -> ProgramResult {
+    if !admin.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if Config::load(config)?.admin().ne(admin.key()) {
+        return Err(VaultError::NotAdmin.into());
+    }
+
+    Ok(())
+}
+
+/// Loader state tag of a ProgramData account
+const PROGRAM_DATA_TAG: u32 = 3;
+
+/// Fails with `NotUpgradeAuthority` unless `authority` signed and is the upgrade
+/// authority `program_data`, the program's ProgramData account, records
+///
+/// Only the deployer can create the config, so nobody can front-run the
+/// deployment and make themselves admin. A program deployed immutable has no
+/// upgrade authority left, and its config can't be created.
+#[inline(always)]
+pub fn check_upgrade_authority(
+    program_data: &AccountInfo,
+    authority: &AccountInfo,
+) -> ProgramResult {
+    if !authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !program_data.is_owned_by(&BPF_LOADER_UPGRADEABLE_ID)
+        || program_data.key().ne(&find_program_data_address().0)
+    {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // `[tag: u32 = 3][slot: u64][authority: Option<Pubkey>]`, then the program bytes
+    let data = program_data.try_borrow_data()?;
+    let tag = array_at(&data, 0).map(u32::from_le_bytes);
+    if tag.ne(&Some(PROGRAM_DATA_TAG)) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    match (data.get(12), array_at::<32>(&data, 13)) {
+        (Some(1), Some(upgrade_authority)) if upgrade_authority.eq(authority.key()) => Ok(()),
+        _ => Err(VaultError::NotUpgradeAuthority.into()),
+    }
+}
+
+/// Returns the referral fee rate, or `0` while the config isn't initialized
+#[inline(always)]
+pub fn referral_bps(config: &AccountInfo) -> Result<u16, ProgramError> {
+    load_or_default(config, Config::referral_bps)
+}
+
+/// Returns the per-owner vault limit, or `0` (no limit) while the config isn't initialized
+#[inline(always)]
+pub fn max_vaults_per_owner(config: &AccountInfo) -> Result<u64, ProgramError> {
+    load_or_default(config, Config::max_vaults_per_owner)
+}
+
+/// Returns the per-deposit cap, or `0` (no limit) while the config isn't initialized
+#[inline(always)]
+pub fn max_deposit_per_tx(config: &AccountInfo) -> Result<u64, ProgramError> {
+    load_or_default(config, Config::max_deposit_per_tx)
+}
+
+/// Returns the rent subsidy account, or the zero pubkey (none) while the config isn't initialized
+#[inline(always)]
+pub fn rent_subsidy(config: &AccountInfo) -> Result<Pubkey, ProgramError> {
+    load_or_default(config, |config| *config.rent_subsidy())
+}
+
+/// Returns the auto-close dust threshold, or `0` (none) while the config isn't initialized
+#[inline(always)]
+pub fn dust_threshold(config: &AccountInfo) -> Result<u64, ProgramError> {
+    load_or_default(config, Config::dust_threshold)
+}
+
+/// Returns the hook program, or the zero pubkey (none) while the config isn't initialized
+#[inline(always)]
+pub fn hook_program(config: &AccountInfo) -> Result<Pubkey, ProgramError> {
+    load_or_default(config, |config| *config.hook_program())
+}
+
+/// Returns the vault reuse cooldown, or `0` (none) while the config isn't initialized
+#[inline(always)]
+pub fn reuse_cooldown_secs(config: &AccountInfo) -> Result<u64, ProgramError> {
+    load_or_default(config, Config::reuse_cooldown_secs)
+}
+
+/// Returns the minimum withdrawal, or `0` (none) while the config isn't initialized
+#[inline(always)]
+pub fn min_withdraw(config: &AccountInfo) -> Result<u64, ProgramError> {
+    load_or_default(config, Config::min_withdraw)
+}
+
+/// Returns the fee rounding direction, towards the owner while the config isn't initialized
+#[inline(always)]
+pub fn fee_rounding(config: &AccountInfo) -> Result<FeeRounding, ProgramError> {
+    load_or_default(config, Config::fee_rounding)
+}
+
+/// Returns whether the admin paused the program, never while the config isn't initialized
+#[inline(always)]
+pub fn is_paused(config: &AccountInfo) -> Result<bool, ProgramError> {
+    load_or_default(config, Config::is_paused)
+}
+
+/// Fails with `DepositTooLarge` if `amount` is above the config's non-zero
+/// per-deposit cap
+#[inline(always)]
+pub fn check_deposit_cap(config: &AccountInfo, amount: u64) -> ProgramResult {
+    let max_deposit = max_deposit_per_tx(config)?;
+    if max_deposit.ne(&0) && amount > max_deposit {
+        return Err(VaultError::DepositTooLarge.into());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,214 @@
+use pinocchio::program_error::ProgramError;
+
+// Custom error codes, as `VaultError` reports them in `ProgramError::Custom`
+//
+// Codes are stable: new errors take the next free code and existing ones never
+// change, so clients can match on them.
+pub const ERR_INVALID_VAULT_OWNER: u32 = 0;
+pub const ERR_VAULT_EMPTY: u32 = 1;
+pub const ERR_INSUFFICIENT_FUNDS: u32 = 2;
+pub const ERR_TOO_SOON_AFTER_CREATION: u32 = 3;
+pub const ERR_MISSING_OWNER_SIGNATURE: u32 = 4;
+pub const ERR_STALE_PRICE: u32 = 5;
+pub const ERR_VESTING_LOCKED: u32 = 6;
+pub const ERR_INVALID_PDA: u32 = 7;
+pub const ERR_DAILY_LIMIT_EXCEEDED: u32 = 8;
+pub const ERR_VAULT_NOT_EMPTY: u32 = 9;
+pub const ERR_UNSUPPORTED_STATE_VERSION: u32 = 10;
+pub const ERR_DESTINATION_NOT_ALLOWED: u32 = 11;
+pub const ERR_SLIPPAGE_EXCEEDED: u32 = 12;
+pub const ERR_INVALID_FEE: u32 = 13;
+pub const ERR_TRANSFER_FAILED: u32 = 14;
+pub const ERR_CANNOT_SHORTEN_LOCK: u32 = 15;
+pub const ERR_VAULT_LOCKED: u32 = 16;
+pub const ERR_DUPLICATE_ACCOUNT: u32 = 17;
+pub const ERR_INVALID_RECIPIENT: u32 = 18;
+pub const ERR_PRICE_CONDITION_NOT_MET: u32 = 19;
+pub const ERR_TOO_MANY_VAULTS: u32 = 20;
+pub const ERR_DEPOSITS_LOCKED: u32 = 21;
+pub const ERR_INVALID_APPROVAL: u32 = 22;
+pub const ERR_NONCE_ALREADY_USED: u32 = 23;
+pub const ERR_DEPOSIT_TOO_LARGE: u32 = 24;
+pub const ERR_UNLOCK_IN_PAST: u32 = 25;
+pub const ERR_INVALID_ACCOUNT_ARRANGEMENT: u32 = 26;
+pub const ERR_ESCROWED_VAULT: u32 = 27;
+pub const ERR_ESCROW_AGENT_SET: u32 = 28;
+pub const ERR_VAULT_TOO_YOUNG: u32 = 29;
+pub const ERR_ALREADY_INITIALIZED: u32 = 30;
+pub const ERR_ROLLING_LIMIT_EXCEEDED: u32 = 31;
+pub const ERR_HOOK_REENTRANCY: u32 = 32;
+pub const ERR_BATCH_TOO_LARGE: u32 = 33;
+pub const ERR_VAULT_FROZEN: u32 = 34;
+pub const ERR_APPROVAL_EXPIRED: u32 = 35;
+pub const ERR_ACCOUNTING_MISMATCH: u32 = 36;
+pub const ERR_INVALID_OWNER_ACCOUNT: u32 = 37;
+pub const ERR_REUSE_COOLDOWN: u32 = 38;
+pub const ERR_NOT_ADMIN: u32 = 39;
+pub const ERR_NOT_PAUSED: u32 = 40;
+pub const ERR_PROGRAM_PAUSED: u32 = 41;
+pub const ERR_WITHDRAW_TOO_SMALL: u32 = 42;
+pub const ERR_NOT_PENDING_ADMIN: u32 = 43;
+pub const ERR_SYSVAR_UNAVAILABLE: u32 = 44;
+pub const ERR_DRIP_NOT_DUE: u32 = 45;
+pub const ERR_OWNER_NOT_WRITABLE: u32 = 46;
+pub const ERR_INVALID_MULTISIG_THRESHOLD: u32 = 47;
+pub const ERR_MULTISIG_THRESHOLD_NOT_MET: u32 = 48;
+pub const ERR_CHECKSUM_MISMATCH: u32 = 49;
+pub const ERR_VAULT_NOT_EXPIRED: u32 = 50;
+pub const ERR_TOO_MANY_MINTS: u32 = 51;
+pub const ERR_NOT_DELEGATE: u32 = 52;
+pub const ERR_DELEGATE_CAP_EXCEEDED: u32 = 53;
+pub const ERR_INVARIANT_VIOLATED: u32 = 54;
+pub const ERR_NOT_UPGRADE_AUTHORITY: u32 = 55;
+pub const ERR_DRAIN_DELAY_NOT_ELAPSED: u32 = 56;
+pub const ERR_RECEIPTS_OUTSTANDING: u32 = 57;
+pub const ERR_TOKENS_OUTSTANDING: u32 = 58;
+pub const ERR_INVALID_TREASURY: u32 = 59;
+pub const ERR_OWNER_STILL_ACTIVE: u32 = 60;
+pub const ERR_REWARD_POOL_UNDERFUNDED: u32 = 61;
+pub const ERR_NOT_VAULT_ADMIN: u32 = 62;
+pub const ERR_INVALID_DESTINATION_ATA: u32 = 63;
+pub const ERR_SOURCE_NOT_WHITELISTED: u32 = 64;
+
+/// Custom errors returned by the vault program
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum VaultError {
+    /// The account passed as the vault is not owned by the expected program
+    InvalidVaultOwner = ERR_INVALID_VAULT_OWNER,
+    /// The vault holds nothing to withdraw, e.g. it was already closed
+    VaultEmpty = ERR_VAULT_EMPTY,
+    /// The vault can't cover the requested withdrawal amount
+    InsufficientFunds = ERR_INSUFFICIENT_FUNDS,
+    /// The vault only accepts deposits in a later slot than the one it was opened in
+    TooSoonAfterCreation = ERR_TOO_SOON_AFTER_CREATION,
+    /// The vault owner did not sign the instruction
+    MissingOwnerSignature = ERR_MISSING_OWNER_SIGNATURE,
+    /// The vault's price oracle was last updated longer ago than its staleness window
+    StalePrice = ERR_STALE_PRICE,
+    /// The vault vests its deposits, so it can only be withdrawn with WithdrawVested
+    VestingLocked = ERR_VESTING_LOCKED,
+    /// The vault key isn't its seeds' canonical-bump PDA, e.g. it was derived with a lower bump
+    InvalidPda = ERR_INVALID_PDA,
+    /// The withdrawal would take the vault past its daily withdrawal limit
+    DailyLimitExceeded = ERR_DAILY_LIMIT_EXCEEDED,
+    /// The vault still holds funds, so closing it would take them along
+    VaultNotEmpty = ERR_VAULT_NOT_EMPTY,
+    /// The vault's state was written with a layout version this program doesn't know
+    UnsupportedStateVersion = ERR_UNSUPPORTED_STATE_VERSION,
+    /// The vault's allowlist doesn't include the withdrawal's recipient
+    DestinationNotAllowed = ERR_DESTINATION_NOT_ALLOWED,
+    /// The withdrawal would move fewer lamports than the client's `min_out`
+    SlippageExceeded = ERR_SLIPPAGE_EXCEEDED,
+    /// The fee rate is above `BPS_DENOMINATOR`, i.e. more than the whole amount
+    InvalidFee = ERR_INVALID_FEE,
+    /// Funding the vault failed, e.g. the owner doesn't hold enough SOL; the cause is logged
+    TransferFailed = ERR_TRANSFER_FAILED,
+    /// The new unlock timestamp isn't later than the vault's current one
+    CannotShortenLock = ERR_CANNOT_SHORTEN_LOCK,
+    /// The vault is time-locked until its unlock timestamp
+    VaultLocked = ERR_VAULT_LOCKED,
+    /// The same vault account was passed more than once to a batch instruction
+    DuplicateAccount = ERR_DUPLICATE_ACCOUNT,
+    /// The withdrawal's recipient is the vault it withdraws from
+    InvalidRecipient = ERR_INVALID_RECIPIENT,
+    /// The configured oracle's price isn't above the withdrawal's threshold
+    PriceConditionNotMet = ERR_PRICE_CONDITION_NOT_MET,
+    /// The owner's registry already lists the config's `max_vaults_per_owner` vaults
+    TooManyVaults = ERR_TOO_MANY_VAULTS,
+    /// The owner locked the vault's deposits, freezing its balance
+    DepositsLocked = ERR_DEPOSITS_LOCKED,
+    /// A relayed withdrawal wasn't preceded by an ed25519 verification of the
+    /// owner's approval
+    InvalidApproval = ERR_INVALID_APPROVAL,
+    /// The approval's nonce isn't above the last one the vault accepted
+    NonceAlreadyUsed = ERR_NONCE_ALREADY_USED,
+    /// A single deposit exceeds the config's `max_deposit_per_tx`
+    DepositTooLarge = ERR_DEPOSIT_TOO_LARGE,
+    /// The unlock timestamp a deposit locks the vault until isn't in the future
+    UnlockInPast = ERR_UNLOCK_IN_PAST,
+    /// The same account was passed as both the owner and the vault
+    InvalidAccountArrangement = ERR_INVALID_ACCOUNT_ARRANGEMENT,
+    /// The vault is escrowed, so only its escrow agent can release its lamports
+    EscrowedVault = ERR_ESCROWED_VAULT,
+    /// The vault already has an escrow agent, which can't be changed
+    EscrowAgentSet = ERR_ESCROW_AGENT_SET,
+    /// The vault hasn't reached its minimum age since creation
+    VaultTooYoung = ERR_VAULT_TOO_YOUNG,
+    /// The config already exists with a different admin
+    AlreadyInitialized = ERR_ALREADY_INITIALIZED,
+    /// The withdrawal would take the vault past its rolling-window withdrawal limit
+    RollingLimitExceeded = ERR_ROLLING_LIMIT_EXCEEDED,
+    /// The configured hook program is the vault program itself, which would re-enter it
+    HookReentrancy = ERR_HOOK_REENTRANCY,
+    /// A batch instruction was passed more than `MAX_BATCH_VAULTS` vaults
+    BatchTooLarge = ERR_BATCH_TOO_LARGE,
+    /// The owner froze the vault, so nothing can be deposited or withdrawn
+    VaultFrozen = ERR_VAULT_FROZEN,
+    /// The signed approval's expiry timestamp has passed
+    ApprovalExpired = ERR_APPROVAL_EXPIRED,
+    /// The opened vault's tracked balance exceeds the lamports it holds above its rent floor
+    AccountingMismatch = ERR_ACCOUNTING_MISMATCH,
+    /// The depositing owner isn't a system-owned wallet, e.g. it's a program's account
+    InvalidOwnerAccount = ERR_INVALID_OWNER_ACCOUNT,
+    /// The vault was closed less than the config's `reuse_cooldown_secs` ago
+    ReuseCooldown = ERR_REUSE_COOLDOWN,
+    /// The signer isn't the config's admin
+    NotAdmin = ERR_NOT_ADMIN,
+    /// EmergencyDrain only runs while the admin has paused the program
+    NotPaused = ERR_NOT_PAUSED,
+    /// The admin paused the program, so it takes no deposits
+    ProgramPaused = ERR_PROGRAM_PAUSED,
+    /// The withdrawal moves less than the config's `min_withdraw` without draining the vault
+    WithdrawTooSmall = ERR_WITHDRAW_TOO_SMALL,
+    /// The signer isn't the admin the config's admin proposed
+    NotPendingAdmin = ERR_NOT_PENDING_ADMIN,
+    /// The runtime couldn't provide the Clock or Rent sysvar
+    SysvarUnavailable = ERR_SYSVAR_UNAVAILABLE,
+    /// The vault has no drip schedule, or no interval elapsed since its last release
+    DripNotDue = ERR_DRIP_NOT_DUE,
+    /// The owner account a withdrawal credits was passed read-only
+    OwnerNotWritable = ERR_OWNER_NOT_WRITABLE,
+    /// A multisig threshold is zero or above the number of signers
+    InvalidMultisigThreshold = ERR_INVALID_MULTISIG_THRESHOLD,
+    /// Fewer of a multisig vault's signers signed than its threshold requires
+    MultisigThresholdNotMet = ERR_MULTISIG_THRESHOLD_NOT_MET,
+    /// The instruction data's trailing checksum byte doesn't match the data (`verify-checksum`)
+    ChecksumMismatch = ERR_CHECKSUM_MISMATCH,
+    /// The vault has no expiry, or it hasn't passed yet
+    VaultNotExpired = ERR_VAULT_NOT_EXPIRED,
+    /// The vault already tracks as many distinct mints as it can hold
+    TooManyMints = ERR_TOO_MANY_MINTS,
+    /// The signer isn't one of the vault's delegates
+    NotDelegate = ERR_NOT_DELEGATE,
+    /// The withdrawal moves more than the signing delegate's per-withdrawal cap
+    DelegateCapExceeded = ERR_DELEGATE_CAP_EXCEEDED,
+    /// An internal invariant failed, e.g. a handler's transfers didn't conserve lamports
+    InvariantViolated = ERR_INVARIANT_VIOLATED,
+    /// The signer isn't the program's upgrade authority, so it can't create the config
+    NotUpgradeAuthority = ERR_NOT_UPGRADE_AUTHORITY,
+    /// The program hasn't been paused for long enough to drain vaults yet
+    DrainDelayNotElapsed = ERR_DRAIN_DELAY_NOT_ELAPSED,
+    /// Receipt tokens still stand for the lamports leaving the vault, and weren't burned
+    ReceiptsOutstanding = ERR_RECEIPTS_OUTSTANDING,
+    /// The vault still tracks SPL tokens, which its address would stop being able to move
+    TokensOutstanding = ERR_TOKENS_OUTSTANDING,
+    /// The treasury account isn't the one the config records
+    InvalidTreasury = ERR_INVALID_TREASURY,
+    /// The vault has no beneficiary, or its owner was active too recently for it to claim
+    OwnerStillActive = ERR_OWNER_STILL_ACTIVE,
+    /// The reward pool holds less above its rent floor than the interest claimed
+    RewardPoolUnderfunded = ERR_REWARD_POOL_UNDERFUNDED,
+    /// The signer isn't the vault's admin, or the vault has an admin acting in the owner's place
+    NotVaultAdmin = ERR_NOT_VAULT_ADMIN,
+    /// The token destination isn't the associated token account the vault sends the mint to
+    InvalidDestinationAta = ERR_INVALID_DESTINATION_ATA,
+    /// The deposit comes from an account the vault's source whitelist doesn't list
+    SourceNotWhitelisted = ERR_SOURCE_NOT_WHITELISTED,
+}
+
+impl From<VaultError> for ProgramError {
+    fn from(e: VaultError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
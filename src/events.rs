@@ -0,0 +1,65 @@
+use pinocchio::pubkey::Pubkey;
+use pinocchio_log::log;
+
+/// Prefix of a deposit event log line
+pub const DEPOSIT_EVENT: &str = "vault:deposit";
+
+/// Prefix of a withdraw event log line
+pub const WITHDRAW_EVENT: &str = "vault:withdraw";
+
+/// Logs a deposit of `amount` lamports into one of `owner`'s vaults
+///
+/// Format: `vault:deposit owner=<hex> amount=<lamports>`, or
+/// `{"event":"deposit","owner":"<hex>","amount":<lamports>}` with the
+/// `json-events` feature. The owner is hex encoded, since base58 costs far more
+/// compute on-chain; `client::parse_events` decodes either format back.
+#[inline(always)]
+pub fn log_deposit(owner: &Pubkey, amount: u64) {
+    log_event(DEPOSIT_EVENT, owner, amount);
+}
+
+/// Logs a withdrawal of `amount` lamports from one of `owner`'s vaults
+///
+/// Format: `vault:withdraw owner=<hex> amount=<lamports>`, or the JSON object
+/// with `"event":"withdraw"`, as for deposits.
+#[inline(always)]
+pub fn log_withdraw(owner: &Pubkey, amount: u64) {
+    log_event(WITHDRAW_EVENT, owner, amount);
+}
+
+/// Logs the `event` line of `owner` moving `amount` lamports in the format the
+/// program was built with
+#[inline(always)]
+fn log_event(event: &str, owner: &Pubkey, amount: u64) {
+    let mut hex = [0u8; 64];
+    let owner = encode_hex(owner, &mut hex);
+
+    if cfg!(feature = "json-events") {
+        // The JSON name drops the `vault:` prefix; braces are passed as arguments
+        // since the format string would read them as placeholders
+        let name = event.strip_prefix("vault:").unwrap_or(event);
+        log!(
+            "{}\"event\":\"{}\",\"owner\":\"{}\",\"amount\":{}{}",
+            "{",
+            name,
+            owner,
+            amount,
+            "}"
+        );
+    } else {
+        log!("{} owner={} amount={}", event, owner, amount);
+    }
+}
+
+/// Writes `key` as lowercase hex into `buf`
+fn encode_hex<'b>(key: &Pubkey, buf: &'b mut [u8; 64]) -> &'b str {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+    for (i, byte) in key.iter().enumerate() {
+        buf[2 * i] = DIGITS[(byte >> 4) as usize];
+        buf[2 * i + 1] = DIGITS[(byte & 0x0f) as usize];
+    }
+
+    // Every byte written is an ASCII hex digit
+    core::str::from_utf8(buf).unwrap_or_default()
+}
@@ -0,0 +1,119 @@
+use pinocchio::{
+    account_info::AccountInfo, instruction::Signer, program_error::ProgramError, pubkey::Pubkey,
+};
+
+use crate::config::Config;
+use crate::error::VaultError;
+use crate::logic::{compute_withdraw_amount_rounded, withdraw_fee_bps, FeeRounding};
+use crate::vault::{transfer_from_vault, transfer_share_from_vault};
+
+/// The config's withdrawal fee, at the rate one vault owner pays
+pub struct WithdrawFee<'a> {
+    /// The config's treasury, which receives the fee
+    pub treasury: &'a AccountInfo,
+    /// Rate the owner pays, `0` for an exempt one
+    pub bps: u16,
+    pub rounding: FeeRounding,
+}
+
+impl WithdrawFee<'_> {
+    /// Splits `lamports` leaving a vault into the recipient's and the treasury's parts
+    #[inline(always)]
+    pub fn split(&self, lamports: u64) -> Result<(u64, u64), ProgramError> {
+        if lamports.eq(&0) {
+            return Ok((0, 0));
+        }
+
+        Ok(compute_withdraw_amount_rounded(
+            lamports,
+            lamports,
+            self.bps,
+            self.rounding,
+        )?)
+    }
+}
+
+/// Takes the config's treasury off the front of `accounts` while the config
+/// charges a withdrawal fee, returning it with the rate vaults of `owner` pay
+///
+/// Returns `None` and `accounts` as they are while the config isn't initialized
+/// or charges no fee. Otherwise the first account has to be the treasury, or
+/// this fails with `InvalidTreasury`; owners the config exempts still pass it,
+/// so a withdrawal's accounts don't depend on who makes it.
+pub fn check_treasury<'a>(
+    config: &AccountInfo,
+    owner: &Pubkey,
+    accounts: &'a [AccountInfo],
+) -> Result<(Option<WithdrawFee<'a>>, &'a [AccountInfo]), ProgramError> {
+    if !config.is_owned_by(&crate::ID) {
+        return Ok((None, accounts));
+    }
+
+    let config = Config::load(config)?;
+    if config.withdraw_fee_bps().eq(&0) {
+        return Ok((None, accounts));
+    }
+
+    let [treasury, rest @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if treasury.key().ne(config.treasury()) {
+        return Err(VaultError::InvalidTreasury.into());
+    }
+
+    let fee = WithdrawFee {
+        treasury,
+        bps: withdraw_fee_bps(owner, config.withdraw_fee_bps(), config.fee_exempt()),
+        rounding: config.fee_rounding(),
+    };
+
+    Ok((Some(fee), rest))
+}
+
+/// Returns the lamports a recipient gets of `lamports` leaving a vault once
+/// `fee`, if any, is taken out
+#[inline(always)]
+pub fn net_of_fee(fee: Option<&WithdrawFee>, lamports: u64) -> Result<u64, ProgramError> {
+    match fee {
+        Some(fee) => Ok(fee.split(lamports)?.0),
+        None => Ok(lamports),
+    }
+}
+
+/// Moves `lamports` out of `vault`, `fee`'s part of them to its treasury and the
+/// rest to `to`, returning the lamports `to` received
+///
+/// See [`transfer_from_vault`] for the checks each transfer runs, and for what
+/// `authority`, the account that authorized it, records. Both transfers together
+/// count as one withdrawal of the vault.
+pub fn pay_out(
+    vault: &AccountInfo,
+    authority: &AccountInfo,
+    to: &AccountInfo,
+    fee: Option<&WithdrawFee>,
+    lamports: u64,
+    signers: &[Signer],
+) -> Result<u64, ProgramError> {
+    let Some(fee) = fee else {
+        transfer_from_vault(vault, authority, to, lamports, signers)?;
+        return Ok(lamports);
+    };
+
+    // The treasury goes first, so the recipient's transfer is the one that
+    // closes a drained vault
+    let (to_recipient, to_treasury) = fee.split(lamports)?;
+    if to_treasury.ne(&0) && to_recipient.ne(&0) {
+        transfer_share_from_vault(vault, authority, fee.treasury, to_treasury, signers)?;
+    } else if to_treasury.ne(&0) {
+        // The fee took everything, so its transfer is the one counted
+        transfer_from_vault(vault, authority, fee.treasury, to_treasury, signers)?;
+    }
+
+    // Still runs the transfer's checks for a withdrawal of nothing
+    if to_recipient.ne(&0) || to_treasury.eq(&0) {
+        transfer_from_vault(vault, authority, to, to_recipient, signers)?;
+    }
+
+    Ok(to_recipient)
+}
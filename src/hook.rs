@@ -0,0 +1,103 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{AccountMeta, Instruction},
+    program::invoke,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    ProgramResult,
+};
+
+use crate::config::{check_config_account, hook_program};
+use crate::error::VaultError;
+use crate::fee::{check_treasury, WithdrawFee};
+
+/// Hook event sent after a deposit
+pub const HOOK_DEPOSIT: u8 = 0;
+
+/// Hook event sent after a withdrawal
+pub const HOOK_WITHDRAW: u8 = 1;
+
+/// Takes the config's hook program off the front of `accounts`, if it has one
+///
+/// Returns `None` and `accounts` as they are while no hook is configured.
+/// Otherwise the first account has to be the hook program, or this fails with
+/// `IncorrectProgramId`.
+pub fn check_hook_program<'a>(
+    config: &AccountInfo,
+    accounts: &'a [AccountInfo],
+) -> Result<(Option<&'a AccountInfo>, &'a [AccountInfo]), ProgramError> {
+    let hook_key = hook_program(config)?;
+    if hook_key.eq(&Pubkey::default()) {
+        return Ok((None, accounts));
+    }
+
+    // The runtime rejects a hook calling back into the vault, but not the vault calling itself
+    if hook_key.eq(&crate::ID) {
+        return Err(VaultError::HookReentrancy.into());
+    }
+
+    let [hook, rest @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if hook.key().ne(&hook_key) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    Ok((Some(hook), rest))
+}
+
+/// Takes the config PDA, then the config's hook program while it has one and
+/// its treasury while it charges a withdrawal fee, off the front of `accounts`,
+/// for an instruction moving lamports out of a vault of `owner`
+///
+/// Every withdrawal takes the config, initialized or not, so a configured hook
+/// or fee can't be skipped by leaving it out; see [`check_hook_program`] and
+/// [`check_treasury`].
+#[allow(clippy::type_complexity)]
+pub fn check_withdraw_config<'a>(
+    owner: &Pubkey,
+    accounts: &'a [AccountInfo],
+) -> Result<
+    (
+        Option<&'a AccountInfo>,
+        Option<WithdrawFee<'a>>,
+        &'a [AccountInfo],
+    ),
+    ProgramError,
+> {
+    let [config, rest @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    check_config_account(config)?;
+    let (hook, rest) = check_hook_program(config, rest)?;
+    let (fee, rest) = check_treasury(config, owner, rest)?;
+
+    Ok((hook, fee, rest))
+}
+
+/// Tells the hook program about `amount` lamports moved by `owner`
+///
+/// Data: `[event: u8][owner: Pubkey][amount: u64]`, where `event` is
+/// `HOOK_DEPOSIT` or `HOOK_WITHDRAW`. The owner's account is passed read-only
+/// and without its signature, so the hook can't spend from it.
+pub fn invoke_hook(
+    hook: &AccountInfo,
+    event: u8,
+    owner: &AccountInfo,
+    amount: u64,
+) -> ProgramResult {
+    let mut data = [0u8; 41];
+    data[0] = event;
+    data[1..33].copy_from_slice(owner.key());
+    data[33..41].copy_from_slice(&amount.to_le_bytes());
+
+    let instruction = Instruction {
+        program_id: hook.key(),
+        accounts: &[AccountMeta::readonly(owner.key())],
+        data: &data,
+    };
+
+    invoke(&instruction, &[owner])
+}
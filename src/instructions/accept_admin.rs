@@ -0,0 +1,78 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+use pinocchio_log::log;
+
+use crate::config::{check_config_account, Config};
+use crate::error::VaultError;
+
+/// Account structure for the AcceptAdmin instruction
+///
+/// Accounts: `[pending_admin, config]`, where `config` is the initialized
+/// config PDA and `pending_admin` the admin its admin proposed.
+pub struct AcceptAdminAccounts<'a> {
+    pub pending_admin: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for AcceptAdminAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [pending_admin, config, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        if !pending_admin.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        check_config_account(config)?;
+
+        let proposed = *Config::load(config)?.pending_admin();
+        if proposed.eq(&Pubkey::default()) || proposed.ne(pending_admin.key()) {
+            return Err(VaultError::NotPendingAdmin.into());
+        }
+
+        Ok(Self {
+            pending_admin,
+            config,
+        })
+    }
+}
+
+/// AcceptAdmin instruction - makes the proposed admin the config's admin
+///
+/// Takes no data. Fails with `NotPendingAdmin` unless signed by the key the
+/// admin proposed with ProposeAdmin, including when no handoff is pending.
+pub struct AcceptAdmin<'a> {
+    pub accounts: AcceptAdminAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for AcceptAdmin<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        if !data.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let accounts = AcceptAdminAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> AcceptAdmin<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &42;
+
+    pub fn process(&mut self) -> ProgramResult {
+        Config::load_mut(self.accounts.config)?.accept_pending_admin();
+
+        log!("AcceptAdmin: admin handed over to");
+        pinocchio::pubkey::log(self.accounts.pending_admin.key());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+use pinocchio_log::log;
+
+use crate::config::{check_admin, check_config_account, Config};
+use crate::error::VaultError;
+use crate::oracle::check_price_gate;
+use crate::parse::read_array;
+use crate::pda::{DEFAULT_SALT, DEFAULT_VAULT_ID};
+use crate::state::VaultState;
+use crate::stats::{check_stats_account, load_stats};
+use crate::sysvar::clock;
+use crate::vault::{
+    check_not_escrowed, check_not_multisig, check_not_vesting, check_vault_address, releasable,
+    transfer_from_vault,
+};
+
+/// Account structure for the AdminRecover instruction
+///
+/// Accounts: `[admin, config, owner, vault, recovery, system_program, stats,
+/// oracle?, rent_sysvar?]`, where `vault` is `owner`'s opened vault, `recovery`
+/// the config's recovery address, `admin` the config's admin and `oracle` only
+/// passed for a price-gated vault. Only the admin signs.
+pub struct AdminRecoverAccounts<'a> {
+    pub admin: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    pub recovery: &'a AccountInfo,
+    pub stats: &'a AccountInfo,
+    pub rent_sysvar: Option<&'a AccountInfo>,
+    pub stats_bump: u8,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &AdminRecoverInstructionData)> for AdminRecoverAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(
+        (accounts, instruction_data): (&'a [AccountInfo], &AdminRecoverInstructionData),
+    ) -> Result<Self, Self::Error> {
+        let [admin, config, owner, vault, recovery, system_program, stats, trailing @ ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        check_config_account(config)?;
+        check_admin(config, admin)?;
+
+        let inactivity_secs = {
+            let config = Config::load(config)?;
+            let recovery_address = config.recovery_address();
+            if recovery_address.eq(&[0u8; 32]) || recovery.key().ne(recovery_address) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            // Without a window the admin could sweep any vault straight away
+            if config.recovery_inactivity_secs().eq(&0) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            config.recovery_inactivity_secs()
+        };
+
+        if system_program.key().ne(&pinocchio_system::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        check_vault_address(owner, vault, &instruction_data.id, &instruction_data.salt)?;
+
+        if recovery.key().eq(vault.key()) {
+            return Err(VaultError::InvalidRecipient.into());
+        }
+
+        {
+            let state = VaultState::load(vault)?;
+            if state.owner().ne(owner.key()) {
+                return Err(ProgramError::InvalidAccountOwner);
+            }
+
+            if !state.is_inactive_for(inactivity_secs, clock()?.unix_timestamp) {
+                return Err(VaultError::OwnerStillActive.into());
+            }
+
+            // The sweep closes the vault, which would strand its tokens
+            if state.holds_tokens() {
+                return Err(VaultError::TokensOutstanding.into());
+            }
+        }
+
+        // Only the owner's inactivity is waited out, not the other signers'
+        check_not_escrowed(vault)?;
+        check_not_multisig(vault)?;
+        check_not_vesting(vault)?;
+        let rent_sysvar = check_price_gate(vault, trailing)?;
+
+        let stats_bump = check_stats_account(stats)?;
+
+        Ok(Self {
+            admin,
+            vault,
+            recovery,
+            stats,
+            rent_sysvar: rent_sysvar.first(),
+            stats_bump,
+        })
+    }
+}
+
+/// Instruction data structure for the AdminRecover instruction
+///
+/// Layout: `[id: u64][salt: [u8; 32]]`. Both fields are optional, but `salt`
+/// requires `id`: they default to the owner's default unsalted vault.
+pub struct AdminRecoverInstructionData {
+    pub id: [u8; 8],
+    pub salt: [u8; 32],
+}
+
+impl<'a> TryFrom<&'a [u8]> for AdminRecoverInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let (id, salt) = match data.len() {
+            0 => (DEFAULT_VAULT_ID, DEFAULT_SALT),
+            8 => (read_array(data, 0)?, DEFAULT_SALT),
+            40 => (read_array(data, 0)?, read_array(data, 8)?),
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        Ok(Self { id, salt })
+    }
+}
+
+/// AdminRecover instruction - sweeps the vault of an owner who has gone
+/// inactive to the config's recovery address, closing it
+///
+/// Only the config's admin can recover, failing with `NotAdmin` otherwise, and
+/// only once the owner made no deposit or withdrawal for the config's
+/// `recovery_inactivity_secs`, failing with `OwnerStillActive` before then.
+/// Without a recovery address or window, or with another `recovery` account, it
+/// fails with `InvalidAccountData`, and a vault still tracking SPL tokens fails
+/// with `TokensOutstanding`. The recovery address is fixed when the config is
+/// created, so the admin can't redirect a sweep. Unlike EmergencyDrain the
+/// owner's freezes, time locks, price gates and withdrawal limits still apply,
+/// and like a closing withdrawal only the tracked balance and, unless that
+/// would strand donated lamports or a gas reserve, the rent floor go.
+pub struct AdminRecover<'a> {
+    pub accounts: AdminRecoverAccounts<'a>,
+    pub instruction_data: AdminRecoverInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for AdminRecover<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = AdminRecoverInstructionData::try_from(data)?;
+        let accounts = AdminRecoverAccounts::try_from((accounts, &instruction_data))?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> AdminRecover<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &68;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let vault = self.accounts.vault;
+        let lamports = releasable(vault, self.accounts.rent_sysvar, true)?;
+
+        // Opened vaults are debited directly, so no signer seeds are needed
+        transfer_from_vault(
+            vault,
+            self.accounts.admin,
+            self.accounts.recovery,
+            lamports,
+            &[],
+        )?;
+
+        load_stats(
+            self.accounts.admin,
+            self.accounts.stats,
+            self.accounts.stats_bump,
+            self.accounts.rent_sysvar,
+        )?
+        .record_withdrawal(lamports, vault.lamports().eq(&0) as u64);
+
+        log!("AdminRecover: moved {} lamports of vault", lamports);
+        pinocchio::pubkey::log(vault.key());
+
+        Ok(())
+    }
+}
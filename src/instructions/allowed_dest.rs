@@ -0,0 +1,119 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use crate::error::VaultError;
+use crate::state::VaultState;
+use crate::vault::check_account_arrangement;
+
+/// Account structure for the AddAllowedDest and RemoveAllowedDest instructions
+///
+/// Accounts: `[owner, vault]`, where `vault` is an opened vault recording `owner`.
+pub struct AllowedDestAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for AllowedDestAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [owner, vault, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        check_account_arrangement(owner, vault)?;
+
+        if !owner.is_signer() {
+            return Err(VaultError::MissingOwnerSignature.into());
+        }
+
+        // Only opened vaults have an allowlist, and the one they record owns it
+        if VaultState::load(vault)?.owner().ne(owner.key()) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        Ok(Self { owner, vault })
+    }
+}
+
+/// Instruction data structure for the AddAllowedDest and RemoveAllowedDest instructions
+///
+/// Layout: `[dest: Pubkey]`.
+pub struct AllowedDestInstructionData {
+    pub dest: Pubkey,
+}
+
+impl<'a> TryFrom<&'a [u8]> for AllowedDestInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let dest: Pubkey = data
+            .try_into()
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        Ok(Self { dest })
+    }
+}
+
+/// AddAllowedDest instruction - lets WithdrawTo send an opened vault's lamports to `dest`
+///
+/// Once the allowlist holds a destination, WithdrawTo rejects every recipient
+/// that isn't on it.
+pub struct AddAllowedDest<'a> {
+    pub accounts: AllowedDestAccounts<'a>,
+    pub instruction_data: AllowedDestInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for AddAllowedDest<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = AllowedDestInstructionData::try_from(data)?;
+        let accounts = AllowedDestAccounts::try_from(accounts)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> AddAllowedDest<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &18;
+
+    pub fn process(&mut self) -> ProgramResult {
+        VaultState::load_mut(self.accounts.vault)?.add_allowed_dest(&self.instruction_data.dest)
+    }
+}
+
+/// RemoveAllowedDest instruction - takes `dest` off an opened vault's allowlist
+///
+/// Removing the last destination lets WithdrawTo send anywhere again.
+pub struct RemoveAllowedDest<'a> {
+    pub accounts: AllowedDestAccounts<'a>,
+    pub instruction_data: AllowedDestInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for RemoveAllowedDest<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = AllowedDestInstructionData::try_from(data)?;
+        let accounts = AllowedDestAccounts::try_from(accounts)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> RemoveAllowedDest<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &19;
+
+    pub fn process(&mut self) -> ProgramResult {
+        VaultState::load_mut(self.accounts.vault)?.remove_allowed_dest(&self.instruction_data.dest)
+    }
+}
@@ -0,0 +1,120 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use crate::error::VaultError;
+use crate::state::VaultState;
+use crate::vault::check_account_arrangement;
+
+/// Account structure for the AddAllowedSource and RemoveAllowedSource instructions
+///
+/// Accounts: `[owner, vault]`, where `vault` is an opened vault recording `owner`.
+pub struct AllowedSourceAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for AllowedSourceAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [owner, vault, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        check_account_arrangement(owner, vault)?;
+
+        if !owner.is_signer() {
+            return Err(VaultError::MissingOwnerSignature.into());
+        }
+
+        // Only opened vaults have a whitelist, and the one they record owns it
+        if VaultState::load(vault)?.owner().ne(owner.key()) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        Ok(Self { owner, vault })
+    }
+}
+
+/// Instruction data structure for the AddAllowedSource and RemoveAllowedSource instructions
+///
+/// Layout: `[source: Pubkey]`.
+pub struct AllowedSourceInstructionData {
+    pub source: Pubkey,
+}
+
+impl<'a> TryFrom<&'a [u8]> for AllowedSourceInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let source: Pubkey = data
+            .try_into()
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        Ok(Self { source })
+    }
+}
+
+/// AddAllowedSource instruction - lets `source` fund deposits into an opened vault
+///
+/// Once the whitelist holds a source, every deposit funded by an account that
+/// isn't on it, other than the owner, fails with `SourceNotWhitelisted`.
+pub struct AddAllowedSource<'a> {
+    pub accounts: AllowedSourceAccounts<'a>,
+    pub instruction_data: AllowedSourceInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for AddAllowedSource<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = AllowedSourceInstructionData::try_from(data)?;
+        let accounts = AllowedSourceAccounts::try_from(accounts)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> AddAllowedSource<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &69;
+
+    pub fn process(&mut self) -> ProgramResult {
+        VaultState::load_mut(self.accounts.vault)?.add_allowed_source(&self.instruction_data.source)
+    }
+}
+
+/// RemoveAllowedSource instruction - takes `source` off an opened vault's whitelist
+///
+/// Removing the last source lets deposits come from anywhere again.
+pub struct RemoveAllowedSource<'a> {
+    pub accounts: AllowedSourceAccounts<'a>,
+    pub instruction_data: AllowedSourceInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for RemoveAllowedSource<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = AllowedSourceInstructionData::try_from(data)?;
+        let accounts = AllowedSourceAccounts::try_from(accounts)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> RemoveAllowedSource<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &70;
+
+    pub fn process(&mut self) -> ProgramResult {
+        VaultState::load_mut(self.accounts.vault)?
+            .remove_allowed_source(&self.instruction_data.source)
+    }
+}
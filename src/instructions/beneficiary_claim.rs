@@ -0,0 +1,197 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+use pinocchio_log::log;
+
+use crate::error::VaultError;
+use crate::fee::{pay_out, WithdrawFee};
+use crate::hook::{check_withdraw_config, invoke_hook, HOOK_WITHDRAW};
+use crate::oracle::check_price_gate;
+use crate::parse::read_array;
+use crate::pda::{DEFAULT_SALT, DEFAULT_VAULT_ID};
+use crate::state::VaultState;
+use crate::stats::{check_stats_account, load_stats};
+use crate::sysvar::clock;
+use crate::vault::{
+    check_not_escrowed, check_not_multisig, check_not_vesting, check_vault_address, releasable,
+};
+
+/// Account structure for the BeneficiaryClaim instruction
+///
+/// Accounts: `[beneficiary, owner, vault, system_program, stats, config,
+/// hook_program?, treasury?, oracle?, rent_sysvar?]`, where `vault` is
+/// `owner`'s opened vault naming `beneficiary`, `hook_program` is only passed
+/// while the config has one, `treasury` only while it charges a withdrawal fee
+/// and `oracle` only for a price-gated vault. The beneficiary signs and pays for
+/// the stats PDA if it doesn't exist yet; the owner doesn't sign.
+pub struct BeneficiaryClaimAccounts<'a> {
+    pub beneficiary: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    pub stats: &'a AccountInfo,
+    /// The config's hook program, told about the withdrawal once it lands
+    pub hook: Option<&'a AccountInfo>,
+    /// The config's withdrawal fee, while it charges one
+    pub fee: Option<WithdrawFee<'a>>,
+    pub rent_sysvar: Option<&'a AccountInfo>,
+    pub stats_bump: u8,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &BeneficiaryClaimInstructionData)>
+    for BeneficiaryClaimAccounts<'a>
+{
+    type Error = ProgramError;
+
+    fn try_from(
+        (accounts, instruction_data): (&'a [AccountInfo], &BeneficiaryClaimInstructionData),
+    ) -> Result<Self, Self::Error> {
+        let [beneficiary, owner, vault, system_program, stats, trailing @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        if !beneficiary.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        if beneficiary.key().eq(vault.key()) {
+            return Err(VaultError::InvalidAccountArrangement.into());
+        }
+
+        if system_program.key().ne(&pinocchio_system::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        check_vault_address(owner, vault, &instruction_data.id, &instruction_data.salt)?;
+
+        {
+            let state = VaultState::load(vault)?;
+            if state.owner().ne(owner.key()) {
+                return Err(ProgramError::InvalidAccountOwner);
+            }
+
+            if state.beneficiary().ne(beneficiary.key())
+                || !state.is_claimable(clock()?.unix_timestamp)
+            {
+                return Err(VaultError::OwnerStillActive.into());
+            }
+
+            // The claim closes the vault, which would strand its tokens
+            if state.holds_tokens() {
+                return Err(VaultError::TokensOutstanding.into());
+            }
+        }
+
+        // Only the owner's inactivity is waited out, not the other signers'
+        check_not_escrowed(vault)?;
+        check_not_multisig(vault)?;
+        check_not_vesting(vault)?;
+        let (hook, fee, trailing) = check_withdraw_config(owner.key(), trailing)?;
+        let rent_sysvar = check_price_gate(vault, trailing)?;
+
+        let stats_bump = check_stats_account(stats)?;
+
+        Ok(Self {
+            beneficiary,
+            vault,
+            stats,
+            hook,
+            fee,
+            rent_sysvar: rent_sysvar.first(),
+            stats_bump,
+        })
+    }
+}
+
+/// Instruction data structure for the BeneficiaryClaim instruction
+///
+/// Layout: `[id: u64][salt: [u8; 32]]`. Both fields are optional, but `salt`
+/// requires `id`: they default to the owner's default unsalted vault.
+pub struct BeneficiaryClaimInstructionData {
+    pub id: [u8; 8],
+    pub salt: [u8; 32],
+}
+
+impl<'a> TryFrom<&'a [u8]> for BeneficiaryClaimInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let (id, salt) = match data.len() {
+            0 => (DEFAULT_VAULT_ID, DEFAULT_SALT),
+            8 => (read_array(data, 0)?, DEFAULT_SALT),
+            40 => (read_array(data, 0)?, read_array(data, 8)?),
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        Ok(Self { id, salt })
+    }
+}
+
+/// BeneficiaryClaim instruction - sends the balance of a vault whose owner has
+/// gone inactive to its beneficiary, closing it
+///
+/// Fails with `OwnerStillActive` unless the signer is the vault's beneficiary
+/// and the owner made no deposit or withdrawal for the vault's
+/// `inactivity_secs`, with `TokensOutstanding` while the vault still tracks
+/// SPL tokens, and the usual withdrawal checks, such as freezes, time locks and
+/// price gates, still apply. Like a closing withdrawal, the tracked balance
+/// goes, along with the rent floor unless that would strand lamports sent to
+/// the vault outside a deposit or its gas reserve, in which case the vault
+/// stays open holding them. The lamports the beneficiary receives after any
+/// fee are recorded in the global stats.
+pub struct BeneficiaryClaim<'a> {
+    pub accounts: BeneficiaryClaimAccounts<'a>,
+    pub instruction_data: BeneficiaryClaimInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for BeneficiaryClaim<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = BeneficiaryClaimInstructionData::try_from(data)?;
+        let accounts = BeneficiaryClaimAccounts::try_from((accounts, &instruction_data))?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> BeneficiaryClaim<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &63;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let vault = self.accounts.vault;
+
+        // Only the tracked balance and, once that drains the vault, its rent
+        // floor go, so donated lamports and the gas reserve stay behind
+        let lamports = releasable(vault, self.accounts.rent_sysvar, true)?;
+
+        // Opened vaults are debited directly, so no signer seeds are needed
+        let received = pay_out(
+            vault,
+            self.accounts.beneficiary,
+            self.accounts.beneficiary,
+            self.accounts.fee.as_ref(),
+            lamports,
+            &[],
+        )?;
+
+        load_stats(
+            self.accounts.beneficiary,
+            self.accounts.stats,
+            self.accounts.stats_bump,
+            self.accounts.rent_sysvar,
+        )?
+        .record_withdrawal(received, 1);
+
+        if let Some(hook) = self.accounts.hook {
+            invoke_hook(hook, HOOK_WITHDRAW, self.accounts.beneficiary, lamports)?;
+        }
+
+        log!(
+            "BeneficiaryClaim: sent {} lamports to the beneficiary",
+            received
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+use pinocchio::{
+    account_info::AccountInfo, cpi::set_return_data, program_error::ProgramError, ProgramResult,
+};
+
+use crate::error::VaultError;
+use crate::state::VaultState;
+use crate::vault::{check_release_allowed, check_vault_owner, is_opened};
+
+/// CanWithdraw instruction - reports whether a vault could release lamports right now
+///
+/// Accounts: `[vault]`, which is only read. Always succeeds with `[reason: u64]`
+/// as return data: `0` if a withdrawal is currently permitted, or the code it
+/// would fail with, e.g. `VaultEmpty` with nothing deposited, `VaultFrozen`,
+/// `VaultLocked` before the unlock timestamp or `VaultTooYoung` before the
+/// minimum age passes. Custom codes are the `VaultError` values, so wallets can
+/// show why a withdraw button is disabled. Signatures and the withdrawal's own
+/// data aren't checked; SimulateWithdraw previews a full Withdraw.
+pub struct CanWithdraw<'a> {
+    pub vault: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for CanWithdraw<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        if !data.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let [vault, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self { vault })
+    }
+}
+
+impl<'a> CanWithdraw<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &50;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let reason = match check_withdrawable(self.vault) {
+            Ok(()) => 0,
+            Err(error) => u64::from(error),
+        };
+        set_return_data(&reason.to_le_bytes());
+
+        Ok(())
+    }
+}
+
+/// Runs the checks on `vault` itself that a withdrawal would hit
+fn check_withdrawable(vault: &AccountInfo) -> ProgramResult {
+    check_vault_owner(vault)?;
+
+    if !is_opened(vault) {
+        if vault.lamports().eq(&0) {
+            return Err(VaultError::VaultEmpty.into());
+        }
+
+        return Ok(());
+    }
+
+    let state = VaultState::load(vault)?;
+    if state.balance().eq(&0) {
+        return Err(VaultError::VaultEmpty.into());
+    }
+
+    check_release_allowed(&state)
+}
@@ -0,0 +1,142 @@
+use pinocchio::{
+    account_info::AccountInfo, cpi::set_return_data, program_error::ProgramError, ProgramResult,
+};
+use pinocchio_log::log;
+
+use crate::error::VaultError;
+use crate::reward_pool::{check_reward_pool_account, pay_from_reward_pool};
+use crate::state::VaultState;
+use crate::stats::{check_stats_account, load_stats};
+use crate::sysvar::clock;
+use crate::vault::{check_account_arrangement, LamportSnapshot};
+
+/// Account structure for the ClaimReward instruction
+///
+/// Accounts: `[owner, vault, system_program, stats, reward_pool, rent_sysvar?]`,
+/// where `vault` is an opened vault recording `owner` and `reward_pool` the
+/// `["reward_pool"]` PDA.
+pub struct ClaimRewardAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    pub stats: &'a AccountInfo,
+    pub reward_pool: &'a AccountInfo,
+    pub rent_sysvar: Option<&'a AccountInfo>,
+    pub stats_bump: u8,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ClaimRewardAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [owner, vault, system_program, stats, reward_pool, rent_sysvar @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        check_account_arrangement(owner, vault)?;
+
+        if !owner.is_signer() {
+            return Err(VaultError::MissingOwnerSignature.into());
+        }
+
+        if system_program.key().ne(&pinocchio_system::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        {
+            let state = VaultState::load(vault)?;
+            if state.owner().ne(owner.key()) {
+                return Err(ProgramError::InvalidAccountOwner);
+            }
+
+            // A frozen vault takes no lamports in, interest included
+            if state.is_frozen() {
+                return Err(VaultError::VaultFrozen.into());
+            }
+        }
+
+        let stats_bump = check_stats_account(stats)?;
+        check_reward_pool_account(reward_pool)?;
+
+        Ok(Self {
+            owner,
+            vault,
+            stats,
+            reward_pool,
+            rent_sysvar: rent_sysvar.first(),
+            stats_bump,
+        })
+    }
+}
+
+/// ClaimReward instruction - credits the interest an opened vault accrued to
+/// its balance, out of the reward pool
+///
+/// Interest accrues on the tracked balance at the vault's `reward_rate_bps` a
+/// year, as `balance * rate_bps * elapsed_secs / (10000 * SECONDS_PER_YEAR)`,
+/// and every balance change folds what accrued so far in first, so lamports
+/// only earn for the time they were in the vault. A pool holding less above
+/// its rent floor than the interest fails with `RewardPoolUnderfunded`, and
+/// closing the vault forfeits interest not yet claimed. The interest counts as
+/// a deposit in the global stats, and the vault's new balance is set as return
+/// data.
+pub struct ClaimReward<'a> {
+    pub accounts: ClaimRewardAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for ClaimReward<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        if !data.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let accounts = ClaimRewardAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> ClaimReward<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &65;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let conserved = || {
+            [
+                self.accounts.owner,
+                self.accounts.vault,
+                self.accounts.stats,
+                self.accounts.reward_pool,
+            ]
+        };
+        let snapshot = LamportSnapshot::take(conserved());
+
+        let interest =
+            VaultState::load_mut(self.accounts.vault)?.claim_interest(clock()?.unix_timestamp);
+        pay_from_reward_pool(
+            self.accounts.reward_pool,
+            self.accounts.vault,
+            interest,
+            self.accounts.rent_sysvar,
+        )?;
+
+        load_stats(
+            self.accounts.owner,
+            self.accounts.stats,
+            self.accounts.stats_bump,
+            self.accounts.rent_sysvar,
+        )?
+        .record_deposit(interest, false);
+
+        snapshot.check_conserved(conserved())?;
+
+        log!("ClaimReward: credited {} lamports of interest", interest);
+
+        // Report the new vault balance so clients don't need a follow-up fetch
+        let balance = VaultState::load(self.accounts.vault)?.balance();
+        set_return_data(&balance.to_le_bytes());
+
+        Ok(())
+    }
+}
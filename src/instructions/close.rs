@@ -0,0 +1,272 @@
+use pinocchio::{
+    account_info::AccountInfo, instruction::Signer, program_error::ProgramError, ProgramResult,
+};
+
+use crate::error::VaultError;
+use crate::instructions::WithdrawAccounts;
+use crate::pda::{vault_signer_seeds, DEFAULT_SALT, DEFAULT_VAULT_ID};
+use crate::tombstone::{check_tombstone_account, record_close};
+use crate::vault::{check_account_count, check_not_vesting, releasable, rent_reserve};
+
+/// Instruction data structure for the Close and CloseAndSweep instructions
+///
+/// Layout: `[id: u64][salt: [u8; 32]][refund: u8][tombstone: u8]`. Every field
+/// is optional, but each one requires the fields before it: `id` and `salt`
+/// default to the owner's default unsalted vault, and `refund` and `tombstone`
+/// to `0`. Only Close takes a `refund` of `1`, which sends the rent to a
+/// `refund_to` account instead of the owner. A `tombstone` of `1` records the
+/// close in the vault's tombstone PDA, which is then passed right after
+/// `stats`, or after `refund_to` with a refund, ahead of `config`.
+pub struct CloseInstructionData {
+    pub id: [u8; 8],
+    pub salt: [u8; 32],
+    pub refund: bool,
+    pub tombstone: bool,
+}
+
+impl<'a> TryFrom<&'a [u8]> for CloseInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let (id, salt) = match data.len() {
+            0 => (DEFAULT_VAULT_ID, DEFAULT_SALT),
+            8 => (data.try_into().unwrap(), DEFAULT_SALT),
+            40..=42 => (
+                data[..8].try_into().unwrap(),
+                data[8..40].try_into().unwrap(),
+            ),
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        let [refund, tombstone] = [40, 41].map(|offset| match data.get(offset) {
+            None | Some(0) => Ok(false),
+            Some(1) => Ok(true),
+            _ => Err(ProgramError::InvalidInstructionData),
+        });
+
+        Ok(Self {
+            id,
+            salt,
+            refund: refund?,
+            tombstone: tombstone?,
+        })
+    }
+}
+
+/// Close instruction - reclaims the rent of a vault that holds nothing else
+///
+/// Uses the same accounts as Withdraw. Fails with `VaultNotEmpty` while the
+/// vault holds anything above its rent-exempt reserve, so a close can't take
+/// funds along by accident; use CloseAndSweep to withdraw them in the same go.
+/// With `refund`, the accounts are `[owner, vault, system_program, stats,
+/// refund_to, ..]` and the rent goes to `refund_to`, e.g. back to whoever
+/// subsidized it. The owner has to sign for that even for an escrowed vault.
+pub struct Close<'a> {
+    pub accounts: WithdrawAccounts<'a>,
+    pub refund_to: &'a AccountInfo,
+    /// The vault's tombstone PDA and its bump, when the close records one
+    pub tombstone: Option<(&'a AccountInfo, u8)>,
+    pub instruction_data: CloseInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for Close<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = CloseInstructionData::try_from(data)?;
+        check_account_count(accounts, WithdrawAccounts::MIN_ACCOUNTS)?;
+        let [owner, vault, system_program, stats, trailing @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        let (refund_to, trailing) = if instruction_data.refund {
+            let [refund_to, trailing @ ..] = trailing else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+            (refund_to, trailing)
+        } else {
+            (owner, trailing)
+        };
+
+        let (tombstone, trailing) = split_tombstone(&instruction_data, owner, vault, trailing)?;
+
+        let accounts = WithdrawAccounts::check(
+            owner,
+            vault,
+            system_program,
+            stats,
+            trailing,
+            &instruction_data.id,
+            &instruction_data.salt,
+        )?;
+
+        if instruction_data.refund {
+            // Only the owner decides who the rent goes to, even when an escrow agent signs
+            if !owner.is_signer() {
+                return Err(VaultError::MissingOwnerSignature.into());
+            }
+
+            if refund_to.key().eq(vault.key()) {
+                return Err(VaultError::InvalidRecipient.into());
+            }
+        }
+
+        Ok(Self {
+            accounts,
+            refund_to,
+            tombstone,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> Close<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &16;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let lamports = self.accounts.vault.lamports();
+        if lamports > rent_reserve(self.accounts.vault, self.accounts.rent_sysvar)? {
+            return Err(VaultError::VaultNotEmpty.into());
+        }
+
+        close_vault(
+            &self.accounts,
+            self.refund_to,
+            self.tombstone,
+            &self.instruction_data,
+            lamports,
+        )
+    }
+}
+
+/// CloseAndSweep instruction - withdraws a vault's balance and closes it
+///
+/// Uses the same accounts as Withdraw. Unlike a closing Withdraw, which leaves
+/// an opened vault holding lamports sent outside a deposit open, this fails
+/// with `VaultNotEmpty` unless the vault actually ends up closed.
+pub struct CloseAndSweep<'a> {
+    pub accounts: WithdrawAccounts<'a>,
+    /// The vault's tombstone PDA and its bump, when the close records one
+    pub tombstone: Option<(&'a AccountInfo, u8)>,
+    pub instruction_data: CloseInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for CloseAndSweep<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = CloseInstructionData::try_from(data)?;
+
+        // The swept balance belongs to the owner, so it can't be refunded elsewhere
+        if instruction_data.refund {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        check_account_count(accounts, WithdrawAccounts::MIN_ACCOUNTS)?;
+        let [owner, vault, system_program, stats, trailing @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        let (tombstone, trailing) = split_tombstone(&instruction_data, owner, vault, trailing)?;
+
+        let accounts = WithdrawAccounts::check(
+            owner,
+            vault,
+            system_program,
+            stats,
+            trailing,
+            &instruction_data.id,
+            &instruction_data.salt,
+        )?;
+
+        // Vesting vaults only release their deposits through WithdrawVested
+        check_not_vesting(accounts.vault)?;
+
+        Ok(Self {
+            accounts,
+            tombstone,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> CloseAndSweep<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &17;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let lamports = releasable(self.accounts.vault, self.accounts.rent_sysvar, true)?;
+        if lamports.ne(&self.accounts.vault.lamports()) {
+            return Err(VaultError::VaultNotEmpty.into());
+        }
+
+        close_vault(
+            &self.accounts,
+            self.accounts.owner,
+            self.tombstone,
+            &self.instruction_data,
+            lamports,
+        )
+    }
+}
+
+/// Takes the vault's tombstone off the front of `trailing` when the close records one
+///
+/// The owner pays the tombstone's rent, so has to sign for it even for an
+/// escrowed vault.
+#[allow(clippy::type_complexity)]
+fn split_tombstone<'a>(
+    instruction_data: &CloseInstructionData,
+    owner: &AccountInfo,
+    vault: &AccountInfo,
+    trailing: &'a [AccountInfo],
+) -> Result<(Option<(&'a AccountInfo, u8)>, &'a [AccountInfo]), ProgramError> {
+    if !instruction_data.tombstone {
+        return Ok((None, trailing));
+    }
+
+    let [tombstone, trailing @ ..] = trailing else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !owner.is_signer() {
+        return Err(VaultError::MissingOwnerSignature.into());
+    }
+
+    let bump = check_tombstone_account(vault, tombstone)?;
+    Ok((Some((tombstone, bump)), trailing))
+}
+
+/// Moves every one of the vault's `lamports` to `to`, which closes it, then
+/// records the close in `tombstone` if there is one
+fn close_vault(
+    accounts: &WithdrawAccounts,
+    to: &AccountInfo,
+    tombstone: Option<(&AccountInfo, u8)>,
+    instruction_data: &CloseInstructionData,
+    lamports: u64,
+) -> ProgramResult {
+    // Create PDA signer seeds
+    let seeds = vault_signer_seeds(
+        accounts.owner.key(),
+        &instruction_data.id,
+        &instruction_data.salt,
+        &accounts.bumps,
+    );
+    let signers = [Signer::from(&seeds)];
+
+    accounts.pay_out(to, lamports, &signers)?;
+    accounts.record_withdrawal(lamports)?;
+    accounts.notify_hook(lamports)?;
+
+    if let Some((tombstone, bump)) = tombstone {
+        record_close(
+            accounts.owner,
+            accounts.vault,
+            tombstone,
+            bump,
+            accounts.rent_sysvar,
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,198 @@
+use pinocchio::{
+    account_info::AccountInfo, instruction::Signer, program_error::ProgramError, ProgramResult,
+};
+use pinocchio_log::log;
+
+use crate::error::VaultError;
+use crate::oracle::check_price_gate;
+use crate::pda::{find_vault_address, log_vault_mismatch, vault_signer_seeds, DEFAULT_SALT};
+use crate::stats::{check_stats_account, load_stats};
+use crate::vault::{
+    check_account_arrangement, check_not_escrowed, check_not_multisig, check_not_vesting,
+    check_vault_owner, record_vault_deposit, releasable, transfer_from_vault,
+};
+
+/// Account structure for the Consolidate instruction
+///
+/// Accounts: `[owner, target, sources @ .., system_program, stats]`.
+pub struct ConsolidateAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub target: &'a AccountInfo,
+    pub sources: &'a [AccountInfo],
+    pub stats: &'a AccountInfo,
+    pub stats_bump: u8,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ConsolidateAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [owner, target, sources @ .., system_program, stats] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if sources.is_empty() {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        // Basic Accounts Checks
+        check_account_arrangement(owner, target)?;
+        sources
+            .iter()
+            .try_for_each(|source| check_account_arrangement(owner, source))?;
+
+        if !owner.is_signer() {
+            return Err(VaultError::MissingOwnerSignature.into());
+        }
+
+        if system_program.key() != &pinocchio_system::ID {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        check_vault_owner(target)?;
+
+        let stats_bump = check_stats_account(stats)?;
+
+        Ok(Self {
+            owner,
+            target,
+            sources,
+            stats,
+            stats_bump,
+        })
+    }
+}
+
+/// Instruction data structure for the Consolidate instruction
+///
+/// Layout: `[target_id: u64]` followed by `[id: u64]` once per source vault,
+/// in the same order. Only unsalted vaults can be consolidated, and sources
+/// can't have a price gate.
+pub struct ConsolidateInstructionData<'a> {
+    pub target_id: [u8; 8],
+    pub source_ids: &'a [u8],
+}
+
+impl<'a> TryFrom<&'a [u8]> for ConsolidateInstructionData<'a> {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() < 16 || !data.len().is_multiple_of(8) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let (target_id, source_ids) = data.split_at(8);
+
+        Ok(Self {
+            target_id: target_id.try_into().unwrap(),
+            source_ids,
+        })
+    }
+}
+
+/// Consolidate instruction - sweeps several of the owner's vaults into one
+///
+/// Each source is drained completely, rent reserve included, which closes it;
+/// the target ends up holding the combined balance. An opened source holding
+/// lamports sent outside a deposit only gives up its tracked balance and stays open.
+pub struct Consolidate<'a> {
+    pub accounts: ConsolidateAccounts<'a>,
+    pub instruction_data: ConsolidateInstructionData<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for Consolidate<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = ConsolidateAccounts::try_from(accounts)?;
+        let instruction_data = ConsolidateInstructionData::try_from(data)?;
+
+        // Every source vault needs a matching id
+        if instruction_data.source_ids.len() / 8 != accounts.sources.len() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let (target_key, target_bump) = find_vault_address(
+            accounts.owner.key(),
+            &instruction_data.target_id,
+            &DEFAULT_SALT,
+        );
+        if accounts.target.key().ne(&target_key) {
+            log_vault_mismatch(target_bump, accounts.target.key());
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> Consolidate<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &4;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let owner = self.accounts.owner;
+        let target = self.accounts.target;
+        let target_created = target.lamports().eq(&0);
+        let mut total: u64 = 0;
+        let mut closed: u64 = 0;
+
+        for (source, id) in self
+            .accounts
+            .sources
+            .iter()
+            .zip(self.instruction_data.source_ids.chunks_exact(8))
+        {
+            let id: &[u8; 8] = id.try_into().unwrap();
+
+            // Accounts Checks
+            if source.key().eq(target.key()) {
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            check_vault_owner(source)?;
+            check_price_gate(source, &[])?;
+            check_not_vesting(source)?;
+            check_not_escrowed(source)?;
+            check_not_multisig(source)?;
+
+            let (source_key, bump) = find_vault_address(owner.key(), id, &DEFAULT_SALT);
+            if source.key().ne(&source_key) {
+                log_vault_mismatch(bump, source.key());
+                return Err(ProgramError::InvalidAccountOwner);
+            }
+
+            // Nothing to sweep, move on to the next vault
+            let lamports = releasable(source, None, true)?;
+            if lamports.eq(&0) {
+                continue;
+            }
+
+            let bumps = [bump];
+            let seeds = vault_signer_seeds(owner.key(), id, &DEFAULT_SALT, &bumps);
+            let signers = [Signer::from(&seeds)];
+
+            transfer_from_vault(source, owner, target, lamports, &signers)?;
+
+            total = total
+                .checked_add(lamports)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            closed += source.lamports().eq(&0) as u64;
+        }
+
+        record_vault_deposit(target, owner, total, 0, None)?;
+
+        // Lamports stay locked, only the number of vaults changes
+        let mut stats = load_stats(owner, self.accounts.stats, self.accounts.stats_bump, None)?;
+        stats.record_close(closed);
+        if target_created && total.ne(&0) {
+            stats.record_open(0);
+        }
+
+        log!("Consolidate: moved {} lamports", total);
+
+        Ok(())
+    }
+}
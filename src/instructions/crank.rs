@@ -0,0 +1,203 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+use pinocchio_log::log;
+
+use crate::error::VaultError;
+use crate::fee::{pay_out, WithdrawFee};
+use crate::hook::{check_withdraw_config, invoke_hook, HOOK_WITHDRAW};
+use crate::logic::compute_drip_due;
+use crate::oracle::check_price_gate;
+use crate::state::VaultState;
+use crate::stats::{check_stats_account, load_stats};
+use crate::sysvar::clock;
+use crate::vault::{
+    check_not_escrowed, check_not_multisig, check_not_vesting, releasable, transfer_from_vault,
+    transfer_share_from_vault,
+};
+
+/// Account structure for the Crank instruction
+///
+/// Accounts: `[cranker, vault, recipient, system_program, stats, config,
+/// hook_program?, treasury?, oracle?, rent_sysvar?]`, where `vault` is an opened vault
+/// with a drip schedule, `recipient` its drip recipient, `hook_program` only
+/// passed while the config has one, `treasury` only while it charges a
+/// withdrawal fee, at the rate the vault's owner pays, and `oracle` only for a
+/// price-gated vault. Anyone can sign as `cranker`, and pays for the stats PDA
+/// if it doesn't exist yet; the owner doesn't sign.
+pub struct CrankAccounts<'a> {
+    pub cranker: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    pub recipient: &'a AccountInfo,
+    pub stats: &'a AccountInfo,
+    /// The config's hook program, told about the release once it lands
+    pub hook: Option<&'a AccountInfo>,
+    /// The config's withdrawal fee, while it charges one
+    pub fee: Option<WithdrawFee<'a>>,
+    pub rent_sysvar: Option<&'a AccountInfo>,
+    pub stats_bump: u8,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for CrankAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [cranker, vault, recipient, system_program, stats, trailing @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        if !cranker.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        if cranker.key().eq(vault.key()) {
+            return Err(VaultError::InvalidAccountArrangement.into());
+        }
+
+        if system_program.key().ne(&pinocchio_system::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let owner = {
+            let state = VaultState::load(vault)?;
+            if state.drip_interval_secs().eq(&0) {
+                return Err(VaultError::DripNotDue.into());
+            }
+
+            if state.drip_recipient().ne(recipient.key()) {
+                return Err(VaultError::InvalidRecipient.into());
+            }
+
+            *state.owner()
+        };
+
+        // The owner may have set an escrow agent, multisig or vesting since scheduling the drip
+        check_not_escrowed(vault)?;
+        check_not_multisig(vault)?;
+        check_not_vesting(vault)?;
+        let (hook, fee, trailing) = check_withdraw_config(&owner, trailing)?;
+        let rent_sysvar = check_price_gate(vault, trailing)?;
+
+        let stats_bump = check_stats_account(stats)?;
+
+        Ok(Self {
+            cranker,
+            vault,
+            recipient,
+            stats,
+            hook,
+            fee,
+            rent_sysvar: rent_sysvar.first(),
+            stats_bump,
+        })
+    }
+}
+
+/// Crank instruction - releases a vault's due drip to its recipient
+///
+/// Takes no data. Releases the drip amount for every whole interval elapsed
+/// since the last release, up to the vault's tracked balance, and moves the
+/// last release forward by those intervals; drips the balance couldn't cover
+/// are forfeited rather than owed. `TIP` lamports of the release go to the
+/// cranker for its trouble. Fails with `DripNotDue` if the vault has no drip
+/// schedule or no interval elapsed yet, and the usual withdrawal checks, such
+/// as freezes, time locks, price gates and `AccountingMismatch`, still apply.
+/// The release is recorded in the global stats like any withdrawal.
+pub struct Crank<'a> {
+    pub accounts: CrankAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for Crank<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        if !data.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let accounts = CrankAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> Crank<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &47;
+
+    /// Lamports of each release paid to the cranker, about one signature's fee
+    pub const TIP: u64 = 5_000;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let vault = self.accounts.vault;
+
+        // Checks the tracked balance against the lamports the vault holds
+        let available = releasable(vault, self.accounts.rent_sysvar, false)?;
+
+        let released = {
+            let mut state = VaultState::load_mut(vault)?;
+            let (intervals, due) = compute_drip_due(
+                clock()?.unix_timestamp,
+                state.drip_last_release(),
+                state.drip_interval_secs(),
+                state.drip_amount(),
+            );
+            if intervals.eq(&0) {
+                return Err(VaultError::DripNotDue.into());
+            }
+
+            state.record_drip(intervals);
+            due.min(available)
+        };
+
+        if released.eq(&0) {
+            return Err(VaultError::VaultEmpty.into());
+        }
+
+        // Opened vaults are debited directly, so no signer seeds are needed
+        let tip = Self::TIP.min(released);
+        if released > tip {
+            pay_out(
+                vault,
+                self.accounts.cranker,
+                self.accounts.recipient,
+                self.accounts.fee.as_ref(),
+                released - tip,
+                &[],
+            )?;
+        }
+        // The tip is only counted as the withdrawal when nothing else moved
+        if released > tip {
+            transfer_share_from_vault(
+                vault,
+                self.accounts.cranker,
+                self.accounts.cranker,
+                tip,
+                &[],
+            )?;
+        } else {
+            transfer_from_vault(
+                vault,
+                self.accounts.cranker,
+                self.accounts.cranker,
+                tip,
+                &[],
+            )?;
+        }
+
+        load_stats(
+            self.accounts.cranker,
+            self.accounts.stats,
+            self.accounts.stats_bump,
+            self.accounts.rent_sysvar,
+        )?
+        .record_withdrawal(released, 0);
+
+        log!("Crank: released {} lamports, {} as tip", released, tip);
+
+        // The owner isn't among the accounts, so the hook hears from the cranker
+        if let Some(hook) = self.accounts.hook {
+            invoke_hook(hook, HOOK_WITHDRAW, self.accounts.cranker, released)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+use pinocchio_log::log;
+
+use crate::instructions::{
+    OpenVault, OpenVaultAccounts, OpenVaultInstructionData, UpgradeToMultisigInstructionData,
+};
+use crate::state::VaultState;
+
+/// CreateMultisig instruction - opens a vault that answers to a set of signers
+/// from the start
+///
+/// Accounts: those of OpenVault. Data: `[id: u64][salt: [u8; 32]][threshold: u8]
+/// [signers: [Pubkey]]`, the vault's derivation followed by UpgradeToMultisig's
+/// data. The vault opens like an OpenVault without flags or label, and takes
+/// the signer set in the same instruction, so its owner never holds sole
+/// control of it; from then on it behaves exactly like a vault handed over with
+/// UpgradeToMultisig.
+pub struct CreateMultisig<'a> {
+    pub open: OpenVault<'a>,
+    pub multisig: UpgradeToMultisigInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for CreateMultisig<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        if data.len() < 40 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let (derivation, multisig) = data.split_at(40);
+        let instruction_data = OpenVaultInstructionData::try_from(derivation)?;
+        let multisig = UpgradeToMultisigInstructionData::try_from(multisig)?;
+        let accounts = OpenVaultAccounts::try_from((accounts, &instruction_data))?;
+
+        Ok(Self {
+            open: OpenVault {
+                accounts,
+                instruction_data,
+            },
+            multisig,
+        })
+    }
+}
+
+impl<'a> CreateMultisig<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &66;
+
+    pub fn process(&mut self) -> ProgramResult {
+        self.open.process()?;
+
+        VaultState::load_mut(self.open.accounts.vault)?
+            .set_multisig(self.multisig.signers(), self.multisig.threshold);
+
+        log!(
+            "CreateMultisig: {} of {} signers",
+            self.multisig.threshold,
+            self.multisig.signer_count
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,175 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+use pinocchio_log::log;
+
+use crate::error::VaultError;
+use crate::fee::{pay_out, WithdrawFee};
+use crate::hook::{check_withdraw_config, invoke_hook, HOOK_WITHDRAW};
+use crate::logic::compute_withdraw_amount;
+use crate::oracle::check_price_gate;
+use crate::state::VaultState;
+use crate::stats::{check_stats_account, load_stats};
+use crate::vault::{check_not_escrowed, check_not_multisig, check_not_vesting, releasable};
+
+/// Account structure for the DelegateWithdraw instruction
+///
+/// Accounts: `[delegate, vault, system_program, stats, config, hook_program?,
+/// treasury?, oracle?, rent_sysvar?]`, where `vault` is an opened vault listing
+/// `delegate`, which signs, is credited and pays for the stats PDA if it
+/// doesn't exist yet, `hook_program` is only passed while the config has one,
+/// `treasury` only while it charges a withdrawal fee, at the rate the vault's
+/// owner pays, and `oracle` only for a price-gated vault. The owner doesn't
+/// sign.
+pub struct DelegateWithdrawAccounts<'a> {
+    pub delegate: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    pub stats: &'a AccountInfo,
+    /// The config's hook program, told about the withdrawal once it lands
+    pub hook: Option<&'a AccountInfo>,
+    /// The config's withdrawal fee, while it charges one
+    pub fee: Option<WithdrawFee<'a>>,
+    pub rent_sysvar: Option<&'a AccountInfo>,
+    pub stats_bump: u8,
+    /// The signing delegate's per-withdrawal cap
+    pub max_per_withdrawal: u64,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for DelegateWithdrawAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [delegate, vault, system_program, stats, trailing @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        if !delegate.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        if delegate.key().eq(vault.key()) {
+            return Err(VaultError::InvalidAccountArrangement.into());
+        }
+
+        if system_program.key().ne(&pinocchio_system::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let (owner, max_per_withdrawal) = {
+            let state = VaultState::load(vault)?;
+            let cap = state
+                .delegate_cap(delegate.key())
+                .ok_or(VaultError::NotDelegate)?;
+            (*state.owner(), cap)
+        };
+
+        // A delegate stands in for the owner, so it can't skip what the owner can't
+        check_not_escrowed(vault)?;
+        check_not_multisig(vault)?;
+        check_not_vesting(vault)?;
+        let (hook, fee, trailing) = check_withdraw_config(&owner, trailing)?;
+        let rent_sysvar = check_price_gate(vault, trailing)?;
+
+        let stats_bump = check_stats_account(stats)?;
+
+        Ok(Self {
+            delegate,
+            vault,
+            stats,
+            hook,
+            fee,
+            rent_sysvar: rent_sysvar.first(),
+            stats_bump,
+            max_per_withdrawal,
+        })
+    }
+}
+
+/// Instruction data structure for the DelegateWithdraw instruction
+///
+/// Layout: `[amount: u64]`, the lamports to withdraw.
+pub struct DelegateWithdrawInstructionData {
+    pub amount: u64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for DelegateWithdrawInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let amount = data
+            .try_into()
+            .map(u64::from_le_bytes)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        Ok(Self { amount })
+    }
+}
+
+/// DelegateWithdraw instruction - withdraws from an opened vault to one of its
+/// delegates
+///
+/// Identifies the delegate by its signature, failing with `NotDelegate` for a
+/// signer the vault doesn't list and `DelegateCapExceeded` for an amount above
+/// that delegate's cap. The vault's freeze, locks, price gate and withdrawal
+/// limits still apply, and the rent floor stays in the vault. The withdrawal
+/// is recorded in the global stats like an owner's.
+pub struct DelegateWithdraw<'a> {
+    pub accounts: DelegateWithdrawAccounts<'a>,
+    pub instruction_data: DelegateWithdrawInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for DelegateWithdraw<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = DelegateWithdrawInstructionData::try_from(data)?;
+        let accounts = DelegateWithdrawAccounts::try_from(accounts)?;
+
+        if instruction_data.amount > accounts.max_per_withdrawal {
+            return Err(VaultError::DelegateCapExceeded.into());
+        }
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> DelegateWithdraw<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &59;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let available = releasable(self.accounts.vault, self.accounts.rent_sysvar, false)?;
+        let (lamports, _) = compute_withdraw_amount(available, self.instruction_data.amount, 0)?;
+
+        // Opened vaults are debited directly, so no signer seeds are needed
+        let received = pay_out(
+            self.accounts.vault,
+            self.accounts.delegate,
+            self.accounts.delegate,
+            self.accounts.fee.as_ref(),
+            lamports,
+            &[],
+        )?;
+
+        load_stats(
+            self.accounts.delegate,
+            self.accounts.stats,
+            self.accounts.stats_bump,
+            self.accounts.rent_sysvar,
+        )?
+        .record_withdrawal(lamports, 0);
+
+        log!(
+            "DelegateWithdraw: sent {} lamports to the delegate",
+            received
+        );
+        pinocchio::pubkey::log(self.accounts.delegate.key());
+
+        if let Some(hook) = self.accounts.hook {
+            invoke_hook(hook, HOOK_WITHDRAW, self.accounts.delegate, lamports)?;
+        }
+
+        Ok(())
+    }
+}
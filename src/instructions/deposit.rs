@@ -1,85 +1,343 @@
-use core::mem::size_of;
 use pinocchio::{
-    account_info::AccountInfo,
-    program_error::ProgramError,
-    pubkey::find_program_address,
-    ProgramResult,
+    account_info::AccountInfo, cpi::set_return_data, instruction::Signer,
+    program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+use pinocchio_log::log;
+
+use crate::config::{
+    check_config_account, check_deposit_cap, fee_rounding, is_paused, referral_bps, rent_subsidy,
+    reuse_cooldown_secs,
+};
+use crate::error::VaultError;
+use crate::events::log_deposit;
+use crate::hook::{check_hook_program, invoke_hook, HOOK_DEPOSIT};
+use crate::logic::bps_of_rounded;
+use crate::parse::{array_at, fit_layout, read_array, read_u64};
+use crate::pda::{vault_signer_seeds, DEFAULT_SALT, DEFAULT_VAULT_ID};
+use crate::receipt::ReceiptAccounts;
+use crate::stats::{check_stats_account, load_stats};
+use crate::state::VaultState;
+use crate::tombstone::{check_reuse_cooldown, check_tombstone_account};
+use crate::vault::{
+    check_account_count, check_deposit_target, create_vault, is_opened, record_vault_deposit,
+    transfer_into, validate_accounts, LamportSnapshot, VaultAccounts,
 };
-use pinocchio_system::instructions::Transfer;
 
 /// Account structure for the Deposit instruction
+///
+/// Accounts: `[owner, vault, system_program, stats, config, tombstone?, referrer?,
+/// rent_subsidy?, receipt_mint?, receipt_account?, token_program?, hook_program?,
+/// rent_sysvar?]`, where `tombstone` is the vault's tombstone PDA, created or
+/// not, only passed while the config has a reuse cooldown, `referrer` only when
+/// the instruction data names one, `rent_subsidy` only for a subsidized deposit, the receipt accounts
+/// only for a deposit minting a receipt and `hook_program` only while the config
+/// has one. `config` is the config PDA, initialized or not.
 pub struct DepositAccounts<'a> {
     pub owner: &'a AccountInfo,
     pub vault: &'a AccountInfo,
+    pub stats: &'a AccountInfo,
+    pub referrer: Option<&'a AccountInfo>,
+    /// The config's rent subsidy account, which pays the rent when the deposit opens the vault
+    pub rent_subsidy: Option<&'a AccountInfo>,
+    /// The vault's receipt mint and the owner's token account the deposit mints receipts to
+    pub receipt: Option<ReceiptAccounts<'a>>,
+    /// The config's hook program, told about the deposit once it lands
+    pub hook: Option<&'a AccountInfo>,
+    pub rent_sysvar: Option<&'a AccountInfo>,
+    pub stats_bump: u8,
+    /// Cut of the amount paid to the referrer, the rest goes into the vault
+    pub referral_fee: u64,
+    /// An idempotent deposit is being retried against a vault that already holds the amount
+    pub already_funded: bool,
+    /// The deposit creates the vault as an opened vault before funding it
+    pub open: bool,
+    pub bumps: [u8; 1],
+}
+
+impl DepositAccounts<'_> {
+    /// Index of the stats PDA in the accounts
+    pub const STATS: usize = VaultAccounts::LEN;
+    /// Index of the config PDA in the accounts
+    pub const CONFIG: usize = VaultAccounts::LEN + 1;
+    /// Number of accounts every deposit takes, before the optional ones
+    pub const MIN_ACCOUNTS: usize = VaultAccounts::LEN + 2;
 }
 
-impl<'a> TryFrom<&'a [AccountInfo]> for DepositAccounts<'a> {
+impl<'a> TryFrom<(&'a [AccountInfo], &DepositInstructionData<'_>)> for DepositAccounts<'a> {
     type Error = ProgramError;
 
-    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
-        let [owner, vault, _] = accounts else {
+    fn try_from(
+        (accounts, instruction_data): (&'a [AccountInfo], &DepositInstructionData<'_>),
+    ) -> Result<Self, Self::Error> {
+        // Accounts Checks
+        check_account_count(accounts, Self::MIN_ACCOUNTS)?;
+        let (VaultAccounts { owner, vault, bump, .. }, rest) =
+            validate_accounts(accounts, &instruction_data.id, &instruction_data.salt)?;
+        let [stats, config, trailing @ ..] = rest else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
-        // Accounts Checks
-        if !owner.is_signer() {
-            return Err(ProgramError::MissingRequiredSignature);
+        // Only a plain wallet funds a deposit, not an account some program owns
+        if !owner.is_owned_by(&pinocchio_system::ID) {
+            return Err(VaultError::InvalidOwnerAccount.into());
+        }
+
+        check_config_account(config)?;
+
+        if is_paused(config)? {
+            return Err(VaultError::ProgramPaused.into());
         }
 
-        if !vault.is_owned_by(&pinocchio_system::ID) {
-            return Err(ProgramError::InvalidAccountOwner);
+        // Guards against fat-fingered amounts, referral fee included
+        check_deposit_cap(config, instruction_data.amount)?;
+
+        // A vault closed with a tombstone can't be funded again until the cooldown is over
+        let trailing = if reuse_cooldown_secs(config)?.ne(&0) {
+            let [tombstone, trailing @ ..] = trailing else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+
+            check_tombstone_account(vault, tombstone)?;
+            if vault.lamports().eq(&0) {
+                check_reuse_cooldown(config, tombstone)?;
+            }
+
+            trailing
+        } else {
+            trailing
+        };
+
+        let (referrer, referral_fee, trailing) = match &instruction_data.referrer {
+            None => (None, 0, trailing),
+            Some(referrer_key) => {
+                let [referrer, rent_sysvar @ ..] = trailing else {
+                    return Err(ProgramError::NotEnoughAccountKeys);
+                };
+
+                if referrer.key().ne(referrer_key) || referrer.key().eq(vault.key()) {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+
+                let fee = bps_of_rounded(
+                    instruction_data.amount,
+                    referral_bps(config)?,
+                    fee_rounding(config)?,
+                );
+                (Some(referrer), fee, rent_sysvar)
+            }
+        };
+
+        let (rent_subsidy, trailing) = if instruction_data.subsidized {
+            let [subsidy, trailing @ ..] = trailing else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+
+            let subsidy_key = rent_subsidy(config)?;
+            if subsidy_key.eq(&[0u8; 32]) || subsidy.key().ne(&subsidy_key) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            if !subsidy.is_signer() {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+
+            (Some(subsidy), trailing)
+        } else {
+            (None, trailing)
+        };
+
+        let (receipt, trailing) = if instruction_data.receipt {
+            let [mint, token_account, token_program, trailing @ ..] = trailing else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+
+            let receipt =
+                ReceiptAccounts::check(mint, token_account, token_program, owner, vault)?;
+            (Some(receipt), trailing)
+        } else {
+            (None, trailing)
+        };
+
+        let (hook, rent_sysvar) = check_hook_program(config, trailing)?;
+
+        // The whole deposit can't go to the referrer
+        if referral_fee.eq(&instruction_data.amount) {
+            return Err(ProgramError::InvalidInstructionData);
         }
 
-        if vault.lamports().ne(&0) {
-            return Err(ProgramError::InvalidAccountData);
+        // Opened vaults also hold their rent floor, so compare their tracked balance
+        let deposited = if is_opened(vault) {
+            VaultState::load(vault)?.balance()
+        } else {
+            vault.lamports()
+        };
+        let already_funded =
+            instruction_data.idempotent && deposited.eq(&(instruction_data.amount - referral_fee));
+
+        check_deposit_target(vault, rent_sysvar.first(), already_funded)?;
+
+        // Only the first deposit into a vault can open it
+        let open = instruction_data.open && !is_opened(vault) && vault.lamports().eq(&0);
+
+        // Only opened vaults have state to track a vesting schedule in
+        if instruction_data.vesting_duration.ne(&0) && !is_opened(vault) && !open {
+            return Err(ProgramError::InvalidInstructionData);
         }
 
-        let (vault_key, _) = find_program_address(&[b"vault", owner.key()], &crate::ID);
-        if vault.key().ne(&vault_key) {
-            return Err(ProgramError::InvalidAccountOwner);
+        // Nor can any other vault record the receipts it has outstanding
+        if receipt.is_some() && !is_opened(vault) && !open {
+            return Err(ProgramError::InvalidInstructionData);
         }
 
+        let stats_bump = check_stats_account(stats)?;
+
         // Return the accounts
-        Ok(Self { owner, vault })
+        Ok(Self {
+            owner,
+            vault,
+            stats,
+            referrer,
+            rent_subsidy,
+            receipt,
+            hook,
+            rent_sysvar: rent_sysvar.first(),
+            stats_bump,
+            referral_fee,
+            already_funded,
+            open,
+            bumps: [bump],
+        })
     }
 }
 
 /// Instruction data structure for the Deposit instruction
-pub struct DepositInstructionData {
+///
+/// Layout: `[amount: u64][id: u64][salt: [u8; 32]][flags: u8][vesting_duration: u64][referrer: Pubkey][memo: [u8]]`.
+/// Trailing fields are optional, but each one requires the fields before it:
+/// `id` defaults to the owner's default vault, `salt` to the zero (unsalted)
+/// salt, `flags` to `0`, `vesting_duration` to `0`, `referrer` to none, which
+/// the zero pubkey also stands for, and `memo` to an empty memo.
+/// With `FLAG_IDEMPOTENT` a vault already holding exactly what the deposit puts
+/// in is treated as a successful retry instead of a non-empty vault. With
+/// `FLAG_OPEN` the first deposit into an empty vault creates it as an opened
+/// vault, program-owned with a `VaultState`, at the owner's expense. A non-zero
+/// `vesting_duration` (in seconds) starts a vesting schedule on an opened vault
+/// that doesn't have one yet. A `referrer` is paid the config's `referral_bps`
+/// share of `amount`; without one no fee is taken. `memo` is the rest of the
+/// data, up to `MAX_MEMO_LEN` bytes of UTF-8 logged as `vault:memo <memo>` for
+/// reconciliation and never stored. An `amount` above the config's non-zero
+/// `max_deposit_per_tx` fails with `DepositTooLarge`. With `FLAG_SUBSIDIZED`,
+/// which requires `FLAG_OPEN`, the config's `rent_subsidy` account signs and
+/// pays the opened vault's rent instead of the owner. With `FLAG_RECEIPT` the
+/// vault PDA mints the owner one receipt token, of the mint passed in, per
+/// lamport the vault receives; only an opened vault, or one the deposit opens,
+/// takes receipts, and always of the mint its first receipts came from. Short of a memo, bytes
+/// past the longest layout the data holds are ignored unless the program is
+/// built with `strict-parsing`.
+pub struct DepositInstructionData<'a> {
     pub amount: u64,
+    pub id: [u8; 8],
+    pub salt: [u8; 32],
+    pub idempotent: bool,
+    pub open: bool,
+    pub subsidized: bool,
+    pub receipt: bool,
+    pub vesting_duration: u64,
+    pub referrer: Option<Pubkey>,
+    pub memo: &'a str,
 }
 
-impl<'a> TryFrom<&'a [u8]> for DepositInstructionData {
+impl DepositInstructionData<'_> {
+    /// Longest memo a deposit can carry, in bytes
+    pub const MAX_MEMO_LEN: usize = 64;
+
+    /// A vault already holding the amount is a successful retry
+    pub const FLAG_IDEMPOTENT: u8 = 1 << 0;
+
+    /// An empty vault is opened before it is funded
+    pub const FLAG_OPEN: u8 = 1 << 1;
+
+    /// The config's rent subsidy account pays the rent of the vault the deposit opens
+    pub const FLAG_SUBSIDIZED: u8 = 1 << 2;
+
+    /// The vault mints the owner a receipt token per lamport deposited
+    pub const FLAG_RECEIPT: u8 = 1 << 3;
+}
+
+impl<'a> TryFrom<&'a [u8]> for DepositInstructionData<'a> {
     type Error = ProgramError;
 
     fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
-        if data.len() != size_of::<u64>() {
+        // Everything after the referrer is the memo, so only shorter data has trailing bytes
+        let data = match data.len() {
+            ..89 => fit_layout(data, &[8, 16, 48, 49, 57]),
+            _ => data,
+        };
+
+        let (id, salt) = match data.len() {
+            8 => (DEFAULT_VAULT_ID, DEFAULT_SALT),
+            16 => (read_array(data, 8)?, DEFAULT_SALT),
+            48 | 49 | 57 | 89.. => (read_array(data, 8)?, read_array(data, 16)?),
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        let flags = data.get(48).copied().unwrap_or(0);
+        let known =
+            Self::FLAG_IDEMPOTENT | Self::FLAG_OPEN | Self::FLAG_SUBSIDIZED | Self::FLAG_RECEIPT;
+        if flags & !known != 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        // Only opening a vault costs rent to subsidize
+        let subsidized = flags & Self::FLAG_SUBSIDIZED != 0;
+        if subsidized && flags & Self::FLAG_OPEN == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let vesting_duration = array_at(data, 49).map_or(0, u64::from_le_bytes);
+
+        let referrer = array_at(data, 57).filter(|referrer: &Pubkey| referrer.ne(&[0u8; 32]));
+
+        let memo = data.get(89..).unwrap_or(&[]);
+        if memo.len() > Self::MAX_MEMO_LEN {
             return Err(ProgramError::InvalidInstructionData);
         }
+        let memo = core::str::from_utf8(memo).map_err(|_| ProgramError::InvalidInstructionData)?;
 
-        let amount = u64::from_le_bytes(data.try_into().unwrap());
+        let amount = read_u64(data, 0)?;
 
         // Instruction Checks
         if amount.eq(&0) {
             return Err(ProgramError::InvalidInstructionData);
         }
 
-        Ok(Self { amount })
+        Ok(Self {
+            amount,
+            id,
+            salt,
+            idempotent: flags & Self::FLAG_IDEMPOTENT != 0,
+            open: flags & Self::FLAG_OPEN != 0,
+            subsidized,
+            receipt: flags & Self::FLAG_RECEIPT != 0,
+            vesting_duration,
+            referrer,
+            memo,
+        })
     }
 }
 
 /// Deposit instruction - transfers lamports from owner to vault PDA
 pub struct Deposit<'a> {
     pub accounts: DepositAccounts<'a>,
-    pub instruction_data: DepositInstructionData,
+    pub instruction_data: DepositInstructionData<'a>,
 }
 
 impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for Deposit<'a> {
     type Error = ProgramError;
 
     fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
-        let accounts = DepositAccounts::try_from(accounts)?;
         let instruction_data = DepositInstructionData::try_from(data)?;
+        let accounts = DepositAccounts::try_from((accounts, &instruction_data))?;
 
         Ok(Self {
             accounts,
@@ -92,13 +350,92 @@ impl<'a> Deposit<'a> {
     pub const DISCRIMINATOR: &'a u8 = &0;
 
     pub fn process(&mut self) -> ProgramResult {
-        Transfer {
-            from: self.accounts.owner,
-            to: self.accounts.vault,
-            lamports: self.instruction_data.amount,
+        // The earlier attempt already landed, so don't transfer twice
+        if !self.accounts.already_funded {
+            let snapshot = LamportSnapshot::take(self.conserved_accounts());
+            let created = self.accounts.vault.lamports().eq(&0);
+            let lamports = self
+                .instruction_data
+                .amount
+                .checked_sub(self.accounts.referral_fee)
+                .ok_or(VaultError::InvariantViolated)?;
+
+            let rent_floor = if self.accounts.open {
+                create_vault(
+                    self.accounts.rent_subsidy.unwrap_or(self.accounts.owner),
+                    self.accounts.owner,
+                    self.accounts.vault,
+                    &self.instruction_data.id,
+                    &self.instruction_data.salt,
+                    self.accounts.bumps[0],
+                    0,
+                    self.accounts.rent_sysvar,
+                )?
+            } else {
+                0
+            };
+
+            if let Some(referrer) = self.accounts.referrer {
+                if self.accounts.referral_fee.ne(&0) {
+                    transfer_into(self.accounts.owner, referrer, self.accounts.referral_fee)?;
+                }
+            }
+
+            transfer_into(self.accounts.owner, self.accounts.vault, lamports)?;
+
+            if let Some(receipt) = &self.accounts.receipt {
+                let seeds = vault_signer_seeds(
+                    self.accounts.owner.key(),
+                    &self.instruction_data.id,
+                    &self.instruction_data.salt,
+                    &self.accounts.bumps,
+                );
+                receipt.mint(self.accounts.vault, lamports, &[Signer::from(&seeds)])?;
+            }
+
+            record_vault_deposit(
+                self.accounts.vault,
+                self.accounts.owner,
+                lamports,
+                self.instruction_data.vesting_duration,
+                self.accounts.rent_sysvar,
+            )?;
+
+            let mut stats = load_stats(
+                self.accounts.owner,
+                self.accounts.stats,
+                self.accounts.stats_bump,
+                self.accounts.rent_sysvar,
+            )?;
+            if self.accounts.open {
+                stats.record_open(rent_floor);
+            }
+            stats.record_deposit(lamports, created && !self.accounts.open);
+
+            snapshot.check_conserved(self.conserved_accounts())?;
+
+            log_deposit(self.accounts.owner.key(), lamports);
+
+            if let Some(hook) = self.accounts.hook {
+                invoke_hook(hook, HOOK_DEPOSIT, self.accounts.owner, lamports)?;
+            }
         }
-        .invoke()?;
+
+        if !self.instruction_data.memo.is_empty() {
+            log!("vault:memo {}", self.instruction_data.memo);
+        }
+
+        // Report the new vault balance so clients don't need a follow-up fetch
+        set_return_data(&self.accounts.vault.lamports().to_le_bytes());
 
         Ok(())
     }
+
+    /// Every account the deposit moves lamports between, the stats PDA's rent included
+    fn conserved_accounts(&self) -> impl Iterator<Item = &AccountInfo> {
+        [self.accounts.owner, self.accounts.vault, self.accounts.stats]
+            .into_iter()
+            .chain(self.accounts.referrer)
+            .chain(self.accounts.rent_subsidy)
+    }
 }
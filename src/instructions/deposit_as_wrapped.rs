@@ -0,0 +1,201 @@
+use pinocchio::{
+    account_info::AccountInfo, cpi::set_return_data, program_error::ProgramError, ProgramResult,
+};
+use pinocchio_system::instructions::Transfer;
+
+use crate::error::VaultError;
+use crate::pda::{DEFAULT_SALT, DEFAULT_VAULT_ID};
+use crate::state::VaultState;
+use crate::token::{
+    check_token_account, check_wrapped_sol_account, find_associated_token_address,
+    CreateAssociatedTokenAccountIdempotent, SyncNative, ASSOCIATED_TOKEN_PROGRAM_ID, NATIVE_MINT,
+    TOKEN_PROGRAM_ID,
+};
+use crate::vault::{check_vault_address, is_opened};
+
+/// Account structure for the DepositAsWrapped instruction
+///
+/// Accounts: `[owner, vault, vault_wsol, native_mint, system_program, token_program,
+/// associated_token_program]`, where `vault_wsol` is the vault's associated
+/// wrapped-SOL token account, created if it doesn't exist yet. An opened
+/// `vault` is writable, since it tracks the wrapped tokens.
+pub struct DepositAsWrappedAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    pub vault_wsol: &'a AccountInfo,
+    pub native_mint: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &DepositAsWrappedInstructionData)>
+    for DepositAsWrappedAccounts<'a>
+{
+    type Error = ProgramError;
+
+    fn try_from(
+        (accounts, instruction_data): (&'a [AccountInfo], &DepositAsWrappedInstructionData),
+    ) -> Result<Self, Self::Error> {
+        let [owner, vault, vault_wsol, native_mint, system_program, token_program, ata_program, ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        if !owner.is_signer() {
+            return Err(VaultError::MissingOwnerSignature.into());
+        }
+
+        if system_program.key().ne(&pinocchio_system::ID)
+            || token_program.key().ne(&TOKEN_PROGRAM_ID)
+            || ata_program.key().ne(&ASSOCIATED_TOKEN_PROGRAM_ID)
+        {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        if native_mint.key().ne(&NATIVE_MINT) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        check_vault_address(owner, vault, &instruction_data.id, &instruction_data.salt)?;
+
+        if is_opened(vault) {
+            let state = VaultState::load(vault)?;
+            if state.is_frozen() {
+                return Err(VaultError::VaultFrozen.into());
+            }
+
+            if state.deposits_locked() {
+                return Err(VaultError::DepositsLocked.into());
+            }
+        }
+
+        let (vault_wsol_key, _) = find_associated_token_address(vault.key(), &NATIVE_MINT);
+        if vault_wsol.key().ne(&vault_wsol_key) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        // Once created, the account has to still be the vault's native account
+        if !vault_wsol.is_owned_by(&pinocchio_system::ID) {
+            check_wrapped_sol_account(vault_wsol, vault.key())?;
+        }
+
+        Ok(Self {
+            owner,
+            vault,
+            vault_wsol,
+            native_mint,
+            system_program,
+            token_program,
+        })
+    }
+}
+
+/// Instruction data structure for the DepositAsWrapped instruction
+///
+/// Layout: `[amount: u64][id: u64][salt: [u8; 32]]`. `id` and `salt` are
+/// optional, but `salt` requires `id`; they default to the owner's default
+/// unsalted vault.
+pub struct DepositAsWrappedInstructionData {
+    pub amount: u64,
+    pub id: [u8; 8],
+    pub salt: [u8; 32],
+}
+
+impl<'a> TryFrom<&'a [u8]> for DepositAsWrappedInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let (id, salt) = match data.len() {
+            8 => (DEFAULT_VAULT_ID, DEFAULT_SALT),
+            16 => (data[8..16].try_into().unwrap(), DEFAULT_SALT),
+            48 => (
+                data[8..16].try_into().unwrap(),
+                data[16..48].try_into().unwrap(),
+            ),
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        let amount = u64::from_le_bytes(data[..8].try_into().unwrap());
+
+        // Instruction Checks
+        if amount.eq(&0) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { amount, id, salt })
+    }
+}
+
+/// DepositAsWrapped instruction - wraps the owner's SOL into the vault's wSOL account
+///
+/// The inverse of DepositWrappedSol: the vault ends up holding `amount` more
+/// wSOL tokens rather than bare lamports, for token-centric integrations. The
+/// owner pays the rent of the vault's associated wSOL account the first time.
+/// An opened vault tracks the wrapped tokens like a DepositToken of the native
+/// mint, so they only leave through WithdrawToken, and like lamport deposits it
+/// fails with `VaultFrozen` or `DepositsLocked` while either applies. The
+/// account's new token balance is set as return data.
+pub struct DepositAsWrapped<'a> {
+    pub accounts: DepositAsWrappedAccounts<'a>,
+    pub instruction_data: DepositAsWrappedInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for DepositAsWrapped<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = DepositAsWrappedInstructionData::try_from(data)?;
+        let accounts = DepositAsWrappedAccounts::try_from((accounts, &instruction_data))?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> DepositAsWrapped<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &44;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let vault_wsol = self.accounts.vault_wsol;
+
+        // Recorded first, so a vault out of mint entries fails before any SOL moves
+        if is_opened(self.accounts.vault) {
+            VaultState::load_mut(self.accounts.vault)?
+                .credit_token(&NATIVE_MINT, self.instruction_data.amount)?;
+        }
+
+        CreateAssociatedTokenAccountIdempotent {
+            funding_account: self.accounts.owner,
+            account: vault_wsol,
+            wallet: self.accounts.vault,
+            mint: self.accounts.native_mint,
+            system_program: self.accounts.system_program,
+            token_program: self.accounts.token_program,
+        }
+        .invoke()?;
+
+        Transfer {
+            from: self.accounts.owner,
+            to: vault_wsol,
+            lamports: self.instruction_data.amount,
+        }
+        .invoke()?;
+
+        // Native accounts only count lamports as tokens once synced
+        SyncNative {
+            account: vault_wsol,
+        }
+        .invoke()?;
+
+        let balance = check_token_account(vault_wsol, self.accounts.vault.key())?;
+
+        // Report the new wSOL balance so clients don't need a follow-up fetch
+        set_return_data(&balance.to_le_bytes());
+
+        Ok(())
+    }
+}
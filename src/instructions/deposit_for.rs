@@ -0,0 +1,160 @@
+use pinocchio::{
+    account_info::AccountInfo, cpi::set_return_data, program_error::ProgramError, ProgramResult,
+};
+
+use crate::error::VaultError;
+use crate::pda::{DEFAULT_SALT, DEFAULT_VAULT_ID};
+use crate::stats::{check_stats_account, load_stats};
+use crate::vault::{check_deposit_vault, record_vault_deposit, transfer_into, LamportSnapshot};
+
+/// Account structure for the DepositFor instruction
+///
+/// Accounts: `[funder, vault, owner, system_program, stats, rent_sysvar?]`, where
+/// `vault` is `owner`'s vault and `funder` pays for the deposit. `owner` doesn't
+/// sign.
+pub struct DepositForAccounts<'a> {
+    pub funder: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    pub stats: &'a AccountInfo,
+    pub rent_sysvar: Option<&'a AccountInfo>,
+    pub stats_bump: u8,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &DepositForInstructionData)> for DepositForAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(
+        (accounts, instruction_data): (&'a [AccountInfo], &DepositForInstructionData),
+    ) -> Result<Self, Self::Error> {
+        let [funder, vault, owner, system_program, stats, rent_sysvar @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        if !funder.is_signer() {
+            return Err(VaultError::MissingOwnerSignature.into());
+        }
+
+        if system_program.key().ne(&pinocchio_system::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        // The vault derives from the owner, so only the owner can withdraw what the funder puts in
+        check_deposit_vault(
+            owner,
+            vault,
+            &instruction_data.id,
+            &instruction_data.salt,
+            rent_sysvar.first(),
+            false,
+        )?;
+
+        let stats_bump = check_stats_account(stats)?;
+
+        Ok(Self {
+            funder,
+            vault,
+            stats,
+            rent_sysvar: rent_sysvar.first(),
+            stats_bump,
+        })
+    }
+}
+
+/// Instruction data structure for the DepositFor instruction
+///
+/// Layout: `[amount: u64][id: u64][salt: [u8; 32]]`. `id` and `salt` are
+/// optional, but `salt` requires `id`; they default to the owner's default
+/// unsalted vault.
+pub struct DepositForInstructionData {
+    pub amount: u64,
+    pub id: [u8; 8],
+    pub salt: [u8; 32],
+}
+
+impl<'a> TryFrom<&'a [u8]> for DepositForInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let (id, salt) = match data.len() {
+            8 => (DEFAULT_VAULT_ID, DEFAULT_SALT),
+            16 => (data[8..16].try_into().unwrap(), DEFAULT_SALT),
+            48 => (
+                data[8..16].try_into().unwrap(),
+                data[16..48].try_into().unwrap(),
+            ),
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        let amount = u64::from_le_bytes(data[..8].try_into().unwrap());
+
+        // Instruction Checks
+        if amount.eq(&0) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { amount, id, salt })
+    }
+}
+
+/// DepositFor instruction - transfers lamports from a third-party funder into
+/// someone else's vault
+pub struct DepositFor<'a> {
+    pub accounts: DepositForAccounts<'a>,
+    pub instruction_data: DepositForInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for DepositFor<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = DepositForInstructionData::try_from(data)?;
+        let accounts = DepositForAccounts::try_from((accounts, &instruction_data))?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> DepositFor<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &23;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let conserved = || {
+            [
+                self.accounts.funder,
+                self.accounts.vault,
+                self.accounts.stats,
+            ]
+        };
+        let snapshot = LamportSnapshot::take(conserved());
+        let created = self.accounts.vault.lamports().eq(&0);
+        let lamports = self.instruction_data.amount;
+
+        transfer_into(self.accounts.funder, self.accounts.vault, lamports)?;
+        record_vault_deposit(
+            self.accounts.vault,
+            self.accounts.funder,
+            lamports,
+            0,
+            self.accounts.rent_sysvar,
+        )?;
+
+        load_stats(
+            self.accounts.funder,
+            self.accounts.stats,
+            self.accounts.stats_bump,
+            self.accounts.rent_sysvar,
+        )?
+        .record_deposit(lamports, created);
+
+        snapshot.check_conserved(conserved())?;
+
+        // Report the new vault balance so clients don't need a follow-up fetch
+        set_return_data(&self.accounts.vault.lamports().to_le_bytes());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,174 @@
+use pinocchio::{
+    account_info::AccountInfo, cpi::set_return_data, program_error::ProgramError, ProgramResult,
+};
+
+use crate::config::{check_config_account, check_deposit_cap};
+use crate::error::VaultError;
+use crate::pda::{DEFAULT_SALT, DEFAULT_VAULT_ID};
+use crate::state::VaultState;
+use crate::stats::{check_stats_account, load_stats};
+use crate::sysvar::clock;
+use crate::vault::{
+    check_deposit_target, record_vault_deposit, transfer_into, validate_accounts, LamportSnapshot,
+    VaultAccounts,
+};
+
+/// Account structure for the DepositLocked instruction
+///
+/// Accounts: `[owner, vault, system_program, stats, config, rent_sysvar?]`,
+/// where `vault` is `owner`'s opened vault and `config` the config PDA,
+/// initialized or not.
+pub struct DepositLockedAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    pub stats: &'a AccountInfo,
+    pub rent_sysvar: Option<&'a AccountInfo>,
+    pub stats_bump: u8,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &DepositLockedInstructionData)> for DepositLockedAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(
+        (accounts, instruction_data): (&'a [AccountInfo], &DepositLockedInstructionData),
+    ) -> Result<Self, Self::Error> {
+        // Accounts Checks
+        let (VaultAccounts { owner, vault, .. }, rest) =
+            validate_accounts(accounts, &instruction_data.id, &instruction_data.salt)?;
+        let [stats, config, rent_sysvar @ ..] = rest else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Only opened vaults have state to record the lock in
+        VaultState::load(vault)?;
+
+        check_deposit_target(vault, rent_sysvar.first(), false)?;
+
+        check_config_account(config)?;
+        check_deposit_cap(config, instruction_data.amount)?;
+
+        let stats_bump = check_stats_account(stats)?;
+
+        Ok(Self {
+            owner,
+            vault,
+            stats,
+            rent_sysvar: rent_sysvar.first(),
+            stats_bump,
+        })
+    }
+}
+
+/// Instruction data structure for the DepositLocked instruction
+///
+/// Layout: `[amount: u64][unlock_ts: i64][id: u64][salt: [u8; 32]]`, with
+/// `unlock_ts` a unix timestamp. `id` and `salt` are optional, but `salt`
+/// requires `id`; they default to the owner's default unsalted vault.
+pub struct DepositLockedInstructionData {
+    pub amount: u64,
+    pub unlock_ts: i64,
+    pub id: [u8; 8],
+    pub salt: [u8; 32],
+}
+
+impl<'a> TryFrom<&'a [u8]> for DepositLockedInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let (id, salt) = match data.len() {
+            16 => (DEFAULT_VAULT_ID, DEFAULT_SALT),
+            24 => (data[16..24].try_into().unwrap(), DEFAULT_SALT),
+            56 => (
+                data[16..24].try_into().unwrap(),
+                data[24..56].try_into().unwrap(),
+            ),
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        let amount = u64::from_le_bytes(data[..8].try_into().unwrap());
+        let unlock_ts = i64::from_le_bytes(data[8..16].try_into().unwrap());
+
+        // Instruction Checks
+        if amount.eq(&0) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            amount,
+            unlock_ts,
+            id,
+            salt,
+        })
+    }
+}
+
+/// DepositLocked instruction - deposits into an opened vault and time-locks it
+/// until `unlock_ts` in one step
+///
+/// There's no window where the deposit sits in the vault unlocked. An
+/// `unlock_ts` that isn't in the future fails with `UnlockInPast`, and like
+/// ExtendLock one that isn't strictly later than the current unlock fails with
+/// `CannotShortenLock`.
+pub struct DepositLocked<'a> {
+    pub accounts: DepositLockedAccounts<'a>,
+    pub instruction_data: DepositLockedInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for DepositLocked<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = DepositLockedInstructionData::try_from(data)?;
+        let accounts = DepositLockedAccounts::try_from((accounts, &instruction_data))?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> DepositLocked<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &30;
+
+    pub fn process(&mut self) -> ProgramResult {
+        if self.instruction_data.unlock_ts <= clock()?.unix_timestamp {
+            return Err(VaultError::UnlockInPast.into());
+        }
+
+        let conserved = || {
+            [
+                self.accounts.owner,
+                self.accounts.vault,
+                self.accounts.stats,
+            ]
+        };
+        let snapshot = LamportSnapshot::take(conserved());
+        let lamports = self.instruction_data.amount;
+
+        transfer_into(self.accounts.owner, self.accounts.vault, lamports)?;
+        record_vault_deposit(
+            self.accounts.vault,
+            self.accounts.owner,
+            lamports,
+            0,
+            self.accounts.rent_sysvar,
+        )?;
+        VaultState::load_mut(self.accounts.vault)?.extend_lock(self.instruction_data.unlock_ts)?;
+
+        load_stats(
+            self.accounts.owner,
+            self.accounts.stats,
+            self.accounts.stats_bump,
+            self.accounts.rent_sysvar,
+        )?
+        .record_deposit(lamports, false);
+
+        snapshot.check_conserved(conserved())?;
+
+        // Report the new vault balance so clients don't need a follow-up fetch
+        set_return_data(&self.accounts.vault.lamports().to_le_bytes());
+
+        Ok(())
+    }
+}
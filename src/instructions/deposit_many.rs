@@ -0,0 +1,158 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+use pinocchio_log::log;
+use pinocchio_system::instructions::Transfer;
+
+use crate::error::VaultError;
+use crate::pda::{DEFAULT_SALT, DEFAULT_VAULT_ID};
+use crate::stats::{check_stats_account, load_stats};
+use crate::vault::{
+    check_batch_size, check_deposit_vault, check_distinct_vaults, record_vault_deposit,
+};
+
+/// Account structure for the DepositMany instruction
+///
+/// Accounts: `[payer, (vault, owner) @ .., system_program, stats]`, with one
+/// vault and owner pair per funded vault, up to `MAX_BATCH_VAULTS` pairs.
+/// Owners don't need to sign, and each vault can only be passed once.
+pub struct DepositManyAccounts<'a> {
+    pub payer: &'a AccountInfo,
+    pub pairs: &'a [AccountInfo],
+    pub stats: &'a AccountInfo,
+    pub stats_bump: u8,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for DepositManyAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [payer, pairs @ .., system_program, stats] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if pairs.is_empty() || !pairs.len().is_multiple_of(2) {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        check_batch_size(pairs.len() / 2)?;
+        check_distinct_vaults(pairs.iter().step_by(2))?;
+
+        // Basic Accounts Checks
+        if !payer.is_signer() {
+            return Err(VaultError::MissingOwnerSignature.into());
+        }
+
+        if system_program.key() != &pinocchio_system::ID {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let stats_bump = check_stats_account(stats)?;
+
+        Ok(Self {
+            payer,
+            pairs,
+            stats,
+            stats_bump,
+        })
+    }
+}
+
+/// Instruction data structure for the DepositMany instruction
+///
+/// Layout: `[count: u8]` followed by `[amount: u64]` once per vault, in the
+/// same order as the accounts. Only default unsalted vaults can be funded, and
+/// every amount must be non-zero.
+pub struct DepositManyInstructionData<'a> {
+    pub amounts: &'a [u8],
+}
+
+impl<'a> TryFrom<&'a [u8]> for DepositManyInstructionData<'a> {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let Some((count, amounts)) = data.split_first() else {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+
+        if count.eq(&0) || amounts.len() != *count as usize * 8 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        if amounts
+            .chunks_exact(8)
+            .any(|amount| amount.iter().all(|byte| byte.eq(&0)))
+        {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { amounts })
+    }
+}
+
+/// DepositMany instruction - funds several owners' vaults from one payer
+pub struct DepositMany<'a> {
+    pub accounts: DepositManyAccounts<'a>,
+    pub instruction_data: DepositManyInstructionData<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for DepositMany<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = DepositManyAccounts::try_from(accounts)?;
+        let instruction_data = DepositManyInstructionData::try_from(data)?;
+
+        // Every vault and owner pair needs a matching amount
+        if instruction_data.amounts.len() / 8 != accounts.pairs.len() / 2 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> DepositMany<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &13;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let payer = self.accounts.payer;
+        let mut total: u64 = 0;
+
+        for (pair, amount) in self
+            .accounts
+            .pairs
+            .chunks_exact(2)
+            .zip(self.instruction_data.amounts.chunks_exact(8))
+        {
+            let (vault, owner) = (&pair[0], &pair[1]);
+            let amount = u64::from_le_bytes(amount.try_into().unwrap());
+
+            // Accounts Checks
+            check_deposit_vault(owner, vault, &DEFAULT_VAULT_ID, &DEFAULT_SALT, None, false)?;
+
+            let created = vault.lamports().eq(&0);
+
+            Transfer {
+                from: payer,
+                to: vault,
+                lamports: amount,
+            }
+            .invoke()?;
+
+            record_vault_deposit(vault, payer, amount, 0, None)?;
+
+            load_stats(payer, self.accounts.stats, self.accounts.stats_bump, None)?
+                .record_deposit(amount, created);
+
+            total = total
+                .checked_add(amount)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+        }
+
+        log!("DepositMany: deposited {} lamports", total);
+
+        Ok(())
+    }
+}
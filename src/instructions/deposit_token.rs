@@ -0,0 +1,154 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+use pinocchio_log::log;
+
+use crate::error::VaultError;
+use crate::state::VaultState;
+use crate::token::{check_token_account, check_vault_token_account, Transfer, TOKEN_PROGRAM_ID};
+use crate::vault::check_account_arrangement;
+
+/// Account structure for the DepositToken instruction
+///
+/// Accounts: `[owner, vault, source, vault_token_account, token_program]`, where
+/// `vault` is an opened vault recording `owner`, `source` a token account of
+/// `owner` and `vault_token_account` the vault's associated token account for
+/// the deposited mint.
+pub struct DepositTokenAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    pub source: &'a AccountInfo,
+    pub vault_token_account: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &DepositTokenInstructionData)> for DepositTokenAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(
+        (accounts, instruction_data): (&'a [AccountInfo], &DepositTokenInstructionData),
+    ) -> Result<Self, Self::Error> {
+        let [owner, vault, source, vault_token_account, token_program, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        check_account_arrangement(owner, vault)?;
+
+        if !owner.is_signer() {
+            return Err(VaultError::MissingOwnerSignature.into());
+        }
+
+        if token_program.key().ne(&TOKEN_PROGRAM_ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        {
+            // Only opened vaults track token balances, and the one they record owns it
+            let state = VaultState::load(vault)?;
+            if state.owner().ne(owner.key()) {
+                return Err(ProgramError::InvalidAccountOwner);
+            }
+
+            if state.is_frozen() {
+                return Err(VaultError::VaultFrozen.into());
+            }
+
+            if state.deposits_locked() {
+                return Err(VaultError::DepositsLocked.into());
+            }
+        }
+
+        // The token program checks the source's mint and balance during the transfer
+        check_token_account(source, owner.key())?;
+        check_vault_token_account(vault_token_account, vault.key(), &instruction_data.mint)?;
+
+        Ok(Self {
+            owner,
+            vault,
+            source,
+            vault_token_account,
+        })
+    }
+}
+
+/// Instruction data structure for the DepositToken instruction
+///
+/// Layout: `[mint: Pubkey][amount: u64]`.
+pub struct DepositTokenInstructionData {
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for DepositTokenInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&40) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let mint = data[..32].try_into().unwrap();
+        let amount = u64::from_le_bytes(data[32..40].try_into().unwrap());
+
+        // Instruction Checks
+        if amount.eq(&0) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { mint, amount })
+    }
+}
+
+/// DepositToken instruction - moves SPL tokens of one mint from the owner into
+/// the opened vault, tracking them per mint
+///
+/// The vault's associated token account for the mint has to exist already. A
+/// vault tracks at most `VaultState::MAX_TOKEN_MINTS` mints at a time, and a
+/// deposit of a further mint fails with `TooManyMints`. Like lamport deposits,
+/// it fails with `VaultFrozen` or `DepositsLocked` while either applies.
+pub struct DepositToken<'a> {
+    pub accounts: DepositTokenAccounts<'a>,
+    pub instruction_data: DepositTokenInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for DepositToken<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = DepositTokenInstructionData::try_from(data)?;
+        let accounts = DepositTokenAccounts::try_from((accounts, &instruction_data))?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> DepositToken<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &54;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let amount = self.instruction_data.amount;
+
+        // Recorded first, so a vault out of mint entries fails before any tokens move
+        let tracked = {
+            let mut state = VaultState::load_mut(self.accounts.vault)?;
+            state.credit_token(&self.instruction_data.mint, amount)?;
+            state.token_balance(&self.instruction_data.mint)
+        };
+
+        Transfer {
+            from: self.accounts.source,
+            to: self.accounts.vault_token_account,
+            authority: self.accounts.owner,
+            amount,
+        }
+        .invoke_signed(&[])?;
+
+        log!("DepositToken: vault now tracks {} tokens of mint", tracked);
+        pinocchio::pubkey::log(&self.instruction_data.mint);
+
+        Ok(())
+    }
+}
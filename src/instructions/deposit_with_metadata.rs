@@ -0,0 +1,83 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::error::VaultError;
+use crate::instructions::{Deposit, DepositInstructionData};
+use crate::metadata::{check_metadata_account, load_metadata, VaultMetadata};
+
+/// DepositWithMetadata instruction - opens and funds a new vault and sets its
+/// metadata memo in one call
+///
+/// Accounts: `[metadata, ...]`, where `metadata` is the vault's `["meta",
+/// vault]` PDA and the rest are Deposit's accounts. Layout: `[memo_len: u8]
+/// [memo: [u8; memo_len]][deposit: [u8]]`, where `memo` follows SetMetadata's
+/// rules and `deposit` is Deposit's instruction data, which must set
+/// `FLAG_OPEN`. The deposit runs with all of Deposit's checks, then the owner
+/// pays for the metadata account. A vault the deposit doesn't open, because it
+/// already holds lamports or is already opened, fails with `VaultNotEmpty`.
+pub struct DepositWithMetadata<'a> {
+    pub deposit: Deposit<'a>,
+    pub metadata: &'a AccountInfo,
+    pub metadata_bump: u8,
+    pub memo: &'a [u8],
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for DepositWithMetadata<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let Some((&memo_len, data)) = data.split_first() else {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+        if data.len() < memo_len as usize {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let (memo, data) = data.split_at(memo_len as usize);
+
+        // Instruction Checks
+        VaultMetadata::check_memo(memo)?;
+
+        if !DepositInstructionData::try_from(data)?.open {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let [metadata, accounts @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        let deposit = Deposit::try_from((data, accounts))?;
+
+        // Only a vault this deposit creates starts out with the metadata
+        if !deposit.accounts.open {
+            return Err(VaultError::VaultNotEmpty.into());
+        }
+
+        let metadata_bump = check_metadata_account(deposit.accounts.vault, metadata)?;
+
+        Ok(Self {
+            deposit,
+            metadata,
+            metadata_bump,
+            memo,
+        })
+    }
+}
+
+impl<'a> DepositWithMetadata<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &71;
+
+    pub fn process(&mut self) -> ProgramResult {
+        self.deposit.process()?;
+
+        load_metadata(
+            self.deposit.accounts.owner,
+            self.deposit.accounts.vault,
+            self.metadata,
+            self.metadata_bump,
+            self.deposit.accounts.rent_sysvar,
+        )?
+        .set_memo(self.memo);
+
+        Ok(())
+    }
+}
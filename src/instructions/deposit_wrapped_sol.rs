@@ -0,0 +1,159 @@
+use pinocchio::{
+    account_info::AccountInfo, cpi::set_return_data, program_error::ProgramError, ProgramResult,
+};
+
+use crate::error::VaultError;
+use crate::pda::{DEFAULT_SALT, DEFAULT_VAULT_ID};
+use crate::stats::{check_stats_account, load_stats};
+use crate::token::{check_wrapped_sol_account, CloseAccount, TOKEN_PROGRAM_ID};
+use crate::vault::{check_deposit_vault, record_vault_deposit};
+
+/// Account structure for the DepositWrappedSol instruction
+///
+/// Accounts: `[owner, vault, system_program, stats, wsol_account, token_program, rent_sysvar?]`.
+pub struct DepositWrappedSolAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    pub stats: &'a AccountInfo,
+    pub wsol_account: &'a AccountInfo,
+    pub rent_sysvar: Option<&'a AccountInfo>,
+    pub stats_bump: u8,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &DepositWrappedSolInstructionData)>
+    for DepositWrappedSolAccounts<'a>
+{
+    type Error = ProgramError;
+
+    fn try_from(
+        (accounts, instruction_data): (&'a [AccountInfo], &DepositWrappedSolInstructionData),
+    ) -> Result<Self, Self::Error> {
+        let [owner, vault, system_program, stats, wsol_account, token_program, rent_sysvar @ ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        if !owner.is_signer() {
+            return Err(VaultError::MissingOwnerSignature.into());
+        }
+
+        if system_program.key().ne(&pinocchio_system::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        if token_program.key().ne(&TOKEN_PROGRAM_ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        check_wrapped_sol_account(wsol_account, owner.key())?;
+
+        check_deposit_vault(
+            owner,
+            vault,
+            &instruction_data.id,
+            &instruction_data.salt,
+            rent_sysvar.first(),
+            false,
+        )?;
+
+        let stats_bump = check_stats_account(stats)?;
+
+        Ok(Self {
+            owner,
+            vault,
+            stats,
+            wsol_account,
+            rent_sysvar: rent_sysvar.first(),
+            stats_bump,
+        })
+    }
+}
+
+/// Instruction data structure for the DepositWrappedSol instruction
+///
+/// Layout: `[id: u64][salt: [u8; 32]]`. Both fields are optional, but `salt`
+/// requires `id`; they default to the owner's default unsalted vault.
+pub struct DepositWrappedSolInstructionData {
+    pub id: [u8; 8],
+    pub salt: [u8; 32],
+}
+
+impl<'a> TryFrom<&'a [u8]> for DepositWrappedSolInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let (id, salt) = match data.len() {
+            0 => (DEFAULT_VAULT_ID, DEFAULT_SALT),
+            8 => (data.try_into().unwrap(), DEFAULT_SALT),
+            40 => (
+                data[..8].try_into().unwrap(),
+                data[8..40].try_into().unwrap(),
+            ),
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        Ok(Self { id, salt })
+    }
+}
+
+/// DepositWrappedSol instruction - unwraps the owner's wSOL account into the vault PDA
+///
+/// Closing a native token account moves all its lamports, so the vault receives
+/// the wrapped amount plus the token account's rent.
+pub struct DepositWrappedSol<'a> {
+    pub accounts: DepositWrappedSolAccounts<'a>,
+    pub instruction_data: DepositWrappedSolInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for DepositWrappedSol<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = DepositWrappedSolInstructionData::try_from(data)?;
+        let accounts = DepositWrappedSolAccounts::try_from((accounts, &instruction_data))?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> DepositWrappedSol<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &6;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let created = self.accounts.vault.lamports().eq(&0);
+        let lamports = self.accounts.wsol_account.lamports();
+
+        CloseAccount {
+            account: self.accounts.wsol_account,
+            destination: self.accounts.vault,
+            authority: self.accounts.owner,
+        }
+        .invoke()?;
+
+        record_vault_deposit(
+            self.accounts.vault,
+            self.accounts.owner,
+            lamports,
+            0,
+            self.accounts.rent_sysvar,
+        )?;
+
+        load_stats(
+            self.accounts.owner,
+            self.accounts.stats,
+            self.accounts.stats_bump,
+            self.accounts.rent_sysvar,
+        )?
+        .record_deposit(lamports, created);
+
+        // Report the new vault balance so clients don't need a follow-up fetch
+        set_return_data(&self.accounts.vault.lamports().to_le_bytes());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+use pinocchio::{
+    account_info::AccountInfo, cpi::set_return_data, program_error::ProgramError, pubkey::Pubkey,
+    ProgramResult,
+};
+
+use crate::pda::{find_vault_address, DEFAULT_SALT, DEFAULT_VAULT_ID};
+
+/// DeriveVault instruction - reports the canonical vault PDA of an owner
+///
+/// Takes no accounts. Layout: `[owner: Pubkey][id: u64][salt: [u8; 32]]`, with
+/// `id` and `salt` optional as in Withdraw. Always succeeds with
+/// `[vault: Pubkey][bump: u8]` as return data, so thin clients that can't run
+/// `find_program_address` themselves can simulate it instead.
+pub struct DeriveVault<'a> {
+    pub owner: &'a Pubkey,
+    pub id: [u8; 8],
+    pub salt: [u8; 32],
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for DeriveVault<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, _accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let (id, salt) = match data.len() {
+            32 => (DEFAULT_VAULT_ID, DEFAULT_SALT),
+            40 => (data[32..40].try_into().unwrap(), DEFAULT_SALT),
+            72 => (
+                data[32..40].try_into().unwrap(),
+                data[40..72].try_into().unwrap(),
+            ),
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        let owner = data[..32].try_into().unwrap();
+
+        Ok(Self { owner, id, salt })
+    }
+}
+
+impl<'a> DeriveVault<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &56;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let (vault, bump) = find_vault_address(self.owner, &self.id, &self.salt);
+
+        let mut derived = [0u8; 33];
+        derived[..32].copy_from_slice(&vault);
+        derived[32] = bump;
+        set_return_data(&derived);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::instructions::*;
+
+/// Declares [`VaultInstruction`] from one list of handlers, so unpacking and
+/// dispatching can't drift apart as instructions are added
+macro_rules! vault_instructions {
+    ($($handler:ident,)*) => {
+        /// An instruction the program understands, holding the data after its
+        /// discriminator byte
+        ///
+        /// Each variant is named after, and routes to, the handler whose
+        /// `DISCRIMINATOR` it was unpacked from, so the byte protocol is
+        /// defined by the handlers alone.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        pub enum VaultInstruction<'a> {
+            $($handler(&'a [u8]),)*
+        }
+
+        impl<'a> VaultInstruction<'a> {
+            /// Splits the discriminator off `instruction_data`, failing with
+            /// `InvalidInstructionData` for an empty or unknown one
+            pub fn unpack(instruction_data: &'a [u8]) -> Result<Self, ProgramError> {
+                match instruction_data.split_first() {
+                    $(Some(($handler::DISCRIMINATOR, data)) => Ok(Self::$handler(data)),)*
+                    _ => Err(ProgramError::InvalidInstructionData),
+                }
+            }
+
+            /// The discriminator byte the instruction was unpacked from
+            pub fn discriminator(&self) -> u8 {
+                match self {
+                    $(Self::$handler(_) => *$handler::DISCRIMINATOR,)*
+                }
+            }
+
+            /// Parses the instruction's accounts and data and runs its handler
+            pub fn dispatch(&self, accounts: &[AccountInfo]) -> ProgramResult {
+                match *self {
+                    $(Self::$handler(data) => $handler::try_from((data, accounts))?.process(),)*
+                }
+            }
+        }
+    };
+}
+
+vault_instructions! {
+    Deposit,
+    Withdraw,
+    WithdrawMany,
+    WithdrawAmount,
+    Consolidate,
+    OpenVault,
+    DepositWrappedSol,
+    VerifyIntegrity,
+    SetLabel,
+    SetOracle,
+    WithdrawVested,
+    SimulateWithdraw,
+    Initialize,
+    DepositMany,
+    SplitDeposit,
+    MergeVaults,
+    Close,
+    CloseAndSweep,
+    AddAllowedDest,
+    RemoveAllowedDest,
+    WithdrawTo,
+    ExtendLock,
+    SweepToken,
+    DepositFor,
+    WithdrawIfPriceAbove,
+    VaultTransfer,
+    SetDepositsLocked,
+    WithdrawWithApproval,
+    GetInfo,
+    MigrateVault,
+    DepositLocked,
+    SplitVault,
+    SetTokenDestOwner,
+    SetEscrowAgent,
+    FundRewardPool,
+    WithdrawAllWithTip,
+    SetMetadata,
+    SetFrozen,
+    Reconcile,
+    SetPaused,
+    EmergencyDrain,
+    ProposeAdmin,
+    AcceptAdmin,
+    WithdrawToNewAccount,
+    DepositAsWrapped,
+    GetInfoMany,
+    SetDrip,
+    Crank,
+    TagVault,
+    UpgradeToMultisig,
+    CanWithdraw,
+    SetExpiry,
+    WithdrawExpired,
+    FundGasReserve,
+    DepositToken,
+    WithdrawToken,
+    DeriveVault,
+    ExtendSlotLock,
+    SetDelegate,
+    DelegateWithdraw,
+    UpgradeVaultState,
+    SetWithdrawFee,
+    SetBeneficiary,
+    BeneficiaryClaim,
+    SetRewardRate,
+    ClaimReward,
+    CreateMultisig,
+    SetDailyLimit,
+    AdminRecover,
+    AddAllowedSource,
+    RemoveAllowedSource,
+    DepositWithMetadata,
+    ExportState,
+}
@@ -0,0 +1,201 @@
+use pinocchio::{
+    account_info::AccountInfo, instruction::Signer, program_error::ProgramError, ProgramResult,
+};
+use pinocchio_log::log;
+use pinocchio_system::instructions::Transfer;
+
+use crate::config::{check_admin, check_config_account, Config};
+use crate::error::VaultError;
+use crate::pda::{vault_signer_seeds, DEFAULT_SALT, DEFAULT_VAULT_ID};
+use crate::stats::{check_stats_account, load_stats};
+use crate::sysvar::clock;
+use crate::vault::{check_vault_address, is_opened, LamportSnapshot};
+
+/// Account structure for the EmergencyDrain instruction
+///
+/// Accounts: `[admin, config, owner, vault, recovery, system_program, stats,
+/// rent_sysvar?]`, where `vault` is `owner`'s vault, `recovery` the config's
+/// recovery address and `admin` the config's admin. Only the admin signs.
+pub struct EmergencyDrainAccounts<'a> {
+    pub admin: &'a AccountInfo,
+    pub owner: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    pub recovery: &'a AccountInfo,
+    pub stats: &'a AccountInfo,
+    pub rent_sysvar: Option<&'a AccountInfo>,
+    pub bumps: [u8; 1],
+    pub stats_bump: u8,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &EmergencyDrainInstructionData)>
+    for EmergencyDrainAccounts<'a>
+{
+    type Error = ProgramError;
+
+    fn try_from(
+        (accounts, instruction_data): (&'a [AccountInfo], &EmergencyDrainInstructionData),
+    ) -> Result<Self, Self::Error> {
+        let [admin, config, owner, vault, recovery, system_program, stats, rent_sysvar @ ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        check_config_account(config)?;
+        check_admin(config, admin)?;
+
+        {
+            let config = Config::load(config)?;
+            if !config.is_paused() {
+                return Err(VaultError::NotPaused.into());
+            }
+
+            // Owners get the whole delay to withdraw before the admin can drain
+            if !config.is_drainable(clock()?.unix_timestamp) {
+                return Err(VaultError::DrainDelayNotElapsed.into());
+            }
+
+            let recovery_address = config.recovery_address();
+            if recovery_address.eq(&[0u8; 32]) || recovery.key().ne(recovery_address) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+        }
+
+        if system_program.key().ne(&pinocchio_system::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let bump = check_vault_address(owner, vault, &instruction_data.id, &instruction_data.salt)?;
+
+        if recovery.key().eq(vault.key()) {
+            return Err(VaultError::InvalidRecipient.into());
+        }
+
+        if vault.lamports().eq(&0) {
+            return Err(VaultError::VaultEmpty.into());
+        }
+
+        let stats_bump = check_stats_account(stats)?;
+
+        Ok(Self {
+            admin,
+            owner,
+            vault,
+            recovery,
+            stats,
+            rent_sysvar: rent_sysvar.first(),
+            bumps: [bump],
+            stats_bump,
+        })
+    }
+}
+
+/// Instruction data structure for the EmergencyDrain instruction
+///
+/// Layout: `[id: u64][salt: [u8; 32]]`. Both fields are optional, but `salt`
+/// requires `id`: they default to the owner's default unsalted vault.
+pub struct EmergencyDrainInstructionData {
+    pub id: [u8; 8],
+    pub salt: [u8; 32],
+}
+
+impl<'a> TryFrom<&'a [u8]> for EmergencyDrainInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let (id, salt) = match data.len() {
+            0 => (DEFAULT_VAULT_ID, DEFAULT_SALT),
+            8 => (data.try_into().unwrap(), DEFAULT_SALT),
+            40 => (data[..8].try_into().unwrap(), data[8..].try_into().unwrap()),
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        Ok(Self { id, salt })
+    }
+}
+
+/// EmergencyDrain instruction - moves every lamport of a vault to the config's
+/// recovery address, closing it
+///
+/// Only the config's admin can drain, failing with `NotAdmin` otherwise, and
+/// only while the program is paused, failing with `NotPaused` otherwise, and
+/// only once it has been paused for `Config::EMERGENCY_DRAIN_DELAY_SECS`,
+/// failing with `DrainDelayNotElapsed` before then. The recovery address is
+/// fixed when the config is created, so the admin can't redirect a drain. The
+/// owner doesn't sign, and the drain overrides the vault's freeze, time lock,
+/// minimum age and withdrawal limits, and burns none of its receipts. Each drain logs the vault and the
+/// lamports moved.
+pub struct EmergencyDrain<'a> {
+    pub accounts: EmergencyDrainAccounts<'a>,
+    pub instruction_data: EmergencyDrainInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for EmergencyDrain<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = EmergencyDrainInstructionData::try_from(data)?;
+        let accounts = EmergencyDrainAccounts::try_from((accounts, &instruction_data))?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> EmergencyDrain<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &40;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let vault = self.accounts.vault;
+        let recovery = self.accounts.recovery;
+        let lamports = vault.lamports();
+
+        let conserved = || [self.accounts.admin, vault, recovery, self.accounts.stats];
+        let snapshot = LamportSnapshot::take(conserved());
+
+        if is_opened(vault) {
+            // Debited directly, so none of the owner's withdrawal restrictions apply
+            let credited = recovery
+                .lamports()
+                .checked_add(lamports)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            *vault.try_borrow_mut_lamports()? = 0;
+            *recovery.try_borrow_mut_lamports()? = credited;
+
+            vault.try_borrow_mut_data()?.fill(0);
+            vault.close()?;
+        } else {
+            let seeds = vault_signer_seeds(
+                self.accounts.owner.key(),
+                &self.instruction_data.id,
+                &self.instruction_data.salt,
+                &self.accounts.bumps,
+            );
+
+            Transfer {
+                from: vault,
+                to: recovery,
+                lamports,
+            }
+            .invoke_signed(&[Signer::from(&seeds)])?;
+        }
+
+        load_stats(
+            self.accounts.admin,
+            self.accounts.stats,
+            self.accounts.stats_bump,
+            self.accounts.rent_sysvar,
+        )?
+        .record_withdrawal(lamports, 1);
+
+        snapshot.check_conserved(conserved())?;
+
+        log!("EmergencyDrain: moved {} lamports of vault", lamports);
+        pinocchio::pubkey::log(vault.key());
+
+        Ok(())
+    }
+}
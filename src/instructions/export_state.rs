@@ -0,0 +1,99 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    cpi::{set_return_data, MAX_RETURN_DATA},
+    program_error::ProgramError,
+    ProgramResult,
+};
+
+use crate::metadata::{check_metadata_account, VaultMetadata};
+use crate::state::VaultState;
+
+/// Version of the export format, written to the first byte of every export
+pub const EXPORT_FORMAT: u8 = 1;
+
+/// Size of the longest export, a vault with metadata
+pub const EXPORT_MAX_LEN: usize = 1 + 2 + VaultState::LEN + 2 + VaultMetadata::LEN;
+
+/// ExportState instruction - reports an opened vault's full state, and its
+/// metadata, as return data for backups
+///
+/// Accounts: `[vault, metadata?]`, where `metadata` is the vault's `["meta",
+/// vault]` PDA, created or not, and both are only read. The export is
+/// `[format: u8][state_len: u16][state: [u8]][metadata_len: u16][metadata: [u8]]`,
+/// with the raw account data behind each length, each starting with its own
+/// layout version; `metadata_len` is `0` when no metadata is passed or none
+/// has been set. An export is longer than return data can hold, so the
+/// instruction data is `[offset: u16]`, defaulting to `0`, and the return data
+/// is the export from `offset` on, up to `MAX_RETURN_DATA` bytes. An `offset`
+/// past the end of the export fails with `InvalidInstructionData`.
+pub struct ExportState<'a> {
+    pub vault: &'a AccountInfo,
+    pub metadata: Option<&'a AccountInfo>,
+    pub offset: usize,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for ExportState<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let offset = match data.len() {
+            0 => 0,
+            2 => u16::from_le_bytes([data[0], data[1]]) as usize,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        let [vault, metadata @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        let metadata = metadata.first();
+        if let Some(metadata) = metadata {
+            check_metadata_account(vault, metadata)?;
+        }
+
+        Ok(Self {
+            vault,
+            metadata,
+            offset,
+        })
+    }
+}
+
+impl<'a> ExportState<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &72;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // Only the state of an opened vault at the current layout is exported
+        drop(VaultState::load(self.vault)?);
+
+        let mut export = [0u8; EXPORT_MAX_LEN];
+        export[0] = EXPORT_FORMAT;
+        export[1..3].copy_from_slice(&(VaultState::LEN as u16).to_le_bytes());
+        export[3..3 + VaultState::LEN].copy_from_slice(&self.vault.try_borrow_data()?);
+
+        let mut len = 3 + VaultState::LEN + 2;
+        if let Some(metadata) = self
+            .metadata
+            .filter(|metadata| metadata.is_owned_by(&crate::ID))
+        {
+            let data = metadata.try_borrow_data()?;
+            if VaultMetadata::from_bytes(&data).is_none() {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            export[len - 2..len].copy_from_slice(&(VaultMetadata::LEN as u16).to_le_bytes());
+            export[len..len + VaultMetadata::LEN].copy_from_slice(&data);
+            len += VaultMetadata::LEN;
+        }
+
+        // Instruction Checks
+        if self.offset >= len {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        set_return_data(&export[self.offset..len.min(self.offset + MAX_RETURN_DATA)]);
+
+        Ok(())
+    }
+}
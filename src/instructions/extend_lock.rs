@@ -0,0 +1,80 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::state::VaultState;
+use crate::vault::check_vault_manager;
+
+/// Account structure for the ExtendLock instruction
+///
+/// Accounts: `[authority, vault]`, where `vault` is an opened vault and
+/// `authority` its admin, or the owner it records if it has none.
+pub struct ExtendLockAccounts<'a> {
+    pub authority: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ExtendLockAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [authority, vault, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        // Only opened vaults can be locked, and their manager decides
+        check_vault_manager(authority, vault)?;
+
+        Ok(Self { authority, vault })
+    }
+}
+
+/// Instruction data structure for the ExtendLock instruction
+///
+/// Layout: `[unlock_ts: i64]`, a unix timestamp.
+pub struct ExtendLockInstructionData {
+    pub unlock_ts: i64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for ExtendLockInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let unlock_ts = data
+            .try_into()
+            .map(i64::from_le_bytes)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        Ok(Self { unlock_ts })
+    }
+}
+
+/// ExtendLock instruction - time-locks an opened vault until `unlock_ts`
+///
+/// The lock can only be extended: a timestamp that isn't strictly later than
+/// the current unlock fails with `CannotShortenLock`.
+pub struct ExtendLock<'a> {
+    pub accounts: ExtendLockAccounts<'a>,
+    pub instruction_data: ExtendLockInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for ExtendLock<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = ExtendLockInstructionData::try_from(data)?;
+        let accounts = ExtendLockAccounts::try_from(accounts)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> ExtendLock<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &21;
+
+    pub fn process(&mut self) -> ProgramResult {
+        VaultState::load_mut(self.accounts.vault)?.extend_lock(self.instruction_data.unlock_ts)
+    }
+}
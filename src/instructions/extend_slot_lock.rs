@@ -0,0 +1,84 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::state::VaultState;
+use crate::vault::check_vault_manager;
+
+/// Account structure for the ExtendSlotLock instruction
+///
+/// Accounts: `[authority, vault]`, where `vault` is an opened vault and
+/// `authority` its admin, or the owner it records if it has none.
+pub struct ExtendSlotLockAccounts<'a> {
+    pub authority: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ExtendSlotLockAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [authority, vault, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        // Only opened vaults can be locked, and their manager decides
+        check_vault_manager(authority, vault)?;
+
+        Ok(Self { authority, vault })
+    }
+}
+
+/// Instruction data structure for the ExtendSlotLock instruction
+///
+/// Layout: `[unlock_slot: u64]`, a slot.
+pub struct ExtendSlotLockInstructionData {
+    pub unlock_slot: u64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for ExtendSlotLockInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let unlock_slot = data
+            .try_into()
+            .map(u64::from_le_bytes)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        Ok(Self { unlock_slot })
+    }
+}
+
+/// ExtendSlotLock instruction - locks an opened vault until slot `unlock_slot`
+///
+/// An alternative to ExtendLock for owners who'd rather count slots than trust
+/// the wall clock. The two locks are independent, and a vault with both stays
+/// locked until both have passed. The slot lock can only be extended: a slot
+/// that isn't strictly later than the current unlock fails with
+/// `CannotShortenLock`.
+pub struct ExtendSlotLock<'a> {
+    pub accounts: ExtendSlotLockAccounts<'a>,
+    pub instruction_data: ExtendSlotLockInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for ExtendSlotLock<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = ExtendSlotLockInstructionData::try_from(data)?;
+        let accounts = ExtendSlotLockAccounts::try_from(accounts)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> ExtendSlotLock<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &57;
+
+    pub fn process(&mut self) -> ProgramResult {
+        VaultState::load_mut(self.accounts.vault)?
+            .extend_slot_lock(self.instruction_data.unlock_slot)
+    }
+}
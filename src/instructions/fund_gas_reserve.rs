@@ -0,0 +1,139 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+use pinocchio_log::log;
+
+use crate::error::VaultError;
+use crate::state::VaultState;
+use crate::stats::{check_stats_account, load_stats};
+use crate::vault::{transfer_into, LamportSnapshot};
+
+/// Account structure for the FundGasReserve instruction
+///
+/// Accounts: `[funder, vault, system_program, stats, rent_sysvar?]`, where
+/// `vault` is an opened vault. Anyone can fund it.
+pub struct FundGasReserveAccounts<'a> {
+    pub funder: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    pub stats: &'a AccountInfo,
+    pub rent_sysvar: Option<&'a AccountInfo>,
+    pub stats_bump: u8,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for FundGasReserveAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [funder, vault, system_program, stats, rent_sysvar @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        if !funder.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        if funder.key().eq(vault.key()) {
+            return Err(VaultError::InvalidAccountArrangement.into());
+        }
+
+        if system_program.key().ne(&pinocchio_system::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        // Only opened vaults have state to earmark the reserve in
+        VaultState::load(vault)?;
+
+        let stats_bump = check_stats_account(stats)?;
+
+        Ok(Self {
+            funder,
+            vault,
+            stats,
+            rent_sysvar: rent_sysvar.first(),
+            stats_bump,
+        })
+    }
+}
+
+/// Instruction data structure for the FundGasReserve instruction
+///
+/// Layout: `[amount: u64]`.
+pub struct FundGasReserveInstructionData {
+    pub amount: u64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for FundGasReserveInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let amount = u64::from_le_bytes(
+            data.try_into()
+                .map_err(|_| ProgramError::InvalidInstructionData)?,
+        );
+
+        // Instruction Checks
+        if amount.eq(&0) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { amount })
+    }
+}
+
+/// FundGasReserve instruction - moves lamports from any funder into an opened
+/// vault's gas reserve
+///
+/// The reserve stays out of the tracked balance, so no withdrawal releases it.
+/// WithdrawAllWithTip pays the relayer's tip from it first, leaving the
+/// owner's balance whole. Funding that would overflow the reserve fails with
+/// `ArithmeticOverflow`. The reserve counts towards the global stats' value
+/// locked like a deposit.
+pub struct FundGasReserve<'a> {
+    pub accounts: FundGasReserveAccounts<'a>,
+    pub instruction_data: FundGasReserveInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for FundGasReserve<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = FundGasReserveInstructionData::try_from(data)?;
+        let accounts = FundGasReserveAccounts::try_from(accounts)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> FundGasReserve<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &53;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let conserved = || [self.accounts.funder, self.accounts.vault, self.accounts.stats];
+        let snapshot = LamportSnapshot::take(conserved());
+        let lamports = self.instruction_data.amount;
+
+        let reserve = {
+            let mut state = VaultState::load_mut(self.accounts.vault)?;
+            state.add_gas_reserve(lamports)?;
+            state.gas_reserve()
+        };
+
+        transfer_into(self.accounts.funder, self.accounts.vault, lamports)?;
+
+        load_stats(
+            self.accounts.funder,
+            self.accounts.stats,
+            self.accounts.stats_bump,
+            self.accounts.rent_sysvar,
+        )?
+        .record_deposit(lamports, false);
+
+        snapshot.check_conserved(conserved())?;
+
+        log!("FundGasReserve: gas reserve now {} lamports", reserve);
+
+        Ok(())
+    }
+}
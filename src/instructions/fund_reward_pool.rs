@@ -0,0 +1,139 @@
+use pinocchio::{
+    account_info::AccountInfo, cpi::set_return_data, program_error::ProgramError, ProgramResult,
+};
+use pinocchio_log::log;
+
+use crate::reward_pool::{check_reward_pool_account, load_reward_pool};
+use crate::vault::{transfer_into, LamportSnapshot};
+
+/// Account structure for the FundRewardPool instruction
+///
+/// Accounts: `[funder, reward_pool, system_program, rent_sysvar?]`, where
+/// `reward_pool` is the `["reward_pool"]` PDA, created or not. Anyone can fund it.
+pub struct FundRewardPoolAccounts<'a> {
+    pub funder: &'a AccountInfo,
+    pub reward_pool: &'a AccountInfo,
+    pub rent_sysvar: Option<&'a AccountInfo>,
+    pub reward_pool_bump: u8,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for FundRewardPoolAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [funder, reward_pool, system_program, rent_sysvar @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        if !funder.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        if system_program.key().ne(&pinocchio_system::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let reward_pool_bump = check_reward_pool_account(reward_pool)?;
+
+        Ok(Self {
+            funder,
+            reward_pool,
+            rent_sysvar: rent_sysvar.first(),
+            reward_pool_bump,
+        })
+    }
+}
+
+/// Instruction data structure for the FundRewardPool instruction
+///
+/// Layout: `[amount: u64]`.
+pub struct FundRewardPoolInstructionData {
+    pub amount: u64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for FundRewardPoolInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let amount = u64::from_le_bytes(
+            data.try_into()
+                .map_err(|_| ProgramError::InvalidInstructionData)?,
+        );
+
+        // Instruction Checks
+        if amount.eq(&0) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { amount })
+    }
+}
+
+/// FundRewardPool instruction - moves lamports from any funder into the reward
+/// pool that vault interest is paid from
+///
+/// The first funding creates the pool at the funder's expense. Funding that
+/// would overflow the pool's balance or its tracked total fails with
+/// `ArithmeticOverflow`.
+pub struct FundRewardPool<'a> {
+    pub accounts: FundRewardPoolAccounts<'a>,
+    pub instruction_data: FundRewardPoolInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for FundRewardPool<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = FundRewardPoolInstructionData::try_from(data)?;
+        let accounts = FundRewardPoolAccounts::try_from(accounts)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> FundRewardPool<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &34;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let conserved = || [self.accounts.funder, self.accounts.reward_pool];
+        let snapshot = LamportSnapshot::take(conserved());
+        let lamports = self.instruction_data.amount;
+
+        let total = {
+            let mut reward_pool = load_reward_pool(
+                self.accounts.funder,
+                self.accounts.reward_pool,
+                self.accounts.reward_pool_bump,
+                self.accounts.rent_sysvar,
+            )?;
+            reward_pool.record_funding(lamports)?;
+            reward_pool.total_funded()
+        };
+
+        // Checked up front so an overflowing balance surfaces as such, not as `TransferFailed`
+        self.accounts
+            .reward_pool
+            .lamports()
+            .checked_add(lamports)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        transfer_into(self.accounts.funder, self.accounts.reward_pool, lamports)?;
+
+        snapshot.check_conserved(conserved())?;
+
+        log!(
+            "FundRewardPool: funded {} lamports, {} in total",
+            lamports,
+            total
+        );
+
+        // Report the pool's new balance so clients don't need a follow-up fetch
+        set_return_data(&self.accounts.reward_pool.lamports().to_le_bytes());
+
+        Ok(())
+    }
+}
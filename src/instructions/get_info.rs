@@ -0,0 +1,61 @@
+use pinocchio::{
+    account_info::AccountInfo, cpi::set_return_data, program_error::ProgramError, ProgramResult,
+};
+
+use crate::state::VaultState;
+
+/// Size of a vault's packed info
+pub const VAULT_INFO_LEN: usize = 50;
+
+/// Packs `state` into the `[balance][owner][bump][unlock_ts][flags]` info
+/// GetInfo and GetInfoMany report
+#[inline(always)]
+pub fn pack_vault_info(state: &VaultState) -> [u8; VAULT_INFO_LEN] {
+    let mut info = [0u8; VAULT_INFO_LEN];
+    info[..8].copy_from_slice(&state.balance().to_le_bytes());
+    info[8..40].copy_from_slice(state.owner());
+    info[40] = state.bump();
+    info[41..49].copy_from_slice(&state.unlock_ts().to_le_bytes());
+    info[49] = state.flags();
+    info
+}
+
+/// GetInfo instruction - reports an opened vault's state as return data
+///
+/// Accounts: `[vault]`, an opened vault, which is only read. Return data is
+/// `[balance: u64][owner: Pubkey][bump: u8][unlock_ts: i64][flags: u8]`, with
+/// `balance` the tracked balance, rent floor excluded, so explorers can fetch
+/// everything with one simulated call.
+pub struct GetInfo<'a> {
+    pub vault: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for GetInfo<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        if !data.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let [vault, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self { vault })
+    }
+}
+
+impl<'a> GetInfo<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &28;
+
+    /// Size of the return data
+    pub const INFO_LEN: usize = VAULT_INFO_LEN;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let state = VaultState::load(self.vault)?;
+        set_return_data(&pack_vault_info(&state));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+use pinocchio::{
+    account_info::AccountInfo, cpi::set_return_data, program_error::ProgramError, ProgramResult,
+};
+
+use crate::instructions::{pack_vault_info, VAULT_INFO_LEN};
+use crate::state::VaultState;
+use crate::vault::{check_batch_size, is_opened, MAX_BATCH_VAULTS};
+
+/// GetInfoMany instruction - reports several vaults' states as return data
+///
+/// Accounts: `[vault, ..]`, up to `MAX_BATCH_VAULTS` vaults, which are only
+/// read. Return data holds one `[len: u8][info]` entry per vault, in account
+/// order, where `info` is the 50-byte layout GetInfo reports. A vault that isn't opened, e.g.
+/// one that doesn't exist, gets an empty entry instead of failing the batch.
+pub struct GetInfoMany<'a> {
+    pub vaults: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for GetInfoMany<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        if !data.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        if accounts.is_empty() {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        check_batch_size(accounts.len())?;
+
+        Ok(Self { vaults: accounts })
+    }
+}
+
+impl<'a> GetInfoMany<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &45;
+
+    /// Size of one entry of the return data
+    pub const ENTRY_LEN: usize = 1 + VAULT_INFO_LEN;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let mut infos = [0u8; MAX_BATCH_VAULTS * Self::ENTRY_LEN];
+        let mut len = 0;
+
+        for vault in self.vaults {
+            if is_opened(vault) {
+                let state = VaultState::load(vault)?;
+                infos[len] = VAULT_INFO_LEN as u8;
+                infos[len + 1..len + Self::ENTRY_LEN].copy_from_slice(&pack_vault_info(&state));
+                len += Self::ENTRY_LEN;
+            } else {
+                len += 1;
+            }
+        }
+
+        set_return_data(&infos[..len]);
+
+        Ok(())
+    }
+}
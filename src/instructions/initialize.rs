@@ -0,0 +1,250 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    ProgramResult,
+};
+use pinocchio_system::instructions::CreateAccount;
+
+use crate::config::{check_config_account, check_upgrade_authority, Config};
+use crate::error::VaultError;
+use crate::logic::BPS_DENOMINATOR;
+use crate::pda::CONFIG_SEED;
+use crate::rent::minimum_balance;
+
+/// Account structure for the Initialize instruction
+///
+/// Accounts: `[admin, config, system_program, program_data, rent_sysvar?]`,
+/// where `config` is the `["config"]` PDA, `admin` pays for it and
+/// `program_data` is the program's ProgramData account.
+pub struct InitializeAccounts<'a> {
+    pub admin: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub rent_sysvar: Option<&'a AccountInfo>,
+    pub bumps: [u8; 1],
+    /// The signer already initialized the config, so this is a repeated call
+    pub already_initialized: bool,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for InitializeAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [admin, config, system_program, program_data, rent_sysvar @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        if !admin.is_signer() {
+            return Err(VaultError::MissingOwnerSignature.into());
+        }
+
+        if system_program.key().ne(&pinocchio_system::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let bump = check_config_account(config)?;
+
+        // The config can only be created once, but its admin may repeat the call
+        let already_initialized = !config.is_owned_by(&pinocchio_system::ID);
+        if already_initialized {
+            if Config::load(config)?.admin().ne(admin.key()) {
+                return Err(VaultError::AlreadyInitialized.into());
+            }
+        } else {
+            // Whoever creates the config becomes its admin, so only the deployer may
+            check_upgrade_authority(program_data, admin)?;
+        }
+
+        Ok(Self {
+            admin,
+            config,
+            rent_sysvar: rent_sysvar.first(),
+            bumps: [bump],
+            already_initialized,
+        })
+    }
+}
+
+/// Instruction data structure for the Initialize instruction
+///
+/// Layout: `[referral_bps: u16][price_oracle: Pubkey][max_vaults_per_owner: u64]
+/// [max_deposit_per_tx: u64][rent_subsidy: Pubkey][dust_threshold: u64]
+/// [hook_program: Pubkey][reuse_cooldown_secs: u64][recovery_address: Pubkey]
+/// [min_withdraw: u64][fee_rounding: u8][recovery_inactivity_secs: u64]`, with
+/// `referral_bps` at most `BPS_DENOMINATOR`. The trailing fields are optional,
+/// but each one requires the fields before it. `price_oracle` defaults to the
+/// zero pubkey, which leaves WithdrawIfPriceAbove unavailable,
+/// `max_vaults_per_owner` to `0`, which doesn't limit OpenVault,
+/// `max_deposit_per_tx` to `0`, which doesn't cap Deposit, `rent_subsidy` to the
+/// zero pubkey, which leaves subsidized deposits unavailable, `dust_threshold`
+/// to `0`, which turns off WithdrawAmount's auto-close, `hook_program` to the
+/// zero pubkey, which disables the deposit and withdraw hook,
+/// `reuse_cooldown_secs` to `0`, which lets a closed vault be funded again right
+/// away, `recovery_address` to the zero pubkey, which leaves EmergencyDrain and
+/// AdminRecover unavailable, `min_withdraw` to `0`, which lets WithdrawAmount
+/// move any amount, `fee_rounding` to `Config::FEE_ROUNDING_FAVOR_OWNER`, which
+/// rounds fees down, and `recovery_inactivity_secs` to `0`, which leaves
+/// AdminRecover unavailable too. A `fee_rounding` other than
+/// `Config::FEE_ROUNDING_FAVOR_OWNER` and `Config::FEE_ROUNDING_FAVOR_TREASURY`
+/// fails. The program starts unpaused. A hook program that is the vault
+/// program itself fails with `HookReentrancy`.
+pub struct InitializeInstructionData {
+    pub referral_bps: u16,
+    pub price_oracle: Pubkey,
+    pub max_vaults_per_owner: u64,
+    pub max_deposit_per_tx: u64,
+    pub rent_subsidy: Pubkey,
+    pub dust_threshold: u64,
+    pub hook_program: Pubkey,
+    pub reuse_cooldown_secs: u64,
+    pub recovery_address: Pubkey,
+    pub min_withdraw: u64,
+    pub fee_rounding: u8,
+    pub recovery_inactivity_secs: u64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for InitializeInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let price_oracle = match data.len() {
+            2 => Pubkey::default(),
+            34 | 42 | 50 | 82 | 90 | 122 | 130 | 162 | 170 | 171 | 179 => data[2..34].try_into().unwrap(),
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        let max_vaults_per_owner = data.get(34..42).map_or(0, |max_vaults| {
+            u64::from_le_bytes(max_vaults.try_into().unwrap())
+        });
+
+        let max_deposit_per_tx = data.get(42..50).map_or(0, |max_deposit| {
+            u64::from_le_bytes(max_deposit.try_into().unwrap())
+        });
+
+        let rent_subsidy = data
+            .get(50..82)
+            .map_or(Pubkey::default(), |subsidy| subsidy.try_into().unwrap());
+
+        let dust_threshold = data.get(82..90).map_or(0, |threshold| {
+            u64::from_le_bytes(threshold.try_into().unwrap())
+        });
+
+        let hook_program = data
+            .get(90..122)
+            .map_or(Pubkey::default(), |hook| hook.try_into().unwrap());
+        if hook_program.eq(&crate::ID) {
+            return Err(VaultError::HookReentrancy.into());
+        }
+
+        let reuse_cooldown_secs = data.get(122..130).map_or(0, |cooldown| {
+            u64::from_le_bytes(cooldown.try_into().unwrap())
+        });
+
+        let recovery_address = data
+            .get(130..162)
+            .map_or(Pubkey::default(), |recovery| recovery.try_into().unwrap());
+
+        let min_withdraw = data.get(162..170).map_or(0, |min_withdraw| {
+            u64::from_le_bytes(min_withdraw.try_into().unwrap())
+        });
+
+        let fee_rounding = data
+            .get(170)
+            .map_or(Config::FEE_ROUNDING_FAVOR_OWNER, |rounding| *rounding);
+        if fee_rounding > Config::FEE_ROUNDING_FAVOR_TREASURY {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let recovery_inactivity_secs = data.get(171..179).map_or(0, |inactivity| {
+            u64::from_le_bytes(inactivity.try_into().unwrap())
+        });
+
+        let referral_bps = u16::from_le_bytes(data[..2].try_into().unwrap());
+        if referral_bps as u64 > BPS_DENOMINATOR {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            referral_bps,
+            price_oracle,
+            max_vaults_per_owner,
+            max_deposit_per_tx,
+            rent_subsidy,
+            dust_threshold,
+            hook_program,
+            reuse_cooldown_secs,
+            recovery_address,
+            min_withdraw,
+            fee_rounding,
+            recovery_inactivity_secs,
+        })
+    }
+}
+
+/// Initialize instruction - creates the config PDA with the signer as its admin
+///
+/// Only the program's upgrade authority can create the config, failing with
+/// `NotUpgradeAuthority` otherwise. Repeating it is a no-op for the config's
+/// admin, whatever the instruction data says, so deployment scripts can run it
+/// unconditionally. Anyone else fails with `AlreadyInitialized`.
+pub struct Initialize<'a> {
+    pub accounts: InitializeAccounts<'a>,
+    pub instruction_data: InitializeInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for Initialize<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = InitializeInstructionData::try_from(data)?;
+        let accounts = InitializeAccounts::try_from(accounts)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> Initialize<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &12;
+
+    pub fn process(&mut self) -> ProgramResult {
+        if self.accounts.already_initialized {
+            return Ok(());
+        }
+
+        let seeds = [Seed::from(CONFIG_SEED), Seed::from(&self.accounts.bumps)];
+        let signers = [Signer::from(&seeds)];
+
+        CreateAccount {
+            from: self.accounts.admin,
+            to: self.accounts.config,
+            lamports: minimum_balance(self.accounts.rent_sysvar, Config::LEN)?,
+            space: Config::LEN as u64,
+            owner: &crate::ID,
+        }
+        .invoke_signed(&signers)?;
+
+        Config::load_mut(self.accounts.config)?.set_inner(
+            self.accounts.admin.key(),
+            self.instruction_data.referral_bps,
+            &self.instruction_data.price_oracle,
+            self.instruction_data.max_vaults_per_owner,
+            self.instruction_data.max_deposit_per_tx,
+            &self.instruction_data.rent_subsidy,
+            self.instruction_data.dust_threshold,
+            &self.instruction_data.hook_program,
+            self.instruction_data.reuse_cooldown_secs,
+            &self.instruction_data.recovery_address,
+            self.instruction_data.min_withdraw,
+            self.instruction_data.fee_rounding,
+            self.instruction_data.recovery_inactivity_secs,
+            self.accounts.bumps[0],
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,181 @@
+use pinocchio::{
+    account_info::AccountInfo, cpi::set_return_data, instruction::Signer,
+    program_error::ProgramError, ProgramResult,
+};
+
+use crate::error::VaultError;
+use crate::oracle::check_price_gate;
+use crate::pda::{find_vault_address, log_vault_mismatch, vault_signer_seeds, DEFAULT_SALT};
+use crate::stats::{check_stats_account, load_stats};
+use crate::vault::{
+    check_account_arrangement, check_not_escrowed, check_not_multisig, check_not_vesting,
+    check_vault_owner, record_vault_deposit, releasable, transfer_from_vault,
+};
+
+/// Account structure for the MergeVaults instruction
+///
+/// Accounts: `[owner, source, dest, system_program, stats]`.
+pub struct MergeVaultsAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub source: &'a AccountInfo,
+    pub dest: &'a AccountInfo,
+    pub stats: &'a AccountInfo,
+    pub source_bumps: [u8; 1],
+    pub stats_bump: u8,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &MergeVaultsInstructionData)> for MergeVaultsAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(
+        (accounts, instruction_data): (&'a [AccountInfo], &MergeVaultsInstructionData),
+    ) -> Result<Self, Self::Error> {
+        let [owner, source, dest, system_program, stats, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Basic Accounts Checks
+        check_account_arrangement(owner, source)?;
+        check_account_arrangement(owner, dest)?;
+
+        if !owner.is_signer() {
+            return Err(VaultError::MissingOwnerSignature.into());
+        }
+
+        if system_program.key() != &pinocchio_system::ID {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        check_vault_owner(source)?;
+        check_vault_owner(dest)?;
+        check_price_gate(source, &[])?;
+        check_not_vesting(source)?;
+        check_not_escrowed(source)?;
+        check_not_multisig(source)?;
+
+        if source.lamports().eq(&0) {
+            return Err(VaultError::VaultEmpty.into());
+        }
+
+        let (source_key, source_bump) =
+            find_vault_address(owner.key(), &instruction_data.source_id, &DEFAULT_SALT);
+        if source.key().ne(&source_key) {
+            log_vault_mismatch(source_bump, source.key());
+            return Err(VaultError::InvalidPda.into());
+        }
+
+        let (dest_key, dest_bump) =
+            find_vault_address(owner.key(), &instruction_data.dest_id, &DEFAULT_SALT);
+        if dest.key().ne(&dest_key) {
+            log_vault_mismatch(dest_bump, dest.key());
+            return Err(VaultError::InvalidPda.into());
+        }
+
+        let stats_bump = check_stats_account(stats)?;
+
+        Ok(Self {
+            owner,
+            source,
+            dest,
+            stats,
+            source_bumps: [source_bump],
+            stats_bump,
+        })
+    }
+}
+
+/// Instruction data structure for the MergeVaults instruction
+///
+/// Layout: `[source_id: u64][dest_id: u64]`. Only distinct, unsalted vaults can
+/// be merged.
+pub struct MergeVaultsInstructionData {
+    pub source_id: [u8; 8],
+    pub dest_id: [u8; 8],
+}
+
+impl<'a> TryFrom<&'a [u8]> for MergeVaultsInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&16) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let source_id: [u8; 8] = data[..8].try_into().unwrap();
+        let dest_id: [u8; 8] = data[8..].try_into().unwrap();
+
+        if source_id.eq(&dest_id) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { source_id, dest_id })
+    }
+}
+
+/// MergeVaults instruction - moves one of the owner's vaults into another
+///
+/// The source is drained completely, rent reserve included, which closes it,
+/// and the dest's tracked balance grows by what it received. Like Consolidate
+/// with a single source, but the dest's new balance is returned.
+pub struct MergeVaults<'a> {
+    pub accounts: MergeVaultsAccounts<'a>,
+    pub instruction_data: MergeVaultsInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for MergeVaults<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = MergeVaultsInstructionData::try_from(data)?;
+        let accounts = MergeVaultsAccounts::try_from((accounts, &instruction_data))?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> MergeVaults<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &15;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let source = self.accounts.source;
+        let dest = self.accounts.dest;
+        let dest_created = dest.lamports().eq(&0);
+
+        let lamports = releasable(source, None, true)?;
+        if lamports.eq(&0) {
+            return Err(VaultError::VaultEmpty.into());
+        }
+
+        // Create PDA signer seeds
+        let seeds = vault_signer_seeds(
+            self.accounts.owner.key(),
+            &self.instruction_data.source_id,
+            &DEFAULT_SALT,
+            &self.accounts.source_bumps,
+        );
+        let signers = [Signer::from(&seeds)];
+
+        transfer_from_vault(source, self.accounts.owner, dest, lamports, &signers)?;
+        record_vault_deposit(dest, self.accounts.owner, lamports, 0, None)?;
+
+        // Lamports stay locked, only the number of vaults changes
+        let mut stats = load_stats(
+            self.accounts.owner,
+            self.accounts.stats,
+            self.accounts.stats_bump,
+            None,
+        )?;
+        stats.record_close(source.lamports().eq(&0) as u64);
+        if dest_created {
+            stats.record_open(0);
+        }
+
+        // Report the dest's new balance so clients don't need a follow-up fetch
+        set_return_data(&dest.lamports().to_le_bytes());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,219 @@
+use pinocchio::{
+    account_info::AccountInfo, instruction::Signer, program_error::ProgramError, ProgramResult,
+};
+use pinocchio_system::instructions::Transfer;
+
+use crate::error::VaultError;
+use crate::pda::{
+    find_registry_address, find_vault_address, log_vault_mismatch, vault_signer_seeds,
+};
+use crate::registry::replace_vault;
+use crate::state::VaultState;
+use crate::vault::{create_vault, is_opened, VaultAccounts};
+
+/// Account structure for the MigrateVault instruction
+///
+/// Accounts: `[owner, old_vault, new_vault, system_program, registry, rent_sysvar?]`,
+/// where `registry` is the owner's `["registry", owner]` PDA, created or not.
+pub struct MigrateVaultAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub old_vault: &'a AccountInfo,
+    pub new_vault: &'a AccountInfo,
+    pub registry: &'a AccountInfo,
+    pub rent_sysvar: Option<&'a AccountInfo>,
+    pub old_bumps: [u8; 1],
+    pub new_bump: u8,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &MigrateVaultInstructionData)> for MigrateVaultAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(
+        (accounts, instruction_data): (&'a [AccountInfo], &MigrateVaultInstructionData),
+    ) -> Result<Self, Self::Error> {
+        let [owner, old_vault, new_vault, system_program, registry, rent_sysvar @ ..] = accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        let VaultAccounts { bump: old_bump, .. } = VaultAccounts::check(
+            owner,
+            old_vault,
+            system_program,
+            &instruction_data.old_id,
+            &instruction_data.old_salt,
+        )?;
+
+        if old_vault.lamports().eq(&0) {
+            return Err(VaultError::VaultEmpty.into());
+        }
+
+        // The old address stays the authority of its token accounts and receipt mint
+        if is_opened(old_vault) {
+            let state = VaultState::load(old_vault)?;
+            if state.holds_tokens() {
+                return Err(VaultError::TokensOutstanding.into());
+            }
+
+            if state.has_receipt_mint() || state.receipt_supply().ne(&0) {
+                return Err(VaultError::ReceiptsOutstanding.into());
+            }
+        }
+
+        // Only an untouched address can take the vault over
+        if !new_vault.is_owned_by(&pinocchio_system::ID) || new_vault.lamports().ne(&0) {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        let (new_key, new_bump) = find_vault_address(
+            owner.key(),
+            &instruction_data.new_id,
+            &instruction_data.new_salt,
+        );
+        if new_vault.key().ne(&new_key) {
+            log_vault_mismatch(new_bump, new_vault.key());
+            return Err(VaultError::InvalidPda.into());
+        }
+
+        if registry.key().ne(&find_registry_address(owner.key()).0) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        Ok(Self {
+            owner,
+            old_vault,
+            new_vault,
+            registry,
+            rent_sysvar: rent_sysvar.first(),
+            old_bumps: [old_bump],
+            new_bump,
+        })
+    }
+}
+
+/// Instruction data structure for the MigrateVault instruction
+///
+/// Layout: `[old_id: u64][old_salt: [u8; 32]][new_id: u64][new_salt: [u8; 32]]`,
+/// the derivations of the vault's current and new address.
+pub struct MigrateVaultInstructionData {
+    pub old_id: [u8; 8],
+    pub old_salt: [u8; 32],
+    pub new_id: [u8; 8],
+    pub new_salt: [u8; 32],
+}
+
+impl<'a> TryFrom<&'a [u8]> for MigrateVaultInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&80) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            old_id: data[..8].try_into().unwrap(),
+            old_salt: data[8..40].try_into().unwrap(),
+            new_id: data[40..48].try_into().unwrap(),
+            new_salt: data[48..80].try_into().unwrap(),
+        })
+    }
+}
+
+/// MigrateVault instruction - moves a vault to the address of another derivation
+///
+/// A system-owned vault signs a transfer of all its lamports to the new
+/// address. An opened vault is recreated there with its whole state, only its
+/// id and bump updated, and its balance moved over; the owner pays the new rent
+/// floor and gets the old one back once the old account is closed, and its
+/// registry entry, if it has one, points to the new address.
+///
+/// Token accounts and a receipt mint stay under the old address's authority, so
+/// a vault still tracking tokens fails with `TokensOutstanding`, and one with a
+/// receipt mint with `ReceiptsOutstanding`.
+pub struct MigrateVault<'a> {
+    pub accounts: MigrateVaultAccounts<'a>,
+    pub instruction_data: MigrateVaultInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for MigrateVault<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = MigrateVaultInstructionData::try_from(data)?;
+        let accounts = MigrateVaultAccounts::try_from((accounts, &instruction_data))?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> MigrateVault<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &29;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let (owner, old_vault, new_vault) = (
+            self.accounts.owner,
+            self.accounts.old_vault,
+            self.accounts.new_vault,
+        );
+
+        if !is_opened(old_vault) {
+            let seeds = vault_signer_seeds(
+                owner.key(),
+                &self.instruction_data.old_id,
+                &self.instruction_data.old_salt,
+                &self.accounts.old_bumps,
+            );
+            let signers = [Signer::from(&seeds)];
+
+            return Transfer {
+                from: old_vault,
+                to: new_vault,
+                lamports: old_vault.lamports(),
+            }
+            .invoke_signed(&signers);
+        }
+
+        let rent_floor = create_vault(
+            owner,
+            owner,
+            new_vault,
+            &self.instruction_data.new_id,
+            &self.instruction_data.new_salt,
+            self.accounts.new_bump,
+            0,
+            self.accounts.rent_sysvar,
+        )?;
+
+        {
+            let old_state = VaultState::load(old_vault)?;
+            VaultState::load_mut(new_vault)?.migrate_from(
+                &old_state,
+                &self.instruction_data.new_id,
+                self.accounts.new_bump,
+            );
+        }
+
+        // Everything above the old rent floor moves, and the floor goes back to the owner
+        let lamports = old_vault.lamports();
+        let moved = lamports.saturating_sub(rent_floor);
+        *new_vault.try_borrow_mut_lamports()? = new_vault
+            .lamports()
+            .checked_add(moved)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        *owner.try_borrow_mut_lamports()? = owner
+            .lamports()
+            .checked_add(lamports - moved)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        *old_vault.try_borrow_mut_lamports()? = 0;
+
+        // Zeroed so an account recreated at the old address never sees stale data
+        old_vault.try_borrow_mut_data()?.fill(0);
+        old_vault.close()?;
+
+        replace_vault(self.accounts.registry, old_vault.key(), new_vault.key())
+    }
+}
@@ -1,5 +1,143 @@
+mod accept_admin;
+mod admin_recover;
+mod allowed_dest;
+mod allowed_source;
+mod beneficiary_claim;
+mod can_withdraw;
+mod claim_reward;
+mod close;
+mod consolidate;
+mod crank;
+mod create_multisig;
+mod delegate_withdraw;
 mod deposit;
+mod deposit_as_wrapped;
+mod deposit_for;
+mod deposit_locked;
+mod deposit_many;
+mod deposit_token;
+mod deposit_with_metadata;
+mod deposit_wrapped_sol;
+mod derive_vault;
+mod dispatch;
+mod emergency_drain;
+mod export_state;
+mod extend_lock;
+mod extend_slot_lock;
+mod fund_gas_reserve;
+mod fund_reward_pool;
+mod get_info;
+mod get_info_many;
+mod initialize;
+mod merge_vaults;
+mod migrate_vault;
+mod open_vault;
+mod propose_admin;
+mod reconcile;
+mod set_beneficiary;
+mod set_daily_limit;
+mod set_delegate;
+mod set_deposits_locked;
+mod set_drip;
+mod set_escrow_agent;
+mod set_expiry;
+mod set_frozen;
+mod set_label;
+mod set_metadata;
+mod set_oracle;
+mod set_paused;
+mod set_reward_rate;
+mod set_token_dest_owner;
+mod set_withdraw_fee;
+mod simulate_withdraw;
+mod split_deposit;
+mod split_vault;
+mod sweep_token;
+mod tag_vault;
+mod upgrade_to_multisig;
+mod upgrade_vault_state;
+mod vault_transfer;
+mod verify_integrity;
 mod withdraw;
+mod withdraw_all_with_tip;
+mod withdraw_amount;
+mod withdraw_expired;
+mod withdraw_if_price_above;
+mod withdraw_many;
+mod withdraw_to;
+mod withdraw_to_new_account;
+mod withdraw_token;
+mod withdraw_vested;
+mod withdraw_with_approval;
 
+pub use accept_admin::*;
+pub use admin_recover::*;
+pub use allowed_dest::*;
+pub use allowed_source::*;
+pub use beneficiary_claim::*;
+pub use can_withdraw::*;
+pub use claim_reward::*;
+pub use close::*;
+pub use consolidate::*;
+pub use crank::*;
+pub use create_multisig::*;
+pub use delegate_withdraw::*;
 pub use deposit::*;
+pub use deposit_as_wrapped::*;
+pub use deposit_for::*;
+pub use deposit_locked::*;
+pub use deposit_many::*;
+pub use deposit_token::*;
+pub use deposit_with_metadata::*;
+pub use deposit_wrapped_sol::*;
+pub use derive_vault::*;
+pub use dispatch::*;
+pub use emergency_drain::*;
+pub use export_state::*;
+pub use extend_lock::*;
+pub use extend_slot_lock::*;
+pub use fund_gas_reserve::*;
+pub use fund_reward_pool::*;
+pub use get_info::*;
+pub use get_info_many::*;
+pub use initialize::*;
+pub use merge_vaults::*;
+pub use migrate_vault::*;
+pub use open_vault::*;
+pub use propose_admin::*;
+pub use reconcile::*;
+pub use set_beneficiary::*;
+pub use set_daily_limit::*;
+pub use set_delegate::*;
+pub use set_deposits_locked::*;
+pub use set_drip::*;
+pub use set_escrow_agent::*;
+pub use set_expiry::*;
+pub use set_frozen::*;
+pub use set_label::*;
+pub use set_metadata::*;
+pub use set_oracle::*;
+pub use set_paused::*;
+pub use set_reward_rate::*;
+pub use set_token_dest_owner::*;
+pub use set_withdraw_fee::*;
+pub use simulate_withdraw::*;
+pub use split_deposit::*;
+pub use split_vault::*;
+pub use sweep_token::*;
+pub use tag_vault::*;
+pub use upgrade_to_multisig::*;
+pub use upgrade_vault_state::*;
+pub use vault_transfer::*;
+pub use verify_integrity::*;
 pub use withdraw::*;
+pub use withdraw_all_with_tip::*;
+pub use withdraw_amount::*;
+pub use withdraw_expired::*;
+pub use withdraw_if_price_above::*;
+pub use withdraw_many::*;
+pub use withdraw_to::*;
+pub use withdraw_to_new_account::*;
+pub use withdraw_token::*;
+pub use withdraw_vested::*;
+pub use withdraw_with_approval::*;
@@ -0,0 +1,261 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use crate::config::{check_config_account, max_vaults_per_owner};
+use crate::error::VaultError;
+use crate::pda::{
+    find_registry_address, find_vault_address, log_vault_mismatch, DEFAULT_SALT, DEFAULT_VAULT_ID,
+};
+use crate::registry::register_vault;
+use crate::state::VaultState;
+use crate::stats::{check_stats_account, load_stats};
+use crate::vault::{check_account_arrangement, create_vault};
+
+/// Account structure for the OpenVault instruction
+///
+/// Accounts: `[owner, vault, system_program, registry, stats, config, rent_sysvar?]`,
+/// where `registry` is the owner's `["registry", owner]` PDA and `config` the
+/// config PDA, initialized or not.
+pub struct OpenVaultAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    pub registry: &'a AccountInfo,
+    pub stats: &'a AccountInfo,
+    pub rent_sysvar: Option<&'a AccountInfo>,
+    pub bumps: [u8; 1],
+    pub registry_bump: u8,
+    pub stats_bump: u8,
+    /// Most vaults the owner's registry can list, `0` for no limit
+    pub max_vaults: u64,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &OpenVaultInstructionData<'_>)> for OpenVaultAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(
+        (accounts, instruction_data): (&'a [AccountInfo], &OpenVaultInstructionData<'_>),
+    ) -> Result<Self, Self::Error> {
+        let [owner, vault, system_program, registry, stats, config, rent_sysvar @ ..] = accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        check_account_arrangement(owner, vault)?;
+
+        if !owner.is_signer() {
+            return Err(VaultError::MissingOwnerSignature.into());
+        }
+
+        if system_program.key().ne(&pinocchio_system::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        // Only an untouched vault address can be opened
+        if !vault.is_owned_by(&pinocchio_system::ID) || vault.lamports().ne(&0) {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        let (vault_key, bump) =
+            find_vault_address(owner.key(), &instruction_data.id, &instruction_data.salt);
+        if vault.key().ne(&vault_key) {
+            log_vault_mismatch(bump, vault.key());
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        let (registry_key, registry_bump) = find_registry_address(owner.key());
+        if registry.key().ne(&registry_key) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        let stats_bump = check_stats_account(stats)?;
+
+        check_config_account(config)?;
+        let max_vaults = max_vaults_per_owner(config)?;
+
+        Ok(Self {
+            owner,
+            vault,
+            registry,
+            stats,
+            rent_sysvar: rent_sysvar.first(),
+            bumps: [bump],
+            registry_bump,
+            stats_bump,
+            max_vaults,
+        })
+    }
+}
+
+/// Instruction data structure for the OpenVault instruction
+///
+/// Layout: `[id: u64][salt: [u8; 32]][flags: u8][daily_limit: u64][min_age_secs: u64]
+/// [rolling_limit: u64][rolling_window_secs: u64][admin: Pubkey][label: [u8]]`.
+/// All fields are optional, but each one requires the fields before it: `id`
+/// and `salt` default to the owner's default unsalted vault, `flags` to `0`
+/// and `label` to an empty label. `flags` takes the `VaultState::FLAG_*` bits;
+/// `daily_limit` is only present with `FLAG_DAILY_LIMIT`, `min_age_secs` with
+/// `FLAG_MIN_AGE` and `rolling_limit` and `rolling_window_secs` with
+/// `FLAG_ROLLING_LIMIT`, and all of them must be non-zero. `admin` is only
+/// present with `FLAG_ADMIN`, and must be neither the zero pubkey nor the owner.
+/// `label` is the rest of the data, up to `VaultState::MAX_LABEL_LEN` bytes of
+/// UTF-8.
+pub struct OpenVaultInstructionData<'a> {
+    pub id: [u8; 8],
+    pub salt: [u8; 32],
+    pub flags: u8,
+    pub daily_limit: u64,
+    pub min_age_secs: u64,
+    pub rolling_limit: u64,
+    pub rolling_window_secs: u64,
+    pub admin: Pubkey,
+    pub label: &'a [u8],
+}
+
+impl<'a> TryFrom<&'a [u8]> for OpenVaultInstructionData<'a> {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let (id, salt) = match data.len() {
+            0 => (DEFAULT_VAULT_ID, DEFAULT_SALT),
+            8 => (data.try_into().unwrap(), DEFAULT_SALT),
+            40.. => (
+                data[..8].try_into().unwrap(),
+                data[8..40].try_into().unwrap(),
+            ),
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        let flags = data.get(40).copied().unwrap_or(0);
+        if flags & !VaultState::KNOWN_FLAGS != 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let rest = data.get(41..).unwrap_or(&[]);
+        let (daily_limit, rest) = take_u64_if(rest, flags & VaultState::FLAG_DAILY_LIMIT != 0)?;
+        let (min_age_secs, rest) = take_u64_if(rest, flags & VaultState::FLAG_MIN_AGE != 0)?;
+        let rolling = flags & VaultState::FLAG_ROLLING_LIMIT != 0;
+        let (rolling_limit, rest) = take_u64_if(rest, rolling)?;
+        let (rolling_window_secs, rest) = take_u64_if(rest, rolling)?;
+        let (admin, label) = take_pubkey_if(rest, flags & VaultState::FLAG_ADMIN != 0)?;
+        VaultState::check_label(label)?;
+
+        Ok(Self {
+            id,
+            salt,
+            flags,
+            daily_limit,
+            min_age_secs,
+            rolling_limit,
+            rolling_window_secs,
+            admin,
+            label,
+        })
+    }
+}
+
+/// Splits a non-zero `u64` off the front of `data` if `present`, or returns `0`
+/// and `data` as is
+fn take_u64_if(data: &[u8], present: bool) -> Result<(u64, &[u8]), ProgramError> {
+    if !present {
+        return Ok((0, data));
+    }
+
+    let value = data
+        .get(..8)
+        .map(|value| u64::from_le_bytes(value.try_into().unwrap()))
+        .filter(|value| value.ne(&0))
+        .ok_or(ProgramError::InvalidInstructionData)?;
+
+    Ok((value, &data[8..]))
+}
+
+/// Splits a non-zero pubkey off the front of `data` if `present`, or returns
+/// the zero pubkey and `data` as is
+fn take_pubkey_if(data: &[u8], present: bool) -> Result<(Pubkey, &[u8]), ProgramError> {
+    if !present {
+        return Ok(([0; 32], data));
+    }
+
+    let key: Pubkey = data
+        .get(..32)
+        .map(|key| key.try_into().unwrap())
+        .filter(|key: &Pubkey| key.ne(&[0; 32]))
+        .ok_or(ProgramError::InvalidInstructionData)?;
+
+    Ok((key, &data[32..]))
+}
+
+/// OpenVault instruction - creates a persistent vault funded with exactly its rent floor
+pub struct OpenVault<'a> {
+    pub accounts: OpenVaultAccounts<'a>,
+    pub instruction_data: OpenVaultInstructionData<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for OpenVault<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = OpenVaultInstructionData::try_from(data)?;
+        let accounts = OpenVaultAccounts::try_from((accounts, &instruction_data))?;
+
+        // The admin only manages the vault, so the owner can't be its own
+        if instruction_data.admin.eq(accounts.owner.key()) {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> OpenVault<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &5;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let rent_floor = create_vault(
+            self.accounts.owner,
+            self.accounts.owner,
+            self.accounts.vault,
+            &self.instruction_data.id,
+            &self.instruction_data.salt,
+            self.accounts.bumps[0],
+            self.instruction_data.flags,
+            self.accounts.rent_sysvar,
+        )?;
+
+        {
+            let mut state = VaultState::load_mut(self.accounts.vault)?;
+            state.set_label(self.instruction_data.label);
+            state.set_daily_limit(self.instruction_data.daily_limit);
+            state.set_min_age_secs(self.instruction_data.min_age_secs);
+            state.set_rolling_limit(
+                self.instruction_data.rolling_limit,
+                self.instruction_data.rolling_window_secs,
+            );
+            state.set_admin(&self.instruction_data.admin);
+        }
+
+        register_vault(
+            self.accounts.owner,
+            self.accounts.registry,
+            self.accounts.registry_bump,
+            self.accounts.vault.key(),
+            self.accounts.max_vaults,
+            self.accounts.rent_sysvar,
+        )?;
+
+        load_stats(
+            self.accounts.owner,
+            self.accounts.stats,
+            self.accounts.stats_bump,
+            self.accounts.rent_sysvar,
+        )?
+        .record_open(rent_floor);
+
+        Ok(())
+    }
+}
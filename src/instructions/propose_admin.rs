@@ -0,0 +1,95 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+use pinocchio_log::log;
+
+use crate::config::{check_admin, check_config_account, Config};
+
+/// Account structure for the ProposeAdmin instruction
+///
+/// Accounts: `[admin, config]`, where `config` is the initialized config PDA
+/// and `admin` its admin.
+pub struct ProposeAdminAccounts<'a> {
+    pub admin: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ProposeAdminAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [admin, config, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        check_config_account(config)?;
+        check_admin(config, admin)?;
+
+        Ok(Self { admin, config })
+    }
+}
+
+/// Instruction data structure for the ProposeAdmin instruction
+///
+/// Layout: `[pending_admin: Pubkey]`, the zero pubkey cancelling a pending
+/// proposal.
+pub struct ProposeAdminInstructionData {
+    pub pending_admin: Pubkey,
+}
+
+impl<'a> TryFrom<&'a [u8]> for ProposeAdminInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let pending_admin = data
+            .try_into()
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        Ok(Self { pending_admin })
+    }
+}
+
+/// ProposeAdmin instruction - proposes a new admin for the config
+///
+/// The admin only changes once the proposed key signs AcceptAdmin, so a typo
+/// can't hand the config to a key nobody controls. Until then the current
+/// admin keeps every power, and can replace the proposal or cancel it by
+/// proposing the zero pubkey. Anyone but the config's admin fails with
+/// `NotAdmin`.
+pub struct ProposeAdmin<'a> {
+    pub accounts: ProposeAdminAccounts<'a>,
+    pub instruction_data: ProposeAdminInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for ProposeAdmin<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = ProposeAdminInstructionData::try_from(data)?;
+        let accounts = ProposeAdminAccounts::try_from(accounts)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> ProposeAdmin<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &41;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let pending_admin = &self.instruction_data.pending_admin;
+        Config::load_mut(self.accounts.config)?.set_pending_admin(pending_admin);
+
+        if pending_admin.eq(&Pubkey::default()) {
+            log!("ProposeAdmin: admin handoff cancelled");
+        } else {
+            log!("ProposeAdmin: proposed admin");
+            pinocchio::pubkey::log(pending_admin);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+use pinocchio::{
+    account_info::AccountInfo, cpi::set_return_data, program_error::ProgramError, ProgramResult,
+};
+use pinocchio_log::log;
+
+use crate::error::VaultError;
+use crate::state::VaultState;
+use crate::sysvar::clock;
+use crate::vault::{check_account_arrangement, rent_reserve};
+
+/// Reconcile instruction - sets an opened vault's tracked balance to the
+/// lamports it actually holds above its rent floor
+///
+/// Accounts: `[owner, vault, rent_sysvar?]`, where `vault` is an opened vault
+/// recording `owner`. Lamports donated to the vault stay out of every
+/// withdrawal until the owner reconciles them into the balance; its gas reserve
+/// stays earmarked and out of the balance. A vault below
+/// its rent floor fails with `InsufficientFunds` instead of reconciling to less
+/// than nothing, and one whose deposits are locked fails with `DepositsLocked`,
+/// since its balance is frozen. The new balance is set as return data.
+pub struct Reconcile<'a> {
+    pub owner: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    pub rent_sysvar: Option<&'a AccountInfo>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for Reconcile<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        if !data.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let [owner, vault, rent_sysvar @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        check_account_arrangement(owner, vault)?;
+
+        if !owner.is_signer() {
+            return Err(VaultError::MissingOwnerSignature.into());
+        }
+
+        // Only opened vaults track a balance, and the one they record owns it
+        if VaultState::load(vault)?.owner().ne(owner.key()) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        Ok(Self {
+            owner,
+            vault,
+            rent_sysvar: rent_sysvar.first(),
+        })
+    }
+}
+
+impl<'a> Reconcile<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &38;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let mut state = VaultState::load_mut(self.vault)?;
+        let withdrawable = self
+            .vault
+            .lamports()
+            .checked_sub(rent_reserve(self.vault, self.rent_sysvar)?)
+            .and_then(|lamports| lamports.checked_sub(state.gas_reserve()))
+            .ok_or(VaultError::InsufficientFunds)?;
+
+        if state.deposits_locked() {
+            return Err(VaultError::DepositsLocked.into());
+        }
+
+        // Lamports recognized now only earn interest from now on
+        state.accrue(clock()?.unix_timestamp);
+        let previous = state.balance();
+        state.set_balance(withdrawable);
+
+        log!(
+            "Reconcile: balance {} -> {} lamports",
+            previous,
+            withdrawable
+        );
+
+        set_return_data(&withdrawable.to_le_bytes());
+
+        Ok(())
+    }
+}
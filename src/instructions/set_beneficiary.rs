@@ -0,0 +1,124 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+use pinocchio_log::log;
+
+use crate::error::VaultError;
+use crate::state::VaultState;
+use crate::sysvar::clock;
+use crate::vault::check_account_arrangement;
+
+/// Account structure for the SetBeneficiary instruction
+///
+/// Accounts: `[owner, vault]`, where `vault` is an opened vault recording `owner`.
+pub struct SetBeneficiaryAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetBeneficiaryAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [owner, vault, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        check_account_arrangement(owner, vault)?;
+
+        if !owner.is_signer() {
+            return Err(VaultError::MissingOwnerSignature.into());
+        }
+
+        if VaultState::load(vault)?.owner().ne(owner.key()) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        Ok(Self { owner, vault })
+    }
+}
+
+/// Instruction data structure for the SetBeneficiary instruction
+///
+/// Layout: `[beneficiary: Pubkey][inactivity_secs: u64]`, where the zero
+/// pubkey, with `inactivity_secs` of `0`, removes the beneficiary. A
+/// beneficiary needs a non-zero `inactivity_secs` and can't be the owner.
+pub struct SetBeneficiaryInstructionData {
+    pub beneficiary: Pubkey,
+    pub inactivity_secs: u64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for SetBeneficiaryInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&40) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let beneficiary: Pubkey = data[..32].try_into().unwrap();
+        let inactivity_secs = u64::from_le_bytes(data[32..].try_into().unwrap());
+
+        // Instruction Checks
+        if beneficiary.eq(&[0; 32]).ne(&inactivity_secs.eq(&0)) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            beneficiary,
+            inactivity_secs,
+        })
+    }
+}
+
+/// SetBeneficiary instruction - names who can claim an opened vault once its
+/// owner goes quiet
+///
+/// Once the owner makes no deposit or withdrawal for `inactivity_secs`, the
+/// beneficiary can take the whole vault with BeneficiaryClaim. Setting or
+/// removing the beneficiary counts as activity too, and replaces any previous
+/// one.
+pub struct SetBeneficiary<'a> {
+    pub accounts: SetBeneficiaryAccounts<'a>,
+    pub instruction_data: SetBeneficiaryInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SetBeneficiary<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = SetBeneficiaryInstructionData::try_from(data)?;
+        let accounts = SetBeneficiaryAccounts::try_from(accounts)?;
+
+        // The owner can already withdraw, so naming themselves would only
+        // confuse clients
+        if instruction_data.beneficiary.eq(accounts.owner.key()) {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetBeneficiary<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &62;
+
+    pub fn process(&mut self) -> ProgramResult {
+        VaultState::load_mut(self.accounts.vault)?.set_beneficiary(
+            &self.instruction_data.beneficiary,
+            self.instruction_data.inactivity_secs,
+            clock()?.unix_timestamp,
+        );
+
+        log!(
+            "SetBeneficiary: claimable after {} inactive seconds",
+            self.instruction_data.inactivity_secs
+        );
+
+        Ok(())
+    }
+}
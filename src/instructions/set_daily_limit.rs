@@ -0,0 +1,97 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+use pinocchio_log::log;
+
+use crate::error::VaultError;
+use crate::state::VaultState;
+use crate::vault::check_vault_manager;
+
+/// Account structure for the SetDailyLimit instruction
+///
+/// Accounts: `[admin, vault]`, where `vault` is an opened vault recording
+/// `admin` as its admin.
+pub struct SetDailyLimitAccounts<'a> {
+    pub admin: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetDailyLimitAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [admin, vault, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        // The owner could lift the limit meant to protect the vault from its
+        // own key, so only an admin can change it
+        if !VaultState::load(vault)?.has_admin() {
+            return Err(VaultError::NotVaultAdmin.into());
+        }
+
+        check_vault_manager(admin, vault)?;
+
+        Ok(Self { admin, vault })
+    }
+}
+
+/// Instruction data structure for the SetDailyLimit instruction
+///
+/// Layout: `[daily_limit: u64]`, or `0` to lift the limit.
+pub struct SetDailyLimitInstructionData {
+    pub daily_limit: u64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for SetDailyLimitInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let daily_limit = data
+            .try_into()
+            .map(u64::from_le_bytes)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        Ok(Self { daily_limit })
+    }
+}
+
+/// SetDailyLimit instruction - replaces the daily withdrawal limit of an opened
+/// vault that has an admin
+///
+/// Lamports already withdrawn in the current window still count against the
+/// new limit. Anyone but the vault's admin, its owner included, fails with
+/// `NotVaultAdmin`.
+pub struct SetDailyLimit<'a> {
+    pub accounts: SetDailyLimitAccounts<'a>,
+    pub instruction_data: SetDailyLimitInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SetDailyLimit<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = SetDailyLimitInstructionData::try_from(data)?;
+        let accounts = SetDailyLimitAccounts::try_from(accounts)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetDailyLimit<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &67;
+
+    pub fn process(&mut self) -> ProgramResult {
+        VaultState::load_mut(self.accounts.vault)?
+            .set_daily_limit(self.instruction_data.daily_limit);
+
+        log!(
+            "SetDailyLimit: {} lamports a day",
+            self.instruction_data.daily_limit
+        );
+
+        Ok(())
+    }
+}
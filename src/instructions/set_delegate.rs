@@ -0,0 +1,119 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+use pinocchio_log::log;
+
+use crate::error::VaultError;
+use crate::state::VaultState;
+use crate::vault::check_account_arrangement;
+
+/// Account structure for the SetDelegate instruction
+///
+/// Accounts: `[owner, vault]`, where `vault` is an opened vault recording `owner`.
+pub struct SetDelegateAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetDelegateAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [owner, vault, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        check_account_arrangement(owner, vault)?;
+
+        if !owner.is_signer() {
+            return Err(VaultError::MissingOwnerSignature.into());
+        }
+
+        if VaultState::load(vault)?.owner().ne(owner.key()) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        Ok(Self { owner, vault })
+    }
+}
+
+/// Instruction data structure for the SetDelegate instruction
+///
+/// Layout: `[delegate: Pubkey][max_per_withdrawal: u64]`, where a zero cap
+/// removes the delegate.
+pub struct SetDelegateInstructionData {
+    pub delegate: Pubkey,
+    pub max_per_withdrawal: u64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for SetDelegateInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&40) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            delegate: data[..32].try_into().unwrap(),
+            max_per_withdrawal: u64::from_le_bytes(data[32..].try_into().unwrap()),
+        })
+    }
+}
+
+/// SetDelegate instruction - lets another key withdraw from an opened vault,
+/// up to a cap per withdrawal
+///
+/// Adds `delegate` or updates its cap, or removes it for a zero cap. A vault
+/// holds up to `VaultState::MAX_DELEGATES` delegates; adding one more, or
+/// removing a key that isn't a delegate, fails with `InvalidArgument`. The
+/// owner delegating to itself fails with `InvalidAccountArrangement`, since
+/// it withdraws without a cap anyway.
+pub struct SetDelegate<'a> {
+    pub accounts: SetDelegateAccounts<'a>,
+    pub instruction_data: SetDelegateInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SetDelegate<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = SetDelegateInstructionData::try_from(data)?;
+        let accounts = SetDelegateAccounts::try_from(accounts)?;
+
+        if instruction_data.delegate.eq(accounts.owner.key())
+            || instruction_data.delegate.eq(accounts.vault.key())
+        {
+            return Err(VaultError::InvalidAccountArrangement.into());
+        }
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetDelegate<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &58;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let mut state = VaultState::load_mut(self.accounts.vault)?;
+        let delegate = &self.instruction_data.delegate;
+
+        if self.instruction_data.max_per_withdrawal.eq(&0) {
+            state.remove_delegate(delegate)?;
+            log!("SetDelegate: removed delegate");
+        } else {
+            state.set_delegate(delegate, self.instruction_data.max_per_withdrawal)?;
+            log!(
+                "SetDelegate: delegate may withdraw {} lamports at a time",
+                self.instruction_data.max_per_withdrawal
+            );
+        }
+        pinocchio::pubkey::log(delegate);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::state::VaultState;
+use crate::vault::check_vault_manager;
+
+/// Account structure for the SetDepositsLocked instruction
+///
+/// Accounts: `[authority, vault]`, where `vault` is an opened vault and
+/// `authority` its admin, or the owner it records if it has none.
+pub struct SetDepositsLockedAccounts<'a> {
+    pub authority: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetDepositsLockedAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [authority, vault, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        // Only opened vaults have state to lock, and their manager decides
+        check_vault_manager(authority, vault)?;
+
+        Ok(Self { authority, vault })
+    }
+}
+
+/// Instruction data structure for the SetDepositsLocked instruction
+///
+/// Layout: `[locked: u8]`, `1` to lock deposits and `0` to unlock them.
+pub struct SetDepositsLockedInstructionData {
+    pub locked: bool,
+}
+
+impl<'a> TryFrom<&'a [u8]> for SetDepositsLockedInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let locked = match data {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        Ok(Self { locked })
+    }
+}
+
+/// SetDepositsLocked instruction - freezes or unfreezes an opened vault's balance
+///
+/// While locked, every deposit into the vault fails with `DepositsLocked`.
+/// Withdrawals aren't affected. Only the vault's admin, or its owner if it has
+/// none, can lock and unlock it.
+pub struct SetDepositsLocked<'a> {
+    pub accounts: SetDepositsLockedAccounts<'a>,
+    pub instruction_data: SetDepositsLockedInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SetDepositsLocked<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = SetDepositsLockedInstructionData::try_from(data)?;
+        let accounts = SetDepositsLockedAccounts::try_from(accounts)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetDepositsLocked<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &26;
+
+    pub fn process(&mut self) -> ProgramResult {
+        VaultState::load_mut(self.accounts.vault)?
+            .set_deposits_locked(self.instruction_data.locked);
+
+        Ok(())
+    }
+}
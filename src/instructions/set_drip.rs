@@ -0,0 +1,131 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use crate::error::VaultError;
+use crate::state::VaultState;
+use crate::sysvar::clock;
+use crate::vault::{
+    check_account_arrangement, check_not_escrowed, check_not_multisig, check_not_vesting,
+};
+
+/// Account structure for the SetDrip instruction
+///
+/// Accounts: `[owner, vault]`, where `vault` is an opened vault recording `owner`.
+pub struct SetDripAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetDripAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [owner, vault, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        check_account_arrangement(owner, vault)?;
+
+        if !owner.is_signer() {
+            return Err(VaultError::MissingOwnerSignature.into());
+        }
+
+        // Only opened vaults have state to hold a schedule, and the one they record owns it
+        if VaultState::load(vault)?.owner().ne(owner.key()) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        // A drip releases lamports without the agent or multisig signers, and can't break a
+        // vesting schedule
+        check_not_escrowed(vault)?;
+        check_not_multisig(vault)?;
+        check_not_vesting(vault)?;
+
+        Ok(Self { owner, vault })
+    }
+}
+
+/// Instruction data structure for the SetDrip instruction
+///
+/// Layout: `[interval_secs: u64][amount_per_interval: u64][recipient: Pubkey]`.
+/// A zero `interval_secs` clears the schedule, and takes zeroes for the rest.
+pub struct SetDripInstructionData {
+    pub interval_secs: u64,
+    pub amount_per_interval: u64,
+    pub recipient: Pubkey,
+}
+
+impl<'a> TryFrom<&'a [u8]> for SetDripInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&48) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let interval_secs = u64::from_le_bytes(data[..8].try_into().unwrap());
+        let amount_per_interval = u64::from_le_bytes(data[8..16].try_into().unwrap());
+        let recipient: Pubkey = data[16..48].try_into().unwrap();
+
+        // Instruction Checks
+        let valid = match interval_secs {
+            0 => amount_per_interval.eq(&0) && recipient.eq(&Pubkey::default()),
+            _ => amount_per_interval.ne(&0) && recipient.ne(&Pubkey::default()),
+        };
+        if !valid {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            interval_secs,
+            amount_per_interval,
+            recipient,
+        })
+    }
+}
+
+/// SetDrip instruction - schedules recurring releases from an opened vault
+///
+/// Once set, anyone can Crank `amount_per_interval` lamports to `recipient` for
+/// every `interval_secs` elapsed, the first interval counted from now. Setting
+/// a new schedule restarts that count. The vault itself as recipient fails
+/// with `InvalidRecipient`, and escrowed or vesting vaults can't drip.
+pub struct SetDrip<'a> {
+    pub accounts: SetDripAccounts<'a>,
+    pub instruction_data: SetDripInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SetDrip<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = SetDripInstructionData::try_from(data)?;
+        let accounts = SetDripAccounts::try_from(accounts)?;
+
+        if instruction_data.recipient.eq(accounts.vault.key()) {
+            return Err(VaultError::InvalidRecipient.into());
+        }
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetDrip<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &46;
+
+    pub fn process(&mut self) -> ProgramResult {
+        VaultState::load_mut(self.accounts.vault)?.set_drip(
+            self.instruction_data.interval_secs,
+            self.instruction_data.amount_per_interval,
+            &self.instruction_data.recipient,
+            clock()?.unix_timestamp,
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use crate::error::VaultError;
+use crate::state::VaultState;
+use crate::vault::check_account_arrangement;
+
+/// Account structure for the SetEscrowAgent instruction
+///
+/// Accounts: `[owner, vault]`, where `vault` is an opened vault recording `owner`.
+pub struct SetEscrowAgentAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetEscrowAgentAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [owner, vault, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        check_account_arrangement(owner, vault)?;
+
+        if !owner.is_signer() {
+            return Err(VaultError::MissingOwnerSignature.into());
+        }
+
+        // Only opened vaults have state to hold the agent, and the one they record owns it
+        let state = VaultState::load(vault)?;
+        if state.owner().ne(owner.key()) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        // The owner gives up withdrawals for good, so an agent can't be replaced
+        if state.is_escrowed() {
+            return Err(VaultError::EscrowAgentSet.into());
+        }
+
+        // Handing a multisig vault to an agent would let the owner alone move it
+        if state.is_multisig() {
+            return Err(VaultError::MultisigThresholdNotMet.into());
+        }
+
+        Ok(Self { owner, vault })
+    }
+}
+
+/// Instruction data structure for the SetEscrowAgent instruction
+///
+/// Layout: `[agent: Pubkey]`, which can't be the zero pubkey.
+pub struct SetEscrowAgentInstructionData {
+    pub agent: Pubkey,
+}
+
+impl<'a> TryFrom<&'a [u8]> for SetEscrowAgentInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let agent: Pubkey = data
+            .try_into()
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        // Instruction Checks
+        if agent.eq(&[0; 32]) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { agent })
+    }
+}
+
+/// SetEscrowAgent instruction - turns an opened vault into a sink only an
+/// escrow agent can withdraw from
+///
+/// Deposits keep working, but every withdrawal needs the agent's signature
+/// in place of the owner's; the paths that can't take it fail with
+/// `EscrowedVault`. The agent is set once and for all, so a second call fails
+/// with `EscrowAgentSet`.
+pub struct SetEscrowAgent<'a> {
+    pub accounts: SetEscrowAgentAccounts<'a>,
+    pub instruction_data: SetEscrowAgentInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SetEscrowAgent<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = SetEscrowAgentInstructionData::try_from(data)?;
+        let accounts = SetEscrowAgentAccounts::try_from(accounts)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetEscrowAgent<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &33;
+
+    pub fn process(&mut self) -> ProgramResult {
+        VaultState::load_mut(self.accounts.vault)?.set_escrow_agent(&self.instruction_data.agent);
+
+        Ok(())
+    }
+}
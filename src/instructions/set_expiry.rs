@@ -0,0 +1,97 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+use pinocchio_log::log;
+
+use crate::error::VaultError;
+use crate::state::VaultState;
+use crate::vault::check_account_arrangement;
+
+/// Account structure for the SetExpiry instruction
+///
+/// Accounts: `[owner, vault]`, where `vault` is an opened vault recording `owner`.
+pub struct SetExpiryAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetExpiryAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [owner, vault, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        check_account_arrangement(owner, vault)?;
+
+        if !owner.is_signer() {
+            return Err(VaultError::MissingOwnerSignature.into());
+        }
+
+        if VaultState::load(vault)?.owner().ne(owner.key()) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        Ok(Self { owner, vault })
+    }
+}
+
+/// Instruction data structure for the SetExpiry instruction
+///
+/// Layout: `[expiry_ts: i64]`, a unix timestamp, or `0` to clear the expiry.
+pub struct SetExpiryInstructionData {
+    pub expiry_ts: i64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for SetExpiryInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let expiry_ts = data
+            .try_into()
+            .map(i64::from_le_bytes)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        Ok(Self { expiry_ts })
+    }
+}
+
+/// SetExpiry instruction - sets when an opened vault expires
+///
+/// Once `expiry_ts` passes, anyone can send the whole vault to its owner with
+/// WithdrawExpired. Before then only the owner withdraws, as usual. Expiring
+/// only hands the vault back to its owner, so the owner sets it even when the
+/// vault has an admin.
+pub struct SetExpiry<'a> {
+    pub accounts: SetExpiryAccounts<'a>,
+    pub instruction_data: SetExpiryInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SetExpiry<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = SetExpiryInstructionData::try_from(data)?;
+        let accounts = SetExpiryAccounts::try_from(accounts)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetExpiry<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &51;
+
+    pub fn process(&mut self) -> ProgramResult {
+        VaultState::load_mut(self.accounts.vault)?.set_expiry_ts(self.instruction_data.expiry_ts);
+
+        log!(
+            "SetExpiry: vault expires at {}",
+            self.instruction_data.expiry_ts
+        );
+
+        Ok(())
+    }
+}
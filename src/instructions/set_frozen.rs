@@ -0,0 +1,84 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::state::VaultState;
+use crate::vault::check_vault_manager;
+
+/// Account structure for the SetFrozen instruction
+///
+/// Accounts: `[authority, vault]`, where `vault` is an opened vault and
+/// `authority` its admin, or the owner it records if it has none.
+pub struct SetFrozenAccounts<'a> {
+    pub authority: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetFrozenAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [authority, vault, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        // Only opened vaults have state to freeze, and their manager decides
+        check_vault_manager(authority, vault)?;
+
+        Ok(Self { authority, vault })
+    }
+}
+
+/// Instruction data structure for the SetFrozen instruction
+///
+/// Layout: `[frozen: u8]`, `1` to freeze the vault and `0` to unfreeze it.
+pub struct SetFrozenInstructionData {
+    pub frozen: bool,
+}
+
+impl<'a> TryFrom<&'a [u8]> for SetFrozenInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let frozen = match data {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        Ok(Self { frozen })
+    }
+}
+
+/// SetFrozen instruction - freezes or unfreezes an opened vault
+///
+/// While frozen, every path that moves lamports into or out of the vault fails
+/// with `VaultFrozen`. Only the vault's admin, or its owner if it has none,
+/// can freeze and unfreeze it.
+pub struct SetFrozen<'a> {
+    pub accounts: SetFrozenAccounts<'a>,
+    pub instruction_data: SetFrozenInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SetFrozen<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = SetFrozenInstructionData::try_from(data)?;
+        let accounts = SetFrozenAccounts::try_from(accounts)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetFrozen<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &37;
+
+    pub fn process(&mut self) -> ProgramResult {
+        VaultState::load_mut(self.accounts.vault)?.set_frozen(self.instruction_data.frozen);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::error::VaultError;
+use crate::state::VaultState;
+use crate::vault::check_account_arrangement;
+
+/// Account structure for the SetLabel instruction
+///
+/// Accounts: `[owner, vault]`, where `vault` is an opened vault recording `owner`.
+pub struct SetLabelAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetLabelAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [owner, vault, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        check_account_arrangement(owner, vault)?;
+
+        if !owner.is_signer() {
+            return Err(VaultError::MissingOwnerSignature.into());
+        }
+
+        // Only opened vaults hold a label, and the one they record owns it
+        if VaultState::load(vault)?.owner().ne(owner.key()) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        Ok(Self { owner, vault })
+    }
+}
+
+/// SetLabel instruction - replaces an opened vault's label
+///
+/// The instruction data is the new label, up to `VaultState::MAX_LABEL_LEN`
+/// bytes of UTF-8; empty data clears it.
+pub struct SetLabel<'a> {
+    pub accounts: SetLabelAccounts<'a>,
+    pub label: &'a [u8],
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SetLabel<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        VaultState::check_label(data)?;
+        let accounts = SetLabelAccounts::try_from(accounts)?;
+
+        Ok(Self {
+            accounts,
+            label: data,
+        })
+    }
+}
+
+impl<'a> SetLabel<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &8;
+
+    pub fn process(&mut self) -> ProgramResult {
+        VaultState::load_mut(self.accounts.vault)?.set_label(self.label);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,95 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::error::VaultError;
+use crate::metadata::{check_metadata_account, load_metadata, VaultMetadata};
+use crate::state::VaultState;
+use crate::vault::check_account_arrangement;
+
+/// Account structure for the SetMetadata instruction
+///
+/// Accounts: `[owner, vault, metadata, system_program, rent_sysvar?]`, where
+/// `vault` is an opened vault recording `owner` and `metadata` is its
+/// `["meta", vault]` PDA, created or not. The owner pays for creating it.
+pub struct SetMetadataAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    pub metadata: &'a AccountInfo,
+    pub rent_sysvar: Option<&'a AccountInfo>,
+    pub metadata_bump: u8,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetMetadataAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [owner, vault, metadata, system_program, rent_sysvar @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        check_account_arrangement(owner, vault)?;
+
+        if !owner.is_signer() {
+            return Err(VaultError::MissingOwnerSignature.into());
+        }
+
+        if system_program.key().ne(&pinocchio_system::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        // Only opened vaults have metadata, and the one they record owns it
+        if VaultState::load(vault)?.owner().ne(owner.key()) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        let metadata_bump = check_metadata_account(vault, metadata)?;
+
+        Ok(Self {
+            owner,
+            vault,
+            metadata,
+            rent_sysvar: rent_sysvar.first(),
+            metadata_bump,
+        })
+    }
+}
+
+/// SetMetadata instruction - replaces the memo in an opened vault's metadata
+///
+/// The instruction data is the new memo, up to `VaultMetadata::MAX_MEMO_LEN`
+/// bytes of UTF-8; empty data clears it but keeps the account.
+pub struct SetMetadata<'a> {
+    pub accounts: SetMetadataAccounts<'a>,
+    pub memo: &'a [u8],
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SetMetadata<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        VaultMetadata::check_memo(data)?;
+        let accounts = SetMetadataAccounts::try_from(accounts)?;
+
+        Ok(Self {
+            accounts,
+            memo: data,
+        })
+    }
+}
+
+impl<'a> SetMetadata<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &36;
+
+    pub fn process(&mut self) -> ProgramResult {
+        load_metadata(
+            self.accounts.owner,
+            self.accounts.vault,
+            self.accounts.metadata,
+            self.accounts.metadata_bump,
+            self.accounts.rent_sysvar,
+        )?
+        .set_memo(self.memo);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use crate::state::VaultState;
+use crate::vault::check_vault_manager;
+
+/// Account structure for the SetOracle instruction
+///
+/// Accounts: `[authority, vault]`, where `vault` is an opened vault and
+/// `authority` its admin, or the owner it records if it has none.
+pub struct SetOracleAccounts<'a> {
+    pub authority: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetOracleAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [authority, vault, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        // Only opened vaults can be gated, and their manager decides
+        check_vault_manager(authority, vault)?;
+
+        Ok(Self { authority, vault })
+    }
+}
+
+/// Instruction data structure for the SetOracle instruction
+///
+/// Layout: `[oracle: Pubkey][max_price_age: u64]`, with `max_price_age` in
+/// seconds. The zero pubkey removes the gate.
+pub struct SetOracleInstructionData {
+    pub oracle: Pubkey,
+    pub max_price_age: u64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for SetOracleInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&40) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            oracle: data[..32].try_into().unwrap(),
+            max_price_age: u64::from_le_bytes(data[32..40].try_into().unwrap()),
+        })
+    }
+}
+
+/// SetOracle instruction - gates an opened vault's withdrawals on a fresh oracle price
+pub struct SetOracle<'a> {
+    pub accounts: SetOracleAccounts<'a>,
+    pub instruction_data: SetOracleInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SetOracle<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = SetOracleInstructionData::try_from(data)?;
+        let accounts = SetOracleAccounts::try_from(accounts)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetOracle<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &9;
+
+    pub fn process(&mut self) -> ProgramResult {
+        VaultState::load_mut(self.accounts.vault)?.set_oracle(
+            &self.instruction_data.oracle,
+            self.instruction_data.max_price_age,
+        );
+
+        Ok(())
+    }
+}
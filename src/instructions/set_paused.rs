@@ -0,0 +1,94 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+use pinocchio_log::log;
+
+use crate::config::{check_admin, check_config_account, Config};
+use crate::sysvar::clock;
+
+/// Account structure for the SetPaused instruction
+///
+/// Accounts: `[admin, config]`, where `config` is the initialized config PDA
+/// and `admin` its admin.
+pub struct SetPausedAccounts<'a> {
+    pub admin: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetPausedAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [admin, config, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        check_config_account(config)?;
+        check_admin(config, admin)?;
+
+        Ok(Self { admin, config })
+    }
+}
+
+/// Instruction data structure for the SetPaused instruction
+///
+/// Layout: `[paused: u8]`, `1` to pause the program and `0` to unpause it.
+pub struct SetPausedInstructionData {
+    pub paused: bool,
+}
+
+impl<'a> TryFrom<&'a [u8]> for SetPausedInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let paused = match data {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        Ok(Self { paused })
+    }
+}
+
+/// SetPaused instruction - pauses or unpauses the program
+///
+/// While paused, deposits fail with `ProgramPaused`, and once the program has
+/// been paused for `Config::EMERGENCY_DRAIN_DELAY_SECS` the admin can move vault
+/// funds to the config's recovery address with EmergencyDrain. Owners can still
+/// withdraw, so the delay gives them time to leave first. Unpausing resets it.
+/// Anyone but the config's admin fails with `NotAdmin`.
+pub struct SetPaused<'a> {
+    pub accounts: SetPausedAccounts<'a>,
+    pub instruction_data: SetPausedInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SetPaused<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = SetPausedInstructionData::try_from(data)?;
+        let accounts = SetPausedAccounts::try_from(accounts)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetPaused<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &39;
+
+    pub fn process(&mut self) -> ProgramResult {
+        Config::load_mut(self.accounts.config)?
+            .set_paused(self.instruction_data.paused, clock()?.unix_timestamp);
+
+        if self.instruction_data.paused {
+            log!("SetPaused: program paused");
+        } else {
+            log!("SetPaused: program unpaused");
+        }
+
+        Ok(())
+    }
+}
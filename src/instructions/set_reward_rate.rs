@@ -0,0 +1,107 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+use pinocchio_log::log;
+
+use crate::config::{check_admin, check_config_account};
+use crate::logic::BPS_DENOMINATOR;
+use crate::state::VaultState;
+use crate::sysvar::clock;
+
+/// Account structure for the SetRewardRate instruction
+///
+/// Accounts: `[admin, config, vault]`, where `config` is the initialized config
+/// PDA, `admin` its admin and `vault` any opened vault.
+pub struct SetRewardRateAccounts<'a> {
+    pub admin: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetRewardRateAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [admin, config, vault, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        check_config_account(config)?;
+        check_admin(config, admin)?;
+
+        // Only opened vaults have state to accrue interest in
+        VaultState::load(vault)?;
+
+        Ok(Self {
+            admin,
+            config,
+            vault,
+        })
+    }
+}
+
+/// Instruction data structure for the SetRewardRate instruction
+///
+/// Layout: `[rate_bps: u16]`, a yearly rate of at most `BPS_DENOMINATOR`, or
+/// `0` to stop the vault accruing.
+pub struct SetRewardRateInstructionData {
+    pub rate_bps: u16,
+}
+
+impl<'a> TryFrom<&'a [u8]> for SetRewardRateInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let rate_bps = data
+            .try_into()
+            .map(u16::from_le_bytes)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        // Instruction Checks
+        if rate_bps as u64 > BPS_DENOMINATOR {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { rate_bps })
+    }
+}
+
+/// SetRewardRate instruction - sets the yearly interest an opened vault earns
+/// from the reward pool
+///
+/// Interest accrued at the previous rate is kept for the next ClaimReward, and
+/// the new rate applies from now on. Anyone but the config's admin fails with
+/// `NotAdmin`.
+pub struct SetRewardRate<'a> {
+    pub accounts: SetRewardRateAccounts<'a>,
+    pub instruction_data: SetRewardRateInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SetRewardRate<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = SetRewardRateInstructionData::try_from(data)?;
+        let accounts = SetRewardRateAccounts::try_from(accounts)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetRewardRate<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &64;
+
+    pub fn process(&mut self) -> ProgramResult {
+        VaultState::load_mut(self.accounts.vault)?
+            .set_reward_rate(self.instruction_data.rate_bps, clock()?.unix_timestamp);
+
+        log!(
+            "SetRewardRate: {} bps a year",
+            self.instruction_data.rate_bps
+        );
+
+        Ok(())
+    }
+}
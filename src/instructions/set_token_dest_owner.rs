@@ -0,0 +1,94 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use crate::error::VaultError;
+use crate::state::VaultState;
+use crate::vault::check_account_arrangement;
+
+/// Account structure for the SetTokenDestOwner instruction
+///
+/// Accounts: `[owner, vault]`, where `vault` is an opened vault recording `owner`.
+pub struct SetTokenDestOwnerAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetTokenDestOwnerAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [owner, vault, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        check_account_arrangement(owner, vault)?;
+
+        if !owner.is_signer() {
+            return Err(VaultError::MissingOwnerSignature.into());
+        }
+
+        // Only opened vaults have state to hold the restriction, and the one they record owns it
+        if VaultState::load(vault)?.owner().ne(owner.key()) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        Ok(Self { owner, vault })
+    }
+}
+
+/// Instruction data structure for the SetTokenDestOwner instruction
+///
+/// Layout: `[dest_owner: Pubkey]`, the zero pubkey to lift the restriction.
+pub struct SetTokenDestOwnerInstructionData {
+    pub dest_owner: Pubkey,
+}
+
+impl<'a> TryFrom<&'a [u8]> for SetTokenDestOwnerInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let dest_owner: Pubkey = data
+            .try_into()
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        Ok(Self { dest_owner })
+    }
+}
+
+/// SetTokenDestOwner instruction - restricts which token accounts SweepToken
+/// can send an opened vault's tokens to
+///
+/// Once set, SweepToken fails with `DestinationNotAllowed` for a destination
+/// token account owned by anyone else; the token program already enforces its
+/// mint.
+pub struct SetTokenDestOwner<'a> {
+    pub accounts: SetTokenDestOwnerAccounts<'a>,
+    pub instruction_data: SetTokenDestOwnerInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SetTokenDestOwner<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = SetTokenDestOwnerInstructionData::try_from(data)?;
+        let accounts = SetTokenDestOwnerAccounts::try_from(accounts)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetTokenDestOwner<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &32;
+
+    pub fn process(&mut self) -> ProgramResult {
+        VaultState::load_mut(self.accounts.vault)?
+            .set_token_dest_owner(&self.instruction_data.dest_owner);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,138 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+use pinocchio_log::log;
+
+use crate::config::{check_admin, check_config_account, Config};
+use crate::error::VaultError;
+use crate::logic::BPS_DENOMINATOR;
+
+/// Account structure for the SetWithdrawFee instruction
+///
+/// Accounts: `[admin, config]`, where `config` is the initialized config PDA
+/// and `admin` its admin.
+pub struct SetWithdrawFeeAccounts<'a> {
+    pub admin: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetWithdrawFeeAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [admin, config, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        check_config_account(config)?;
+        check_admin(config, admin)?;
+
+        Ok(Self { admin, config })
+    }
+}
+
+/// Instruction data structure for the SetWithdrawFee instruction
+///
+/// Layout: `[fee_bps: u16][treasury: Pubkey][exempt: [Pubkey]]`, with `fee_bps`
+/// at most `BPS_DENOMINATOR` and up to `Config::MAX_FEE_EXEMPT` exempt owners.
+/// A non-zero `fee_bps` needs a non-zero `treasury`, or fails with
+/// `InvalidTreasury`.
+pub struct SetWithdrawFeeInstructionData {
+    pub fee_bps: u16,
+    pub treasury: Pubkey,
+    pub exempt: [Pubkey; Config::MAX_FEE_EXEMPT],
+    pub exempt_count: usize,
+}
+
+impl<'a> TryFrom<&'a [u8]> for SetWithdrawFeeInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() < 34 || !(data.len() - 34).is_multiple_of(32) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let exempt_count = (data.len() - 34) / 32;
+        if exempt_count > Config::MAX_FEE_EXEMPT {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let fee_bps = u16::from_le_bytes(data[..2].try_into().unwrap());
+        if fee_bps as u64 > BPS_DENOMINATOR {
+            return Err(VaultError::InvalidFee.into());
+        }
+
+        let treasury: Pubkey = data[2..34].try_into().unwrap();
+        if fee_bps.ne(&0) && treasury.eq(&Pubkey::default()) {
+            return Err(VaultError::InvalidTreasury.into());
+        }
+
+        let mut exempt = [Pubkey::default(); Config::MAX_FEE_EXEMPT];
+        for (entry, key) in exempt.iter_mut().zip(data[34..].chunks_exact(32)) {
+            *entry = key.try_into().unwrap();
+        }
+
+        Ok(Self {
+            fee_bps,
+            treasury,
+            exempt,
+            exempt_count,
+        })
+    }
+}
+
+/// SetWithdrawFee instruction - sets the fee every withdrawal pays the
+/// treasury, and the owners it's waived for
+///
+/// Replaces any previous fee, treasury and exempt owners. While the fee is
+/// non-zero, every withdrawal path passes the treasury right after the config
+/// and any hook program, and pays it `fee_bps` of the lamports it moves out of
+/// the vault, rounded as the config's `fee_rounding` says; exempt owners pay
+/// nothing, but still pass the treasury. A zero `fee_bps` turns the fee off.
+/// Anyone but the config's admin fails with `NotAdmin`.
+pub struct SetWithdrawFee<'a> {
+    pub accounts: SetWithdrawFeeAccounts<'a>,
+    pub instruction_data: SetWithdrawFeeInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SetWithdrawFee<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = SetWithdrawFeeInstructionData::try_from(data)?;
+        let accounts = SetWithdrawFeeAccounts::try_from(accounts)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetWithdrawFee<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &61;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let SetWithdrawFeeInstructionData {
+            fee_bps,
+            treasury,
+            exempt,
+            exempt_count,
+        } = &self.instruction_data;
+
+        Config::load_mut(self.accounts.config)?.set_withdraw_fee(
+            *fee_bps,
+            treasury,
+            &exempt[..*exempt_count],
+        );
+
+        log!(
+            "SetWithdrawFee: {} bps, {} exempt owners",
+            *fee_bps,
+            *exempt_count
+        );
+
+        Ok(())
+    }
+}
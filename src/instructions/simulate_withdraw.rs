@@ -0,0 +1,46 @@
+use pinocchio::{
+    account_info::AccountInfo, cpi::set_return_data, program_error::ProgramError, ProgramResult,
+};
+
+use crate::instructions::Withdraw;
+
+/// SimulateWithdraw instruction - previews a Withdraw without moving any lamports
+///
+/// Takes the same accounts and instruction data as Withdraw and runs all of
+/// its checks, then always succeeds with `[error: u64][lamports: u64]` as
+/// return data: `error` is `0` and `lamports` the amount the withdraw would
+/// move, or `error` is the code the withdraw would fail with and `lamports` is
+/// `0`. Custom codes are the `VaultError` values.
+pub struct SimulateWithdraw<'a> {
+    pub data: &'a [u8],
+    pub accounts: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SimulateWithdraw<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        Ok(Self { data, accounts })
+    }
+}
+
+impl<'a> SimulateWithdraw<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &11;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let outcome = Withdraw::try_from((self.data, self.accounts))
+            .and_then(|withdraw| withdraw.amount());
+
+        let (error, lamports) = match outcome {
+            Ok(lamports) => (0, lamports),
+            Err(error) => (u64::from(error), 0),
+        };
+
+        let mut result = [0u8; 16];
+        result[..8].copy_from_slice(&error.to_le_bytes());
+        result[8..].copy_from_slice(&lamports.to_le_bytes());
+        set_return_data(&result);
+
+        Ok(())
+    }
+}
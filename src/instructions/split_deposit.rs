@@ -0,0 +1,179 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+use pinocchio_system::instructions::Transfer;
+
+use crate::logic::{bps_of, BPS_DENOMINATOR};
+use crate::error::VaultError;
+use crate::pda::DEFAULT_SALT;
+use crate::stats::{check_stats_account, load_stats};
+use crate::vault::{check_deposit_vault, record_vault_deposit};
+
+/// Account structure for the SplitDeposit instruction
+///
+/// Accounts: `[owner, first_vault, second_vault, system_program, stats, rent_sysvar?]`.
+pub struct SplitDepositAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub first_vault: &'a AccountInfo,
+    pub second_vault: &'a AccountInfo,
+    pub stats: &'a AccountInfo,
+    pub rent_sysvar: Option<&'a AccountInfo>,
+    pub stats_bump: u8,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &SplitDepositInstructionData)> for SplitDepositAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(
+        (accounts, instruction_data): (&'a [AccountInfo], &SplitDepositInstructionData),
+    ) -> Result<Self, Self::Error> {
+        let [owner, first_vault, second_vault, system_program, stats, rent_sysvar @ ..] = accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        if !owner.is_signer() {
+            return Err(VaultError::MissingOwnerSignature.into());
+        }
+
+        if system_program.key().ne(&pinocchio_system::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        check_deposit_vault(
+            owner,
+            first_vault,
+            &instruction_data.first_id,
+            &DEFAULT_SALT,
+            rent_sysvar.first(),
+            false,
+        )?;
+        check_deposit_vault(
+            owner,
+            second_vault,
+            &instruction_data.second_id,
+            &DEFAULT_SALT,
+            rent_sysvar.first(),
+            false,
+        )?;
+
+        let stats_bump = check_stats_account(stats)?;
+
+        Ok(Self {
+            owner,
+            first_vault,
+            second_vault,
+            stats,
+            rent_sysvar: rent_sysvar.first(),
+            stats_bump,
+        })
+    }
+}
+
+/// Instruction data structure for the SplitDeposit instruction
+///
+/// Layout: `[amount: u64][split_bps: u16][first_id: u64][second_id: u64]`. The
+/// first vault receives `split_bps` basis points of `amount`, rounded down, and
+/// the second one the rest. Only unsalted vaults can be split into, and they
+/// have to be distinct.
+pub struct SplitDepositInstructionData {
+    pub amount: u64,
+    pub split_bps: u16,
+    pub first_id: [u8; 8],
+    pub second_id: [u8; 8],
+}
+
+impl<'a> TryFrom<&'a [u8]> for SplitDepositInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&26) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let amount = u64::from_le_bytes(data[..8].try_into().unwrap());
+        let split_bps = u16::from_le_bytes(data[8..10].try_into().unwrap());
+        let first_id: [u8; 8] = data[10..18].try_into().unwrap();
+        let second_id: [u8; 8] = data[18..26].try_into().unwrap();
+
+        // Instruction Checks
+        if amount.eq(&0) || split_bps as u64 > BPS_DENOMINATOR || first_id.eq(&second_id) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            amount,
+            split_bps,
+            first_id,
+            second_id,
+        })
+    }
+}
+
+/// SplitDeposit instruction - splits one deposit across two of the owner's vaults
+pub struct SplitDeposit<'a> {
+    pub accounts: SplitDepositAccounts<'a>,
+    pub instruction_data: SplitDepositInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SplitDeposit<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = SplitDepositInstructionData::try_from(data)?;
+        let accounts = SplitDepositAccounts::try_from((accounts, &instruction_data))?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SplitDeposit<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &14;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let first = bps_of(
+            self.instruction_data.amount,
+            self.instruction_data.split_bps,
+        );
+        let second = self.instruction_data.amount - first;
+
+        self.deposit(self.accounts.first_vault, first)?;
+        self.deposit(self.accounts.second_vault, second)
+    }
+
+    fn deposit(&self, vault: &AccountInfo, lamports: u64) -> ProgramResult {
+        // A 0 or 10000 bps split leaves one of the vaults out
+        if lamports.eq(&0) {
+            return Ok(());
+        }
+
+        let created = vault.lamports().eq(&0);
+
+        Transfer {
+            from: self.accounts.owner,
+            to: vault,
+            lamports,
+        }
+        .invoke()?;
+
+        record_vault_deposit(
+            vault,
+            self.accounts.owner,
+            lamports,
+            0,
+            self.accounts.rent_sysvar,
+        )?;
+
+        load_stats(
+            self.accounts.owner,
+            self.accounts.stats,
+            self.accounts.stats_bump,
+            self.accounts.rent_sysvar,
+        )?
+        .record_deposit(lamports, created);
+
+        Ok(())
+    }
+}
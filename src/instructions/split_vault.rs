@@ -0,0 +1,178 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::error::VaultError;
+use crate::logic::compute_withdraw_amount;
+use crate::oracle::check_price_gate;
+use crate::pda::{find_vault_address, log_vault_mismatch};
+use crate::state::VaultState;
+use crate::sysvar::clock;
+use crate::vault::{check_not_vesting, create_vault, debit_vault, releasable, VaultAccounts};
+
+/// Account structure for the SplitVault instruction
+///
+/// Accounts: `[owner, source_vault, new_vault, system_program, oracle?,
+/// rent_sysvar?]`, where `source_vault` is one of `owner`'s opened vaults and
+/// `oracle` is only passed for a price-gated one.
+pub struct SplitVaultAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub source_vault: &'a AccountInfo,
+    pub new_vault: &'a AccountInfo,
+    pub rent_sysvar: Option<&'a AccountInfo>,
+    pub new_bump: u8,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &SplitVaultInstructionData)> for SplitVaultAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(
+        (accounts, instruction_data): (&'a [AccountInfo], &SplitVaultInstructionData),
+    ) -> Result<Self, Self::Error> {
+        let [owner, source_vault, new_vault, system_program, trailing @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        VaultAccounts::check(
+            owner,
+            source_vault,
+            system_program,
+            &instruction_data.source_id,
+            &instruction_data.source_salt,
+        )?;
+
+        // Only opened vaults have locks to carry over
+        VaultState::load(source_vault)?;
+
+        // A vesting schedule can't be split without breaking its accounting
+        check_not_vesting(source_vault)?;
+
+        // A split moves lamports out like a withdraw, so a stale price stops it too
+        let rent_sysvar = check_price_gate(source_vault, trailing)?;
+
+        // Only an untouched address can become the new vault
+        if !new_vault.is_owned_by(&pinocchio_system::ID) || new_vault.lamports().ne(&0) {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        let (new_key, new_bump) = find_vault_address(
+            owner.key(),
+            &instruction_data.new_id,
+            &instruction_data.new_salt,
+        );
+        if new_vault.key().ne(&new_key) {
+            log_vault_mismatch(new_bump, new_vault.key());
+            return Err(VaultError::InvalidPda.into());
+        }
+
+        Ok(Self {
+            owner,
+            source_vault,
+            new_vault,
+            rent_sysvar: rent_sysvar.first(),
+            new_bump,
+        })
+    }
+}
+
+/// Instruction data structure for the SplitVault instruction
+///
+/// Layout: `[amount: u64][source_id: u64][source_salt: [u8; 32]][new_id: u64]
+/// [new_salt: [u8; 32]]`, the lamports to move and the derivations of the source
+/// and new vault.
+pub struct SplitVaultInstructionData {
+    pub amount: u64,
+    pub source_id: [u8; 8],
+    pub source_salt: [u8; 32],
+    pub new_id: [u8; 8],
+    pub new_salt: [u8; 32],
+}
+
+impl<'a> TryFrom<&'a [u8]> for SplitVaultInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&88) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let amount = u64::from_le_bytes(data[..8].try_into().unwrap());
+
+        // Instruction Checks
+        if amount.eq(&0) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            amount,
+            source_id: data[8..16].try_into().unwrap(),
+            source_salt: data[16..48].try_into().unwrap(),
+            new_id: data[48..56].try_into().unwrap(),
+            new_salt: data[56..88].try_into().unwrap(),
+        })
+    }
+}
+
+/// SplitVault instruction - moves part of an opened vault's balance into a new
+/// opened vault that keeps the same locks
+///
+/// The new vault is created with the source's flags, unlock timestamp, price
+/// gate and daily and rolling limits, so a split can't be used to get around a
+/// lock; the owner pays its rent floor. The amount comes out of the source's
+/// tracked balance, failing with `InsufficientFunds` if that can't cover it,
+/// and counts against the source's limits. The new vault starts out with
+/// everything already withdrawn against them, split included, so splitting
+/// again doesn't reset them.
+pub struct SplitVault<'a> {
+    pub accounts: SplitVaultAccounts<'a>,
+    pub instruction_data: SplitVaultInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SplitVault<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = SplitVaultInstructionData::try_from(data)?;
+        let accounts = SplitVaultAccounts::try_from((accounts, &instruction_data))?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SplitVault<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &31;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let (source_vault, new_vault) = (self.accounts.source_vault, self.accounts.new_vault);
+
+        let available = releasable(source_vault, self.accounts.rent_sysvar, false)?;
+        let (lamports, _) = compute_withdraw_amount(available, self.instruction_data.amount, 0)?;
+
+        let flags = VaultState::load(source_vault)?.flags();
+        create_vault(
+            self.accounts.owner,
+            self.accounts.owner,
+            new_vault,
+            &self.instruction_data.new_id,
+            &self.instruction_data.new_salt,
+            self.accounts.new_bump,
+            flags,
+            self.accounts.rent_sysvar,
+        )?;
+
+        // The split counts against the source's limits, and the new vault takes
+        // over its locks, so only those keep it from running
+        debit_vault(source_vault, self.accounts.owner, new_vault, lamports)?;
+
+        {
+            let source_state = VaultState::load(source_vault)?;
+            let mut new_state = VaultState::load_mut(new_vault)?;
+            new_state.inherit_locks(&source_state);
+            new_state.record_deposit(lamports, clock()?.unix_timestamp, 0);
+        }
+
+        Ok(())
+    }
+}
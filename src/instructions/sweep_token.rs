@@ -0,0 +1,135 @@
+use pinocchio::{
+    account_info::AccountInfo, instruction::Signer, program_error::ProgramError, ProgramResult,
+};
+
+use crate::error::VaultError;
+use crate::instructions::CloseInstructionData;
+use crate::pda::{find_vault_address, log_vault_mismatch, vault_signer_seeds};
+use crate::state::VaultState;
+use crate::token::{
+    check_token_account, token_account_mint, token_account_owner, Transfer, TOKEN_PROGRAM_ID,
+};
+use crate::vault::{check_account_arrangement, check_vault_owner, is_opened};
+
+/// Account structure for the SweepToken instruction
+///
+/// Accounts: `[owner, vault, token_account, destination, token_program]`, where
+/// `token_account` is any token account owned by the vault PDA, e.g. its ATA,
+/// and `destination` a token account of the same mint. An opened vault with a
+/// token destination owner only sends to token accounts of that owner.
+pub struct SweepTokenAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    pub token_account: &'a AccountInfo,
+    pub destination: &'a AccountInfo,
+    pub amount: u64,
+    pub bumps: [u8; 1],
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &[u8; 8], &[u8; 32])> for SweepTokenAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(
+        (accounts, id, salt): (&'a [AccountInfo], &[u8; 8], &[u8; 32]),
+    ) -> Result<Self, Self::Error> {
+        let [owner, vault, token_account, destination, token_program, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        check_account_arrangement(owner, vault)?;
+
+        if !owner.is_signer() {
+            return Err(VaultError::MissingOwnerSignature.into());
+        }
+
+        if token_program.key().ne(&TOKEN_PROGRAM_ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        check_vault_owner(vault)?;
+
+        let (vault_key, bump) = find_vault_address(owner.key(), id, salt);
+        if vault.key().ne(&vault_key) {
+            log_vault_mismatch(bump, vault.key());
+            return Err(VaultError::InvalidPda.into());
+        }
+
+        // The token program checks the destination's mint during the transfer
+        let mut amount = check_token_account(token_account, vault.key())?;
+
+        // Tokens deposited with DepositToken only leave through WithdrawToken
+        if is_opened(vault) {
+            let tracked =
+                VaultState::load(vault)?.token_balance(&token_account_mint(token_account)?);
+            amount = amount.saturating_sub(tracked);
+        }
+
+        if amount.eq(&0) {
+            return Err(VaultError::VaultEmpty.into());
+        }
+
+        if is_opened(vault)
+            && !VaultState::load(vault)?.is_token_dest_allowed(&token_account_owner(destination)?)
+        {
+            return Err(VaultError::DestinationNotAllowed.into());
+        }
+
+        Ok(Self {
+            owner,
+            vault,
+            token_account,
+            destination,
+            amount,
+            bumps: [bump],
+        })
+    }
+}
+
+/// SweepToken instruction - moves the whole balance of a token account owned by
+/// the vault PDA to a destination of the owner's choosing
+///
+/// Recovers tokens sent to the vault by mistake. Takes the same instruction
+/// data as Close to pick the vault. Tokens an opened vault tracks from
+/// DepositToken stay behind.
+pub struct SweepToken<'a> {
+    pub accounts: SweepTokenAccounts<'a>,
+    pub instruction_data: CloseInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SweepToken<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = CloseInstructionData::try_from(data)?;
+        let accounts =
+            SweepTokenAccounts::try_from((accounts, &instruction_data.id, &instruction_data.salt))?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SweepToken<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &22;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let seeds = vault_signer_seeds(
+            self.accounts.owner.key(),
+            &self.instruction_data.id,
+            &self.instruction_data.salt,
+            &self.accounts.bumps,
+        );
+        let signers = [Signer::from(&seeds)];
+
+        Transfer {
+            from: self.accounts.token_account,
+            to: self.accounts.destination,
+            authority: self.accounts.vault,
+            amount: self.accounts.amount,
+        }
+        .invoke_signed(&signers)
+    }
+}
@@ -0,0 +1,119 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::error::VaultError;
+use crate::pda::find_tag_index_address;
+use crate::state::VaultState;
+use crate::tag::add_to_tag_index;
+use crate::vault::check_account_arrangement;
+
+/// Account structure for the TagVault instruction
+///
+/// Accounts: `[owner, vault, system_program, tag_index, rent_sysvar?]`, where
+/// `vault` is an opened vault recording `owner` and `tag_index` the owner's
+/// `["tag", owner, tag]` PDA.
+pub struct TagVaultAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    pub tag_index: &'a AccountInfo,
+    pub rent_sysvar: Option<&'a AccountInfo>,
+    pub tag_index_bump: u8,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &TagVaultInstructionData)> for TagVaultAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(
+        (accounts, instruction_data): (&'a [AccountInfo], &TagVaultInstructionData),
+    ) -> Result<Self, Self::Error> {
+        let [owner, vault, system_program, tag_index, rent_sysvar @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        check_account_arrangement(owner, vault)?;
+
+        if !owner.is_signer() {
+            return Err(VaultError::MissingOwnerSignature.into());
+        }
+
+        if system_program.key().ne(&pinocchio_system::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        // Only opened vaults can be tagged, and only by the owner they record
+        if VaultState::load(vault)?.owner().ne(owner.key()) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        let (tag_index_key, tag_index_bump) =
+            find_tag_index_address(owner.key(), &instruction_data.tag);
+        if tag_index.key().ne(&tag_index_key) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        Ok(Self {
+            owner,
+            vault,
+            tag_index,
+            rent_sysvar: rent_sysvar.first(),
+            tag_index_bump,
+        })
+    }
+}
+
+/// Instruction data structure for the TagVault instruction
+///
+/// Layout: `[tag: u32]`.
+pub struct TagVaultInstructionData {
+    pub tag: [u8; 4],
+}
+
+impl<'a> TryFrom<&'a [u8]> for TagVaultInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let tag = data
+            .try_into()
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        Ok(Self { tag })
+    }
+}
+
+/// TagVault instruction - lists an opened vault in the owner's index of `tag`
+///
+/// A vault can carry any number of tags, and tagging it twice with the same
+/// one lists it once. `client::vaults_by_tag` reads the index back.
+pub struct TagVault<'a> {
+    pub accounts: TagVaultAccounts<'a>,
+    pub instruction_data: TagVaultInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for TagVault<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = TagVaultInstructionData::try_from(data)?;
+        let accounts = TagVaultAccounts::try_from((accounts, &instruction_data))?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> TagVault<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &48;
+
+    pub fn process(&mut self) -> ProgramResult {
+        add_to_tag_index(
+            self.accounts.owner,
+            self.accounts.tag_index,
+            self.accounts.tag_index_bump,
+            &self.instruction_data.tag,
+            self.accounts.vault.key(),
+            self.accounts.rent_sysvar,
+        )
+    }
+}
@@ -0,0 +1,154 @@
+use core::mem::size_of;
+
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+use pinocchio_log::log;
+
+use crate::error::VaultError;
+use crate::state::VaultState;
+use crate::vault::{check_account_arrangement, check_not_escrowed};
+
+/// Account structure for the UpgradeToMultisig instruction
+///
+/// Accounts: `[owner, vault]`, where `vault` is an opened vault recording `owner`.
+pub struct UpgradeToMultisigAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for UpgradeToMultisigAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [owner, vault, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        check_account_arrangement(owner, vault)?;
+
+        if !owner.is_signer() {
+            return Err(VaultError::MissingOwnerSignature.into());
+        }
+
+        // Only opened vaults have state to hold the signers, and the one they record owns it
+        let state = VaultState::load(vault)?;
+        if state.owner().ne(owner.key()) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        // The owner gives up sole control for good, so the signer set can't be replaced
+        if state.is_multisig() {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        // An escrowed vault already answers to its agent alone
+        check_not_escrowed(vault)?;
+
+        Ok(Self { owner, vault })
+    }
+}
+
+/// Instruction data structure for the UpgradeToMultisig instruction
+///
+/// Layout: `[threshold: u8][signers: [Pubkey]]`, with between one and
+/// `VaultState::MAX_MULTISIG_SIGNERS` distinct, non-zero signers and a
+/// `threshold` between `1` and their count, failing with
+/// `InvalidMultisigThreshold` otherwise.
+pub struct UpgradeToMultisigInstructionData {
+    pub threshold: u8,
+    pub signers: [Pubkey; VaultState::MAX_MULTISIG_SIGNERS],
+    pub signer_count: usize,
+}
+
+impl UpgradeToMultisigInstructionData {
+    /// The signers the data lists
+    #[inline(always)]
+    pub fn signers(&self) -> &[Pubkey] {
+        &self.signers[..self.signer_count]
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for UpgradeToMultisigInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let [threshold, keys @ ..] = data else {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+
+        let signer_count = keys.len() / size_of::<Pubkey>();
+        if keys.len() % size_of::<Pubkey>() != 0
+            || signer_count.eq(&0)
+            || signer_count > VaultState::MAX_MULTISIG_SIGNERS
+        {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let mut signers = [[0u8; 32]; VaultState::MAX_MULTISIG_SIGNERS];
+        for (index, key) in keys.chunks_exact(size_of::<Pubkey>()).enumerate() {
+            // Every signer has to be a real key, and each one is counted once
+            if key.eq(&[0; 32]) || signers[..index].iter().any(|signer| signer.eq(key)) {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+
+            signers[index].copy_from_slice(key);
+        }
+
+        if *threshold == 0 || *threshold as usize > signer_count {
+            return Err(VaultError::InvalidMultisigThreshold.into());
+        }
+
+        Ok(Self {
+            threshold: *threshold,
+            signers,
+            signer_count,
+        })
+    }
+}
+
+/// UpgradeToMultisig instruction - hands an opened vault to a set of signers
+///
+/// The vault keeps its address, balance and every other setting. From then on
+/// the Withdraw-based instructions take the signers after the instruction's
+/// fixed accounts and need `threshold` of their signatures instead of the
+/// owner's, and every other path that moves lamports out fails with
+/// `MultisigThresholdNotMet`. The upgrade can't be undone.
+pub struct UpgradeToMultisig<'a> {
+    pub accounts: UpgradeToMultisigAccounts<'a>,
+    pub instruction_data: UpgradeToMultisigInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for UpgradeToMultisig<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = UpgradeToMultisigInstructionData::try_from(data)?;
+        let accounts = UpgradeToMultisigAccounts::try_from(accounts)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> UpgradeToMultisig<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &49;
+
+    pub fn process(&mut self) -> ProgramResult {
+        VaultState::load_mut(self.accounts.vault)?.set_multisig(
+            self.instruction_data.signers(),
+            self.instruction_data.threshold,
+        );
+
+        log!(
+            "UpgradeToMultisig: {} of {} signers",
+            self.instruction_data.threshold,
+            self.instruction_data.signer_count
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,131 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+use pinocchio_log::log;
+use pinocchio_system::instructions::Transfer;
+
+use crate::rent::minimum_balance;
+use crate::state::VaultState;
+use crate::vault::VaultAccounts;
+
+/// Account structure for the UpgradeVaultState instruction
+///
+/// Accounts: `[owner, vault, system_program, rent_sysvar?]`, where `vault` is
+/// an opened vault an older program wrote.
+pub struct UpgradeVaultStateAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    pub rent_sysvar: Option<&'a AccountInfo>,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &UpgradeVaultStateInstructionData)>
+    for UpgradeVaultStateAccounts<'a>
+{
+    type Error = ProgramError;
+
+    fn try_from(
+        (accounts, instruction_data): (&'a [AccountInfo], &UpgradeVaultStateInstructionData),
+    ) -> Result<Self, Self::Error> {
+        let [owner, vault, system_program, rent_sysvar @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        VaultAccounts::check(
+            owner,
+            vault,
+            system_program,
+            &instruction_data.id,
+            &instruction_data.salt,
+        )?;
+
+        // Only an opened vault in an older layout has anything to upgrade
+        if !vault.is_owned_by(&crate::ID) || !VaultState::is_outdated(&vault.try_borrow_data()?) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(Self {
+            owner,
+            vault,
+            rent_sysvar: rent_sysvar.first(),
+        })
+    }
+}
+
+/// Instruction data structure for the UpgradeVaultState instruction
+///
+/// Layout: `[id: u64][salt: [u8; 32]]`, the vault's derivation.
+pub struct UpgradeVaultStateInstructionData {
+    pub id: [u8; 8],
+    pub salt: [u8; 32],
+}
+
+impl<'a> TryFrom<&'a [u8]> for UpgradeVaultStateInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&40) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            id: data[..8].try_into().unwrap(),
+            salt: data[8..].try_into().unwrap(),
+        })
+    }
+}
+
+/// UpgradeVaultState instruction - grows an opened vault an older program
+/// wrote to the current `VaultState` layout
+///
+/// Every older layout is a prefix of the current one, so the account grows in
+/// place to `VaultState::LEN`, the fields it lacked zeroed, which leaves every
+/// feature they configure off, and takes the current version. The owner tops
+/// the rent up to the new floor. Until then, every instruction reading the
+/// vault's state fails with `UnsupportedStateVersion`; an up-to-date vault
+/// fails with `InvalidAccountData`.
+pub struct UpgradeVaultState<'a> {
+    pub accounts: UpgradeVaultStateAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for UpgradeVaultState<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = UpgradeVaultStateInstructionData::try_from(data)?;
+        let accounts = UpgradeVaultStateAccounts::try_from((accounts, &instruction_data))?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> UpgradeVaultState<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &60;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let (owner, vault) = (self.accounts.owner, self.accounts.vault);
+        let previous = vault.data_len();
+
+        // Grow to the current layout and top the rent up to the new size, out
+        // of the owner's lamports rather than the vault's tracked balance
+        let shortfall = minimum_balance(self.accounts.rent_sysvar, VaultState::LEN)?
+            .saturating_sub(minimum_balance(self.accounts.rent_sysvar, previous)?);
+        vault.resize(VaultState::LEN)?;
+        if shortfall.ne(&0) {
+            Transfer {
+                from: owner,
+                to: vault,
+                lamports: shortfall,
+            }
+            .invoke()?;
+        }
+
+        VaultState::initialize(vault)?.upgrade();
+
+        log!(
+            "UpgradeVaultState: {} -> {} bytes",
+            previous,
+            VaultState::LEN
+        );
+
+        Ok(())
+    }
+}
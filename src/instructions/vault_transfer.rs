@@ -0,0 +1,181 @@
+use pinocchio::{
+    account_info::AccountInfo, cpi::set_return_data, instruction::Signer,
+    program_error::ProgramError, ProgramResult,
+};
+
+use crate::error::VaultError;
+use crate::logic::compute_withdraw_amount;
+use crate::oracle::check_price_gate;
+use crate::pda::{find_vault_address, log_vault_mismatch, vault_signer_seeds, DEFAULT_SALT};
+use crate::stats::{check_stats_account, load_stats};
+use crate::vault::{
+    check_account_arrangement, check_not_escrowed, check_not_multisig, check_not_vesting,
+    check_vault_owner, record_vault_deposit, releasable, transfer_from_vault,
+};
+
+/// Account structure for the VaultTransfer instruction
+///
+/// Accounts: `[source_owner, source, dest_owner, dest, system_program, stats]`,
+/// where both owners sign.
+pub struct VaultTransferAccounts<'a> {
+    pub source_owner: &'a AccountInfo,
+    pub source: &'a AccountInfo,
+    pub dest: &'a AccountInfo,
+    pub stats: &'a AccountInfo,
+    pub source_bumps: [u8; 1],
+    pub stats_bump: u8,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &VaultTransferInstructionData)> for VaultTransferAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(
+        (accounts, instruction_data): (&'a [AccountInfo], &VaultTransferInstructionData),
+    ) -> Result<Self, Self::Error> {
+        let [source_owner, source, dest_owner, dest, system_program, stats, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Basic Accounts Checks
+        check_account_arrangement(source_owner, source)?;
+        check_account_arrangement(dest_owner, dest)?;
+
+        if !source_owner.is_signer() || !dest_owner.is_signer() {
+            return Err(VaultError::MissingOwnerSignature.into());
+        }
+
+        if system_program.key() != &pinocchio_system::ID {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        check_vault_owner(source)?;
+        check_vault_owner(dest)?;
+        check_price_gate(source, &[])?;
+        check_not_vesting(source)?;
+        check_not_escrowed(source)?;
+        check_not_multisig(source)?;
+
+        if source.key().eq(dest.key()) {
+            return Err(VaultError::DuplicateAccount.into());
+        }
+
+        let (source_key, source_bump) = find_vault_address(
+            source_owner.key(),
+            &instruction_data.source_id,
+            &DEFAULT_SALT,
+        );
+        if source.key().ne(&source_key) {
+            log_vault_mismatch(source_bump, source.key());
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        let (dest_key, dest_bump) =
+            find_vault_address(dest_owner.key(), &instruction_data.dest_id, &DEFAULT_SALT);
+        if dest.key().ne(&dest_key) {
+            log_vault_mismatch(dest_bump, dest.key());
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        let stats_bump = check_stats_account(stats)?;
+
+        Ok(Self {
+            source_owner,
+            source,
+            dest,
+            stats,
+            source_bumps: [source_bump],
+            stats_bump,
+        })
+    }
+}
+
+/// Instruction data structure for the VaultTransfer instruction
+///
+/// Layout: `[amount: u64][source_id: u64][dest_id: u64]`. Only unsalted vaults
+/// can take part.
+pub struct VaultTransferInstructionData {
+    pub amount: u64,
+    pub source_id: [u8; 8],
+    pub dest_id: [u8; 8],
+}
+
+impl<'a> TryFrom<&'a [u8]> for VaultTransferInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len().ne(&24) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            amount: u64::from_le_bytes(data[..8].try_into().unwrap()),
+            source_id: data[8..16].try_into().unwrap(),
+            dest_id: data[16..24].try_into().unwrap(),
+        })
+    }
+}
+
+/// VaultTransfer instruction - moves lamports from one owner's vault into
+/// another owner's vault, with both owners signing
+///
+/// The source keeps its rent-exempt reserve, so it stays open, and the dest's
+/// tracked balance grows by what it received.
+pub struct VaultTransfer<'a> {
+    pub accounts: VaultTransferAccounts<'a>,
+    pub instruction_data: VaultTransferInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for VaultTransfer<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = VaultTransferInstructionData::try_from(data)?;
+        let accounts = VaultTransferAccounts::try_from((accounts, &instruction_data))?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> VaultTransfer<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &25;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let source = self.accounts.source;
+        let dest = self.accounts.dest;
+        let dest_created = dest.lamports().eq(&0);
+
+        let available = releasable(source, None, false)?;
+        let (lamports, _) = compute_withdraw_amount(available, self.instruction_data.amount, 0)?;
+
+        // Create PDA signer seeds
+        let seeds = vault_signer_seeds(
+            self.accounts.source_owner.key(),
+            &self.instruction_data.source_id,
+            &DEFAULT_SALT,
+            &self.accounts.source_bumps,
+        );
+        let signers = [Signer::from(&seeds)];
+
+        transfer_from_vault(source, self.accounts.source_owner, dest, lamports, &signers)?;
+        record_vault_deposit(dest, self.accounts.source_owner, lamports, 0, None)?;
+
+        // Lamports stay locked, only the number of vaults can change
+        if dest_created {
+            load_stats(
+                self.accounts.source_owner,
+                self.accounts.stats,
+                self.accounts.stats_bump,
+                None,
+            )?
+            .record_open(0);
+        }
+
+        // Report the dest's new balance so clients don't need a follow-up fetch
+        set_return_data(&dest.lamports().to_le_bytes());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,148 @@
+use pinocchio::{
+    account_info::AccountInfo, cpi::set_return_data, program_error::ProgramError, ProgramResult,
+};
+
+use crate::pda::{find_vault_address, DEFAULT_SALT};
+use crate::state::VaultState;
+use crate::sysvar::clock;
+use crate::vault::rent_reserve;
+
+/// Outcome of a VerifyIntegrity check, returned as a single byte of return data
+///
+/// Only the first inconsistency found is reported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum IntegrityStatus {
+    /// Every invariant holds
+    Healthy,
+    /// The recorded owner and id (with the given salt) don't derive to the vault
+    AddressMismatch,
+    /// The recorded bump isn't the vault's canonical bump
+    BumpMismatch,
+    /// Flags outside of the ones the program defines are set
+    UnknownFlags,
+    /// The recorded creation slot is later than the current slot
+    CreationSlotInFuture,
+    /// The vault holds less than its rent-exempt floor
+    BelowRentFloor,
+    /// The label is longer than a vault can hold or isn't valid UTF-8
+    InvalidLabel,
+}
+
+/// Account structure for the VerifyIntegrity instruction
+///
+/// Accounts: `[vault, rent_sysvar?]`. Nothing is written, so no signer is needed.
+pub struct VerifyIntegrityAccounts<'a> {
+    pub vault: &'a AccountInfo,
+    pub rent_sysvar: Option<&'a AccountInfo>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for VerifyIntegrityAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [vault, rent_sysvar @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Only opened vaults have state to verify
+        VaultState::load(vault)?;
+
+        Ok(Self {
+            vault,
+            rent_sysvar: rent_sysvar.first(),
+        })
+    }
+}
+
+/// Instruction data structure for the VerifyIntegrity instruction
+///
+/// Layout: `[salt: [u8; 32]]`. The salt isn't stored in the vault, so a salted
+/// vault needs it to be re-derived; it defaults to the zero (unsalted) salt.
+pub struct VerifyIntegrityInstructionData {
+    pub salt: [u8; 32],
+}
+
+impl<'a> TryFrom<&'a [u8]> for VerifyIntegrityInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let salt = match data.len() {
+            0 => DEFAULT_SALT,
+            32 => data.try_into().unwrap(),
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        Ok(Self { salt })
+    }
+}
+
+/// VerifyIntegrity instruction - checks an opened vault's state against its account
+pub struct VerifyIntegrity<'a> {
+    pub accounts: VerifyIntegrityAccounts<'a>,
+    pub instruction_data: VerifyIntegrityInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for VerifyIntegrity<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = VerifyIntegrityInstructionData::try_from(data)?;
+        let accounts = VerifyIntegrityAccounts::try_from(accounts)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> VerifyIntegrity<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &7;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let status = self.check()?;
+
+        set_return_data(&[status as u8]);
+
+        Ok(())
+    }
+
+    fn check(&self) -> Result<IntegrityStatus, ProgramError> {
+        let vault = self.accounts.vault;
+        let state = VaultState::load(vault)?;
+
+        let (vault_key, bump) = find_vault_address(
+            state.owner(),
+            &state.id().to_le_bytes(),
+            &self.instruction_data.salt,
+        );
+        if vault.key().ne(&vault_key) {
+            return Ok(IntegrityStatus::AddressMismatch);
+        }
+
+        if state.bump().ne(&bump) {
+            return Ok(IntegrityStatus::BumpMismatch);
+        }
+
+        if state.flags() & !VaultState::KNOWN_FLAGS != 0 {
+            return Ok(IntegrityStatus::UnknownFlags);
+        }
+
+        if state.creation_slot() > clock()?.slot {
+            return Ok(IntegrityStatus::CreationSlotInFuture);
+        }
+
+        if vault.lamports() < rent_reserve(vault, self.accounts.rent_sysvar)? {
+            return Ok(IntegrityStatus::BelowRentFloor);
+        }
+
+        if state.label_len() as usize > VaultState::MAX_LABEL_LEN
+            || VaultState::check_label(state.label()).is_err()
+        {
+            return Ok(IntegrityStatus::InvalidLabel);
+        }
+
+        Ok(IntegrityStatus::Healthy)
+    }
+}
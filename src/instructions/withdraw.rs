@@ -1,87 +1,449 @@
 use pinocchio::{
-    account_info::AccountInfo,
-    instruction::{Seed, Signer},
-    program_error::ProgramError,
-    pubkey::find_program_address,
-    ProgramResult,
+    account_info::AccountInfo, cpi::set_return_data, instruction::Signer,
+    program_error::ProgramError, ProgramResult,
+};
+use pinocchio_log::log;
+
+use crate::config::check_config_account;
+use crate::error::VaultError;
+use crate::events::log_withdraw;
+use crate::fee::{check_treasury, net_of_fee, pay_out, WithdrawFee};
+use crate::hook::{check_hook_program, invoke_hook, HOOK_WITHDRAW};
+use crate::oracle::check_price_gate;
+use crate::parse::{array_at, fit_layout, read_array};
+use crate::pda::{vault_signer_seeds, DEFAULT_SALT, DEFAULT_VAULT_ID};
+use crate::receipt::ReceiptAccounts;
+use crate::stats::{check_stats_account, load_stats};
+use crate::vault::{
+    check_account_count, check_escrow_agent, check_multisig_signers, check_not_vesting,
+    record_owner_activity, releasable, rent_reserve, LamportSnapshot, VaultAccounts,
 };
-use pinocchio_system::instructions::Transfer;
 
 /// Account structure for the Withdraw instruction
+///
+/// Accounts: `[owner, vault, system_program, stats, config, hook_program?,
+/// treasury?, escrow_agent?, multisig_signers.., oracle?, rent_sysvar?]`, where
+/// `config` is the config PDA, initialized or not, `hook_program` is only passed
+/// while the config has one, `treasury` only while it charges a withdrawal
+/// fee, `escrow_agent` only, and signs instead of `owner`, for
+/// an escrowed vault, `multisig_signers` only for a multisig one, whose
+/// threshold of them signs instead of `owner`, and `oracle` only for a
+/// price-gated one.
 pub struct WithdrawAccounts<'a> {
     pub owner: &'a AccountInfo,
     pub vault: &'a AccountInfo,
+    pub stats: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    /// The config's hook program, told about the withdrawal once it lands
+    pub hook: Option<&'a AccountInfo>,
+    /// The config's withdrawal fee, while it charges one
+    pub fee: Option<WithdrawFee<'a>>,
+    pub rent_sysvar: Option<&'a AccountInfo>,
     pub bumps: [u8; 1],
+    pub stats_bump: u8,
 }
 
-impl<'a> TryFrom<&'a [AccountInfo]> for WithdrawAccounts<'a> {
+impl WithdrawAccounts<'_> {
+    /// Index of the stats PDA in the accounts
+    pub const STATS: usize = VaultAccounts::LEN;
+    /// Number of accounts every withdrawal takes, before the optional ones
+    pub const MIN_ACCOUNTS: usize = VaultAccounts::LEN + 2;
+
+    /// Records `lamports` withdrawn from the vault in the global stats
+    pub fn record_withdrawal(&self, lamports: u64) -> ProgramResult {
+        load_stats(self.owner, self.stats, self.stats_bump, self.rent_sysvar)?
+            .record_withdrawal(lamports, self.vault.lamports().eq(&0) as u64);
+
+        Ok(())
+    }
+
+    /// Moves `lamports` out of the vault, the withdrawal fee to the treasury
+    /// and the rest to `to`, returning the lamports `to` received, and counts
+    /// the owner as active
+    pub fn pay_out(
+        &self,
+        to: &AccountInfo,
+        lamports: u64,
+        signers: &[Signer],
+    ) -> Result<u64, ProgramError> {
+        // Recorded here too, since a multisig or escrow agent signs instead
+        // of the owner, and first, since paying the whole vault out closes it
+        record_owner_activity(self.vault)?;
+        pay_out(
+            self.vault,
+            self.owner,
+            to,
+            self.fee.as_ref(),
+            lamports,
+            signers,
+        )
+    }
+
+    /// Tells the config's hook program, if any, about `lamports` withdrawn
+    pub fn notify_hook(&self, lamports: u64) -> ProgramResult {
+        match self.hook {
+            Some(hook) => invoke_hook(hook, HOOK_WITHDRAW, self.owner, lamports),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &[u8; 8], &[u8; 32])> for WithdrawAccounts<'a> {
     type Error = ProgramError;
 
-    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
-        let [owner, vault, _] = accounts else {
+    fn try_from(
+        (accounts, id, salt): (&'a [AccountInfo], &[u8; 8], &[u8; 32]),
+    ) -> Result<Self, Self::Error> {
+        check_account_count(accounts, Self::MIN_ACCOUNTS)?;
+        let [owner, vault, system_program, stats, trailing @ ..] = accounts else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
-        // Basic Accounts Checks
-        if !owner.is_signer() {
-            return Err(ProgramError::MissingRequiredSignature);
-        }
+        Self::check(owner, vault, system_program, stats, trailing, id, salt)
+    }
+}
 
-        if !vault.is_owned_by(&pinocchio_system::ID) {
-            return Err(ProgramError::InvalidAccountOwner);
-        }
+impl<'a> WithdrawAccounts<'a> {
+    /// Checks already destructured accounts, for instructions that take extra
+    /// accounts ahead of the optional `trailing` ones
+    pub fn check(
+        owner: &'a AccountInfo,
+        vault: &'a AccountInfo,
+        system_program: &'a AccountInfo,
+        stats: &'a AccountInfo,
+        trailing: &'a [AccountInfo],
+        id: &[u8; 8],
+        salt: &[u8; 32],
+    ) -> Result<Self, ProgramError> {
+        // The config's hook hears about every withdrawal, so it can't be left out
+        let [config, trailing @ ..] = trailing else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        check_config_account(config)?;
+        let (hook, trailing) = check_hook_program(config, trailing)?;
+        let (fee, trailing) = check_treasury(config, owner.key(), trailing)?;
+
+        // Basic Accounts Checks, with an escrowed vault's agent or a multisig
+        // vault's signers signing in place of the owner
+        let (escrowed, trailing) = check_escrow_agent(vault, trailing)?;
+        let (multisig, trailing) = check_multisig_signers(vault, trailing)?;
+        let VaultAccounts { bump, .. } = if escrowed || multisig {
+            VaultAccounts::check_unsigned(owner, vault, system_program, id, salt)?
+        } else {
+            VaultAccounts::check(owner, vault, system_program, id, salt)?
+        };
 
+        // A closed or never-funded vault has no lamports, so a repeated close lands here
         if vault.lamports().eq(&0) {
-            return Err(ProgramError::InvalidAccountData);
+            return Err(VaultError::VaultEmpty.into());
         }
 
-        let (vault_key, bump) = find_program_address(&[b"vault", owner.key().as_ref()], &crate::ID);
-        if &vault_key != vault.key() {
-            return Err(ProgramError::InvalidAccountOwner);
-        }
+        let stats_bump = check_stats_account(stats)?;
+
+        let rent_sysvar = check_price_gate(vault, trailing)?;
 
         Ok(Self {
             owner,
             vault,
+            stats,
+            config,
+            hook,
+            fee,
+            rent_sysvar: rent_sysvar.first(),
             bumps: [bump],
+            stats_bump,
         })
     }
 }
 
-/// Withdraw instruction - transfers all lamports from vault back to owner
+/// Instruction data structure for the Withdraw instruction
+///
+/// Layout: `[id: u64][close: u8][salt: [u8; 32]][min_out: u64][keep_alive: u64]
+/// [receipt: u8][hook: u8][idempotent: u8]`. All fields are optional, but each
+/// one requires the fields before it: `id` defaults to the owner's default
+/// vault, `close` to `1`, `salt` to the zero (unsalted) salt, and `min_out`,
+/// `keep_alive`, `receipt`, `hook` and `idempotent` to `0`. With `close = 0` the
+/// vault keeps its rent-exempt reserve so the account persists. The withdrawal
+/// fails with `SlippageExceeded` if it would pay the owner fewer than
+/// `min_out` lamports once any withdrawal fee is taken out, e.g. because the
+/// vault or the fee changed after the client read them. A
+/// non-zero `keep_alive` leaves that many lamports behind and keeps the vault
+/// open whatever `close` says; it has to cover the rent-exempt reserve. With
+/// `receipt = 1` the owner's receipt tokens for the withdrawn lamports are
+/// burned first; without it, withdrawing lamports receipts still stand for
+/// fails with `ReceiptsOutstanding`. `hook` is reserved and must be `0`:
+/// the config's hook program, if any, is told about every withdrawal once it
+/// lands, whatever the data says. With `idempotent = 1` withdrawing from a vault with no
+/// lamports at all, e.g. one already closed, succeeds without doing anything
+/// instead of failing with `VaultEmpty`. Only Withdraw itself takes the
+/// receipt accounts and the `idempotent` flag. Bytes past the longest layout
+/// the data holds are ignored unless the program is built with
+/// `strict-parsing`.
+pub struct WithdrawInstructionData {
+    pub id: [u8; 8],
+    pub close: bool,
+    pub salt: [u8; 32],
+    pub min_out: u64,
+    pub keep_alive: u64,
+    pub receipt: bool,
+    pub idempotent: bool,
+}
+
+impl WithdrawInstructionData {
+    /// Fails with `SlippageExceeded` if `lamports` falls short of `min_out`
+    #[inline(always)]
+    pub fn check_min_out(&self, lamports: u64) -> ProgramResult {
+        if lamports < self.min_out {
+            return Err(VaultError::SlippageExceeded.into());
+        }
+
+        Ok(())
+    }
+
+    /// Returns the lamports a withdraw with this data moves out of `accounts.vault`
+    ///
+    /// Fails with `AccountNotRentExempt` for a `keep_alive` below the vault's
+    /// rent-exempt reserve, `VaultEmpty` if nothing can be released and
+    /// `SlippageExceeded` if less than `min_out` is left once the withdrawal
+    /// fee is taken out.
+    pub fn amount(&self, accounts: &WithdrawAccounts) -> Result<u64, ProgramError> {
+        let vault = accounts.vault;
+
+        // Draining every lamport closes the vault; otherwise leave the rent-exempt reserve
+        let mut lamports = releasable(
+            vault,
+            accounts.rent_sysvar,
+            self.close && self.keep_alive.eq(&0),
+        )?;
+
+        if self.keep_alive.ne(&0) {
+            if self.keep_alive < rent_reserve(vault, accounts.rent_sysvar)? {
+                return Err(ProgramError::AccountNotRentExempt);
+            }
+
+            lamports = lamports.min(vault.lamports().saturating_sub(self.keep_alive));
+        }
+
+        if lamports.eq(&0) {
+            return Err(VaultError::VaultEmpty.into());
+        }
+
+        self.check_min_out(net_of_fee(accounts.fee.as_ref(), lamports)?)?;
+
+        Ok(lamports)
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for WithdrawInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let data = fit_layout(data, &[0, 8, 9, 41, 49, 57, 58, 59, 60]);
+
+        let (id, close, salt) = match data.len() {
+            0 => (DEFAULT_VAULT_ID, true, DEFAULT_SALT),
+            8 => (read_array(data, 0)?, true, DEFAULT_SALT),
+            9 | 41 | 49 | 57 | 58 | 59 | 60 => {
+                let close = match data.get(8) {
+                    Some(0) => false,
+                    Some(1) => true,
+                    _ => return Err(ProgramError::InvalidInstructionData),
+                };
+                let salt = match data.len() {
+                    9 => DEFAULT_SALT,
+                    _ => read_array(data, 9)?,
+                };
+                (read_array(data, 0)?, close, salt)
+            }
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        let min_out = array_at(data, 41).map_or(0, u64::from_le_bytes);
+
+        let keep_alive = array_at(data, 49).map_or(0, u64::from_le_bytes);
+
+        let [receipt, idempotent] = [57, 59].map(|offset| match data.get(offset) {
+            None | Some(0) => Ok(false),
+            Some(1) => Ok(true),
+            _ => Err(ProgramError::InvalidInstructionData),
+        });
+        let (receipt, idempotent) = (receipt?, idempotent?);
+
+        // The hook byte is reserved, so a client setting it isn't silently ignored
+        if data.get(58).is_some_and(|hook| hook.ne(&0)) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            id,
+            close,
+            salt,
+            min_out,
+            keep_alive,
+            receipt,
+            idempotent,
+        })
+    }
+}
+
+/// Withdraw instruction - transfers the vault's lamports back to owner, closing it by default
+///
+/// Burning receipts takes `[receipt_mint, receipt_account, token_program]`
+/// right after `stats`, ahead of `config` and the rest of the accounts
+/// [`WithdrawAccounts`] takes.
 pub struct Withdraw<'a> {
     pub accounts: WithdrawAccounts<'a>,
+    pub receipt: Option<ReceiptAccounts<'a>>,
+    pub instruction_data: WithdrawInstructionData,
 }
 
-impl<'a> TryFrom<&'a [AccountInfo]> for Withdraw<'a> {
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for Withdraw<'a> {
     type Error = ProgramError;
 
-    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
-        let accounts = WithdrawAccounts::try_from(accounts)?;
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = WithdrawInstructionData::try_from(data)?;
+        check_account_count(accounts, WithdrawAccounts::MIN_ACCOUNTS)?;
+        let [owner, vault, system_program, stats, trailing @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Self::check(owner, vault, system_program, stats, trailing, instruction_data)
+    }
+}
+
+impl<'a> Withdraw<'a> {
+    /// Checks already destructured accounts, for instructions that take extra
+    /// accounts ahead of the receipt and optional `trailing` ones
+    pub fn check(
+        owner: &'a AccountInfo,
+        vault: &'a AccountInfo,
+        system_program: &'a AccountInfo,
+        stats: &'a AccountInfo,
+        trailing: &'a [AccountInfo],
+        instruction_data: WithdrawInstructionData,
+    ) -> Result<Self, ProgramError> {
+        let (receipt, trailing) = if instruction_data.receipt {
+            let [mint, token_account, token_program, trailing @ ..] = trailing else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+            (Some([mint, token_account, token_program]), trailing)
+        } else {
+            (None, trailing)
+        };
+
+        let id = &instruction_data.id;
+        let salt = &instruction_data.salt;
+
+        // There's nothing left to check against a vault without lamports, but the owner
+        if instruction_data.idempotent && vault.lamports().eq(&0) {
+            let VaultAccounts { bump, .. } =
+                VaultAccounts::check(owner, vault, system_program, id, salt)?;
+            let [config, ..] = trailing else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+            check_config_account(config)?;
 
-        Ok(Self { accounts })
+            let accounts = WithdrawAccounts {
+                owner,
+                vault,
+                stats,
+                config,
+                hook: None,
+                fee: None,
+                rent_sysvar: None,
+                bumps: [bump],
+                stats_bump: check_stats_account(stats)?,
+            };
+
+            return Ok(Self {
+                accounts,
+                receipt: None,
+                instruction_data,
+            });
+        }
+
+        let accounts =
+            WithdrawAccounts::check(owner, vault, system_program, stats, trailing, id, salt)?;
+
+        // Crediting a read-only owner would only fail later, with a cryptic runtime error
+        if !owner.is_writable() {
+            log!("Withdraw: owner account must be writable");
+            return Err(VaultError::OwnerNotWritable.into());
+        }
+
+        let receipt = receipt
+            .map(|[mint, token_account, token_program]| {
+                ReceiptAccounts::check(mint, token_account, token_program, owner, vault)
+            })
+            .transpose()?;
+
+        // Vesting vaults only release their deposits through WithdrawVested
+        check_not_vesting(accounts.vault)?;
+
+        Ok(Self {
+            accounts,
+            receipt,
+            instruction_data,
+        })
     }
 }
 
 impl<'a> Withdraw<'a> {
     pub const DISCRIMINATOR: &'a u8 = &1;
 
+    /// Returns the lamports this withdraw moves to the owner
+    pub fn amount(&self) -> Result<u64, ProgramError> {
+        self.instruction_data.amount(&self.accounts)
+    }
+
     pub fn process(&mut self) -> ProgramResult {
+        // Only an idempotent withdraw gets this far with an empty vault
+        if self.accounts.vault.lamports().eq(&0) {
+            log!("Withdraw: vault already empty");
+            set_return_data(&0u64.to_le_bytes());
+            return Ok(());
+        }
+
         // Create PDA signer seeds
-        let seeds = [
-            Seed::from(b"vault"),
-            Seed::from(self.accounts.owner.key().as_ref()),
-            Seed::from(&self.accounts.bumps),
-        ];
+        let seeds = vault_signer_seeds(
+            self.accounts.owner.key(),
+            &self.instruction_data.id,
+            &self.instruction_data.salt,
+            &self.accounts.bumps,
+        );
         let signers = [Signer::from(&seeds)];
 
-        // Transfer all lamports from vault to owner
-        Transfer {
-            from: self.accounts.vault,
-            to: self.accounts.owner,
-            lamports: self.accounts.vault.lamports(),
+        let lamports = self.amount()?;
+
+        let treasury = self.accounts.fee.as_ref().map(|fee| fee.treasury);
+        let conserved = || {
+            [
+                self.accounts.owner,
+                self.accounts.vault,
+                self.accounts.stats,
+            ]
+            .into_iter()
+            .chain(treasury)
+        };
+        let snapshot = LamportSnapshot::take(conserved());
+
+        // Receipts are burned before the lamports they stand for leave the vault
+        if let Some(receipt) = &self.receipt {
+            receipt.burn(self.accounts.owner, self.accounts.vault, lamports)?;
         }
-        .invoke_signed(&signers)?;
+
+        self.accounts
+            .pay_out(self.accounts.owner, lamports, &signers)?;
+        self.accounts.record_withdrawal(lamports)?;
+
+        snapshot.check_conserved(conserved())?;
+
+        log_withdraw(self.accounts.owner.key(), lamports);
+
+        self.accounts.notify_hook(lamports)?;
+
+        // Report the new vault balance so clients don't need a follow-up fetch
+        set_return_data(&self.accounts.vault.lamports().to_le_bytes());
 
         Ok(())
     }
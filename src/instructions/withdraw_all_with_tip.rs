@@ -0,0 +1,261 @@
+use pinocchio::{
+    account_info::AccountInfo, cpi::set_return_data, instruction::Signer,
+    program_error::ProgramError, ProgramResult,
+};
+
+use crate::approval::{check_ed25519_approval, tip_approval_message};
+use crate::error::VaultError;
+use crate::fee::{pay_out, WithdrawFee};
+use crate::hook::{check_withdraw_config, invoke_hook, HOOK_WITHDRAW};
+use crate::logic::compute_withdraw_amount;
+use crate::oracle::check_price_gate;
+use crate::pda::{vault_signer_seeds, DEFAULT_SALT, DEFAULT_VAULT_ID};
+use crate::state::VaultState;
+use crate::stats::{check_stats_account, load_stats};
+use crate::sysvar::clock;
+use crate::vault::{
+    check_not_escrowed, check_not_multisig, check_not_vesting, check_vault_address,
+    pay_from_gas_reserve, releasable, transfer_from_vault, transfer_share_from_vault,
+};
+
+/// Account structure for the WithdrawAllWithTip instruction
+///
+/// Accounts: `[relayer, owner, vault, system_program, stats, instructions_sysvar,
+/// config, hook_program?, treasury?, oracle?, rent_sysvar?]`, the same as
+/// WithdrawWithApproval. Only `relayer`
+/// signs; it pays the fees and receives the tip.
+pub struct WithdrawAllWithTipAccounts<'a> {
+    pub relayer: &'a AccountInfo,
+    pub owner: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    pub stats: &'a AccountInfo,
+    /// The config's hook program, told about the withdrawal once it lands
+    pub hook: Option<&'a AccountInfo>,
+    /// The config's withdrawal fee, while it charges one
+    pub fee: Option<WithdrawFee<'a>>,
+    pub rent_sysvar: Option<&'a AccountInfo>,
+    pub bumps: [u8; 1],
+    pub stats_bump: u8,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &WithdrawAllWithTipInstructionData)>
+    for WithdrawAllWithTipAccounts<'a>
+{
+    type Error = ProgramError;
+
+    fn try_from(
+        (accounts, instruction_data): (&'a [AccountInfo], &WithdrawAllWithTipInstructionData),
+    ) -> Result<Self, Self::Error> {
+        let [relayer, owner, vault, system_program, stats, instructions_sysvar, trailing @ ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        if !relayer.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        if system_program.key().ne(&pinocchio_system::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let bump = check_vault_address(owner, vault, &instruction_data.id, &instruction_data.salt)?;
+
+        // Only opened vaults have state to record the used nonce in
+        VaultState::load(vault)?;
+
+        // The owner's signature covers the tip and who it goes to
+        check_ed25519_approval(
+            instructions_sysvar,
+            owner.key(),
+            &tip_approval_message(
+                vault.key(),
+                instruction_data.tip,
+                instruction_data.nonce,
+                relayer.key(),
+                instruction_data.expiry_ts,
+            ),
+        )?;
+
+        if clock()?.unix_timestamp > instruction_data.expiry_ts {
+            return Err(VaultError::ApprovalExpired.into());
+        }
+
+        // Vesting vaults only release their deposits through WithdrawVested
+        check_not_vesting(vault)?;
+        check_not_escrowed(vault)?;
+        check_not_multisig(vault)?;
+
+        let stats_bump = check_stats_account(stats)?;
+
+        let (hook, fee, trailing) = check_withdraw_config(owner.key(), trailing)?;
+        let rent_sysvar = check_price_gate(vault, trailing)?;
+
+        Ok(Self {
+            relayer,
+            owner,
+            vault,
+            stats,
+            hook,
+            fee,
+            rent_sysvar: rent_sysvar.first(),
+            bumps: [bump],
+            stats_bump,
+        })
+    }
+}
+
+/// Instruction data structure for the WithdrawAllWithTip instruction
+///
+/// Layout: `[tip: u64][nonce: u64][expiry_ts: i64][id: u64][salt: [u8; 32]]`,
+/// with `id` and `salt` optional as in WithdrawWithApproval. The owner approves
+/// it by signing `approval::tip_approval_message(vault, tip, nonce, relayer,
+/// expiry_ts)`, which an ed25519 precompile instruction right before this one
+/// has to verify. The nonce shares the vault's counter with
+/// WithdrawWithApproval, and the approval stops working once the clock passes
+/// `expiry_ts`.
+pub struct WithdrawAllWithTipInstructionData {
+    pub tip: u64,
+    pub nonce: u64,
+    pub expiry_ts: i64,
+    pub id: [u8; 8],
+    pub salt: [u8; 32],
+}
+
+impl<'a> TryFrom<&'a [u8]> for WithdrawAllWithTipInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let (id, salt) = match data.len() {
+            24 => (DEFAULT_VAULT_ID, DEFAULT_SALT),
+            32 => (data[24..32].try_into().unwrap(), DEFAULT_SALT),
+            64 => (
+                data[24..32].try_into().unwrap(),
+                data[32..64].try_into().unwrap(),
+            ),
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        let tip = u64::from_le_bytes(data[..8].try_into().unwrap());
+        let nonce = u64::from_le_bytes(data[8..16].try_into().unwrap());
+        let expiry_ts = i64::from_le_bytes(data[16..24].try_into().unwrap());
+
+        Ok(Self {
+            tip,
+            nonce,
+            expiry_ts,
+            id,
+            salt,
+        })
+    }
+}
+
+/// WithdrawAllWithTip instruction - a relayer withdraws the owner's whole
+/// balance on their signed, single-use approval, keeping a tip for its fees
+///
+/// The vault keeps its rent-exempt reserve. `tip` lamports go to the relayer,
+/// drawn from the vault's gas reserve first and only then from its balance,
+/// and the rest of the balance goes to the owner; a tip above what the two
+/// cover fails with `InsufficientFunds`.
+pub struct WithdrawAllWithTip<'a> {
+    pub accounts: WithdrawAllWithTipAccounts<'a>,
+    pub instruction_data: WithdrawAllWithTipInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for WithdrawAllWithTip<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = WithdrawAllWithTipInstructionData::try_from(data)?;
+        let accounts = WithdrawAllWithTipAccounts::try_from((accounts, &instruction_data))?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> WithdrawAllWithTip<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &35;
+
+    pub fn process(&mut self) -> ProgramResult {
+        VaultState::load_mut(self.accounts.vault)?.use_nonce(self.instruction_data.nonce)?;
+
+        let tip = self.instruction_data.tip;
+        let available = releasable(self.accounts.vault, self.accounts.rent_sysvar, false)?;
+        let (lamports, _) = compute_withdraw_amount(available, available, 0)?;
+        let tip_from_reserve = tip.min(VaultState::load(self.accounts.vault)?.gas_reserve());
+        let tip_from_balance = tip - tip_from_reserve;
+        let to_owner = lamports
+            .checked_sub(tip_from_balance)
+            .ok_or(VaultError::InsufficientFunds)?;
+
+        // Create PDA signer seeds
+        let seeds = vault_signer_seeds(
+            self.accounts.owner.key(),
+            &self.instruction_data.id,
+            &self.instruction_data.salt,
+            &self.accounts.bumps,
+        );
+        let signers = [Signer::from(&seeds)];
+
+        pay_from_gas_reserve(self.accounts.vault, self.accounts.relayer, tip_from_reserve)?;
+        // The tip is only counted as the withdrawal when nothing goes to the owner
+        if tip_from_balance.ne(&0) && to_owner.ne(&0) {
+            transfer_share_from_vault(
+                self.accounts.vault,
+                self.accounts.owner,
+                self.accounts.relayer,
+                tip_from_balance,
+                &signers,
+            )?;
+        } else if tip_from_balance.ne(&0) {
+            transfer_from_vault(
+                self.accounts.vault,
+                self.accounts.owner,
+                self.accounts.relayer,
+                tip_from_balance,
+                &signers,
+            )?;
+        }
+        if to_owner.ne(&0) {
+            pay_out(
+                self.accounts.vault,
+                self.accounts.owner,
+                self.accounts.owner,
+                self.accounts.fee.as_ref(),
+                to_owner,
+                &signers,
+            )?;
+        }
+
+        load_stats(
+            self.accounts.relayer,
+            self.accounts.stats,
+            self.accounts.stats_bump,
+            self.accounts.rent_sysvar,
+        )?
+        .record_withdrawal(
+            lamports + tip_from_reserve,
+            self.accounts.vault.lamports().eq(&0) as u64,
+        );
+
+        // The tip left the vault too, so the hook hears about it along with the rest
+        if let Some(hook) = self.accounts.hook {
+            invoke_hook(
+                hook,
+                HOOK_WITHDRAW,
+                self.accounts.owner,
+                lamports + tip_from_reserve,
+            )?;
+        }
+
+        // Report the new vault balance so clients don't need a follow-up fetch
+        set_return_data(&self.accounts.vault.lamports().to_le_bytes());
+
+        Ok(())
+    }
+}
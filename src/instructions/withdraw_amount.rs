@@ -0,0 +1,186 @@
+use pinocchio::{
+    account_info::AccountInfo, cpi::set_return_data, instruction::Signer,
+    program_error::ProgramError, ProgramResult,
+};
+
+use crate::config::{dust_threshold, min_withdraw};
+use crate::instructions::WithdrawAccounts;
+use crate::logic::{check_min_withdraw, compute_withdraw_amount};
+use crate::parse::{read_array, read_u64};
+use crate::pda::{vault_signer_seeds, DEFAULT_SALT, DEFAULT_VAULT_ID};
+use crate::vault::{check_account_count, check_not_vesting, releasable};
+
+/// How a WithdrawAmount behaves when the vault can't cover the full amount
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum WithdrawMode {
+    /// Fail with `VaultError::InsufficientFunds`
+    Exact,
+    /// Withdraw whatever is available
+    BestEffort,
+}
+
+impl TryFrom<u8> for WithdrawMode {
+    type Error = ProgramError;
+
+    fn try_from(mode: u8) -> Result<Self, Self::Error> {
+        match mode {
+            0 => Ok(Self::Exact),
+            1 => Ok(Self::BestEffort),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+}
+
+/// Instruction data structure for the WithdrawAmount instruction
+///
+/// Layout: `[amount: u64][mode: u8][id: u64][salt: [u8; 32]][auto_close: u8]`.
+/// The trailing fields are optional, but each one requires the fields before
+/// it: `id` and `salt` default to the owner's default unsalted vault and
+/// `auto_close` to `0`. A zero `amount` is a no-op rather than an error, so
+/// callers computing the amount don't have to special-case nothing to withdraw.
+/// With `auto_close = 1` a withdraw leaving less than the config's dust
+/// threshold releasable, but not nothing, sweeps the rest to the owner and
+/// closes the vault.
+pub struct WithdrawAmountInstructionData {
+    pub amount: u64,
+    pub mode: WithdrawMode,
+    pub id: [u8; 8],
+    pub salt: [u8; 32],
+    pub auto_close: bool,
+}
+
+impl<'a> TryFrom<&'a [u8]> for WithdrawAmountInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let (id, salt) = match data.len() {
+            9 => (DEFAULT_VAULT_ID, DEFAULT_SALT),
+            17 => (read_array(data, 9)?, DEFAULT_SALT),
+            49 | 50 => (read_array(data, 9)?, read_array(data, 17)?),
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        let amount = read_u64(data, 0)?;
+        let mode = match data.get(8) {
+            Some(mode) => WithdrawMode::try_from(*mode)?,
+            None => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        let auto_close = match data.get(49) {
+            None | Some(0) => false,
+            Some(1) => true,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        Ok(Self {
+            amount,
+            mode,
+            id,
+            salt,
+            auto_close,
+        })
+    }
+}
+
+/// WithdrawAmount instruction - transfers part of the vault's balance back to owner
+///
+/// Uses the same accounts as Withdraw. The vault keeps its rent-exempt reserve, so only the lamports above it can
+/// be withdrawn, unless auto-close sweeps it. An amount below the config's
+/// `min_withdraw` fails with `WithdrawTooSmall`, unless it's everything the
+/// vault has to release.
+pub struct WithdrawAmount<'a> {
+    pub accounts: WithdrawAccounts<'a>,
+    pub instruction_data: WithdrawAmountInstructionData,
+    /// Releasable balance below which the vault is swept and closed, `0` for never
+    pub dust_threshold: u64,
+    /// Smallest amount that can be withdrawn short of draining the vault, `0` for none
+    pub min_withdraw: u64,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for WithdrawAmount<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = WithdrawAmountInstructionData::try_from(data)?;
+        check_account_count(accounts, WithdrawAccounts::MIN_ACCOUNTS)?;
+        let [owner, vault, system_program, stats, trailing @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        let id = &instruction_data.id;
+        let salt = &instruction_data.salt;
+        let accounts =
+            WithdrawAccounts::check(owner, vault, system_program, stats, trailing, id, salt)?;
+        let config = accounts.config;
+
+        let dust_threshold = match instruction_data.auto_close {
+            true => dust_threshold(config)?,
+            false => 0,
+        };
+
+        // Vesting vaults only release their deposits through WithdrawVested
+        check_not_vesting(accounts.vault)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+            dust_threshold,
+            min_withdraw: min_withdraw(config)?,
+        })
+    }
+}
+
+impl<'a> WithdrawAmount<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &3;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // Nothing to move, but the accounts were still checked
+        if self.instruction_data.amount.eq(&0) {
+            set_return_data(&self.accounts.vault.lamports().to_le_bytes());
+            return Ok(());
+        }
+
+        let available = releasable(self.accounts.vault, self.accounts.rent_sysvar, false)?;
+
+        let requested = match self.instruction_data.mode {
+            WithdrawMode::Exact => self.instruction_data.amount,
+            WithdrawMode::BestEffort => self.instruction_data.amount.min(available),
+        };
+
+        // Dust withdrawals only churn the vault's counters, but draining it is always fine
+        check_min_withdraw(requested, available, self.min_withdraw)?;
+
+        let (lamports, _) = compute_withdraw_amount(available, requested, 0)?;
+
+        // Create PDA signer seeds
+        let seeds = vault_signer_seeds(
+            self.accounts.owner.key(),
+            &self.instruction_data.id,
+            &self.instruction_data.salt,
+            &self.accounts.bumps,
+        );
+        let signers = [Signer::from(&seeds)];
+
+        self.accounts
+            .pay_out(self.accounts.owner, lamports, &signers)?;
+
+        // Sweep dust the withdraw left behind rather than keep an account alive for it
+        let dust = releasable(self.accounts.vault, self.accounts.rent_sysvar, false)?;
+        let swept = if dust.ne(&0) && dust < self.dust_threshold {
+            let rest = releasable(self.accounts.vault, self.accounts.rent_sysvar, true)?;
+            self.accounts.pay_out(self.accounts.owner, rest, &signers)?;
+            rest
+        } else {
+            0
+        };
+
+        self.accounts.record_withdrawal(lamports + swept)?;
+        self.accounts.notify_hook(lamports + swept)?;
+
+        // Report the new vault balance so clients don't need a follow-up fetch
+        set_return_data(&self.accounts.vault.lamports().to_le_bytes());
+
+        Ok(())
+    }
+}
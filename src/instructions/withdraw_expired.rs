@@ -0,0 +1,181 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+use pinocchio_log::log;
+
+use crate::error::VaultError;
+use crate::fee::{pay_out, WithdrawFee};
+use crate::hook::{check_withdraw_config, invoke_hook, HOOK_WITHDRAW};
+use crate::oracle::check_price_gate;
+use crate::parse::read_array;
+use crate::pda::{DEFAULT_SALT, DEFAULT_VAULT_ID};
+use crate::state::VaultState;
+use crate::stats::{check_stats_account, load_stats};
+use crate::sysvar::clock;
+use crate::vault::{
+    check_not_escrowed, check_not_multisig, check_not_vesting, check_vault_address,
+};
+
+/// Account structure for the WithdrawExpired instruction
+///
+/// Accounts: `[caller, owner, vault, system_program, stats, config,
+/// hook_program?, treasury?, oracle?, rent_sysvar?]`, where `vault` is
+/// `owner`'s expired opened vault, `hook_program` is only passed while the
+/// config has one, `treasury` only while it charges a withdrawal fee and
+/// `oracle` only for a price-gated vault. Anyone can sign as `caller`, and pays
+/// for the stats PDA if it doesn't exist yet; the owner doesn't sign.
+pub struct WithdrawExpiredAccounts<'a> {
+    pub caller: &'a AccountInfo,
+    pub owner: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    pub stats: &'a AccountInfo,
+    /// The config's hook program, told about the withdrawal once it lands
+    pub hook: Option<&'a AccountInfo>,
+    /// The config's withdrawal fee, while it charges one
+    pub fee: Option<WithdrawFee<'a>>,
+    pub rent_sysvar: Option<&'a AccountInfo>,
+    pub stats_bump: u8,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &WithdrawExpiredInstructionData)>
+    for WithdrawExpiredAccounts<'a>
+{
+    type Error = ProgramError;
+
+    fn try_from(
+        (accounts, instruction_data): (&'a [AccountInfo], &WithdrawExpiredInstructionData),
+    ) -> Result<Self, Self::Error> {
+        let [caller, owner, vault, system_program, stats, trailing @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        if !caller.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        if caller.key().eq(vault.key()) {
+            return Err(VaultError::InvalidAccountArrangement.into());
+        }
+
+        if system_program.key().ne(&pinocchio_system::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        check_vault_address(owner, vault, &instruction_data.id, &instruction_data.salt)?;
+
+        {
+            let state = VaultState::load(vault)?;
+            if state.owner().ne(owner.key()) {
+                return Err(ProgramError::InvalidAccountOwner);
+            }
+
+            if !state.is_expired(clock()?.unix_timestamp) {
+                return Err(VaultError::VaultNotExpired.into());
+            }
+        }
+
+        // Only the owner is credited, but the other signers still have to agree
+        check_not_escrowed(vault)?;
+        check_not_multisig(vault)?;
+        check_not_vesting(vault)?;
+        let (hook, fee, trailing) = check_withdraw_config(owner.key(), trailing)?;
+        let rent_sysvar = check_price_gate(vault, trailing)?;
+
+        let stats_bump = check_stats_account(stats)?;
+
+        Ok(Self {
+            caller,
+            owner,
+            vault,
+            stats,
+            hook,
+            fee,
+            rent_sysvar: rent_sysvar.first(),
+            stats_bump,
+        })
+    }
+}
+
+/// Instruction data structure for the WithdrawExpired instruction
+///
+/// Layout: `[id: u64][salt: [u8; 32]]`. Both fields are optional, but `salt`
+/// requires `id`: they default to the owner's default unsalted vault.
+pub struct WithdrawExpiredInstructionData {
+    pub id: [u8; 8],
+    pub salt: [u8; 32],
+}
+
+impl<'a> TryFrom<&'a [u8]> for WithdrawExpiredInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let (id, salt) = match data.len() {
+            0 => (DEFAULT_VAULT_ID, DEFAULT_SALT),
+            8 => (read_array(data, 0)?, DEFAULT_SALT),
+            40 => (read_array(data, 0)?, read_array(data, 8)?),
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        Ok(Self { id, salt })
+    }
+}
+
+/// WithdrawExpired instruction - sends every lamport of an expired vault to its
+/// owner, closing it
+///
+/// Fails with `VaultNotExpired` if the vault has no expiry or it hasn't passed
+/// yet, `InvalidPda` if the vault isn't the owner's vault the data derives, and
+/// the usual withdrawal checks, such as freezes, time locks and price gates,
+/// still apply. The withdrawal is recorded in the global stats.
+pub struct WithdrawExpired<'a> {
+    pub accounts: WithdrawExpiredAccounts<'a>,
+    pub instruction_data: WithdrawExpiredInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for WithdrawExpired<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = WithdrawExpiredInstructionData::try_from(data)?;
+        let accounts = WithdrawExpiredAccounts::try_from((accounts, &instruction_data))?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> WithdrawExpired<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &52;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let vault = self.accounts.vault;
+        let lamports = vault.lamports();
+
+        // Opened vaults are debited directly, so no signer seeds are needed
+        let received = pay_out(
+            vault,
+            self.accounts.caller,
+            self.accounts.owner,
+            self.accounts.fee.as_ref(),
+            lamports,
+            &[],
+        )?;
+
+        load_stats(
+            self.accounts.caller,
+            self.accounts.stats,
+            self.accounts.stats_bump,
+            self.accounts.rent_sysvar,
+        )?
+        .record_withdrawal(lamports, 1);
+
+        if let Some(hook) = self.accounts.hook {
+            invoke_hook(hook, HOOK_WITHDRAW, self.accounts.owner, lamports)?;
+        }
+
+        log!("WithdrawExpired: sent {} lamports to the owner", received);
+
+        Ok(())
+    }
+}
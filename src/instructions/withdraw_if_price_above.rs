@@ -0,0 +1,69 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::config::Config;
+use crate::error::VaultError;
+use crate::instructions::{Withdraw, WithdrawInstructionData};
+use crate::oracle::price;
+
+/// WithdrawIfPriceAbove instruction - withdraws like Withdraw, but only while
+/// the configured oracle's price is above a threshold
+///
+/// Layout: `[min_price: i64]` followed by the Withdraw instruction data. Takes
+/// the Withdraw accounts with the config's price oracle right after `stats`,
+/// ahead of the receipt accounts when it burns receipts. Fails with
+/// `PriceConditionNotMet` unless the price is strictly above `min_price`.
+pub struct WithdrawIfPriceAbove<'a> {
+    pub withdraw: Withdraw<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for WithdrawIfPriceAbove<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let (min_price, data) = data
+            .split_first_chunk::<8>()
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        let instruction_data = WithdrawInstructionData::try_from(data)?;
+
+        // Only Withdraw itself withdraws idempotently
+        if instruction_data.idempotent {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let [owner, vault, system_program, stats, price_oracle, trailing @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        let withdraw = Withdraw::check(
+            owner,
+            vault,
+            system_program,
+            stats,
+            trailing,
+            instruction_data,
+        )?;
+
+        // Only the oracle the admin configured can unlock the withdrawal
+        {
+            let config = Config::load(withdraw.accounts.config)?;
+            if config.price_oracle().eq(&[0u8; 32]) || price_oracle.key().ne(config.price_oracle())
+            {
+                return Err(ProgramError::InvalidAccountData);
+            }
+        }
+
+        if price(price_oracle)? <= i64::from_le_bytes(*min_price) {
+            return Err(VaultError::PriceConditionNotMet.into());
+        }
+
+        Ok(Self { withdraw })
+    }
+}
+
+impl<'a> WithdrawIfPriceAbove<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &24;
+
+    pub fn process(&mut self) -> ProgramResult {
+        self.withdraw.process()
+    }
+}
@@ -0,0 +1,189 @@
+use pinocchio::{
+    account_info::AccountInfo, instruction::Signer, program_error::ProgramError, ProgramResult,
+};
+use pinocchio_log::log;
+
+use crate::error::VaultError;
+use crate::fee::{pay_out, WithdrawFee};
+use crate::hook::{check_withdraw_config, invoke_hook, HOOK_WITHDRAW};
+use crate::oracle::check_price_gate;
+use crate::pda::{find_vault_address, log_vault_mismatch, vault_signer_seeds, DEFAULT_SALT};
+use crate::stats::{check_stats_account, load_stats};
+use crate::vault::{
+    check_account_arrangement, check_batch_size, check_distinct_vaults, check_not_escrowed,
+    check_not_multisig, check_not_vesting, check_vault_owner, releasable,
+};
+
+/// Account structure for the WithdrawMany instruction
+///
+/// Accounts: `[owner, config, hook_program?, treasury?, vaults @ ..,
+/// system_program, stats]`, with at most `MAX_BATCH_VAULTS` vaults, where
+/// `hook_program` is only passed while the config has one and `treasury` only
+/// while it charges a withdrawal fee. Each vault can only be passed once.
+pub struct WithdrawManyAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub vaults: &'a [AccountInfo],
+    pub stats: &'a AccountInfo,
+    pub stats_bump: u8,
+    /// The config's hook program, told about the batch's total once it lands
+    pub hook: Option<&'a AccountInfo>,
+    /// The config's withdrawal fee, while it charges one
+    pub fee: Option<WithdrawFee<'a>>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for WithdrawManyAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [owner, trailing @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        let (hook, fee, trailing) = check_withdraw_config(owner.key(), trailing)?;
+        let [vaults @ .., system_program, stats] = trailing else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if vaults.is_empty() {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        check_batch_size(vaults.len())?;
+        check_distinct_vaults(vaults.iter())?;
+        vaults
+            .iter()
+            .try_for_each(|vault| check_account_arrangement(owner, vault))?;
+
+        // Basic Accounts Checks
+        if !owner.is_signer() {
+            return Err(VaultError::MissingOwnerSignature.into());
+        }
+
+        if system_program.key() != &pinocchio_system::ID {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let stats_bump = check_stats_account(stats)?;
+
+        Ok(Self {
+            owner,
+            vaults,
+            stats,
+            stats_bump,
+            hook,
+            fee,
+        })
+    }
+}
+
+/// Instruction data structure for the WithdrawMany instruction
+///
+/// Layout: `[id: u64]` repeated once per vault account, in the same order.
+/// Only unsalted vaults without a price gate can be batched.
+pub struct WithdrawManyInstructionData<'a> {
+    pub ids: &'a [u8],
+}
+
+impl<'a> TryFrom<&'a [u8]> for WithdrawManyInstructionData<'a> {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.is_empty() || !data.len().is_multiple_of(8) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { ids: data })
+    }
+}
+
+/// WithdrawMany instruction - drains several of the owner's vaults in one go
+pub struct WithdrawMany<'a> {
+    pub accounts: WithdrawManyAccounts<'a>,
+    pub instruction_data: WithdrawManyInstructionData<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for WithdrawMany<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = WithdrawManyAccounts::try_from(accounts)?;
+        let instruction_data = WithdrawManyInstructionData::try_from(data)?;
+
+        // Every vault account needs a matching id
+        if instruction_data.ids.len() / 8 != accounts.vaults.len() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> WithdrawMany<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &2;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let owner = self.accounts.owner;
+        let mut total: u64 = 0;
+        let mut closed: u64 = 0;
+
+        for (vault, id) in self
+            .accounts
+            .vaults
+            .iter()
+            .zip(self.instruction_data.ids.chunks_exact(8))
+        {
+            let id: &[u8; 8] = id.try_into().unwrap();
+
+            // Accounts Checks
+            check_vault_owner(vault)?;
+            check_price_gate(vault, &[])?;
+            check_not_vesting(vault)?;
+            check_not_escrowed(vault)?;
+            check_not_multisig(vault)?;
+
+            let (vault_key, bump) = find_vault_address(owner.key(), id, &DEFAULT_SALT);
+            if vault.key().ne(&vault_key) {
+                log_vault_mismatch(bump, vault.key());
+                return Err(VaultError::InvalidPda.into());
+            }
+
+            // Nothing to drain, move on to the next vault
+            let lamports = releasable(vault, None, true)?;
+            if lamports.eq(&0) {
+                continue;
+            }
+
+            let bumps = [bump];
+            let seeds = vault_signer_seeds(owner.key(), id, &DEFAULT_SALT, &bumps);
+            let signers = [Signer::from(&seeds)];
+
+            pay_out(
+                vault,
+                owner,
+                owner,
+                self.accounts.fee.as_ref(),
+                lamports,
+                &signers,
+            )?;
+
+            total = total
+                .checked_add(lamports)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            closed += vault.lamports().eq(&0) as u64;
+        }
+
+        load_stats(owner, self.accounts.stats, self.accounts.stats_bump, None)?
+            .record_withdrawal(total, closed);
+
+        log!("WithdrawMany: withdrew {} lamports", total);
+
+        if let Some(hook) = self.accounts.hook {
+            invoke_hook(hook, HOOK_WITHDRAW, owner, total)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,120 @@
+use pinocchio::{
+    account_info::AccountInfo, cpi::set_return_data, instruction::Signer,
+    program_error::ProgramError, ProgramResult,
+};
+
+use crate::error::VaultError;
+use crate::instructions::{WithdrawAccounts, WithdrawInstructionData};
+use crate::pda::vault_signer_seeds;
+use crate::state::VaultState;
+use crate::vault::{check_not_vesting, is_opened};
+
+/// Account structure for the WithdrawTo instruction
+///
+/// Accounts: `[owner, vault, system_program, stats, recipient, config, hook_program?,
+/// escrow_agent?, multisig_signers.., oracle?, rent_sysvar?]`, the Withdraw
+/// accounts with the recipient right after `stats`.
+pub struct WithdrawToAccounts<'a> {
+    pub withdraw: WithdrawAccounts<'a>,
+    pub recipient: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &WithdrawInstructionData)> for WithdrawToAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(
+        (accounts, instruction_data): (&'a [AccountInfo], &WithdrawInstructionData),
+    ) -> Result<Self, Self::Error> {
+        let [owner, vault, system_program, stats, recipient, trailing @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        let withdraw = WithdrawAccounts::check(
+            owner,
+            vault,
+            system_program,
+            stats,
+            trailing,
+            &instruction_data.id,
+            &instruction_data.salt,
+        )?;
+
+        // Withdrawing into the vault itself would move nothing yet count as a withdrawal
+        if recipient.key().eq(vault.key()) {
+            return Err(VaultError::InvalidRecipient.into());
+        }
+
+        // An opened vault's allowlist, once non-empty, names every permitted recipient
+        // besides the owner, who can always withdraw like with Withdraw
+        if is_opened(vault)
+            && recipient.key().ne(owner.key())
+            && !VaultState::load(vault)?.is_dest_allowed(recipient.key())
+        {
+            return Err(VaultError::DestinationNotAllowed.into());
+        }
+
+        Ok(Self {
+            withdraw,
+            recipient,
+        })
+    }
+}
+
+/// WithdrawTo instruction - withdraws like Withdraw, but to another recipient
+///
+/// Takes the same instruction data as Withdraw. The owner as recipient makes it
+/// a plain Withdraw, and the vault itself fails with `InvalidRecipient`.
+pub struct WithdrawTo<'a> {
+    pub accounts: WithdrawToAccounts<'a>,
+    pub instruction_data: WithdrawInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for WithdrawTo<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = WithdrawInstructionData::try_from(data)?;
+
+        // Only Withdraw itself takes receipt accounts to burn from and withdraws idempotently
+        if instruction_data.receipt || instruction_data.idempotent {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let accounts = WithdrawToAccounts::try_from((accounts, &instruction_data))?;
+
+        // Vesting vaults only release their deposits through WithdrawVested
+        check_not_vesting(accounts.withdraw.vault)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> WithdrawTo<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &20;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let withdraw = &self.accounts.withdraw;
+
+        // Create PDA signer seeds
+        let seeds = vault_signer_seeds(
+            withdraw.owner.key(),
+            &self.instruction_data.id,
+            &self.instruction_data.salt,
+            &withdraw.bumps,
+        );
+        let signers = [Signer::from(&seeds)];
+
+        let lamports = self.instruction_data.amount(withdraw)?;
+
+        withdraw.pay_out(self.accounts.recipient, lamports, &signers)?;
+        withdraw.record_withdrawal(lamports)?;
+        withdraw.notify_hook(lamports)?;
+
+        // Report the new vault balance so clients don't need a follow-up fetch
+        set_return_data(&withdraw.vault.lamports().to_le_bytes());
+
+        Ok(())
+    }
+}
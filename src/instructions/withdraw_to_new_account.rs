@@ -0,0 +1,92 @@
+use pinocchio::{
+    account_info::AccountInfo, cpi::set_return_data, instruction::Signer,
+    program_error::ProgramError, ProgramResult,
+};
+
+use crate::fee::net_of_fee;
+use crate::instructions::{WithdrawInstructionData, WithdrawToAccounts};
+use crate::pda::vault_signer_seeds;
+use crate::rent::minimum_balance;
+use crate::vault::check_not_vesting;
+
+/// WithdrawToNewAccount instruction - withdraws like WithdrawTo, into a brand-new
+/// system account
+///
+/// Takes the WithdrawTo accounts and data, with the recipient signing as a fresh
+/// keypair so the withdrawal is what creates its account, e.g. to onboard a new
+/// wallet in one instruction. A recipient that already holds lamports or data
+/// fails with `AccountAlreadyInitialized`, and a withdrawal too small to leave
+/// the new account rent-exempt with `AccountNotRentExempt`.
+pub struct WithdrawToNewAccount<'a> {
+    pub accounts: WithdrawToAccounts<'a>,
+    pub instruction_data: WithdrawInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for WithdrawToNewAccount<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = WithdrawInstructionData::try_from(data)?;
+
+        // Only Withdraw itself takes receipt accounts to burn from and withdraws idempotently
+        if instruction_data.receipt || instruction_data.idempotent {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let accounts = WithdrawToAccounts::try_from((accounts, &instruction_data))?;
+
+        // The new account's keypair signs, so nobody funds an address they don't control
+        let recipient = accounts.recipient;
+        if !recipient.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        if !recipient.is_owned_by(&pinocchio_system::ID)
+            || recipient.lamports().ne(&0)
+            || recipient.data_len().ne(&0)
+        {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        // Vesting vaults only release their deposits through WithdrawVested
+        check_not_vesting(accounts.withdraw.vault)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> WithdrawToNewAccount<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &43;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let withdraw = &self.accounts.withdraw;
+
+        // The new account only ever holds what the fee leaves
+        let lamports = self.instruction_data.amount(withdraw)?;
+        let received = net_of_fee(withdraw.fee.as_ref(), lamports)?;
+        if received < minimum_balance(withdraw.rent_sysvar, 0)? {
+            return Err(ProgramError::AccountNotRentExempt);
+        }
+
+        // Create PDA signer seeds
+        let seeds = vault_signer_seeds(
+            withdraw.owner.key(),
+            &self.instruction_data.id,
+            &self.instruction_data.salt,
+            &withdraw.bumps,
+        );
+        let signers = [Signer::from(&seeds)];
+
+        // Crediting the empty system account is what brings it into existence
+        withdraw.pay_out(self.accounts.recipient, lamports, &signers)?;
+        withdraw.record_withdrawal(lamports)?;
+        withdraw.notify_hook(lamports)?;
+
+        // Report the new vault balance so clients don't need a follow-up fetch
+        set_return_data(&withdraw.vault.lamports().to_le_bytes());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,199 @@
+use pinocchio::{
+    account_info::AccountInfo, instruction::Signer, program_error::ProgramError, pubkey::Pubkey,
+    ProgramResult,
+};
+use pinocchio_log::log;
+
+use crate::error::VaultError;
+use crate::pda::{vault_signer_seeds, DEFAULT_SALT, DEFAULT_VAULT_ID};
+use crate::state::VaultState;
+use crate::token::{
+    check_vault_token_account, find_associated_token_address, token_account_owner, Transfer,
+    TOKEN_PROGRAM_ID,
+};
+use crate::vault::{
+    check_not_escrowed, check_not_multisig, check_release_allowed, check_vault_address,
+};
+
+/// Account structure for the WithdrawToken instruction
+///
+/// Accounts: `[owner, vault, vault_token_account, destination, token_program]`,
+/// where `vault` is `owner`'s opened vault, `vault_token_account` its associated
+/// token account for the withdrawn mint and `destination` the owner's associated
+/// token account for the same mint. A vault with a token destination owner
+/// sends to that owner's associated token account instead.
+pub struct WithdrawTokenAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    pub vault_token_account: &'a AccountInfo,
+    pub destination: &'a AccountInfo,
+    pub bumps: [u8; 1],
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &WithdrawTokenInstructionData)> for WithdrawTokenAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(
+        (accounts, instruction_data): (&'a [AccountInfo], &WithdrawTokenInstructionData),
+    ) -> Result<Self, Self::Error> {
+        let [owner, vault, vault_token_account, destination, token_program, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        if !owner.is_signer() {
+            return Err(VaultError::MissingOwnerSignature.into());
+        }
+
+        if token_program.key().ne(&TOKEN_PROGRAM_ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let bump = check_vault_address(owner, vault, &instruction_data.id, &instruction_data.salt)?;
+
+        {
+            // Only opened vaults track token balances
+            let state = VaultState::load(vault)?;
+            check_release_allowed(&state)?;
+
+            if state.token_balance(&instruction_data.mint) < instruction_data.amount {
+                return Err(VaultError::InsufficientFunds.into());
+            }
+
+            // Derived on-chain, so a client can't send the tokens anywhere else
+            let wallet = match state.token_dest_owner() {
+                dest_owner if dest_owner.eq(&[0; 32]) => owner.key(),
+                dest_owner => dest_owner,
+            };
+            let (destination_ata, _) =
+                find_associated_token_address(wallet, &instruction_data.mint);
+            if destination.key().ne(&destination_ata) {
+                return Err(VaultError::InvalidDestinationAta.into());
+            }
+
+            if !state.is_token_dest_allowed(&token_account_owner(destination)?) {
+                return Err(VaultError::DestinationNotAllowed.into());
+            }
+        }
+
+        // An escrow agent or multisig has to agree to anything leaving the vault
+        check_not_escrowed(vault)?;
+        check_not_multisig(vault)?;
+
+        check_vault_token_account(vault_token_account, vault.key(), &instruction_data.mint)?;
+
+        Ok(Self {
+            owner,
+            vault,
+            vault_token_account,
+            destination,
+            bumps: [bump],
+        })
+    }
+}
+
+/// Instruction data structure for the WithdrawToken instruction
+///
+/// Layout: `[mint: Pubkey][amount: u64][id: u64][salt: [u8; 32]]`. Both `id`
+/// and `salt` are optional, but `salt` requires `id`: they default to the
+/// owner's default unsalted vault.
+pub struct WithdrawTokenInstructionData {
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub id: [u8; 8],
+    pub salt: [u8; 32],
+}
+
+impl<'a> TryFrom<&'a [u8]> for WithdrawTokenInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let (id, salt) = match data.len() {
+            40 => (DEFAULT_VAULT_ID, DEFAULT_SALT),
+            48 => (data[40..48].try_into().unwrap(), DEFAULT_SALT),
+            80 => (
+                data[40..48].try_into().unwrap(),
+                data[48..80].try_into().unwrap(),
+            ),
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        let mint = data[..32].try_into().unwrap();
+        let amount = u64::from_le_bytes(data[32..40].try_into().unwrap());
+
+        // Instruction Checks
+        if amount.eq(&0) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            mint,
+            amount,
+            id,
+            salt,
+        })
+    }
+}
+
+/// WithdrawToken instruction - moves tracked SPL tokens of one mint out of the
+/// opened vault
+///
+/// Each mint is withdrawn on its own, and never more than the vault tracks of
+/// it, failing with `InsufficientFunds` otherwise, and only to the owner's
+/// associated token account for the mint, or the token destination owner's
+/// while the vault has one, failing with `InvalidDestinationAta` otherwise.
+/// Freezes, time locks and the minimum age apply as they do to lamports.
+pub struct WithdrawToken<'a> {
+    pub accounts: WithdrawTokenAccounts<'a>,
+    pub instruction_data: WithdrawTokenInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for WithdrawToken<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = WithdrawTokenInstructionData::try_from(data)?;
+        let accounts = WithdrawTokenAccounts::try_from((accounts, &instruction_data))?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> WithdrawToken<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &55;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let mint = &self.instruction_data.mint;
+        let amount = self.instruction_data.amount;
+
+        let tracked = {
+            let mut state = VaultState::load_mut(self.accounts.vault)?;
+            state.debit_token(mint, amount)?;
+            state.token_balance(mint)
+        };
+
+        let seeds = vault_signer_seeds(
+            self.accounts.owner.key(),
+            &self.instruction_data.id,
+            &self.instruction_data.salt,
+            &self.accounts.bumps,
+        );
+        let signers = [Signer::from(&seeds)];
+
+        Transfer {
+            from: self.accounts.vault_token_account,
+            to: self.accounts.destination,
+            authority: self.accounts.vault,
+            amount,
+        }
+        .invoke_signed(&signers)?;
+
+        log!("WithdrawToken: vault now tracks {} tokens of mint", tracked);
+        pinocchio::pubkey::log(mint);
+
+        Ok(())
+    }
+}
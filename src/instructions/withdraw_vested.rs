@@ -0,0 +1,111 @@
+use pinocchio::{
+    account_info::AccountInfo, cpi::set_return_data, instruction::Signer,
+    program_error::ProgramError, ProgramResult,
+};
+
+use crate::error::VaultError;
+use crate::instructions::WithdrawAccounts;
+use crate::pda::{vault_signer_seeds, DEFAULT_SALT, DEFAULT_VAULT_ID};
+use crate::state::VaultState;
+use crate::sysvar::clock;
+use crate::vault::releasable;
+
+/// Instruction data structure for the WithdrawVested instruction
+///
+/// Layout: `[id: u64][salt: [u8; 32]]`. Both fields are optional, but `salt`
+/// requires `id`; they default to the owner's default unsalted vault.
+pub struct WithdrawVestedInstructionData {
+    pub id: [u8; 8],
+    pub salt: [u8; 32],
+}
+
+impl<'a> TryFrom<&'a [u8]> for WithdrawVestedInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let (id, salt) = match data.len() {
+            0 => (DEFAULT_VAULT_ID, DEFAULT_SALT),
+            8 => (data.try_into().unwrap(), DEFAULT_SALT),
+            40 => (
+                data[..8].try_into().unwrap(),
+                data[8..40].try_into().unwrap(),
+            ),
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        Ok(Self { id, salt })
+    }
+}
+
+/// WithdrawVested instruction - transfers the vested, not yet withdrawn deposits back to owner
+///
+/// Uses the same accounts as Withdraw, and only applies to opened vaults with a
+/// vesting schedule. Deposits vest linearly: `total_deposited * elapsed / duration`
+/// is available, minus whatever was already withdrawn. The vault keeps its
+/// rent-exempt reserve.
+pub struct WithdrawVested<'a> {
+    pub accounts: WithdrawAccounts<'a>,
+    pub instruction_data: WithdrawVestedInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for WithdrawVested<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = WithdrawVestedInstructionData::try_from(data)?;
+        let accounts = WithdrawAccounts::try_from((
+            accounts,
+            &instruction_data.id,
+            &instruction_data.salt,
+        ))?;
+
+        if !VaultState::load(accounts.vault)?.is_vesting() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> WithdrawVested<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &10;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let unlocked = {
+            let state = VaultState::load(self.accounts.vault)?;
+            state
+                .vested(clock()?.unix_timestamp)
+                .saturating_sub(state.withdrawn_so_far())
+        };
+
+        let available = releasable(self.accounts.vault, self.accounts.rent_sysvar, false)?;
+
+        let lamports = unlocked.min(available);
+        if lamports.eq(&0) {
+            return Err(VaultError::VaultEmpty.into());
+        }
+
+        // Create PDA signer seeds
+        let seeds = vault_signer_seeds(
+            self.accounts.owner.key(),
+            &self.instruction_data.id,
+            &self.instruction_data.salt,
+            &self.accounts.bumps,
+        );
+        let signers = [Signer::from(&seeds)];
+
+        self.accounts
+            .pay_out(self.accounts.owner, lamports, &signers)?;
+        VaultState::load_mut(self.accounts.vault)?.record_vested_withdrawal(lamports);
+        self.accounts.record_withdrawal(lamports)?;
+        self.accounts.notify_hook(lamports)?;
+
+        // Report the new vault balance so clients don't need a follow-up fetch
+        set_return_data(&self.accounts.vault.lamports().to_le_bytes());
+
+        Ok(())
+    }
+}
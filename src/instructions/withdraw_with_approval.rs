@@ -0,0 +1,228 @@
+use pinocchio::{
+    account_info::AccountInfo, cpi::set_return_data, instruction::Signer,
+    program_error::ProgramError, ProgramResult,
+};
+
+use crate::approval::{approval_message, check_ed25519_approval};
+use crate::error::VaultError;
+use crate::fee::{pay_out, WithdrawFee};
+use crate::hook::{check_withdraw_config, invoke_hook, HOOK_WITHDRAW};
+use crate::logic::compute_withdraw_amount;
+use crate::oracle::check_price_gate;
+use crate::pda::{vault_signer_seeds, DEFAULT_SALT, DEFAULT_VAULT_ID};
+use crate::state::VaultState;
+use crate::stats::{check_stats_account, load_stats};
+use crate::sysvar::clock;
+use crate::vault::{
+    check_not_escrowed, check_not_multisig, check_not_vesting, check_vault_address, releasable,
+};
+
+/// Account structure for the WithdrawWithApproval instruction
+///
+/// Accounts: `[relayer, owner, vault, system_program, stats, instructions_sysvar,
+/// config, hook_program?, treasury?, oracle?, rent_sysvar?]`, where `vault` is
+/// `owner`'s opened vault, `hook_program` is only passed while the config has
+/// one, `treasury` only while it charges a withdrawal fee and `oracle` only for
+/// a price-gated vault. Only `relayer` signs; it
+/// pays the fees and the stats PDA's rent if it doesn't exist yet.
+pub struct WithdrawWithApprovalAccounts<'a> {
+    pub relayer: &'a AccountInfo,
+    pub owner: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    pub stats: &'a AccountInfo,
+    /// The config's hook program, told about the withdrawal once it lands
+    pub hook: Option<&'a AccountInfo>,
+    /// The config's withdrawal fee, while it charges one
+    pub fee: Option<WithdrawFee<'a>>,
+    pub rent_sysvar: Option<&'a AccountInfo>,
+    pub bumps: [u8; 1],
+    pub stats_bump: u8,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &WithdrawWithApprovalInstructionData)>
+    for WithdrawWithApprovalAccounts<'a>
+{
+    type Error = ProgramError;
+
+    fn try_from(
+        (accounts, instruction_data): (&'a [AccountInfo], &WithdrawWithApprovalInstructionData),
+    ) -> Result<Self, Self::Error> {
+        let [relayer, owner, vault, system_program, stats, instructions_sysvar, trailing @ ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Accounts Checks
+        if !relayer.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        if system_program.key().ne(&pinocchio_system::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let bump = check_vault_address(owner, vault, &instruction_data.id, &instruction_data.salt)?;
+
+        // Only opened vaults have state to record the used nonce in
+        VaultState::load(vault)?;
+
+        // The owner's signature stands in for the owner signing the transaction
+        check_ed25519_approval(
+            instructions_sysvar,
+            owner.key(),
+            &approval_message(
+                vault.key(),
+                instruction_data.amount,
+                instruction_data.nonce,
+                instruction_data.expiry_ts,
+            ),
+        )?;
+
+        if clock()?.unix_timestamp > instruction_data.expiry_ts {
+            return Err(VaultError::ApprovalExpired.into());
+        }
+
+        // Vesting vaults only release their deposits through WithdrawVested
+        check_not_vesting(vault)?;
+        check_not_escrowed(vault)?;
+        check_not_multisig(vault)?;
+
+        let stats_bump = check_stats_account(stats)?;
+
+        let (hook, fee, trailing) = check_withdraw_config(owner.key(), trailing)?;
+        let rent_sysvar = check_price_gate(vault, trailing)?;
+
+        Ok(Self {
+            relayer,
+            owner,
+            vault,
+            stats,
+            hook,
+            fee,
+            rent_sysvar: rent_sysvar.first(),
+            bumps: [bump],
+            stats_bump,
+        })
+    }
+}
+
+/// Instruction data structure for the WithdrawWithApproval instruction
+///
+/// Layout: `[amount: u64][nonce: u64][expiry_ts: i64][id: u64][salt: [u8; 32]]`.
+/// `id` and `salt` are optional, but `salt` requires `id`; they default to the
+/// owner's default unsalted vault. The owner approves it by signing
+/// `approval::approval_message(vault, amount, nonce, expiry_ts)`, which an
+/// ed25519 precompile instruction right before this one has to verify. `nonce`
+/// has to be above the last one the vault accepted, and the approval stops
+/// working once the clock passes `expiry_ts`.
+pub struct WithdrawWithApprovalInstructionData {
+    pub amount: u64,
+    pub nonce: u64,
+    pub expiry_ts: i64,
+    pub id: [u8; 8],
+    pub salt: [u8; 32],
+}
+
+impl<'a> TryFrom<&'a [u8]> for WithdrawWithApprovalInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let (id, salt) = match data.len() {
+            24 => (DEFAULT_VAULT_ID, DEFAULT_SALT),
+            32 => (data[24..32].try_into().unwrap(), DEFAULT_SALT),
+            64 => (
+                data[24..32].try_into().unwrap(),
+                data[32..64].try_into().unwrap(),
+            ),
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        let amount = u64::from_le_bytes(data[..8].try_into().unwrap());
+        let nonce = u64::from_le_bytes(data[8..16].try_into().unwrap());
+        let expiry_ts = i64::from_le_bytes(data[16..24].try_into().unwrap());
+
+        // Instruction Checks
+        if amount.eq(&0) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            amount,
+            nonce,
+            expiry_ts,
+            id,
+            salt,
+        })
+    }
+}
+
+/// WithdrawWithApproval instruction - a relayer withdraws to the owner on their
+/// signed, single-use approval
+///
+/// The vault keeps its rent-exempt reserve, and exactly `amount` lamports go to
+/// the owner, never the relayer.
+pub struct WithdrawWithApproval<'a> {
+    pub accounts: WithdrawWithApprovalAccounts<'a>,
+    pub instruction_data: WithdrawWithApprovalInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for WithdrawWithApproval<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = WithdrawWithApprovalInstructionData::try_from(data)?;
+        let accounts = WithdrawWithApprovalAccounts::try_from((accounts, &instruction_data))?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> WithdrawWithApproval<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &27;
+
+    pub fn process(&mut self) -> ProgramResult {
+        VaultState::load_mut(self.accounts.vault)?.use_nonce(self.instruction_data.nonce)?;
+
+        let available = releasable(self.accounts.vault, self.accounts.rent_sysvar, false)?;
+        let (lamports, _) = compute_withdraw_amount(available, self.instruction_data.amount, 0)?;
+
+        // Create PDA signer seeds
+        let seeds = vault_signer_seeds(
+            self.accounts.owner.key(),
+            &self.instruction_data.id,
+            &self.instruction_data.salt,
+            &self.accounts.bumps,
+        );
+        let signers = [Signer::from(&seeds)];
+
+        pay_out(
+            self.accounts.vault,
+            self.accounts.owner,
+            self.accounts.owner,
+            self.accounts.fee.as_ref(),
+            lamports,
+            &signers,
+        )?;
+
+        load_stats(
+            self.accounts.relayer,
+            self.accounts.stats,
+            self.accounts.stats_bump,
+            self.accounts.rent_sysvar,
+        )?
+        .record_withdrawal(lamports, self.accounts.vault.lamports().eq(&0) as u64);
+
+        if let Some(hook) = self.accounts.hook {
+            invoke_hook(hook, HOOK_WITHDRAW, self.accounts.owner, lamports)?;
+        }
+
+        // Report the new vault balance so clients don't need a follow-up fetch
+        set_return_data(&self.accounts.vault.lamports().to_le_bytes());
+
+        Ok(())
+    }
+}
@@ -1,29 +1,51 @@
 #![no_std]
 
-use pinocchio::{account_info::AccountInfo, entrypoint, nostd_panic_handler, program_error::ProgramError, pubkey::Pubkey, ProgramResult};
+use pinocchio::{account_info::AccountInfo, entrypoint, nostd_panic_handler, pubkey::Pubkey, ProgramResult};
 
 entrypoint!(process_instruction);
 nostd_panic_handler!();
 
+#[cfg(not(target_os = "solana"))]
+pub mod client;
+
+pub mod approval;
+pub mod config;
+pub mod error;
+pub use error::*;
+pub mod events;
+pub mod fee;
+pub mod hook;
+
 pub mod instructions;
 pub use instructions::*;
 
-// 22222222222222222222222222222222222222222222
-pub const ID: Pubkey = [
-    0x0f, 0x1e, 0x6b, 0x14, 0x21, 0xc0, 0x4a, 0x07,
-    0x04, 0x31, 0x26, 0x5c, 0x19, 0xc5, 0xbb, 0xee,
-    0x19, 0x92, 0xba, 0xe8, 0xaf, 0xd1, 0xcd, 0x07,
-    0x8e, 0xf8, 0xaf, 0x70, 0x47, 0xdc, 0x11, 0xf7,
-];
+pub mod logic;
+pub mod metadata;
+pub mod oracle;
+pub mod parse;
+pub mod pda;
+pub mod receipt;
+pub mod registry;
+pub mod rent;
+pub mod reward_pool;
+pub mod state;
+pub mod stats;
+pub mod sysvar;
+pub mod tag;
+pub mod token;
+pub mod tombstone;
+pub mod vault;
+
+#[cfg(all(feature = "testing", not(target_os = "solana")))]
+pub mod testing;
+
+// Program id, exported as `ID` so clients and tests don't repeat the literal
+pinocchio_pubkey::declare_id!("22222222222222222222222222222222222222222222");
 
 fn process_instruction(
     _program_id: &Pubkey,
     accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    match instruction_data.split_first() {
-        Some((Deposit::DISCRIMINATOR, data)) => Deposit::try_from((data, accounts))?.process(),
-        Some((Withdraw::DISCRIMINATOR, _)) => Withdraw::try_from(accounts)?.process(),
-        _ => Err(ProgramError::InvalidInstructionData),
-    }
+    VaultInstruction::unpack(parse::verify_checksum(instruction_data)?)?.dispatch(accounts)
 }
\ No newline at end of file
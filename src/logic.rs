@@ -0,0 +1,193 @@
+//! Business logic as pure functions, free of accounts and the Solana runtime
+//!
+//! Handlers gather the inputs from their accounts and call in here, so the
+//! arithmetic can be tested on the host without LiteSVM.
+//!
+//! # Rounding
+//!
+//! Fees are computed on the exact product `amount * bps` and round down by
+//! default. Whatever the division leaves over, under one lamport's worth of
+//! fee, goes to the owner and never to the treasury, so each part of a split
+//! adds back up to the amount it came from to the lamport. A config can opt
+//! into [`FeeRounding::FavorTreasury`] instead, rounding fees up so the dust
+//! goes to the fee's recipient; the parts still add up exactly.
+
+use crate::error::VaultError;
+
+/// Denominator of every basis-point rate
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Returns the share of `amount` owed for a fee of `bps` basis points, rounded down
+#[inline(always)]
+pub fn bps_of(amount: u64, bps: u16) -> u64 {
+    // For `bps` up to `BPS_DENOMINATOR` the result always fits back in a u64
+    (amount as u128 * bps as u128 / BPS_DENOMINATOR as u128) as u64
+}
+
+/// Which side of a fee its rounding dust goes to
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FeeRounding {
+    /// The fee rounds down, leaving the dust with the owner
+    #[default]
+    FavorOwner,
+    /// The fee rounds up, sending the dust to the treasury or referrer
+    FavorTreasury,
+}
+
+/// Returns the share of `amount` owed for a fee of `bps` basis points, rounded
+/// in the direction `rounding` picks
+#[inline(always)]
+pub fn bps_of_rounded(amount: u64, bps: u16, rounding: FeeRounding) -> u64 {
+    match rounding {
+        FeeRounding::FavorOwner => bps_of(amount, bps),
+        // Never more than `amount` while `bps` is up to `BPS_DENOMINATOR`
+        FeeRounding::FavorTreasury => {
+            (amount as u128 * bps as u128).div_ceil(BPS_DENOMINATOR as u128) as u64
+        }
+    }
+}
+
+/// Splits a withdrawal of `requested` lamports out of `balance` into the part
+/// paid to the owner and a `fee_bps` fee paid to the treasury
+///
+/// Follows the module's default rounding policy: the fee rounds down and the
+/// owner receives the remainder, rounding dust included.
+///
+/// Fails with `VaultEmpty` for nothing requested, `InsufficientFunds` if
+/// `balance` can't cover it, and `InvalidFee` for a fee above 100%.
+#[inline(always)]
+pub fn compute_withdraw_amount(
+    balance: u64,
+    requested: u64,
+    fee_bps: u16,
+) -> Result<(u64, u64), VaultError> {
+    compute_withdraw_amount_rounded(balance, requested, fee_bps, FeeRounding::FavorOwner)
+}
+
+/// [`compute_withdraw_amount`] with the fee rounded in the direction
+/// `rounding` picks; the owner receives whatever the fee leaves
+#[inline(always)]
+pub fn compute_withdraw_amount_rounded(
+    balance: u64,
+    requested: u64,
+    fee_bps: u16,
+    rounding: FeeRounding,
+) -> Result<(u64, u64), VaultError> {
+    if fee_bps as u64 > BPS_DENOMINATOR {
+        return Err(VaultError::InvalidFee);
+    }
+
+    if requested.eq(&0) {
+        return Err(VaultError::VaultEmpty);
+    }
+
+    if requested > balance {
+        return Err(VaultError::InsufficientFunds);
+    }
+
+    // Only the fee is rounded, so the two parts always add back up to the request
+    let to_treasury = bps_of_rounded(requested, fee_bps, rounding);
+    let to_owner = requested - to_treasury;
+    Ok((to_owner, to_treasury))
+}
+
+/// Returns the withdrawal fee rate `owner` pays, waived to `0` if the `exempt`
+/// whitelist lists them
+///
+/// Feed the result to [`compute_withdraw_amount`], so exempt owners get the whole
+/// request while everyone else pays `fee_bps`.
+#[inline(always)]
+pub fn withdraw_fee_bps(owner: &[u8; 32], fee_bps: u16, exempt: &[[u8; 32]]) -> u16 {
+    if exempt.contains(owner) {
+        return 0;
+    }
+
+    fee_bps
+}
+
+/// Fails with `AccountingMismatch` if an opened vault's `tracked` balance claims
+/// more than the `lamports` it holds above its rent-exempt `reserve`
+///
+/// Every lamport the program tracks is also held by the vault, so a balance
+/// past that means the accounting went wrong somewhere; withdrawals stop rather
+/// than release lamports that aren't there.
+#[inline(always)]
+pub fn check_tracked_balance(lamports: u64, reserve: u64, tracked: u64) -> Result<(), VaultError> {
+    if tracked > lamports.saturating_sub(reserve) {
+        return Err(VaultError::AccountingMismatch);
+    }
+
+    Ok(())
+}
+
+/// Fails with `WithdrawTooSmall` if a withdrawal of `requested` lamports is
+/// below a non-zero `min_withdraw`, unless it takes all `available` lamports
+///
+/// Draining what's left is always allowed, so a vault holding less than the
+/// minimum can still be emptied.
+#[inline(always)]
+pub fn check_min_withdraw(
+    requested: u64,
+    available: u64,
+    min_withdraw: u64,
+) -> Result<(), VaultError> {
+    if requested < min_withdraw && requested < available {
+        return Err(VaultError::WithdrawTooSmall);
+    }
+
+    Ok(())
+}
+
+/// Returns the lamports a withdraw can release from a vault holding `lamports`
+/// with a rent-exempt `reserve`
+///
+/// `tracked` is an opened vault's tracked balance, `None` for a system-owned
+/// vault. See [`crate::vault::releasable`] for how `close` changes the result.
+#[inline(always)]
+pub fn compute_releasable(lamports: u64, reserve: u64, tracked: Option<u64>, close: bool) -> u64 {
+    let spendable = lamports.saturating_sub(reserve);
+    let Some(tracked) = tracked else {
+        return if close { lamports } else { spendable };
+    };
+
+    let tracked = spendable.min(tracked);
+    if close && lamports - tracked <= reserve {
+        return lamports;
+    }
+
+    tracked
+}
+
+/// Returns how many whole drip intervals elapsed between `last_release` and
+/// `now`, and the lamports they release at `amount` each
+///
+/// A zero `interval_secs` means no drip schedule, so nothing is ever due.
+#[inline(always)]
+pub fn compute_drip_due(
+    now: i64,
+    last_release: i64,
+    interval_secs: u64,
+    amount: u64,
+) -> (u64, u64) {
+    if interval_secs.eq(&0) || now <= last_release {
+        return (0, 0);
+    }
+
+    let intervals = now.abs_diff(last_release) / interval_secs;
+    (intervals, intervals.saturating_mul(amount))
+}
+
+/// Seconds in the 365-day year interest rates are quoted per
+pub const SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+
+/// Returns the interest `balance` accrues over `elapsed_secs` at a yearly rate
+/// of `rate_bps` basis points, rounded down
+///
+/// Computed as `balance * rate_bps * elapsed_secs / (10000 * SECONDS_PER_YEAR)`
+/// in u128, saturating at `u64::MAX` for spans no vault will ever reach.
+#[inline(always)]
+pub fn compute_interest(balance: u64, rate_bps: u16, elapsed_secs: u64) -> u64 {
+    let interest = (balance as u128 * rate_bps as u128).saturating_mul(elapsed_secs as u128)
+        / (BPS_DENOMINATOR as u128 * SECONDS_PER_YEAR as u128);
+    u64::try_from(interest).unwrap_or(u64::MAX)
+}
@@ -0,0 +1,143 @@
+use core::mem::size_of;
+
+use pinocchio::{
+    account_info::{AccountInfo, RefMut},
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    ProgramResult,
+};
+use pinocchio_system::instructions::CreateAccount;
+
+use crate::error::VaultError;
+use crate::pda::{find_metadata_address, METADATA_SEED};
+use crate::rent::minimum_balance;
+
+/// Optional metadata of an opened vault, kept out of `VaultState`
+///
+/// Lives in the `["meta", vault]` PDA, which is only created the first time
+/// SetMetadata writes to it, so deposits and withdrawals never load it. Starts
+/// with its own layout version, independent of `VaultState::VERSION`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct VaultMetadata {
+    version: u8,
+    memo_len: u8,
+    memo: [u8; 128],
+}
+
+impl VaultMetadata {
+    pub const LEN: usize = size_of::<Self>();
+
+    /// Current layout version, written to the first byte of the account
+    pub const VERSION: u8 = 1;
+
+    /// Longest memo the metadata can hold, in bytes
+    pub const MAX_MEMO_LEN: usize = 128;
+
+    /// Reinterprets raw account data as vault metadata, e.g. off-chain
+    #[inline(always)]
+    pub fn from_bytes(data: &[u8]) -> Option<&Self> {
+        if data.len().ne(&Self::LEN) || data[0].ne(&Self::VERSION) {
+            return None;
+        }
+
+        Some(unsafe { &*(data.as_ptr() as *const Self) })
+    }
+
+    /// Checks that `memo` fits in the metadata and is valid UTF-8
+    #[inline(always)]
+    pub fn check_memo(memo: &[u8]) -> ProgramResult {
+        if memo.len() > Self::MAX_MEMO_LEN || core::str::from_utf8(memo).is_err() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    #[inline(always)]
+    pub fn memo(&self) -> &[u8] {
+        &self.memo[..(self.memo_len as usize).min(Self::MAX_MEMO_LEN)]
+    }
+
+    /// Replaces the memo; `memo` must already have passed `check_memo`
+    #[inline(always)]
+    pub fn set_memo(&mut self, memo: &[u8]) {
+        self.memo = [0; 128];
+        self.memo[..memo.len()].copy_from_slice(memo);
+        self.memo_len = memo.len() as u8;
+    }
+}
+
+/// Checks that `metadata` is the metadata PDA of `vault`
+#[inline(always)]
+pub fn check_metadata_account(
+    vault: &AccountInfo,
+    metadata: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    let (metadata_key, bump) = find_metadata_address(vault.key());
+    if metadata.key().ne(&metadata_key) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    Ok(bump)
+}
+
+/// Mutably borrows `vault`'s metadata, creating the account at `payer`'s
+/// expense on first use
+pub fn load_metadata<'a>(
+    payer: &AccountInfo,
+    vault: &AccountInfo,
+    metadata: &'a AccountInfo,
+    bump: u8,
+    rent_sysvar: Option<&AccountInfo>,
+) -> Result<RefMut<'a, VaultMetadata>, ProgramError> {
+    if metadata.is_owned_by(&pinocchio_system::ID) {
+        create_metadata(payer, vault, metadata, bump, rent_sysvar)?;
+        metadata.try_borrow_mut_data()?[0] = VaultMetadata::VERSION;
+    } else if !metadata.is_owned_by(&crate::ID) {
+        return Err(VaultError::InvalidVaultOwner.into());
+    }
+
+    let data = metadata.try_borrow_mut_data()?;
+    if data.len().ne(&VaultMetadata::LEN) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if data[0].ne(&VaultMetadata::VERSION) {
+        return Err(VaultError::UnsupportedStateVersion.into());
+    }
+
+    Ok(RefMut::map(data, |data| unsafe {
+        &mut *(data.as_mut_ptr() as *mut VaultMetadata)
+    }))
+}
+
+fn create_metadata(
+    payer: &AccountInfo,
+    vault: &AccountInfo,
+    metadata: &AccountInfo,
+    bump: u8,
+    rent_sysvar: Option<&AccountInfo>,
+) -> ProgramResult {
+    let bumps = [bump];
+    let seeds = [
+        Seed::from(METADATA_SEED),
+        Seed::from(vault.key().as_ref()),
+        Seed::from(&bumps),
+    ];
+    let signers = [Signer::from(&seeds)];
+
+    CreateAccount {
+        from: payer,
+        to: metadata,
+        lamports: minimum_balance(rent_sysvar, VaultMetadata::LEN)?,
+        space: VaultMetadata::LEN as u64,
+        owner: &crate::ID,
+    }
+    .invoke_signed(&signers)
+}
@@ -0,0 +1,68 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError};
+
+use crate::error::VaultError;
+use crate::state::VaultState;
+use crate::sysvar::clock;
+use crate::vault::is_opened;
+
+/// Size of the price account prefix the program reads
+///
+/// Layout: `[price: i64][publish_time: i64]..`, with `publish_time` a unix
+/// timestamp in seconds. Any trailing data is ignored.
+pub const PRICE_ACCOUNT_LEN: usize = 16;
+
+/// Returns the price `oracle` holds
+#[inline(always)]
+pub fn price(oracle: &AccountInfo) -> Result<i64, ProgramError> {
+    let data = oracle.try_borrow_data()?;
+    let price = data.get(..8).ok_or(ProgramError::InvalidAccountData)?;
+
+    Ok(i64::from_le_bytes(price.try_into().unwrap()))
+}
+
+/// Returns the unix timestamp the price in `oracle` was published at
+#[inline(always)]
+pub fn publish_time(oracle: &AccountInfo) -> Result<i64, ProgramError> {
+    let data = oracle.try_borrow_data()?;
+    let publish_time = data
+        .get(8..PRICE_ACCOUNT_LEN)
+        .ok_or(ProgramError::InvalidAccountData)?;
+
+    Ok(i64::from_le_bytes(publish_time.try_into().unwrap()))
+}
+
+/// Enforces `vault`'s price gate, if it has one, and returns the accounts after it
+///
+/// A price-gated vault takes its oracle as the first of `trailing`, and fails
+/// with `StalePrice` unless the price is at most the vault's `max_price_age`
+/// seconds old. Any other vault leaves `trailing` untouched.
+pub fn check_price_gate<'a>(
+    vault: &AccountInfo,
+    trailing: &'a [AccountInfo],
+) -> Result<&'a [AccountInfo], ProgramError> {
+    if !is_opened(vault) {
+        return Ok(trailing);
+    }
+
+    let state = VaultState::load(vault)?;
+    if !state.is_price_gated() {
+        return Ok(trailing);
+    }
+
+    let [oracle, rest @ ..] = trailing else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if oracle.key().ne(state.oracle()) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let age = clock()?
+        .unix_timestamp
+        .saturating_sub(publish_time(oracle)?);
+    if age > i64::try_from(state.max_price_age()).unwrap_or(i64::MAX) {
+        return Err(VaultError::StalePrice.into());
+    }
+
+    Ok(rest)
+}
@@ -0,0 +1,72 @@
+use pinocchio::program_error::ProgramError;
+
+use crate::error::VaultError;
+
+/// Trims `data` to the longest of `layouts`, an instruction's data lengths in
+/// ascending order, that it holds, so trailing bytes past it are ignored
+///
+/// With the `strict-parsing` feature `data` comes back untouched, so trailing
+/// bytes leave it matching none of the layouts and the instruction fails with
+/// `InvalidInstructionData`.
+#[inline(always)]
+pub fn fit_layout<'a>(data: &'a [u8], layouts: &[usize]) -> &'a [u8] {
+    if cfg!(feature = "strict-parsing") {
+        return data;
+    }
+
+    match layouts.iter().rev().find(|&&len| len <= data.len()) {
+        Some(&len) => &data[..len],
+        None => data,
+    }
+}
+
+/// Copies the `N` bytes at `offset` out of `data`, or `None` if it ends before them
+///
+/// Handlers read fixed-width fields through this and [`read_array`] rather than
+/// slicing and unwrapping, so malformed data fails cleanly instead of panicking.
+#[inline(always)]
+pub fn array_at<const N: usize>(data: &[u8], offset: usize) -> Option<[u8; N]> {
+    data.get(offset..offset.checked_add(N)?)?.try_into().ok()
+}
+
+/// Copies the `N` bytes at `offset` out of `data`, failing with
+/// `InvalidInstructionData` if it ends before them
+#[inline(always)]
+pub fn read_array<const N: usize>(data: &[u8], offset: usize) -> Result<[u8; N], ProgramError> {
+    array_at(data, offset).ok_or(ProgramError::InvalidInstructionData)
+}
+
+/// Reads the little-endian `u64` at `offset`, failing like [`read_array`]
+#[inline(always)]
+pub fn read_u64(data: &[u8], offset: usize) -> Result<u64, ProgramError> {
+    read_array(data, offset).map(u64::from_le_bytes)
+}
+
+/// Returns the XOR of every byte of `data`, the checksum clients append to it
+#[inline(always)]
+pub fn checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0, |checksum, byte| checksum ^ byte)
+}
+
+/// Checks and strips the checksum byte off the end of `instruction_data`
+///
+/// With the `verify-checksum` feature the last byte has to be the [`checksum`]
+/// of every byte before it, discriminator included, or this fails with
+/// `ChecksumMismatch`. Without it `instruction_data` comes back untouched, so an
+/// appended checksum is just a trailing byte, which Deposit and Withdraw ignore.
+#[inline(always)]
+pub fn verify_checksum(instruction_data: &[u8]) -> Result<&[u8], ProgramError> {
+    if !cfg!(feature = "verify-checksum") {
+        return Ok(instruction_data);
+    }
+
+    let Some((expected, data)) = instruction_data.split_last() else {
+        return Err(ProgramError::InvalidInstructionData);
+    };
+
+    if checksum(data).ne(expected) {
+        return Err(VaultError::ChecksumMismatch.into());
+    }
+
+    Ok(data)
+}
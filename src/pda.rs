@@ -0,0 +1,182 @@
+use pinocchio::{
+    instruction::Seed,
+    program_error::ProgramError,
+    pubkey::{find_program_address, Pubkey},
+};
+#[cfg(feature = "verbose-logs")]
+use pinocchio_log::log;
+
+/// Seed prefix shared by every vault PDA
+pub const VAULT_SEED: &[u8] = b"vault";
+
+/// Seed prefix of an owner's vault registry PDA
+pub const REGISTRY_SEED: &[u8] = b"registry";
+
+/// Seed of the global stats PDA
+pub const STATS_SEED: &[u8] = b"stats";
+
+/// Seed of the config PDA
+pub const CONFIG_SEED: &[u8] = b"config";
+
+/// Seed of the reward pool PDA
+pub const REWARD_POOL_SEED: &[u8] = b"reward_pool";
+
+/// Seed prefix of a vault's metadata PDA
+pub const METADATA_SEED: &[u8] = b"meta";
+
+/// Seed prefix of a closed vault's tombstone PDA
+pub const TOMBSTONE_SEED: &[u8] = b"tombstone";
+
+/// Seed prefix of an owner's tag index PDA
+pub const TAG_SEED: &[u8] = b"tag";
+
+/// Id of the default vault, which keeps the original `["vault", owner]` address
+pub const DEFAULT_VAULT_ID: [u8; 8] = [0; 8];
+
+/// Salt of an unsalted vault, which keeps the address derived from owner and id only
+pub const DEFAULT_SALT: [u8; 32] = [0; 32];
+
+/// Returns the id seed for a vault.
+///
+/// The default vault has no id seed, so its address is unchanged; every other
+/// id appends its little-endian bytes as a third seed.
+#[inline(always)]
+pub fn id_seed(id: &[u8; 8]) -> &[u8] {
+    if id.eq(&DEFAULT_VAULT_ID) {
+        &[]
+    } else {
+        id
+    }
+}
+
+/// Returns the salt seed for a vault.
+///
+/// A zero salt adds no seed, so unsalted vaults keep their address; any other
+/// salt makes the vault address impossible to derive from the owner alone.
+#[inline(always)]
+pub fn salt_seed(salt: &[u8; 32]) -> &[u8] {
+    if salt.eq(&DEFAULT_SALT) {
+        &[]
+    } else {
+        salt
+    }
+}
+
+/// Most bytes an application seed can have, the size of a salt
+pub const MAX_APPLICATION_SEED_LEN: usize = 32;
+
+/// Returns the salt namespacing a vault by an application-defined `seed`, e.g.
+/// an order id
+///
+/// The seed is zero-padded to 32 bytes and then used like any other salt, so
+/// every instruction taking a salt takes it, and withdrawals derive the same
+/// vault from it as deposits. Seeds only differing in trailing zero bytes share
+/// a vault, and an empty seed is the unsalted vault. Seeds longer than
+/// `MAX_APPLICATION_SEED_LEN` fail with `InvalidInstructionData`.
+#[inline(always)]
+pub fn application_salt(seed: &[u8]) -> Result<[u8; 32], ProgramError> {
+    if seed.len() > MAX_APPLICATION_SEED_LEN {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let mut salt = DEFAULT_SALT;
+    salt[..seed.len()].copy_from_slice(seed);
+    Ok(salt)
+}
+
+/// Returns the seeds the vault PDA of `owner`, `id` and `salt` signs with
+///
+/// Every CPI signed by a vault takes its seeds from here, so they can't drift
+/// from the ones `find_vault_address` derives with. `bump` is borrowed because
+/// the seeds point into it; unsalted vaults pass `DEFAULT_SALT`.
+#[inline(always)]
+pub fn vault_signer_seeds<'a>(
+    owner: &'a Pubkey,
+    id: &'a [u8; 8],
+    salt: &'a [u8; 32],
+    bump: &'a [u8; 1],
+) -> [Seed<'a>; 5] {
+    [
+        Seed::from(VAULT_SEED),
+        Seed::from(owner.as_ref()),
+        Seed::from(id_seed(id)),
+        Seed::from(salt_seed(salt)),
+        Seed::from(bump),
+    ]
+}
+
+/// Derives the vault PDA and its canonical bump for `owner`, vault `id` and `salt`
+#[inline(always)]
+pub fn find_vault_address(owner: &Pubkey, id: &[u8; 8], salt: &[u8; 32]) -> (Pubkey, u8) {
+    find_program_address(
+        &[VAULT_SEED, owner.as_ref(), id_seed(id), salt_seed(salt)],
+        &crate::ID,
+    )
+}
+
+/// Derives the registry PDA and its canonical bump for `owner`
+#[inline(always)]
+pub fn find_registry_address(owner: &Pubkey) -> (Pubkey, u8) {
+    find_program_address(&[REGISTRY_SEED, owner.as_ref()], &crate::ID)
+}
+
+/// Derives the tag index PDA and its canonical bump for `owner` and `tag`
+#[inline(always)]
+pub fn find_tag_index_address(owner: &Pubkey, tag: &[u8; 4]) -> (Pubkey, u8) {
+    find_program_address(&[TAG_SEED, owner.as_ref(), tag], &crate::ID)
+}
+
+/// Derives the metadata PDA and its canonical bump for `vault`
+#[inline(always)]
+pub fn find_metadata_address(vault: &Pubkey) -> (Pubkey, u8) {
+    find_program_address(&[METADATA_SEED, vault.as_ref()], &crate::ID)
+}
+
+/// Derives the tombstone PDA and its canonical bump for `vault`
+#[inline(always)]
+pub fn find_tombstone_address(vault: &Pubkey) -> (Pubkey, u8) {
+    find_program_address(&[TOMBSTONE_SEED, vault.as_ref()], &crate::ID)
+}
+
+/// Derives the global stats PDA and its canonical bump
+#[inline(always)]
+pub fn find_stats_address() -> (Pubkey, u8) {
+    find_program_address(&[STATS_SEED], &crate::ID)
+}
+
+/// Derives the config PDA and its canonical bump
+#[inline(always)]
+pub fn find_config_address() -> (Pubkey, u8) {
+    find_program_address(&[CONFIG_SEED], &crate::ID)
+}
+
+/// Derives the reward pool PDA and its canonical bump
+#[inline(always)]
+pub fn find_reward_pool_address() -> (Pubkey, u8) {
+    find_program_address(&[REWARD_POOL_SEED], &crate::ID)
+}
+
+/// Upgradeable BPF loader, which owns the program's ProgramData account
+pub const BPF_LOADER_UPGRADEABLE_ID: Pubkey =
+    pinocchio_pubkey::from_str("BPFLoaderUpgradeab1e11111111111111111111111");
+
+/// Derives the address of the program's ProgramData account, which records its
+/// upgrade authority
+#[inline(always)]
+pub fn find_program_data_address() -> (Pubkey, u8) {
+    find_program_address(&[crate::ID.as_ref()], &BPF_LOADER_UPGRADEABLE_ID)
+}
+
+/// Logs the expected canonical bump and the provided vault key after a PDA
+/// mismatch. Compiled out unless the `verbose-logs` feature is enabled.
+#[inline(always)]
+pub fn log_vault_mismatch(_bump: u8, _vault: &Pubkey) {
+    #[cfg(feature = "verbose-logs")]
+    {
+        log!(
+            "Vault PDA mismatch: expected bump {}, provided vault:",
+            _bump
+        );
+        pinocchio::pubkey::log(_vault);
+    }
+}
@@ -0,0 +1,104 @@
+use pinocchio::{
+    account_info::AccountInfo, instruction::Signer, program_error::ProgramError, ProgramResult,
+};
+
+use crate::error::VaultError;
+use crate::state::VaultState;
+use crate::token::{check_mint_authority, check_token_account, Burn, MintTo, TOKEN_PROGRAM_ID};
+use crate::vault::is_opened;
+
+/// Receipt mint of a vault and the owner's token account of it
+///
+/// The mint is any SPL mint whose mint authority is the vault PDA, so only the
+/// program can mint receipts, one token per lamport deposited. Only deposits
+/// into opened vaults mint receipts, since their state records the mint on the
+/// first deposit and how many receipts are outstanding; every later deposit or
+/// burn has to use that mint. Lamports receipts stand for only leave the vault
+/// through a Withdraw that passes the receipt accounts, which burns a token per
+/// lamport before paying out; every other way out fails with
+/// `ReceiptsOutstanding`.
+pub struct ReceiptAccounts<'a> {
+    pub mint: &'a AccountInfo,
+    pub token_account: &'a AccountInfo,
+}
+
+impl<'a> ReceiptAccounts<'a> {
+    /// Checks `[receipt_mint, receipt_account, token_program]` against `owner`'s `vault`
+    ///
+    /// The owner has to sign, since the burn on withdrawal debits their token
+    /// account, even where an escrow agent signs for the vault. A mint other
+    /// than the one an opened vault recorded fails with `InvalidAccountData`.
+    pub fn check(
+        mint: &'a AccountInfo,
+        token_account: &'a AccountInfo,
+        token_program: &AccountInfo,
+        owner: &AccountInfo,
+        vault: &AccountInfo,
+    ) -> Result<Self, ProgramError> {
+        if !owner.is_signer() {
+            return Err(VaultError::MissingOwnerSignature.into());
+        }
+
+        if token_program.key().ne(&TOKEN_PROGRAM_ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        if is_opened(vault) {
+            let recorded = *VaultState::load(vault)?.receipt_mint();
+            if recorded.ne(&[0; 32]) && mint.key().ne(&recorded) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+        }
+
+        check_mint_authority(mint, vault.key())?;
+        check_token_account(token_account, owner.key())?;
+
+        Ok(Self {
+            mint,
+            token_account,
+        })
+    }
+
+    /// Mints `lamports` receipt tokens to the owner, signed by the vault PDA,
+    /// and records them in its state
+    pub fn mint(&self, vault: &AccountInfo, lamports: u64, signers: &[Signer]) -> ProgramResult {
+        VaultState::load_mut(vault)?.record_receipts_minted(self.mint.key(), lamports)?;
+
+        MintTo {
+            mint: self.mint,
+            account: self.token_account,
+            mint_authority: vault,
+            amount: lamports,
+        }
+        .invoke_signed(signers)
+    }
+
+    /// Burns the owner's receipt tokens for `lamports` about to leave `vault`
+    ///
+    /// Receipts only stand for deposits, so neither the rent floor an opened
+    /// vault releases on closing nor lamports deposited without a receipt are
+    /// burned for.
+    pub fn burn(&self, owner: &AccountInfo, vault: &AccountInfo, lamports: u64) -> ProgramResult {
+        let amount = match is_opened(vault) {
+            true => {
+                let mut state = VaultState::load_mut(vault)?;
+                let amount = state.receipts_to_burn(lamports);
+                state.record_receipts_burned(amount);
+                amount
+            }
+            false => lamports,
+        };
+
+        if amount.eq(&0) {
+            return Ok(());
+        }
+
+        Burn {
+            account: self.token_account,
+            mint: self.mint,
+            authority: owner,
+            amount,
+        }
+        .invoke()
+    }
+}
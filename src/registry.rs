@@ -0,0 +1,159 @@
+use core::mem::size_of;
+
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    ProgramResult,
+};
+use pinocchio_system::instructions::{CreateAccount, Transfer};
+
+use crate::error::VaultError;
+use crate::pda::REGISTRY_SEED;
+use crate::rent::minimum_balance;
+
+/// Header of an owner's vault registry
+///
+/// The registry lists every vault the owner opened: the header is followed by
+/// `count` vault pubkeys, and the account grows by one entry per new vault.
+#[repr(C)]
+pub struct RegistryHeader {
+    owner: Pubkey,
+    count: [u8; 8],
+}
+
+impl RegistryHeader {
+    pub const LEN: usize = size_of::<Self>();
+
+    #[inline(always)]
+    pub fn owner(&self) -> &Pubkey {
+        &self.owner
+    }
+
+    #[inline(always)]
+    pub fn count(&self) -> u64 {
+        u64::from_le_bytes(self.count)
+    }
+}
+
+/// Returns the registry account size needed to hold `count` vaults
+#[inline(always)]
+pub const fn registry_len(count: usize) -> usize {
+    RegistryHeader::LEN + count * size_of::<Pubkey>()
+}
+
+/// Appends `vault` to `owner`'s registry, creating the registry on first use
+///
+/// A vault that is already listed (e.g. reopened after a close) is not added
+/// twice. A new one fails with `TooManyVaults` once the registry lists
+/// `max_vaults` vaults, unless `max_vaults` is `0`. The owner pays for the
+/// extra rent as the registry grows.
+pub fn register_vault(
+    owner: &AccountInfo,
+    registry: &AccountInfo,
+    registry_bump: u8,
+    vault: &Pubkey,
+    max_vaults: u64,
+    rent_sysvar: Option<&AccountInfo>,
+) -> ProgramResult {
+    let count = if registry.is_owned_by(&pinocchio_system::ID) {
+        let bumps = [registry_bump];
+        let seeds = [
+            Seed::from(REGISTRY_SEED),
+            Seed::from(owner.key().as_ref()),
+            Seed::from(&bumps),
+        ];
+        let signers = [Signer::from(&seeds)];
+
+        CreateAccount {
+            from: owner,
+            to: registry,
+            lamports: minimum_balance(rent_sysvar, registry_len(1))?,
+            space: registry_len(1) as u64,
+            owner: &crate::ID,
+        }
+        .invoke_signed(&signers)?;
+
+        let mut data = registry.try_borrow_mut_data()?;
+        data[..32].copy_from_slice(owner.key());
+        0
+    } else {
+        if !registry.is_owned_by(&crate::ID) {
+            return Err(VaultError::InvalidVaultOwner.into());
+        }
+
+        let count = {
+            let data = registry.try_borrow_data()?;
+            let count = u64::from_le_bytes(data[32..40].try_into().unwrap()) as usize;
+            if data.len().ne(&registry_len(count)) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            // Guard against listing the same vault twice
+            let already_listed = data[RegistryHeader::LEN..]
+                .chunks_exact(size_of::<Pubkey>())
+                .any(|entry| entry.eq(vault.as_ref()));
+            if already_listed {
+                return Ok(());
+            }
+
+            count
+        };
+
+        if max_vaults.ne(&0) && count as u64 >= max_vaults {
+            return Err(VaultError::TooManyVaults.into());
+        }
+
+        // Grow by one entry and top the rent up to the new size
+        registry.resize(registry_len(count + 1))?;
+        let shortfall = minimum_balance(rent_sysvar, registry_len(count + 1))?
+            .saturating_sub(registry.lamports());
+        if shortfall.ne(&0) {
+            Transfer {
+                from: owner,
+                to: registry,
+                lamports: shortfall,
+            }
+            .invoke()?;
+        }
+
+        count
+    };
+
+    let mut data = registry.try_borrow_mut_data()?;
+    let entry = registry_len(count);
+    data[entry..entry + size_of::<Pubkey>()].copy_from_slice(vault);
+    data[32..40].copy_from_slice(&(count as u64 + 1).to_le_bytes());
+
+    Ok(())
+}
+
+/// Replaces `old` with `new` in `registry`, for a vault that moved address
+///
+/// Only opened vaults are listed, and not even all of them, so a registry
+/// that doesn't exist yet or doesn't list `old` is left as it is.
+pub fn replace_vault(registry: &AccountInfo, old: &Pubkey, new: &Pubkey) -> ProgramResult {
+    if registry.is_owned_by(&pinocchio_system::ID) {
+        return Ok(());
+    }
+
+    if !registry.is_owned_by(&crate::ID) {
+        return Err(VaultError::InvalidVaultOwner.into());
+    }
+
+    let mut data = registry.try_borrow_mut_data()?;
+    let count = u64::from_le_bytes(data[32..40].try_into().unwrap()) as usize;
+    if data.len().ne(&registry_len(count)) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if let Some(entry) = data[RegistryHeader::LEN..]
+        .chunks_exact_mut(size_of::<Pubkey>())
+        .find(|entry| entry.eq(&old.as_ref()))
+    {
+        entry.copy_from_slice(new);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,19 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, sysvars::rent::Rent};
+
+use crate::sysvar::rent;
+
+/// Returns the rent-exempt minimum balance for an account holding `data_len` bytes.
+///
+/// When the rent sysvar account is passed it is validated and read directly;
+/// otherwise the value comes from the `Rent::get()` syscall, failing with
+/// `SysvarUnavailable` if the runtime can't provide it.
+#[inline(always)]
+pub fn minimum_balance(
+    rent_sysvar: Option<&AccountInfo>,
+    data_len: usize,
+) -> Result<u64, ProgramError> {
+    match rent_sysvar {
+        Some(rent_sysvar) => Ok(Rent::from_account_info(rent_sysvar)?.minimum_balance(data_len)),
+        None => Ok(rent()?.minimum_balance(data_len)),
+    }
+}
@@ -0,0 +1,147 @@
+use core::mem::size_of;
+
+use pinocchio::{
+    account_info::{AccountInfo, RefMut},
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    ProgramResult,
+};
+use pinocchio_system::instructions::CreateAccount;
+
+use crate::error::VaultError;
+use crate::pda::{find_reward_pool_address, REWARD_POOL_SEED};
+use crate::rent::minimum_balance;
+
+/// Lamports set aside to pay vault interest from
+///
+/// Lives in the `["reward_pool"]` PDA, which is created on first funding.
+/// `total_funded` counts every lamport FundRewardPool put in, its rent floor
+/// aside.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct RewardPool {
+    total_funded: [u8; 8],
+}
+
+impl RewardPool {
+    pub const LEN: usize = size_of::<Self>();
+
+    /// Reinterprets raw account data as the reward pool, e.g. off-chain
+    #[inline(always)]
+    pub fn from_bytes(data: &[u8]) -> Option<&Self> {
+        if data.len().ne(&Self::LEN) {
+            return None;
+        }
+
+        Some(unsafe { &*(data.as_ptr() as *const Self) })
+    }
+
+    #[inline(always)]
+    pub fn total_funded(&self) -> u64 {
+        u64::from_le_bytes(self.total_funded)
+    }
+
+    /// Records `lamports` added to the pool, failing with `ArithmeticOverflow`
+    /// if the total no longer fits
+    #[inline(always)]
+    pub fn record_funding(&mut self, lamports: u64) -> ProgramResult {
+        self.total_funded = self
+            .total_funded()
+            .checked_add(lamports)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            .to_le_bytes();
+
+        Ok(())
+    }
+}
+
+/// Checks that `reward_pool` is the reward pool PDA
+#[inline(always)]
+pub fn check_reward_pool_account(reward_pool: &AccountInfo) -> Result<u8, ProgramError> {
+    let (reward_pool_key, bump) = find_reward_pool_address();
+    if reward_pool.key().ne(&reward_pool_key) {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+
+    Ok(bump)
+}
+
+/// Moves `lamports` of interest out of the reward pool into `to`
+///
+/// The program owns the pool, so it debits it directly. Only what the pool
+/// holds above its rent floor can be paid out; anything more fails with
+/// `RewardPoolUnderfunded`, as does a pool that was never funded.
+pub fn pay_from_reward_pool(
+    reward_pool: &AccountInfo,
+    to: &AccountInfo,
+    lamports: u64,
+    rent_sysvar: Option<&AccountInfo>,
+) -> ProgramResult {
+    if lamports.eq(&0) {
+        return Ok(());
+    }
+
+    if !reward_pool.is_owned_by(&crate::ID) || reward_pool.data_len().ne(&RewardPool::LEN) {
+        return Err(VaultError::RewardPoolUnderfunded.into());
+    }
+
+    let available = reward_pool
+        .lamports()
+        .saturating_sub(minimum_balance(rent_sysvar, RewardPool::LEN)?);
+    if available < lamports {
+        return Err(VaultError::RewardPoolUnderfunded.into());
+    }
+
+    let credited = to
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    *reward_pool.try_borrow_mut_lamports()? -= lamports;
+    *to.try_borrow_mut_lamports()? = credited;
+
+    Ok(())
+}
+
+/// Mutably borrows the reward pool, creating the account at `payer`'s expense on first use
+pub fn load_reward_pool<'a>(
+    payer: &AccountInfo,
+    reward_pool: &'a AccountInfo,
+    bump: u8,
+    rent_sysvar: Option<&AccountInfo>,
+) -> Result<RefMut<'a, RewardPool>, ProgramError> {
+    if reward_pool.is_owned_by(&pinocchio_system::ID) {
+        create_reward_pool(payer, reward_pool, bump, rent_sysvar)?;
+    } else if !reward_pool.is_owned_by(&crate::ID) {
+        return Err(VaultError::InvalidVaultOwner.into());
+    }
+
+    if reward_pool.data_len().ne(&RewardPool::LEN) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(RefMut::map(
+        reward_pool.try_borrow_mut_data()?,
+        |data| unsafe { &mut *(data.as_mut_ptr() as *mut RewardPool) },
+    ))
+}
+
+fn create_reward_pool(
+    payer: &AccountInfo,
+    reward_pool: &AccountInfo,
+    bump: u8,
+    rent_sysvar: Option<&AccountInfo>,
+) -> ProgramResult {
+    let bumps = [bump];
+    let seeds = [Seed::from(REWARD_POOL_SEED), Seed::from(&bumps)];
+    let signers = [Signer::from(&seeds)];
+
+    CreateAccount {
+        from: payer,
+        to: reward_pool,
+        lamports: minimum_balance(rent_sysvar, RewardPool::LEN)?,
+        space: RewardPool::LEN as u64,
+        owner: &crate::ID,
+    }
+    .invoke_signed(&signers)
+}
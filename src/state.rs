@@ -0,0 +1,1389 @@
+use core::mem::{align_of, size_of};
+
+use pinocchio::{
+    account_info::{AccountInfo, Ref, RefMut},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    ProgramResult,
+};
+
+use crate::error::VaultError;
+use crate::logic::compute_interest;
+
+/// State of a vault opened with OpenVault
+///
+/// Opened vaults are owned by the program and persist at their rent floor, so
+/// deposits add to them rather than requiring an empty vault. The salt is not
+/// stored, since that would make a salted vault derivable again.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct VaultState {
+    /// Layout version, so accounts written by an older program can be told
+    /// apart and grown with UpgradeVaultState
+    version: u8,
+    owner: Pubkey,
+    id: [u8; 8],
+    bump: u8,
+    creation_slot: [u8; 8],
+    flags: u8,
+    /// Human-readable UTF-8 name for wallets, zero-padded to its maximum length
+    label_len: u8,
+    label: [u8; 32],
+    /// When non-zero, gates withdrawals on a price at most `max_price_age`
+    /// seconds old
+    oracle: Pubkey,
+    max_price_age: [u8; 8],
+    /// When `vesting_duration` is non-zero, `total_deposited` is released
+    /// linearly from `vesting_start`, and only through WithdrawVested
+    vesting_start: [u8; 8],
+    vesting_duration: [u8; 8],
+    total_deposited: [u8; 8],
+    withdrawn_so_far: [u8; 8],
+    /// Lamports deposited and not yet withdrawn, so lamports sent to the vault
+    /// outside a deposit can be told apart and are never released
+    balance: [u8; 8],
+    /// When non-zero, caps the tracked lamports leaving the vault within each
+    /// day-long window, which opens at the first withdrawal after the last one
+    /// expired
+    daily_limit: [u8; 8],
+    daily_withdrawn: [u8; 8],
+    window_start: [u8; 8],
+    /// When non-empty, the only recipients WithdrawTo can pay; only the first
+    /// `allowed_dest_count` are set
+    allowed_dest_count: u8,
+    allowed_dests: [Pubkey; 4],
+    /// Nothing leaves the vault before this unix timestamp, which can only ever
+    /// be pushed back
+    unlock_ts: [u8; 8],
+    /// Nonce of the last withdrawal approval the owner signed for a relayer,
+    /// which only ever grows
+    used_nonce: [u8; 8],
+    token_dest_owner: Pubkey,
+    /// Once set, only this agent's signature releases the vault's lamports, and
+    /// it can never be changed
+    escrow_agent: Pubkey,
+    /// Creation timestamp, before which plus a non-zero `min_age_secs` nothing
+    /// leaves the vault
+    created_ts: [u8; 8],
+    min_age_secs: [u8; 8],
+    /// Every deposit and the lamports it brought in, for analytics;
+    /// withdrawals never lower them
+    total_deposit_count: [u8; 8],
+    lifetime_deposited: [u8; 8],
+    /// When non-zero, caps the tracked lamports leaving the vault within any
+    /// `rolling_window_secs` long stretch, counted over `recent_withdrawals`, a
+    /// ring of the latest withdrawals whose oldest slot is `recent_head`
+    rolling_limit: [u8; 8],
+    rolling_window_secs: [u8; 8],
+    recent_withdrawals: [TimedWithdrawal; VaultState::MAX_RECENT_WITHDRAWALS],
+    recent_head: u8,
+    /// When non-zero, lets anyone Crank `drip_amount` to `drip_recipient` for
+    /// every interval elapsed since `drip_last_release`
+    drip_interval_secs: [u8; 8],
+    drip_amount: [u8; 8],
+    drip_recipient: Pubkey,
+    drip_last_release: [u8; 8],
+    /// When non-zero, withdrawals need that many of the first
+    /// `multisig_signer_count` `multisig_signers` to sign instead of the owner
+    multisig_threshold: u8,
+    multisig_signer_count: u8,
+    multisig_signers: [Pubkey; VaultState::MAX_MULTISIG_SIGNERS],
+    /// Once this unix timestamp passes, if set, anyone can withdraw the whole
+    /// vault to its owner
+    expiry_ts: [u8; 8],
+    /// Lamports outside `balance`, earmarked for the tips relayers take on the
+    /// owner's signed withdrawals
+    gas_reserve: [u8; 8],
+    /// SPL tokens deposited into the vault's associated token accounts, one
+    /// entry per mint, of which only the first `token_mint_count` are set
+    token_mint_count: u8,
+    token_balances: [TokenBalance; VaultState::MAX_TOKEN_MINTS],
+    /// Nothing leaves the vault before this slot either; with `unlock_ts` also
+    /// set, the later of the two unlocks the vault
+    unlock_slot: [u8; 8],
+    /// Keys besides the owner that may withdraw to themselves with
+    /// DelegateWithdraw, each up to its own cap, of which only the first
+    /// `delegate_count` are set
+    delegate_count: u8,
+    delegates: [Delegate; VaultState::MAX_DELEGATES],
+    /// Mint the vault's receipt tokens were first minted from, if any, and how
+    /// many are still outstanding; that many tracked lamports only leave
+    /// through a Withdraw that burns them
+    receipt_mint: Pubkey,
+    receipt_supply: [u8; 8],
+    /// Once the owner goes `inactivity_secs` without a deposit or withdrawal
+    /// after `last_activity_ts`, this key can claim the whole vault
+    beneficiary: Pubkey,
+    inactivity_secs: [u8; 8],
+    last_activity_ts: [u8; 8],
+    /// When non-zero, accrues yearly interest on `balance` from the reward
+    /// pool, folded into `accrued_interest` up to `last_accrual_ts` whenever
+    /// the balance changes, and credited by ClaimReward
+    reward_rate_bps: [u8; 2],
+    last_accrual_ts: [u8; 8],
+    accrued_interest: [u8; 8],
+    /// Fixed when the vault is opened; freezes it, locks its deposits and sets
+    /// its daily limit in the owner's place, but can never withdraw
+    admin: Pubkey,
+    /// Withdrawals the vault ever made, for analytics alongside
+    /// `total_deposit_count`
+    withdraw_count: [u8; 8],
+    /// When non-empty, the only accounts besides the owner deposits can come
+    /// from; only the first `allowed_source_count` are set
+    allowed_source_count: u8,
+    allowed_sources: [Pubkey; 4],
+}
+
+/// Tracked lamports withdrawn at a unix timestamp, counted against a rolling limit
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct TimedWithdrawal {
+    ts: [u8; 8],
+    lamports: [u8; 8],
+}
+
+/// Tokens of `mint` deposited into a vault and not yet withdrawn
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct TokenBalance {
+    mint: Pubkey,
+    amount: [u8; 8],
+}
+
+/// A key allowed to withdraw up to `max_per_withdrawal` lamports at a time
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Delegate {
+    key: Pubkey,
+    max_per_withdrawal: [u8; 8],
+}
+
+impl TimedWithdrawal {
+    /// Whether the withdrawal still counts `window` seconds back from `now`
+    #[inline(always)]
+    fn in_window(&self, now: i64, window: i64) -> bool {
+        u64::from_le_bytes(self.lamports).ne(&0)
+            && now.saturating_sub(i64::from_le_bytes(self.ts)) < window
+    }
+}
+
+// Account data is cast to `VaultState` in place, so it must have no padding or
+// alignment, and `LEN` is bumped along with every field added to it
+const _: () = assert!(size_of::<VaultState>() == VaultState::LEN);
+const _: () = assert!(align_of::<VaultState>() == 1);
+
+impl VaultState {
+    /// Bytes allocated to an opened vault
+    pub const LEN: usize = 1413;
+
+    /// Layout version written to newly opened vaults
+    ///
+    /// Version `1` covers every layout before the receipt fields, and version
+    /// `2` every one since, so a version alone doesn't fix a layout's length.
+    /// Each layout is a prefix of the next, which UpgradeVaultState grows in
+    /// place to the current one.
+    pub const VERSION: u8 = 2;
+
+    /// Deposits are only accepted in a slot after the one the vault was opened in
+    pub const FLAG_DEFER_DEPOSITS: u8 = 1 << 0;
+
+    /// OpenVault data carries a daily withdrawal limit after the flags
+    pub const FLAG_DAILY_LIMIT: u8 = 1 << 1;
+
+    /// Deposits are rejected, freezing the balance; toggled with SetDepositsLocked
+    pub const FLAG_DEPOSITS_LOCKED: u8 = 1 << 2;
+
+    /// OpenVault data carries a minimum age before withdrawals after the flags
+    /// and any daily limit
+    pub const FLAG_MIN_AGE: u8 = 1 << 3;
+
+    /// OpenVault data carries a rolling withdrawal limit and its window after
+    /// the flags, any daily limit and any minimum age
+    pub const FLAG_ROLLING_LIMIT: u8 = 1 << 4;
+
+    /// Every deposit and withdrawal is rejected; toggled with SetFrozen
+    pub const FLAG_FROZEN: u8 = 1 << 5;
+
+    /// OpenVault data carries the vault's admin after the flags and any limits
+    /// and minimum age
+    pub const FLAG_ADMIN: u8 = 1 << 6;
+
+    /// Every flag the program defines
+    pub const KNOWN_FLAGS: u8 = Self::FLAG_DEFER_DEPOSITS
+        | Self::FLAG_DAILY_LIMIT
+        | Self::FLAG_DEPOSITS_LOCKED
+        | Self::FLAG_MIN_AGE
+        | Self::FLAG_ROLLING_LIMIT
+        | Self::FLAG_FROZEN
+        | Self::FLAG_ADMIN;
+
+    /// Length of a daily withdrawal limit window, in seconds
+    pub const WITHDRAW_WINDOW: i64 = 24 * 60 * 60;
+
+    /// Longest label a vault can hold, in bytes
+    pub const MAX_LABEL_LEN: usize = 32;
+
+    /// Most withdrawal destinations a vault's allowlist can hold
+    pub const MAX_ALLOWED_DESTS: usize = 4;
+
+    /// Most deposit sources a vault's whitelist can hold
+    pub const MAX_ALLOWED_SOURCES: usize = 4;
+
+    /// Withdrawals the rolling limit keeps apart; older ones in the window are
+    /// folded into the latest
+    pub const MAX_RECENT_WITHDRAWALS: usize = 8;
+
+    /// Most signers a multisig vault can have
+    pub const MAX_MULTISIG_SIGNERS: usize = 5;
+
+    /// Most distinct mints a vault can track token balances of
+    pub const MAX_TOKEN_MINTS: usize = 4;
+
+    /// Most delegates a vault can have
+    pub const MAX_DELEGATES: usize = 4;
+
+    /// Reinterprets raw account data as vault state, e.g. off-chain
+    #[inline(always)]
+    pub fn from_bytes(data: &[u8]) -> Option<&Self> {
+        if data.len().ne(&Self::LEN) || data[0].ne(&Self::VERSION) {
+            return None;
+        }
+
+        Some(unsafe { &*(data.as_ptr() as *const Self) })
+    }
+
+    /// Whether `data` is the state of a vault an older program opened, laid out
+    /// as a shorter prefix of the current fields
+    ///
+    /// Fields were appended within a version too, so any known version counts
+    /// and only the length tells an older layout apart.
+    #[inline(always)]
+    pub fn is_outdated(data: &[u8]) -> bool {
+        data.len() < Self::LEN
+            && data
+                .first()
+                .is_some_and(|version| (1..=Self::VERSION).contains(version))
+    }
+
+    /// Checks that `label` fits in a vault and is valid UTF-8
+    #[inline(always)]
+    pub fn check_label(label: &[u8]) -> ProgramResult {
+        if label.len() > Self::MAX_LABEL_LEN || core::str::from_utf8(label).is_err() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(())
+    }
+
+    /// Borrows the state of an opened vault
+    #[inline(always)]
+    pub fn load(account: &AccountInfo) -> Result<Ref<'_, Self>, ProgramError> {
+        Self::check(account)?;
+        let state = Ref::map(account.try_borrow_data()?, |data| unsafe {
+            &*(data.as_ptr() as *const Self)
+        });
+        state.check_version()?;
+        Ok(state)
+    }
+
+    /// Mutably borrows the state of an opened vault
+    #[inline(always)]
+    pub fn load_mut(account: &AccountInfo) -> Result<RefMut<'_, Self>, ProgramError> {
+        let state = Self::initialize(account)?;
+        state.check_version()?;
+        Ok(state)
+    }
+
+    /// Mutably borrows the state of a vault that was just created, which has
+    /// no version yet
+    #[inline(always)]
+    pub fn initialize(account: &AccountInfo) -> Result<RefMut<'_, Self>, ProgramError> {
+        Self::check(account)?;
+        Ok(RefMut::map(account.try_borrow_mut_data()?, |data| unsafe {
+            &mut *(data.as_mut_ptr() as *mut Self)
+        }))
+    }
+
+    #[inline(always)]
+    fn check(account: &AccountInfo) -> Result<(), ProgramError> {
+        if !account.is_owned_by(&crate::ID) {
+            return Err(VaultError::InvalidVaultOwner.into());
+        }
+
+        if account.data_len().ne(&Self::LEN) {
+            // An older layout is readable again once UpgradeVaultState grows it
+            if Self::is_outdated(&account.try_borrow_data()?) {
+                return Err(VaultError::UnsupportedStateVersion.into());
+            }
+
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn check_version(&self) -> ProgramResult {
+        if self.version.ne(&Self::VERSION) {
+            return Err(VaultError::UnsupportedStateVersion.into());
+        }
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    #[inline(always)]
+    pub fn owner(&self) -> &Pubkey {
+        &self.owner
+    }
+
+    #[inline(always)]
+    pub fn id(&self) -> u64 {
+        u64::from_le_bytes(self.id)
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> u8 {
+        self.bump
+    }
+
+    #[inline(always)]
+    pub fn creation_slot(&self) -> u64 {
+        u64::from_le_bytes(self.creation_slot)
+    }
+
+    #[inline(always)]
+    pub fn flags(&self) -> u8 {
+        self.flags
+    }
+
+    #[inline(always)]
+    pub fn defers_deposits(&self) -> bool {
+        self.flags & Self::FLAG_DEFER_DEPOSITS != 0
+    }
+
+    #[inline(always)]
+    pub fn deposits_locked(&self) -> bool {
+        self.flags & Self::FLAG_DEPOSITS_LOCKED != 0
+    }
+
+    #[inline(always)]
+    pub fn set_deposits_locked(&mut self, locked: bool) {
+        match locked {
+            true => self.flags |= Self::FLAG_DEPOSITS_LOCKED,
+            false => self.flags &= !Self::FLAG_DEPOSITS_LOCKED,
+        }
+    }
+
+    #[inline(always)]
+    pub fn is_frozen(&self) -> bool {
+        self.flags & Self::FLAG_FROZEN != 0
+    }
+
+    #[inline(always)]
+    pub fn set_frozen(&mut self, frozen: bool) {
+        match frozen {
+            true => self.flags |= Self::FLAG_FROZEN,
+            false => self.flags &= !Self::FLAG_FROZEN,
+        }
+    }
+
+    /// Raw label bytes; a corrupted length is clamped to the label's capacity
+    #[inline(always)]
+    pub fn label(&self) -> &[u8] {
+        &self.label[..(self.label_len as usize).min(Self::MAX_LABEL_LEN)]
+    }
+
+    /// Length recorded for the label, unclamped
+    #[inline(always)]
+    pub fn label_len(&self) -> u8 {
+        self.label_len
+    }
+
+    /// Replaces the label; `label` must have passed [`Self::check_label`]
+    #[inline(always)]
+    pub fn set_label(&mut self, label: &[u8]) {
+        self.label = [0; 32];
+        self.label[..label.len()].copy_from_slice(label);
+        self.label_len = label.len() as u8;
+    }
+
+    #[inline(always)]
+    pub fn oracle(&self) -> &Pubkey {
+        &self.oracle
+    }
+
+    #[inline(always)]
+    pub fn max_price_age(&self) -> u64 {
+        u64::from_le_bytes(self.max_price_age)
+    }
+
+    /// Withdrawals require a fresh price from the vault's oracle
+    #[inline(always)]
+    pub fn is_price_gated(&self) -> bool {
+        self.oracle.ne(&[0; 32])
+    }
+
+    /// Gates withdrawals on `oracle`, or removes the gate for the zero pubkey
+    #[inline(always)]
+    pub fn set_oracle(&mut self, oracle: &Pubkey, max_price_age: u64) {
+        self.oracle = *oracle;
+        self.max_price_age = max_price_age.to_le_bytes();
+    }
+
+    /// Unix timestamp the vesting schedule started at
+    #[inline(always)]
+    pub fn vesting_start(&self) -> i64 {
+        i64::from_le_bytes(self.vesting_start)
+    }
+
+    /// Length of the vesting schedule in seconds, `0` when the vault doesn't vest
+    #[inline(always)]
+    pub fn vesting_duration(&self) -> u64 {
+        u64::from_le_bytes(self.vesting_duration)
+    }
+
+    /// Lamports deposited over the vault's lifetime, rent floor excluded
+    #[inline(always)]
+    pub fn total_deposited(&self) -> u64 {
+        u64::from_le_bytes(self.total_deposited)
+    }
+
+    /// Lamports withdrawn through WithdrawVested so far
+    #[inline(always)]
+    pub fn withdrawn_so_far(&self) -> u64 {
+        u64::from_le_bytes(self.withdrawn_so_far)
+    }
+
+    /// Lamports deposited and not yet withdrawn, rent floor excluded
+    #[inline(always)]
+    pub fn balance(&self) -> u64 {
+        u64::from_le_bytes(self.balance)
+    }
+
+    #[inline(always)]
+    pub fn is_vesting(&self) -> bool {
+        self.vesting_duration().ne(&0)
+    }
+
+    /// Portion of the deposits vested at unix timestamp `now`
+    #[inline(always)]
+    pub fn vested(&self, now: i64) -> u64 {
+        let duration = self.vesting_duration();
+        if duration.eq(&0) {
+            return self.total_deposited();
+        }
+
+        let elapsed = (now.saturating_sub(self.vesting_start()).max(0) as u64).min(duration);
+        (self.total_deposited() as u128 * elapsed as u128 / duration as u128) as u64
+    }
+
+    /// Deposits the vault ever received
+    #[inline(always)]
+    pub fn total_deposit_count(&self) -> u64 {
+        u64::from_le_bytes(self.total_deposit_count)
+    }
+
+    /// Lamports the vault ever received through deposits
+    #[inline(always)]
+    pub fn lifetime_deposited(&self) -> u64 {
+        u64::from_le_bytes(self.lifetime_deposited)
+    }
+
+    /// Withdrawals the vault ever made
+    #[inline(always)]
+    pub fn withdraw_count(&self) -> u64 {
+        u64::from_le_bytes(self.withdraw_count)
+    }
+
+    #[inline(always)]
+    pub fn count_withdrawal(&mut self) {
+        self.withdraw_count = self.withdraw_count().saturating_add(1).to_le_bytes();
+    }
+
+    /// Adds a deposit, starting a `vesting_duration` schedule at `now` unless
+    /// one is already running; later deposits vest on the same schedule
+    #[inline(always)]
+    pub fn record_deposit(&mut self, lamports: u64, now: i64, vesting_duration: u64) {
+        self.total_deposited = self
+            .total_deposited()
+            .saturating_add(lamports)
+            .to_le_bytes();
+        self.balance = self.balance().saturating_add(lamports).to_le_bytes();
+        self.total_deposit_count = self
+            .total_deposit_count()
+            .saturating_add(1)
+            .to_le_bytes();
+        self.lifetime_deposited = self
+            .lifetime_deposited()
+            .saturating_add(lamports)
+            .to_le_bytes();
+
+        if !self.is_vesting() && vesting_duration.ne(&0) {
+            self.vesting_start = now.to_le_bytes();
+            self.vesting_duration = vesting_duration.to_le_bytes();
+        }
+    }
+
+    /// Most tracked lamports that can leave the vault per window, `0` for no limit
+    #[inline(always)]
+    pub fn daily_limit(&self) -> u64 {
+        u64::from_le_bytes(self.daily_limit)
+    }
+
+    /// Tracked lamports withdrawn in the window starting at [`Self::window_start`]
+    #[inline(always)]
+    pub fn daily_withdrawn(&self) -> u64 {
+        u64::from_le_bytes(self.daily_withdrawn)
+    }
+
+    #[inline(always)]
+    pub fn window_start(&self) -> i64 {
+        i64::from_le_bytes(self.window_start)
+    }
+
+    #[inline(always)]
+    pub fn set_daily_limit(&mut self, daily_limit: u64) {
+        self.daily_limit = daily_limit.to_le_bytes();
+    }
+
+    /// Counts `lamports` against the daily limit at unix timestamp `now`,
+    /// starting a new window if the current one has rolled over
+    #[inline(always)]
+    pub fn record_daily_withdrawal(&mut self, lamports: u64, now: i64) -> ProgramResult {
+        if self.daily_limit().eq(&0) {
+            return Ok(());
+        }
+
+        if now.saturating_sub(self.window_start()) >= Self::WITHDRAW_WINDOW {
+            self.window_start = now.to_le_bytes();
+            self.daily_withdrawn = [0; 8];
+        }
+
+        let withdrawn = self
+            .daily_withdrawn()
+            .checked_add(lamports)
+            .filter(|withdrawn| withdrawn <= &self.daily_limit())
+            .ok_or(VaultError::DailyLimitExceeded)?;
+        self.daily_withdrawn = withdrawn.to_le_bytes();
+
+        Ok(())
+    }
+
+    /// Destinations WithdrawTo may send to; a corrupted count is clamped to the
+    /// allowlist's capacity
+    #[inline(always)]
+    pub fn allowed_dests(&self) -> &[Pubkey] {
+        &self.allowed_dests[..(self.allowed_dest_count as usize).min(Self::MAX_ALLOWED_DESTS)]
+    }
+
+    /// An empty allowlist lets WithdrawTo send anywhere
+    #[inline(always)]
+    pub fn is_dest_allowed(&self, dest: &Pubkey) -> bool {
+        let allowed = self.allowed_dests();
+        allowed.is_empty() || allowed.contains(dest)
+    }
+
+    /// Adds `dest` to the allowlist, which fails with `InvalidArgument` once
+    /// it's full; adding a listed destination again does nothing
+    #[inline(always)]
+    pub fn add_allowed_dest(&mut self, dest: &Pubkey) -> ProgramResult {
+        if self.allowed_dests().contains(dest) {
+            return Ok(());
+        }
+
+        let count = self.allowed_dests().len();
+        if count.eq(&Self::MAX_ALLOWED_DESTS) {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        self.allowed_dests[count] = *dest;
+        self.allowed_dest_count = count as u8 + 1;
+
+        Ok(())
+    }
+
+    /// Removes `dest` from the allowlist, which fails with `InvalidArgument`
+    /// if it isn't listed
+    #[inline(always)]
+    pub fn remove_allowed_dest(&mut self, dest: &Pubkey) -> ProgramResult {
+        let count = self.allowed_dests().len();
+        let index = self
+            .allowed_dests()
+            .iter()
+            .position(|allowed| allowed.eq(dest))
+            .ok_or(ProgramError::InvalidArgument)?;
+
+        // Keep the set destinations contiguous by moving the last one into the gap
+        self.allowed_dests[index] = self.allowed_dests[count - 1];
+        self.allowed_dests[count - 1] = [0; 32];
+        self.allowed_dest_count = count as u8 - 1;
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn allowed_sources(&self) -> &[Pubkey] {
+        &self.allowed_sources[..(self.allowed_source_count as usize).min(Self::MAX_ALLOWED_SOURCES)]
+    }
+
+    /// An empty whitelist lets deposits come from anywhere, and the owner can
+    /// always deposit
+    #[inline(always)]
+    pub fn is_source_allowed(&self, source: &Pubkey) -> bool {
+        let allowed = self.allowed_sources();
+        allowed.is_empty() || allowed.contains(source) || self.owner.eq(source)
+    }
+
+    /// Adds `source` to the whitelist, which fails with `InvalidArgument` once
+    /// it's full; adding a listed source again does nothing
+    #[inline(always)]
+    pub fn add_allowed_source(&mut self, source: &Pubkey) -> ProgramResult {
+        if self.allowed_sources().contains(source) {
+            return Ok(());
+        }
+
+        let count = self.allowed_sources().len();
+        if count.eq(&Self::MAX_ALLOWED_SOURCES) {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        self.allowed_sources[count] = *source;
+        self.allowed_source_count = count as u8 + 1;
+
+        Ok(())
+    }
+
+    /// Removes `source` from the whitelist, which fails with `InvalidArgument`
+    /// if it isn't listed
+    #[inline(always)]
+    pub fn remove_allowed_source(&mut self, source: &Pubkey) -> ProgramResult {
+        let count = self.allowed_sources().len();
+        let index = self
+            .allowed_sources()
+            .iter()
+            .position(|allowed| allowed.eq(source))
+            .ok_or(ProgramError::InvalidArgument)?;
+
+        // Keep the set sources contiguous by moving the last one into the gap
+        self.allowed_sources[index] = self.allowed_sources[count - 1];
+        self.allowed_sources[count - 1] = [0; 32];
+        self.allowed_source_count = count as u8 - 1;
+
+        Ok(())
+    }
+
+    /// Unix timestamp the vault unlocks at, `0` when it was never locked
+    #[inline(always)]
+    pub fn unlock_ts(&self) -> i64 {
+        i64::from_le_bytes(self.unlock_ts)
+    }
+
+    #[inline(always)]
+    pub fn is_locked(&self, now: i64) -> bool {
+        now < self.unlock_ts()
+    }
+
+    /// Moves the unlock to `unlock_ts`, failing with `CannotShortenLock` unless
+    /// that's strictly later than the current one
+    #[inline(always)]
+    pub fn extend_lock(&mut self, unlock_ts: i64) -> ProgramResult {
+        if unlock_ts <= self.unlock_ts() {
+            return Err(VaultError::CannotShortenLock.into());
+        }
+
+        self.unlock_ts = unlock_ts.to_le_bytes();
+
+        Ok(())
+    }
+
+    /// Slot the vault unlocks at, `0` when it was never slot-locked
+    #[inline(always)]
+    pub fn unlock_slot(&self) -> u64 {
+        u64::from_le_bytes(self.unlock_slot)
+    }
+
+    #[inline(always)]
+    pub fn is_slot_locked(&self, slot: u64) -> bool {
+        slot < self.unlock_slot()
+    }
+
+    /// Moves the slot unlock to `unlock_slot`, failing with `CannotShortenLock`
+    /// unless that's strictly later than the current one
+    #[inline(always)]
+    pub fn extend_slot_lock(&mut self, unlock_slot: u64) -> ProgramResult {
+        if unlock_slot <= self.unlock_slot() {
+            return Err(VaultError::CannotShortenLock.into());
+        }
+
+        self.unlock_slot = unlock_slot.to_le_bytes();
+
+        Ok(())
+    }
+
+    /// Nonce of the last approval accepted, `0` when none was
+    #[inline(always)]
+    pub fn used_nonce(&self) -> u64 {
+        u64::from_le_bytes(self.used_nonce)
+    }
+
+    /// Consumes an approval's `nonce`, failing with `NonceAlreadyUsed` unless
+    /// it's strictly above the last one, so no approval can be used twice
+    #[inline(always)]
+    pub fn use_nonce(&mut self, nonce: u64) -> ProgramResult {
+        if nonce <= self.used_nonce() {
+            return Err(VaultError::NonceAlreadyUsed.into());
+        }
+
+        self.used_nonce = nonce.to_le_bytes();
+
+        Ok(())
+    }
+
+    /// Owner every token account SweepToken sends to has to have, or the zero
+    /// pubkey if any destination is allowed
+    #[inline(always)]
+    pub fn token_dest_owner(&self) -> &Pubkey {
+        &self.token_dest_owner
+    }
+
+    /// An unset destination owner lets SweepToken send anywhere
+    #[inline(always)]
+    pub fn is_token_dest_allowed(&self, dest_owner: &Pubkey) -> bool {
+        self.token_dest_owner.eq(&[0; 32]) || self.token_dest_owner.eq(dest_owner)
+    }
+
+    #[inline(always)]
+    pub fn set_token_dest_owner(&mut self, dest_owner: &Pubkey) {
+        self.token_dest_owner = *dest_owner;
+    }
+
+    /// Agent whose signature withdrawals need in place of the owner's, or the
+    /// zero pubkey if the vault isn't escrowed
+    #[inline(always)]
+    pub fn escrow_agent(&self) -> &Pubkey {
+        &self.escrow_agent
+    }
+
+    #[inline(always)]
+    pub fn is_escrowed(&self) -> bool {
+        self.escrow_agent.ne(&[0; 32])
+    }
+
+    #[inline(always)]
+    pub fn set_escrow_agent(&mut self, agent: &Pubkey) {
+        self.escrow_agent = *agent;
+    }
+
+    /// Unix timestamp the vault was created at
+    #[inline(always)]
+    pub fn created_ts(&self) -> i64 {
+        i64::from_le_bytes(self.created_ts)
+    }
+
+    /// Seconds after creation before anything can leave the vault, or `0` for none
+    #[inline(always)]
+    pub fn min_age_secs(&self) -> u64 {
+        u64::from_le_bytes(self.min_age_secs)
+    }
+
+    #[inline(always)]
+    pub fn set_min_age_secs(&mut self, min_age_secs: u64) {
+        self.min_age_secs = min_age_secs.to_le_bytes();
+    }
+
+    /// Whether the vault is younger than its minimum age at `now`
+    #[inline(always)]
+    pub fn is_too_young(&self, now: i64) -> bool {
+        let min_age = i64::try_from(self.min_age_secs()).unwrap_or(i64::MAX);
+        now < self.created_ts().saturating_add(min_age)
+    }
+
+    /// Most tracked lamports that can leave the vault per rolling window, `0` for no limit
+    #[inline(always)]
+    pub fn rolling_limit(&self) -> u64 {
+        u64::from_le_bytes(self.rolling_limit)
+    }
+
+    /// Length of the rolling limit's window, in seconds
+    #[inline(always)]
+    pub fn rolling_window_secs(&self) -> u64 {
+        u64::from_le_bytes(self.rolling_window_secs)
+    }
+
+    #[inline(always)]
+    pub fn set_rolling_limit(&mut self, rolling_limit: u64, rolling_window_secs: u64) {
+        self.rolling_limit = rolling_limit.to_le_bytes();
+        self.rolling_window_secs = rolling_window_secs.to_le_bytes();
+    }
+
+    /// Tracked lamports withdrawn within the rolling window ending at `now`
+    #[inline(always)]
+    pub fn rolling_withdrawn(&self, now: i64) -> u64 {
+        let window = i64::try_from(self.rolling_window_secs()).unwrap_or(i64::MAX);
+        self.recent_withdrawals
+            .iter()
+            .filter(|withdrawal| withdrawal.in_window(now, window))
+            .fold(0, |total, withdrawal| {
+                total.saturating_add(u64::from_le_bytes(withdrawal.lamports))
+            })
+    }
+
+    /// Counts `lamports` against the rolling limit at unix timestamp `now`
+    ///
+    /// Once every slot of the ring is still in the window, the withdrawal is
+    /// folded into the latest one and takes its timestamp, which only ever
+    /// keeps lamports counted for longer than they would be.
+    #[inline(always)]
+    pub fn record_rolling_withdrawal(&mut self, lamports: u64, now: i64) -> ProgramResult {
+        if self.rolling_limit().eq(&0) || lamports.eq(&0) {
+            return Ok(());
+        }
+
+        self.rolling_withdrawn(now)
+            .checked_add(lamports)
+            .filter(|withdrawn| withdrawn <= &self.rolling_limit())
+            .ok_or(VaultError::RollingLimitExceeded)?;
+
+        let window = i64::try_from(self.rolling_window_secs()).unwrap_or(i64::MAX);
+        let head = self.recent_head as usize % Self::MAX_RECENT_WITHDRAWALS;
+        let (slot, lamports) = if self.recent_withdrawals[head].in_window(now, window) {
+            let latest = (head + Self::MAX_RECENT_WITHDRAWALS - 1) % Self::MAX_RECENT_WITHDRAWALS;
+            let folded = u64::from_le_bytes(self.recent_withdrawals[latest].lamports);
+            (latest, folded.saturating_add(lamports))
+        } else {
+            self.recent_head = ((head + 1) % Self::MAX_RECENT_WITHDRAWALS) as u8;
+            (head, lamports)
+        };
+
+        self.recent_withdrawals[slot] = TimedWithdrawal {
+            ts: now.to_le_bytes(),
+            lamports: lamports.to_le_bytes(),
+        };
+
+        Ok(())
+    }
+
+    /// Seconds between two drip releases, or `0` for no drip schedule
+    #[inline(always)]
+    pub fn drip_interval_secs(&self) -> u64 {
+        u64::from_le_bytes(self.drip_interval_secs)
+    }
+
+    /// Lamports released to the drip recipient per elapsed interval
+    #[inline(always)]
+    pub fn drip_amount(&self) -> u64 {
+        u64::from_le_bytes(self.drip_amount)
+    }
+
+    #[inline(always)]
+    pub fn drip_recipient(&self) -> &Pubkey {
+        &self.drip_recipient
+    }
+
+    /// Unix timestamp the drip's intervals are counted from
+    #[inline(always)]
+    pub fn drip_last_release(&self) -> i64 {
+        i64::from_le_bytes(self.drip_last_release)
+    }
+
+    /// Replaces the drip schedule, counting its first interval from `now`
+    #[inline(always)]
+    pub fn set_drip(&mut self, interval_secs: u64, amount: u64, recipient: &Pubkey, now: i64) {
+        self.drip_interval_secs = interval_secs.to_le_bytes();
+        self.drip_amount = amount.to_le_bytes();
+        self.drip_recipient = *recipient;
+        self.drip_last_release = now.to_le_bytes();
+    }
+
+    /// Moves the drip's last release `intervals` intervals forward
+    #[inline(always)]
+    pub fn record_drip(&mut self, intervals: u64) {
+        let elapsed = intervals.saturating_mul(self.drip_interval_secs());
+        self.drip_last_release = self
+            .drip_last_release()
+            .saturating_add_unsigned(elapsed)
+            .to_le_bytes();
+    }
+
+    /// Signatures a withdrawal needs from the multisig signers, or `0` if the
+    /// vault isn't a multisig one
+    #[inline(always)]
+    pub fn multisig_threshold(&self) -> u8 {
+        self.multisig_threshold
+    }
+
+    /// Signers of a multisig vault; a corrupted count is clamped to the
+    /// signer set's capacity
+    #[inline(always)]
+    pub fn multisig_signers(&self) -> &[Pubkey] {
+        &self.multisig_signers
+            [..(self.multisig_signer_count as usize).min(Self::MAX_MULTISIG_SIGNERS)]
+    }
+
+    #[inline(always)]
+    pub fn is_multisig(&self) -> bool {
+        self.multisig_threshold.ne(&0)
+    }
+
+    /// Makes the vault a multisig one; `signers` must fit the signer set and
+    /// `threshold` be between `1` and their count
+    #[inline(always)]
+    pub fn set_multisig(&mut self, signers: &[Pubkey], threshold: u8) {
+        self.multisig_signers = [[0; 32]; Self::MAX_MULTISIG_SIGNERS];
+        self.multisig_signers[..signers.len()].copy_from_slice(signers);
+        self.multisig_signer_count = signers.len() as u8;
+        self.multisig_threshold = threshold;
+    }
+
+    /// Unix timestamp after which anyone can withdraw the vault to its owner,
+    /// `0` when it never expires
+    #[inline(always)]
+    pub fn expiry_ts(&self) -> i64 {
+        i64::from_le_bytes(self.expiry_ts)
+    }
+
+    #[inline(always)]
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expiry_ts().ne(&0) && now >= self.expiry_ts()
+    }
+
+    #[inline(always)]
+    pub fn set_expiry_ts(&mut self, expiry_ts: i64) {
+        self.expiry_ts = expiry_ts.to_le_bytes();
+    }
+
+    /// Lamports held for relayer tips, outside the tracked balance
+    #[inline(always)]
+    pub fn gas_reserve(&self) -> u64 {
+        u64::from_le_bytes(self.gas_reserve)
+    }
+
+    #[inline(always)]
+    pub fn add_gas_reserve(&mut self, lamports: u64) -> ProgramResult {
+        self.gas_reserve = self
+            .gas_reserve()
+            .checked_add(lamports)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            .to_le_bytes();
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn spend_gas_reserve(&mut self, lamports: u64) {
+        self.gas_reserve = self.gas_reserve().saturating_sub(lamports).to_le_bytes();
+    }
+
+    fn token_balances(&self) -> &[TokenBalance] {
+        &self.token_balances[..(self.token_mint_count as usize).min(Self::MAX_TOKEN_MINTS)]
+    }
+
+    /// Whether any tracked balance is non-zero
+    #[inline(always)]
+    pub fn holds_tokens(&self) -> bool {
+        self.token_balances()
+            .iter()
+            .any(|balance| balance.amount.ne(&[0; 8]))
+    }
+
+    /// Tokens of `mint` the vault tracks, `0` for a mint it holds none of
+    #[inline(always)]
+    pub fn token_balance(&self, mint: &Pubkey) -> u64 {
+        self.token_balances()
+            .iter()
+            .find(|balance| balance.mint.eq(mint))
+            .map_or(0, |balance| u64::from_le_bytes(balance.amount))
+    }
+
+    /// Adds `amount` tokens of `mint` to the tracked balances, which fails with
+    /// `TooManyMints` for a new mint once every entry is taken
+    #[inline(always)]
+    pub fn credit_token(&mut self, mint: &Pubkey, amount: u64) -> ProgramResult {
+        let count = self.token_balances().len();
+        let index = match self
+            .token_balances()
+            .iter()
+            .position(|balance| balance.mint.eq(mint))
+        {
+            Some(index) => index,
+            None if count.eq(&Self::MAX_TOKEN_MINTS) => {
+                return Err(VaultError::TooManyMints.into())
+            }
+            None => {
+                self.token_balances[count] = TokenBalance {
+                    mint: *mint,
+                    amount: [0; 8],
+                };
+                self.token_mint_count = count as u8 + 1;
+                count
+            }
+        };
+
+        let entry = &mut self.token_balances[index];
+        entry.amount = u64::from_le_bytes(entry.amount)
+            .checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            .to_le_bytes();
+
+        Ok(())
+    }
+
+    /// Takes `amount` tokens of `mint` off the tracked balances, which fails
+    /// with `InsufficientFunds` if the vault tracks fewer; a mint whose balance
+    /// reaches zero frees its entry
+    #[inline(always)]
+    pub fn debit_token(&mut self, mint: &Pubkey, amount: u64) -> ProgramResult {
+        let count = self.token_balances().len();
+        let index = self
+            .token_balances()
+            .iter()
+            .position(|balance| balance.mint.eq(mint))
+            .ok_or(VaultError::InsufficientFunds)?;
+
+        let remaining = u64::from_le_bytes(self.token_balances[index].amount)
+            .checked_sub(amount)
+            .ok_or(VaultError::InsufficientFunds)?;
+        self.token_balances[index].amount = remaining.to_le_bytes();
+
+        // Keep the set entries contiguous by moving the last one into the gap
+        if remaining.eq(&0) {
+            self.token_balances[index] = self.token_balances[count - 1];
+            self.token_balances[count - 1] = TokenBalance {
+                mint: [0; 32],
+                amount: [0; 8],
+            };
+            self.token_mint_count = count as u8 - 1;
+        }
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn delegates(&self) -> &[Delegate] {
+        &self.delegates[..(self.delegate_count as usize).min(Self::MAX_DELEGATES)]
+    }
+
+    /// Per-withdrawal cap of `key`, or `None` if it isn't one of the vault's delegates
+    #[inline(always)]
+    pub fn delegate_cap(&self, key: &Pubkey) -> Option<u64> {
+        self.delegates()
+            .iter()
+            .find(|delegate| delegate.key.eq(key))
+            .map(|delegate| u64::from_le_bytes(delegate.max_per_withdrawal))
+    }
+
+    /// Lets `key` withdraw up to `max_per_withdrawal` lamports at a time,
+    /// updating the cap of a listed delegate; adding a new one fails with
+    /// `InvalidArgument` once the list is full
+    #[inline(always)]
+    pub fn set_delegate(&mut self, key: &Pubkey, max_per_withdrawal: u64) -> ProgramResult {
+        let count = self.delegates().len();
+        let index = match self
+            .delegates()
+            .iter()
+            .position(|delegate| delegate.key.eq(key))
+        {
+            Some(index) => index,
+            None if count.eq(&Self::MAX_DELEGATES) => return Err(ProgramError::InvalidArgument),
+            None => {
+                self.delegate_count = count as u8 + 1;
+                count
+            }
+        };
+
+        self.delegates[index] = Delegate {
+            key: *key,
+            max_per_withdrawal: max_per_withdrawal.to_le_bytes(),
+        };
+
+        Ok(())
+    }
+
+    /// Removes `key` from the delegates, which fails with `InvalidArgument` if
+    /// it isn't listed
+    #[inline(always)]
+    pub fn remove_delegate(&mut self, key: &Pubkey) -> ProgramResult {
+        let count = self.delegates().len();
+        let index = self
+            .delegates()
+            .iter()
+            .position(|delegate| delegate.key.eq(key))
+            .ok_or(ProgramError::InvalidArgument)?;
+
+        // Keep the set delegates contiguous by moving the last one into the gap
+        self.delegates[index] = self.delegates[count - 1];
+        self.delegates[count - 1] = Delegate {
+            key: [0; 32],
+            max_per_withdrawal: [0; 8],
+        };
+        self.delegate_count = count as u8 - 1;
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn receipt_mint(&self) -> &Pubkey {
+        &self.receipt_mint
+    }
+
+    /// Whether the vault has a receipt mint, whose mint authority is its address
+    #[inline(always)]
+    pub fn has_receipt_mint(&self) -> bool {
+        self.receipt_mint.ne(&[0; 32])
+    }
+
+    #[inline(always)]
+    pub fn receipt_supply(&self) -> u64 {
+        u64::from_le_bytes(self.receipt_supply)
+    }
+
+    /// Records `amount` receipt tokens minted from `mint`, which becomes the
+    /// vault's receipt mint on its first receipts; any other mint fails with
+    /// `InvalidAccountData`
+    #[inline(always)]
+    pub fn record_receipts_minted(&mut self, mint: &Pubkey, amount: u64) -> ProgramResult {
+        if self.receipt_mint.eq(&[0; 32]) {
+            self.receipt_mint = *mint;
+        } else if self.receipt_mint.ne(mint) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        self.receipt_supply = self
+            .receipt_supply()
+            .checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            .to_le_bytes();
+
+        Ok(())
+    }
+
+    /// Receipt tokens that have to be burned for `lamports` to leave the vault
+    ///
+    /// Lamports no receipt stands for leave first, so only what `lamports`
+    /// takes of the tracked balance past those needs a burn.
+    #[inline(always)]
+    pub fn receipts_to_burn(&self, lamports: u64) -> u64 {
+        let unreceipted = self.balance().saturating_sub(self.receipt_supply());
+        lamports
+            .min(self.balance())
+            .saturating_sub(unreceipted)
+            .min(self.receipt_supply())
+    }
+
+    /// Takes `amount` burned receipt tokens off the outstanding supply
+    #[inline(always)]
+    pub fn record_receipts_burned(&mut self, amount: u64) {
+        self.receipt_supply = self.receipt_supply().saturating_sub(amount).to_le_bytes();
+    }
+
+    /// Key that can claim the vault once its owner has been inactive for
+    /// [`Self::inactivity_secs`], or the zero pubkey for none
+    #[inline(always)]
+    pub fn beneficiary(&self) -> &Pubkey {
+        &self.beneficiary
+    }
+
+    #[inline(always)]
+    pub fn has_beneficiary(&self) -> bool {
+        self.beneficiary.ne(&[0; 32])
+    }
+
+    /// Seconds without owner activity before the beneficiary can claim
+    #[inline(always)]
+    pub fn inactivity_secs(&self) -> u64 {
+        u64::from_le_bytes(self.inactivity_secs)
+    }
+
+    /// Unix timestamp of the owner's last deposit or withdrawal
+    #[inline(always)]
+    pub fn last_activity_ts(&self) -> i64 {
+        i64::from_le_bytes(self.last_activity_ts)
+    }
+
+    /// Replaces the beneficiary, or removes it for the zero pubkey, and counts
+    /// the owner as active at `now`
+    #[inline(always)]
+    pub fn set_beneficiary(&mut self, beneficiary: &Pubkey, inactivity_secs: u64, now: i64) {
+        self.beneficiary = *beneficiary;
+        self.inactivity_secs = inactivity_secs.to_le_bytes();
+        self.last_activity_ts = now.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn record_activity(&mut self, now: i64) {
+        self.last_activity_ts = now.to_le_bytes();
+    }
+
+    /// Whether the beneficiary, if any, can claim the vault at `now`
+    #[inline(always)]
+    pub fn is_claimable(&self, now: i64) -> bool {
+        self.has_beneficiary() && self.is_inactive_for(self.inactivity_secs(), now)
+    }
+
+    /// Whether the owner has been inactive for at least `secs` at `now`
+    #[inline(always)]
+    pub fn is_inactive_for(&self, secs: u64, now: i64) -> bool {
+        let inactivity = i64::try_from(secs).unwrap_or(i64::MAX);
+        now.saturating_sub(self.last_activity_ts()) >= inactivity
+    }
+
+    /// Yearly interest rate the reward pool pays on the balance, in basis points
+    #[inline(always)]
+    pub fn reward_rate_bps(&self) -> u16 {
+        u16::from_le_bytes(self.reward_rate_bps)
+    }
+
+    /// Unix timestamp interest has been accrued up to
+    #[inline(always)]
+    pub fn last_accrual_ts(&self) -> i64 {
+        i64::from_le_bytes(self.last_accrual_ts)
+    }
+
+    /// Interest accrued up to [`Self::last_accrual_ts`] and not yet claimed
+    #[inline(always)]
+    pub fn accrued_interest(&self) -> u64 {
+        u64::from_le_bytes(self.accrued_interest)
+    }
+
+    /// Folds the interest the balance earned since the last accrual into the
+    /// accrued interest, so a balance change only counts from `now` on
+    #[inline(always)]
+    pub fn accrue(&mut self, now: i64) {
+        let elapsed = now.saturating_sub(self.last_accrual_ts()).max(0) as u64;
+        let interest = compute_interest(self.balance(), self.reward_rate_bps(), elapsed);
+        self.accrued_interest = self
+            .accrued_interest()
+            .saturating_add(interest)
+            .to_le_bytes();
+        self.last_accrual_ts = now.to_le_bytes();
+    }
+
+    /// Accrues at the current rate up to `now`, then switches to `rate_bps`
+    #[inline(always)]
+    pub fn set_reward_rate(&mut self, rate_bps: u16, now: i64) {
+        self.accrue(now);
+        self.reward_rate_bps = rate_bps.to_le_bytes();
+    }
+
+    /// Accrues up to `now` and credits every lamport of interest accrued to the
+    /// tracked balance, returning how many
+    #[inline(always)]
+    pub fn claim_interest(&mut self, now: i64) -> u64 {
+        self.accrue(now);
+        let interest = self.accrued_interest();
+        self.accrued_interest = [0; 8];
+        self.balance = self.balance().saturating_add(interest).to_le_bytes();
+        interest
+    }
+
+    /// Key that manages the vault in the owner's place, or the zero pubkey for
+    /// none
+    #[inline(always)]
+    pub fn admin(&self) -> &Pubkey {
+        &self.admin
+    }
+
+    #[inline(always)]
+    pub fn has_admin(&self) -> bool {
+        self.admin.ne(&[0; 32])
+    }
+
+    #[inline(always)]
+    pub fn set_admin(&mut self, admin: &Pubkey) {
+        self.admin = *admin;
+    }
+
+    /// Takes `lamports` released to the owner off the tracked balance
+    #[inline(always)]
+    pub fn record_withdrawal(&mut self, lamports: u64) {
+        self.balance = self.balance().saturating_sub(lamports).to_le_bytes();
+    }
+
+    /// Lowers the tracked balance to `withdrawable` if it claims more than that
+    #[inline(always)]
+    pub fn cap_balance(&mut self, withdrawable: u64) {
+        self.balance = self.balance().min(withdrawable).to_le_bytes();
+    }
+
+    /// Sets the tracked balance to `withdrawable`, recognizing lamports that
+    /// reached the vault outside a deposit
+    #[inline(always)]
+    pub fn set_balance(&mut self, withdrawable: u64) {
+        self.balance = withdrawable.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn record_vested_withdrawal(&mut self, lamports: u64) {
+        self.withdrawn_so_far = self
+            .withdrawn_so_far()
+            .saturating_add(lamports)
+            .to_le_bytes();
+    }
+
+    /// Takes on `source`'s unlock timestamp and slot, daily and rolling limits
+    /// with what's already been withdrawn against them, price gate, escrow
+    /// agent, multisig signers and minimum age, counted from `source`'s
+    /// creation, for a vault split off it; its flags are set when the vault is
+    /// created
+    #[inline(always)]
+    pub fn inherit_locks(&mut self, source: &Self) {
+        self.unlock_ts = source.unlock_ts;
+        self.unlock_slot = source.unlock_slot;
+        self.daily_limit = source.daily_limit;
+        self.daily_withdrawn = source.daily_withdrawn;
+        self.window_start = source.window_start;
+        self.oracle = source.oracle;
+        self.max_price_age = source.max_price_age;
+        self.escrow_agent = source.escrow_agent;
+        self.created_ts = source.created_ts;
+        self.min_age_secs = source.min_age_secs;
+        self.rolling_limit = source.rolling_limit;
+        self.rolling_window_secs = source.rolling_window_secs;
+        self.recent_withdrawals = source.recent_withdrawals;
+        self.recent_head = source.recent_head;
+        self.multisig_threshold = source.multisig_threshold;
+        self.multisig_signer_count = source.multisig_signer_count;
+        self.multisig_signers = source.multisig_signers;
+        self.admin = source.admin;
+    }
+
+    /// Takes over `old`'s whole state under a new address, keeping only the
+    /// `id` and canonical `bump` of that address
+    #[inline(always)]
+    pub fn migrate_from(&mut self, old: &Self, id: &[u8; 8], bump: u8) {
+        *self = *old;
+        self.id = *id;
+        self.bump = bump;
+    }
+
+    /// Stamps state grown from an older layout with the current version
+    #[inline(always)]
+    pub fn upgrade(&mut self) {
+        self.version = Self::VERSION;
+    }
+
+    #[inline(always)]
+    pub fn set_inner(
+        &mut self,
+        owner: &Pubkey,
+        id: &[u8; 8],
+        bump: u8,
+        creation_slot: u64,
+        created_ts: i64,
+        flags: u8,
+    ) {
+        self.version = Self::VERSION;
+        self.owner = *owner;
+        self.id = *id;
+        self.bump = bump;
+        self.creation_slot = creation_slot.to_le_bytes();
+        self.created_ts = created_ts.to_le_bytes();
+        self.last_activity_ts = created_ts.to_le_bytes();
+        self.flags = flags;
+    }
+}
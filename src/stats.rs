@@ -0,0 +1,164 @@
+use core::mem::size_of;
+
+use pinocchio::{
+    account_info::{AccountInfo, RefMut},
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    ProgramResult,
+};
+use pinocchio_system::instructions::CreateAccount;
+
+use crate::error::VaultError;
+use crate::pda::{find_stats_address, STATS_SEED};
+use crate::rent::minimum_balance;
+
+/// Protocol-wide statistics across every vault
+///
+/// Lives in the `["stats"]` PDA, which is created on first use and updated by
+/// every instruction that moves lamports in or out of a vault account. The
+/// value locked counts every lamport held by vaults, rent floors and gas
+/// reserves of opened vaults included. SPL tokens, wrapped SOL among them, sit
+/// in the vaults' token accounts and aren't counted.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct GlobalStats {
+    total_vaults: [u8; 8],
+    total_value_locked: [u8; 8],
+    lifetime_deposits: [u8; 8],
+    lifetime_withdrawals: [u8; 8],
+}
+
+impl GlobalStats {
+    pub const LEN: usize = size_of::<Self>();
+
+    /// Reinterprets raw account data as stats, e.g. off-chain
+    #[inline(always)]
+    pub fn from_bytes(data: &[u8]) -> Option<&Self> {
+        if data.len().ne(&Self::LEN) {
+            return None;
+        }
+
+        Some(unsafe { &*(data.as_ptr() as *const Self) })
+    }
+
+    #[inline(always)]
+    pub fn total_vaults(&self) -> u64 {
+        u64::from_le_bytes(self.total_vaults)
+    }
+
+    #[inline(always)]
+    pub fn total_value_locked(&self) -> u64 {
+        u64::from_le_bytes(self.total_value_locked)
+    }
+
+    #[inline(always)]
+    pub fn lifetime_deposits(&self) -> u64 {
+        u64::from_le_bytes(self.lifetime_deposits)
+    }
+
+    #[inline(always)]
+    pub fn lifetime_withdrawals(&self) -> u64 {
+        u64::from_le_bytes(self.lifetime_withdrawals)
+    }
+
+    /// Records a new vault created with `lamports` already in it
+    #[inline(always)]
+    pub fn record_open(&mut self, lamports: u64) {
+        self.total_vaults = self.total_vaults().saturating_add(1).to_le_bytes();
+        self.add_value(lamports);
+    }
+
+    /// Records a deposit, counting the vault if the deposit brought it into existence
+    #[inline(always)]
+    pub fn record_deposit(&mut self, lamports: u64, created: bool) {
+        if created {
+            self.total_vaults = self.total_vaults().saturating_add(1).to_le_bytes();
+        }
+        self.add_value(lamports);
+        self.lifetime_deposits = self
+            .lifetime_deposits()
+            .saturating_add(lamports)
+            .to_le_bytes();
+    }
+
+    /// Records lamports withdrawn to an owner, uncounting `closed` vaults it emptied
+    #[inline(always)]
+    pub fn record_withdrawal(&mut self, lamports: u64, closed: u64) {
+        self.record_close(closed);
+        self.total_value_locked = self
+            .total_value_locked()
+            .saturating_sub(lamports)
+            .to_le_bytes();
+        self.lifetime_withdrawals = self
+            .lifetime_withdrawals()
+            .saturating_add(lamports)
+            .to_le_bytes();
+    }
+
+    /// Uncounts `closed` vaults emptied without their lamports leaving the protocol
+    #[inline(always)]
+    pub fn record_close(&mut self, closed: u64) {
+        self.total_vaults = self.total_vaults().saturating_sub(closed).to_le_bytes();
+    }
+
+    #[inline(always)]
+    fn add_value(&mut self, lamports: u64) {
+        self.total_value_locked = self
+            .total_value_locked()
+            .saturating_add(lamports)
+            .to_le_bytes();
+    }
+}
+
+/// Checks that `stats` is the global stats PDA
+#[inline(always)]
+pub fn check_stats_account(stats: &AccountInfo) -> Result<u8, ProgramError> {
+    let (stats_key, bump) = find_stats_address();
+    if stats.key().ne(&stats_key) {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+
+    Ok(bump)
+}
+
+/// Mutably borrows the global stats, creating the account at `payer`'s expense on first use
+pub fn load_stats<'a>(
+    payer: &AccountInfo,
+    stats: &'a AccountInfo,
+    bump: u8,
+    rent_sysvar: Option<&AccountInfo>,
+) -> Result<RefMut<'a, GlobalStats>, ProgramError> {
+    if stats.is_owned_by(&pinocchio_system::ID) {
+        create_stats(payer, stats, bump, rent_sysvar)?;
+    } else if !stats.is_owned_by(&crate::ID) {
+        return Err(VaultError::InvalidVaultOwner.into());
+    }
+
+    if stats.data_len().ne(&GlobalStats::LEN) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(RefMut::map(stats.try_borrow_mut_data()?, |data| unsafe {
+        &mut *(data.as_mut_ptr() as *mut GlobalStats)
+    }))
+}
+
+fn create_stats(
+    payer: &AccountInfo,
+    stats: &AccountInfo,
+    bump: u8,
+    rent_sysvar: Option<&AccountInfo>,
+) -> ProgramResult {
+    let bumps = [bump];
+    let seeds = [Seed::from(STATS_SEED), Seed::from(&bumps)];
+    let signers = [Signer::from(&seeds)];
+
+    CreateAccount {
+        from: payer,
+        to: stats,
+        lamports: minimum_balance(rent_sysvar, GlobalStats::LEN)?,
+        space: GlobalStats::LEN as u64,
+        owner: &crate::ID,
+    }
+    .invoke_signed(&signers)
+}
@@ -0,0 +1,27 @@
+use pinocchio::{
+    program_error::ProgramError,
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
+};
+
+use crate::error::VaultError;
+
+/// Fetches the clock sysvar, failing with `SysvarUnavailable` if the runtime
+/// can't provide it
+#[inline(always)]
+pub fn clock() -> Result<Clock, ProgramError> {
+    Clock::get().map_err(sysvar_unavailable)
+}
+
+/// Fetches the rent sysvar, failing with `SysvarUnavailable` if the runtime
+/// can't provide it
+#[inline(always)]
+pub fn rent() -> Result<Rent, ProgramError> {
+    Rent::get().map_err(sysvar_unavailable)
+}
+
+/// Maps a failed sysvar syscall to `SysvarUnavailable`, since the runtime folds
+/// every reason into `UnsupportedSysvar` anyway
+#[inline(always)]
+fn sysvar_unavailable(_: ProgramError) -> ProgramError {
+    VaultError::SysvarUnavailable.into()
+}
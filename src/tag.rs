@@ -0,0 +1,132 @@
+use core::mem::size_of;
+
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    ProgramResult,
+};
+use pinocchio_system::instructions::{CreateAccount, Transfer};
+
+use crate::error::VaultError;
+use crate::pda::TAG_SEED;
+use crate::rent::minimum_balance;
+
+/// Header of an owner's index of vaults sharing a tag
+///
+/// The index lives in the `["tag", owner, tag]` PDA: the header is followed by
+/// `count` vault pubkeys, and the account grows by one entry per tagged vault.
+#[repr(C)]
+pub struct TagIndexHeader {
+    owner: Pubkey,
+    tag: [u8; 4],
+    count: [u8; 8],
+}
+
+impl TagIndexHeader {
+    pub const LEN: usize = size_of::<Self>();
+
+    #[inline(always)]
+    pub fn owner(&self) -> &Pubkey {
+        &self.owner
+    }
+
+    #[inline(always)]
+    pub fn tag(&self) -> u32 {
+        u32::from_le_bytes(self.tag)
+    }
+
+    #[inline(always)]
+    pub fn count(&self) -> u64 {
+        u64::from_le_bytes(self.count)
+    }
+}
+
+/// Returns the tag index account size needed to hold `count` vaults
+#[inline(always)]
+pub const fn tag_index_len(count: usize) -> usize {
+    TagIndexHeader::LEN + count * size_of::<Pubkey>()
+}
+
+/// Appends `vault` to `owner`'s index of `tag`, creating the index on first use
+///
+/// A vault that is already listed is not added twice. The owner pays for the
+/// extra rent as the index grows.
+pub fn add_to_tag_index(
+    owner: &AccountInfo,
+    index: &AccountInfo,
+    index_bump: u8,
+    tag: &[u8; 4],
+    vault: &Pubkey,
+    rent_sysvar: Option<&AccountInfo>,
+) -> ProgramResult {
+    let count = if index.is_owned_by(&pinocchio_system::ID) {
+        let bumps = [index_bump];
+        let seeds = [
+            Seed::from(TAG_SEED),
+            Seed::from(owner.key().as_ref()),
+            Seed::from(tag),
+            Seed::from(&bumps),
+        ];
+        let signers = [Signer::from(&seeds)];
+
+        CreateAccount {
+            from: owner,
+            to: index,
+            lamports: minimum_balance(rent_sysvar, tag_index_len(1))?,
+            space: tag_index_len(1) as u64,
+            owner: &crate::ID,
+        }
+        .invoke_signed(&signers)?;
+
+        let mut data = index.try_borrow_mut_data()?;
+        data[..32].copy_from_slice(owner.key());
+        data[32..36].copy_from_slice(tag);
+        0
+    } else {
+        if !index.is_owned_by(&crate::ID) {
+            return Err(VaultError::InvalidVaultOwner.into());
+        }
+
+        let count = {
+            let data = index.try_borrow_data()?;
+            let count = u64::from_le_bytes(data[36..44].try_into().unwrap()) as usize;
+            if data.len().ne(&tag_index_len(count)) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            // Guard against listing the same vault twice
+            let already_listed = data[TagIndexHeader::LEN..]
+                .chunks_exact(size_of::<Pubkey>())
+                .any(|entry| entry.eq(vault.as_ref()));
+            if already_listed {
+                return Ok(());
+            }
+
+            count
+        };
+
+        // Grow by one entry and top the rent up to the new size
+        index.resize(tag_index_len(count + 1))?;
+        let shortfall = minimum_balance(rent_sysvar, tag_index_len(count + 1))?
+            .saturating_sub(index.lamports());
+        if shortfall.ne(&0) {
+            Transfer {
+                from: owner,
+                to: index,
+                lamports: shortfall,
+            }
+            .invoke()?;
+        }
+
+        count
+    };
+
+    let mut data = index.try_borrow_mut_data()?;
+    let entry = tag_index_len(count);
+    data[entry..entry + size_of::<Pubkey>()].copy_from_slice(vault);
+    data[36..44].copy_from_slice(&(count as u64 + 1).to_le_bytes());
+
+    Ok(())
+}
@@ -0,0 +1,187 @@
+//! LiteSVM helpers for tests, enabled with the `testing` feature.
+
+extern crate std;
+
+use std::{fmt, io, path::Path, path::PathBuf};
+
+use litesvm::{error::LiteSVMError, types::TransactionResult, LiteSVM};
+use solana_account::Account;
+use solana_fee_structure::FeeStructure;
+use solana_instruction::Instruction;
+use solana_keypair::Keypair;
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+
+use crate::client::{self, sol_to_lamports, PROGRAM_ID};
+
+/// Where `cargo build-sbf` writes the program binary, relative to the crate root
+pub const PROGRAM_PATH: &str = "target/deploy/blueshift_vault.so";
+
+/// Errors returned while loading the vault program into LiteSVM
+#[derive(Debug)]
+pub enum LoadProgramError {
+    /// The program binary couldn't be read, usually because it hasn't been built
+    Read { path: PathBuf, source: io::Error },
+    /// LiteSVM rejected the program binary
+    Add(LiteSVMError),
+    /// No executable account exists at the program id after loading
+    NotLoaded,
+}
+
+impl fmt::Display for LoadProgramError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Read { path, source } => write!(
+                f,
+                "failed to read vault program at {} ({source}); run `cargo build-sbf` first",
+                path.display()
+            ),
+            Self::Add(err) => write!(f, "LiteSVM rejected the vault program: {err}"),
+            Self::NotLoaded => write!(f, "vault program is not executable at {PROGRAM_ID}"),
+        }
+    }
+}
+
+impl std::error::Error for LoadProgramError {}
+
+/// Loads the built vault program into `svm` under the canonical program id
+pub fn load_vault_program(svm: &mut LiteSVM) -> Result<(), LoadProgramError> {
+    load_vault_program_from(svm, PROGRAM_PATH)
+}
+
+/// Loads the vault program binary at `path` into `svm` under the canonical program id
+pub fn load_vault_program_from(
+    svm: &mut LiteSVM,
+    path: impl AsRef<Path>,
+) -> Result<(), LoadProgramError> {
+    let path = path.as_ref();
+    let program_bytes = std::fs::read(path).map_err(|source| LoadProgramError::Read {
+        path: path.to_path_buf(),
+        source,
+    })?;
+
+    svm.add_program(PROGRAM_ID, &program_bytes)
+        .map_err(LoadProgramError::Add)?;
+
+    // Make sure the program actually landed before tests start sending to it
+    match svm.get_account(&PROGRAM_ID) {
+        Some(account) if account.executable => Ok(()),
+        _ => Err(LoadProgramError::NotLoaded),
+    }
+}
+
+/// Records `authority` as the vault program's upgrade authority in a
+/// ProgramData account, which Initialize requires the config's creator to be
+///
+/// LiteSVM loads programs without the upgradeable loader, so tests that
+/// initialize the config write the account themselves.
+pub fn set_upgrade_authority(svm: &mut LiteSVM, authority: &Pubkey) {
+    // `[tag: u32 = 3][slot: u64][authority: Option<Pubkey>]`
+    let mut data = 3u32.to_le_bytes().to_vec();
+    data.extend_from_slice(&0u64.to_le_bytes());
+    data.push(1);
+    data.extend_from_slice(authority.as_ref());
+
+    svm.set_account(
+        client::find_program_data_address().0,
+        Account {
+            lamports: svm.minimum_balance_for_rent_exemption(data.len()),
+            data,
+            owner: client::BPF_LOADER_UPGRADEABLE_ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    )
+    .expect("Failed to set the program data account");
+}
+
+/// Returns the lamports the fee payers of `transactions` are charged for them
+///
+/// LiteSVM doesn't report the fee it charged, so this recomputes it the way
+/// LiteSVM does under its default fee structure: a flat fee per signature and
+/// no prioritization fee. The fee is charged whether or not a transaction fails.
+pub fn total_fees<'a>(transactions: impl IntoIterator<Item = &'a Transaction>) -> u64 {
+    let lamports_per_signature = FeeStructure::default().lamports_per_signature;
+    transactions
+        .into_iter()
+        .map(|tx| tx.message.header.num_required_signatures as u64 * lamports_per_signature)
+        .sum()
+}
+
+/// Sends `instructions` in one transaction under a fresh blockhash, paid for by
+/// the first of `signers` and signed by all of them
+///
+/// The fresh blockhash keeps sending the same instructions twice from being
+/// deduplicated.
+///
+/// # Panics
+///
+/// If `signers` is empty.
+#[allow(clippy::result_large_err)]
+pub fn send_transaction(
+    svm: &mut LiteSVM,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> TransactionResult {
+    svm.expire_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&signers[0].pubkey()),
+        signers,
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(tx)
+}
+
+/// LiteSVM with the vault program loaded, for tests that only need the
+/// default vault's basic flows
+///
+/// Every transaction is paid for and signed by the owner it acts for, and gets
+/// a fresh blockhash, so sending the same instruction twice isn't deduplicated.
+/// Anything else goes through `svm` directly.
+pub struct VaultHarness {
+    pub svm: LiteSVM,
+}
+
+// Results are LiteSVM's own, large error variant and all
+#[allow(clippy::result_large_err)]
+impl VaultHarness {
+    /// Starts a fresh LiteSVM with the built vault program loaded
+    pub fn new() -> Result<Self, LoadProgramError> {
+        let mut svm = LiteSVM::new();
+        load_vault_program(&mut svm)?;
+
+        Ok(Self { svm })
+    }
+
+    /// Airdrops `sol` SOL to `owner`
+    ///
+    /// # Panics
+    ///
+    /// If `sol` isn't a valid SOL amount, see [`sol_to_lamports`].
+    pub fn fund(&mut self, owner: &Pubkey, sol: f64) -> TransactionResult {
+        self.svm.airdrop(owner, sol_to_lamports(sol))
+    }
+
+    /// Deposits `amount` lamports into `owner`'s default vault
+    pub fn deposit(&mut self, owner: &Keypair, amount: u64) -> TransactionResult {
+        self.send(owner, client::deposit_instruction(&owner.pubkey(), amount))
+    }
+
+    /// Withdraws everything from `owner`'s default vault, closing it
+    pub fn withdraw(&mut self, owner: &Keypair) -> TransactionResult {
+        self.send(owner, client::withdraw_instruction(&owner.pubkey()))
+    }
+
+    /// Returns the lamports in `owner`'s default vault, or `None` if it doesn't exist
+    pub fn balance(&self, owner: &Pubkey) -> Option<u64> {
+        client::vault_balance(&|address: &Pubkey| self.svm.get_account(address), owner)
+    }
+
+    /// Sends `instruction` in its own transaction, paid for and signed by `owner`
+    pub fn send(&mut self, owner: &Keypair, instruction: Instruction) -> TransactionResult {
+        send_transaction(&mut self.svm, &[instruction], &[owner])
+    }
+}
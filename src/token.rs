@@ -0,0 +1,410 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{AccountMeta, Instruction, Signer},
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    pubkey::{find_program_address, Pubkey},
+    ProgramResult,
+};
+
+/// SPL Token program id
+pub const TOKEN_PROGRAM_ID: Pubkey =
+    pinocchio_pubkey::from_str("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+/// SPL Associated Token Account program id
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    pinocchio_pubkey::from_str("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/// Mint of wrapped SOL
+pub const NATIVE_MINT: Pubkey =
+    pinocchio_pubkey::from_str("So11111111111111111111111111111111111111112");
+
+/// Size of an SPL Token account
+pub const TOKEN_ACCOUNT_LEN: usize = 165;
+
+/// Size of an SPL Token mint
+pub const MINT_LEN: usize = 82;
+
+/// Derives the associated token account of `wallet` for `mint`
+#[inline(always)]
+pub fn find_associated_token_address(wallet: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    find_program_address(
+        &[wallet.as_ref(), TOKEN_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+}
+
+/// Checks that `account` is an initialized wrapped-SOL token account owned by `owner`
+///
+/// Layout: `[mint: 32][owner: 32][amount: 8][delegate: 36][state: 1][is_native: 12]..`.
+pub fn check_wrapped_sol_account(account: &AccountInfo, owner: &Pubkey) -> ProgramResult {
+    if !account.is_owned_by(&TOKEN_PROGRAM_ID) {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+
+    let data = account.try_borrow_data()?;
+    if data.len().ne(&TOKEN_ACCOUNT_LEN) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Only a native account unwraps to lamports when closed
+    if data[..32].ne(&NATIVE_MINT) || data[109..113].ne(&1u32.to_le_bytes()) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if data[32..64].ne(owner) {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    Ok(())
+}
+
+/// Checks that `account` is an initialized token account of any mint owned by
+/// `owner`, and returns its token balance
+pub fn check_token_account(account: &AccountInfo, owner: &Pubkey) -> Result<u64, ProgramError> {
+    if !account.is_owned_by(&TOKEN_PROGRAM_ID) {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+
+    let data = account.try_borrow_data()?;
+    if data.len().ne(&TOKEN_ACCOUNT_LEN) || data[108].eq(&0) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if data[32..64].ne(owner) {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    Ok(u64::from_le_bytes(data[64..72].try_into().unwrap()))
+}
+
+/// Checks that `account` is `vault`'s initialized associated token account for
+/// `mint`, and returns its token balance
+pub fn check_vault_token_account(
+    account: &AccountInfo,
+    vault: &Pubkey,
+    mint: &Pubkey,
+) -> Result<u64, ProgramError> {
+    if account.key().ne(&find_associated_token_address(vault, mint).0) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let amount = check_token_account(account, vault)?;
+    if token_account_mint(account)?.ne(mint) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(amount)
+}
+
+/// Returns the mint of `account`, an initialized token account
+pub fn token_account_mint(account: &AccountInfo) -> Result<Pubkey, ProgramError> {
+    if !account.is_owned_by(&TOKEN_PROGRAM_ID) {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+
+    let data = account.try_borrow_data()?;
+    if data.len().ne(&TOKEN_ACCOUNT_LEN) || data[108].eq(&0) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(data[..32].try_into().unwrap())
+}
+
+/// Returns the owner of `account`, an initialized token account of any mint
+pub fn token_account_owner(account: &AccountInfo) -> Result<Pubkey, ProgramError> {
+    if !account.is_owned_by(&TOKEN_PROGRAM_ID) {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+
+    let data = account.try_borrow_data()?;
+    if data.len().ne(&TOKEN_ACCOUNT_LEN) || data[108].eq(&0) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(data[32..64].try_into().unwrap())
+}
+
+/// Checks that `account` is an initialized mint whose mint authority is `authority`
+///
+/// Layout: `[mint_authority: COption<Pubkey>][supply: u64][decimals: u8][is_initialized: u8]..`.
+pub fn check_mint_authority(account: &AccountInfo, authority: &Pubkey) -> ProgramResult {
+    if !account.is_owned_by(&TOKEN_PROGRAM_ID) {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+
+    let data = account.try_borrow_data()?;
+    if data.len().ne(&MINT_LEN) || data[45].eq(&0) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if data[..4].ne(&1u32.to_le_bytes()) || data[4..36].ne(authority) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(())
+}
+
+/// Close a token account, sending all its lamports to `destination`.
+///
+/// ### Accounts:
+///   0. `[WRITE]` Account to close
+///   1. `[WRITE]` Destination account
+///   2. `[SIGNER]` Account owner
+pub struct CloseAccount<'a> {
+    /// Token account to close.
+    pub account: &'a AccountInfo,
+
+    /// Recipient of the account's lamports.
+    pub destination: &'a AccountInfo,
+
+    /// Owner of the token account.
+    pub authority: &'a AccountInfo,
+}
+
+impl CloseAccount<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        let account_metas: [AccountMeta; 3] = [
+            AccountMeta::writable(self.account.key()),
+            AccountMeta::writable(self.destination.key()),
+            AccountMeta::readonly_signer(self.authority.key()),
+        ];
+
+        let instruction = Instruction {
+            program_id: &TOKEN_PROGRAM_ID,
+            accounts: &account_metas,
+            data: &[9],
+        };
+
+        invoke(
+            &instruction,
+            &[self.account, self.destination, self.authority],
+        )
+    }
+}
+
+/// Transfer `amount` tokens from one token account to another.
+///
+/// ### Accounts:
+///   0. `[WRITE]` Source account
+///   1. `[WRITE]` Destination account
+///   2. `[SIGNER]` Source account owner
+pub struct Transfer<'a> {
+    /// Token account to debit.
+    pub from: &'a AccountInfo,
+
+    /// Token account to credit, of the same mint.
+    pub to: &'a AccountInfo,
+
+    /// Owner of the source account.
+    pub authority: &'a AccountInfo,
+
+    /// Amount of tokens to transfer.
+    pub amount: u64,
+}
+
+impl Transfer<'_> {
+    #[inline(always)]
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let account_metas: [AccountMeta; 3] = [
+            AccountMeta::writable(self.from.key()),
+            AccountMeta::writable(self.to.key()),
+            AccountMeta::readonly_signer(self.authority.key()),
+        ];
+
+        // `[discriminator: u8][amount: u64]`
+        let mut data = [0u8; 9];
+        data[0] = 3;
+        data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+
+        let instruction = Instruction {
+            program_id: &TOKEN_PROGRAM_ID,
+            accounts: &account_metas,
+            data: &data,
+        };
+
+        invoke_signed(
+            &instruction,
+            &[self.from, self.to, self.authority],
+            signers,
+        )
+    }
+}
+
+/// Mint `amount` new tokens into a token account.
+///
+/// ### Accounts:
+///   0. `[WRITE]` Mint
+///   1. `[WRITE]` Account to credit
+///   2. `[SIGNER]` Mint authority
+pub struct MintTo<'a> {
+    /// Mint of the new tokens.
+    pub mint: &'a AccountInfo,
+
+    /// Token account to credit, of the same mint.
+    pub account: &'a AccountInfo,
+
+    /// Mint authority of the mint.
+    pub mint_authority: &'a AccountInfo,
+
+    /// Amount of tokens to mint.
+    pub amount: u64,
+}
+
+impl MintTo<'_> {
+    #[inline(always)]
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let account_metas: [AccountMeta; 3] = [
+            AccountMeta::writable(self.mint.key()),
+            AccountMeta::writable(self.account.key()),
+            AccountMeta::readonly_signer(self.mint_authority.key()),
+        ];
+
+        // `[discriminator: u8][amount: u64]`
+        let mut data = [0u8; 9];
+        data[0] = 7;
+        data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+
+        let instruction = Instruction {
+            program_id: &TOKEN_PROGRAM_ID,
+            accounts: &account_metas,
+            data: &data,
+        };
+
+        invoke_signed(
+            &instruction,
+            &[self.mint, self.account, self.mint_authority],
+            signers,
+        )
+    }
+}
+
+/// Burn `amount` tokens from a token account.
+///
+/// ### Accounts:
+///   0. `[WRITE]` Account to debit
+///   1. `[WRITE]` Mint
+///   2. `[SIGNER]` Account owner
+pub struct Burn<'a> {
+    /// Token account to debit.
+    pub account: &'a AccountInfo,
+
+    /// Mint of the burned tokens.
+    pub mint: &'a AccountInfo,
+
+    /// Owner of the token account.
+    pub authority: &'a AccountInfo,
+
+    /// Amount of tokens to burn.
+    pub amount: u64,
+}
+
+impl Burn<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        let account_metas: [AccountMeta; 3] = [
+            AccountMeta::writable(self.account.key()),
+            AccountMeta::writable(self.mint.key()),
+            AccountMeta::readonly_signer(self.authority.key()),
+        ];
+
+        // `[discriminator: u8][amount: u64]`
+        let mut data = [0u8; 9];
+        data[0] = 8;
+        data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+
+        let instruction = Instruction {
+            program_id: &TOKEN_PROGRAM_ID,
+            accounts: &account_metas,
+            data: &data,
+        };
+
+        invoke(&instruction, &[self.account, self.mint, self.authority])
+    }
+}
+
+/// Sync a native token account's token amount with the lamports it holds.
+///
+/// ### Accounts:
+///   0. `[WRITE]` Native token account
+pub struct SyncNative<'a> {
+    /// Native token account to sync.
+    pub account: &'a AccountInfo,
+}
+
+impl SyncNative<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        let account_metas: [AccountMeta; 1] = [AccountMeta::writable(self.account.key())];
+
+        let instruction = Instruction {
+            program_id: &TOKEN_PROGRAM_ID,
+            accounts: &account_metas,
+            data: &[17],
+        };
+
+        invoke(&instruction, &[self.account])
+    }
+}
+
+/// Create `wallet`'s associated token account for `mint`, unless it already exists.
+///
+/// ### Accounts:
+///   0. `[WRITE, SIGNER]` Funding account
+///   1. `[WRITE]` Associated token account
+///   2. `[]` Wallet owning the new account
+///   3. `[]` Mint
+///   4. `[]` System program
+///   5. `[]` Token program
+pub struct CreateAssociatedTokenAccountIdempotent<'a> {
+    /// Account paying the new account's rent.
+    pub funding_account: &'a AccountInfo,
+
+    /// Associated token account to create.
+    pub account: &'a AccountInfo,
+
+    /// Owner of the new account.
+    pub wallet: &'a AccountInfo,
+
+    /// Mint of the new account.
+    pub mint: &'a AccountInfo,
+
+    /// System program.
+    pub system_program: &'a AccountInfo,
+
+    /// Token program.
+    pub token_program: &'a AccountInfo,
+}
+
+impl CreateAssociatedTokenAccountIdempotent<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        let account_metas: [AccountMeta; 6] = [
+            AccountMeta::writable_signer(self.funding_account.key()),
+            AccountMeta::writable(self.account.key()),
+            AccountMeta::readonly(self.wallet.key()),
+            AccountMeta::readonly(self.mint.key()),
+            AccountMeta::readonly(self.system_program.key()),
+            AccountMeta::readonly(self.token_program.key()),
+        ];
+
+        let instruction = Instruction {
+            program_id: &ASSOCIATED_TOKEN_PROGRAM_ID,
+            accounts: &account_metas,
+            data: &[1],
+        };
+
+        invoke(
+            &instruction,
+            &[
+                self.funding_account,
+                self.account,
+                self.wallet,
+                self.mint,
+                self.system_program,
+                self.token_program,
+            ],
+        )
+    }
+}
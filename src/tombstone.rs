@@ -0,0 +1,133 @@
+use core::mem::size_of;
+
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    ProgramResult,
+};
+use pinocchio_system::instructions::CreateAccount;
+
+use crate::config::reuse_cooldown_secs;
+use crate::error::VaultError;
+use crate::pda::{find_tombstone_address, TOMBSTONE_SEED};
+use crate::rent::minimum_balance;
+use crate::sysvar::clock;
+
+/// When a vault was last closed, outliving the vault itself
+///
+/// Lives in the `["tombstone", vault]` PDA, which Close and CloseAndSweep
+/// create or update when asked to. While the config has a `reuse_cooldown_secs`,
+/// Deposit won't fund the vault's address again until that long after
+/// `closed_at`, so indexers see the close before the address comes back.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Tombstone {
+    closed_at: [u8; 8],
+}
+
+impl Tombstone {
+    pub const LEN: usize = size_of::<Self>();
+
+    /// Reinterprets raw account data as a tombstone, e.g. off-chain
+    #[inline(always)]
+    pub fn from_bytes(data: &[u8]) -> Option<&Self> {
+        if data.len().ne(&Self::LEN) {
+            return None;
+        }
+
+        Some(unsafe { &*(data.as_ptr() as *const Self) })
+    }
+
+    /// Unix timestamp of the vault's last recorded close
+    #[inline(always)]
+    pub fn closed_at(&self) -> i64 {
+        i64::from_le_bytes(self.closed_at)
+    }
+}
+
+/// Checks that `tombstone` is the tombstone PDA of `vault`
+#[inline(always)]
+pub fn check_tombstone_account(
+    vault: &AccountInfo,
+    tombstone: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    let (tombstone_key, bump) = find_tombstone_address(vault.key());
+    if tombstone.key().ne(&tombstone_key) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    Ok(bump)
+}
+
+/// Records that `vault` closed now, creating its tombstone at `payer`'s expense
+/// the first time
+pub fn record_close(
+    payer: &AccountInfo,
+    vault: &AccountInfo,
+    tombstone: &AccountInfo,
+    bump: u8,
+    rent_sysvar: Option<&AccountInfo>,
+) -> ProgramResult {
+    if tombstone.is_owned_by(&pinocchio_system::ID) {
+        create_tombstone(payer, vault, tombstone, bump, rent_sysvar)?;
+    } else if !tombstone.is_owned_by(&crate::ID) {
+        return Err(VaultError::InvalidVaultOwner.into());
+    }
+
+    let mut data = tombstone.try_borrow_mut_data()?;
+    if data.len().ne(&Tombstone::LEN) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    data.copy_from_slice(&clock()?.unix_timestamp.to_le_bytes());
+
+    Ok(())
+}
+
+/// Fails with `ReuseCooldown` if `tombstone` records a close less than the
+/// config's `reuse_cooldown_secs` ago
+///
+/// A vault that was never closed with a tombstone has nothing to wait for.
+pub fn check_reuse_cooldown(config: &AccountInfo, tombstone: &AccountInfo) -> ProgramResult {
+    if !tombstone.is_owned_by(&crate::ID) {
+        return Ok(());
+    }
+
+    let data = tombstone.try_borrow_data()?;
+    let closed_at = Tombstone::from_bytes(&data)
+        .ok_or(ProgramError::InvalidAccountData)?
+        .closed_at();
+
+    let reusable_at = closed_at.saturating_add_unsigned(reuse_cooldown_secs(config)?);
+    if clock()?.unix_timestamp < reusable_at {
+        return Err(VaultError::ReuseCooldown.into());
+    }
+
+    Ok(())
+}
+
+fn create_tombstone(
+    payer: &AccountInfo,
+    vault: &AccountInfo,
+    tombstone: &AccountInfo,
+    bump: u8,
+    rent_sysvar: Option<&AccountInfo>,
+) -> ProgramResult {
+    let bumps = [bump];
+    let seeds = [
+        Seed::from(TOMBSTONE_SEED),
+        Seed::from(vault.key().as_ref()),
+        Seed::from(&bumps),
+    ];
+    let signers = [Signer::from(&seeds)];
+
+    CreateAccount {
+        from: payer,
+        to: tombstone,
+        lamports: minimum_balance(rent_sysvar, Tombstone::LEN)?,
+        space: Tombstone::LEN as u64,
+        owner: &crate::ID,
+    }
+    .invoke_signed(&signers)
+}
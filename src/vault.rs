@@ -0,0 +1,808 @@
+use pinocchio::{
+    account_info::AccountInfo, instruction::Signer, program_error::ProgramError, ProgramResult,
+};
+use pinocchio_log::log;
+use pinocchio_system::instructions::{CreateAccount, Transfer};
+
+use crate::error::VaultError;
+use crate::logic::{check_tracked_balance, compute_releasable};
+use crate::pda::{find_vault_address, log_vault_mismatch, vault_signer_seeds};
+use crate::rent::minimum_balance;
+use crate::state::VaultState;
+use crate::sysvar::clock;
+
+/// Checks that `vault` is either a plain system account or a vault opened by this program
+#[inline(always)]
+pub fn check_vault_owner(vault: &AccountInfo) -> ProgramResult {
+    if !vault.is_owned_by(&pinocchio_system::ID) && !vault.is_owned_by(&crate::ID) {
+        return Err(VaultError::InvalidVaultOwner.into());
+    }
+
+    Ok(())
+}
+
+/// Fails with `InvalidAccountArrangement` if `owner` and `vault` are the same account
+///
+/// A vault PDA can't sign, so such an arrangement would fail anyway, but only
+/// with a misleading missing-signature error.
+#[inline(always)]
+pub fn check_account_arrangement(owner: &AccountInfo, vault: &AccountInfo) -> ProgramResult {
+    if owner.key().eq(vault.key()) {
+        return Err(VaultError::InvalidAccountArrangement.into());
+    }
+
+    Ok(())
+}
+
+/// Checks that `authority` signed for and manages the opened `vault`: its
+/// admin if it has one, and its owner otherwise
+///
+/// Once a vault has an admin, its owner and everyone else fail with
+/// `NotVaultAdmin`, so the owner can't undo the admin's maintenance.
+pub fn check_vault_manager(authority: &AccountInfo, vault: &AccountInfo) -> ProgramResult {
+    check_account_arrangement(authority, vault)?;
+
+    let state = VaultState::load(vault)?;
+    if state.has_admin() {
+        if state.admin().ne(authority.key()) {
+            return Err(VaultError::NotVaultAdmin.into());
+        }
+
+        if !authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        return Ok(());
+    }
+
+    if !authority.is_signer() {
+        return Err(VaultError::MissingOwnerSignature.into());
+    }
+
+    if state.owner().ne(authority.key()) {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+
+    Ok(())
+}
+
+/// Returns whether `vault` was opened with OpenVault and is owned by the program
+#[inline(always)]
+pub fn is_opened(vault: &AccountInfo) -> bool {
+    vault.is_owned_by(&crate::ID)
+}
+
+/// Most vaults a single WithdrawMany or DepositMany can process
+///
+/// Larger batches would run out of compute partway through, so they're
+/// rejected before any lamports move.
+pub const MAX_BATCH_VAULTS: usize = 10;
+
+/// Fails with `BatchTooLarge` if a batch names more than `MAX_BATCH_VAULTS` vaults
+#[inline(always)]
+pub fn check_batch_size(vaults: usize) -> ProgramResult {
+    if vaults > MAX_BATCH_VAULTS {
+        return Err(VaultError::BatchTooLarge.into());
+    }
+
+    Ok(())
+}
+
+/// Fails with `DuplicateAccount` if any two of `vaults` share a key
+///
+/// The runtime hands a repeated account to the program twice, so a batch
+/// naming one vault more than once would process it once per occurrence.
+#[inline(always)]
+pub fn check_distinct_vaults<'a, I>(vaults: I) -> ProgramResult
+where
+    I: Iterator<Item = &'a AccountInfo> + Clone,
+{
+    for (index, vault) in vaults.clone().enumerate() {
+        if vaults
+            .clone()
+            .skip(index + 1)
+            .any(|other| other.key().eq(vault.key()))
+        {
+            return Err(VaultError::DuplicateAccount.into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the rent-exempt reserve `vault` has to keep to stay open
+#[inline(always)]
+pub fn rent_reserve(
+    vault: &AccountInfo,
+    rent_sysvar: Option<&AccountInfo>,
+) -> Result<u64, ProgramError> {
+    minimum_balance(rent_sysvar, vault.data_len())
+}
+
+/// Returns the lamports a withdraw can release from `vault`
+///
+/// With `close` a system-owned vault releases every lamport, and otherwise keeps
+/// its rent-exempt reserve. Opened vaults only release their tracked balance,
+/// so lamports sent to them outside a deposit stay in the vault; a close also
+/// returns the rent floor, unless those lamports would be left behind in a
+/// closed account, in which case the vault stays open. An opened vault whose
+/// tracked balance exceeds what it holds above the rent floor fails with
+/// `AccountingMismatch`.
+pub fn releasable(
+    vault: &AccountInfo,
+    rent_sysvar: Option<&AccountInfo>,
+    close: bool,
+) -> Result<u64, ProgramError> {
+    let reserve = rent_reserve(vault, rent_sysvar)?;
+    let tracked = match is_opened(vault) {
+        true => {
+            let balance = VaultState::load(vault)?.balance();
+            check_tracked_balance(vault.lamports(), reserve, balance)?;
+            Some(balance)
+        }
+        false => None,
+    };
+
+    Ok(compute_releasable(vault.lamports(), reserve, tracked, close))
+}
+
+/// Accounts every vault instruction starts with, checked once by [`validate_accounts`]
+pub struct VaultAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    /// The vault's canonical bump
+    pub bump: u8,
+}
+
+/// Fails with `NotEnoughAccountKeys` if fewer than `expected` accounts were passed
+///
+/// Logs both counts, since the runtime error alone doesn't say which accounts
+/// are missing.
+#[inline(always)]
+pub fn check_account_count(accounts: &[AccountInfo], expected: usize) -> ProgramResult {
+    if accounts.len() < expected {
+        log!(
+            "Expected at least {} accounts, got {}",
+            expected,
+            accounts.len()
+        );
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    Ok(())
+}
+
+/// Unpacks `[owner, vault, system_program, ..]` and runs the checks they share
+///
+/// The owner has to sign, the system program has to be the real one, and the
+/// vault has to be `owner`'s vault `id` with `salt` (see [`check_vault_address`]).
+/// Returns the checked accounts along with the ones after them.
+pub fn validate_accounts<'a>(
+    accounts: &'a [AccountInfo],
+    id: &[u8; 8],
+    salt: &[u8; 32],
+) -> Result<(VaultAccounts<'a>, &'a [AccountInfo]), ProgramError> {
+    check_account_count(accounts, VaultAccounts::LEN)?;
+    let [owner, vault, system_program, rest @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    Ok((VaultAccounts::check(owner, vault, system_program, id, salt)?, rest))
+}
+
+impl<'a> VaultAccounts<'a> {
+    /// Index of the owner in the accounts
+    pub const OWNER: usize = 0;
+    /// Index of the vault in the accounts
+    pub const VAULT: usize = 1;
+    /// Index of the system program in the accounts
+    pub const SYSTEM_PROGRAM: usize = 2;
+    /// Number of accounts every vault instruction starts with
+    pub const LEN: usize = 3;
+
+    /// Runs the [`validate_accounts`] checks on already destructured accounts,
+    /// for instructions that lay their accounts out differently
+    pub fn check(
+        owner: &'a AccountInfo,
+        vault: &'a AccountInfo,
+        system_program: &'a AccountInfo,
+        id: &[u8; 8],
+        salt: &[u8; 32],
+    ) -> Result<Self, ProgramError> {
+        check_account_arrangement(owner, vault)?;
+
+        if !owner.is_signer() {
+            return Err(VaultError::MissingOwnerSignature.into());
+        }
+
+        Self::check_unsigned(owner, vault, system_program, id, salt)
+    }
+
+    /// Runs the [`Self::check`] checks except the owner's signature, for an
+    /// escrowed vault whose escrow agent signs in its place
+    pub fn check_unsigned(
+        owner: &'a AccountInfo,
+        vault: &'a AccountInfo,
+        system_program: &'a AccountInfo,
+        id: &[u8; 8],
+        salt: &[u8; 32],
+    ) -> Result<Self, ProgramError> {
+        check_account_arrangement(owner, vault)?;
+
+        if system_program.key().ne(&pinocchio_system::ID) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let bump = check_vault_address(owner, vault, id, salt)?;
+
+        Ok(Self {
+            owner,
+            vault,
+            system_program,
+            bump,
+        })
+    }
+}
+
+/// Checks that `vault` is `owner`'s vault `id` with `salt`, and returns its canonical bump
+///
+/// A vault PDA is a system account unless it was opened, so anything else is a
+/// lookalike and fails with `InvalidVaultOwner`. Only the canonical bump is
+/// accepted, so an address derived from the same seeds with any other bump
+/// can't stand in for the vault; a mismatch fails with `InvalidPda`.
+pub fn check_vault_address(
+    owner: &AccountInfo,
+    vault: &AccountInfo,
+    id: &[u8; 8],
+    salt: &[u8; 32],
+) -> Result<u8, ProgramError> {
+    check_account_arrangement(owner, vault)?;
+    check_vault_owner(vault)?;
+
+    let (vault_key, bump) = find_vault_address(owner.key(), id, salt);
+    if vault.key().ne(&vault_key) {
+        log_vault_mismatch(bump, vault.key());
+        return Err(VaultError::InvalidPda.into());
+    }
+
+    Ok(bump)
+}
+
+/// Checks that `vault` is `owner`'s vault `id` with `salt` and can take a deposit
+///
+/// See [`check_vault_address`] and [`check_deposit_target`]. Returns the
+/// vault's canonical bump.
+pub fn check_deposit_vault(
+    owner: &AccountInfo,
+    vault: &AccountInfo,
+    id: &[u8; 8],
+    salt: &[u8; 32],
+    rent_sysvar: Option<&AccountInfo>,
+    retry: bool,
+) -> Result<u8, ProgramError> {
+    let bump = check_vault_address(owner, vault, id, salt)?;
+    check_deposit_target(vault, rent_sysvar, retry)?;
+
+    Ok(bump)
+}
+
+/// Checks that an already validated `vault` can take a deposit
+///
+/// Opened vaults take additive deposits, unless they defer deposits and are
+/// still in their creation slot; any other vault has to be empty, which for one
+/// withdrawn without closing means holding only its rent-exempt reserve.
+/// `retry` skips the emptiness check for an idempotent deposit that already landed.
+pub fn check_deposit_target(
+    vault: &AccountInfo,
+    rent_sysvar: Option<&AccountInfo>,
+    retry: bool,
+) -> ProgramResult {
+    if !retry && !is_opened(vault) && vault.lamports() > rent_reserve(vault, rent_sysvar)? {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Stops a vault from being opened and funded in the same slot when it opted in
+    if is_opened(vault) {
+        let state = VaultState::load(vault)?;
+        if state.defers_deposits() && clock()?.slot <= state.creation_slot() {
+            return Err(VaultError::TooSoonAfterCreation.into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Creates `owner`'s vault `id` with `salt` as an opened vault, funded by `payer`
+///
+/// The vault is allocated `VaultState::LEN` bytes owned by the program, signed
+/// for with its PDA seeds, and funded with exactly its rent floor, which is
+/// returned.
+#[allow(clippy::too_many_arguments)]
+pub fn create_vault(
+    payer: &AccountInfo,
+    owner: &AccountInfo,
+    vault: &AccountInfo,
+    id: &[u8; 8],
+    salt: &[u8; 32],
+    bump: u8,
+    flags: u8,
+    rent_sysvar: Option<&AccountInfo>,
+) -> Result<u64, ProgramError> {
+    let bumps = [bump];
+    let seeds = vault_signer_seeds(owner.key(), id, salt, &bumps);
+    let signers = [Signer::from(&seeds)];
+
+    let rent_floor = minimum_balance(rent_sysvar, VaultState::LEN)?;
+
+    CreateAccount {
+        from: payer,
+        to: vault,
+        lamports: rent_floor,
+        space: VaultState::LEN as u64,
+        owner: &crate::ID,
+    }
+    .invoke_signed(&signers)?;
+
+    let clock = clock()?;
+    VaultState::initialize(vault)?.set_inner(
+        owner.key(),
+        id,
+        bump,
+        clock.slot,
+        clock.unix_timestamp,
+        flags,
+    );
+
+    Ok(rent_floor)
+}
+
+/// Fails with `VestingLocked` if `vault` vests its deposits
+///
+/// Every withdrawal path except WithdrawVested calls this, so a vesting
+/// schedule can't be bypassed.
+#[inline(always)]
+pub fn check_not_vesting(vault: &AccountInfo) -> ProgramResult {
+    if is_opened(vault) && VaultState::load(vault)?.is_vesting() {
+        return Err(VaultError::VestingLocked.into());
+    }
+
+    Ok(())
+}
+
+/// Fails with `EscrowedVault` if `vault` has an escrow agent
+///
+/// Every withdrawal path the owner authorizes without going through
+/// [`check_escrow_agent`] calls this, so an escrow can't be bypassed.
+#[inline(always)]
+pub fn check_not_escrowed(vault: &AccountInfo) -> ProgramResult {
+    if is_opened(vault) && VaultState::load(vault)?.is_escrowed() {
+        return Err(VaultError::EscrowedVault.into());
+    }
+
+    Ok(())
+}
+
+/// Checks the escrow agent an escrowed `vault` expects as the first of
+/// `accounts`, and returns whether the vault is escrowed along with the
+/// accounts after the agent
+///
+/// A missing or different agent fails with `EscrowedVault`, and one that
+/// didn't sign with `MissingRequiredSignature`. Vaults without an agent take
+/// no account.
+pub fn check_escrow_agent<'a>(
+    vault: &AccountInfo,
+    accounts: &'a [AccountInfo],
+) -> Result<(bool, &'a [AccountInfo]), ProgramError> {
+    if !is_opened(vault) || !VaultState::load(vault)?.is_escrowed() {
+        return Ok((false, accounts));
+    }
+
+    let [escrow_agent, rest @ ..] = accounts else {
+        return Err(VaultError::EscrowedVault.into());
+    };
+
+    if VaultState::load(vault)?.escrow_agent().ne(escrow_agent.key()) {
+        return Err(VaultError::EscrowedVault.into());
+    }
+
+    if !escrow_agent.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    Ok((true, rest))
+}
+
+/// Fails with `MultisigThresholdNotMet` if `vault` is a multisig vault
+///
+/// Every withdrawal path the owner authorizes without going through
+/// [`check_multisig_signers`] calls this, so the owner alone can't move a
+/// multisig vault's lamports.
+#[inline(always)]
+pub fn check_not_multisig(vault: &AccountInfo) -> ProgramResult {
+    if is_opened(vault) && VaultState::load(vault)?.is_multisig() {
+        return Err(VaultError::MultisigThresholdNotMet.into());
+    }
+
+    Ok(())
+}
+
+/// Checks the signer set a multisig `vault` expects at the front of
+/// `accounts`, and returns whether the vault is a multisig one along with the
+/// accounts after the signers
+///
+/// Every signer is passed, in the order the vault lists them, and at least
+/// the vault's threshold of them has to sign. Missing or different signers and
+/// too few signatures fail with `MultisigThresholdNotMet`. Other vaults take no
+/// account.
+pub fn check_multisig_signers<'a>(
+    vault: &AccountInfo,
+    accounts: &'a [AccountInfo],
+) -> Result<(bool, &'a [AccountInfo]), ProgramError> {
+    if !is_opened(vault) {
+        return Ok((false, accounts));
+    }
+
+    let state = VaultState::load(vault)?;
+    if !state.is_multisig() {
+        return Ok((false, accounts));
+    }
+
+    let signers = state.multisig_signers();
+    if accounts.len() < signers.len() {
+        return Err(VaultError::MultisigThresholdNotMet.into());
+    }
+
+    let (passed, rest) = accounts.split_at(signers.len());
+    let mut signatures = 0u8;
+    for (account, signer) in passed.iter().zip(signers) {
+        if account.key().ne(signer) {
+            return Err(VaultError::MultisigThresholdNotMet.into());
+        }
+
+        signatures += account.is_signer() as u8;
+    }
+
+    if signatures < state.multisig_threshold() {
+        return Err(VaultError::MultisigThresholdNotMet.into());
+    }
+
+    Ok((true, rest))
+}
+
+/// Records a deposit of `lamports`, already moved into the vault, in an opened
+/// vault's state
+///
+/// Every path that moves lamports into a vault records them here, so a vault
+/// with its deposits locked fails them all with `DepositsLocked`, a frozen one
+/// with `VaultFrozen`, and one whose source whitelist doesn't list `authority`,
+/// the account funding the deposit, with `SourceNotWhitelisted`. System-owned
+/// vaults have no state, so they can't vest or be locked and record nothing.
+/// A vault drained below its rent floor from outside the program restores the
+/// floor out of the deposit first, and only the rest is tracked as deposited,
+/// so the balance never counts lamports the vault needs to stay rent-exempt;
+/// the floor comes from `rent_sysvar` when one is passed.
+/// A deposit `authority` signs as the vault's owner counts the owner as active.
+#[inline(always)]
+pub fn record_vault_deposit(
+    vault: &AccountInfo,
+    authority: &AccountInfo,
+    lamports: u64,
+    vesting_duration: u64,
+    rent_sysvar: Option<&AccountInfo>,
+) -> ProgramResult {
+    if !is_opened(vault) {
+        return Ok(());
+    }
+
+    let mut state = VaultState::load_mut(vault)?;
+    if state.is_frozen() {
+        return Err(VaultError::VaultFrozen.into());
+    }
+
+    if state.deposits_locked() {
+        return Err(VaultError::DepositsLocked.into());
+    }
+
+    if !state.is_source_allowed(authority.key()) {
+        return Err(VaultError::SourceNotWhitelisted.into());
+    }
+
+    let withdrawable = vault
+        .lamports()
+        .saturating_sub(minimum_balance(rent_sysvar, vault.data_len())?);
+    let credited = lamports.min(withdrawable.saturating_sub(state.balance()));
+
+    // The balance earns interest at its old size up to the deposit
+    let now = clock()?.unix_timestamp;
+    state.accrue(now);
+    state.record_deposit(credited, now, vesting_duration);
+    state.cap_balance(withdrawable);
+    refresh_owner_activity(&mut state, authority, now);
+
+    Ok(())
+}
+
+/// Counts the owner of an opened vault as active now, which pushes back when
+/// its beneficiary can claim it
+///
+/// `record_vault_deposit` and `debit_vault` already do this when the owner
+/// signs; withdrawals the owner authorizes through a multisig or escrow agent
+/// call it directly. System-owned vaults have no state, so they record nothing.
+#[inline(always)]
+pub fn record_owner_activity(vault: &AccountInfo) -> ProgramResult {
+    if !is_opened(vault) {
+        return Ok(());
+    }
+
+    VaultState::load_mut(vault)?.record_activity(clock()?.unix_timestamp);
+
+    Ok(())
+}
+
+/// Counts the owner as active at `now` when `authority` is the vault's owner
+/// and signed
+#[inline(always)]
+fn refresh_owner_activity(state: &mut VaultState, authority: &AccountInfo, now: i64) {
+    if authority.is_signer() && authority.key().eq(state.owner()) {
+        state.record_activity(now);
+    }
+}
+
+/// Transfers `lamports` from `from` to `to` for a deposit, with a System Program CPI
+///
+/// A CPI that fails aborts the whole transaction before control returns here,
+/// so `from`'s balance is checked up front. Any failure is logged along with
+/// what caused it and surfaces as `TransferFailed`, so clients can tell a
+/// payer short of SOL apart from the program's own checks.
+pub fn transfer_into(from: &AccountInfo, to: &AccountInfo, lamports: u64) -> ProgramResult {
+    if from.lamports() < lamports {
+        log!(
+            "Transfer failed: {} lamports needed, {} available",
+            lamports,
+            from.lamports()
+        );
+        return Err(VaultError::TransferFailed.into());
+    }
+
+    Transfer {
+        from,
+        to,
+        lamports,
+    }
+    .invoke()
+    .map_err(|err| {
+        log!("Transfer failed with program error {}", u64::from(err));
+        VaultError::TransferFailed.into()
+    })
+}
+
+/// Fails with `VaultFrozen`, `VaultLocked` or `VaultTooYoung` while an opened
+/// vault's state keeps anything from leaving it
+#[inline(always)]
+pub fn check_release_allowed(state: &VaultState) -> ProgramResult {
+    if state.is_frozen() {
+        return Err(VaultError::VaultFrozen.into());
+    }
+
+    if state.unlock_ts().ne(&0) && state.is_locked(clock()?.unix_timestamp) {
+        return Err(VaultError::VaultLocked.into());
+    }
+
+    // Both locks apply, so whichever unlocks later holds the vault
+    if state.unlock_slot().ne(&0) && state.is_slot_locked(clock()?.slot) {
+        return Err(VaultError::VaultLocked.into());
+    }
+
+    // Funds can't flow in and back out straight away, e.g. within a flash loan
+    if state.min_age_secs().ne(&0) && state.is_too_young(clock()?.unix_timestamp) {
+        return Err(VaultError::VaultTooYoung.into());
+    }
+
+    Ok(())
+}
+
+/// Moves `lamports` out of `vault` into `to`
+///
+/// System-owned vaults sign a System Program transfer with their PDA seeds.
+/// Opened vaults go through `debit_vault` once nothing keeps the lamports from
+/// leaving: nothing leaves a vault that's frozen, still time-locked or younger
+/// than its minimum age.
+pub fn transfer_from_vault(
+    vault: &AccountInfo,
+    authority: &AccountInfo,
+    to: &AccountInfo,
+    lamports: u64,
+    signers: &[Signer],
+) -> ProgramResult {
+    release_from_vault(vault, authority, to, lamports, signers, true)
+}
+
+/// Like [`transfer_from_vault`], but for lamports split off a withdrawal that
+/// another transfer counts, such as its fee or a relayer's tip
+pub fn transfer_share_from_vault(
+    vault: &AccountInfo,
+    authority: &AccountInfo,
+    to: &AccountInfo,
+    lamports: u64,
+    signers: &[Signer],
+) -> ProgramResult {
+    release_from_vault(vault, authority, to, lamports, signers, false)
+}
+
+#[inline(always)]
+fn release_from_vault(
+    vault: &AccountInfo,
+    authority: &AccountInfo,
+    to: &AccountInfo,
+    lamports: u64,
+    signers: &[Signer],
+    counted: bool,
+) -> ProgramResult {
+    if !is_opened(vault) {
+        return Transfer {
+            from: vault,
+            to,
+            lamports,
+        }
+        .invoke_signed(signers);
+    }
+
+    check_release_allowed(&*VaultState::load(vault)?)?;
+
+    debit(vault, authority, to, lamports, counted)
+}
+
+/// Moves `lamports` out of the opened `vault` into `to`
+///
+/// The program owns the vault, so it debits it directly, and closes it once
+/// the last lamport is gone. Its state is zeroed before the close, so an
+/// account recreated at the same address never sees stale data; otherwise the
+/// lamports are taken off its tracked balance. Either way the tracked lamports
+/// count against the vault's daily and rolling withdrawal limits, and fail with
+/// `ReceiptsOutstanding` if receipt tokens still stand for them. Unlike
+/// `transfer_from_vault` it doesn't check the vault's freeze, time lock or
+/// minimum age, so it's only for moves into a vault that takes those over.
+/// A debit `authority` signs as the vault's owner counts the owner as active,
+/// and every debit counts as one of the vault's withdrawals.
+pub fn debit_vault(
+    vault: &AccountInfo,
+    authority: &AccountInfo,
+    to: &AccountInfo,
+    lamports: u64,
+) -> ProgramResult {
+    debit(vault, authority, to, lamports, true)
+}
+
+#[inline(always)]
+fn debit(
+    vault: &AccountInfo,
+    authority: &AccountInfo,
+    to: &AccountInfo,
+    lamports: u64,
+    counted: bool,
+) -> ProgramResult {
+    {
+        let mut state = VaultState::load_mut(vault)?;
+
+        // Only Withdraw burns receipts, and it does so before getting here
+        if state.receipts_to_burn(lamports).ne(&0) {
+            return Err(VaultError::ReceiptsOutstanding.into());
+        }
+
+        // The balance earns interest at its old size up to the withdrawal
+        state.accrue(clock()?.unix_timestamp);
+        refresh_owner_activity(&mut state, authority, clock()?.unix_timestamp);
+        if counted {
+            state.count_withdrawal();
+        }
+
+        // Only the tracked balance counts, so a close's rent floor is never limited
+        if state.daily_limit().ne(&0) {
+            let tracked = lamports.min(state.balance());
+            state.record_daily_withdrawal(tracked, clock()?.unix_timestamp)?;
+        }
+
+        if state.rolling_limit().ne(&0) {
+            let tracked = lamports.min(state.balance());
+            state.record_rolling_withdrawal(tracked, clock()?.unix_timestamp)?;
+        }
+    }
+
+    let remaining = vault
+        .lamports()
+        .checked_sub(lamports)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    let credited = to
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    *vault.try_borrow_mut_lamports()? = remaining;
+    *to.try_borrow_mut_lamports()? = credited;
+
+    if remaining.eq(&0) {
+        vault.try_borrow_mut_data()?.fill(0);
+        vault.close()?;
+    } else {
+        VaultState::load_mut(vault)?.record_withdrawal(lamports);
+    }
+
+    Ok(())
+}
+
+/// Pays up to `lamports` of an opened vault's gas reserve to `to`, returning how
+/// many it paid
+///
+/// The reserve sits outside the tracked balance, so it's debited directly and
+/// counts against none of the vault's withdrawal limits.
+pub fn pay_from_gas_reserve(
+    vault: &AccountInfo,
+    to: &AccountInfo,
+    lamports: u64,
+) -> Result<u64, ProgramError> {
+    let mut state = VaultState::load_mut(vault)?;
+    let paid = lamports.min(state.gas_reserve());
+    if paid.eq(&0) {
+        return Ok(0);
+    }
+
+    let remaining = vault
+        .lamports()
+        .checked_sub(paid)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    let credited = to
+        .lamports()
+        .checked_add(paid)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    *vault.try_borrow_mut_lamports()? = remaining;
+    *to.try_borrow_mut_lamports()? = credited;
+    state.spend_gas_reserve(paid);
+
+    Ok(paid)
+}
+
+/// Lamports held by the accounts a handler moves lamports between, taken before
+/// its transfers so [`Self::check_conserved`] can check none were lost or made
+/// up along the way. Compiled out unless the `debug-asserts` feature is enabled.
+pub struct LamportSnapshot {
+    #[cfg(feature = "debug-asserts")]
+    total: u128,
+}
+
+impl LamportSnapshot {
+    #[inline(always)]
+    pub fn take<'a>(_accounts: impl IntoIterator<Item = &'a AccountInfo>) -> Self {
+        Self {
+            #[cfg(feature = "debug-asserts")]
+            total: Self::total(_accounts),
+        }
+    }
+
+    /// Fails with `InvariantViolated` unless `accounts`, the same ones the
+    /// snapshot was taken of, still hold the same lamports in total
+    ///
+    /// Returns an error rather than panicking, so a broken invariant fails the
+    /// instruction without aborting the program and burning its whole budget.
+    #[inline(always)]
+    pub fn check_conserved<'a>(
+        &self,
+        _accounts: impl IntoIterator<Item = &'a AccountInfo>,
+    ) -> ProgramResult {
+        #[cfg(feature = "debug-asserts")]
+        if self.total.ne(&Self::total(_accounts)) {
+            log!("Lamports not conserved");
+            return Err(VaultError::InvariantViolated.into());
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "debug-asserts")]
+    fn total<'a>(accounts: impl IntoIterator<Item = &'a AccountInfo>) -> u128 {
+        accounts
+            .into_iter()
+            .fold(0, |total, account| total + u128::from(account.lamports()))
+    }
+}
@@ -0,0 +1,103 @@
+//! Sends Deposit data with a correct and a corrupted checksum byte to programs
+//! built with and without the `verify-checksum` feature, which decides whether
+//! the byte is checked:
+//! ```bash
+//! cargo build-sbf && cargo test --test checksum_tests
+//! cargo build-sbf --features verify-checksum
+//! cargo test --features verify-checksum --test checksum_tests
+//! ```
+
+use blueshift_vault::client;
+use blueshift_vault::testing::load_vault_program;
+use litesvm::LiteSVM;
+use solana_sdk::{
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+// Program ID from lib.rs
+const PROGRAM_ID: Pubkey = Pubkey::new_from_array(blueshift_vault::ID);
+
+/// Helper function to find vault PDA
+fn find_vault_pda(owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vault", owner.as_ref()], &PROGRAM_ID)
+}
+
+/// Deposits `amount` into `owner`'s default vault with a checksum byte
+/// appended, flipping a bit of the amount afterwards if `corrupt`
+fn deposit_with_checksum(
+    svm: &mut LiteSVM,
+    owner: &Keypair,
+    amount: u64,
+    corrupt: bool,
+) -> Result<(), TransactionError> {
+    let mut instruction =
+        client::with_checksum(client::deposit_instruction(&owner.pubkey(), amount));
+    if corrupt {
+        instruction.data[1] ^= 0x01;
+    }
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&owner.pubkey()),
+        &[owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx).map(|_| ()).map_err(|f| f.err)
+}
+
+/// Helper function to set up LiteSVM with the program and a funded owner
+fn setup() -> (LiteSVM, Keypair) {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    (svm, owner)
+}
+
+#[test]
+fn test_deposit_with_correct_checksum_succeeds() {
+    let (mut svm, owner) = setup();
+
+    deposit_with_checksum(&mut svm, &owner, LAMPORTS_PER_SOL, false)
+        .expect("Deposit with a correct checksum should succeed");
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    assert_eq!(svm.get_balance(&vault_pda), Some(LAMPORTS_PER_SOL));
+}
+
+#[test]
+#[cfg(not(feature = "verify-checksum"))]
+fn test_corrupted_checksum_is_ignored_by_default() {
+    let (mut svm, owner) = setup();
+
+    // The amount itself is corrupted, and nothing notices
+    deposit_with_checksum(&mut svm, &owner, LAMPORTS_PER_SOL, true)
+        .expect("Deposit with a corrupted checksum should succeed");
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    assert_eq!(svm.get_balance(&vault_pda), Some(LAMPORTS_PER_SOL ^ 0x01));
+}
+
+#[test]
+#[cfg(feature = "verify-checksum")]
+fn test_corrupted_checksum_is_rejected_under_verify_checksum() {
+    use blueshift_vault::error::VaultError;
+    use solana_sdk::instruction::InstructionError;
+
+    let (mut svm, owner) = setup();
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    assert_eq!(
+        deposit_with_checksum(&mut svm, &owner, LAMPORTS_PER_SOL, true),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::ChecksumMismatch as u32)
+        ))
+    );
+    assert!(svm.get_account(&vault_pda).is_none());
+}
@@ -0,0 +1,304 @@
+use blueshift_vault::client::{
+    self, lamports_to_sol, sol, sol_to_lamports, VaultEvent, LAMPORTS_PER_SOL,
+};
+
+#[test]
+fn test_sol_to_lamports_converts_whole_and_fractional_sol() {
+    assert_eq!(sol_to_lamports(0.0), 0);
+    assert_eq!(sol_to_lamports(1.0), LAMPORTS_PER_SOL);
+    assert_eq!(sol_to_lamports(2.5), 2_500_000_000);
+    assert_eq!(sol_to_lamports(0.000_000_001), 1);
+    assert_eq!(sol(2.5), sol_to_lamports(2.5));
+}
+
+#[test]
+fn test_sol_to_lamports_rounds_to_nearest_lamport() {
+    // `0.1 * 1e9` and `0.3 * 1e9` aren't whole numbers in floating point
+    assert_eq!(sol_to_lamports(0.1), 100_000_000);
+    assert_eq!(sol_to_lamports(0.3), 300_000_000);
+    assert_eq!(sol_to_lamports(0.000_000_000_4), 0);
+    assert_eq!(sol_to_lamports(0.000_000_000_5), 1);
+    assert_eq!(sol_to_lamports(0.000_000_001_6), 2);
+}
+
+#[test]
+fn test_sol_to_lamports_accepts_amounts_up_to_u64() {
+    // The largest float below 2^64 lamports
+    let max_sol = (u64::MAX - 2048) as f64 / LAMPORTS_PER_SOL as f64;
+    assert!(sol_to_lamports(max_sol) > u64::MAX - 4096);
+}
+
+#[test]
+#[should_panic(expected = "non-negative")]
+fn test_sol_to_lamports_rejects_nan() {
+    sol_to_lamports(f64::NAN);
+}
+
+#[test]
+#[should_panic(expected = "non-negative")]
+fn test_sol_to_lamports_rejects_negative() {
+    sol_to_lamports(-0.5);
+}
+
+#[test]
+#[should_panic(expected = "overflows")]
+fn test_sol_to_lamports_rejects_overflow() {
+    sol_to_lamports(u64::MAX as f64 / LAMPORTS_PER_SOL as f64);
+}
+
+#[test]
+#[should_panic(expected = "overflows")]
+fn test_sol_to_lamports_rejects_infinity() {
+    sol_to_lamports(f64::INFINITY);
+}
+
+#[test]
+fn test_lamports_to_sol_boundaries() {
+    assert_eq!(lamports_to_sol(0), 0.0);
+    assert_eq!(lamports_to_sol(1), 0.000_000_001);
+    assert_eq!(lamports_to_sol(LAMPORTS_PER_SOL), 1.0);
+    assert_eq!(lamports_to_sol(2_500_000_000), 2.5);
+    assert!(lamports_to_sol(u64::MAX) > 18_446_744_073.0);
+}
+
+#[test]
+fn test_lamports_round_trip_through_sol() {
+    for lamports in [
+        0,
+        1,
+        999_999_999,
+        LAMPORTS_PER_SOL,
+        123_456_789_012,
+        (1 << 53) - 1,
+    ] {
+        assert_eq!(sol_to_lamports(lamports_to_sol(lamports)), lamports);
+    }
+}
+
+#[test]
+fn test_sol_amounts_feed_instruction_builders() {
+    let owner = solana_pubkey::Pubkey::new_unique();
+    let instruction = client::deposit_instruction(&owner, sol(2.5));
+    assert_eq!(instruction.data[1..9], 2_500_000_000u64.to_le_bytes());
+}
+
+#[test]
+fn test_max_deposit_amount_keeps_fees_and_rent() {
+    assert_eq!(
+        client::max_deposit_amount(sol(1.0), 5_000, 890_880),
+        999_104_120
+    );
+    assert_eq!(client::max_deposit_amount(895_880, 5_000, 890_880), 0);
+    assert_eq!(client::max_deposit_amount(1_000, 5_000, 890_880), 0);
+    assert_eq!(client::max_deposit_amount(u64::MAX, u64::MAX, u64::MAX), 0);
+    assert_eq!(client::max_deposit_amount(u64::MAX, 0, 1), u64::MAX - 1);
+}
+
+#[test]
+fn test_parse_events_decodes_only_vault_events() {
+    let owner = solana_pubkey::Pubkey::new_unique();
+    let hex: String = owner.to_bytes().iter().map(|byte| format!("{byte:02x}")).collect();
+
+    let logs: Vec<String> = [
+        "Program 22222222222222222222222222222222222222222222 invoke [1]".to_string(),
+        format!("Program log: vault:deposit owner={hex} amount=2500000000"),
+        "Program log: vault:memo invoice-42".to_string(),
+        "Program log: unrelated amount=5".to_string(),
+        format!("vault:withdraw owner={hex} amount=1"),
+        // Malformed vault lines are skipped rather than misread
+        format!("Program log: vault:deposit owner={} amount=1", &hex[..62]),
+        format!("Program log: vault:deposit owner={hex} amount=-1"),
+        format!("Program log: vault:withdraw owner={hex} amount=1 extra"),
+        "Program 22222222222222222222222222222222222222222222 success".to_string(),
+    ]
+    .into();
+
+    assert_eq!(
+        client::parse_events(&logs),
+        vec![
+            VaultEvent::Deposit {
+                owner,
+                amount: 2_500_000_000
+            },
+            VaultEvent::Withdraw { owner, amount: 1 },
+        ]
+    );
+    assert!(client::parse_events(&[]).is_empty());
+}
+
+#[test]
+fn test_parse_events_decodes_json_events() {
+    let owner = solana_pubkey::Pubkey::new_unique();
+    let hex: String = owner.to_bytes().iter().map(|byte| format!("{byte:02x}")).collect();
+
+    let logs: Vec<String> = [
+        format!(r#"Program log: {{"event":"deposit","owner":"{hex}","amount":2500000000}}"#),
+        format!(r#"{{"event":"withdraw","owner":"{hex}","amount":1}}"#),
+        // Malformed or unknown JSON events are skipped rather than misread
+        format!(r#"Program log: {{"event":"memo","owner":"{hex}","amount":1}}"#),
+        format!(r#"Program log: {{"event":"deposit","owner":"{hex}","amount":1"#),
+        format!(r#"Program log: {{"event":"deposit","owner":"{hex}","amount":-1}}"#),
+        format!(r#"Program log: {{"owner":"{hex}","event":"deposit","amount":1}}"#),
+        r#"Program log: {"event":"deposit","owner":"00","amount":1}"#.to_string(),
+    ]
+    .into();
+
+    assert_eq!(
+        client::parse_events(&logs),
+        vec![
+            VaultEvent::Deposit {
+                owner,
+                amount: 2_500_000_000
+            },
+            VaultEvent::Withdraw { owner, amount: 1 },
+        ]
+    );
+}
+
+#[test]
+fn test_derive_vaults_matches_single_derivation() {
+    let owner = solana_pubkey::Pubkey::new_unique();
+    let ids: Vec<u64> = (0..100).collect();
+
+    let vaults = client::derive_vaults(&owner, &ids);
+    assert_eq!(vaults.len(), ids.len());
+    for id in [0, 1, 42, 99] {
+        assert_eq!(vaults[id as usize], client::find_vault_address(&owner, id));
+    }
+
+    // The default vault keeps the original `["vault", owner]` address
+    let (default_vault, _bump) = solana_pubkey::Pubkey::find_program_address(
+        &[b"vault", owner.as_ref()],
+        &client::PROGRAM_ID,
+    );
+    assert_eq!(vaults[0].0, default_vault);
+    assert!(client::derive_vaults(&owner, &[]).is_empty());
+}
+
+#[test]
+fn test_vault_signer_seeds_sign_for_the_derived_vault() {
+    use blueshift_vault::pda::{vault_signer_seeds, DEFAULT_SALT};
+
+    let owner = solana_pubkey::Pubkey::new_unique();
+    let owner_bytes = owner.to_bytes();
+    let salt = [7u8; 32];
+    let create = |id: &[u8; 8], salt: &[u8; 32], bump: u8| {
+        let bump = [bump];
+        let seeds = vault_signer_seeds(&owner_bytes, id, salt, &bump);
+        let seeds: Vec<&[u8]> = seeds.iter().map(|seed| &**seed).collect();
+        solana_pubkey::Pubkey::create_program_address(&seeds, &client::PROGRAM_ID).ok()
+    };
+
+    for (id, salt) in [(0, DEFAULT_SALT), (3, DEFAULT_SALT), (3, salt)] {
+        let (vault, bump) = client::find_salted_vault_address(&owner, id, &salt);
+        assert_eq!(create(&id.to_le_bytes(), &salt, bump), Some(vault));
+
+        // A wrong bump, id or salt signs for some other address, or none at all
+        assert_ne!(create(&id.to_le_bytes(), &salt, bump.wrapping_sub(1)), Some(vault));
+        assert_ne!(create(&(id + 1).to_le_bytes(), &salt, bump), Some(vault));
+        assert_ne!(create(&id.to_le_bytes(), &[1u8; 32], bump), Some(vault));
+    }
+}
+
+#[test]
+fn test_application_salt_bounds_seed_length() {
+    use blueshift_vault::pda::{application_salt, DEFAULT_SALT, MAX_APPLICATION_SEED_LEN};
+    use pinocchio::program_error::ProgramError;
+
+    let mut salt = DEFAULT_SALT;
+    salt[..10].copy_from_slice(b"order-1234");
+    assert_eq!(application_salt(b"order-1234"), Ok(salt));
+    assert_eq!(application_salt(&[9u8; 32]), Ok([9u8; 32]));
+    assert_eq!(application_salt(&[]), Ok(DEFAULT_SALT));
+    assert_eq!(
+        application_salt(&[9u8; MAX_APPLICATION_SEED_LEN + 1]),
+        Err(ProgramError::InvalidInstructionData)
+    );
+
+    let owner = solana_pubkey::Pubkey::new_unique();
+    assert_eq!(
+        client::find_seeded_vault_address(&owner, 3, b"order-1234"),
+        Some(client::find_salted_vault_address(&owner, 3, &salt))
+    );
+    assert_ne!(
+        client::find_seeded_vault_address(&owner, 3, b"order-1235"),
+        client::find_seeded_vault_address(&owner, 3, b"order-1234")
+    );
+    assert_eq!(
+        client::find_seeded_vault_address(&owner, 3, &[9u8; 33]),
+        None
+    );
+}
+
+#[test]
+fn test_withdraw_idempotent_instruction_sets_only_the_idempotent_flag() {
+    use blueshift_vault::{pda::DEFAULT_SALT, WithdrawInstructionData};
+
+    let owner = solana_pubkey::Pubkey::new_unique();
+    let instruction = client::withdraw_idempotent_instruction(&owner);
+    assert_eq!(
+        instruction.accounts,
+        client::withdraw_instruction(&owner).accounts
+    );
+
+    let data = WithdrawInstructionData::try_from(&instruction.data[1..]).unwrap();
+    assert!(data.idempotent && data.close);
+    assert!(!data.receipt);
+    assert_eq!((data.id, data.salt), ([0; 8], DEFAULT_SALT));
+    assert_eq!((data.min_out, data.keep_alive), (0, 0));
+}
+
+#[test]
+fn test_withdraw_data_rejects_a_set_hook_byte() {
+    use blueshift_vault::WithdrawInstructionData;
+
+    let owner = solana_pubkey::Pubkey::new_unique();
+    let mut data = client::withdraw_idempotent_instruction(&owner).data;
+    // `[discriminator][id][close][salt][min_out][keep_alive][receipt][hook]`
+    data[59] = 1;
+    assert!(WithdrawInstructionData::try_from(&data[1..]).is_err());
+}
+
+#[test]
+fn test_with_checksum_appends_the_xor_of_the_data() {
+    let owner = solana_pubkey::Pubkey::new_unique();
+    let deposit = client::deposit_instruction(&owner, 0x0102);
+    let checked = client::with_checksum(deposit.clone());
+
+    assert_eq!(checked.accounts, deposit.accounts);
+    assert_eq!(checked.data[..deposit.data.len()], deposit.data[..]);
+    // Discriminator `0` and the amount's two non-zero bytes
+    assert_eq!(checked.data.last(), Some(&(0x02 ^ 0x01)));
+    assert_eq!(checked.data.len(), deposit.data.len() + 1);
+}
+
+#[test]
+fn test_decode_state_export_splits_state_and_metadata() {
+    use blueshift_vault::{metadata::VaultMetadata, state::VaultState, EXPORT_FORMAT};
+
+    let mut state = vec![0u8; VaultState::LEN];
+    state[0] = VaultState::VERSION;
+    let mut export = vec![EXPORT_FORMAT];
+    export.extend_from_slice(&(VaultState::LEN as u16).to_le_bytes());
+    export.extend_from_slice(&state);
+    export.extend_from_slice(&0u16.to_le_bytes());
+
+    let (decoded, metadata) = client::decode_state_export(&export).unwrap();
+    assert_eq!(decoded.version(), VaultState::VERSION);
+    assert!(metadata.is_none());
+
+    // Trailing bytes, a truncated section or an unknown format aren't an export
+    assert!(client::decode_state_export(&[&export[..], &[0]].concat()).is_none());
+    assert!(client::decode_state_export(&export[..export.len() - 1]).is_none());
+    export[0] = EXPORT_FORMAT + 1;
+    assert!(client::decode_state_export(&export).is_none());
+
+    let mut metadata = vec![0u8; VaultMetadata::LEN];
+    metadata[0] = VaultMetadata::VERSION;
+    export[0] = EXPORT_FORMAT;
+    let len = export.len();
+    export[len - 2..].copy_from_slice(&(VaultMetadata::LEN as u16).to_le_bytes());
+    export.extend_from_slice(&metadata);
+    let (_, metadata) = client::decode_state_export(&export).unwrap();
+    assert_eq!(metadata.map(|metadata| metadata.version()), Some(1));
+}
@@ -0,0 +1,216 @@
+use blueshift_vault::client;
+use blueshift_vault::testing::load_vault_program;
+use litesvm::LiteSVM;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+// System Program ID
+const SYSTEM_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("11111111111111111111111111111111");
+
+// Program ID from lib.rs
+const PROGRAM_ID: Pubkey = Pubkey::new_from_array(blueshift_vault::ID);
+
+// Compute unit budgets, shared with the limits `client` requests. These include
+// the PDA bump search, so they depend on the owner key: the tests use a fixed
+// owner to keep the measurement stable. Raise them deliberately when a change
+// is expected to cost more.
+const DEPOSIT_CU_LIMIT: u64 = client::DEPOSIT_COMPUTE_UNITS as u64;
+const WITHDRAW_CU_LIMIT: u64 = client::WITHDRAW_COMPUTE_UNITS as u64;
+
+// Owners the bump-derivation benchmark deposits for, derived from fixed seeds
+// so every run measures the same bumps
+const BENCH_OWNERS: u8 = 32;
+
+/// Helper function to set up LiteSVM with the program and a funded, fixed owner
+fn setup() -> (LiteSVM, Keypair) {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new_from_array([7u8; 32]);
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    (svm, owner)
+}
+
+/// Helper function to find the global stats PDA
+fn find_stats_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"stats"], &PROGRAM_ID)
+}
+
+/// Helper function to find vault PDA
+fn find_vault_pda(owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vault", owner.as_ref()], &PROGRAM_ID)
+}
+
+/// Helper function to find the config PDA
+fn find_config_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"config"], &PROGRAM_ID)
+}
+
+/// Helper function to create deposit instruction
+fn create_deposit_instruction(owner: Pubkey, vault: Pubkey, amount: u64) -> Instruction {
+    let mut instruction_data = vec![0u8]; // Discriminator for Deposit
+    instruction_data.extend_from_slice(&amount.to_le_bytes());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(owner, true),                       // owner (signer, writable)
+            AccountMeta::new(vault, false),                      // vault (writable)
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system program
+            AccountMeta::new(find_stats_pda().0, false),         // stats (writable)
+            AccountMeta::new_readonly(find_config_pda().0, false), // config
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create withdraw instruction
+fn create_withdraw_instruction(owner: Pubkey, vault: Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(owner, true),                       // owner (signer, writable)
+            AccountMeta::new(vault, false),                      // vault (writable)
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system program
+            AccountMeta::new(find_stats_pda().0, false),         // stats (writable)
+            AccountMeta::new_readonly(find_config_pda().0, false), // config
+        ],
+        data: vec![1u8], // Discriminator for Withdraw
+    }
+}
+
+/// Helper function to send a single instruction and return the compute units it consumed
+fn measure(svm: &mut LiteSVM, owner: &Keypair, instruction: Instruction) -> u64 {
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&owner.pubkey()),
+        &[owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(tx)
+        .expect("Transaction should succeed")
+        .compute_units_consumed
+}
+
+#[test]
+fn test_deposit_compute_units() {
+    let (mut svm, owner) = setup();
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL);
+    let consumed = measure(&mut svm, &owner, deposit_ix);
+
+    println!("deposit: {consumed} CU (limit {DEPOSIT_CU_LIMIT})");
+    assert!(
+        consumed < DEPOSIT_CU_LIMIT,
+        "Deposit used {consumed} CU, over the {DEPOSIT_CU_LIMIT} CU budget"
+    );
+}
+
+#[test]
+fn test_withdraw_compute_units() {
+    let (mut svm, owner) = setup();
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL);
+    measure(&mut svm, &owner, deposit_ix);
+
+    let withdraw_ix = create_withdraw_instruction(owner.pubkey(), vault_pda);
+    let consumed = measure(&mut svm, &owner, withdraw_ix);
+
+    println!("withdraw: {consumed} CU (limit {WITHDRAW_CU_LIMIT})");
+    assert!(
+        consumed < WITHDRAW_CU_LIMIT,
+        "Withdraw used {consumed} CU, over the {WITHDRAW_CU_LIMIT} CU budget"
+    );
+}
+
+#[test]
+fn test_bump_search_cost_across_owners() {
+    // The setup owner's deposit creates the stats PDA, so it isn't measured
+    let (mut svm, owner) = setup();
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL);
+    measure(&mut svm, &owner, deposit_ix);
+
+    // `find_program_address` tries bumps from 255 down, one
+    // `create_program_address` per attempt; a vault's stored bump takes one
+    let samples: Vec<(u64, u64)> = (1..=BENCH_OWNERS)
+        .map(|seed| {
+            let owner = Keypair::new_from_array([seed; 32]);
+            svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+                .expect("Failed to airdrop");
+
+            let (vault_pda, bump) = find_vault_pda(&owner.pubkey());
+            let deposit_ix =
+                create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL);
+            let attempts = (u8::MAX - bump) as u64 + 1;
+            (attempts, measure(&mut svm, &owner, deposit_ix))
+        })
+        .collect();
+
+    // Least-squares fit of the deposit's CU against the attempts it took
+    let n = samples.len() as f64;
+    let mean_attempts = samples.iter().map(|(a, _)| *a as f64).sum::<f64>() / n;
+    let mean_cu = samples.iter().map(|(_, cu)| *cu as f64).sum::<f64>() / n;
+    let covariance: f64 = samples
+        .iter()
+        .map(|(a, cu)| (*a as f64 - mean_attempts) * (*cu as f64 - mean_cu))
+        .sum();
+    let variance: f64 = samples
+        .iter()
+        .map(|(a, _)| (*a as f64 - mean_attempts).powi(2))
+        .sum();
+    assert!(variance > 0.0, "Every benchmark owner took the same attempts");
+    let cu_per_attempt = covariance / variance;
+    let max_attempts = samples.iter().map(|(a, _)| *a).max().unwrap();
+
+    for (seed, (attempts, cu)) in (1..=BENCH_OWNERS).zip(&samples) {
+        println!("bump bench: owner seed {seed}: {attempts} attempt(s), deposit {cu} CU");
+    }
+    println!(
+        "bump bench: {BENCH_OWNERS} owners, {mean_attempts:.2} attempts on average \
+         (worst {max_attempts}), {cu_per_attempt:.0} CU per extra attempt"
+    );
+    println!(
+        "bump bench: a stored bump saves {:.0} CU per derivation on average",
+        (mean_attempts - 1.0) * cu_per_attempt
+    );
+
+    assert!(
+        cu_per_attempt > 0.0,
+        "Each extra bump attempt should cost compute, got {cu_per_attempt:.0} CU"
+    );
+}
+
+#[test]
+fn test_deposit_with_budget_requests_deposit_limit() {
+    let owner = Pubkey::new_unique();
+    let instructions = client::deposit_with_budget(&owner, LAMPORTS_PER_SOL);
+
+    assert_eq!(instructions.len(), 2);
+    assert_eq!(
+        instructions[1],
+        client::deposit_instruction(&owner, LAMPORTS_PER_SOL)
+    );
+
+    // `SetComputeUnitLimit` is discriminator 2 followed by the limit
+    let budget = &instructions[0];
+    assert_eq!(budget.program_id, client::COMPUTE_BUDGET_PROGRAM_ID);
+    assert!(budget.accounts.is_empty());
+    assert_eq!(budget.data[0], 2);
+    let units = u32::from_le_bytes(budget.data[1..5].try_into().unwrap());
+    assert_eq!(units, client::DEPOSIT_COMPUTE_UNITS);
+
+    // Enough for a deposit, well under the 200k CU an instruction gets by default
+    assert!(units as u64 >= DEPOSIT_CU_LIMIT && units < 200_000);
+}
@@ -0,0 +1,120 @@
+//! Runs Deposit and Withdraw against a program built with the `debug-asserts`
+//! feature, whose handlers fail with `InvariantViolated` if their transfers
+//! don't conserve lamports:
+//! ```bash
+//! cargo build-sbf --features debug-asserts
+//! cargo test --features debug-asserts --test debug_asserts_tests
+//! ```
+#![cfg(feature = "debug-asserts")]
+
+use blueshift_vault::testing::load_vault_program;
+use litesvm::LiteSVM;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+// System Program ID
+const SYSTEM_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("11111111111111111111111111111111");
+
+// Program ID from lib.rs
+const PROGRAM_ID: Pubkey = Pubkey::new_from_array(blueshift_vault::ID);
+
+/// Helper function to find the global stats PDA
+fn find_stats_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"stats"], &PROGRAM_ID)
+}
+
+/// Helper function to find vault PDA
+fn find_vault_pda(owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vault", owner.as_ref()], &PROGRAM_ID)
+}
+
+/// Helper function to find the config PDA
+fn find_config_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"config"], &PROGRAM_ID)
+}
+
+/// Helper function to create an instruction on the accounts Deposit and Withdraw share
+fn create_instruction(owner: Pubkey, vault: Pubkey, instruction_data: Vec<u8>) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(owner, true),  // owner (signer, writable)
+            AccountMeta::new(vault, false), // vault (writable)
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system program
+            AccountMeta::new(find_stats_pda().0, false), // stats (writable)
+            AccountMeta::new_readonly(find_config_pda().0, false), // config
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create deposit instruction, opening the vault if `open`
+fn create_deposit_instruction(
+    owner: Pubkey,
+    vault: Pubkey,
+    amount: u64,
+    open: bool,
+) -> Instruction {
+    let mut instruction_data = vec![0u8]; // Discriminator for Deposit
+    instruction_data.extend_from_slice(&amount.to_le_bytes());
+    instruction_data.extend_from_slice(&0u64.to_le_bytes()); // default id
+    instruction_data.extend_from_slice(&[0u8; 32]); // unsalted
+    instruction_data.push((open as u8) << 1); // FLAG_OPEN
+
+    create_instruction(owner, vault, instruction_data)
+}
+
+/// Helper function to create withdraw instruction for the default vault
+fn create_withdraw_instruction(owner: Pubkey, vault: Pubkey, close: bool) -> Instruction {
+    let mut instruction_data = vec![1u8]; // Discriminator for Withdraw
+    instruction_data.extend_from_slice(&0u64.to_le_bytes()); // default id
+    instruction_data.push(close as u8);
+
+    create_instruction(owner, vault, instruction_data)
+}
+
+#[test]
+fn test_deposit_withdraw_cycle_conserves_lamports() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+
+    // Covers the system-owned and the opened vault paths, each creating the
+    // vault, withdrawing down to its reserve, refunding it and closing it
+    for open in [false, true] {
+        let steps = [
+            create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL, open),
+            create_withdraw_instruction(owner.pubkey(), vault_pda, false),
+            create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL / 2, open),
+            create_withdraw_instruction(owner.pubkey(), vault_pda, true),
+        ];
+
+        for instruction in steps {
+            let recent_blockhash = svm.latest_blockhash();
+            let tx = Transaction::new_signed_with_payer(
+                &[instruction],
+                Some(&owner.pubkey()),
+                &[&owner],
+                recent_blockhash,
+            );
+            svm.send_transaction(tx)
+                .expect("Every step should conserve lamports");
+            svm.expire_blockhash();
+        }
+
+        assert!(
+            svm.get_account(&vault_pda).is_none(),
+            "Vault should be closed"
+        );
+    }
+}
@@ -0,0 +1,322 @@
+use blueshift_vault::*;
+use pinocchio::program_error::ProgramError;
+
+const PAYLOAD: &[u8] = &[7, 8, 9];
+
+/// Every handler and the variant its discriminator has to unpack to
+fn routes() -> Vec<(u8, VaultInstruction<'static>)> {
+    vec![
+        (*Deposit::DISCRIMINATOR, VaultInstruction::Deposit(PAYLOAD)),
+        (
+            *Withdraw::DISCRIMINATOR,
+            VaultInstruction::Withdraw(PAYLOAD),
+        ),
+        (
+            *WithdrawMany::DISCRIMINATOR,
+            VaultInstruction::WithdrawMany(PAYLOAD),
+        ),
+        (
+            *WithdrawAmount::DISCRIMINATOR,
+            VaultInstruction::WithdrawAmount(PAYLOAD),
+        ),
+        (
+            *Consolidate::DISCRIMINATOR,
+            VaultInstruction::Consolidate(PAYLOAD),
+        ),
+        (
+            *OpenVault::DISCRIMINATOR,
+            VaultInstruction::OpenVault(PAYLOAD),
+        ),
+        (
+            *DepositWrappedSol::DISCRIMINATOR,
+            VaultInstruction::DepositWrappedSol(PAYLOAD),
+        ),
+        (
+            *VerifyIntegrity::DISCRIMINATOR,
+            VaultInstruction::VerifyIntegrity(PAYLOAD),
+        ),
+        (
+            *SetLabel::DISCRIMINATOR,
+            VaultInstruction::SetLabel(PAYLOAD),
+        ),
+        (
+            *SetOracle::DISCRIMINATOR,
+            VaultInstruction::SetOracle(PAYLOAD),
+        ),
+        (
+            *WithdrawVested::DISCRIMINATOR,
+            VaultInstruction::WithdrawVested(PAYLOAD),
+        ),
+        (
+            *SimulateWithdraw::DISCRIMINATOR,
+            VaultInstruction::SimulateWithdraw(PAYLOAD),
+        ),
+        (
+            *Initialize::DISCRIMINATOR,
+            VaultInstruction::Initialize(PAYLOAD),
+        ),
+        (
+            *DepositMany::DISCRIMINATOR,
+            VaultInstruction::DepositMany(PAYLOAD),
+        ),
+        (
+            *SplitDeposit::DISCRIMINATOR,
+            VaultInstruction::SplitDeposit(PAYLOAD),
+        ),
+        (
+            *MergeVaults::DISCRIMINATOR,
+            VaultInstruction::MergeVaults(PAYLOAD),
+        ),
+        (*Close::DISCRIMINATOR, VaultInstruction::Close(PAYLOAD)),
+        (
+            *CloseAndSweep::DISCRIMINATOR,
+            VaultInstruction::CloseAndSweep(PAYLOAD),
+        ),
+        (
+            *AddAllowedDest::DISCRIMINATOR,
+            VaultInstruction::AddAllowedDest(PAYLOAD),
+        ),
+        (
+            *RemoveAllowedDest::DISCRIMINATOR,
+            VaultInstruction::RemoveAllowedDest(PAYLOAD),
+        ),
+        (
+            *WithdrawTo::DISCRIMINATOR,
+            VaultInstruction::WithdrawTo(PAYLOAD),
+        ),
+        (
+            *ExtendLock::DISCRIMINATOR,
+            VaultInstruction::ExtendLock(PAYLOAD),
+        ),
+        (
+            *SweepToken::DISCRIMINATOR,
+            VaultInstruction::SweepToken(PAYLOAD),
+        ),
+        (
+            *DepositFor::DISCRIMINATOR,
+            VaultInstruction::DepositFor(PAYLOAD),
+        ),
+        (
+            *WithdrawIfPriceAbove::DISCRIMINATOR,
+            VaultInstruction::WithdrawIfPriceAbove(PAYLOAD),
+        ),
+        (
+            *VaultTransfer::DISCRIMINATOR,
+            VaultInstruction::VaultTransfer(PAYLOAD),
+        ),
+        (
+            *SetDepositsLocked::DISCRIMINATOR,
+            VaultInstruction::SetDepositsLocked(PAYLOAD),
+        ),
+        (
+            *WithdrawWithApproval::DISCRIMINATOR,
+            VaultInstruction::WithdrawWithApproval(PAYLOAD),
+        ),
+        (*GetInfo::DISCRIMINATOR, VaultInstruction::GetInfo(PAYLOAD)),
+        (
+            *MigrateVault::DISCRIMINATOR,
+            VaultInstruction::MigrateVault(PAYLOAD),
+        ),
+        (
+            *DepositLocked::DISCRIMINATOR,
+            VaultInstruction::DepositLocked(PAYLOAD),
+        ),
+        (
+            *SplitVault::DISCRIMINATOR,
+            VaultInstruction::SplitVault(PAYLOAD),
+        ),
+        (
+            *SetTokenDestOwner::DISCRIMINATOR,
+            VaultInstruction::SetTokenDestOwner(PAYLOAD),
+        ),
+        (
+            *SetEscrowAgent::DISCRIMINATOR,
+            VaultInstruction::SetEscrowAgent(PAYLOAD),
+        ),
+        (
+            *FundRewardPool::DISCRIMINATOR,
+            VaultInstruction::FundRewardPool(PAYLOAD),
+        ),
+        (
+            *WithdrawAllWithTip::DISCRIMINATOR,
+            VaultInstruction::WithdrawAllWithTip(PAYLOAD),
+        ),
+        (
+            *SetMetadata::DISCRIMINATOR,
+            VaultInstruction::SetMetadata(PAYLOAD),
+        ),
+        (
+            *SetFrozen::DISCRIMINATOR,
+            VaultInstruction::SetFrozen(PAYLOAD),
+        ),
+        (
+            *Reconcile::DISCRIMINATOR,
+            VaultInstruction::Reconcile(PAYLOAD),
+        ),
+        (
+            *SetPaused::DISCRIMINATOR,
+            VaultInstruction::SetPaused(PAYLOAD),
+        ),
+        (
+            *EmergencyDrain::DISCRIMINATOR,
+            VaultInstruction::EmergencyDrain(PAYLOAD),
+        ),
+        (
+            *ProposeAdmin::DISCRIMINATOR,
+            VaultInstruction::ProposeAdmin(PAYLOAD),
+        ),
+        (
+            *AcceptAdmin::DISCRIMINATOR,
+            VaultInstruction::AcceptAdmin(PAYLOAD),
+        ),
+        (
+            *WithdrawToNewAccount::DISCRIMINATOR,
+            VaultInstruction::WithdrawToNewAccount(PAYLOAD),
+        ),
+        (
+            *DepositAsWrapped::DISCRIMINATOR,
+            VaultInstruction::DepositAsWrapped(PAYLOAD),
+        ),
+        (
+            *GetInfoMany::DISCRIMINATOR,
+            VaultInstruction::GetInfoMany(PAYLOAD),
+        ),
+        (*SetDrip::DISCRIMINATOR, VaultInstruction::SetDrip(PAYLOAD)),
+        (*Crank::DISCRIMINATOR, VaultInstruction::Crank(PAYLOAD)),
+        (
+            *TagVault::DISCRIMINATOR,
+            VaultInstruction::TagVault(PAYLOAD),
+        ),
+        (
+            *UpgradeToMultisig::DISCRIMINATOR,
+            VaultInstruction::UpgradeToMultisig(PAYLOAD),
+        ),
+        (
+            *CanWithdraw::DISCRIMINATOR,
+            VaultInstruction::CanWithdraw(PAYLOAD),
+        ),
+        (
+            *SetExpiry::DISCRIMINATOR,
+            VaultInstruction::SetExpiry(PAYLOAD),
+        ),
+        (
+            *WithdrawExpired::DISCRIMINATOR,
+            VaultInstruction::WithdrawExpired(PAYLOAD),
+        ),
+        (
+            *FundGasReserve::DISCRIMINATOR,
+            VaultInstruction::FundGasReserve(PAYLOAD),
+        ),
+        (
+            *DepositToken::DISCRIMINATOR,
+            VaultInstruction::DepositToken(PAYLOAD),
+        ),
+        (
+            *WithdrawToken::DISCRIMINATOR,
+            VaultInstruction::WithdrawToken(PAYLOAD),
+        ),
+        (
+            *DeriveVault::DISCRIMINATOR,
+            VaultInstruction::DeriveVault(PAYLOAD),
+        ),
+        (
+            *ExtendSlotLock::DISCRIMINATOR,
+            VaultInstruction::ExtendSlotLock(PAYLOAD),
+        ),
+        (
+            *SetDelegate::DISCRIMINATOR,
+            VaultInstruction::SetDelegate(PAYLOAD),
+        ),
+        (
+            *DelegateWithdraw::DISCRIMINATOR,
+            VaultInstruction::DelegateWithdraw(PAYLOAD),
+        ),
+        (
+            *UpgradeVaultState::DISCRIMINATOR,
+            VaultInstruction::UpgradeVaultState(PAYLOAD),
+        ),
+        (
+            *SetWithdrawFee::DISCRIMINATOR,
+            VaultInstruction::SetWithdrawFee(PAYLOAD),
+        ),
+        (
+            *SetBeneficiary::DISCRIMINATOR,
+            VaultInstruction::SetBeneficiary(PAYLOAD),
+        ),
+        (
+            *BeneficiaryClaim::DISCRIMINATOR,
+            VaultInstruction::BeneficiaryClaim(PAYLOAD),
+        ),
+        (
+            *SetRewardRate::DISCRIMINATOR,
+            VaultInstruction::SetRewardRate(PAYLOAD),
+        ),
+        (
+            *ClaimReward::DISCRIMINATOR,
+            VaultInstruction::ClaimReward(PAYLOAD),
+        ),
+        (
+            *CreateMultisig::DISCRIMINATOR,
+            VaultInstruction::CreateMultisig(PAYLOAD),
+        ),
+        (
+            *SetDailyLimit::DISCRIMINATOR,
+            VaultInstruction::SetDailyLimit(PAYLOAD),
+        ),
+        (
+            *AdminRecover::DISCRIMINATOR,
+            VaultInstruction::AdminRecover(PAYLOAD),
+        ),
+        (
+            *AddAllowedSource::DISCRIMINATOR,
+            VaultInstruction::AddAllowedSource(PAYLOAD),
+        ),
+        (
+            *RemoveAllowedSource::DISCRIMINATOR,
+            VaultInstruction::RemoveAllowedSource(PAYLOAD),
+        ),
+        (
+            *DepositWithMetadata::DISCRIMINATOR,
+            VaultInstruction::DepositWithMetadata(PAYLOAD),
+        ),
+        (
+            *ExportState::DISCRIMINATOR,
+            VaultInstruction::ExportState(PAYLOAD),
+        ),
+    ]
+}
+
+#[test]
+fn test_every_discriminator_routes_to_its_handler() {
+    for (discriminator, expected) in routes() {
+        let mut instruction_data = vec![discriminator];
+        instruction_data.extend_from_slice(PAYLOAD);
+
+        let instruction = VaultInstruction::unpack(&instruction_data).unwrap();
+        assert_eq!(instruction, expected);
+        assert_eq!(instruction.discriminator(), discriminator);
+    }
+}
+
+#[test]
+fn test_discriminators_are_unique_and_unknown_bytes_are_rejected() {
+    let routes = routes();
+    for byte in 0..=u8::MAX {
+        let matching = routes
+            .iter()
+            .filter(|(discriminator, _)| *discriminator == byte);
+        match matching.count() {
+            0 => assert_eq!(
+                VaultInstruction::unpack(&[byte]),
+                Err(ProgramError::InvalidInstructionData)
+            ),
+            1 => assert!(VaultInstruction::unpack(&[byte]).is_ok()),
+            _ => panic!("discriminator {byte} is taken by more than one handler"),
+        }
+    }
+
+    assert_eq!(
+        VaultInstruction::unpack(&[]),
+        Err(ProgramError::InvalidInstructionData)
+    );
+}
@@ -0,0 +1,86 @@
+//! Runs a deposit and withdraw against programs built with and without the
+//! `json-events` feature, which decides how their events are logged:
+//! ```bash
+//! cargo build-sbf && cargo test --test event_format_tests
+//! cargo build-sbf --features json-events
+//! cargo test --features json-events --test event_format_tests
+//! ```
+
+use blueshift_vault::{client, testing::load_vault_program};
+use litesvm::LiteSVM;
+use solana_sdk::{
+    instruction::Instruction,
+    native_token::LAMPORTS_PER_SOL,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+/// Sends `instruction` signed by `owner` and returns its program logs
+fn send(svm: &mut LiteSVM, owner: &Keypair, instruction: Instruction) -> Vec<String> {
+    svm.expire_blockhash();
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&owner.pubkey()),
+        &[owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx)
+        .expect("Transaction should succeed")
+        .logs
+}
+
+/// Deposits and withdraws 1 SOL, returning the owner's hex key and both transactions' logs
+fn deposit_and_withdraw() -> (String, Vec<String>, Vec<String>) {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let deposit = client::deposit_instruction(&owner.pubkey(), LAMPORTS_PER_SOL);
+    let deposit_logs = send(&mut svm, &owner, deposit);
+    let withdraw = client::withdraw_instruction(&owner.pubkey());
+    let withdraw_logs = send(&mut svm, &owner, withdraw);
+
+    let hex = owner
+        .pubkey()
+        .to_bytes()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    (hex, deposit_logs, withdraw_logs)
+}
+
+#[test]
+#[cfg(not(feature = "json-events"))]
+fn test_events_are_logged_as_key_value_by_default() {
+    let (hex, deposit_logs, withdraw_logs) = deposit_and_withdraw();
+
+    assert!(deposit_logs.contains(&format!(
+        "Program log: vault:deposit owner={hex} amount={LAMPORTS_PER_SOL}"
+    )));
+    assert!(withdraw_logs.contains(&format!(
+        "Program log: vault:withdraw owner={hex} amount={LAMPORTS_PER_SOL}"
+    )));
+    assert!(!deposit_logs.iter().any(|log| log.contains(r#"{"event""#)));
+    assert_eq!(client::parse_events(&deposit_logs).len(), 1);
+    assert_eq!(client::parse_events(&withdraw_logs).len(), 1);
+}
+
+#[test]
+#[cfg(feature = "json-events")]
+fn test_events_are_logged_as_json_under_json_events() {
+    let (hex, deposit_logs, withdraw_logs) = deposit_and_withdraw();
+
+    assert!(deposit_logs.contains(&format!(
+        r#"Program log: {{"event":"deposit","owner":"{hex}","amount":{LAMPORTS_PER_SOL}}}"#
+    )));
+    assert!(withdraw_logs.contains(&format!(
+        r#"Program log: {{"event":"withdraw","owner":"{hex}","amount":{LAMPORTS_PER_SOL}}}"#
+    )));
+    assert!(!deposit_logs.iter().any(|log| log.contains("vault:deposit")));
+    assert_eq!(client::parse_events(&deposit_logs).len(), 1);
+    assert_eq!(client::parse_events(&withdraw_logs).len(), 1);
+}
@@ -0,0 +1,33 @@
+//! Records every callback from the vault program as a log line, so tests can
+//! assert that the deposit and withdraw hooks fired.
+
+#![no_std]
+
+use pinocchio::{
+    account_info::AccountInfo, entrypoint, nostd_panic_handler, program_error::ProgramError,
+    pubkey::Pubkey, ProgramResult,
+};
+use pinocchio_log::log;
+
+entrypoint!(process_instruction);
+nostd_panic_handler!();
+
+/// Logs `hook: event=<event> amount=<lamports>` for `[event: u8][owner: Pubkey][amount: u64]`
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let [owner, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if instruction_data.len().ne(&41) || owner.key().ne(&instruction_data[1..33]) {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let amount = u64::from_le_bytes(instruction_data[33..41].try_into().unwrap());
+    log!("hook: event={} amount={}", instruction_data[0], amount);
+
+    Ok(())
+}
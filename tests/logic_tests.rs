@@ -0,0 +1,474 @@
+use blueshift_vault::{
+    error::VaultError,
+    logic::{
+        bps_of, bps_of_rounded, check_min_withdraw, check_tracked_balance, compute_drip_due,
+        compute_interest, compute_releasable, compute_withdraw_amount,
+        compute_withdraw_amount_rounded, withdraw_fee_bps, FeeRounding, BPS_DENOMINATOR,
+        SECONDS_PER_YEAR,
+    },
+    rent::minimum_balance,
+    state::VaultState,
+    sysvar::{clock, rent},
+};
+
+const RESERVE: u64 = 890_880;
+
+#[test]
+fn test_bps_of_rounds_down() {
+    assert_eq!(bps_of(0, 5_000), 0);
+    assert_eq!(bps_of(1, 9_999), 0);
+    assert_eq!(bps_of(10_001, 1), 1);
+    assert_eq!(bps_of(1_000, 0), 0);
+    assert_eq!(bps_of(1_000, BPS_DENOMINATOR as u16), 1_000);
+    assert_eq!(bps_of(u64::MAX, BPS_DENOMINATOR as u16), u64::MAX);
+    assert_eq!(bps_of(u64::MAX, 5_000), u64::MAX / 2);
+}
+
+#[test]
+fn test_compute_withdraw_amount_without_fee() {
+    assert_eq!(compute_withdraw_amount(100, 100, 0), Ok((100, 0)));
+    assert_eq!(compute_withdraw_amount(100, 1, 0), Ok((1, 0)));
+    assert_eq!(
+        compute_withdraw_amount(u64::MAX, u64::MAX, 0),
+        Ok((u64::MAX, 0))
+    );
+}
+
+#[test]
+fn test_whitelisted_owner_pays_no_withdraw_fee() {
+    let exempt_owner = [1u8; 32];
+    let other_owner = [2u8; 32];
+    let whitelist = [[9u8; 32], exempt_owner];
+
+    // Identical withdrawals under the same 2.5% fee
+    let fee_bps = withdraw_fee_bps(&exempt_owner, 250, &whitelist);
+    assert_eq!(
+        compute_withdraw_amount(1_000, 1_000, fee_bps),
+        Ok((1_000, 0))
+    );
+
+    let fee_bps = withdraw_fee_bps(&other_owner, 250, &whitelist);
+    assert_eq!(
+        compute_withdraw_amount(1_000, 1_000, fee_bps),
+        Ok((975, 25))
+    );
+
+    // Nobody is exempt under an empty whitelist
+    assert_eq!(withdraw_fee_bps(&exempt_owner, 250, &[]), 250);
+}
+
+#[test]
+fn test_compute_withdraw_amount_splits_fee() {
+    assert_eq!(compute_withdraw_amount(1_000, 1_000, 250), Ok((975, 25)));
+
+    // The fee rounds down, so the owner keeps the remainder
+    assert_eq!(compute_withdraw_amount(999, 999, 100), Ok((990, 9)));
+    assert_eq!(compute_withdraw_amount(1, 1, 9_999), Ok((1, 0)));
+
+    // A 100% fee sends everything to the treasury
+    assert_eq!(
+        compute_withdraw_amount(1_000, 1_000, BPS_DENOMINATOR as u16),
+        Ok((0, 1_000))
+    );
+    assert_eq!(
+        compute_withdraw_amount(u64::MAX, u64::MAX, BPS_DENOMINATOR as u16),
+        Ok((0, u64::MAX))
+    );
+
+    // Both parts always add back up to the request
+    for (requested, fee_bps) in [(u64::MAX, 1), (u64::MAX, 9_999), (12_345, 3_333)] {
+        let (to_owner, to_treasury) =
+            compute_withdraw_amount(u64::MAX, requested, fee_bps).unwrap();
+        assert_eq!(to_owner + to_treasury, requested);
+    }
+}
+
+#[test]
+fn test_fee_rounding_direction_at_indivisible_amounts() {
+    use FeeRounding::{FavorOwner, FavorTreasury};
+
+    // 1% of 999 is 9.99 lamports: the dust goes to whichever side is favored
+    assert_eq!(bps_of_rounded(999, 100, FavorOwner), 9);
+    assert_eq!(bps_of_rounded(999, 100, FavorTreasury), 10);
+    assert_eq!(
+        compute_withdraw_amount_rounded(999, 999, 100, FavorOwner),
+        Ok((990, 9))
+    );
+    assert_eq!(
+        compute_withdraw_amount_rounded(999, 999, 100, FavorTreasury),
+        Ok((989, 10))
+    );
+
+    // Any fee at all on a single lamport takes it when rounding up
+    assert_eq!(
+        compute_withdraw_amount_rounded(1, 1, 1, FavorOwner),
+        Ok((1, 0))
+    );
+    assert_eq!(
+        compute_withdraw_amount_rounded(1, 1, 1, FavorTreasury),
+        Ok((0, 1))
+    );
+
+    // Exact divisions and zero fees come out the same either way
+    for (amount, bps) in [(1_000, 250), (0, 5_000), (1_000, 0), (u64::MAX, 10_000)] {
+        assert_eq!(
+            bps_of_rounded(amount, bps, FavorOwner),
+            bps_of_rounded(amount, bps, FavorTreasury)
+        );
+    }
+
+    // Rounding up never takes more than the request, and the parts still add up
+    for (requested, fee_bps) in [(u64::MAX, 1), (u64::MAX, 9_999), (12_345, 3_333)] {
+        let (to_owner, to_treasury) =
+            compute_withdraw_amount_rounded(u64::MAX, requested, fee_bps, FavorTreasury).unwrap();
+        assert_eq!(to_owner + to_treasury, requested);
+        assert_eq!(
+            to_treasury,
+            bps_of_rounded(requested, fee_bps, FavorOwner) + 1
+        );
+    }
+
+    // The default split keeps favoring the owner
+    assert_eq!(
+        compute_withdraw_amount(999, 999, 100),
+        compute_withdraw_amount_rounded(999, 999, 100, FavorOwner)
+    );
+}
+
+#[test]
+fn test_compute_withdraw_amount_rejects_invalid_requests() {
+    assert_eq!(
+        compute_withdraw_amount(0, 0, 0),
+        Err(VaultError::VaultEmpty)
+    );
+    assert_eq!(
+        compute_withdraw_amount(100, 0, 0),
+        Err(VaultError::VaultEmpty)
+    );
+    assert_eq!(
+        compute_withdraw_amount(0, 1, 0),
+        Err(VaultError::InsufficientFunds)
+    );
+    assert_eq!(
+        compute_withdraw_amount(u64::MAX - 1, u64::MAX, 0),
+        Err(VaultError::InsufficientFunds)
+    );
+    assert_eq!(
+        compute_withdraw_amount(100, 100, BPS_DENOMINATOR as u16 + 1),
+        Err(VaultError::InvalidFee)
+    );
+    assert_eq!(
+        compute_withdraw_amount(100, 100, u16::MAX),
+        Err(VaultError::InvalidFee)
+    );
+}
+
+#[test]
+fn test_compute_releasable_for_system_vaults() {
+    // A close drains everything, otherwise the reserve stays behind
+    assert_eq!(
+        compute_releasable(RESERVE + 500, RESERVE, None, true),
+        RESERVE + 500
+    );
+    assert_eq!(compute_releasable(RESERVE + 500, RESERVE, None, false), 500);
+
+    // Nothing above the reserve means nothing to release without a close
+    assert_eq!(compute_releasable(RESERVE, RESERVE, None, false), 0);
+    assert_eq!(compute_releasable(RESERVE - 1, RESERVE, None, false), 0);
+    assert_eq!(compute_releasable(0, RESERVE, None, true), 0);
+    assert_eq!(compute_releasable(u64::MAX, RESERVE, None, true), u64::MAX);
+}
+
+#[test]
+fn test_compute_releasable_for_opened_vaults() {
+    // Only the tracked balance leaves without a close
+    assert_eq!(
+        compute_releasable(RESERVE + 500, RESERVE, Some(500), false),
+        500
+    );
+    assert_eq!(compute_releasable(RESERVE, RESERVE, Some(0), false), 0);
+
+    // A close returns the rent floor along with the balance
+    assert_eq!(
+        compute_releasable(RESERVE + 500, RESERVE, Some(500), true),
+        RESERVE + 500
+    );
+    assert_eq!(compute_releasable(RESERVE, RESERVE, Some(0), true), RESERVE);
+
+    // Donated lamports are never released, so the close keeps the vault open
+    assert_eq!(
+        compute_releasable(RESERVE + 800, RESERVE, Some(500), true),
+        500
+    );
+    assert_eq!(
+        compute_releasable(RESERVE + 800, RESERVE, Some(500), false),
+        500
+    );
+
+    // A tracked balance above the lamports held is capped at what's spendable
+    assert_eq!(
+        compute_releasable(RESERVE + 500, RESERVE, Some(u64::MAX), false),
+        500
+    );
+    assert_eq!(
+        compute_releasable(u64::MAX, RESERVE, Some(u64::MAX), true),
+        u64::MAX
+    );
+}
+
+#[test]
+fn test_check_tracked_balance_rejects_balances_above_spendable_lamports() {
+    assert_eq!(check_tracked_balance(RESERVE + 500, RESERVE, 500), Ok(()));
+    assert_eq!(check_tracked_balance(RESERVE + 800, RESERVE, 500), Ok(()));
+    assert_eq!(check_tracked_balance(RESERVE, RESERVE, 0), Ok(()));
+    assert_eq!(check_tracked_balance(0, RESERVE, 0), Ok(()));
+
+    assert_eq!(
+        check_tracked_balance(RESERVE + 500, RESERVE, 501),
+        Err(VaultError::AccountingMismatch)
+    );
+    assert_eq!(
+        check_tracked_balance(RESERVE - 1, RESERVE, 1),
+        Err(VaultError::AccountingMismatch)
+    );
+}
+
+#[test]
+fn test_compute_withdraw_amount_rounding_dust_goes_to_owner() {
+    // `amount * fee_bps` isn't a multiple of 10000 in any of these, so the
+    // exact fee has a fractional part that the treasury doesn't get
+    let cases = [
+        // 1 lamport at 99.99%: the 0.9999 lamport fee rounds to nothing
+        (1, 9_999, 1, 0),
+        // 0.3333 lamports of fee
+        (1, 3_333, 1, 0),
+        // 9.99 lamports of fee
+        (999, 100, 990, 9),
+        // 1.4999 lamports of fee
+        (14_999, 1, 14_998, 1),
+        // 333.3 lamports of fee
+        (3_333, 1_000, 3_000, 333),
+        // 2.5 lamports of fee: a half rounds down too
+        (5, 5_000, 3, 2),
+        // 9_998.0001 lamports of fee
+        (9_999, 9_999, 1, 9_998),
+    ];
+
+    for (requested, fee_bps, to_owner, to_treasury) in cases {
+        assert_ne!(
+            requested as u128 * fee_bps as u128 % BPS_DENOMINATOR as u128,
+            0
+        );
+        assert_eq!(
+            compute_withdraw_amount(requested, requested, fee_bps),
+            Ok((to_owner, to_treasury)),
+            "{requested} lamports at {fee_bps} bps"
+        );
+    }
+
+    // The treasury never gets more than the exact fee, and the owner never
+    // loses a whole lamport to rounding
+    for requested in [1, 7, 999, 10_001, 123_456_789, u64::MAX] {
+        for fee_bps in [1, 3, 333, 4_999, 9_999] {
+            let (to_owner, to_treasury) =
+                compute_withdraw_amount(requested, requested, fee_bps).unwrap();
+            let exact_fee = requested as u128 * fee_bps as u128;
+            let denominator = BPS_DENOMINATOR as u128;
+
+            assert!(to_treasury as u128 * denominator <= exact_fee);
+            assert!(exact_fee < (to_treasury as u128 + 1) * denominator);
+            assert_eq!(to_owner + to_treasury, requested);
+        }
+    }
+}
+
+#[test]
+fn test_vault_error_codes_are_distinct_and_stable() {
+    use blueshift_vault::error::*;
+    use pinocchio::program_error::ProgramError;
+    use VaultError::*;
+
+    // `(variant, constant, code)`; a code here must never change once released
+    let codes = [
+        (InvalidVaultOwner, ERR_INVALID_VAULT_OWNER, 0),
+        (VaultEmpty, ERR_VAULT_EMPTY, 1),
+        (InsufficientFunds, ERR_INSUFFICIENT_FUNDS, 2),
+        (TooSoonAfterCreation, ERR_TOO_SOON_AFTER_CREATION, 3),
+        (MissingOwnerSignature, ERR_MISSING_OWNER_SIGNATURE, 4),
+        (StalePrice, ERR_STALE_PRICE, 5),
+        (VestingLocked, ERR_VESTING_LOCKED, 6),
+        (InvalidPda, ERR_INVALID_PDA, 7),
+        (DailyLimitExceeded, ERR_DAILY_LIMIT_EXCEEDED, 8),
+        (VaultNotEmpty, ERR_VAULT_NOT_EMPTY, 9),
+        (UnsupportedStateVersion, ERR_UNSUPPORTED_STATE_VERSION, 10),
+        (DestinationNotAllowed, ERR_DESTINATION_NOT_ALLOWED, 11),
+        (SlippageExceeded, ERR_SLIPPAGE_EXCEEDED, 12),
+        (InvalidFee, ERR_INVALID_FEE, 13),
+        (TransferFailed, ERR_TRANSFER_FAILED, 14),
+        (CannotShortenLock, ERR_CANNOT_SHORTEN_LOCK, 15),
+        (VaultLocked, ERR_VAULT_LOCKED, 16),
+        (DuplicateAccount, ERR_DUPLICATE_ACCOUNT, 17),
+        (InvalidRecipient, ERR_INVALID_RECIPIENT, 18),
+        (PriceConditionNotMet, ERR_PRICE_CONDITION_NOT_MET, 19),
+        (TooManyVaults, ERR_TOO_MANY_VAULTS, 20),
+        (DepositsLocked, ERR_DEPOSITS_LOCKED, 21),
+        (InvalidApproval, ERR_INVALID_APPROVAL, 22),
+        (NonceAlreadyUsed, ERR_NONCE_ALREADY_USED, 23),
+        (DepositTooLarge, ERR_DEPOSIT_TOO_LARGE, 24),
+        (UnlockInPast, ERR_UNLOCK_IN_PAST, 25),
+        (
+            InvalidAccountArrangement,
+            ERR_INVALID_ACCOUNT_ARRANGEMENT,
+            26,
+        ),
+        (EscrowedVault, ERR_ESCROWED_VAULT, 27),
+        (EscrowAgentSet, ERR_ESCROW_AGENT_SET, 28),
+        (VaultTooYoung, ERR_VAULT_TOO_YOUNG, 29),
+        (AlreadyInitialized, ERR_ALREADY_INITIALIZED, 30),
+        (RollingLimitExceeded, ERR_ROLLING_LIMIT_EXCEEDED, 31),
+        (HookReentrancy, ERR_HOOK_REENTRANCY, 32),
+        (BatchTooLarge, ERR_BATCH_TOO_LARGE, 33),
+        (VaultFrozen, ERR_VAULT_FROZEN, 34),
+        (ApprovalExpired, ERR_APPROVAL_EXPIRED, 35),
+        (AccountingMismatch, ERR_ACCOUNTING_MISMATCH, 36),
+        (InvalidOwnerAccount, ERR_INVALID_OWNER_ACCOUNT, 37),
+        (ReuseCooldown, ERR_REUSE_COOLDOWN, 38),
+        (NotAdmin, ERR_NOT_ADMIN, 39),
+        (NotPaused, ERR_NOT_PAUSED, 40),
+        (ProgramPaused, ERR_PROGRAM_PAUSED, 41),
+        (WithdrawTooSmall, ERR_WITHDRAW_TOO_SMALL, 42),
+        (NotPendingAdmin, ERR_NOT_PENDING_ADMIN, 43),
+        (SysvarUnavailable, ERR_SYSVAR_UNAVAILABLE, 44),
+        (DripNotDue, ERR_DRIP_NOT_DUE, 45),
+        (OwnerNotWritable, ERR_OWNER_NOT_WRITABLE, 46),
+        (InvalidMultisigThreshold, ERR_INVALID_MULTISIG_THRESHOLD, 47),
+        (MultisigThresholdNotMet, ERR_MULTISIG_THRESHOLD_NOT_MET, 48),
+        (ChecksumMismatch, ERR_CHECKSUM_MISMATCH, 49),
+        (VaultNotExpired, ERR_VAULT_NOT_EXPIRED, 50),
+        (TooManyMints, ERR_TOO_MANY_MINTS, 51),
+        (NotDelegate, ERR_NOT_DELEGATE, 52),
+        (DelegateCapExceeded, ERR_DELEGATE_CAP_EXCEEDED, 53),
+        (InvariantViolated, ERR_INVARIANT_VIOLATED, 54),
+        (NotUpgradeAuthority, ERR_NOT_UPGRADE_AUTHORITY, 55),
+        (DrainDelayNotElapsed, ERR_DRAIN_DELAY_NOT_ELAPSED, 56),
+        (ReceiptsOutstanding, ERR_RECEIPTS_OUTSTANDING, 57),
+        (TokensOutstanding, ERR_TOKENS_OUTSTANDING, 58),
+        (InvalidTreasury, ERR_INVALID_TREASURY, 59),
+        (OwnerStillActive, ERR_OWNER_STILL_ACTIVE, 60),
+        (RewardPoolUnderfunded, ERR_REWARD_POOL_UNDERFUNDED, 61),
+        (NotVaultAdmin, ERR_NOT_VAULT_ADMIN, 62),
+        (InvalidDestinationAta, ERR_INVALID_DESTINATION_ATA, 63),
+        (SourceNotWhitelisted, ERR_SOURCE_NOT_WHITELISTED, 64),
+    ];
+
+    for (index, (variant, constant, code)) in codes.iter().enumerate() {
+        assert_eq!(*constant, *code);
+        assert_eq!(*variant as u32, *code);
+        assert_eq!(ProgramError::from(*variant), ProgramError::Custom(*code));
+        assert!(codes[index + 1..].iter().all(|(_, _, other)| other != code));
+    }
+}
+
+#[test]
+fn test_check_min_withdraw_allows_draining_below_the_minimum() {
+    assert_eq!(check_min_withdraw(1, 1_000, 0), Ok(()));
+    assert_eq!(check_min_withdraw(500, 1_000, 500), Ok(()));
+    assert_eq!(check_min_withdraw(1_000, 1_000, 500), Ok(()));
+
+    // Everything the vault has left, even under the minimum
+    assert_eq!(check_min_withdraw(200, 200, 500), Ok(()));
+
+    assert_eq!(
+        check_min_withdraw(499, 1_000, 500),
+        Err(VaultError::WithdrawTooSmall)
+    );
+    assert_eq!(
+        check_min_withdraw(199, 200, 500),
+        Err(VaultError::WithdrawTooSmall)
+    );
+}
+
+#[test]
+fn test_fully_populated_vault_state_spans_exactly_len_bytes() {
+    let mut data = vec![0xAB; VaultState::LEN];
+    data[0] = VaultState::VERSION;
+
+    let state = VaultState::from_bytes(&data).expect("State should be readable");
+    assert_eq!(core::mem::size_of_val(state), VaultState::LEN);
+    assert_eq!(state.owner(), &[0xAB; 32]);
+    assert_eq!(state.rolling_window_secs(), u64::from_le_bytes([0xAB; 8]));
+
+    // Neither a truncated nor an oversized account reads as vault state
+    assert!(VaultState::from_bytes(&data[..VaultState::LEN - 1]).is_none());
+    data.push(0xAB);
+    assert!(VaultState::from_bytes(&data).is_none());
+}
+
+#[test]
+fn test_only_shorter_older_layouts_are_outdated() {
+    let mut data = vec![0u8; VaultState::LEN - 40];
+    data[0] = 1;
+    assert!(VaultState::is_outdated(&data));
+
+    // Fields were appended after the current version was introduced too
+    let mut short_current = vec![0u8; 1178];
+    short_current[0] = VaultState::VERSION;
+    assert!(VaultState::is_outdated(&short_current));
+
+    // The current layout, an unknown version, or no version at all isn't
+    data[0] = VaultState::VERSION + 1;
+    assert!(!VaultState::is_outdated(&data));
+    data[0] = 0;
+    assert!(!VaultState::is_outdated(&data));
+    assert!(!VaultState::is_outdated(&[]));
+
+    let mut current = vec![0u8; VaultState::LEN];
+    current[0] = 1;
+    assert!(!VaultState::is_outdated(&current));
+}
+
+#[test]
+fn test_unavailable_sysvars_fail_with_sysvar_unavailable() {
+    use pinocchio::program_error::ProgramError;
+
+    // Off-chain there is no runtime to serve the sysvar syscalls, so every fetch fails
+    let unavailable = ProgramError::from(VaultError::SysvarUnavailable);
+    assert_eq!(clock().err(), Some(unavailable));
+    assert_eq!(rent().err(), Some(unavailable));
+    assert_eq!(minimum_balance(None, 0), Err(unavailable));
+}
+
+#[test]
+fn test_compute_drip_due_counts_whole_elapsed_intervals() {
+    assert_eq!(compute_drip_due(1_059, 1_000, 60, 7), (0, 0));
+    assert_eq!(compute_drip_due(1_060, 1_000, 60, 7), (1, 7));
+    assert_eq!(compute_drip_due(1_179, 1_000, 60, 7), (2, 14));
+
+    // No schedule, or a clock behind the last release, has nothing due
+    assert_eq!(compute_drip_due(i64::MAX, 0, 0, 7), (0, 0));
+    assert_eq!(compute_drip_due(999, 1_000, 60, 7), (0, 0));
+    assert_eq!(
+        compute_drip_due(i64::MAX, i64::MIN, 1, u64::MAX),
+        (u64::MAX, u64::MAX)
+    );
+}
+
+#[test]
+fn test_compute_interest_pays_the_yearly_rate_pro_rata() {
+    // 5% a year on 100 SOL
+    let balance = 100_000_000_000;
+    assert_eq!(
+        compute_interest(balance, 500, SECONDS_PER_YEAR),
+        5_000_000_000
+    );
+    assert_eq!(
+        compute_interest(balance, 500, SECONDS_PER_YEAR / 2),
+        2_500_000_000
+    );
+
+    // Rounds down, and nothing accrues without a rate or time
+    assert_eq!(compute_interest(1, 500, SECONDS_PER_YEAR), 0);
+    assert_eq!(compute_interest(balance, 0, SECONDS_PER_YEAR), 0);
+    assert_eq!(compute_interest(balance, 500, 0), 0);
+    assert_eq!(compute_interest(u64::MAX, u16::MAX, u64::MAX), u64::MAX);
+}
@@ -0,0 +1,105 @@
+//! Sends Deposit data with trailing bytes to programs built with and without
+//! the `strict-parsing` feature, which decides whether they're ignored:
+//! ```bash
+//! cargo build-sbf && cargo test --test parsing_tests
+//! cargo build-sbf --features strict-parsing
+//! cargo test --features strict-parsing --test parsing_tests
+//! ```
+
+use blueshift_vault::testing::load_vault_program;
+use litesvm::LiteSVM;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+// System Program ID
+const SYSTEM_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("11111111111111111111111111111111");
+
+// Program ID from lib.rs
+const PROGRAM_ID: Pubkey = Pubkey::new_from_array(blueshift_vault::ID);
+
+/// Helper function to find vault PDA
+fn find_vault_pda(owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vault", owner.as_ref()], &PROGRAM_ID)
+}
+
+/// Deposits `amount` into `owner`'s default vault with two bytes after the amount
+fn deposit_with_trailing_bytes(
+    svm: &mut LiteSVM,
+    owner: &Keypair,
+    amount: u64,
+) -> Result<(), TransactionError> {
+    let mut instruction_data = vec![0u8]; // Discriminator for Deposit
+    instruction_data.extend_from_slice(&amount.to_le_bytes());
+    instruction_data.extend_from_slice(&[0xab, 0xcd]); // trailing bytes
+
+    let instruction = Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(owner.pubkey(), true), // owner (signer, writable)
+            AccountMeta::new(find_vault_pda(&owner.pubkey()).0, false), // vault (writable)
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system program
+            AccountMeta::new(
+                Pubkey::find_program_address(&[b"stats"], &PROGRAM_ID).0,
+                false,
+            ), // stats (writable)
+            AccountMeta::new_readonly(
+                Pubkey::find_program_address(&[b"config"], &PROGRAM_ID).0,
+                false,
+            ), // config
+        ],
+        data: instruction_data,
+    };
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&owner.pubkey()),
+        &[owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx).map(|_| ()).map_err(|f| f.err)
+}
+
+#[test]
+#[cfg(not(feature = "strict-parsing"))]
+fn test_trailing_bytes_are_ignored_by_default() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    deposit_with_trailing_bytes(&mut svm, &owner, LAMPORTS_PER_SOL)
+        .expect("Deposit with trailing bytes should succeed");
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    assert_eq!(svm.get_balance(&vault_pda), Some(LAMPORTS_PER_SOL));
+}
+
+#[test]
+#[cfg(feature = "strict-parsing")]
+fn test_trailing_bytes_are_rejected_under_strict_parsing() {
+    use solana_sdk::instruction::InstructionError;
+
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    assert_eq!(
+        deposit_with_trailing_bytes(&mut svm, &owner, LAMPORTS_PER_SOL),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::InvalidInstructionData
+        ))
+    );
+    assert!(svm.get_account(&vault_pda).is_none());
+}
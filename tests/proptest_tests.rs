@@ -0,0 +1,310 @@
+use blueshift_vault::testing::load_vault_program;
+use blueshift_vault::{
+    DepositInstructionData, VaultInstruction, WithdrawAmountInstructionData,
+    WithdrawInstructionData,
+};
+use litesvm::LiteSVM;
+use proptest::prelude::*;
+use proptest::test_runner::RngSeed;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+// System Program ID
+const SYSTEM_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("11111111111111111111111111111111");
+
+// Program ID from lib.rs
+const PROGRAM_ID: Pubkey = Pubkey::new_from_array(blueshift_vault::ID);
+
+// Lamports airdropped to the owner before each sequence
+const OWNER_FUNDING: u64 = 100 * LAMPORTS_PER_SOL;
+
+// Fixed seed so a failing sequence is reproduced (and shrunk) on every run
+const PROPTEST_SEED: u64 = 0x5eed;
+
+/// A single vault operation in a generated sequence
+#[derive(Clone, Debug)]
+enum Op {
+    Deposit(u64),
+    Withdraw { close: bool },
+    WithdrawAmount { amount: u64, mode: u8 },
+}
+
+fn op_strategy() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        (1..=3 * LAMPORTS_PER_SOL).prop_map(Op::Deposit),
+        any::<bool>().prop_map(|close| Op::Withdraw { close }),
+        (1..=2 * LAMPORTS_PER_SOL, 0..=1u8)
+            .prop_map(|(amount, mode)| Op::WithdrawAmount { amount, mode }),
+    ]
+}
+
+/// Helper function to set up LiteSVM with the program, a fee payer and a funded owner
+///
+/// Fees are charged to the separate payer, so owner and vault lamports are
+/// conserved exactly, apart from the owner paying rent for the stats account.
+fn setup() -> (LiteSVM, Keypair, Keypair) {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let payer = Keypair::new();
+    svm.airdrop(&payer.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), OWNER_FUNDING)
+        .expect("Failed to airdrop");
+
+    (svm, payer, owner)
+}
+
+/// Helper function to find the global stats PDA
+fn find_stats_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"stats"], &PROGRAM_ID)
+}
+
+/// Helper function to find vault PDA
+fn find_vault_pda(owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vault", owner.as_ref()], &PROGRAM_ID)
+}
+
+/// Helper function to find the config PDA
+fn find_config_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"config"], &PROGRAM_ID)
+}
+
+/// Helper function to build the instruction for an operation
+fn create_instruction(owner: Pubkey, vault: Pubkey, op: &Op) -> Instruction {
+    let instruction_data = match op {
+        Op::Deposit(amount) => {
+            let mut data = vec![0u8]; // Discriminator for Deposit
+            data.extend_from_slice(&amount.to_le_bytes());
+            data
+        }
+        Op::Withdraw { close } => {
+            let mut data = vec![1u8]; // Discriminator for Withdraw
+            data.extend_from_slice(&0u64.to_le_bytes());
+            data.push(*close as u8);
+            data
+        }
+        Op::WithdrawAmount { amount, mode } => {
+            let mut data = vec![3u8]; // Discriminator for WithdrawAmount
+            data.extend_from_slice(&amount.to_le_bytes());
+            data.push(*mode);
+            data
+        }
+    };
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(owner, true),                         // owner (signer, writable)
+            AccountMeta::new(vault, false),                        // vault (writable)
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),   // system program
+            AccountMeta::new(find_stats_pda().0, false),           // stats (writable)
+            AccountMeta::new_readonly(find_config_pda().0, false), // config
+        ],
+        data: instruction_data,
+    }
+}
+
+/// A generated account list entry: which of the candidate accounts, and whether writable
+fn account_strategy() -> impl Strategy<Value = (usize, bool)> {
+    (0..7usize, any::<bool>())
+}
+
+/// Helper function to build an instruction on an arbitrary account list, drawn
+/// from the accounts a Deposit or Withdraw of `owner`'s default vault take plus
+/// a stranger and the program itself
+fn create_malformed_instruction(
+    owner: Pubkey,
+    data: Vec<u8>,
+    accounts: &[(usize, bool)],
+) -> Instruction {
+    let candidates = [
+        owner,
+        find_vault_pda(&owner).0,
+        SYSTEM_PROGRAM_ID,
+        find_stats_pda().0,
+        find_config_pda().0,
+        Pubkey::new_unique(),
+        PROGRAM_ID,
+    ];
+
+    let accounts = accounts
+        .iter()
+        .map(|&(index, writable)| {
+            let key = candidates[index];
+            // Only the owner can sign, and signs wherever it appears
+            let signer = key == owner;
+            match writable || signer {
+                true => AccountMeta::new(key, signer),
+                false => AccountMeta::new_readonly(key, signer),
+            }
+        })
+        .collect();
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts,
+        data,
+    }
+}
+
+fn balance(svm: &LiteSVM, address: &Pubkey) -> u64 {
+    svm.get_account(address).map_or(0, |account| account.lamports)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: 32,
+        rng_seed: RngSeed::Fixed(PROPTEST_SEED),
+        failure_persistence: None,
+        ..ProptestConfig::default()
+    })]
+
+    #[test]
+    fn test_random_sequences_preserve_invariants(
+        ops in prop::collection::vec(op_strategy(), 1..16)
+    ) {
+        let (mut svm, payer, owner) = setup();
+        let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+        let (stats_pda, _bump) = find_stats_pda();
+        let rent_reserve = svm.minimum_balance_for_rent_exemption(0);
+
+        for op in &ops {
+            let vault_before = balance(&svm, &vault_pda);
+
+            // Identical operations in a row must not be deduplicated as the same transaction
+            svm.expire_blockhash();
+            let instruction = create_instruction(owner.pubkey(), vault_pda, op);
+            let tx = Transaction::new_signed_with_payer(
+                &[instruction],
+                Some(&payer.pubkey()),
+                &[&payer, &owner],
+                svm.latest_blockhash(),
+            );
+
+            let tx_result = svm.send_transaction(tx);
+
+            // A panic on-chain surfaces as `ProgramFailedToComplete`
+            if let Err(failed) = &tx_result {
+                prop_assert_ne!(
+                    &failed.err,
+                    &TransactionError::InstructionError(0, InstructionError::ProgramFailedToComplete),
+                    "{:?} should never panic", op
+                );
+            }
+
+            let owner_after = balance(&svm, &owner.pubkey());
+            let vault_after = balance(&svm, &vault_pda);
+            let stats_rent = balance(&svm, &stats_pda);
+
+            // Lamports only move between owner and vault, besides the owner funding the stats account
+            prop_assert_eq!(
+                owner_after + vault_after + stats_rent,
+                OWNER_FUNDING,
+                "Lamports should be conserved after {:?}", op
+            );
+
+            // A vault is either closed or rent-exempt, never partially drained below the reserve
+            prop_assert!(
+                vault_after == 0 || vault_after >= rent_reserve,
+                "Vault left with {} lamports after {:?}", vault_after, op
+            );
+
+            // Failed operations must not move lamports
+            if tx_result.is_err() {
+                prop_assert_eq!(vault_after, vault_before, "Failed {:?} changed the vault", op);
+            }
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: 256,
+        rng_seed: RngSeed::Fixed(PROPTEST_SEED),
+        failure_persistence: None,
+        ..ProptestConfig::default()
+    })]
+
+    #[test]
+    fn test_instruction_data_parsers_never_panic(
+        data in prop::collection::vec(any::<u8>(), 0..160)
+    ) {
+        // Errors are fine, a panic fails the test
+        let _ = VaultInstruction::unpack(&data);
+        let _ = DepositInstructionData::try_from(data.as_slice());
+        let _ = WithdrawInstructionData::try_from(data.as_slice());
+        let _ = WithdrawAmountInstructionData::try_from(data.as_slice());
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: 64,
+        rng_seed: RngSeed::Fixed(PROPTEST_SEED),
+        failure_persistence: None,
+        ..ProptestConfig::default()
+    })]
+
+    #[test]
+    fn test_malformed_instructions_fail_cleanly(
+        discriminator in prop_oneof![Just(0u8), Just(1u8), Just(3u8), any::<u8>()],
+        payload in prop::collection::vec(any::<u8>(), 0..96),
+        accounts in prop::collection::vec(account_strategy(), 0..8),
+    ) {
+        let (mut svm, payer, owner) = setup();
+        let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+
+        // Fund the vault first, so withdraw paths have something to reach for
+        let deposit =
+            create_instruction(owner.pubkey(), vault_pda, &Op::Deposit(LAMPORTS_PER_SOL));
+        let tx = Transaction::new_signed_with_payer(
+            &[deposit],
+            Some(&payer.pubkey()),
+            &[&payer, &owner],
+            svm.latest_blockhash(),
+        );
+        svm.send_transaction(tx).expect("Deposit should succeed");
+        let vault_before = balance(&svm, &vault_pda);
+
+        let mut data = vec![discriminator];
+        data.extend_from_slice(&payload);
+        let instruction = create_malformed_instruction(owner.pubkey(), data, &accounts);
+
+        // The owner only signs if the generated list includes it
+        let signs = instruction.accounts.iter().any(|meta| meta.is_signer);
+        let signers: Vec<&Keypair> = match signs {
+            true => vec![&payer, &owner],
+            false => vec![&payer],
+        };
+
+        svm.expire_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&payer.pubkey()),
+            &signers,
+            svm.latest_blockhash(),
+        );
+
+        // A panic on-chain surfaces as `ProgramFailedToComplete`
+        if let Err(failed) = svm.send_transaction(tx) {
+            prop_assert_ne!(
+                &failed.err,
+                &TransactionError::InstructionError(0, InstructionError::ProgramFailedToComplete),
+                "Malformed instruction should fail with an error, not a panic"
+            );
+            prop_assert_eq!(
+                balance(&svm, &vault_pda),
+                vault_before,
+                "Failed instruction changed the vault"
+            );
+        }
+    }
+}
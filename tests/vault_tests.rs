@@ -1,17 +1,89 @@
+use blueshift_vault::testing::{
+    load_vault_program, load_vault_program_from, send_transaction, set_upgrade_authority,
+    total_fees, LoadProgramError, VaultHarness,
+};
+use blueshift_vault::{
+    approval, client,
+    logic::SECONDS_PER_YEAR,
+    reward_pool::RewardPool,
+    state::VaultState,
+    token,
+    vault::{VaultAccounts, MAX_BATCH_VAULTS},
+    DepositAccounts, IntegrityStatus, VaultError, EXPORT_MAX_LEN,
+};
 use litesvm::LiteSVM;
 use solana_sdk::{
-    instruction::{AccountMeta, Instruction},
+    account::Account,
+    clock::Clock,
+    instruction::{AccountMeta, Instruction, InstructionError},
     native_token::LAMPORTS_PER_SOL,
     pubkey::Pubkey,
     signature::{Keypair, Signer},
-    transaction::Transaction,
+    transaction::{Transaction, TransactionError},
 };
 
 // System Program ID
 const SYSTEM_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("11111111111111111111111111111111");
 
+// Rent Sysvar ID
+const RENT_SYSVAR_ID: Pubkey = solana_sdk::pubkey!("SysvarRent111111111111111111111111111111111");
+
 // Program ID from lib.rs
-const PROGRAM_ID: Pubkey = solana_sdk::pubkey!("22222222222222222222222222222222222222222222");
+const PROGRAM_ID: Pubkey = Pubkey::new_from_array(blueshift_vault::ID);
+
+// Instructions Sysvar ID
+const INSTRUCTIONS_SYSVAR_ID: Pubkey =
+    solana_sdk::pubkey!("Sysvar1nstructions1111111111111111111111111");
+
+// Ed25519 signature verification precompile ID
+const ED25519_PROGRAM_ID: Pubkey = Pubkey::new_from_array(approval::ED25519_PROGRAM_ID);
+
+// SPL Token program ID, which LiteSVM loads by default
+const TOKEN_PROGRAM_ID: Pubkey = Pubkey::new_from_array(token::TOKEN_PROGRAM_ID);
+
+// SPL Associated Token Account program ID, which LiteSVM loads by default
+const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    Pubkey::new_from_array(token::ASSOCIATED_TOKEN_PROGRAM_ID);
+
+// Wrapped SOL mint, which LiteSVM creates by default
+const NATIVE_MINT: Pubkey = Pubkey::new_from_array(token::NATIVE_MINT);
+
+// The deployed `.so` is built for this address; fail to compile if lib.rs drifts from it
+const _: () = {
+    let expected = solana_sdk::pubkey!("22222222222222222222222222222222222222222222").to_bytes();
+    let mut i = 0;
+    while i < expected.len() {
+        assert!(PROGRAM_ID.to_bytes()[i] == expected[i], "Program id drifted");
+        i += 1;
+    }
+};
+
+/// Helper function to send `instruction` paid for and signed by `signer`,
+/// keeping only the transaction error
+fn send(
+    svm: &mut LiteSVM,
+    instruction: Instruction,
+    signer: &Keypair,
+) -> Result<(), TransactionError> {
+    send_all(svm, &[instruction], &[signer])
+}
+
+/// Helper function to send `instructions` in one transaction, paid for by the
+/// first of `signers` and signed by all of them
+fn send_all(
+    svm: &mut LiteSVM,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), TransactionError> {
+    send_transaction(svm, instructions, signers)
+        .map(|_| ())
+        .map_err(|f| f.err)
+}
+
+/// Helper function to find the global stats PDA
+fn find_stats_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"stats"], &PROGRAM_ID)
+}
 
 /// Helper function to find vault PDA
 fn find_vault_pda(owner: &Pubkey) -> (Pubkey, u8) {
@@ -29,388 +101,11326 @@ fn create_deposit_instruction(owner: Pubkey, vault: Pubkey, amount: u64) -> Inst
             AccountMeta::new(owner, true),           // owner (signer, writable)
             AccountMeta::new(vault, false),          // vault (writable)
             AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system program
+            AccountMeta::new(find_stats_pda().0, false),         // stats (writable)
+            AccountMeta::new_readonly(find_config_pda().0, false), // config
         ],
         data: instruction_data,
     }
 }
 
-/// Helper function to create withdraw instruction
-fn create_withdraw_instruction(owner: Pubkey, vault: Pubkey) -> Instruction {
-    let instruction_data = vec![1u8]; // Discriminator for Withdraw
+/// Helper function to find vault PDA for a specific vault id
+fn find_vault_pda_with_id(owner: &Pubkey, id: u64) -> (Pubkey, u8) {
+    if id == 0 {
+        return find_vault_pda(owner);
+    }
+    Pubkey::find_program_address(&[b"vault", owner.as_ref(), &id.to_le_bytes()], &PROGRAM_ID)
+}
+
+/// Helper function to create deposit instruction for a specific vault id
+fn create_deposit_instruction_with_id(
+    owner: Pubkey,
+    vault: Pubkey,
+    amount: u64,
+    id: u64,
+) -> Instruction {
+    let mut instruction = create_deposit_instruction(owner, vault, amount);
+    instruction.data.extend_from_slice(&id.to_le_bytes());
+    instruction
+}
+
+/// Helper function to create an idempotent deposit instruction for the default vault
+fn create_idempotent_deposit_instruction(owner: Pubkey, vault: Pubkey, amount: u64) -> Instruction {
+    let mut instruction = create_deposit_instruction_with_salt(owner, vault, amount, &[0u8; 32]);
+    instruction.data.push(1); // idempotent
+    instruction
+}
+
+/// Helper function to find the vault registry PDA
+fn find_registry_pda(owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"registry", owner.as_ref()], &PROGRAM_ID)
+}
+
+/// Helper function to create open vault instruction for the default vault
+fn create_open_vault_instruction(owner: Pubkey, vault: Pubkey) -> Instruction {
+    let (registry, _bump) = find_registry_pda(&owner);
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(owner, true),                       // owner (signer, writable)
+            AccountMeta::new(vault, false),                      // vault (writable)
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system program
+            AccountMeta::new(registry, false),                   // registry (writable)
+            AccountMeta::new(find_stats_pda().0, false),         // stats (writable)
+            AccountMeta::new_readonly(find_config_pda().0, false), // config
+        ],
+        data: vec![5u8], // Discriminator for OpenVault
+    }
+}
+
+/// Helper function to create open vault instruction for a specific vault id
+fn create_open_vault_instruction_with_id(owner: Pubkey, vault: Pubkey, id: u64) -> Instruction {
+    let mut instruction = create_open_vault_instruction(owner, vault);
+    instruction.data.extend_from_slice(&id.to_le_bytes());
+    instruction
+}
+
+/// Helper function to create open vault instruction for the default vault with `flags`
+fn create_open_vault_instruction_with_flags(owner: Pubkey, vault: Pubkey, flags: u8) -> Instruction {
+    let mut instruction = create_open_vault_instruction(owner, vault);
+    instruction.data.extend_from_slice(&0u64.to_le_bytes()); // default id
+    instruction.data.extend_from_slice(&[0u8; 32]); // no salt
+    instruction.data.push(flags);
+    instruction
+}
+
+/// Helper function to create open vault instruction for the default vault managed by `admin`
+fn create_open_vault_instruction_with_admin(
+    owner: Pubkey,
+    vault: Pubkey,
+    admin: Pubkey,
+) -> Instruction {
+    let mut instruction =
+        create_open_vault_instruction_with_flags(owner, vault, VaultState::FLAG_ADMIN);
+    instruction.data.extend_from_slice(admin.as_ref());
+    instruction
+}
+
+/// Helper function to create a wrapped-SOL deposit instruction for the default vault
+fn create_deposit_wrapped_sol_instruction(
+    owner: Pubkey,
+    vault: Pubkey,
+    wsol_account: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(owner, true),                       // owner (signer, writable)
+            AccountMeta::new(vault, false),                      // vault (writable)
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system program
+            AccountMeta::new(find_stats_pda().0, false),         // stats (writable)
+            AccountMeta::new(wsol_account, false),               // wSOL account (writable)
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),  // token program
+        ],
+        data: vec![6u8], // Discriminator for DepositWrappedSol
+    }
+}
+
+/// Helper function to find the vault's associated wSOL token account
+fn find_vault_wsol_ata(vault: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            vault.as_ref(),
+            TOKEN_PROGRAM_ID.as_ref(),
+            NATIVE_MINT.as_ref(),
+        ],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Helper function to create a deposit as wrapped instruction for the default vault
+fn create_deposit_as_wrapped_instruction(owner: Pubkey, vault: Pubkey, amount: u64) -> Instruction {
+    let mut instruction_data = vec![44u8]; // Discriminator for DepositAsWrapped
+    instruction_data.extend_from_slice(&amount.to_le_bytes());
 
     Instruction {
         program_id: PROGRAM_ID,
         accounts: vec![
             AccountMeta::new(owner, true),           // owner (signer, writable)
             AccountMeta::new(vault, false),          // vault (writable)
+            AccountMeta::new(find_vault_wsol_ata(&vault), false), // vault wSOL account (writable)
+            AccountMeta::new_readonly(NATIVE_MINT, false), // native mint
             AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system program
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false), // token program
+            AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false), // ATA program
         ],
         data: instruction_data,
     }
 }
 
-#[test]
-fn test_deposit_success() {
-    // Setup LiteSVM
-    let mut svm = LiteSVM::new();
-    
-    // Load the program
-    let program_bytes =
-        std::fs::read("target/deploy/blueshift_vault.so").expect("Failed to read program file");
-    svm.add_program(PROGRAM_ID, &program_bytes)
-        .expect("Failed to add program");
+/// Helper function to write an initialized wSOL token account holding `amount` for `owner`
+fn set_wrapped_sol_account(svm: &mut LiteSVM, address: Pubkey, owner: &Pubkey, amount: u64) {
+    let rent = svm.minimum_balance_for_rent_exemption(token::TOKEN_ACCOUNT_LEN);
 
-    // Create owner keypair and fund it
-    let owner = Keypair::new();
-    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
-        .expect("Failed to airdrop");
+    // `[mint][owner][amount][delegate][state][is_native][delegated_amount][close_authority]`
+    let mut data = vec![0u8; token::TOKEN_ACCOUNT_LEN];
+    data[..32].copy_from_slice(&token::NATIVE_MINT);
+    data[32..64].copy_from_slice(owner.as_ref());
+    data[64..72].copy_from_slice(&amount.to_le_bytes());
+    data[108] = 1; // initialized
+    data[109..113].copy_from_slice(&1u32.to_le_bytes());
+    data[113..121].copy_from_slice(&rent.to_le_bytes());
 
-    // Find vault PDA
-    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    svm.set_account(
+        address,
+        Account {
+            lamports: amount + rent,
+            data,
+            owner: TOKEN_PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    )
+    .expect("Failed to set wSOL account");
+}
 
-    // Get initial balances
-    let owner_initial_balance = svm
-        .get_account(&owner.pubkey())
-        .expect("Owner account should exist")
-        .lamports;
+/// Helper function to set an initialized token account of `mint` owned by `owner`
+fn set_token_account(
+    svm: &mut LiteSVM,
+    address: Pubkey,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+) {
+    let rent = svm.minimum_balance_for_rent_exemption(token::TOKEN_ACCOUNT_LEN);
 
-    // Create deposit instruction
-    let deposit_amount = 2 * LAMPORTS_PER_SOL;
-    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, deposit_amount);
+    let mut data = vec![0u8; token::TOKEN_ACCOUNT_LEN];
+    data[..32].copy_from_slice(mint.as_ref());
+    data[32..64].copy_from_slice(owner.as_ref());
+    data[64..72].copy_from_slice(&amount.to_le_bytes());
+    data[108] = 1; // initialized
 
-    // Create and send transaction
-    let recent_blockhash = svm.latest_blockhash();
-    let tx = Transaction::new_signed_with_payer(
-        &[deposit_ix],
-        Some(&owner.pubkey()),
-        &[&owner],
-        recent_blockhash,
-    );
+    svm.set_account(
+        address,
+        Account {
+            lamports: rent,
+            data,
+            owner: TOKEN_PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    )
+    .expect("Failed to set token account");
+}
 
-    let tx_result = svm.send_transaction(tx);
-    assert!(tx_result.is_ok(), "Deposit transaction should succeed");
+/// Returns the token balance of the token account at `address`
+fn token_balance(svm: &LiteSVM, address: &Pubkey) -> u64 {
+    let account = svm.get_account(address).expect("token account should exist");
+    u64::from_le_bytes(account.data[64..72].try_into().unwrap())
+}
 
-    // Verify vault balance
-    let vault_account = svm
-        .get_account(&vault_pda)
-        .expect("Vault account should exist");
-    assert_eq!(
-        vault_account.lamports, deposit_amount,
-        "Vault should contain the deposited amount"
-    );
+/// Helper function to create a verify integrity instruction for an unsalted vault
+fn create_verify_integrity_instruction(vault: Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![AccountMeta::new_readonly(vault, false)], // vault
+        data: vec![7u8], // Discriminator for VerifyIntegrity
+    }
+}
 
-    // Verify owner balance decreased
-    let owner_final_balance = svm
-        .get_account(&owner.pubkey())
-        .expect("Owner account should exist")
-        .lamports;
-    
-    // Owner balance should decrease by at least the deposit amount (plus transaction fees)
-    assert!(
-        owner_final_balance < owner_initial_balance - deposit_amount,
-        "Owner balance should decrease"
+/// Helper function to create open vault instruction for the default vault with a label
+fn create_open_vault_instruction_with_label(
+    owner: Pubkey,
+    vault: Pubkey,
+    label: &[u8],
+) -> Instruction {
+    let mut instruction = create_open_vault_instruction_with_flags(owner, vault, 0);
+    instruction.data.extend_from_slice(label);
+    instruction
+}
+
+/// Helper function to create set deposits locked instruction
+fn create_set_deposits_locked_instruction(
+    owner: Pubkey,
+    vault: Pubkey,
+    locked: bool,
+) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(owner, true), // owner (signer)
+            AccountMeta::new(vault, false),         // vault (writable)
+        ],
+        data: vec![26u8, locked as u8], // Discriminator for SetDepositsLocked
+    }
+}
+
+/// Helper function to create an ed25519 precompile instruction verifying `signer`'s
+/// signature over `message`
+fn create_ed25519_verify_instruction(signer: &Keypair, message: &[u8]) -> Instruction {
+    // `[num_signatures][padding][offsets][public_key][signature][message]`, with
+    // every offset pointing into this instruction (`u16::MAX`)
+    let (public_key_offset, signature_offset, message_offset) = (16u16, 48u16, 112u16);
+    let mut data = vec![1u8, 0];
+    for field in [
+        signature_offset,
+        u16::MAX,
+        public_key_offset,
+        u16::MAX,
+        message_offset,
+        message.len() as u16,
+        u16::MAX,
+    ] {
+        data.extend_from_slice(&field.to_le_bytes());
+    }
+    data.extend_from_slice(signer.pubkey().as_ref());
+    data.extend_from_slice(signer.sign_message(message).as_ref());
+    data.extend_from_slice(message);
+
+    Instruction {
+        program_id: ED25519_PROGRAM_ID,
+        accounts: vec![],
+        data,
+    }
+}
+
+/// Helper function to create withdraw with approval instruction for the default vault
+fn create_withdraw_with_approval_instruction(
+    relayer: Pubkey,
+    owner: Pubkey,
+    vault: Pubkey,
+    amount: u64,
+    nonce: u64,
+    expiry_ts: i64,
+) -> Instruction {
+    let mut instruction_data = vec![27u8]; // Discriminator for WithdrawWithApproval
+    instruction_data.extend_from_slice(&amount.to_le_bytes());
+    instruction_data.extend_from_slice(&nonce.to_le_bytes());
+    instruction_data.extend_from_slice(&expiry_ts.to_le_bytes());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(relayer, true),                     // relayer (signer, writable)
+            AccountMeta::new(owner, false),                      // owner (writable)
+            AccountMeta::new(vault, false),                      // vault (writable)
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system program
+            AccountMeta::new(find_stats_pda().0, false),         // stats (writable)
+            AccountMeta::new_readonly(INSTRUCTIONS_SYSVAR_ID, false), // instructions sysvar
+            AccountMeta::new_readonly(find_config_pda().0, false), // config
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create withdraw all with tip instruction for the default vault
+fn create_withdraw_all_with_tip_instruction(
+    relayer: Pubkey,
+    owner: Pubkey,
+    vault: Pubkey,
+    tip: u64,
+    nonce: u64,
+    expiry_ts: i64,
+) -> Instruction {
+    // `[tip][nonce][expiry_ts]`, laid out like WithdrawWithApproval's `[amount][nonce][expiry_ts]`
+    let mut instruction =
+        create_withdraw_with_approval_instruction(relayer, owner, vault, tip, nonce, expiry_ts);
+    instruction.data[0] = 35; // Discriminator for WithdrawAllWithTip
+    instruction
+}
+
+/// Helper function to create fund gas reserve instruction for an opened vault
+fn create_fund_gas_reserve_instruction(funder: Pubkey, vault: Pubkey, amount: u64) -> Instruction {
+    let mut instruction_data = vec![53u8]; // Discriminator for FundGasReserve
+    instruction_data.extend_from_slice(&amount.to_le_bytes());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(funder, true), // funder (signer, writable)
+            AccountMeta::new(vault, false), // vault (writable)
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system program
+            AccountMeta::new(find_stats_pda().0, false), // stats (writable)
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create set metadata instruction for an opened vault
+fn create_set_metadata_instruction(owner: Pubkey, vault: Pubkey, memo: &[u8]) -> Instruction {
+    let mut instruction_data = vec![36u8]; // Discriminator for SetMetadata
+    instruction_data.extend_from_slice(memo);
+    let (metadata, _bump) = client::find_metadata_address(&vault);
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(owner, true),                       // owner (signer, writable)
+            AccountMeta::new_readonly(vault, false),             // vault
+            AccountMeta::new(metadata, false),                   // metadata (writable)
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system program
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create deposit with metadata instruction that opens and funds the empty default vault
+fn create_deposit_with_metadata_instruction(
+    owner: Pubkey,
+    vault: Pubkey,
+    amount: u64,
+    memo: &[u8],
+) -> Instruction {
+    let mut instruction = create_opening_deposit_instruction(owner, vault, amount);
+    let mut instruction_data = vec![71u8, memo.len() as u8]; // Discriminator for DepositWithMetadata
+    instruction_data.extend_from_slice(memo);
+    instruction_data.extend_from_slice(&instruction.data[1..]);
+    instruction.data = instruction_data;
+    let (metadata, _bump) = client::find_metadata_address(&vault);
+    instruction
+        .accounts
+        .insert(0, AccountMeta::new(metadata, false)); // metadata (writable)
+    instruction
+}
+
+/// Helper function to create set frozen instruction
+fn create_set_frozen_instruction(owner: Pubkey, vault: Pubkey, frozen: bool) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(owner, true), // owner (signer)
+            AccountMeta::new(vault, false),         // vault (writable)
+        ],
+        data: vec![37u8, frozen as u8], // Discriminator for SetFrozen
+    }
+}
+
+/// Helper function to create a set daily limit instruction signed by the vault's admin
+fn create_set_daily_limit_instruction(
+    admin: Pubkey,
+    vault: Pubkey,
+    daily_limit: u64,
+) -> Instruction {
+    let mut instruction_data = vec![67u8]; // Discriminator for SetDailyLimit
+    instruction_data.extend_from_slice(&daily_limit.to_le_bytes());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(admin, true), // admin (signer)
+            AccountMeta::new(vault, false),         // vault (writable)
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create a set drip instruction for an opened vault
+fn create_set_drip_instruction(
+    owner: Pubkey,
+    vault: Pubkey,
+    interval_secs: u64,
+    amount_per_interval: u64,
+    recipient: Pubkey,
+) -> Instruction {
+    let mut instruction_data = vec![46u8]; // Discriminator for SetDrip
+    instruction_data.extend_from_slice(&interval_secs.to_le_bytes());
+    instruction_data.extend_from_slice(&amount_per_interval.to_le_bytes());
+    instruction_data.extend_from_slice(recipient.as_ref());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(owner, true), // owner (signer)
+            AccountMeta::new(vault, false),         // vault (writable)
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create a crank instruction releasing a vault's drip
+fn create_crank_instruction(cranker: Pubkey, vault: Pubkey, recipient: Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(cranker, true),    // cranker (signer, writable)
+            AccountMeta::new(vault, false),     // vault (writable)
+            AccountMeta::new(recipient, false), // recipient (writable)
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system program
+            AccountMeta::new(find_stats_pda().0, false), // stats (writable)
+            AccountMeta::new_readonly(find_config_pda().0, false), // config
+        ],
+        data: vec![47u8], // Discriminator for Crank
+    }
+}
+
+/// Helper function to create set expiry instruction for an opened vault
+fn create_set_expiry_instruction(owner: Pubkey, vault: Pubkey, expiry_ts: i64) -> Instruction {
+    let mut data = vec![51u8]; // Discriminator for SetExpiry
+    data.extend_from_slice(&expiry_ts.to_le_bytes());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(owner, true), // owner (signer)
+            AccountMeta::new(vault, false),         // vault (writable)
+        ],
+        data,
+    }
+}
+
+/// Helper function to create withdraw expired instruction, signed by anyone
+fn create_withdraw_expired_instruction(
+    caller: Pubkey,
+    owner: Pubkey,
+    vault: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(caller, true), // caller (signer, writable)
+            AccountMeta::new(owner, false), // owner (writable)
+            AccountMeta::new(vault, false), // vault (writable)
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system program
+            AccountMeta::new(find_stats_pda().0, false), // stats (writable)
+            AccountMeta::new_readonly(find_config_pda().0, false), // config
+        ],
+        data: vec![52u8], // Discriminator for WithdrawExpired
+    }
+}
+
+/// Helper function to create a set beneficiary instruction for an opened vault
+fn create_set_beneficiary_instruction(
+    owner: Pubkey,
+    vault: Pubkey,
+    beneficiary: Pubkey,
+    inactivity_secs: u64,
+) -> Instruction {
+    let mut data = vec![62u8]; // Discriminator for SetBeneficiary
+    data.extend_from_slice(beneficiary.as_ref());
+    data.extend_from_slice(&inactivity_secs.to_le_bytes());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(owner, true), // owner (signer)
+            AccountMeta::new(vault, false),         // vault (writable)
+        ],
+        data,
+    }
+}
+
+/// Helper function to create a beneficiary claim instruction, signed by the beneficiary
+fn create_beneficiary_claim_instruction(
+    beneficiary: Pubkey,
+    owner: Pubkey,
+    vault: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(beneficiary, true), // beneficiary (signer, writable)
+            AccountMeta::new_readonly(owner, false), // owner
+            AccountMeta::new(vault, false),      // vault (writable)
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system program
+            AccountMeta::new(find_stats_pda().0, false), // stats (writable)
+            AccountMeta::new_readonly(find_config_pda().0, false), // config
+        ],
+        data: vec![63u8], // Discriminator for BeneficiaryClaim
+    }
+}
+
+/// Helper function to create reconcile instruction for an opened vault
+fn create_reconcile_instruction(owner: Pubkey, vault: Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(owner, true), // owner (signer)
+            AccountMeta::new(vault, false),         // vault (writable)
+        ],
+        data: vec![38u8], // Discriminator for Reconcile
+    }
+}
+
+/// Helper function to create a set paused instruction signed by the config's admin
+fn create_set_paused_instruction(admin: Pubkey, paused: bool) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(admin, true),       // admin (signer)
+            AccountMeta::new(find_config_pda().0, false), // config (writable)
+        ],
+        data: vec![39u8, paused as u8], // Discriminator for SetPaused
+    }
+}
+
+/// Helper function to create a set withdraw fee instruction signed by the config's admin
+fn create_set_withdraw_fee_instruction(
+    admin: Pubkey,
+    fee_bps: u16,
+    treasury: Pubkey,
+    exempt: &[Pubkey],
+) -> Instruction {
+    let mut instruction_data = vec![61u8]; // Discriminator for SetWithdrawFee
+    instruction_data.extend_from_slice(&fee_bps.to_le_bytes());
+    instruction_data.extend_from_slice(treasury.as_ref());
+    for owner in exempt {
+        instruction_data.extend_from_slice(owner.as_ref());
+    }
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(admin, true),       // admin (signer)
+            AccountMeta::new(find_config_pda().0, false), // config (writable)
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create an emergency drain instruction for an owner's default vault
+fn create_emergency_drain_instruction(
+    admin: Pubkey,
+    owner: Pubkey,
+    vault: Pubkey,
+    recovery: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(admin, true), // admin (signer, writable)
+            AccountMeta::new_readonly(find_config_pda().0, false), // config
+            AccountMeta::new_readonly(owner, false), // owner
+            AccountMeta::new(vault, false), // vault (writable)
+            AccountMeta::new(recovery, false), // recovery (writable)
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system program
+            AccountMeta::new(find_stats_pda().0, false), // stats (writable)
+        ],
+        data: vec![40u8], // Discriminator for EmergencyDrain
+    }
+}
+
+/// Helper function to create an admin recover instruction for `owner`'s default vault
+fn create_admin_recover_instruction(
+    admin: Pubkey,
+    owner: Pubkey,
+    vault: Pubkey,
+    recovery: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(admin, true), // admin (signer, writable)
+            AccountMeta::new_readonly(find_config_pda().0, false), // config
+            AccountMeta::new_readonly(owner, false), // owner
+            AccountMeta::new(vault, false), // vault (writable)
+            AccountMeta::new(recovery, false), // recovery (writable)
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system program
+            AccountMeta::new(find_stats_pda().0, false), // stats (writable)
+        ],
+        data: vec![68u8], // Discriminator for AdminRecover
+    }
+}
+
+/// Helper function to create a propose admin instruction signed by the config's admin
+fn create_propose_admin_instruction(admin: Pubkey, pending_admin: Pubkey) -> Instruction {
+    let mut instruction_data = vec![41u8]; // Discriminator for ProposeAdmin
+    instruction_data.extend_from_slice(pending_admin.as_ref());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(admin, true),       // admin (signer)
+            AccountMeta::new(find_config_pda().0, false), // config (writable)
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create an accept admin instruction signed by the proposed admin
+fn create_accept_admin_instruction(pending_admin: Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(pending_admin, true), // pending admin (signer)
+            AccountMeta::new(find_config_pda().0, false),   // config (writable)
+        ],
+        data: vec![42u8], // Discriminator for AcceptAdmin
+    }
+}
+
+/// Helper function to find an owner's tag index PDA
+fn find_tag_index_pda(owner: &Pubkey, tag: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"tag", owner.as_ref(), &tag.to_le_bytes()], &PROGRAM_ID)
+}
+
+/// Helper function to create tag vault instruction
+fn create_tag_vault_instruction(owner: Pubkey, vault: Pubkey, tag: u32) -> Instruction {
+    let mut instruction_data = vec![48u8]; // Discriminator for TagVault
+    instruction_data.extend_from_slice(&tag.to_le_bytes());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(owner, true),           // owner (signer, writable)
+            AccountMeta::new_readonly(vault, false), // vault
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system program
+            AccountMeta::new(find_tag_index_pda(&owner, tag).0, false), // tag index (writable)
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create derive vault instruction, taking no accounts
+fn create_derive_vault_instruction(owner: Pubkey, id: u64, salt: &[u8; 32]) -> Instruction {
+    let mut instruction_data = vec![56u8]; // Discriminator for DeriveVault
+    instruction_data.extend_from_slice(owner.as_ref());
+    instruction_data.extend_from_slice(&id.to_le_bytes());
+    instruction_data.extend_from_slice(salt);
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create get info instruction
+fn create_get_info_instruction(vault: Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![AccountMeta::new_readonly(vault, false)], // vault
+        data: vec![28u8], // Discriminator for GetInfo
+    }
+}
+
+/// Helper function to create can withdraw instruction
+fn create_can_withdraw_instruction(vault: Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![AccountMeta::new_readonly(vault, false)], // vault
+        data: vec![50u8],                                        // Discriminator for CanWithdraw
+    }
+}
+
+/// Helper function to create a get info many instruction over `vaults`
+fn create_get_info_many_instruction(vaults: &[Pubkey]) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vaults
+            .iter()
+            .map(|vault| AccountMeta::new_readonly(*vault, false)) // vaults
+            .collect(),
+        data: vec![45u8], // Discriminator for GetInfoMany
+    }
+}
+
+/// Helper function to create migrate vault instruction between two unsalted vault ids
+fn create_migrate_vault_instruction(owner: Pubkey, old_id: u64, new_id: u64) -> Instruction {
+    let mut instruction_data = vec![29u8]; // Discriminator for MigrateVault
+    instruction_data.extend_from_slice(&old_id.to_le_bytes());
+    instruction_data.extend_from_slice(&[0u8; 32]); // old salt
+    instruction_data.extend_from_slice(&new_id.to_le_bytes());
+    instruction_data.extend_from_slice(&[0u8; 32]); // new salt
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(owner, true), // owner (signer, writable)
+            AccountMeta::new(find_vault_pda_with_id(&owner, old_id).0, false), // old vault
+            AccountMeta::new(find_vault_pda_with_id(&owner, new_id).0, false), // new vault
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system program
+            AccountMeta::new(find_registry_pda(&owner).0, false), // registry (writable)
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create deposit locked instruction
+fn create_deposit_locked_instruction(
+    owner: Pubkey,
+    vault: Pubkey,
+    amount: u64,
+    unlock_ts: i64,
+) -> Instruction {
+    let mut instruction_data = vec![30u8]; // Discriminator for DepositLocked
+    instruction_data.extend_from_slice(&amount.to_le_bytes());
+    instruction_data.extend_from_slice(&unlock_ts.to_le_bytes());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(owner, true),                         // owner (signer, writable)
+            AccountMeta::new(vault, false),                        // vault (writable)
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),   // system program
+            AccountMeta::new(find_stats_pda().0, false),           // stats (writable)
+            AccountMeta::new_readonly(find_config_pda().0, false), // config
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create split vault instruction between unsalted vaults
+fn create_split_vault_instruction(
+    owner: Pubkey,
+    amount: u64,
+    source_id: u64,
+    new_id: u64,
+) -> Instruction {
+    let mut instruction_data = vec![31u8]; // Discriminator for SplitVault
+    instruction_data.extend_from_slice(&amount.to_le_bytes());
+    instruction_data.extend_from_slice(&source_id.to_le_bytes());
+    instruction_data.extend_from_slice(&[0u8; 32]); // source salt
+    instruction_data.extend_from_slice(&new_id.to_le_bytes());
+    instruction_data.extend_from_slice(&[0u8; 32]); // new salt
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(owner, true), // owner (signer, writable)
+            AccountMeta::new(find_vault_pda_with_id(&owner, source_id).0, false), // source vault
+            AccountMeta::new(find_vault_pda_with_id(&owner, new_id).0, false), // new vault
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system program
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create set token dest owner instruction
+fn create_set_token_dest_owner_instruction(
+    owner: Pubkey,
+    vault: Pubkey,
+    dest_owner: &Pubkey,
+) -> Instruction {
+    let mut instruction_data = vec![32u8]; // Discriminator for SetTokenDestOwner
+    instruction_data.extend_from_slice(dest_owner.as_ref());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(owner, true), // owner (signer)
+            AccountMeta::new(vault, false),         // vault (writable)
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create set escrow agent instruction
+fn create_set_escrow_agent_instruction(
+    owner: Pubkey,
+    vault: Pubkey,
+    agent: &Pubkey,
+) -> Instruction {
+    let mut instruction_data = vec![33u8]; // Discriminator for SetEscrowAgent
+    instruction_data.extend_from_slice(agent.as_ref());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(owner, true), // owner (signer)
+            AccountMeta::new(vault, false),         // vault (writable)
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create upgrade to multisig instruction
+fn create_upgrade_to_multisig_instruction(
+    owner: Pubkey,
+    vault: Pubkey,
+    threshold: u8,
+    signers: &[Pubkey],
+) -> Instruction {
+    let mut instruction_data = vec![49u8, threshold]; // Discriminator for UpgradeToMultisig
+    for signer in signers {
+        instruction_data.extend_from_slice(signer.as_ref());
+    }
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(owner, true), // owner (signer)
+            AccountMeta::new(vault, false),         // vault (writable)
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create create multisig instruction for the default vault
+fn create_create_multisig_instruction(
+    owner: Pubkey,
+    vault: Pubkey,
+    threshold: u8,
+    signers: &[Pubkey],
+) -> Instruction {
+    let mut instruction = create_open_vault_instruction(owner, vault);
+    instruction.data[0] = 66; // Discriminator for CreateMultisig
+    instruction.data.extend_from_slice(&0u64.to_le_bytes()); // default id
+    instruction.data.extend_from_slice(&[0u8; 32]); // no salt
+    instruction.data.push(threshold);
+    for signer in signers {
+        instruction.data.extend_from_slice(signer.as_ref());
+    }
+    instruction
+}
+
+/// Helper function to create a withdraw instruction for a multisig vault,
+/// passing every signer and getting signatures from those flagged
+fn create_multisig_withdraw_instruction(
+    owner: Pubkey,
+    vault: Pubkey,
+    signers: &[(Pubkey, bool)],
+) -> Instruction {
+    let mut instruction = create_withdraw_instruction(owner, vault);
+    instruction.accounts[0].is_signer = false;
+    for (signer, signs) in signers {
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(*signer, *signs)); // multisig signer
+    }
+    instruction
+}
+
+/// Helper function to create fund reward pool instruction
+fn create_fund_reward_pool_instruction(funder: Pubkey, amount: u64) -> Instruction {
+    let mut instruction_data = vec![34u8]; // Discriminator for FundRewardPool
+    instruction_data.extend_from_slice(&amount.to_le_bytes());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(funder, true), // funder (signer, writable)
+            AccountMeta::new(client::find_reward_pool_address().0, false), // reward pool
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system program
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create a set reward rate instruction signed by the config's admin
+fn create_set_reward_rate_instruction(admin: Pubkey, vault: Pubkey, rate_bps: u16) -> Instruction {
+    let mut instruction_data = vec![64u8]; // Discriminator for SetRewardRate
+    instruction_data.extend_from_slice(&rate_bps.to_le_bytes());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(admin, true), // admin (signer)
+            AccountMeta::new_readonly(find_config_pda().0, false), // config
+            AccountMeta::new(vault, false),         // vault (writable)
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create a claim reward instruction for an opened vault
+fn create_claim_reward_instruction(owner: Pubkey, vault: Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(owner, true),  // owner (signer, writable)
+            AccountMeta::new(vault, false), // vault (writable)
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system program
+            AccountMeta::new(find_stats_pda().0, false), // stats (writable)
+            AccountMeta::new(client::find_reward_pool_address().0, false), // reward pool (writable)
+        ],
+        data: vec![65u8], // Discriminator for ClaimReward
+    }
+}
+
+/// Helper function to set an initialized receipt mint whose mint authority is `vault`
+fn set_receipt_mint(svm: &mut LiteSVM, address: Pubkey, vault: &Pubkey) {
+    let rent = svm.minimum_balance_for_rent_exemption(token::MINT_LEN);
+
+    let mut data = vec![0u8; token::MINT_LEN];
+    data[..4].copy_from_slice(&1u32.to_le_bytes()); // mint authority is set
+    data[4..36].copy_from_slice(vault.as_ref());
+    data[44] = 9; // decimals
+    data[45] = 1; // initialized
+
+    svm.set_account(
+        address,
+        Account {
+            lamports: rent,
+            data,
+            owner: TOKEN_PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    )
+    .expect("Failed to set receipt mint");
+}
+
+/// Helper function to create a deposit into the default vault, opening it if it
+/// isn't yet, that mints receipt tokens
+fn create_receipt_deposit_instruction(
+    owner: Pubkey,
+    vault: Pubkey,
+    amount: u64,
+    mint: Pubkey,
+    receipt_account: Pubkey,
+) -> Instruction {
+    let mut instruction = create_deposit_instruction_with_salt(owner, vault, amount, &[0u8; 32]);
+    instruction.data.push(
+        blueshift_vault::DepositInstructionData::FLAG_RECEIPT
+            | blueshift_vault::DepositInstructionData::FLAG_OPEN,
+    );
+    instruction.accounts.extend([
+        AccountMeta::new(mint, false),                      // receipt mint (writable)
+        AccountMeta::new(receipt_account, false),           // receipt account (writable)
+        AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false), // token program
+    ]);
+    instruction
+}
+
+/// Helper function to create a withdraw from the default vault that burns receipt tokens
+fn create_receipt_withdraw_instruction(
+    owner: Pubkey,
+    vault: Pubkey,
+    mint: Pubkey,
+    receipt_account: Pubkey,
+) -> Instruction {
+    let mut instruction = create_withdraw_instruction_with_flags(owner, vault, 0, true);
+    instruction.data.extend_from_slice(&[0u8; 32]); // salt
+    instruction.data.extend_from_slice(&0u64.to_le_bytes()); // min_out
+    instruction.data.extend_from_slice(&0u64.to_le_bytes()); // keep_alive
+    instruction.data.push(1); // burn receipts
+    instruction.accounts.splice(
+        4..4,
+        [
+            AccountMeta::new(mint, false),                      // receipt mint (writable)
+            AccountMeta::new(receipt_account, false),           // receipt account (writable)
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false), // token program
+        ],
+    );
+    instruction
+}
+
+/// Helper function to create set label instruction
+fn create_set_label_instruction(owner: Pubkey, vault: Pubkey, label: &[u8]) -> Instruction {
+    let mut instruction_data = vec![8u8]; // Discriminator for SetLabel
+    instruction_data.extend_from_slice(label);
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(owner, true), // owner (signer)
+            AccountMeta::new(vault, false),         // vault (writable)
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create set oracle instruction
+fn create_set_oracle_instruction(
+    owner: Pubkey,
+    vault: Pubkey,
+    oracle: Pubkey,
+    max_price_age: u64,
+) -> Instruction {
+    let mut instruction_data = vec![9u8]; // Discriminator for SetOracle
+    instruction_data.extend_from_slice(oracle.as_ref());
+    instruction_data.extend_from_slice(&max_price_age.to_le_bytes());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(owner, true), // owner (signer)
+            AccountMeta::new(vault, false),         // vault (writable)
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to write a mock price account published at `publish_time`
+fn set_mock_oracle(svm: &mut LiteSVM, oracle: Pubkey, publish_time: i64) {
+    // `[price: i64][publish_time: i64]`
+    let mut data = 150i64.to_le_bytes().to_vec();
+    data.extend_from_slice(&publish_time.to_le_bytes());
+
+    svm.set_account(
+        oracle,
+        Account {
+            lamports: svm.minimum_balance_for_rent_exemption(data.len()),
+            data,
+            owner: Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    )
+    .expect("Failed to set oracle account");
+}
+
+/// Helper function to create withdraw instruction
+fn create_withdraw_instruction(owner: Pubkey, vault: Pubkey) -> Instruction {
+    let instruction_data = vec![1u8]; // Discriminator for Withdraw
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(owner, true),           // owner (signer, writable)
+            AccountMeta::new(vault, false),          // vault (writable)
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system program
+            AccountMeta::new(find_stats_pda().0, false),         // stats (writable)
+            AccountMeta::new_readonly(find_config_pda().0, false), // config
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create withdraw instruction with an explicit id and close flag
+fn create_withdraw_instruction_with_flags(
+    owner: Pubkey,
+    vault: Pubkey,
+    id: u64,
+    close: bool,
+) -> Instruction {
+    let mut instruction = create_withdraw_instruction(owner, vault);
+    instruction.data.extend_from_slice(&id.to_le_bytes());
+    instruction.data.push(close as u8);
+    instruction
+}
+
+/// Helper function to find the default-id vault PDA for a salt
+fn find_vault_pda_with_salt(owner: &Pubkey, salt: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vault", owner.as_ref(), salt], &PROGRAM_ID)
+}
+
+/// Helper function to create deposit instruction into a salted default-id vault
+fn create_deposit_instruction_with_salt(
+    owner: Pubkey,
+    vault: Pubkey,
+    amount: u64,
+    salt: &[u8; 32],
+) -> Instruction {
+    let mut instruction = create_deposit_instruction_with_id(owner, vault, amount, 0);
+    instruction.data.extend_from_slice(salt);
+    instruction
+}
+
+/// Helper function to create a deposit instruction for the default vault that starts vesting
+fn create_vesting_deposit_instruction(
+    owner: Pubkey,
+    vault: Pubkey,
+    amount: u64,
+    vesting_duration: u64,
+) -> Instruction {
+    let mut instruction = create_deposit_instruction_with_salt(owner, vault, amount, &[0u8; 32]);
+    instruction.data.push(0); // not idempotent
+    instruction
+        .data
+        .extend_from_slice(&vesting_duration.to_le_bytes());
+    instruction
+}
+
+/// Helper function to create a deposit instruction that opens the empty default vault first
+fn create_opening_deposit_instruction(owner: Pubkey, vault: Pubkey, amount: u64) -> Instruction {
+    let mut instruction = create_deposit_instruction_with_salt(owner, vault, amount, &[0u8; 32]);
+    instruction.data.push(0b10); // open flag
+    instruction
+}
+
+/// Helper function to find the config PDA
+fn find_config_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"config"], &PROGRAM_ID)
+}
+
+/// Helper function to create initialize instruction for the config
+fn create_initialize_instruction(admin: Pubkey, referral_bps: u16) -> Instruction {
+    let mut instruction_data = vec![12u8]; // Discriminator for Initialize
+    instruction_data.extend_from_slice(&referral_bps.to_le_bytes());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(admin, true),                       // admin (signer, writable)
+            AccountMeta::new(find_config_pda().0, false),        // config (writable)
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system program
+            AccountMeta::new_readonly(client::find_program_data_address().0, false), // program data
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create a deposit instruction for the default vault that pays a referrer
+fn create_referred_deposit_instruction(
+    owner: Pubkey,
+    vault: Pubkey,
+    amount: u64,
+    referrer: Pubkey,
+) -> Instruction {
+    let mut instruction = create_vesting_deposit_instruction(owner, vault, amount, 0);
+    instruction.data.extend_from_slice(referrer.as_ref());
+    instruction.accounts.push(AccountMeta::new(referrer, false)); // referrer (writable)
+    instruction
+}
+
+/// Helper function to create withdraw vested instruction for the default vault
+fn create_withdraw_vested_instruction(owner: Pubkey, vault: Pubkey) -> Instruction {
+    let mut instruction = create_withdraw_instruction(owner, vault);
+    instruction.data = vec![10u8]; // Discriminator for WithdrawVested
+    instruction
+}
+
+/// Helper function to create simulate withdraw instruction for the default vault
+fn create_simulate_withdraw_instruction(owner: Pubkey, vault: Pubkey) -> Instruction {
+    let mut instruction = create_withdraw_instruction(owner, vault);
+    instruction.data = vec![11u8]; // Discriminator for SimulateWithdraw
+    instruction
+}
+
+/// Splits SimulateWithdraw return data into the would-be error code and lamports
+fn simulated_withdraw(return_data: &[u8]) -> (u64, u64) {
+    assert_eq!(return_data.len(), 16, "Simulation should return 16 bytes");
+    (
+        u64::from_le_bytes(return_data[..8].try_into().unwrap()),
+        u64::from_le_bytes(return_data[8..].try_into().unwrap()),
+    )
+}
+
+/// Helper function to create deposit many instruction funding each owner's default vault
+fn create_deposit_many_instruction(payer: Pubkey, deposits: &[(Pubkey, u64)]) -> Instruction {
+    let mut instruction_data = vec![13u8, deposits.len() as u8]; // Discriminator for DepositMany
+    let mut accounts = vec![AccountMeta::new(payer, true)]; // payer (signer, writable)
+    for (owner, amount) in deposits {
+        instruction_data.extend_from_slice(&amount.to_le_bytes());
+        accounts.push(AccountMeta::new(find_vault_pda(owner).0, false)); // vault (writable)
+        accounts.push(AccountMeta::new_readonly(*owner, false)); // owner
+    }
+    accounts.push(AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false)); // system program
+    accounts.push(AccountMeta::new(find_stats_pda().0, false)); // stats (writable)
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts,
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create split deposit instruction across two unsalted vaults
+fn create_split_deposit_instruction(
+    owner: Pubkey,
+    amount: u64,
+    split_bps: u16,
+    first_id: u64,
+    second_id: u64,
+) -> Instruction {
+    let mut instruction_data = vec![14u8]; // Discriminator for SplitDeposit
+    instruction_data.extend_from_slice(&amount.to_le_bytes());
+    instruction_data.extend_from_slice(&split_bps.to_le_bytes());
+    instruction_data.extend_from_slice(&first_id.to_le_bytes());
+    instruction_data.extend_from_slice(&second_id.to_le_bytes());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(owner, true), // owner (signer, writable)
+            AccountMeta::new(find_vault_pda_with_id(&owner, first_id).0, false), // first vault
+            AccountMeta::new(find_vault_pda_with_id(&owner, second_id).0, false), // second vault
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system program
+            AccountMeta::new(find_stats_pda().0, false),         // stats (writable)
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create withdraw instruction that closes a salted default-id vault
+fn create_withdraw_instruction_with_salt(
+    owner: Pubkey,
+    vault: Pubkey,
+    salt: &[u8; 32],
+) -> Instruction {
+    let mut instruction = create_withdraw_instruction_with_flags(owner, vault, 0, true);
+    instruction.data.extend_from_slice(salt);
+    instruction
+}
+
+/// Helper function to create withdraw amount instruction for the default vault
+fn create_withdraw_amount_instruction(
+    owner: Pubkey,
+    vault: Pubkey,
+    amount: u64,
+    mode: u8,
+) -> Instruction {
+    let mut instruction_data = vec![3u8]; // Discriminator for WithdrawAmount
+    instruction_data.extend_from_slice(&amount.to_le_bytes());
+    instruction_data.push(mode);
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(owner, true),                         // owner (signer, writable)
+            AccountMeta::new(vault, false),                        // vault (writable)
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),   // system program
+            AccountMeta::new(find_stats_pda().0, false),           // stats (writable)
+            AccountMeta::new_readonly(find_config_pda().0, false), // config
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create withdraw many instruction
+fn create_withdraw_many_instruction(owner: Pubkey, vaults: &[(Pubkey, u64)]) -> Instruction {
+    let mut instruction_data = vec![2u8]; // Discriminator for WithdrawMany
+    let mut accounts = vec![
+        AccountMeta::new(owner, true), // owner (signer, writable)
+        AccountMeta::new_readonly(find_config_pda().0, false), // config
+    ];
+
+    for (vault, id) in vaults {
+        instruction_data.extend_from_slice(&id.to_le_bytes());
+        accounts.push(AccountMeta::new(*vault, false)); // vault (writable)
+    }
+    accounts.push(AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false)); // system program
+    accounts.push(AccountMeta::new(find_stats_pda().0, false)); // stats (writable)
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts,
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create consolidate instruction sweeping `sources` into `target`
+fn create_consolidate_instruction(
+    owner: Pubkey,
+    target: (Pubkey, u64),
+    sources: &[(Pubkey, u64)],
+) -> Instruction {
+    let mut instruction_data = vec![4u8]; // Discriminator for Consolidate
+    instruction_data.extend_from_slice(&target.1.to_le_bytes());
+    let mut accounts = vec![
+        AccountMeta::new(owner, true),     // owner (signer, writable)
+        AccountMeta::new(target.0, false), // target vault (writable)
+    ];
+
+    for (vault, id) in sources {
+        instruction_data.extend_from_slice(&id.to_le_bytes());
+        accounts.push(AccountMeta::new(*vault, false)); // source vault (writable)
+    }
+    accounts.push(AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false)); // system program
+    accounts.push(AccountMeta::new(find_stats_pda().0, false)); // stats (writable)
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts,
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create merge vaults instruction between two unsalted vaults
+fn create_merge_vaults_instruction(owner: Pubkey, source_id: u64, dest_id: u64) -> Instruction {
+    let mut instruction_data = vec![15u8]; // Discriminator for MergeVaults
+    instruction_data.extend_from_slice(&source_id.to_le_bytes());
+    instruction_data.extend_from_slice(&dest_id.to_le_bytes());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(owner, true), // owner (signer, writable)
+            AccountMeta::new(find_vault_pda_with_id(&owner, source_id).0, false), // source vault
+            AccountMeta::new(find_vault_pda_with_id(&owner, dest_id).0, false),   // dest vault
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system program
+            AccountMeta::new(find_stats_pda().0, false),         // stats (writable)
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create a close (16) or close and sweep (17) instruction
+/// for the default vault
+fn create_close_instruction(owner: Pubkey, vault: Pubkey, discriminator: u8) -> Instruction {
+    let mut instruction = create_withdraw_instruction(owner, vault);
+    instruction.data[0] = discriminator;
+    instruction
+}
+
+/// Helper function to create an add (69) or remove (70) allowed source instruction
+fn create_allowed_source_instruction(
+    owner: Pubkey,
+    vault: Pubkey,
+    source: Pubkey,
+    discriminator: u8,
+) -> Instruction {
+    // Same `[owner, vault]` accounts and `[key]` data as the destination allowlist
+    create_allowed_dest_instruction(owner, vault, source, discriminator)
+}
+
+/// Helper function to create an add (18) or remove (19) allowed destination instruction
+fn create_allowed_dest_instruction(
+    owner: Pubkey,
+    vault: Pubkey,
+    dest: Pubkey,
+    discriminator: u8,
+) -> Instruction {
+    let mut instruction_data = vec![discriminator];
+    instruction_data.extend_from_slice(dest.as_ref());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(owner, true), // owner (signer)
+            AccountMeta::new(vault, false),         // vault (writable)
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create a withdraw to instruction that keeps the default vault open
+fn create_withdraw_to_instruction(owner: Pubkey, vault: Pubkey, recipient: Pubkey) -> Instruction {
+    let mut instruction = create_withdraw_instruction_with_flags(owner, vault, 0, false);
+    instruction.data[0] = 20; // Discriminator for WithdrawTo
+    instruction
+        .accounts
+        .insert(4, AccountMeta::new(recipient, false)); // recipient (writable)
+    instruction
+}
+
+/// Helper function to create a withdraw to new account instruction, with the
+/// recipient signing as the new account
+fn create_withdraw_to_new_account_instruction(
+    owner: Pubkey,
+    vault: Pubkey,
+    recipient: Pubkey,
+) -> Instruction {
+    let mut instruction = create_withdraw_instruction_with_flags(owner, vault, 0, false);
+    instruction.data[0] = 43; // Discriminator for WithdrawToNewAccount
+    instruction
+        .accounts
+        .insert(4, AccountMeta::new(recipient, true)); // recipient (signer, writable)
+    instruction
+}
+
+/// Helper function to create a sweep token instruction for the default vault
+fn create_sweep_token_instruction(
+    owner: Pubkey,
+    vault: Pubkey,
+    token_account: Pubkey,
+    destination: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(owner, true),             // owner (signer)
+            AccountMeta::new_readonly(vault, false),            // vault
+            AccountMeta::new(token_account, false),             // token account (writable)
+            AccountMeta::new(destination, false),               // destination (writable)
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false), // token program
+        ],
+        data: vec![22u8], // Discriminator for SweepToken
+    }
+}
+
+/// Helper function to find `wallet`'s associated token account for `mint`, e.g. a vault's
+fn find_ata(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[wallet.as_ref(), TOKEN_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Helper function to create deposit token instruction for an opened vault
+fn create_deposit_token_instruction(
+    owner: Pubkey,
+    vault: Pubkey,
+    source: Pubkey,
+    mint: Pubkey,
+    amount: u64,
+) -> Instruction {
+    let mut instruction_data = vec![54u8]; // Discriminator for DepositToken
+    instruction_data.extend_from_slice(mint.as_ref());
+    instruction_data.extend_from_slice(&amount.to_le_bytes());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(owner, true), // owner (signer)
+            AccountMeta::new(vault, false),         // vault (writable)
+            AccountMeta::new(source, false),        // source (writable)
+            AccountMeta::new(find_ata(&vault, &mint), false), // vault token account (writable)
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),     // token program
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create withdraw token instruction for the default vault
+fn create_withdraw_token_instruction(
+    owner: Pubkey,
+    vault: Pubkey,
+    destination: Pubkey,
+    mint: Pubkey,
+    amount: u64,
+) -> Instruction {
+    let mut instruction_data = vec![55u8]; // Discriminator for WithdrawToken
+    instruction_data.extend_from_slice(mint.as_ref());
+    instruction_data.extend_from_slice(&amount.to_le_bytes());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(owner, true), // owner (signer)
+            AccountMeta::new(vault, false),         // vault (writable)
+            AccountMeta::new(find_ata(&vault, &mint), false), // vault token account (writable)
+            AccountMeta::new(destination, false),                   // destination (writable)
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),     // token program
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create a deposit for instruction funding `owner`'s default vault
+fn create_deposit_for_instruction(
+    funder: Pubkey,
+    vault: Pubkey,
+    owner: Pubkey,
+    amount: u64,
+) -> Instruction {
+    let mut instruction_data = vec![23u8]; // Discriminator for DepositFor
+    instruction_data.extend_from_slice(&amount.to_le_bytes());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(funder, true),                      // funder (signer, writable)
+            AccountMeta::new(vault, false),                      // vault (writable)
+            AccountMeta::new_readonly(owner, false),             // owner
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system program
+            AccountMeta::new(find_stats_pda().0, false),         // stats (writable)
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create a withdraw if price above instruction closing the default vault
+fn create_withdraw_if_price_above_instruction(
+    owner: Pubkey,
+    vault: Pubkey,
+    price_oracle: Pubkey,
+    min_price: i64,
+) -> Instruction {
+    let mut instruction = create_withdraw_instruction(owner, vault);
+    instruction.data = vec![24u8]; // Discriminator for WithdrawIfPriceAbove
+    instruction.data.extend_from_slice(&min_price.to_le_bytes());
+    instruction
+        .accounts
+        .insert(4, AccountMeta::new_readonly(price_oracle, false)); // price oracle
+    instruction
+}
+
+/// Helper function to create a vault transfer instruction between two default vaults
+fn create_vault_transfer_instruction(
+    source_owner: Pubkey,
+    dest_owner: Pubkey,
+    amount: u64,
+    dest_signs: bool,
+) -> Instruction {
+    let mut instruction_data = vec![25u8]; // Discriminator for VaultTransfer
+    instruction_data.extend_from_slice(&amount.to_le_bytes());
+    instruction_data.extend_from_slice(&0u64.to_le_bytes()); // source id
+    instruction_data.extend_from_slice(&0u64.to_le_bytes()); // dest id
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(source_owner, true), // source owner (signer)
+            AccountMeta::new(find_vault_pda(&source_owner).0, false), // source vault
+            AccountMeta::new_readonly(dest_owner, dest_signs), // dest owner (signer)
+            AccountMeta::new(find_vault_pda(&dest_owner).0, false), // dest vault
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system program
+            AccountMeta::new(find_stats_pda().0, false),   // stats (writable)
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create extend lock instruction
+fn create_extend_lock_instruction(owner: Pubkey, vault: Pubkey, unlock_ts: i64) -> Instruction {
+    let mut instruction_data = vec![21u8]; // Discriminator for ExtendLock
+    instruction_data.extend_from_slice(&unlock_ts.to_le_bytes());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(owner, true), // owner (signer)
+            AccountMeta::new(vault, false),         // vault (writable)
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create extend slot lock instruction
+fn create_extend_slot_lock_instruction(
+    owner: Pubkey,
+    vault: Pubkey,
+    unlock_slot: u64,
+) -> Instruction {
+    let mut instruction_data = vec![57u8]; // Discriminator for ExtendSlotLock
+    instruction_data.extend_from_slice(&unlock_slot.to_le_bytes());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(owner, true), // owner (signer)
+            AccountMeta::new(vault, false),         // vault (writable)
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create set delegate instruction
+fn create_set_delegate_instruction(
+    owner: Pubkey,
+    vault: Pubkey,
+    delegate: Pubkey,
+    max_per_withdrawal: u64,
+) -> Instruction {
+    let mut instruction_data = vec![58u8]; // Discriminator for SetDelegate
+    instruction_data.extend_from_slice(delegate.as_ref());
+    instruction_data.extend_from_slice(&max_per_withdrawal.to_le_bytes());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(owner, true), // owner (signer)
+            AccountMeta::new(vault, false),         // vault (writable)
+        ],
+        data: instruction_data,
+    }
+}
+
+/// Helper function to create delegate withdraw instruction
+fn create_delegate_withdraw_instruction(
+    delegate: Pubkey,
+    vault: Pubkey,
+    amount: u64,
+) -> Instruction {
+    let mut instruction_data = vec![59u8]; // Discriminator for DelegateWithdraw
+    instruction_data.extend_from_slice(&amount.to_le_bytes());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(delegate, true), // delegate (signer, writable)
+            AccountMeta::new(vault, false),   // vault (writable)
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system program
+            AccountMeta::new(find_stats_pda().0, false), // stats (writable)
+            AccountMeta::new_readonly(find_config_pda().0, false), // config
+        ],
+        data: instruction_data,
+    }
+}
+
+#[test]
+fn test_deposit_success() {
+    let mut harness = VaultHarness::new().expect("Failed to load program");
+
+    // Create owner keypair and fund it
+    let owner = Keypair::new();
+    harness
+        .fund(&owner.pubkey(), 10.0)
+        .expect("Failed to airdrop");
+
+    // Get initial balances
+    let owner_initial_balance = harness
+        .svm
+        .get_balance(&owner.pubkey())
+        .expect("Owner account should exist");
+
+    let deposit_amount = 2 * LAMPORTS_PER_SOL;
+    let tx_result = harness.deposit(&owner, deposit_amount);
+    assert!(tx_result.is_ok(), "Deposit transaction should succeed");
+
+    // Verify vault balance
+    assert_eq!(
+        harness.balance(&owner.pubkey()),
+        Some(deposit_amount),
+        "Vault should contain the deposited amount"
+    );
+
+    // Verify owner balance decreased
+    let owner_final_balance = harness
+        .svm
+        .get_balance(&owner.pubkey())
+        .expect("Owner account should exist");
+
+    // Owner balance should decrease by at least the deposit amount (plus transaction fees)
+    assert!(
+        owner_final_balance < owner_initial_balance - deposit_amount,
+        "Owner balance should decrease"
+    );
+}
+
+#[test]
+fn test_deposit_with_zero_amount_fails() {
+    let mut harness = VaultHarness::new().expect("Failed to load program");
+
+    let owner = Keypair::new();
+    harness
+        .fund(&owner.pubkey(), 10.0)
+        .expect("Failed to airdrop");
+
+    // Try to deposit zero lamports
+    let tx_result = harness.deposit(&owner, 0);
+    assert!(tx_result.is_err(), "Deposit with zero amount should fail");
+}
+
+#[test]
+fn test_deposit_non_empty_vault_fails() {
+    let mut harness = VaultHarness::new().expect("Failed to load program");
+
+    let owner = Keypair::new();
+    harness
+        .fund(&owner.pubkey(), 10.0)
+        .expect("Failed to airdrop");
+
+    // First deposit - should succeed
+    let deposit_amount = 2 * LAMPORTS_PER_SOL;
+    harness
+        .deposit(&owner, deposit_amount)
+        .expect("First deposit should succeed");
+
+    // Second deposit - should fail because vault is not empty
+    let tx_result = harness.deposit(&owner, deposit_amount);
+    assert!(
+        tx_result.is_err(),
+        "Second deposit should fail when vault is not empty"
+    );
+}
+
+#[test]
+fn test_withdraw_success() {
+    let mut harness = VaultHarness::new().expect("Failed to load program");
+
+    let owner = Keypair::new();
+    harness
+        .fund(&owner.pubkey(), 10.0)
+        .expect("Failed to airdrop");
+
+    // First, deposit some lamports
+    let deposit_amount = 2 * LAMPORTS_PER_SOL;
+    harness
+        .deposit(&owner, deposit_amount)
+        .expect("Deposit should succeed");
+
+    // Get owner balance before withdrawal
+    let owner_balance_before = harness
+        .svm
+        .get_balance(&owner.pubkey())
+        .expect("Owner account should exist");
+
+    // Now withdraw
+    let tx_result = harness.withdraw(&owner);
+    assert!(tx_result.is_ok(), "Withdraw transaction should succeed");
+
+    // Verify vault is empty (it may still exist with 0 lamports or may not exist at all)
+    assert_eq!(
+        harness.balance(&owner.pubkey()).unwrap_or(0),
+        0,
+        "Vault should be empty after withdrawal"
+    );
+
+    // Verify owner balance increased
+    let owner_balance_after = harness
+        .svm
+        .get_balance(&owner.pubkey())
+        .expect("Owner account should exist");
+
+    assert!(
+        owner_balance_after > owner_balance_before,
+        "Owner balance should increase after withdrawal"
+    );
+
+    // Owner should have received approximately the deposited amount (minus fees)
+    assert!(
+        owner_balance_after >= owner_balance_before + deposit_amount - 10000,
+        "Owner should receive approximately the deposited amount"
+    );
+}
+
+#[test]
+fn test_withdraw_empty_vault_fails() {
+    let mut harness = VaultHarness::new().expect("Failed to load program");
+
+    let owner = Keypair::new();
+    harness
+        .fund(&owner.pubkey(), 10.0)
+        .expect("Failed to airdrop");
+
+    // Try to withdraw from empty vault
+    let tx_result = harness.withdraw(&owner);
+    assert!(tx_result.is_err(), "Withdraw from empty vault should fail");
+}
+
+#[test]
+fn test_withdraw_unauthorized_fails() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    let attacker = Keypair::new();
+    
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop to owner");
+    svm.airdrop(&attacker.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop to attacker");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+
+    // Owner deposits
+    let deposit_amount = 2 * LAMPORTS_PER_SOL;
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, deposit_amount);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(tx).expect("Deposit should succeed");
+
+    // Attacker tries to withdraw from owner's vault
+    let withdraw_ix = create_withdraw_instruction(attacker.pubkey(), vault_pda);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix],
+        Some(&attacker.pubkey()),
+        &[&attacker],
+        recent_blockhash,
+    );
+
+    let tx_result = svm.send_transaction(tx);
+    assert!(
+        tx_result.is_err(),
+        "Attacker should not be able to withdraw from owner's vault"
+    );
+}
+
+#[test]
+fn test_deposit_and_withdraw_full_cycle() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    let initial_airdrop = 10 * LAMPORTS_PER_SOL;
+    svm.airdrop(&owner.pubkey(), initial_airdrop)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+
+    // Deposit
+    let deposit_amount = 5 * LAMPORTS_PER_SOL;
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, deposit_amount);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let deposit_tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(deposit_tx.clone())
+        .expect("Deposit should succeed");
+
+    // Verify deposit
+    let vault_balance = svm
+        .get_account(&vault_pda)
+        .expect("Vault should exist")
+        .lamports;
+    assert_eq!(vault_balance, deposit_amount);
+
+    // Withdraw
+    let withdraw_ix = create_withdraw_instruction(owner.pubkey(), vault_pda);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let withdraw_tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(withdraw_tx.clone())
+        .expect("Withdraw should succeed");
+
+    // Verify withdrawal - vault may not exist or have 0 lamports
+    let vault_balance_after = svm
+        .get_account(&vault_pda)
+        .map(|acc| acc.lamports)
+        .unwrap_or(0);
+    assert_eq!(vault_balance_after, 0, "Vault should be empty");
+
+    let owner_final_balance = svm
+        .get_account(&owner.pubkey())
+        .expect("Owner should exist")
+        .lamports;
+
+    // The owner gets everything back except the fees and the stats PDA's rent,
+    // which the first deposit paid for
+    let (stats_pda, _) = find_stats_pda();
+    let stats_rent = svm.get_account(&stats_pda).expect("Stats should exist").lamports;
+    assert_eq!(
+        owner_final_balance,
+        initial_airdrop - total_fees([&deposit_tx, &withdraw_tx]) - stats_rent
+    );
+}
+
+#[test]
+fn test_withdraw_many_drains_all_vaults() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    // Fund three vaults with different ids
+    let deposit_amount = LAMPORTS_PER_SOL;
+    let vaults: Vec<(Pubkey, u64)> = (0..3)
+        .map(|id| (find_vault_pda_with_id(&owner.pubkey(), id).0, id))
+        .collect();
+
+    for (vault, id) in &vaults {
+        let deposit_ix =
+            create_deposit_instruction_with_id(owner.pubkey(), *vault, deposit_amount, *id);
+
+        let recent_blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[deposit_ix],
+            Some(&owner.pubkey()),
+            &[&owner],
+            recent_blockhash,
+        );
+
+        svm.send_transaction(tx).expect("Deposit should succeed");
+    }
+
+    // A vault passed with another vault's id isn't the PDA that id derives
+    let swapped = [(vaults[0].0, 1), (vaults[1].0, 0)];
+    let tx = Transaction::new_signed_with_payer(
+        &[create_withdraw_many_instruction(owner.pubkey(), &swapped)],
+        Some(&owner.pubkey()),
+        &[&owner],
+        svm.latest_blockhash(),
+    );
+    assert_eq!(
+        svm.send_transaction(tx).map(|_| ()).map_err(|f| f.err),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::InvalidPda as u32)
+        ))
+    );
+
+    let owner_balance_before = svm
+        .get_account(&owner.pubkey())
+        .expect("Owner account should exist")
+        .lamports;
+
+    // Drain all of them in a single instruction
+    let withdraw_many_ix = create_withdraw_many_instruction(owner.pubkey(), &vaults);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_many_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    let tx_result = svm.send_transaction(tx);
+    assert!(tx_result.is_ok(), "WithdrawMany transaction should succeed");
+
+    for (vault, _) in &vaults {
+        let vault_balance = svm.get_account(vault).map(|acc| acc.lamports).unwrap_or(0);
+        assert_eq!(vault_balance, 0, "Every vault should be drained");
+    }
+
+    let owner_balance_after = svm
+        .get_account(&owner.pubkey())
+        .expect("Owner account should exist")
+        .lamports;
+    assert!(
+        owner_balance_after >= owner_balance_before + 3 * deposit_amount - 10000,
+        "Owner should receive the lamports of all three vaults"
+    );
+
+    let logs = tx_result.unwrap().logs;
+    assert!(
+        logs.iter()
+            .any(|log| log.contains(&format!("withdrew {} lamports", 3 * deposit_amount))),
+        "WithdrawMany should log the total withdrawn"
+    );
+}
+
+#[test]
+fn test_withdraw_many_skips_empty_vaults() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    // Only the first vault is funded
+    let (funded_vault, _bump) = find_vault_pda_with_id(&owner.pubkey(), 1);
+    let (empty_vault, _bump) = find_vault_pda_with_id(&owner.pubkey(), 2);
+
+    let deposit_amount = 2 * LAMPORTS_PER_SOL;
+    let deposit_ix =
+        create_deposit_instruction_with_id(owner.pubkey(), funded_vault, deposit_amount, 1);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(tx).expect("Deposit should succeed");
+
+    let withdraw_many_ix =
+        create_withdraw_many_instruction(owner.pubkey(), &[(funded_vault, 1), (empty_vault, 2)]);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_many_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    let tx_result = svm.send_transaction(tx);
+    assert!(
+        tx_result.is_ok(),
+        "WithdrawMany should skip the empty vault instead of failing"
+    );
+
+    let vault_balance = svm
+        .get_account(&funded_vault)
+        .map(|acc| acc.lamports)
+        .unwrap_or(0);
+    assert_eq!(vault_balance, 0, "Funded vault should be drained");
+}
+
+#[test]
+fn test_deposit_with_wrong_system_program_fails() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+
+    // Substitute a key that only looks like the system program
+    let fake_system_program = solana_sdk::pubkey!("11111111111111111111111111111112");
+    let mut deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL);
+    deposit_ix.accounts[2] = AccountMeta::new_readonly(fake_system_program, false);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    let tx_result = svm.send_transaction(tx);
+    assert_eq!(
+        tx_result.expect_err("Deposit with a wrong system program should fail").err,
+        TransactionError::InstructionError(0, InstructionError::IncorrectProgramId),
+        "Deposit should reject the substituted system program"
+    );
+
+    assert!(
+        svm.get_account(&vault_pda).is_none(),
+        "Vault should not be funded"
+    );
+}
+
+#[test]
+fn test_withdraw_with_random_account_as_vault_fails() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    // A funded account that is not the owner's vault PDA
+    let fake_vault = Pubkey::new_unique();
+    svm.airdrop(&fake_vault, 2 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop to fake vault");
+
+    let withdraw_ix = create_withdraw_instruction(owner.pubkey(), fake_vault);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    let tx_result = svm.send_transaction(tx);
+    assert!(
+        tx_result.is_err(),
+        "Withdraw from an account that is not the vault PDA should fail"
+    );
+
+    assert_eq!(
+        svm.get_account(&fake_vault)
+            .expect("Fake vault should exist")
+            .lamports,
+        2 * LAMPORTS_PER_SOL,
+        "Fake vault should keep its lamports"
+    );
+}
+
+#[test]
+fn test_withdraw_from_vault_owned_by_other_program_fails() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+
+    // Plant a lookalike account at the vault address owned by another program
+    svm.set_account(
+        vault_pda,
+        Account {
+            lamports: 2 * LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    )
+    .expect("Failed to set lookalike vault");
+
+    let withdraw_ix = create_withdraw_instruction(owner.pubkey(), vault_pda);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    let tx_result = svm.send_transaction(tx);
+    assert_eq!(
+        tx_result.expect_err("Withdraw from a lookalike vault should fail").err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::InvalidVaultOwner as u32)
+        ),
+        "Withdraw should reject a vault owned by another program"
+    );
+}
+
+#[test]
+fn test_withdraw_with_close_flag_removes_vault() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, 2 * LAMPORTS_PER_SOL);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(tx).expect("Deposit should succeed");
+
+    // Withdraw with the close flag set
+    let withdraw_ix = create_withdraw_instruction_with_flags(owner.pubkey(), vault_pda, 0, true);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(tx).expect("Withdraw should succeed");
+
+    assert!(
+        svm.get_account(&vault_pda).is_none(),
+        "Vault account should be closed after a closing withdraw"
+    );
+}
+
+#[test]
+fn test_withdraw_without_close_flag_keeps_vault() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let rent_reserve = svm.minimum_balance_for_rent_exemption(0);
+
+    let deposit_amount = 2 * LAMPORTS_PER_SOL;
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, deposit_amount);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(tx).expect("Deposit should succeed");
+
+    // Withdraw but keep the account alive
+    let withdraw_ix = create_withdraw_instruction_with_flags(owner.pubkey(), vault_pda, 0, false);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(tx).expect("Withdraw should succeed");
+
+    let vault_account = svm
+        .get_account(&vault_pda)
+        .expect("Vault account should persist");
+    assert_eq!(
+        vault_account.lamports, rent_reserve,
+        "Vault should keep exactly its rent-exempt reserve"
+    );
+
+    // The persisted vault can be funded again
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, deposit_amount);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(tx)
+        .expect("Deposit into a persisted vault should succeed");
+
+    let vault_balance = svm
+        .get_account(&vault_pda)
+        .expect("Vault should exist")
+        .lamports;
+    assert_eq!(vault_balance, rent_reserve + deposit_amount);
+}
+
+#[test]
+fn test_withdraw_rent_sysvar_account_matches_syscall() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let rent_reserve = svm.minimum_balance_for_rent_exemption(0);
+    let deposit_amount = 2 * LAMPORTS_PER_SOL;
+    let mut vault_balances = vec![];
+
+    // Keep-open withdraw once via `Rent::get()` and once via the passed sysvar account
+    for pass_rent_sysvar in [false, true] {
+        let owner = Keypair::new();
+        svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop");
+
+        let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+
+        let mut deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, deposit_amount);
+        let mut withdraw_ix =
+            create_withdraw_instruction_with_flags(owner.pubkey(), vault_pda, 0, false);
+        if pass_rent_sysvar {
+            deposit_ix
+                .accounts
+                .push(AccountMeta::new_readonly(RENT_SYSVAR_ID, false));
+            withdraw_ix
+                .accounts
+                .push(AccountMeta::new_readonly(RENT_SYSVAR_ID, false));
+        }
+
+        let recent_blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[deposit_ix, withdraw_ix],
+            Some(&owner.pubkey()),
+            &[&owner],
+            recent_blockhash,
+        );
+
+        svm.send_transaction(tx)
+            .expect("Deposit and withdraw should succeed");
+
+        vault_balances.push(
+            svm.get_account(&vault_pda)
+                .expect("Vault account should persist")
+                .lamports,
+        );
+    }
+
+    assert_eq!(
+        vault_balances,
+        vec![rent_reserve, rent_reserve],
+        "Both rent sources should leave the same rent-exempt reserve"
+    );
+}
+
+#[test]
+fn test_withdraw_with_spoofed_rent_sysvar_fails() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, 2 * LAMPORTS_PER_SOL);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(tx).expect("Deposit should succeed");
+
+    // Pass an arbitrary account in place of the rent sysvar
+    let spoofed_rent = Pubkey::new_unique();
+    let mut withdraw_ix =
+        create_withdraw_instruction_with_flags(owner.pubkey(), vault_pda, 0, false);
+    withdraw_ix
+        .accounts
+        .push(AccountMeta::new_readonly(spoofed_rent, false));
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    let tx_result = svm.send_transaction(tx);
+    assert_eq!(
+        tx_result.expect_err("Withdraw with a spoofed rent sysvar should fail").err,
+        TransactionError::InstructionError(0, InstructionError::InvalidArgument),
+        "Withdraw should reject a spoofed rent sysvar"
+    );
+}
+
+#[test]
+fn test_client_vault_balance() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    assert_eq!(
+        client::find_vault_address(&owner.pubkey(), 0).0,
+        vault_pda,
+        "Client should derive the same vault PDA"
+    );
+
+    let deposit_amount = 2 * LAMPORTS_PER_SOL;
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, deposit_amount);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(tx).expect("Deposit should succeed");
+
+    let fetcher = |address: &Pubkey| svm.get_account(address);
+    assert_eq!(
+        client::vault_balance(&fetcher, &owner.pubkey()),
+        Some(deposit_amount),
+        "Client should report the deposited amount"
+    );
+
+    let withdraw_ix = create_withdraw_instruction(owner.pubkey(), vault_pda);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(tx).expect("Withdraw should succeed");
+
+    let fetcher = |address: &Pubkey| svm.get_account(address);
+    assert_eq!(
+        client::vault_balance(&fetcher, &owner.pubkey()),
+        None,
+        "Closed vault should have no balance"
+    );
+}
+
+#[test]
+fn test_deposit_more_than_owner_balance_fails() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    // Fund the owner with less than the deposit amount
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, 2 * LAMPORTS_PER_SOL);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    // The shortfall is caught before the system program transfer and logged
+    let failure = svm
+        .send_transaction(tx)
+        .expect_err("Under-funded deposit should fail");
+    assert_eq!(
+        failure.err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::TransferFailed as u32)
+        ),
+        "Deposit should fail with TransferFailed"
+    );
+    assert!(
+        failure.meta.logs.iter().any(|log| log.contains(&format!(
+            "Transfer failed: {} lamports needed",
+            2 * LAMPORTS_PER_SOL
+        ))),
+        "The shortfall should be logged"
+    );
+
+    assert!(
+        svm.get_account(&vault_pda).is_none(),
+        "Vault should not be funded"
+    );
+}
+
+#[test]
+fn test_salted_vaults_hold_independent_balances() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let salt_a = [1u8; 32];
+    let salt_b = [2u8; 32];
+    let (vault_a, _bump) = find_vault_pda_with_salt(&owner.pubkey(), &salt_a);
+    let (vault_b, _bump) = find_vault_pda_with_salt(&owner.pubkey(), &salt_b);
+    let (default_vault, _bump) = find_vault_pda(&owner.pubkey());
+
+    assert_ne!(vault_a, vault_b, "Different salts should give different vaults");
+    assert_ne!(vault_a, default_vault, "A salted vault should not be the default vault");
+    assert_eq!(
+        client::find_salted_vault_address(&owner.pubkey(), 0, &salt_a).0,
+        vault_a,
+        "Client derivation should match"
+    );
+
+    let deposit_a = create_deposit_instruction_with_salt(
+        owner.pubkey(),
+        vault_a,
+        LAMPORTS_PER_SOL,
+        &salt_a,
+    );
+    let deposit_b = create_deposit_instruction_with_salt(
+        owner.pubkey(),
+        vault_b,
+        2 * LAMPORTS_PER_SOL,
+        &salt_b,
+    );
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_a, deposit_b],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(tx).expect("Deposits should succeed");
+
+    // Drain only the first salted vault
+    let withdraw_ix = create_withdraw_instruction_with_salt(owner.pubkey(), vault_a, &salt_a);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(tx).expect("Withdraw should succeed");
+
+    assert!(
+        svm.get_account(&vault_a).is_none(),
+        "First salted vault should be drained"
+    );
+    assert_eq!(
+        svm.get_account(&vault_b)
+            .expect("Second salted vault should exist")
+            .lamports,
+        2 * LAMPORTS_PER_SOL,
+        "Second salted vault should keep its balance"
+    );
+
+    // The wrong salt does not derive the vault, so withdrawal is refused
+    let withdraw_ix = create_withdraw_instruction_with_salt(owner.pubkey(), vault_b, &salt_a);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    let tx_result = svm.send_transaction(tx);
+    assert_eq!(
+        tx_result.expect_err("Withdraw with the wrong salt should fail").err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::InvalidPda as u32)
+        ),
+    );
+}
+
+#[test]
+fn test_zero_salt_uses_default_vault() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+
+    // A zero salt must land in the canonical `["vault", owner]` vault
+    let deposit_ix = create_deposit_instruction_with_salt(
+        owner.pubkey(),
+        vault_pda,
+        LAMPORTS_PER_SOL,
+        &[0u8; 32],
+    );
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(tx)
+        .expect("Deposit with a zero salt should succeed");
+
+    let fetcher = |address: &Pubkey| svm.get_account(address);
+    assert_eq!(
+        client::vault_balance(&fetcher, &owner.pubkey()),
+        Some(LAMPORTS_PER_SOL),
+        "Zero-salt deposit should fund the default vault"
+    );
+
+    // And the plain withdraw instruction can drain it
+    let withdraw_ix = create_withdraw_instruction(owner.pubkey(), vault_pda);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(tx).expect("Withdraw should succeed");
+}
+
+#[test]
+fn test_withdraw_twice_fails_with_vault_empty() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(tx).expect("Deposit should succeed");
+
+    // First close drains the vault
+    let withdraw_ix = create_withdraw_instruction(owner.pubkey(), vault_pda);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(tx).expect("First withdraw should succeed");
+    assert!(
+        svm.get_account(&vault_pda).is_none(),
+        "Vault should be closed"
+    );
+
+    // Second close, in a separate transaction, hits the already-closed vault
+    svm.expire_blockhash();
+    let withdraw_ix = create_withdraw_instruction(owner.pubkey(), vault_pda);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    let tx_result = svm.send_transaction(tx);
+    assert_eq!(
+        tx_result.expect_err("Second withdraw should fail").err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::VaultEmpty as u32)
+        ),
+        "Closing an already-closed vault should fail with VaultEmpty"
+    );
+}
+
+#[test]
+fn test_deposit_returns_new_vault_balance() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+
+    let deposit_amount = 2 * LAMPORTS_PER_SOL;
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, deposit_amount);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    let tx_result = svm.send_transaction(tx).expect("Deposit should succeed");
+
+    // The balance is reported as a little-endian u64
+    assert_eq!(tx_result.return_data.program_id, PROGRAM_ID);
+    let returned_balance = u64::from_le_bytes(
+        tx_result
+            .return_data
+            .data
+            .as_slice()
+            .try_into()
+            .expect("Return data should be a u64"),
+    );
+    assert_eq!(
+        returned_balance, deposit_amount,
+        "Return data should hold the new vault balance"
+    );
+    assert_eq!(
+        returned_balance,
+        svm.get_account(&vault_pda)
+            .expect("Vault should exist")
+            .lamports
+    );
+}
+
+#[test]
+fn test_withdraw_amount_exact_mode_fails_when_underfunded() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+
+    let deposit_amount = LAMPORTS_PER_SOL;
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, deposit_amount);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(tx).expect("Deposit should succeed");
+
+    // Ask for more than the vault holds, in exact mode
+    let withdraw_ix =
+        create_withdraw_amount_instruction(owner.pubkey(), vault_pda, 2 * LAMPORTS_PER_SOL, 0);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    let tx_result = svm.send_transaction(tx);
+    assert_eq!(
+        tx_result.expect_err("Exact withdraw should fail").err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::InsufficientFunds as u32)
+        ),
+        "Exact withdraw of an under-funded vault should fail with InsufficientFunds"
+    );
+
+    assert_eq!(
+        svm.get_account(&vault_pda)
+            .expect("Vault should exist")
+            .lamports,
+        deposit_amount,
+        "Vault balance should be untouched"
+    );
+}
+
+#[test]
+fn test_withdraw_amount_best_effort_mode_drains_available() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let rent_reserve = svm.minimum_balance_for_rent_exemption(0);
+
+    let deposit_amount = LAMPORTS_PER_SOL;
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, deposit_amount);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(tx).expect("Deposit should succeed");
+
+    // Ask for more than the vault holds, in best-effort mode
+    let withdraw_ix =
+        create_withdraw_amount_instruction(owner.pubkey(), vault_pda, 2 * LAMPORTS_PER_SOL, 1);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    let tx_result = svm
+        .send_transaction(tx)
+        .expect("Best-effort withdraw should succeed");
+
+    // Everything above the rent-exempt reserve is withdrawn
+    assert_eq!(
+        svm.get_account(&vault_pda)
+            .expect("Vault should persist")
+            .lamports,
+        rent_reserve,
+        "Vault should keep only its rent-exempt reserve"
+    );
+    assert_eq!(
+        tx_result.return_data.data,
+        rent_reserve.to_le_bytes().to_vec(),
+        "Return data should hold the new vault balance"
+    );
+}
+
+#[test]
+fn test_consolidate_sweeps_dust_vaults_into_target() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    // Fund a target vault and three small source vaults
+    let target_amount = LAMPORTS_PER_SOL;
+    let dust_amount = LAMPORTS_PER_SOL / 100;
+    let target = (find_vault_pda_with_id(&owner.pubkey(), 0).0, 0);
+    let sources: Vec<(Pubkey, u64)> = (1..4)
+        .map(|id| (find_vault_pda_with_id(&owner.pubkey(), id).0, id))
+        .collect();
+
+    let deposits = std::iter::once((target, target_amount))
+        .chain(sources.iter().map(|source| (*source, dust_amount)));
+    for ((vault, id), amount) in deposits {
+        let deposit_ix = create_deposit_instruction_with_id(owner.pubkey(), vault, amount, id);
+
+        let recent_blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[deposit_ix],
+            Some(&owner.pubkey()),
+            &[&owner],
+            recent_blockhash,
+        );
+
+        svm.send_transaction(tx).expect("Deposit should succeed");
+    }
+
+    // Sweep the sources into the target in a single instruction
+    let consolidate_ix = create_consolidate_instruction(owner.pubkey(), target, &sources);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[consolidate_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(tx)
+        .expect("Consolidate should succeed");
+
+    for (source, _) in &sources {
+        assert!(
+            svm.get_account(source).is_none(),
+            "Every source vault should be closed"
+        );
+    }
+
+    assert_eq!(
+        svm.get_account(&target.0)
+            .expect("Target vault should exist")
+            .lamports,
+        target_amount + 3 * dust_amount,
+        "Target vault should hold the combined balance"
+    );
+}
+
+#[test]
+fn test_consolidate_foreign_source_fails() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    let other = Keypair::new();
+    for keypair in [&owner, &other] {
+        svm.airdrop(&keypair.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop");
+    }
+
+    // Another user's vault can't be swept into the owner's vault
+    let target = (find_vault_pda(&owner.pubkey()).0, 0);
+    let (other_vault, _bump) = find_vault_pda_with_id(&other.pubkey(), 1);
+
+    let deposit_ix =
+        create_deposit_instruction_with_id(other.pubkey(), other_vault, LAMPORTS_PER_SOL, 1);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&other.pubkey()),
+        &[&other],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(tx).expect("Deposit should succeed");
+
+    let consolidate_ix =
+        create_consolidate_instruction(owner.pubkey(), target, &[(other_vault, 1)]);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[consolidate_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    let tx_result = svm.send_transaction(tx);
+    assert_eq!(
+        tx_result.expect_err("Consolidating a foreign vault should fail").err,
+        TransactionError::InstructionError(0, InstructionError::InvalidAccountOwner),
+    );
+
+    assert_eq!(
+        svm.get_account(&other_vault)
+            .expect("Foreign vault should exist")
+            .lamports,
+        LAMPORTS_PER_SOL,
+        "Foreign vault should be untouched"
+    );
+}
+
+// Needs the program built with `--features verbose-logs`
+#[cfg(feature = "verbose-logs")]
+#[test]
+fn test_withdraw_pda_mismatch_logs_expected_bump() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    // Fund vault 1, then try to withdraw it as if it were the default vault
+    let (other_vault, _bump) = find_vault_pda_with_id(&owner.pubkey(), 1);
+    let (_default_vault, expected_bump) = find_vault_pda(&owner.pubkey());
+
+    let deposit_ix =
+        create_deposit_instruction_with_id(owner.pubkey(), other_vault, LAMPORTS_PER_SOL, 1);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(tx).expect("Deposit should succeed");
+
+    let withdraw_ix = create_withdraw_instruction(owner.pubkey(), other_vault);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    let failed = svm
+        .send_transaction(tx)
+        .expect_err("Withdraw with a mismatched PDA should fail");
+    assert_eq!(
+        failed.err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::InvalidPda as u32)
+        ),
+    );
+
+    let logs = failed.meta.logs;
+    assert!(
+        logs.iter()
+            .any(|log| log.contains(&format!("expected bump {expected_bump}"))),
+        "Mismatch should log the expected canonical bump"
+    );
+    assert!(
+        logs.iter().any(|log| log.contains(&other_vault.to_string())),
+        "Mismatch should log the provided vault key"
+    );
+}
+
+#[test]
+fn test_idempotent_deposit_retry_does_not_double_deposit() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let deposit_amount = 2 * LAMPORTS_PER_SOL;
+
+    // Send the same idempotent deposit twice, as a client retrying after a timeout would
+    for attempt in 0..2 {
+        svm.expire_blockhash();
+        let deposit_ix =
+            create_idempotent_deposit_instruction(owner.pubkey(), vault_pda, deposit_amount);
+
+        let recent_blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[deposit_ix],
+            Some(&owner.pubkey()),
+            &[&owner],
+            recent_blockhash,
+        );
+
+        svm.send_transaction(tx)
+            .unwrap_or_else(|_| panic!("Idempotent deposit attempt {attempt} should succeed"));
+    }
+
+    assert_eq!(
+        svm.get_account(&vault_pda)
+            .expect("Vault should exist")
+            .lamports,
+        deposit_amount,
+        "Vault should hold the amount once, not twice"
+    );
+}
+
+#[test]
+fn test_withdraw_without_close_flag_one_lamport_above_rent() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let rent_reserve = svm.minimum_balance_for_rent_exemption(0);
+
+    // Fund the vault with its rent-exempt reserve plus a single lamport
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, rent_reserve + 1);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(tx).expect("Deposit should succeed");
+
+    let withdraw_ix = create_withdraw_instruction_with_flags(owner.pubkey(), vault_pda, 0, false);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    let tx_result = svm.send_transaction(tx).expect("Withdraw should succeed");
+
+    // Exactly the one spendable lamport leaves the vault
+    assert_eq!(
+        svm.get_account(&vault_pda)
+            .expect("Vault account should persist")
+            .lamports,
+        rent_reserve,
+        "Vault should be left exactly rent-exempt"
+    );
+    assert_eq!(
+        tx_result.return_data.data,
+        rent_reserve.to_le_bytes().to_vec(),
+        "Return data should hold the new vault balance"
+    );
+}
+
+#[test]
+fn test_load_vault_program_with_wrong_path_fails() {
+    let mut svm = LiteSVM::new();
+
+    let err = load_vault_program_from(&mut svm, "target/deploy/missing.so")
+        .expect_err("Loading a missing binary should fail");
+    assert!(
+        matches!(err, LoadProgramError::Read { .. }),
+        "Missing binary should be reported as a read error, got {err:?}"
+    );
+    assert!(
+        err.to_string().contains("target/deploy/missing.so"),
+        "Error should name the path it tried: {err}"
+    );
+
+    assert!(
+        svm.get_account(&PROGRAM_ID).is_none(),
+        "Nothing should be deployed at the program id"
+    );
+}
+
+#[test]
+fn test_opened_vault_persists_at_rent_floor() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let rent_floor = svm.minimum_balance_for_rent_exemption(VaultState::LEN);
+
+    let open_ix = create_open_vault_instruction(owner.pubkey(), vault_pda);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[open_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(tx).expect("OpenVault should succeed");
+
+    let vault_account = svm
+        .get_account(&vault_pda)
+        .expect("Opened vault should exist");
+    assert_eq!(vault_account.owner, PROGRAM_ID, "Program should own the vault");
+    assert_eq!(vault_account.data.len(), VaultState::LEN);
+    assert_eq!(
+        vault_account.lamports, rent_floor,
+        "Vault should hold exactly its rent floor"
+    );
+
+    // Deposits into an opened vault add up instead of requiring an empty vault
+    let deposit_amount = LAMPORTS_PER_SOL;
+    for _ in 0..2 {
+        svm.expire_blockhash();
+        let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, deposit_amount);
+
+        let recent_blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[deposit_ix],
+            Some(&owner.pubkey()),
+            &[&owner],
+            recent_blockhash,
+        );
+
+        svm.send_transaction(tx).expect("Deposit should succeed");
+    }
+
+    assert_eq!(
+        svm.get_account(&vault_pda)
+            .expect("Vault should exist")
+            .lamports,
+        rent_floor + 2 * deposit_amount
+    );
+
+    // Withdraw everything spendable, keeping the vault open
+    let withdraw_ix = create_withdraw_instruction_with_flags(owner.pubkey(), vault_pda, 0, false);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(tx).expect("Withdraw should succeed");
+
+    let vault_account = svm
+        .get_account(&vault_pda)
+        .expect("Vault should still exist");
+    assert_eq!(
+        vault_account.lamports, rent_floor,
+        "Vault should be back at its rent floor"
+    );
+    assert_eq!(vault_account.owner, PROGRAM_ID, "Vault should stay open");
+}
+
+#[test]
+fn test_withdraw_with_close_flag_closes_opened_vault() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+
+    let open_ix = create_open_vault_instruction(owner.pubkey(), vault_pda);
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[open_ix, deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(tx)
+        .expect("OpenVault and deposit should succeed");
+
+    let owner_balance_before = svm
+        .get_account(&owner.pubkey())
+        .expect("Owner account should exist")
+        .lamports;
+    let vault_balance = svm
+        .get_account(&vault_pda)
+        .expect("Vault should exist")
+        .lamports;
+
+    let withdraw_ix = create_withdraw_instruction_with_flags(owner.pubkey(), vault_pda, 0, true);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(tx).expect("Withdraw should succeed");
+
+    assert!(
+        svm.get_account(&vault_pda).is_none(),
+        "Closing should remove the opened vault"
+    );
+
+    // The rent floor comes back to the owner along with the deposit
+    let owner_balance_after = svm
+        .get_account(&owner.pubkey())
+        .expect("Owner account should exist")
+        .lamports;
+    assert!(
+        owner_balance_after >= owner_balance_before + vault_balance - 10000,
+        "Owner should receive the whole vault balance"
+    );
+}
+
+#[test]
+fn test_deferred_deposits_rejected_in_creation_slot() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+
+    let open_ix = create_open_vault_instruction_with_flags(
+        owner.pubkey(),
+        vault_pda,
+        VaultState::FLAG_DEFER_DEPOSITS,
+    );
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[open_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(tx).expect("OpenVault should succeed");
+
+    // Still in the creation slot
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    let tx_result = svm.send_transaction(tx);
+    assert_eq!(
+        tx_result.expect_err("Same-slot deposit should fail").err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::TooSoonAfterCreation as u32)
+        ),
+    );
+
+    // One slot later the deposit goes through
+    let creation_slot = svm.get_sysvar::<Clock>().slot;
+    svm.warp_to_slot(creation_slot + 1);
+    svm.expire_blockhash();
+
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(tx)
+        .expect("Deposit in a later slot should succeed");
+
+    let rent_floor = svm.minimum_balance_for_rent_exemption(VaultState::LEN);
+    assert_eq!(
+        svm.get_account(&vault_pda)
+            .expect("Vault should exist")
+            .lamports,
+        rent_floor + LAMPORTS_PER_SOL
+    );
+}
+
+#[test]
+fn test_registry_lists_opened_vaults() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    // Open three vaults, each in its own transaction so the registry grows each time
+    let vaults: Vec<Pubkey> = (0..3)
+        .map(|id| find_vault_pda_with_id(&owner.pubkey(), id).0)
+        .collect();
+
+    for (id, vault) in vaults.iter().enumerate() {
+        let open_ix = create_open_vault_instruction_with_id(owner.pubkey(), *vault, id as u64);
+
+        let recent_blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[open_ix],
+            Some(&owner.pubkey()),
+            &[&owner],
+            recent_blockhash,
+        );
+
+        svm.send_transaction(tx).expect("OpenVault should succeed");
+    }
+
+    let fetcher = |address: &Pubkey| svm.get_account(address);
+    assert_eq!(
+        client::vault_registry(&fetcher, &owner.pubkey()),
+        Some(vaults.clone()),
+        "Registry should list every opened vault in order"
+    );
+
+    // The registry is kept rent-exempt as it grows
+    let (registry_pda, _bump) = find_registry_pda(&owner.pubkey());
+    let registry = svm
+        .get_account(&registry_pda)
+        .expect("Registry should exist");
+    assert!(registry.lamports >= svm.minimum_balance_for_rent_exemption(registry.data.len()));
+
+    // Closing and reopening a vault doesn't list it twice
+    let withdraw_ix = create_withdraw_instruction_with_flags(owner.pubkey(), vaults[1], 1, true);
+    let open_ix = create_open_vault_instruction_with_id(owner.pubkey(), vaults[1], 1);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx).expect("Withdraw should succeed");
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[open_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx).expect("Reopening should succeed");
+
+    let fetcher = |address: &Pubkey| svm.get_account(address);
+    assert_eq!(
+        client::vault_registry(&fetcher, &owner.pubkey()),
+        Some(vaults),
+        "Reopened vault should not be listed twice"
+    );
+}
+
+#[test]
+fn test_global_stats_track_vault_operations() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (opened_vault, _bump) = find_vault_pda(&owner.pubkey());
+    let (legacy_vault, _bump) = find_vault_pda_with_id(&owner.pubkey(), 1);
+    let rent_floor = svm.minimum_balance_for_rent_exemption(VaultState::LEN);
+
+    // Open one vault and fund it, and fund a second, system-owned vault
+    let open_ix = create_open_vault_instruction(owner.pubkey(), opened_vault);
+    let deposit_opened_ix =
+        create_deposit_instruction(owner.pubkey(), opened_vault, 2 * LAMPORTS_PER_SOL);
+    let deposit_legacy_ix =
+        create_deposit_instruction_with_id(owner.pubkey(), legacy_vault, LAMPORTS_PER_SOL, 1);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[open_ix, deposit_opened_ix, deposit_legacy_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx)
+        .expect("Open and deposits should succeed");
+
+    let fetcher = |address: &Pubkey| svm.get_account(address);
+    let stats =
+        client::global_stats(&fetcher).expect("Stats should exist after the first operation");
+    assert_eq!(stats.total_vaults(), 2);
+    assert_eq!(stats.total_value_locked(), rent_floor + 3 * LAMPORTS_PER_SOL);
+    assert_eq!(stats.lifetime_deposits(), 3 * LAMPORTS_PER_SOL);
+    assert_eq!(stats.lifetime_withdrawals(), 0);
+
+    // Closing the opened vault returns its deposits and its rent floor to the owner
+    let withdraw_ix = create_withdraw_instruction(owner.pubkey(), opened_vault);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx).expect("Withdraw should succeed");
+
+    let fetcher = |address: &Pubkey| svm.get_account(address);
+    let stats = client::global_stats(&fetcher).expect("Stats should exist");
+    assert_eq!(stats.total_vaults(), 1, "Closed vault should no longer be counted");
+    assert_eq!(
+        stats.total_value_locked(),
+        LAMPORTS_PER_SOL,
+        "Only the system-owned vault's lamports should remain locked"
+    );
+    assert_eq!(stats.lifetime_deposits(), 3 * LAMPORTS_PER_SOL);
+    assert_eq!(stats.lifetime_withdrawals(), rent_floor + 2 * LAMPORTS_PER_SOL);
+}
+
+#[test]
+fn test_closed_opened_vault_is_recreated_with_clean_state() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+
+    // Open with a flag set so stale state would be visible after recreating
+    let open_ix = create_open_vault_instruction_with_flags(
+        owner.pubkey(),
+        vault_pda,
+        VaultState::FLAG_DEFER_DEPOSITS,
+    );
+    let withdraw_ix = create_withdraw_instruction_with_flags(owner.pubkey(), vault_pda, 0, true);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[open_ix, withdraw_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(tx)
+        .expect("OpenVault and closing withdraw should succeed");
+    assert!(
+        svm.get_account(&vault_pda).is_none(),
+        "Closing should remove the opened vault"
+    );
+
+    // Recreate the vault at the same PDA, without any flags, in a later slot
+    let creation_slot = svm.get_sysvar::<Clock>().slot + 1;
+    svm.warp_to_slot(creation_slot);
+    svm.expire_blockhash();
+
+    let open_ix = create_open_vault_instruction(owner.pubkey(), vault_pda);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[open_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    svm.send_transaction(tx).expect("Reopening should succeed");
+
+    let vault_account = svm
+        .get_account(&vault_pda)
+        .expect("Reopened vault should exist");
+    assert_eq!(vault_account.data.len(), VaultState::LEN);
+
+    // `[version: 1][owner: 32][id: 8][bump: 1][creation_slot: 8][flags: 1]`
+    assert_eq!(&vault_account.data[1..33], owner.pubkey().as_ref());
+    assert_eq!(
+        &vault_account.data[33..41],
+        &[0u8; 8],
+        "Id should be the default"
+    );
+    assert_eq!(
+        &vault_account.data[42..50],
+        &creation_slot.to_le_bytes(),
+        "Creation slot should be the reopening slot"
+    );
+    assert_eq!(vault_account.data[50], 0, "No flags should carry over");
+}
+
+#[test]
+fn test_deposit_wrapped_sol_unwraps_into_vault() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let wsol_account = Pubkey::new_unique();
+    set_wrapped_sol_account(
+        &mut svm,
+        wsol_account,
+        &owner.pubkey(),
+        2 * LAMPORTS_PER_SOL,
+    );
+
+    let unwrapped = svm
+        .get_account(&wsol_account)
+        .expect("wSOL account should exist")
+        .lamports;
+
+    let deposit_ix =
+        create_deposit_wrapped_sol_instruction(owner.pubkey(), vault_pda, wsol_account);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    let tx_result = svm
+        .send_transaction(tx)
+        .expect("Wrapped SOL deposit should succeed");
+
+    // The vault receives everything the token account held, its rent included
+    let vault_account = svm.get_account(&vault_pda).expect("Vault should exist");
+    assert_eq!(vault_account.lamports, unwrapped);
+    assert_eq!(vault_account.owner, SYSTEM_PROGRAM_ID);
+    assert_eq!(
+        tx_result.return_data.data,
+        unwrapped.to_le_bytes().to_vec(),
+        "Return data should hold the new vault balance"
+    );
+
+    assert!(
+        svm.get_account(&wsol_account)
+            .is_none_or(|account| account.lamports == 0),
+        "wSOL account should be closed"
+    );
+}
+
+#[test]
+fn test_deposit_without_owner_signature_fails() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let payer = Keypair::new();
+    svm.airdrop(&payer.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+
+    // The owner is passed without signing, so only the program's own check can catch it
+    let mut deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL);
+    deposit_ix.accounts[0].is_signer = false;
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+
+    let tx_result = svm.send_transaction(tx);
+    assert_eq!(
+        tx_result.expect_err("Unsigned deposit should fail").err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::MissingOwnerSignature as u32)
+        ),
+    );
+
+    assert!(
+        svm.get_account(&vault_pda).is_none(),
+        "Vault should not be funded"
+    );
+}
+
+#[test]
+fn test_verify_integrity_reports_state_inconsistencies() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+
+    let open_ix = create_open_vault_instruction(owner.pubkey(), vault_pda);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[open_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx).expect("OpenVault should succeed");
+
+    let verify = |svm: &mut LiteSVM| {
+        svm.expire_blockhash();
+        let verify_ix = create_verify_integrity_instruction(vault_pda);
+
+        let recent_blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[verify_ix],
+            Some(&owner.pubkey()),
+            &[&owner],
+            recent_blockhash,
+        );
+        svm.send_transaction(tx)
+            .expect("VerifyIntegrity should succeed")
+            .return_data
+            .data
+    };
+
+    assert_eq!(verify(&mut svm), vec![IntegrityStatus::Healthy as u8]);
+
+    // `[version: 1][owner: 32][id: 8][bump: 1][creation_slot: 8][flags: 1]`
+    let healthy = svm.get_account(&vault_pda).expect("Vault should exist");
+
+    // Corrupt the flags directly
+    let mut corrupted = healthy.clone();
+    corrupted.data[50] = 0x80;
+    svm.set_account(vault_pda, corrupted)
+        .expect("Failed to corrupt vault");
+    assert_eq!(verify(&mut svm), vec![IntegrityStatus::UnknownFlags as u8]);
+
+    // Corrupt the bump directly
+    let mut corrupted = healthy.clone();
+    corrupted.data[41] = corrupted.data[41].wrapping_sub(1);
+    svm.set_account(vault_pda, corrupted)
+        .expect("Failed to corrupt vault");
+    assert_eq!(verify(&mut svm), vec![IntegrityStatus::BumpMismatch as u8]);
+
+    // Corrupt the recorded owner directly
+    let mut corrupted = healthy;
+    corrupted.data[1..33].copy_from_slice(Pubkey::new_unique().as_ref());
+    svm.set_account(vault_pda, corrupted)
+        .expect("Failed to corrupt vault");
+    assert_eq!(verify(&mut svm), vec![IntegrityStatus::AddressMismatch as u8]);
+}
+
+#[test]
+fn test_vault_label_set_and_updated() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+
+    let open_ix = create_open_vault_instruction_with_label(owner.pubkey(), vault_pda, b"Savings");
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[open_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx).expect("OpenVault should succeed");
+
+    let fetcher = |address: &Pubkey| svm.get_account(address);
+    assert_eq!(
+        client::vault_label(&fetcher, &vault_pda).as_deref(),
+        Some("Savings"),
+        "Label should be set at creation"
+    );
+
+    // A full-length multi-byte label fits exactly
+    let label = "Rainy day fund ☂ (Oct 2026)✓";
+    assert_eq!(label.len(), VaultState::MAX_LABEL_LEN);
+    let set_label_ix = create_set_label_instruction(owner.pubkey(), vault_pda, label.as_bytes());
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[set_label_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx).expect("SetLabel should succeed");
+
+    let fetcher = |address: &Pubkey| svm.get_account(address);
+    assert_eq!(
+        client::vault_label(&fetcher, &vault_pda).as_deref(),
+        Some(label),
+        "Label should be replaced"
+    );
+}
+
+#[test]
+fn test_vault_label_oversized_or_invalid_fails() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+
+    // Oversized at creation
+    let oversized = [b'a'; VaultState::MAX_LABEL_LEN + 1];
+    let open_ix = create_open_vault_instruction_with_label(owner.pubkey(), vault_pda, &oversized);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[open_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    let tx_result = svm.send_transaction(tx);
+    assert_eq!(
+        tx_result.expect_err("Oversized label should fail").err,
+        TransactionError::InstructionError(0, InstructionError::InvalidInstructionData),
+    );
+
+    let open_ix = create_open_vault_instruction(owner.pubkey(), vault_pda);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[open_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx).expect("OpenVault should succeed");
+
+    // Oversized and non-UTF-8 updates are rejected alike
+    for label in [&oversized[..], &[0xff, 0xfe][..]] {
+        svm.expire_blockhash();
+        let set_label_ix = create_set_label_instruction(owner.pubkey(), vault_pda, label);
+
+        let recent_blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[set_label_ix],
+            Some(&owner.pubkey()),
+            &[&owner],
+            recent_blockhash,
+        );
+
+        let tx_result = svm.send_transaction(tx);
+        assert_eq!(
+            tx_result.expect_err("Invalid label should fail").err,
+            TransactionError::InstructionError(0, InstructionError::InvalidInstructionData),
+        );
+    }
+
+    let fetcher = |address: &Pubkey| svm.get_account(address);
+    assert_eq!(
+        client::vault_label(&fetcher, &vault_pda).as_deref(),
+        Some(""),
+        "Label should stay empty"
+    );
+}
+
+#[test]
+#[allow(deprecated)] // The runtime still reports a missing account as `NotEnoughAccountKeys`
+fn test_price_gated_withdraw_rejects_stale_price() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let oracle = Pubkey::new_unique();
+    let max_price_age = 60;
+
+    let open_ix = create_open_vault_instruction(owner.pubkey(), vault_pda);
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, 2 * LAMPORTS_PER_SOL);
+    let set_oracle_ix =
+        create_set_oracle_instruction(owner.pubkey(), vault_pda, oracle, max_price_age);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[open_ix, deposit_ix, set_oracle_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx)
+        .expect("Opening, funding and gating the vault should succeed");
+
+    let mut withdraw_ix =
+        create_withdraw_amount_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL, 0);
+    withdraw_ix
+        .accounts
+        .push(AccountMeta::new_readonly(oracle, false)); // oracle
+
+    // A price published within the window lets the withdrawal through
+    let now = svm.get_sysvar::<Clock>().unix_timestamp;
+    set_mock_oracle(&mut svm, oracle, now - max_price_age as i64);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix.clone()],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx)
+        .expect("Withdraw with a fresh price should succeed");
+
+    // One second older than the window is stale
+    set_mock_oracle(&mut svm, oracle, now - max_price_age as i64 - 1);
+    svm.expire_blockhash();
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    let tx_result = svm.send_transaction(tx);
+    assert_eq!(
+        tx_result.expect_err("Withdraw with a stale price should fail").err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::StalePrice as u32)
+        ),
+    );
+
+    // The gate can't be skipped by leaving the oracle out
+    svm.expire_blockhash();
+    let withdraw_ix = create_withdraw_instruction(owner.pubkey(), vault_pda);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    let tx_result = svm.send_transaction(tx);
+    assert_eq!(
+        tx_result
+            .expect_err("Withdraw without the oracle should fail")
+            .err,
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys),
+    );
+}
+
+#[test]
+fn test_withdraw_vested_releases_linearly() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let deposit_amount = 4 * LAMPORTS_PER_SOL;
+    let vesting_duration = 1_000;
+    let rent_floor = svm.minimum_balance_for_rent_exemption(VaultState::LEN);
+
+    let open_ix = create_open_vault_instruction(owner.pubkey(), vault_pda);
+    let deposit_ix = create_vesting_deposit_instruction(
+        owner.pubkey(),
+        vault_pda,
+        deposit_amount,
+        vesting_duration,
+    );
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[open_ix, deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx)
+        .expect("Opening and funding a vesting vault should succeed");
+
+    // Plain withdrawals can't bypass the schedule
+    let withdraw_ix = create_withdraw_instruction(owner.pubkey(), vault_pda);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    let tx_result = svm.send_transaction(tx);
+    assert_eq!(
+        tx_result
+            .expect_err("Withdraw from a vesting vault should fail")
+            .err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::VestingLocked as u32)
+        ),
+    );
+
+    // Halfway through the schedule
+    let mut clock = svm.get_sysvar::<Clock>();
+    clock.unix_timestamp += vesting_duration as i64 / 2;
+    svm.set_sysvar::<Clock>(&clock);
+
+    let withdraw_vested_ix = create_withdraw_vested_instruction(owner.pubkey(), vault_pda);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_vested_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx).expect("WithdrawVested should succeed");
+
+    assert_eq!(
+        svm.get_account(&vault_pda)
+            .expect("Vault should still exist")
+            .lamports,
+        rent_floor + deposit_amount / 2,
+        "Only half of the deposit should be withdrawable"
+    );
+
+    // Nothing more has vested in the meantime
+    svm.expire_blockhash();
+    let withdraw_vested_ix = create_withdraw_vested_instruction(owner.pubkey(), vault_pda);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_vested_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    let tx_result = svm.send_transaction(tx);
+    assert_eq!(
+        tx_result.expect_err("Nothing new should have vested").err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::VaultEmpty as u32)
+        ),
+    );
+}
+
+#[test]
+fn test_simulate_withdraw_reports_outcome_without_moving_funds() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let payer = Keypair::new();
+    svm.airdrop(&payer.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (plain_vault, _bump) = find_vault_pda_with_id(&owner.pubkey(), 1);
+    let (vesting_vault, _bump) = find_vault_pda(&owner.pubkey());
+
+    let deposit_plain_ix =
+        create_deposit_instruction_with_id(owner.pubkey(), plain_vault, LAMPORTS_PER_SOL, 1);
+    let open_ix = create_open_vault_instruction(owner.pubkey(), vesting_vault);
+    let deposit_vesting_ix =
+        create_vesting_deposit_instruction(owner.pubkey(), vesting_vault, LAMPORTS_PER_SOL, 1_000);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_plain_ix, open_ix, deposit_vesting_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx)
+        .expect("Funding both vaults should succeed");
+
+    let balance = |svm: &LiteSVM, address: &Pubkey| {
+        svm.get_account(address)
+            .expect("Account should exist")
+            .lamports
+    };
+    let owner_before = balance(&svm, &owner.pubkey());
+    let vesting_before = balance(&svm, &vesting_vault);
+
+    // A locked vault reports why its withdraw would fail
+    let simulate_ix = create_simulate_withdraw_instruction(owner.pubkey(), vesting_vault);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[simulate_ix],
+        Some(&payer.pubkey()),
+        &[&payer, &owner],
+        recent_blockhash,
+    );
+    let tx_result = svm
+        .send_transaction(tx)
+        .expect("SimulateWithdraw should succeed");
+
+    assert_eq!(
+        simulated_withdraw(&tx_result.return_data.data),
+        (VaultError::VestingLocked as u64, 0),
+        "Simulation should report the vesting lock"
+    );
+    assert_eq!(balance(&svm, &vesting_vault), vesting_before);
+    assert_eq!(balance(&svm, &owner.pubkey()), owner_before);
+
+    // An unlocked vault reports the amount a withdraw would move
+    let mut simulate_ix = create_simulate_withdraw_instruction(owner.pubkey(), plain_vault);
+    simulate_ix.data.extend_from_slice(&1u64.to_le_bytes());
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[simulate_ix],
+        Some(&payer.pubkey()),
+        &[&payer, &owner],
+        recent_blockhash,
+    );
+    let tx_result = svm
+        .send_transaction(tx)
+        .expect("SimulateWithdraw should succeed");
+
+    assert_eq!(
+        simulated_withdraw(&tx_result.return_data.data),
+        (0, LAMPORTS_PER_SOL)
+    );
+    assert_eq!(balance(&svm, &plain_vault), LAMPORTS_PER_SOL);
+}
+
+#[test]
+fn test_referred_deposit_splits_fee_to_referrer() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let admin = Keypair::new();
+    set_upgrade_authority(&mut svm, &admin.pubkey());
+    let owner = Keypair::new();
+    let referrer = Pubkey::new_unique();
+    svm.airdrop(&admin.pubkey(), LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+    svm.airdrop(&referrer, LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    // 2.5% of every referred deposit goes to the referrer
+    let initialize_ix = create_initialize_instruction(admin.pubkey(), 250);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[initialize_ix],
+        Some(&admin.pubkey()),
+        &[&admin],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx).expect("Initialize should succeed");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let deposit_amount = 2 * LAMPORTS_PER_SOL;
+    let deposit_ix =
+        create_referred_deposit_instruction(owner.pubkey(), vault_pda, deposit_amount, referrer);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx)
+        .expect("Referred deposit should succeed");
+
+    let fee = deposit_amount * 250 / 10_000;
+    assert_eq!(
+        svm.get_account(&referrer).unwrap().lamports,
+        LAMPORTS_PER_SOL + fee,
+        "Referrer should receive its cut"
+    );
+    assert_eq!(
+        svm.get_account(&vault_pda).unwrap().lamports,
+        deposit_amount - fee,
+        "Vault should receive the remainder"
+    );
+
+    // Initializing a second time is rejected
+    let initialize_ix = create_initialize_instruction(owner.pubkey(), 0);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[initialize_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    assert!(
+        svm.send_transaction(tx).is_err(),
+        "Config should only be initialized once"
+    );
+}
+
+#[test]
+fn test_deposit_to_non_canonical_bump_vault_fails() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    // Derive the vault seeds with the highest valid bump below the canonical one
+    let (_vault_pda, canonical_bump) = find_vault_pda(&owner.pubkey());
+    let non_canonical_vault = (0..canonical_bump)
+        .rev()
+        .find_map(|bump| {
+            Pubkey::create_program_address(
+                &[b"vault", owner.pubkey().as_ref(), &[bump]],
+                &PROGRAM_ID,
+            )
+            .ok()
+        })
+        .expect("A non-canonical bump should exist");
+
+    let deposit_ix =
+        create_deposit_instruction(owner.pubkey(), non_canonical_vault, LAMPORTS_PER_SOL);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+
+    let tx_result = svm.send_transaction(tx);
+    assert_eq!(
+        tx_result
+            .expect_err("Deposit to a non-canonical bump address should fail")
+            .err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::InvalidPda as u32)
+        ),
+    );
+}
+
+#[test]
+fn test_deposit_many_funds_each_owners_vault() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let payer = Keypair::new();
+    svm.airdrop(&payer.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let deposits = [
+        (Pubkey::new_unique(), LAMPORTS_PER_SOL),
+        (Pubkey::new_unique(), 2 * LAMPORTS_PER_SOL),
+        (Pubkey::new_unique(), 3 * LAMPORTS_PER_SOL),
+    ];
+    let deposit_many_ix = create_deposit_many_instruction(payer.pubkey(), &deposits);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_many_ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx)
+        .expect("DepositMany should succeed");
+
+    for (owner, amount) in deposits {
+        let (vault_pda, _bump) = find_vault_pda(&owner);
+        assert_eq!(
+            svm.get_account(&vault_pda).unwrap().lamports,
+            amount,
+            "Each vault should hold its owner's amount"
+        );
+    }
+
+    let fetcher = |address: &Pubkey| svm.get_account(address);
+    let stats = client::global_stats(&fetcher).expect("Stats should exist");
+    assert_eq!(stats.total_vaults(), 3);
+    assert_eq!(stats.lifetime_deposits(), 6 * LAMPORTS_PER_SOL);
+}
+
+#[test]
+fn test_split_deposit_divides_amount_between_vaults() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 20 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (savings_vault, _bump) = find_vault_pda_with_id(&owner.pubkey(), 1);
+    let (spending_vault, _bump) = find_vault_pda_with_id(&owner.pubkey(), 2);
+
+    // 70% to savings, 30% to spending
+    let split_ix =
+        create_split_deposit_instruction(owner.pubkey(), 10 * LAMPORTS_PER_SOL, 7_000, 1, 2);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[split_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx)
+        .expect("SplitDeposit should succeed");
+
+    assert_eq!(
+        svm.get_account(&savings_vault).unwrap().lamports,
+        7 * LAMPORTS_PER_SOL
+    );
+    assert_eq!(
+        svm.get_account(&spending_vault).unwrap().lamports,
+        3 * LAMPORTS_PER_SOL
+    );
+
+    // A split above 100% is rejected
+    let split_ix =
+        create_split_deposit_instruction(owner.pubkey(), LAMPORTS_PER_SOL, 10_001, 3, 4);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[split_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    assert_eq!(
+        svm.send_transaction(tx)
+            .expect_err("A split above 10000 bps should fail")
+            .err,
+        TransactionError::InstructionError(0, InstructionError::InvalidInstructionData),
+    );
+}
+
+#[test]
+fn test_withdraw_leaves_externally_donated_lamports() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let payer = Keypair::new();
+    let owner = Keypair::new();
+    svm.airdrop(&payer.pubkey(), LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let rent_floor = svm.minimum_balance_for_rent_exemption(VaultState::LEN);
+    let deposit_amount = 2 * LAMPORTS_PER_SOL;
+
+    let open_ix = create_open_vault_instruction(owner.pubkey(), vault_pda);
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, deposit_amount);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[open_ix, deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx)
+        .expect("Open and deposit should succeed");
+
+    // Lamports sent straight to the vault aren't part of its tracked balance
+    let donation = LAMPORTS_PER_SOL;
+    svm.airdrop(&vault_pda, donation)
+        .expect("Failed to airdrop");
+
+    let owner_before = svm.get_account(&owner.pubkey()).unwrap().lamports;
+
+    let withdraw_ix = create_withdraw_instruction(owner.pubkey(), vault_pda);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix],
+        Some(&payer.pubkey()),
+        &[&payer, &owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx).expect("Withdraw should succeed");
+
+    assert_eq!(
+        svm.get_account(&owner.pubkey()).unwrap().lamports,
+        owner_before + deposit_amount,
+        "Only the tracked deposit should be returned"
+    );
+
+    let vault_account = svm
+        .get_account(&vault_pda)
+        .expect("Vault should stay open to hold the donation");
+    assert_eq!(vault_account.lamports, rent_floor + donation);
+    let state = VaultState::from_bytes(&vault_account.data).expect("State should be readable");
+    assert_eq!(state.balance(), 0);
+}
+
+#[test]
+fn test_merge_vaults_moves_source_into_dest() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (source_vault, _bump) = find_vault_pda_with_id(&owner.pubkey(), 1);
+    let (dest_vault, _bump) = find_vault_pda_with_id(&owner.pubkey(), 2);
+
+    let deposit_source_ix =
+        create_deposit_instruction_with_id(owner.pubkey(), source_vault, LAMPORTS_PER_SOL, 1);
+    let deposit_dest_ix =
+        create_deposit_instruction_with_id(owner.pubkey(), dest_vault, 2 * LAMPORTS_PER_SOL, 2);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_source_ix, deposit_dest_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx).expect("Deposits should succeed");
+
+    // `[source_id][dest_id]` naming a vault other than the dest account
+    let mut mismatched_ix = create_merge_vaults_instruction(owner.pubkey(), 1, 2);
+    mismatched_ix.data[9..17].copy_from_slice(&3u64.to_le_bytes());
+    let tx = Transaction::new_signed_with_payer(
+        &[mismatched_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        svm.latest_blockhash(),
+    );
+    assert_eq!(
+        svm.send_transaction(tx).map(|_| ()).map_err(|f| f.err),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::InvalidPda as u32)
+        ))
+    );
+
+    let merge_ix = create_merge_vaults_instruction(owner.pubkey(), 1, 2);
+
+    svm.expire_blockhash();
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[merge_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    let meta = svm.send_transaction(tx).expect("Merge should succeed");
+
+    assert_eq!(
+        svm.get_account(&dest_vault).unwrap().lamports,
+        3 * LAMPORTS_PER_SOL,
+        "Dest should hold the combined balance"
+    );
+    assert_eq!(
+        meta.return_data.data[..8],
+        (3 * LAMPORTS_PER_SOL).to_le_bytes(),
+        "Merge should return the dest's new balance"
+    );
+    assert!(
+        svm.get_account(&source_vault).is_none(),
+        "Source should be closed"
+    );
+}
+
+#[test]
+fn test_first_deposit_with_open_flag_creates_program_owned_vault() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, bump) = find_vault_pda(&owner.pubkey());
+    let rent_floor = svm.minimum_balance_for_rent_exemption(VaultState::LEN);
+    let deposit_amount = 2 * LAMPORTS_PER_SOL;
+
+    let deposit_ix = create_opening_deposit_instruction(owner.pubkey(), vault_pda, deposit_amount);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx)
+        .expect("Opening deposit should succeed");
+
+    let vault_account = svm
+        .get_account(&vault_pda)
+        .expect("Vault account should exist");
+    assert_eq!(vault_account.owner, PROGRAM_ID, "Vault should be program-owned");
+    assert_eq!(vault_account.lamports, rent_floor + deposit_amount);
+
+    let state = VaultState::from_bytes(&vault_account.data).expect("State should be readable");
+    assert_eq!(state.owner(), &owner.pubkey().to_bytes());
+    assert_eq!(state.bump(), bump);
+    assert_eq!(state.balance(), deposit_amount);
+
+    // Later deposits with the flag just add to the opened vault
+    let deposit_ix = create_opening_deposit_instruction(owner.pubkey(), vault_pda, deposit_amount);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx)
+        .expect("Second deposit should succeed");
+
+    assert_eq!(
+        svm.get_account(&vault_pda).unwrap().lamports,
+        rent_floor + 2 * deposit_amount
+    );
+}
+
+#[test]
+fn test_daily_withdrawal_limit_resets_after_a_day() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let daily_limit = LAMPORTS_PER_SOL;
+
+    let mut open_ix = create_open_vault_instruction_with_flags(
+        owner.pubkey(),
+        vault_pda,
+        VaultState::FLAG_DAILY_LIMIT,
+    );
+    open_ix.data.extend_from_slice(&daily_limit.to_le_bytes());
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, 3 * LAMPORTS_PER_SOL);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[open_ix, deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx)
+        .expect("Open and deposit should succeed");
+
+    let withdraw = |svm: &mut LiteSVM, amount: u64| {
+        svm.expire_blockhash();
+        let withdraw_ix = create_withdraw_amount_instruction(owner.pubkey(), vault_pda, amount, 0);
+        let tx = Transaction::new_signed_with_payer(
+            &[withdraw_ix],
+            Some(&owner.pubkey()),
+            &[&owner],
+            svm.latest_blockhash(),
+        );
+        svm.send_transaction(tx)
+            .map(|_| ())
+            .map_err(|failed| failed.err)
+    };
+
+    // Up to the limit in two withdrawals
+    withdraw(&mut svm, daily_limit / 2).expect("First withdrawal should succeed");
+    withdraw(&mut svm, daily_limit / 2).expect("Withdrawing up to the limit should succeed");
+
+    assert_eq!(
+        withdraw(&mut svm, 1).expect_err("Withdrawing past the limit should fail"),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::DailyLimitExceeded as u32)
+        ),
+    );
+
+    // A day later the allowance is back
+    let mut clock = svm.get_sysvar::<Clock>();
+    clock.unix_timestamp += VaultState::WITHDRAW_WINDOW;
+    svm.set_sysvar::<Clock>(&clock);
+
+    withdraw(&mut svm, daily_limit).expect("Withdrawal in the next window should succeed");
+}
+
+#[test]
+fn test_client_instruction_builders_deposit_and_withdraw() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[client::deposit_instruction(&owner.pubkey(), LAMPORTS_PER_SOL)],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx).expect("Deposit should succeed");
+
+    let fetcher = |address: &Pubkey| svm.get_account(address);
+    assert_eq!(
+        client::vault_balance(&fetcher, &owner.pubkey()),
+        Some(LAMPORTS_PER_SOL)
+    );
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[client::withdraw_instruction(&owner.pubkey())],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx).expect("Withdraw should succeed");
+
+    let fetcher = |address: &Pubkey| svm.get_account(address);
+    assert_eq!(client::vault_balance(&fetcher, &owner.pubkey()), None);
+}
+
+#[test]
+fn test_close_refuses_non_empty_vault_and_close_and_sweep_returns_funds() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let payer = Keypair::new();
+    let owner = Keypair::new();
+    svm.airdrop(&payer.pubkey(), LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let rent_floor = svm.minimum_balance_for_rent_exemption(VaultState::LEN);
+    let deposit_amount = 2 * LAMPORTS_PER_SOL;
+
+    let open_ix = create_open_vault_instruction(owner.pubkey(), vault_pda);
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, deposit_amount);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[open_ix, deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx)
+        .expect("Open and deposit should succeed");
+
+    // The safe close won't take the tracked balance along with the rent
+    let close_ix = create_close_instruction(owner.pubkey(), vault_pda, 16);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[close_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    let result = svm.send_transaction(tx);
+    assert_eq!(
+        result.unwrap_err().err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::VaultNotEmpty as u32)
+        )
+    );
+    assert_eq!(
+        svm.get_account(&vault_pda).unwrap().lamports,
+        rent_floor + deposit_amount
+    );
+
+    let owner_before = svm.get_account(&owner.pubkey()).unwrap().lamports;
+
+    let sweep_ix = create_close_instruction(owner.pubkey(), vault_pda, 17);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[sweep_ix],
+        Some(&payer.pubkey()),
+        &[&payer, &owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx)
+        .expect("CloseAndSweep should succeed");
+
+    assert_eq!(
+        svm.get_account(&owner.pubkey()).unwrap().lamports,
+        owner_before + deposit_amount + rent_floor,
+        "The balance and the rent reserve should both be returned"
+    );
+    assert!(
+        svm.get_account(&vault_pda).is_none(),
+        "Vault should be closed"
+    );
+}
+
+#[test]
+fn test_opened_vault_records_state_version() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+
+    let open_ix = create_open_vault_instruction(owner.pubkey(), vault_pda);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[open_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx).expect("OpenVault should succeed");
+
+    let vault_account = svm.get_account(&vault_pda).expect("Vault should exist");
+    let state = VaultState::from_bytes(&vault_account.data).expect("State should be readable");
+    assert_eq!(state.version(), VaultState::VERSION);
+    assert_eq!(state.version(), 2);
+}
+
+#[test]
+fn test_unsupported_state_version_is_rejected() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+
+    let open_ix = create_open_vault_instruction(owner.pubkey(), vault_pda);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[open_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx).expect("OpenVault should succeed");
+
+    // Hand-craft a vault written by some future layout
+    let mut future = svm.get_account(&vault_pda).expect("Vault should exist");
+    future.data[0] = 255;
+    svm.set_account(vault_pda, future.clone())
+        .expect("Failed to overwrite vault");
+
+    assert!(
+        VaultState::from_bytes(&future.data).is_none(),
+        "Off-chain parsing should reject the version"
+    );
+
+    let set_label_ix = create_set_label_instruction(owner.pubkey(), vault_pda, b"Savings");
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[set_label_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    let result = svm.send_transaction(tx);
+    assert_eq!(
+        result.unwrap_err().err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::UnsupportedStateVersion as u32)
+        )
+    );
+}
+
+#[test]
+fn test_upgrade_vault_state_grows_an_older_layout() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let upgrade_ix = || {
+        let mut data = vec![60u8]; // Discriminator for UpgradeVaultState
+        data.extend_from_slice(&0u64.to_le_bytes()); // id
+        data.extend_from_slice(&[0u8; 32]); // salt
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(owner.pubkey(), true), // owner (signer, writable)
+                AccountMeta::new(find_vault_pda(&owner.pubkey()).0, false), // vault
+                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system program
+            ],
+            data,
+        }
+    };
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    send(
+        &mut svm,
+        create_open_vault_instruction(owner.pubkey(), vault_pda),
+        &owner,
+    )
+    .expect("OpenVault should succeed");
+    send(
+        &mut svm,
+        create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL),
+        &owner,
+    )
+    .expect("Deposit should succeed");
+
+    // An up-to-date vault has nothing to upgrade
+    assert_eq!(
+        send(&mut svm, upgrade_ix(), &owner),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::InvalidAccountData
+        ))
+    );
+
+    // Hand-craft the version 1 layout from before the receipt fields
+    let old_len = VaultState::LEN - 40;
+    let mut old = svm.get_account(&vault_pda).expect("Vault should exist");
+    old.data.truncate(old_len);
+    old.data[0] = 1;
+    old.lamports = svm.minimum_balance_for_rent_exemption(old_len) + LAMPORTS_PER_SOL;
+    svm.set_account(vault_pda, old)
+        .expect("Failed to overwrite vault");
+
+    assert_eq!(
+        send(
+            &mut svm,
+            create_set_label_instruction(owner.pubkey(), vault_pda, b"Savings"),
+            &owner
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::UnsupportedStateVersion as u32)
+        ))
+    );
+
+    send(&mut svm, upgrade_ix(), &owner).expect("UpgradeVaultState should succeed");
+    let vault_account = svm.get_account(&vault_pda).expect("Vault should exist");
+    assert_eq!(
+        vault_account.lamports,
+        svm.minimum_balance_for_rent_exemption(VaultState::LEN) + LAMPORTS_PER_SOL
+    );
+    let state = VaultState::from_bytes(&vault_account.data).expect("State should be readable");
+    assert_eq!(state.version(), VaultState::VERSION);
+    assert_eq!(state.balance(), LAMPORTS_PER_SOL);
+    assert_eq!(state.receipt_supply(), 0);
+
+    send(
+        &mut svm,
+        create_set_label_instruction(owner.pubkey(), vault_pda, b"Savings"),
+        &owner,
+    )
+    .expect("An upgraded vault should be usable again");
+}
+
+#[test]
+fn test_withdraw_to_enforces_destination_allowlist() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let allowed = Pubkey::new_unique();
+    let other = Pubkey::new_unique();
+    let deposit_amount = LAMPORTS_PER_SOL;
+
+    let open_ix = create_open_vault_instruction(owner.pubkey(), vault_pda);
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, deposit_amount);
+    let add_ix = create_allowed_dest_instruction(owner.pubkey(), vault_pda, allowed, 18);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[open_ix, deposit_ix, add_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx)
+        .expect("Open, deposit and AddAllowedDest should succeed");
+
+    let vault_account = svm.get_account(&vault_pda).expect("Vault should exist");
+    let state = VaultState::from_bytes(&vault_account.data).expect("State should be readable");
+    assert_eq!(state.allowed_dests(), &[allowed.to_bytes()]);
+
+    let mut withdraw_to = |recipient: Pubkey| {
+        let withdraw_ix = create_withdraw_to_instruction(owner.pubkey(), vault_pda, recipient);
+        let recent_blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[withdraw_ix],
+            Some(&owner.pubkey()),
+            &[&owner],
+            recent_blockhash,
+        );
+        svm.send_transaction(tx).map(|_| ()).map_err(|f| f.err)
+    };
+
+    // A recipient missing from the allowlist is rejected
+    assert_eq!(
+        withdraw_to(other),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::DestinationNotAllowed as u32)
+        ))
+    );
+    withdraw_to(allowed).expect("WithdrawTo an allowed recipient should succeed");
+    assert_eq!(svm.get_account(&allowed).unwrap().lamports, deposit_amount);
+
+    // Emptying the allowlist lets any recipient through again
+    let remove_ix = create_allowed_dest_instruction(owner.pubkey(), vault_pda, allowed, 19);
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, deposit_amount);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[remove_ix, deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx)
+        .expect("RemoveAllowedDest and deposit should succeed");
+
+    let withdraw_ix = create_withdraw_to_instruction(owner.pubkey(), vault_pda, other);
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx)
+        .expect("WithdrawTo with an empty allowlist should succeed");
+    assert_eq!(svm.get_account(&other).unwrap().lamports, deposit_amount);
+}
+
+#[test]
+fn test_withdraw_min_out_guards_against_slippage() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let deposit_amount = LAMPORTS_PER_SOL;
+
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, deposit_amount);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx).expect("Deposit should succeed");
+
+    let vault_lamports = svm.get_account(&vault_pda).unwrap().lamports;
+
+    let mut withdraw = |min_out: u64| {
+        let mut withdraw_ix =
+            create_withdraw_instruction_with_salt(owner.pubkey(), vault_pda, &[0u8; 32]);
+        withdraw_ix.data.extend_from_slice(&min_out.to_le_bytes());
+
+        let recent_blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[withdraw_ix],
+            Some(&owner.pubkey()),
+            &[&owner],
+            recent_blockhash,
+        );
+        svm.send_transaction(tx).map(|_| ()).map_err(|f| f.err)
+    };
+
+    // More than the vault holds can never be withdrawn
+    assert_eq!(
+        withdraw(vault_lamports + 1),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::SlippageExceeded as u32)
+        ))
+    );
+    withdraw(deposit_amount).expect("Withdraw within min_out should succeed");
+
+    assert!(
+        svm.get_account(&vault_pda).is_none(),
+        "Vault should be closed"
+    );
+}
+
+#[test]
+fn test_extend_lock_only_pushes_unlock_back() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let now = svm.get_sysvar::<Clock>().unix_timestamp;
+    let unlock_ts = now + 3600;
+
+    let open_ix = create_open_vault_instruction(owner.pubkey(), vault_pda);
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL);
+    let lock_ix = create_extend_lock_instruction(owner.pubkey(), vault_pda, unlock_ts);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[open_ix, deposit_ix, lock_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx)
+        .expect("Open, deposit and ExtendLock should succeed");
+
+    let mut send = |instruction: Instruction| {
+        let recent_blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&owner.pubkey()),
+            &[&owner],
+            recent_blockhash,
+        );
+        let result = svm.send_transaction(tx).map(|_| ()).map_err(|f| f.err);
+        svm.expire_blockhash();
+        result
+    };
+
+    // Extending succeeds, shortening or repeating the current unlock doesn't
+    send(create_extend_lock_instruction(owner.pubkey(), vault_pda, unlock_ts + 60))
+        .expect("Extending the lock should succeed");
+    for shorter in [unlock_ts, unlock_ts + 60] {
+        assert_eq!(
+            send(create_extend_lock_instruction(owner.pubkey(), vault_pda, shorter)),
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(VaultError::CannotShortenLock as u32)
+            ))
+        );
+    }
+
+    assert_eq!(
+        send(create_withdraw_instruction(owner.pubkey(), vault_pda)),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::VaultLocked as u32)
+        ))
+    );
+
+    let vault_account = svm.get_account(&vault_pda).expect("Vault should exist");
+    let state = VaultState::from_bytes(&vault_account.data).expect("State should be readable");
+    assert_eq!(state.unlock_ts(), unlock_ts + 60);
+
+    // Once the lock expires the vault withdraws as usual
+    let mut clock = svm.get_sysvar::<Clock>();
+    clock.unix_timestamp = unlock_ts + 60;
+    svm.set_sysvar::<Clock>(&clock);
+
+    let withdraw_ix = create_withdraw_instruction(owner.pubkey(), vault_pda);
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx)
+        .expect("Withdraw after the unlock should succeed");
+}
+
+#[test]
+fn test_client_fetch_vault_state_decodes_fields() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, bump) = find_vault_pda(&owner.pubkey());
+    let unlock_ts = svm.get_sysvar::<Clock>().unix_timestamp + 3600;
+    let deposit_amount = LAMPORTS_PER_SOL;
+
+    let fetcher = |address: &Pubkey| svm.get_account(address);
+    assert!(
+        client::fetch_vault_state(&fetcher, &owner.pubkey(), 0).is_none(),
+        "A missing vault has no state"
+    );
+
+    let open_ix = create_open_vault_instruction_with_label(owner.pubkey(), vault_pda, b"Savings");
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, deposit_amount);
+    let lock_ix = create_extend_lock_instruction(owner.pubkey(), vault_pda, unlock_ts);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[open_ix, deposit_ix, lock_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx)
+        .expect("Open, deposit and ExtendLock should succeed");
+
+    let fetcher = |address: &Pubkey| svm.get_account(address);
+    let state = client::fetch_vault_state(&fetcher, &owner.pubkey(), 0)
+        .expect("Opened vault should decode");
+    assert_eq!(state.version(), VaultState::VERSION);
+    assert_eq!(state.owner(), &owner.pubkey().to_bytes());
+    assert_eq!(state.id(), 0);
+    assert_eq!(state.bump(), bump);
+    assert_eq!(state.label(), b"Savings");
+    assert_eq!(state.unlock_ts(), unlock_ts);
+    assert_eq!(state.balance(), deposit_amount);
+    assert_eq!(state.total_deposited(), deposit_amount);
+
+    // Truncated data isn't a vault state
+    let mut truncated = svm.get_account(&vault_pda).unwrap();
+    truncated.data.pop();
+    svm.set_account(vault_pda, truncated)
+        .expect("Failed to truncate vault");
+
+    let fetcher = |address: &Pubkey| svm.get_account(address);
+    assert!(client::fetch_vault_state(&fetcher, &owner.pubkey(), 0).is_none());
+}
+
+#[test]
+fn test_batch_instructions_reject_duplicate_vaults() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let duplicate = Err(TransactionError::InstructionError(
+        0,
+        InstructionError::Custom(VaultError::DuplicateAccount as u32),
+    ));
+
+    let mut send = |instruction: Instruction| {
+        let recent_blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&owner.pubkey()),
+            &[&owner],
+            recent_blockhash,
+        );
+        let result = svm.send_transaction(tx).map(|_| ()).map_err(|f| f.err);
+        svm.expire_blockhash();
+        result
+    };
+
+    let deposit_many_ix = create_deposit_many_instruction(
+        owner.pubkey(),
+        &[(owner.pubkey(), LAMPORTS_PER_SOL), (owner.pubkey(), LAMPORTS_PER_SOL)],
+    );
+    assert_eq!(send(deposit_many_ix), duplicate);
+
+    send(create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL))
+        .expect("Deposit should succeed");
+
+    let withdraw_many_ix =
+        create_withdraw_many_instruction(owner.pubkey(), &[(vault_pda, 0), (vault_pda, 0)]);
+    assert_eq!(send(withdraw_many_ix), duplicate);
+
+    assert_eq!(
+        svm.get_account(&vault_pda).unwrap().lamports,
+        LAMPORTS_PER_SOL,
+        "The vault should be untouched"
+    );
+}
+
+#[test]
+fn test_sweep_token_recovers_tokens_sent_to_vault_ata() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+
+    // Someone sends an unexpected token to the vault's associated token account
+    let ata_program = solana_sdk::pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+    let mint = Pubkey::new_unique();
+    let (vault_ata, _) = Pubkey::find_program_address(
+        &[vault_pda.as_ref(), TOKEN_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &ata_program,
+    );
+    set_token_account(&mut svm, vault_ata, &mint, &vault_pda, 1_000);
+
+    let destination = Pubkey::new_unique();
+    set_token_account(&mut svm, destination, &mint, &owner.pubkey(), 0);
+
+    let sweep_ix =
+        create_sweep_token_instruction(owner.pubkey(), vault_pda, vault_ata, destination);
+    let tx = Transaction::new_signed_with_payer(
+        &[sweep_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        svm.latest_blockhash(),
+    );
+    let result = svm.send_transaction(tx);
+    assert!(result.is_ok(), "Sweep should succeed: {:?}", result.err());
+
+    assert_eq!(token_balance(&svm, &vault_ata), 0);
+    assert_eq!(token_balance(&svm, &destination), 1_000);
+}
+
+#[test]
+fn test_sweep_token_rejects_account_not_owned_by_vault() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+
+    // A token account owned by someone else can't be swept through the vault
+    let mint = Pubkey::new_unique();
+    let token_account = Pubkey::new_unique();
+    set_token_account(&mut svm, token_account, &mint, &Pubkey::new_unique(), 1_000);
+
+    let destination = Pubkey::new_unique();
+    set_token_account(&mut svm, destination, &mint, &owner.pubkey(), 0);
+
+    let sweep_ix =
+        create_sweep_token_instruction(owner.pubkey(), vault_pda, token_account, destination);
+    let tx = Transaction::new_signed_with_payer(
+        &[sweep_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        svm.latest_blockhash(),
+    );
+    let result = svm.send_transaction(tx);
+    assert!(result.is_err(), "Sweep of a foreign token account should fail");
+
+    assert_eq!(token_balance(&svm, &token_account), 1_000);
+}
+
+#[test]
+fn test_deposit_for_funds_vault_only_owner_can_withdraw() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    svm.airdrop(&alice.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop to Alice");
+    svm.airdrop(&bob.pubkey(), LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop to Bob");
+
+    let (bob_vault, _bump) = find_vault_pda(&bob.pubkey());
+
+    // Alice pays for a deposit into Bob's vault without Bob signing
+    let deposit_ix =
+        create_deposit_for_instruction(alice.pubkey(), bob_vault, bob.pubkey(), LAMPORTS_PER_SOL);
+    send(&mut svm, deposit_ix, &alice).expect("Alice should be able to fund Bob's vault");
+
+    // The vault is still Bob's, so Alice can't take the funds back out
+    let steal_ix = create_withdraw_instruction(alice.pubkey(), bob_vault);
+    assert!(
+        send(&mut svm, steal_ix, &alice).is_err(),
+        "Only the owner should be able to withdraw"
+    );
+
+    let bob_before = svm.get_balance(&bob.pubkey()).unwrap();
+    let withdraw_ix = create_withdraw_instruction(bob.pubkey(), bob_vault);
+    send(&mut svm, withdraw_ix, &bob).expect("Bob should be able to withdraw");
+
+    assert_eq!(svm.get_balance(&bob_vault).unwrap_or(0), 0);
+    assert!(svm.get_balance(&bob.pubkey()).unwrap() > bob_before);
+}
+
+#[test]
+fn test_deposit_source_whitelist_rejects_unlisted_funders() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    let listed = Keypair::new();
+    let unlisted = Keypair::new();
+    for signer in [&owner, &listed, &unlisted] {
+        svm.airdrop(&signer.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop");
+    }
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    send_all(
+        &mut svm,
+        &[
+            create_open_vault_instruction(owner.pubkey(), vault_pda),
+            create_allowed_source_instruction(owner.pubkey(), vault_pda, listed.pubkey(), 69),
+        ],
+        &[&owner],
+    )
+    .expect("OpenVault and AddAllowedSource should succeed");
+
+    let deposit_for = |funder: &Keypair| {
+        create_deposit_for_instruction(funder.pubkey(), vault_pda, owner.pubkey(), LAMPORTS_PER_SOL)
+    };
+    send(&mut svm, deposit_for(&listed), &listed)
+        .expect("DepositFor from a listed source should succeed");
+    assert_eq!(
+        send(&mut svm, deposit_for(&unlisted), &unlisted),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::SourceNotWhitelisted as u32)
+        ))
+    );
+
+    // The owner never needs listing
+    send(
+        &mut svm,
+        create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL),
+        &owner,
+    )
+    .expect("The owner's deposit should succeed");
+
+    // Emptying the whitelist lets anyone fund the vault again
+    send(
+        &mut svm,
+        create_allowed_source_instruction(owner.pubkey(), vault_pda, listed.pubkey(), 70),
+        &owner,
+    )
+    .expect("RemoveAllowedSource should succeed");
+    send(&mut svm, deposit_for(&unlisted), &unlisted)
+        .expect("DepositFor without a whitelist should succeed");
+
+    let account = svm.get_account(&vault_pda).expect("Vault should exist");
+    let state = VaultState::from_bytes(&account.data).expect("State should be readable");
+    assert_eq!(state.balance(), 3 * LAMPORTS_PER_SOL);
+}
+
+#[test]
+fn test_withdraw_to_degenerate_recipients() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let deposit_amount = LAMPORTS_PER_SOL;
+
+    // The allowlist doesn't name the owner, who can still withdraw to themselves
+    let open_ix = create_open_vault_instruction(owner.pubkey(), vault_pda);
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, deposit_amount);
+    let add_ix =
+        create_allowed_dest_instruction(owner.pubkey(), vault_pda, Pubkey::new_unique(), 18);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[open_ix, deposit_ix, add_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx)
+        .expect("Open, deposit and AddAllowedDest should succeed");
+
+    let withdraw_to = |svm: &mut LiteSVM, recipient: Pubkey| {
+        let withdraw_ix = create_withdraw_to_instruction(owner.pubkey(), vault_pda, recipient);
+        let recent_blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[withdraw_ix],
+            Some(&owner.pubkey()),
+            &[&owner],
+            recent_blockhash,
+        );
+        svm.send_transaction(tx).map(|_| ()).map_err(|f| f.err)
+    };
+
+    // The vault can't be its own recipient
+    assert_eq!(
+        withdraw_to(&mut svm, vault_pda),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::InvalidRecipient as u32)
+        ))
+    );
+
+    // The owner as recipient is a plain withdraw of the tracked balance
+    let vault_before = svm.get_balance(&vault_pda).unwrap();
+    let owner_before = svm.get_balance(&owner.pubkey()).unwrap();
+    withdraw_to(&mut svm, owner.pubkey()).expect("WithdrawTo the owner should succeed");
+
+    let vault_after = svm.get_balance(&vault_pda).unwrap();
+    assert_eq!(vault_before - vault_after, deposit_amount);
+    assert_eq!(
+        svm.get_balance(&owner.pubkey()).unwrap(),
+        owner_before + deposit_amount - 5_000
+    );
+
+    let vault_account = svm.get_account(&vault_pda).expect("Vault should stay open");
+    let state = VaultState::from_bytes(&vault_account.data).expect("State should be readable");
+    assert_eq!(state.balance(), 0);
+}
+
+#[test]
+fn test_withdraw_keep_alive_retains_balance() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, 2 * LAMPORTS_PER_SOL);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx).expect("Deposit should succeed");
+
+    let withdraw = |svm: &mut LiteSVM, keep_alive: u64| {
+        // `close = 1` is overridden by a non-zero `keep_alive`
+        let mut withdraw_ix =
+            create_withdraw_instruction_with_salt(owner.pubkey(), vault_pda, &[0u8; 32]);
+        withdraw_ix.data.extend_from_slice(&0u64.to_le_bytes()); // min_out
+        withdraw_ix.data.extend_from_slice(&keep_alive.to_le_bytes());
+
+        let recent_blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[withdraw_ix],
+            Some(&owner.pubkey()),
+            &[&owner],
+            recent_blockhash,
+        );
+        svm.send_transaction(tx).map(|_| ()).map_err(|f| f.err)
+    };
+
+    // Less than the rent-exempt reserve can't keep the account alive
+    assert_eq!(
+        withdraw(&mut svm, 1),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::AccountNotRentExempt
+        ))
+    );
+
+    let keep_alive = LAMPORTS_PER_SOL / 2;
+    withdraw(&mut svm, keep_alive).expect("Withdraw with keep_alive should succeed");
+
+    let vault_account = svm.get_account(&vault_pda).expect("Vault should still exist");
+    assert_eq!(vault_account.lamports, keep_alive);
+}
+
+#[test]
+fn test_withdraw_if_price_above_threshold() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    set_upgrade_authority(&mut svm, &owner.pubkey());
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    // The mock oracle holds a price of 150
+    let price_oracle = Pubkey::new_unique();
+    set_mock_oracle(&mut svm, price_oracle, 0);
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let mut initialize_ix = create_initialize_instruction(owner.pubkey(), 0);
+    initialize_ix.data.extend_from_slice(price_oracle.as_ref());
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[initialize_ix, deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx)
+        .expect("Initialize and deposit should succeed");
+
+    let withdraw = |svm: &mut LiteSVM, oracle: Pubkey, min_price: i64| {
+        let withdraw_ix = create_withdraw_if_price_above_instruction(
+            owner.pubkey(),
+            vault_pda,
+            oracle,
+            min_price,
+        );
+        let recent_blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[withdraw_ix],
+            Some(&owner.pubkey()),
+            &[&owner],
+            recent_blockhash,
+        );
+        let result = svm.send_transaction(tx).map(|_| ()).map_err(|f| f.err);
+        svm.expire_blockhash();
+        result
+    };
+
+    // A price account other than the configured one is rejected
+    let other_oracle = Pubkey::new_unique();
+    set_mock_oracle(&mut svm, other_oracle, 0);
+    assert_eq!(
+        withdraw(&mut svm, other_oracle, 100),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::InvalidAccountData
+        ))
+    );
+
+    // Below the threshold, or at it, the vault stays put
+    for min_price in [150, 200] {
+        assert_eq!(
+            withdraw(&mut svm, price_oracle, min_price),
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(VaultError::PriceConditionNotMet as u32)
+            ))
+        );
+    }
+    assert!(svm.get_account(&vault_pda).is_some());
+
+    withdraw(&mut svm, price_oracle, 100).expect("Withdraw above the threshold should succeed");
+    assert!(
+        svm.get_account(&vault_pda).is_none(),
+        "Vault should be closed"
+    );
+}
+
+#[test]
+fn test_withdraw_if_price_above_burns_receipt_tokens() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    set_upgrade_authority(&mut svm, &owner.pubkey());
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    // The mock oracle holds a price of 150
+    let price_oracle = Pubkey::new_unique();
+    set_mock_oracle(&mut svm, price_oracle, 0);
+    let mut initialize_ix = create_initialize_instruction(owner.pubkey(), 0);
+    initialize_ix.data.extend_from_slice(price_oracle.as_ref());
+    send(&mut svm, initialize_ix, &owner).expect("Initialize should succeed");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let mint = Pubkey::new_unique();
+    let receipt_account = Pubkey::new_unique();
+    set_receipt_mint(&mut svm, mint, &vault_pda);
+    set_token_account(&mut svm, receipt_account, &mint, &owner.pubkey(), 0);
+    send(
+        &mut svm,
+        create_receipt_deposit_instruction(
+            owner.pubkey(),
+            vault_pda,
+            LAMPORTS_PER_SOL,
+            mint,
+            receipt_account,
+        ),
+        &owner,
+    )
+    .expect("Deposit with a receipt should succeed");
+
+    // Without the receipt accounts the receipted lamports stay put
+    assert_eq!(
+        send(
+            &mut svm,
+            create_withdraw_if_price_above_instruction(
+                owner.pubkey(),
+                vault_pda,
+                price_oracle,
+                100
+            ),
+            &owner
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::ReceiptsOutstanding as u32)
+        ))
+    );
+
+    // `[min_price]` followed by the receipt-burning Withdraw data, with the
+    // price oracle ahead of the receipt accounts
+    let mut withdraw_ix =
+        create_receipt_withdraw_instruction(owner.pubkey(), vault_pda, mint, receipt_account);
+    let mut data = vec![24u8]; // Discriminator for WithdrawIfPriceAbove
+    data.extend_from_slice(&100i64.to_le_bytes());
+    data.extend_from_slice(&withdraw_ix.data[1..]);
+    withdraw_ix.data = data;
+    withdraw_ix
+        .accounts
+        .insert(4, AccountMeta::new_readonly(price_oracle, false)); // price oracle
+    send(&mut svm, withdraw_ix, &owner).expect("Withdraw burning the receipt should succeed");
+    assert_eq!(token_balance(&svm, &receipt_account), 0);
+    assert!(svm.get_account(&vault_pda).is_none());
+}
+
+#[test]
+fn test_vault_transfer_requires_both_owners() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    svm.airdrop(&alice.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop to Alice");
+    svm.airdrop(&bob.pubkey(), LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop to Bob");
+
+    let (alice_vault, _) = find_vault_pda(&alice.pubkey());
+    let (bob_vault, _) = find_vault_pda(&bob.pubkey());
+
+    let deposit_ix =
+        create_deposit_instruction(alice.pubkey(), alice_vault, 2 * LAMPORTS_PER_SOL);
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&alice.pubkey()),
+        &[&alice],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx).expect("Deposit should succeed");
+
+    // Without the destination owner's signature nothing moves
+    let transfer_ix =
+        create_vault_transfer_instruction(alice.pubkey(), bob.pubkey(), LAMPORTS_PER_SOL, false);
+    let tx = Transaction::new_signed_with_payer(
+        &[transfer_ix],
+        Some(&alice.pubkey()),
+        &[&alice],
+        svm.latest_blockhash(),
+    );
+    assert_eq!(
+        svm.send_transaction(tx).unwrap_err().err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::MissingOwnerSignature as u32)
+        )
+    );
+    assert_eq!(svm.get_balance(&alice_vault).unwrap(), 2 * LAMPORTS_PER_SOL);
+
+    let transfer_ix =
+        create_vault_transfer_instruction(alice.pubkey(), bob.pubkey(), LAMPORTS_PER_SOL, true);
+    let tx = Transaction::new_signed_with_payer(
+        &[transfer_ix],
+        Some(&alice.pubkey()),
+        &[&alice, &bob],
+        svm.latest_blockhash(),
+    );
+    let result = svm.send_transaction(tx);
+    assert!(result.is_ok(), "Transfer should succeed: {:?}", result.err());
+
+    assert_eq!(svm.get_balance(&alice_vault).unwrap(), LAMPORTS_PER_SOL);
+    assert_eq!(svm.get_balance(&bob_vault).unwrap(), LAMPORTS_PER_SOL);
+}
+
+#[test]
+fn test_open_vault_enforces_max_vaults_per_owner() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    set_upgrade_authority(&mut svm, &owner.pubkey());
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    // `[referral_bps][price_oracle][max_vaults_per_owner]`
+    let max_vaults = 2u64;
+    let mut initialize_ix = create_initialize_instruction(owner.pubkey(), 0);
+    initialize_ix.data.extend_from_slice(&[0u8; 32]);
+    initialize_ix.data.extend_from_slice(&max_vaults.to_le_bytes());
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[initialize_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx).expect("Initialize should succeed");
+
+    let open = |svm: &mut LiteSVM, id: u64| {
+        let (vault, _) = find_vault_pda_with_id(&owner.pubkey(), id);
+        let open_ix = create_open_vault_instruction_with_id(owner.pubkey(), vault, id);
+        let recent_blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[open_ix],
+            Some(&owner.pubkey()),
+            &[&owner],
+            recent_blockhash,
+        );
+        svm.send_transaction(tx).map(|_| ()).map_err(|f| f.err)
+    };
+
+    for id in 0..max_vaults {
+        open(&mut svm, id).expect("Opening up to the limit should succeed");
+    }
+
+    assert_eq!(
+        open(&mut svm, max_vaults),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::TooManyVaults as u32)
+        ))
+    );
+    assert_eq!(
+        client::vault_registry(&|address: &Pubkey| svm.get_account(address), &owner.pubkey())
+            .map(|vaults| vaults.len()),
+        Some(max_vaults as usize)
+    );
+}
+
+#[test]
+fn test_deposit_memo_is_logged() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let deposit = |svm: &mut LiteSVM, memo: &[u8]| {
+        // `[amount][id][salt][flags][vesting_duration][referrer][memo]`, with no referrer
+        let mut deposit_ix =
+            create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL);
+        deposit_ix.data.extend_from_slice(&0u64.to_le_bytes());
+        deposit_ix.data.extend_from_slice(&[0u8; 32]);
+        deposit_ix.data.push(0);
+        deposit_ix.data.extend_from_slice(&0u64.to_le_bytes());
+        deposit_ix.data.extend_from_slice(&[0u8; 32]);
+        deposit_ix.data.extend_from_slice(memo);
+
+        let recent_blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[deposit_ix],
+            Some(&owner.pubkey()),
+            &[&owner],
+            recent_blockhash,
+        );
+        svm.send_transaction(tx)
+            .map(|meta| meta.logs)
+            .map_err(|f| f.err)
+    };
+
+    // Memos are capped at 64 bytes
+    assert_eq!(
+        deposit(&mut svm, &[b'x'; 65]),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::InvalidInstructionData
+        ))
+    );
+
+    let logs = deposit(&mut svm, b"invoice-42").expect("Deposit with a memo should succeed");
+    assert!(
+        logs.iter()
+            .any(|line| line == "Program log: vault:memo invoice-42"),
+        "Memo should be logged: {logs:?}"
+    );
+    assert_eq!(svm.get_balance(&vault_pda).unwrap(), LAMPORTS_PER_SOL);
+}
+
+#[test]
+fn test_deposits_locked_rejects_deposits_but_not_withdrawals() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+
+    send(
+        &mut svm,
+        create_open_vault_instruction(owner.pubkey(), vault_pda),
+        &owner,
+    )
+    .expect("Failed to open vault");
+    send(
+        &mut svm,
+        create_deposit_instruction(owner.pubkey(), vault_pda, 2 * LAMPORTS_PER_SOL),
+        &owner,
+    )
+    .expect("Failed to deposit");
+
+    send(
+        &mut svm,
+        create_set_deposits_locked_instruction(owner.pubkey(), vault_pda, true),
+        &owner,
+    )
+    .expect("Failed to lock deposits");
+    assert_eq!(
+        send(
+            &mut svm,
+            create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL),
+            &owner
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::DepositsLocked as u32)
+        ))
+    );
+
+    // Withdrawals are unaffected by the lock
+    let rent_floor = svm.minimum_balance_for_rent_exemption(VaultState::LEN);
+    send(
+        &mut svm,
+        create_withdraw_instruction(owner.pubkey(), vault_pda),
+        &owner,
+    )
+    .expect("Withdraw should succeed while deposits are locked");
+    assert_eq!(svm.get_balance(&vault_pda).unwrap(), rent_floor);
+
+    send(
+        &mut svm,
+        create_set_deposits_locked_instruction(owner.pubkey(), vault_pda, false),
+        &owner,
+    )
+    .expect("Failed to unlock deposits");
+    send(
+        &mut svm,
+        create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL),
+        &owner,
+    )
+    .expect("Deposit should succeed once unlocked");
+    assert_eq!(
+        svm.get_balance(&vault_pda).unwrap(),
+        rent_floor + LAMPORTS_PER_SOL
+    );
+}
+
+#[test]
+#[allow(deprecated)] // The runtime still reports a missing account as `NotEnoughAccountKeys`
+fn test_validate_accounts_rejects_malformed_arrangements() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let (other_vault, _bump) = find_vault_pda_with_id(&owner.pubkey(), 1);
+    let foreign_vault = Pubkey::new_unique();
+    svm.set_account(
+        foreign_vault,
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    )
+    .expect("Failed to set foreign account");
+
+    send(
+        &mut svm,
+        create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL),
+        &owner,
+    )
+    .expect("Failed to deposit");
+
+    // Deposit and withdraw share the validator, so each arrangement fails the same way for both
+    let well_formed = [
+        create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL),
+        create_withdraw_instruction(owner.pubkey(), vault_pda),
+    ];
+    for instruction in well_formed {
+        // The owner also pays the fee, so it always signs; stand a non-signer in for it
+        let mut unsigned = instruction.clone();
+        unsigned.accounts[0] = AccountMeta::new(Pubkey::new_unique(), false);
+
+        let mut wrong_system_program = instruction.clone();
+        wrong_system_program.accounts[2] = AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false);
+
+        let mut foreign = instruction.clone();
+        foreign.accounts[1] = AccountMeta::new(foreign_vault, false);
+
+        let mut mismatched = instruction.clone();
+        mismatched.accounts[1] = AccountMeta::new(other_vault, false);
+
+        let mut truncated = instruction.clone();
+        truncated.accounts.truncate(2);
+
+        let cases = [
+            (
+                unsigned,
+                InstructionError::Custom(VaultError::MissingOwnerSignature as u32),
+            ),
+            (wrong_system_program, InstructionError::IncorrectProgramId),
+            (
+                foreign,
+                InstructionError::Custom(VaultError::InvalidVaultOwner as u32),
+            ),
+            (
+                mismatched,
+                InstructionError::Custom(VaultError::InvalidPda as u32),
+            ),
+            (truncated, InstructionError::NotEnoughAccountKeys),
+        ];
+        for (malformed, expected) in cases {
+            assert_eq!(
+                send(&mut svm, malformed, &owner),
+                Err(TransactionError::InstructionError(0, expected))
+            );
+        }
+    }
+
+    assert_eq!(svm.get_balance(&vault_pda).unwrap(), LAMPORTS_PER_SOL);
+}
+
+#[test]
+fn test_withdraw_with_approval_is_relayed_and_replay_safe() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    let relayer = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+    svm.airdrop(&relayer.pubkey(), LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    for instruction in [
+        create_open_vault_instruction(owner.pubkey(), vault_pda),
+        create_deposit_instruction(owner.pubkey(), vault_pda, 2 * LAMPORTS_PER_SOL),
+    ] {
+        let recent_blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&owner.pubkey()),
+            &[&owner],
+            recent_blockhash,
+        );
+        svm.send_transaction(tx).expect("Failed to fund vault");
+    }
+
+    // The owner signs off-chain; only the relayer signs and pays for the transaction
+    let relay = |svm: &mut LiteSVM, amount: u64, nonce: u64| {
+        let message =
+            approval::approval_message(&vault_pda.to_bytes(), amount, nonce, i64::MAX);
+        let instructions = [
+            create_ed25519_verify_instruction(&owner, &message),
+            create_withdraw_with_approval_instruction(
+                relayer.pubkey(),
+                owner.pubkey(),
+                vault_pda,
+                amount,
+                nonce,
+                i64::MAX,
+            ),
+        ];
+
+        svm.expire_blockhash();
+        let recent_blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&relayer.pubkey()),
+            &[&relayer],
+            recent_blockhash,
+        );
+        svm.send_transaction(tx).map(|_| ()).map_err(|f| f.err)
+    };
+
+    let owner_before = svm.get_balance(&owner.pubkey()).unwrap();
+    relay(&mut svm, LAMPORTS_PER_SOL, 1).expect("Approved withdrawal should succeed");
+    assert_eq!(
+        svm.get_balance(&owner.pubkey()).unwrap(),
+        owner_before + LAMPORTS_PER_SOL
+    );
+
+    // Replaying the approval, or any older nonce, is rejected
+    assert_eq!(
+        relay(&mut svm, LAMPORTS_PER_SOL, 1),
+        Err(TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(VaultError::NonceAlreadyUsed as u32)
+        ))
+    );
+    assert_eq!(
+        client::fetch_vault_state(
+            &|address: &Pubkey| svm.get_account(address),
+            &owner.pubkey(),
+            0
+        )
+        .map(|state| (state.used_nonce(), state.balance())),
+        Some((1, LAMPORTS_PER_SOL))
+    );
+
+    // An approval for another amount doesn't carry over
+    let message =
+        approval::approval_message(&vault_pda.to_bytes(), LAMPORTS_PER_SOL, 2, i64::MAX);
+    let forged = [
+        create_ed25519_verify_instruction(&owner, &message),
+        create_withdraw_with_approval_instruction(
+            relayer.pubkey(),
+            owner.pubkey(),
+            vault_pda,
+            LAMPORTS_PER_SOL / 2,
+            2,
+            i64::MAX,
+        ),
+    ];
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &forged,
+        Some(&relayer.pubkey()),
+        &[&relayer],
+        recent_blockhash,
+    );
+    assert_eq!(
+        svm.send_transaction(tx).map(|_| ()).map_err(|f| f.err),
+        Err(TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(VaultError::InvalidApproval as u32)
+        ))
+    );
+}
+
+#[test]
+fn test_deposit_enforces_max_deposit_per_tx() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    set_upgrade_authority(&mut svm, &owner.pubkey());
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    // `[referral_bps][price_oracle][max_vaults_per_owner][max_deposit_per_tx]`
+    let max_deposit = 2 * LAMPORTS_PER_SOL;
+    let mut initialize_ix = create_initialize_instruction(owner.pubkey(), 0);
+    initialize_ix.data.extend_from_slice(&[0u8; 32]);
+    initialize_ix.data.extend_from_slice(&0u64.to_le_bytes());
+    initialize_ix.data.extend_from_slice(&max_deposit.to_le_bytes());
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[initialize_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx).expect("Initialize should succeed");
+
+    let deposit = |svm: &mut LiteSVM, id: u64, amount: u64| {
+        let (vault, _) = find_vault_pda_with_id(&owner.pubkey(), id);
+        let deposit_ix = create_deposit_instruction_with_id(owner.pubkey(), vault, amount, id);
+        let recent_blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[deposit_ix],
+            Some(&owner.pubkey()),
+            &[&owner],
+            recent_blockhash,
+        );
+        svm.send_transaction(tx).map(|_| ()).map_err(|f| f.err)
+    };
+
+    assert_eq!(
+        deposit(&mut svm, 0, max_deposit + 1),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::DepositTooLarge as u32)
+        ))
+    );
+
+    deposit(&mut svm, 0, max_deposit - 1).expect("Deposit under the cap should succeed");
+    deposit(&mut svm, 1, max_deposit).expect("Deposit at the cap should succeed");
+    assert_eq!(
+        svm.get_balance(&find_vault_pda(&owner.pubkey()).0).unwrap(),
+        max_deposit - 1
+    );
+}
+
+#[test]
+fn test_get_info_reports_vault_state_in_simulation() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    let explorer = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+    svm.airdrop(&explorer.pubkey(), LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, bump) = find_vault_pda(&owner.pubkey());
+    let unlock_ts = svm.get_sysvar::<Clock>().unix_timestamp + 1_000;
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_open_vault_instruction(owner.pubkey(), vault_pda),
+            create_deposit_instruction(owner.pubkey(), vault_pda, 2 * LAMPORTS_PER_SOL),
+            create_extend_lock_instruction(owner.pubkey(), vault_pda, unlock_ts),
+            create_set_deposits_locked_instruction(owner.pubkey(), vault_pda, true),
+        ],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx).expect("Failed to set up vault");
+    let vault_before = svm.get_account(&vault_pda).unwrap();
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[create_get_info_instruction(vault_pda)],
+        Some(&explorer.pubkey()),
+        &[&explorer],
+        recent_blockhash,
+    );
+    let simulated = svm
+        .simulate_transaction(tx)
+        .expect("GetInfo should succeed");
+
+    // `[balance][owner][bump][unlock_ts][flags]`
+    let info = simulated.meta.return_data.data;
+    assert_eq!(info.len(), 50);
+    assert_eq!(
+        u64::from_le_bytes(info[..8].try_into().unwrap()),
+        2 * LAMPORTS_PER_SOL
+    );
+    assert_eq!(&info[8..40], owner.pubkey().as_ref());
+    assert_eq!(info[40], bump);
+    assert_eq!(
+        i64::from_le_bytes(info[41..49].try_into().unwrap()),
+        unlock_ts
+    );
+    assert_eq!(info[49], VaultState::FLAG_DEPOSITS_LOCKED);
+
+    assert_eq!(svm.get_account(&vault_pda).unwrap(), vault_before);
+}
+
+#[test]
+fn test_export_state_reports_the_full_state_and_metadata_in_chunks() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let memo = "Backed up nightly".as_bytes();
+    send_all(
+        &mut svm,
+        &[
+            create_deposit_with_metadata_instruction(
+                owner.pubkey(),
+                vault_pda,
+                2 * LAMPORTS_PER_SOL,
+                memo,
+            ),
+            create_set_deposits_locked_instruction(owner.pubkey(), vault_pda, true),
+        ],
+        &[&owner],
+    )
+    .expect("Failed to set up vault");
+
+    // The export doesn't fit in one return data, so read it from successive offsets
+    let export_from = |svm: &LiteSVM, offset: u16| {
+        let tx = Transaction::new_signed_with_payer(
+            &[client::export_state_instruction(&vault_pda, offset)],
+            Some(&owner.pubkey()),
+            &[&owner],
+            svm.latest_blockhash(),
+        );
+        svm.simulate_transaction(tx)
+            .map(|simulated| simulated.meta.return_data.data)
+            .map_err(|failed| failed.err)
+    };
+    let mut export = export_from(&svm, 0).expect("ExportState should succeed");
+    assert_eq!(export.len(), 1024);
+    export.extend(export_from(&svm, 1024).expect("ExportState should succeed"));
+    assert_eq!(export.len(), EXPORT_MAX_LEN);
+
+    let vault = svm.get_account(&vault_pda).unwrap();
+    assert_eq!(export[3..3 + VaultState::LEN], vault.data[..]);
+    let (state, metadata) = client::decode_state_export(&export).expect("Export should decode");
+    assert_eq!(state.balance(), 2 * LAMPORTS_PER_SOL);
+    assert_eq!(state.flags(), VaultState::FLAG_DEPOSITS_LOCKED);
+    assert_eq!(
+        metadata.map(|metadata| metadata.memo().to_vec()),
+        Some(memo.to_vec())
+    );
+
+    // Reading past the end of the export fails
+    assert_eq!(
+        export_from(&svm, export.len() as u16),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::InvalidInstructionData
+        ))
+    );
+}
+
+#[test]
+fn test_migrate_vault_moves_funds_and_state_to_new_pda() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let fetcher = |svm: &LiteSVM, address: &Pubkey| svm.get_account(address);
+
+    // An old-style `["vault", owner]` vault holding a plain deposit
+    let (old_vault, _bump) = find_vault_pda(&owner.pubkey());
+    let (new_vault, _bump) = find_vault_pda_with_id(&owner.pubkey(), 1);
+    send_all(
+        &mut svm,
+        &[create_deposit_instruction(
+            owner.pubkey(),
+            old_vault,
+            LAMPORTS_PER_SOL,
+        )],
+        &[&owner],
+    )
+    .expect("Failed to deposit");
+
+    // The new address has to derive from the owner's seeds
+    let mut mismatched = create_migrate_vault_instruction(owner.pubkey(), 0, 1);
+    mismatched.accounts[2] = AccountMeta::new(Pubkey::new_unique(), false);
+    assert_eq!(
+        send_all(&mut svm, &[mismatched], &[&owner]),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::InvalidPda as u32)
+        ))
+    );
+
+    send_all(
+        &mut svm,
+        &[create_migrate_vault_instruction(owner.pubkey(), 0, 1)],
+        &[&owner],
+    )
+    .expect("Migrating a plain vault should succeed");
+    assert!(svm
+        .get_account(&old_vault)
+        .is_none_or(|account| account.lamports == 0));
+    assert_eq!(svm.get_balance(&new_vault).unwrap(), LAMPORTS_PER_SOL);
+
+    // An opened vault takes its state and registry entry along
+    let (opened_vault, _bump) = find_vault_pda_with_id(&owner.pubkey(), 2);
+    let (migrated_vault, migrated_bump) = find_vault_pda_with_id(&owner.pubkey(), 3);
+    send_all(
+        &mut svm,
+        &[
+            create_open_vault_instruction_with_id(owner.pubkey(), opened_vault, 2),
+            create_deposit_instruction_with_id(owner.pubkey(), opened_vault, LAMPORTS_PER_SOL, 2),
+            create_set_label_instruction(owner.pubkey(), opened_vault, b"savings"),
+        ],
+        &[&owner],
+    )
+    .expect("Failed to set up opened vault");
+    let rent_floor = svm.minimum_balance_for_rent_exemption(VaultState::LEN);
+
+    send_all(
+        &mut svm,
+        &[create_migrate_vault_instruction(owner.pubkey(), 2, 3)],
+        &[&owner],
+    )
+    .expect("Migrating an opened vault should succeed");
+    assert!(svm
+        .get_account(&opened_vault)
+        .is_none_or(|account| account.lamports == 0));
+    assert_eq!(
+        svm.get_balance(&migrated_vault).unwrap(),
+        rent_floor + LAMPORTS_PER_SOL
+    );
+
+    let state = client::fetch_vault_state(
+        &|address: &Pubkey| fetcher(&svm, address),
+        &owner.pubkey(),
+        3,
+    )
+    .expect("Migrated vault should hold its state");
+    assert_eq!(state.owner(), &owner.pubkey().to_bytes());
+    assert_eq!((state.id(), state.bump()), (3, migrated_bump));
+    assert_eq!(state.balance(), LAMPORTS_PER_SOL);
+    assert_eq!(state.label(), b"savings");
+    assert_eq!(
+        client::vault_registry(&|address: &Pubkey| fetcher(&svm, address), &owner.pubkey()),
+        Some(vec![migrated_vault])
+    );
+}
+
+#[test]
+fn test_migrate_vault_refuses_a_vault_holding_tokens_or_receipts() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let token_owner = Keypair::new();
+    let receipt_owner = Keypair::new();
+    for owner in [&token_owner, &receipt_owner] {
+        svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop");
+    }
+
+    // An opened vault tracking tokens its address is the authority of
+    let (token_vault, _bump) = find_vault_pda(&token_owner.pubkey());
+    let mint = Pubkey::new_unique();
+    let source = Pubkey::new_unique();
+    set_token_account(&mut svm, source, &mint, &token_owner.pubkey(), 1_000);
+    set_token_account(
+        &mut svm,
+        find_ata(&token_vault, &mint),
+        &mint,
+        &token_vault,
+        0,
+    );
+    send(
+        &mut svm,
+        create_open_vault_instruction(token_owner.pubkey(), token_vault),
+        &token_owner,
+    )
+    .expect("OpenVault should succeed");
+    send(
+        &mut svm,
+        create_deposit_instruction(token_owner.pubkey(), token_vault, LAMPORTS_PER_SOL),
+        &token_owner,
+    )
+    .expect("Deposit should succeed");
+    send(
+        &mut svm,
+        create_deposit_token_instruction(token_owner.pubkey(), token_vault, source, mint, 700),
+        &token_owner,
+    )
+    .expect("DepositToken should succeed");
+
+    assert_eq!(
+        send(
+            &mut svm,
+            create_migrate_vault_instruction(token_owner.pubkey(), 0, 1),
+            &token_owner
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::TokensOutstanding as u32)
+        ))
+    );
+
+    // A vault whose receipt mint its address is the mint authority of
+    let (receipt_vault, _bump) = find_vault_pda(&receipt_owner.pubkey());
+    let receipt_mint = Pubkey::new_unique();
+    let receipt_account = Pubkey::new_unique();
+    set_receipt_mint(&mut svm, receipt_mint, &receipt_vault);
+    set_token_account(
+        &mut svm,
+        receipt_account,
+        &receipt_mint,
+        &receipt_owner.pubkey(),
+        0,
+    );
+    send(
+        &mut svm,
+        create_receipt_deposit_instruction(
+            receipt_owner.pubkey(),
+            receipt_vault,
+            LAMPORTS_PER_SOL,
+            receipt_mint,
+            receipt_account,
+        ),
+        &receipt_owner,
+    )
+    .expect("Deposit with a receipt should succeed");
+
+    assert_eq!(
+        send(
+            &mut svm,
+            create_migrate_vault_instruction(receipt_owner.pubkey(), 0, 1),
+            &receipt_owner
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::ReceiptsOutstanding as u32)
+        ))
+    );
+}
+
+#[test]
+fn test_withdraw_amount_of_zero_is_a_no_op() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL);
+    let recent_blockhash = svm.latest_blockhash();
+    let deposit_tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(deposit_tx).expect("Deposit should succeed");
+
+    for mode in [0, 1] {
+        let owner_balance = svm.get_balance(&owner.pubkey()).unwrap();
+        let withdraw_ix = create_withdraw_amount_instruction(owner.pubkey(), vault_pda, 0, mode);
+        let recent_blockhash = svm.latest_blockhash();
+        let withdraw_tx = Transaction::new_signed_with_payer(
+            &[withdraw_ix],
+            Some(&owner.pubkey()),
+            &[&owner],
+            recent_blockhash,
+        );
+        svm.send_transaction(withdraw_tx.clone())
+            .expect("Zero-amount withdraw should succeed");
+
+        // Only the fee leaves the owner, and the vault is untouched
+        assert_eq!(svm.get_balance(&vault_pda).unwrap(), LAMPORTS_PER_SOL);
+        assert_eq!(
+            svm.get_balance(&owner.pubkey()).unwrap(),
+            owner_balance - total_fees([&withdraw_tx])
+        );
+        svm.expire_blockhash();
+    }
+}
+
+#[test]
+fn test_stored_bump_is_canonical_for_random_owners() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    // Different owners land on different canonical bumps
+    for _ in 0..8 {
+        let owner = Keypair::new();
+        svm.airdrop(&owner.pubkey(), LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop");
+
+        let (vault_pda, canonical_bump) =
+            Pubkey::find_program_address(&[b"vault", owner.pubkey().as_ref()], &PROGRAM_ID);
+        let recent_blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[create_open_vault_instruction(owner.pubkey(), vault_pda)],
+            Some(&owner.pubkey()),
+            &[&owner],
+            recent_blockhash,
+        );
+        svm.send_transaction(tx).expect("OpenVault should succeed");
+
+        let state = client::fetch_vault_state(
+            &|address: &Pubkey| svm.get_account(address),
+            &owner.pubkey(),
+            0,
+        )
+        .expect("Opened vault should hold its state");
+        assert_eq!(state.bump(), canonical_bump);
+
+        let recent_blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[create_get_info_instruction(vault_pda)],
+            Some(&owner.pubkey()),
+            &[&owner],
+            recent_blockhash,
+        );
+        let simulated = svm
+            .simulate_transaction(tx)
+            .expect("GetInfo should succeed");
+        assert_eq!(simulated.meta.return_data.data[40], canonical_bump);
+    }
+}
+
+#[test]
+fn test_deposit_locked_funds_and_locks_vault_atomically() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    send(
+        &mut svm,
+        create_open_vault_instruction(owner.pubkey(), vault_pda),
+        &owner,
+    )
+    .expect("OpenVault should succeed");
+
+    // An unlock that isn't in the future is rejected
+    let now = svm.get_sysvar::<Clock>().unix_timestamp;
+    for past in [now - 1, now] {
+        assert_eq!(
+            send(
+                &mut svm,
+                create_deposit_locked_instruction(
+                    owner.pubkey(),
+                    vault_pda,
+                    LAMPORTS_PER_SOL,
+                    past
+                ),
+                &owner
+            ),
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(VaultError::UnlockInPast as u32)
+            ))
+        );
+    }
+
+    let unlock_ts = now + 3600;
+    send(
+        &mut svm,
+        create_deposit_locked_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL, unlock_ts),
+        &owner,
+    )
+    .expect("DepositLocked should succeed");
+
+    let vault_account = svm.get_account(&vault_pda).expect("Vault should exist");
+    let state = VaultState::from_bytes(&vault_account.data).expect("State should be readable");
+    assert_eq!(state.balance(), LAMPORTS_PER_SOL);
+    assert_eq!(state.unlock_ts(), unlock_ts);
+
+    assert_eq!(
+        send(
+            &mut svm,
+            create_withdraw_instruction(owner.pubkey(), vault_pda),
+            &owner
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::VaultLocked as u32)
+        ))
+    );
+
+    // Once the clock passes the unlock the deposit withdraws as usual
+    let mut clock = svm.get_sysvar::<Clock>();
+    clock.unix_timestamp = unlock_ts;
+    svm.set_sysvar::<Clock>(&clock);
+
+    send(
+        &mut svm,
+        create_withdraw_instruction(owner.pubkey(), vault_pda),
+        &owner,
+    )
+    .expect("Withdraw after the unlock should succeed");
+}
+
+#[test]
+fn test_subsidized_deposit_charges_rent_to_subsidy_account() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    let subsidy = Keypair::new();
+    let initial_airdrop = 10 * LAMPORTS_PER_SOL;
+    svm.airdrop(&owner.pubkey(), initial_airdrop)
+        .expect("Failed to airdrop");
+    svm.airdrop(&subsidy.pubkey(), LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    // `[referral_bps][price_oracle][max_vaults_per_owner][max_deposit_per_tx][rent_subsidy]`
+    let admin = Keypair::new();
+    set_upgrade_authority(&mut svm, &admin.pubkey());
+    svm.airdrop(&admin.pubkey(), LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+    let mut initialize_ix = create_initialize_instruction(admin.pubkey(), 0);
+    initialize_ix.data.extend_from_slice(&[0u8; 32]);
+    initialize_ix.data.extend_from_slice(&0u64.to_le_bytes());
+    initialize_ix.data.extend_from_slice(&0u64.to_le_bytes());
+    initialize_ix.data.extend_from_slice(subsidy.pubkey().as_ref());
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[initialize_ix],
+        Some(&admin.pubkey()),
+        &[&admin],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx).expect("Initialize should succeed");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let deposit_amount = LAMPORTS_PER_SOL;
+    let subsidized_deposit = |subsidy: Pubkey| {
+        let mut instruction =
+            create_opening_deposit_instruction(owner.pubkey(), vault_pda, deposit_amount);
+        *instruction.data.last_mut().unwrap() |= 0b100; // subsidized flag
+        // rent subsidy (signer, writable)
+        instruction.accounts.push(AccountMeta::new(subsidy, true));
+        instruction
+    };
+
+    // Only the configured subsidy account can pay
+    let impostor = Keypair::new();
+    svm.airdrop(&impostor.pubkey(), LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[subsidized_deposit(impostor.pubkey())],
+        Some(&owner.pubkey()),
+        &[&owner, &impostor],
+        recent_blockhash,
+    );
+    assert_eq!(
+        svm.send_transaction(tx).map(|_| ()).map_err(|f| f.err),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::InvalidAccountData
+        ))
+    );
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[subsidized_deposit(subsidy.pubkey())],
+        Some(&owner.pubkey()),
+        &[&owner, &subsidy],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx.clone())
+        .expect("Subsidized deposit should succeed");
+
+    // The subsidy account pays the rent floor, the owner only the deposit and fees
+    let rent_floor = svm.minimum_balance_for_rent_exemption(VaultState::LEN);
+    let (stats_pda, _) = find_stats_pda();
+    let stats_rent = svm.get_balance(&stats_pda).unwrap();
+    assert_eq!(
+        svm.get_balance(&subsidy.pubkey()).unwrap(),
+        LAMPORTS_PER_SOL - rent_floor
+    );
+    assert_eq!(
+        svm.get_balance(&owner.pubkey()).unwrap(),
+        initial_airdrop - deposit_amount - stats_rent - total_fees([&tx])
+    );
+    assert_eq!(
+        svm.get_balance(&vault_pda).unwrap(),
+        rent_floor + deposit_amount
+    );
+}
+
+#[test]
+fn test_split_vault_carries_lock_into_new_vault() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (source_vault, _bump) = find_vault_pda(&owner.pubkey());
+    let (new_vault, _bump) = find_vault_pda_with_id(&owner.pubkey(), 1);
+    let unlock_ts = svm.get_sysvar::<Clock>().unix_timestamp + 3600;
+    send_all(
+        &mut svm,
+        &[
+            create_open_vault_instruction(owner.pubkey(), source_vault),
+            create_deposit_instruction(owner.pubkey(), source_vault, 3 * LAMPORTS_PER_SOL),
+            create_extend_lock_instruction(owner.pubkey(), source_vault, unlock_ts),
+        ],
+        &[&owner],
+    )
+    .expect("Failed to set up locked vault");
+
+    // Only what the source tracks can be split off
+    assert_eq!(
+        send_all(
+            &mut svm,
+            &[create_split_vault_instruction(
+                owner.pubkey(),
+                4 * LAMPORTS_PER_SOL,
+                0,
+                1
+            )],
+            &[&owner]
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::InsufficientFunds as u32)
+        ))
+    );
+
+    send_all(
+        &mut svm,
+        &[create_split_vault_instruction(
+            owner.pubkey(),
+            LAMPORTS_PER_SOL,
+            0,
+            1,
+        )],
+        &[&owner],
+    )
+    .expect("Splitting a locked vault should succeed");
+
+    let rent_floor = svm.minimum_balance_for_rent_exemption(VaultState::LEN);
+    let source_account = svm.get_account(&source_vault).unwrap();
+    let source_state = VaultState::from_bytes(&source_account.data).unwrap();
+    assert_eq!(source_state.balance(), 2 * LAMPORTS_PER_SOL);
+    assert_eq!(source_account.lamports, rent_floor + 2 * LAMPORTS_PER_SOL);
+
+    let new_account = svm.get_account(&new_vault).expect("New vault should exist");
+    let new_state = VaultState::from_bytes(&new_account.data).unwrap();
+    assert_eq!(new_state.id(), 1);
+    assert_eq!(new_state.balance(), LAMPORTS_PER_SOL);
+    assert_eq!(new_state.unlock_ts(), unlock_ts);
+    assert_eq!(new_account.lamports, rent_floor + LAMPORTS_PER_SOL);
+
+    // The split-off half stays locked until the source's unlock
+    assert_eq!(
+        send_all(
+            &mut svm,
+            &[create_withdraw_instruction_with_flags(
+                owner.pubkey(),
+                new_vault,
+                1,
+                false
+            )],
+            &[&owner]
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::VaultLocked as u32)
+        ))
+    );
+}
+
+#[test]
+fn test_vault_passed_as_owner_is_rejected() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL);
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    svm.send_transaction(tx).expect("Deposit should succeed");
+
+    // The vault PDA can't sign, so it's passed as a non-signer owner
+    let mut deposit_ix = create_deposit_instruction(vault_pda, vault_pda, LAMPORTS_PER_SOL);
+    deposit_ix.accounts[0] = AccountMeta::new(vault_pda, false);
+    let mut withdraw_ix = create_withdraw_instruction(vault_pda, vault_pda);
+    withdraw_ix.accounts[0] = AccountMeta::new(vault_pda, false);
+
+    for instruction in [deposit_ix, withdraw_ix] {
+        let recent_blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&owner.pubkey()),
+            &[&owner],
+            recent_blockhash,
+        );
+        assert_eq!(
+            svm.send_transaction(tx).map(|_| ()).map_err(|f| f.err),
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(VaultError::InvalidAccountArrangement as u32)
+            ))
+        );
+    }
+
+    assert_eq!(svm.get_balance(&vault_pda).unwrap(), LAMPORTS_PER_SOL);
+}
+
+#[test]
+fn test_sweep_token_only_reaches_allowed_destination_owner() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let treasury = Pubkey::new_unique();
+    send(
+        &mut svm,
+        create_open_vault_instruction(owner.pubkey(), vault_pda),
+        &owner,
+    )
+    .expect("OpenVault should succeed");
+    send(
+        &mut svm,
+        create_set_token_dest_owner_instruction(owner.pubkey(), vault_pda, &treasury),
+        &owner,
+    )
+    .expect("SetTokenDestOwner should succeed");
+
+    let mint = Pubkey::new_unique();
+    let vault_token_account = Pubkey::new_unique();
+    set_token_account(&mut svm, vault_token_account, &mint, &vault_pda, 1_000);
+
+    // Even the owner's own token account is off limits once a destination owner is set
+    let owner_destination = Pubkey::new_unique();
+    set_token_account(&mut svm, owner_destination, &mint, &owner.pubkey(), 0);
+    assert_eq!(
+        send(
+            &mut svm,
+            create_sweep_token_instruction(
+                owner.pubkey(),
+                vault_pda,
+                vault_token_account,
+                owner_destination
+            ),
+            &owner
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::DestinationNotAllowed as u32)
+        ))
+    );
+    assert_eq!(token_balance(&svm, &vault_token_account), 1_000);
+
+    let treasury_destination = Pubkey::new_unique();
+    set_token_account(&mut svm, treasury_destination, &mint, &treasury, 0);
+    send(
+        &mut svm,
+        create_sweep_token_instruction(
+            owner.pubkey(),
+            vault_pda,
+            vault_token_account,
+            treasury_destination,
+        ),
+        &owner,
+    )
+    .expect("Sweep to the allowed destination owner should succeed");
+    assert_eq!(token_balance(&svm, &treasury_destination), 1_000);
+
+    // Clearing the restriction allows any destination again
+    set_token_account(&mut svm, vault_token_account, &mint, &vault_pda, 500);
+    send(
+        &mut svm,
+        create_set_token_dest_owner_instruction(owner.pubkey(), vault_pda, &Pubkey::default()),
+        &owner,
+    )
+    .expect("Clearing the destination owner should succeed");
+    send(
+        &mut svm,
+        create_sweep_token_instruction(
+            owner.pubkey(),
+            vault_pda,
+            vault_token_account,
+            owner_destination,
+        ),
+        &owner,
+    )
+    .expect("Sweep without a destination owner should succeed");
+    assert_eq!(token_balance(&svm, &owner_destination), 500);
+}
+
+#[test]
+fn test_deposit_and_withdraw_events_parse_from_logs() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let send = |svm: &mut LiteSVM, instruction: Instruction| {
+        send_transaction(svm, &[instruction], &[&owner])
+            .map(|meta| client::parse_events(&meta.logs))
+            .map_err(|f| f.err)
+    };
+
+    let amount = LAMPORTS_PER_SOL;
+    assert_eq!(
+        send(
+            &mut svm,
+            create_deposit_instruction(owner.pubkey(), vault_pda, amount)
+        ),
+        Ok(vec![client::VaultEvent::Deposit {
+            owner: owner.pubkey(),
+            amount
+        }])
+    );
+    assert_eq!(
+        send(
+            &mut svm,
+            create_withdraw_instruction(owner.pubkey(), vault_pda)
+        ),
+        Ok(vec![client::VaultEvent::Withdraw {
+            owner: owner.pubkey(),
+            amount
+        }])
+    );
+}
+
+#[test]
+fn test_escrowed_vault_only_releases_to_the_escrow_agent() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    let agent = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+    svm.airdrop(&agent.pubkey(), LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    send(
+        &mut svm,
+        create_open_vault_instruction(owner.pubkey(), vault_pda),
+        &owner,
+    )
+    .expect("OpenVault should succeed");
+    send(
+        &mut svm,
+        create_set_escrow_agent_instruction(owner.pubkey(), vault_pda, &agent.pubkey()),
+        &owner,
+    )
+    .expect("SetEscrowAgent should succeed");
+
+    // Deposits still land in an escrowed vault
+    let amount = LAMPORTS_PER_SOL;
+    send(
+        &mut svm,
+        create_deposit_instruction(owner.pubkey(), vault_pda, amount),
+        &owner,
+    )
+    .expect("Deposit into an escrowed vault should succeed");
+
+    // The agent can't be replaced, not even by the owner
+    assert_eq!(
+        send(
+            &mut svm,
+            create_set_escrow_agent_instruction(owner.pubkey(), vault_pda, &owner.pubkey()),
+            &owner,
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::EscrowAgentSet as u32)
+        ))
+    );
+
+    assert_eq!(
+        send(
+            &mut svm,
+            create_withdraw_instruction(owner.pubkey(), vault_pda),
+            &owner
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::EscrowedVault as u32)
+        ))
+    );
+
+    // The agent signs in place of the owner
+    let mut agent_withdraw = create_withdraw_instruction(owner.pubkey(), vault_pda);
+    agent_withdraw.accounts[0].is_signer = false;
+    agent_withdraw
+        .accounts
+        .push(AccountMeta::new_readonly(agent.pubkey(), true));
+    let owner_before = svm.get_balance(&owner.pubkey()).unwrap();
+    send(&mut svm, agent_withdraw, &agent).expect("Escrow agent withdraw should succeed");
+    assert!(svm.get_balance(&owner.pubkey()).unwrap() >= owner_before + amount);
+}
+
+#[test]
+fn test_fund_reward_pool_tracks_total() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let funders = [Keypair::new(), Keypair::new()];
+    for funder in &funders {
+        svm.airdrop(&funder.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop");
+    }
+
+    let fund = |svm: &mut LiteSVM, funder: &Keypair, amount: u64| {
+        send(
+            svm,
+            create_fund_reward_pool_instruction(funder.pubkey(), amount),
+            funder,
+        )
+    };
+
+    assert_eq!(
+        fund(&mut svm, &funders[0], 0),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::InvalidInstructionData
+        ))
+    );
+
+    // Anyone can top the pool up, and the first funder pays its rent
+    fund(&mut svm, &funders[0], 2 * LAMPORTS_PER_SOL).expect("First funding should succeed");
+    fund(&mut svm, &funders[1], LAMPORTS_PER_SOL).expect("Second funding should succeed");
+
+    let reward_pool = client::reward_pool(&|address: &Pubkey| svm.get_account(address))
+        .expect("Reward pool should exist");
+    assert_eq!(reward_pool.total_funded(), 3 * LAMPORTS_PER_SOL);
+
+    let rent_floor = svm.minimum_balance_for_rent_exemption(RewardPool::LEN);
+    assert_eq!(
+        svm.get_balance(&client::find_reward_pool_address().0),
+        Some(3 * LAMPORTS_PER_SOL + rent_floor)
+    );
+}
+
+#[test]
+fn test_claim_reward_credits_a_year_of_interest_from_the_reward_pool() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let admin = Keypair::new();
+    set_upgrade_authority(&mut svm, &admin.pubkey());
+    let owner = Keypair::new();
+    for signer in [&admin, &owner] {
+        svm.airdrop(&signer.pubkey(), 20 * LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop");
+    }
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let deposit = 10 * LAMPORTS_PER_SOL;
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_open_vault_instruction(owner.pubkey(), vault_pda),
+            create_deposit_instruction(owner.pubkey(), vault_pda, deposit),
+        ],
+        Some(&owner.pubkey()),
+        &[&owner],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .expect("OpenVault and deposit should succeed");
+
+    // 5% a year, which only the admin can set
+    send(
+        &mut svm,
+        create_initialize_instruction(admin.pubkey(), 0),
+        &admin,
+    )
+    .expect("Initialize should succeed");
+    assert_eq!(
+        send(
+            &mut svm,
+            create_set_reward_rate_instruction(owner.pubkey(), vault_pda, 500),
+            &owner
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::NotAdmin as u32)
+        ))
+    );
+    send(
+        &mut svm,
+        create_set_reward_rate_instruction(admin.pubkey(), vault_pda, 500),
+        &admin,
+    )
+    .expect("SetRewardRate should succeed");
+
+    let mut clock = svm.get_sysvar::<Clock>();
+    clock.unix_timestamp += SECONDS_PER_YEAR as i64;
+    svm.set_sysvar::<Clock>(&clock);
+    let interest = deposit / 20;
+
+    // A pool short of the interest pays nothing
+    send(
+        &mut svm,
+        create_fund_reward_pool_instruction(admin.pubkey(), interest - 1),
+        &admin,
+    )
+    .expect("FundRewardPool should succeed");
+    assert_eq!(
+        send(
+            &mut svm,
+            create_claim_reward_instruction(owner.pubkey(), vault_pda),
+            &owner
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::RewardPoolUnderfunded as u32)
+        ))
+    );
+
+    send(
+        &mut svm,
+        create_fund_reward_pool_instruction(admin.pubkey(), 1),
+        &admin,
+    )
+    .expect("FundRewardPool should succeed");
+    let vault_before = svm.get_balance(&vault_pda).unwrap();
+    send(
+        &mut svm,
+        create_claim_reward_instruction(owner.pubkey(), vault_pda),
+        &owner,
+    )
+    .expect("ClaimReward should succeed once the pool covers the interest");
+
+    assert_eq!(
+        svm.get_balance(&vault_pda).unwrap(),
+        vault_before + interest
+    );
+    let account = svm.get_account(&vault_pda).unwrap();
+    let state = VaultState::from_bytes(&account.data).expect("vault should be opened");
+    assert_eq!(state.balance(), deposit + interest);
+    assert_eq!(state.accrued_interest(), 0);
+    assert_eq!(
+        svm.get_balance(&client::find_reward_pool_address().0),
+        Some(svm.minimum_balance_for_rent_exemption(RewardPool::LEN))
+    );
+}
+
+#[test]
+fn test_close_refunds_rent_to_the_refund_account() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let close_with_refund = |vault: Pubkey, refund_to: Pubkey| {
+        let mut instruction = create_close_instruction(owner.pubkey(), vault, 16);
+        instruction.data.extend_from_slice(&0u64.to_le_bytes()); // default id
+        instruction.data.extend_from_slice(&[0u8; 32]); // unsalted
+        instruction.data.push(1); // refund
+        instruction
+            .accounts
+            .insert(4, AccountMeta::new(refund_to, false)); // refund_to (writable)
+        instruction
+    };
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    send(
+        &mut svm,
+        create_open_vault_instruction(owner.pubkey(), vault_pda),
+        &owner,
+    )
+    .expect("OpenVault should succeed");
+    let rent_floor = svm.get_balance(&vault_pda).unwrap();
+
+    assert_eq!(
+        send(&mut svm, close_with_refund(vault_pda, vault_pda), &owner),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::InvalidRecipient as u32)
+        ))
+    );
+
+    let refund_to = Pubkey::new_unique();
+    let owner_before = svm.get_balance(&owner.pubkey()).unwrap();
+    send(&mut svm, close_with_refund(vault_pda, refund_to), &owner)
+        .expect("Close with a refund account should succeed");
+
+    assert!(svm.get_account(&vault_pda).is_none());
+    assert_eq!(svm.get_balance(&refund_to), Some(rent_floor));
+    assert!(svm.get_balance(&owner.pubkey()).unwrap() < owner_before);
+}
+
+#[test]
+fn test_withdraw_waits_for_the_vault_min_age() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let min_age_secs = 3_600u64;
+    let flags = VaultState::FLAG_MIN_AGE;
+    let mut open_ix = create_open_vault_instruction_with_flags(owner.pubkey(), vault_pda, flags);
+    open_ix.data.extend_from_slice(&min_age_secs.to_le_bytes());
+    send(&mut svm, open_ix, &owner).expect("OpenVault with a minimum age should succeed");
+    send(
+        &mut svm,
+        create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL),
+        &owner,
+    )
+    .expect("Deposit into a young vault should succeed");
+
+    assert_eq!(
+        send(
+            &mut svm,
+            create_withdraw_instruction(owner.pubkey(), vault_pda),
+            &owner
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::VaultTooYoung as u32)
+        ))
+    );
+
+    let mut clock = svm.get_sysvar::<Clock>();
+    clock.unix_timestamp += min_age_secs as i64;
+    svm.set_sysvar::<Clock>(&clock);
+
+    send(
+        &mut svm,
+        create_withdraw_instruction(owner.pubkey(), vault_pda),
+        &owner,
+    )
+    .expect("Withdraw once the vault is old enough should succeed");
+    assert!(svm.get_account(&vault_pda).is_none());
+}
+
+#[test]
+fn test_lifetime_deposit_counters_survive_withdrawals() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    send(
+        &mut svm,
+        create_open_vault_instruction(owner.pubkey(), vault_pda),
+        &owner,
+    )
+    .expect("OpenVault should succeed");
+
+    let deposits = [LAMPORTS_PER_SOL, LAMPORTS_PER_SOL / 2, 3 * LAMPORTS_PER_SOL];
+    for amount in deposits {
+        send(
+            &mut svm,
+            create_deposit_instruction(owner.pubkey(), vault_pda, amount),
+            &owner,
+        )
+        .expect("Deposit should succeed");
+    }
+    send(
+        &mut svm,
+        create_withdraw_amount_instruction(owner.pubkey(), vault_pda, 2 * LAMPORTS_PER_SOL, 0),
+        &owner,
+    )
+    .expect("WithdrawAmount should succeed");
+
+    let fetcher = |address: &Pubkey| svm.get_account(address);
+    let state = client::fetch_vault_state(&fetcher, &owner.pubkey(), 0)
+        .expect("Opened vault should decode");
+    let deposited: u64 = deposits.iter().sum();
+    assert_eq!(state.total_deposit_count(), deposits.len() as u64);
+    assert_eq!(state.lifetime_deposited(), deposited);
+    assert_eq!(state.balance(), deposited - 2 * LAMPORTS_PER_SOL);
+}
+
+#[test]
+fn test_withdraw_count_counts_each_withdrawal_once_with_its_fee() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let admin = Keypair::new();
+    set_upgrade_authority(&mut svm, &admin.pubkey());
+    let owner = Keypair::new();
+    for signer in [&admin, &owner] {
+        svm.airdrop(&signer.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop");
+    }
+
+    // A 10% fee, so each withdrawal debits the vault twice
+    let treasury = Pubkey::new_unique();
+    send_all(
+        &mut svm,
+        &[
+            create_initialize_instruction(admin.pubkey(), 0),
+            create_set_withdraw_fee_instruction(admin.pubkey(), 1_000, treasury, &[]),
+        ],
+        &[&admin],
+    )
+    .expect("Initialize and SetWithdrawFee should succeed");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    send_all(
+        &mut svm,
+        &[
+            create_open_vault_instruction(owner.pubkey(), vault_pda),
+            create_deposit_instruction(owner.pubkey(), vault_pda, 3 * LAMPORTS_PER_SOL),
+        ],
+        &[&owner],
+    )
+    .expect("OpenVault and deposit should succeed");
+
+    for _ in 0..2 {
+        let mut withdraw_ix =
+            create_withdraw_amount_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL, 0);
+        withdraw_ix.accounts.push(AccountMeta::new(treasury, false)); // treasury (writable)
+        send(&mut svm, withdraw_ix, &owner).expect("WithdrawAmount should succeed");
+    }
+
+    let fetcher = |address: &Pubkey| svm.get_account(address);
+    let state = client::fetch_vault_state(&fetcher, &owner.pubkey(), 0)
+        .expect("Opened vault should decode");
+    assert_eq!(state.withdraw_count(), 2);
+    assert_eq!(state.total_deposit_count(), 1);
+    assert_eq!(svm.get_balance(&treasury), Some(2 * LAMPORTS_PER_SOL / 10));
+}
+
+#[test]
+fn test_deposit_mints_and_withdraw_burns_receipt_tokens() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let mint = Pubkey::new_unique();
+    let receipt_account = Pubkey::new_unique();
+    set_receipt_mint(&mut svm, mint, &vault_pda);
+    set_token_account(&mut svm, receipt_account, &mint, &owner.pubkey(), 0);
+
+    let amount = 2 * LAMPORTS_PER_SOL;
+    send(
+        &mut svm,
+        create_receipt_deposit_instruction(
+            owner.pubkey(),
+            vault_pda,
+            amount,
+            mint,
+            receipt_account,
+        ),
+        &owner,
+    )
+    .expect("Deposit with a receipt should succeed");
+    let rent_floor = svm.minimum_balance_for_rent_exemption(VaultState::LEN);
+    assert_eq!(svm.get_balance(&vault_pda), Some(rent_floor + amount));
+    assert_eq!(token_balance(&svm, &receipt_account), amount);
+
+    let state = |svm: &LiteSVM| {
+        let account = svm.get_account(&vault_pda).expect("vault should exist");
+        *VaultState::from_bytes(&account.data).unwrap()
+    };
+    assert_eq!(state(&svm).receipt_mint(), mint.as_ref());
+    assert_eq!(state(&svm).receipt_supply(), amount);
+
+    // Once the vault has recorded a mint, no other one can issue its receipts
+    let second_mint = Pubkey::new_unique();
+    let second_account = Pubkey::new_unique();
+    set_receipt_mint(&mut svm, second_mint, &vault_pda);
+    set_token_account(&mut svm, second_account, &second_mint, &owner.pubkey(), 0);
+    assert_eq!(
+        send(
+            &mut svm,
+            create_receipt_deposit_instruction(
+                owner.pubkey(),
+                vault_pda,
+                amount,
+                second_mint,
+                second_account,
+            ),
+            &owner
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::InvalidAccountData
+        ))
+    );
+
+    // A mint the vault isn't the authority of can't issue receipts
+    let other_mint = Pubkey::new_unique();
+    set_receipt_mint(&mut svm, other_mint, &owner.pubkey());
+    assert_eq!(
+        send(
+            &mut svm,
+            create_receipt_deposit_instruction(
+                owner.pubkey(),
+                vault_pda,
+                amount,
+                other_mint,
+                receipt_account,
+            ),
+            &owner
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::InvalidAccountData
+        ))
+    );
+
+    let supply = |svm: &LiteSVM| {
+        let data = svm.get_account(&mint).expect("mint should exist").data;
+        u64::from_le_bytes(data[36..44].try_into().unwrap())
+    };
+    assert_eq!(supply(&svm), amount);
+
+    // The receipted lamports can't leave without a burn, whichever way they go
+    for instruction in [
+        create_withdraw_instruction(owner.pubkey(), vault_pda),
+        create_withdraw_amount_instruction(owner.pubkey(), vault_pda, 1, 0),
+    ] {
+        assert_eq!(
+            send(&mut svm, instruction, &owner),
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(VaultError::ReceiptsOutstanding as u32)
+            ))
+        );
+    }
+
+    send(
+        &mut svm,
+        create_receipt_withdraw_instruction(owner.pubkey(), vault_pda, mint, receipt_account),
+        &owner,
+    )
+    .expect("Withdraw burning the receipt should succeed");
+    assert_eq!(token_balance(&svm, &receipt_account), 0);
+    assert_eq!(supply(&svm), 0);
+    assert!(svm.get_account(&vault_pda).is_none());
+}
+
+#[test]
+fn test_withdraw_amount_auto_closes_a_vault_left_with_dust() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    set_upgrade_authority(&mut svm, &owner.pubkey());
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    // `[referral_bps][price_oracle][max_vaults_per_owner][max_deposit_per_tx][rent_subsidy]
+    // [dust_threshold]`
+    let dust_threshold = LAMPORTS_PER_SOL / 100;
+    let mut initialize_ix = create_initialize_instruction(owner.pubkey(), 0);
+    initialize_ix.data.extend_from_slice(&[0u8; 32]);
+    initialize_ix.data.extend_from_slice(&0u64.to_le_bytes());
+    initialize_ix.data.extend_from_slice(&0u64.to_le_bytes());
+    initialize_ix.data.extend_from_slice(&[0u8; 32]);
+    initialize_ix
+        .data
+        .extend_from_slice(&dust_threshold.to_le_bytes());
+    send(&mut svm, initialize_ix, &owner).expect("Initialize should succeed");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    send(
+        &mut svm,
+        create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL),
+        &owner,
+    )
+    .expect("Deposit should succeed");
+
+    // Leave 1000 lamports of dust above the rent-exempt reserve
+    let reserve = svm.minimum_balance_for_rent_exemption(0);
+    let amount = LAMPORTS_PER_SOL - reserve - 1_000;
+    let mut withdraw_ix = create_withdraw_amount_instruction(owner.pubkey(), vault_pda, amount, 0);
+    withdraw_ix.data.extend_from_slice(&0u64.to_le_bytes()); // id
+    withdraw_ix.data.extend_from_slice(&[0u8; 32]); // salt
+    withdraw_ix.data.push(1); // auto_close
+
+    let owner_before = svm.get_balance(&owner.pubkey()).unwrap();
+    send(&mut svm, withdraw_ix, &owner).expect("Auto-closing WithdrawAmount should succeed");
+    assert!(svm.get_account(&vault_pda).is_none());
+    assert_eq!(
+        svm.get_balance(&owner.pubkey()).unwrap(),
+        owner_before + LAMPORTS_PER_SOL - 5_000
+    );
+}
+
+#[test]
+fn test_initialize_again_by_the_admin_is_a_no_op() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let admin = Keypair::new();
+    set_upgrade_authority(&mut svm, &admin.pubkey());
+    svm.airdrop(&admin.pubkey(), LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    send(
+        &mut svm,
+        create_initialize_instruction(admin.pubkey(), 250),
+        &admin,
+    )
+    .expect("Initialize should succeed");
+    let (config_pda, _bump) = find_config_pda();
+    let config = svm.get_account(&config_pda).expect("config should exist");
+
+    // Differing settings don't overwrite the existing config
+    send(
+        &mut svm,
+        create_initialize_instruction(admin.pubkey(), 500),
+        &admin,
+    )
+    .expect("Repeated Initialize by the admin should succeed");
+    assert_eq!(svm.get_account(&config_pda), Some(config));
+}
+
+#[test]
+fn test_initialize_by_another_admin_fails_once_initialized() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let admin = Keypair::new();
+    set_upgrade_authority(&mut svm, &admin.pubkey());
+    let other = Keypair::new();
+    for signer in [&admin, &other] {
+        svm.airdrop(&signer.pubkey(), LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop");
+    }
+
+    let initialize = |svm: &mut LiteSVM, signer: &Keypair| {
+        send(
+            svm,
+            create_initialize_instruction(signer.pubkey(), 250),
+            signer,
+        )
+    };
+
+    initialize(&mut svm, &admin).expect("Initialize should succeed");
+    assert_eq!(
+        initialize(&mut svm, &other),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::AlreadyInitialized as u32)
+        ))
+    );
+}
+
+#[test]
+fn test_withdraw_pays_the_fee_to_the_treasury_unless_the_owner_is_exempt() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let admin = Keypair::new();
+    set_upgrade_authority(&mut svm, &admin.pubkey());
+    let exempt = Keypair::new();
+    let charged = Keypair::new();
+    for signer in [&admin, &exempt, &charged] {
+        svm.airdrop(&signer.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop");
+    }
+
+    // A 10% fee, waived for `exempt`
+    let treasury = Pubkey::new_unique();
+    send(
+        &mut svm,
+        create_initialize_instruction(admin.pubkey(), 0),
+        &admin,
+    )
+    .expect("Initialize should succeed");
+    send(
+        &mut svm,
+        create_set_withdraw_fee_instruction(admin.pubkey(), 1_000, treasury, &[exempt.pubkey()]),
+        &admin,
+    )
+    .expect("SetWithdrawFee should succeed");
+
+    let withdraw = |owner: &Keypair, treasury: Option<Pubkey>| {
+        let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+        let mut instruction = create_withdraw_instruction(owner.pubkey(), vault_pda);
+        if let Some(treasury) = treasury {
+            instruction.accounts.push(AccountMeta::new(treasury, false)); // treasury (writable)
+        }
+        instruction
+    };
+
+    for owner in [&exempt, &charged] {
+        let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+        send(
+            &mut svm,
+            create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL),
+            owner,
+        )
+        .expect("Deposit should succeed");
+    }
+
+    // Every owner passes the treasury, exempt or not
+    assert!(send(&mut svm, withdraw(&exempt, None), &exempt).is_err());
+    assert_eq!(
+        send(
+            &mut svm,
+            withdraw(&exempt, Some(Pubkey::new_unique())),
+            &exempt
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::InvalidTreasury as u32)
+        ))
+    );
+
+    // Identical withdrawals, only the charged owner's pays the treasury
+    let mut received = Vec::new();
+    for owner in [&exempt, &charged] {
+        let before = svm.get_balance(&owner.pubkey()).unwrap();
+        send(&mut svm, withdraw(owner, Some(treasury)), owner).expect("Withdraw should succeed");
+        received.push(svm.get_balance(&owner.pubkey()).unwrap() + 5_000 - before);
+    }
+    assert_eq!(received[0] - received[1], LAMPORTS_PER_SOL / 10);
+    assert_eq!(svm.get_balance(&treasury), Some(LAMPORTS_PER_SOL / 10));
+}
+
+#[test]
+fn test_rolling_limit_recovers_as_the_window_slides() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let warp_to = |svm: &mut LiteSVM, unix_timestamp: i64| {
+        let mut clock = svm.get_sysvar::<Clock>();
+        clock.unix_timestamp = unix_timestamp;
+        svm.set_sysvar::<Clock>(&clock);
+    };
+
+    // `[rolling_limit][rolling_window_secs]` after the flags
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let window_secs = 3_600u64;
+    let flags = VaultState::FLAG_ROLLING_LIMIT;
+    let mut open_ix = create_open_vault_instruction_with_flags(owner.pubkey(), vault_pda, flags);
+    open_ix
+        .data
+        .extend_from_slice(&LAMPORTS_PER_SOL.to_le_bytes());
+    open_ix.data.extend_from_slice(&window_secs.to_le_bytes());
+    send(&mut svm, open_ix, &owner).expect("OpenVault with a rolling limit should succeed");
+    send(
+        &mut svm,
+        create_deposit_instruction(owner.pubkey(), vault_pda, 3 * LAMPORTS_PER_SOL),
+        &owner,
+    )
+    .expect("Deposit should succeed");
+
+    let withdraw = |svm: &mut LiteSVM, amount: u64| {
+        send(
+            svm,
+            create_withdraw_amount_instruction(owner.pubkey(), vault_pda, amount, 0),
+            &owner,
+        )
+    };
+    let exceeded = Err(TransactionError::InstructionError(
+        0,
+        InstructionError::Custom(VaultError::RollingLimitExceeded as u32),
+    ));
+
+    let (first, second) = (4 * LAMPORTS_PER_SOL / 10, 6 * LAMPORTS_PER_SOL / 10);
+    let start = svm.get_sysvar::<Clock>().unix_timestamp;
+    withdraw(&mut svm, first).expect("First withdrawal should succeed");
+    warp_to(&mut svm, start + 1_000);
+    withdraw(&mut svm, second).expect("Withdrawal up to the limit should succeed");
+    assert_eq!(withdraw(&mut svm, 1), exceeded);
+
+    // Only the first withdrawal has left the window
+    warp_to(&mut svm, start + window_secs as i64);
+    withdraw(&mut svm, first).expect("Freed-up lamports should be withdrawable");
+    assert_eq!(withdraw(&mut svm, 1), exceeded);
+
+    warp_to(&mut svm, start + 1_000 + window_secs as i64);
+    withdraw(&mut svm, second).expect("Withdrawal after the window should succeed");
+}
+
+#[test]
+fn test_rolling_limit_counts_more_withdrawals_than_the_ring_holds() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    set_upgrade_authority(&mut svm, &owner.pubkey());
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let flags = VaultState::FLAG_ROLLING_LIMIT;
+    let mut open_ix = create_open_vault_instruction_with_flags(owner.pubkey(), vault_pda, flags);
+    open_ix
+        .data
+        .extend_from_slice(&LAMPORTS_PER_SOL.to_le_bytes());
+    open_ix.data.extend_from_slice(&3_600u64.to_le_bytes());
+    send(&mut svm, open_ix, &owner).expect("OpenVault with a rolling limit should succeed");
+    send(
+        &mut svm,
+        create_deposit_instruction(owner.pubkey(), vault_pda, 3 * LAMPORTS_PER_SOL),
+        &owner,
+    )
+    .expect("Deposit should succeed");
+
+    // Ten withdrawals don't fit the ring of `MAX_RECENT_WITHDRAWALS`, but all of them still count
+    let amount = LAMPORTS_PER_SOL / 10;
+    for second in 0..10 {
+        let mut clock = svm.get_sysvar::<Clock>();
+        clock.unix_timestamp += second;
+        svm.set_sysvar::<Clock>(&clock);
+        send(
+            &mut svm,
+            create_withdraw_amount_instruction(owner.pubkey(), vault_pda, amount, 0),
+            &owner,
+        )
+        .expect("Withdrawal within the limit should succeed");
+    }
+
+    assert_eq!(
+        send(
+            &mut svm,
+            create_withdraw_amount_instruction(owner.pubkey(), vault_pda, 1, 0),
+            &owner
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::RollingLimitExceeded as u32)
+        ))
+    );
+}
+
+/// Where `cargo build-sbf --manifest-path tests/hook_program/Cargo.toml` writes the test hook
+const HOOK_PROGRAM_PATH: &str = "tests/hook_program/target/deploy/hook_program.so";
+
+#[test]
+#[allow(deprecated)] // The runtime still reports a missing account as `NotEnoughAccountKeys`
+fn test_hook_program_is_called_after_deposit_and_withdraw() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+    let hook_program = Pubkey::new_unique();
+    svm.add_program_from_file(hook_program, HOOK_PROGRAM_PATH)
+        .expect("Failed to load hook program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let send = |svm: &mut LiteSVM, instruction: Instruction| {
+        send_transaction(svm, &[instruction], &[&owner])
+            .map(|meta| meta.logs)
+            .map_err(|f| f.err)
+    };
+
+    // `[referral_bps][price_oracle][max_vaults_per_owner][max_deposit_per_tx][rent_subsidy]
+    // [dust_threshold][hook_program]`
+    let mut initialize_ix = create_initialize_instruction(owner.pubkey(), 0);
+    initialize_ix.data.extend_from_slice(&[0u8; 32]);
+    initialize_ix.data.extend_from_slice(&0u64.to_le_bytes());
+    initialize_ix.data.extend_from_slice(&0u64.to_le_bytes());
+    initialize_ix.data.extend_from_slice(&[0u8; 32]);
+    initialize_ix.data.extend_from_slice(&0u64.to_le_bytes());
+    initialize_ix.data.extend_from_slice(hook_program.as_ref());
+    send(&mut svm, initialize_ix).expect("Initialize should succeed");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let hook_meta = AccountMeta::new_readonly(hook_program, false);
+
+    // A configured hook can't be left out
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL);
+    assert_eq!(
+        send(&mut svm, deposit_ix.clone()),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::NotEnoughAccountKeys
+        ))
+    );
+
+    let mut hooked_deposit_ix = deposit_ix;
+    hooked_deposit_ix.accounts.push(hook_meta.clone());
+    let logs = send(&mut svm, hooked_deposit_ix).expect("Hooked deposit should succeed");
+    let fired = |logs: &[String], event: u8| {
+        let line = format!("Program log: hook: event={event} amount={LAMPORTS_PER_SOL}");
+        logs.iter().any(|log| log == &line)
+    };
+    assert!(fired(&logs, blueshift_vault::hook::HOOK_DEPOSIT));
+
+    // Nor can it on the way out, whatever the withdraw's data says
+    let withdraw_ix = create_withdraw_instruction(owner.pubkey(), vault_pda);
+    assert_eq!(
+        send(&mut svm, withdraw_ix.clone()),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::NotEnoughAccountKeys
+        ))
+    );
+
+    let mut hooked_withdraw_ix = withdraw_ix;
+    hooked_withdraw_ix.accounts.push(hook_meta);
+    let logs = send(&mut svm, hooked_withdraw_ix).expect("Hooked withdraw should succeed");
+    assert!(fired(&logs, blueshift_vault::hook::HOOK_WITHDRAW));
+}
+
+#[test]
+fn test_initialize_rejects_the_vault_program_as_its_own_hook() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let admin = Keypair::new();
+    set_upgrade_authority(&mut svm, &admin.pubkey());
+    svm.airdrop(&admin.pubkey(), LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let mut initialize_ix = create_initialize_instruction(admin.pubkey(), 0);
+    initialize_ix.data.extend_from_slice(&[0u8; 32]);
+    initialize_ix.data.extend_from_slice(&0u64.to_le_bytes());
+    initialize_ix.data.extend_from_slice(&0u64.to_le_bytes());
+    initialize_ix.data.extend_from_slice(&[0u8; 32]);
+    initialize_ix.data.extend_from_slice(&0u64.to_le_bytes());
+    initialize_ix.data.extend_from_slice(PROGRAM_ID.as_ref());
+
+    let tx = Transaction::new_signed_with_payer(
+        &[initialize_ix],
+        Some(&admin.pubkey()),
+        &[&admin],
+        svm.latest_blockhash(),
+    );
+    assert_eq!(
+        svm.send_transaction(tx).map(|_| ()).map_err(|f| f.err),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::HookReentrancy as u32)
+        ))
+    );
+    assert!(svm.get_account(&find_config_pda().0).is_none());
+}
+
+#[test]
+fn test_deposit_restores_rent_floor_of_drained_vault_before_crediting() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    send(
+        &mut svm,
+        create_open_vault_instruction(owner.pubkey(), vault_pda),
+        &owner,
+    )
+    .expect("OpenVault should succeed");
+    send(
+        &mut svm,
+        create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL),
+        &owner,
+    )
+    .expect("Deposit should succeed");
+
+    // Drain the vault below its rent floor from outside the program
+    let rent_floor = svm.minimum_balance_for_rent_exemption(VaultState::LEN);
+    let mut drained = svm.get_account(&vault_pda).expect("Vault should exist");
+    drained.lamports = rent_floor - 1_000;
+    svm.set_account(vault_pda, drained)
+        .expect("Failed to drain vault");
+
+    send(
+        &mut svm,
+        create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL / 2),
+        &owner,
+    )
+    .expect("Deposit into a drained vault should succeed");
+
+    // The deposit tops the rent floor back up and only the rest is withdrawable
+    let deposited = LAMPORTS_PER_SOL / 2 - 1_000;
+    assert_eq!(svm.get_balance(&vault_pda).unwrap(), rent_floor + deposited);
+    let state = client::fetch_vault_state(
+        &|address: &Pubkey| svm.get_account(address),
+        &owner.pubkey(),
+        0,
+    )
+    .expect("Opened vault should hold its state");
+    assert_eq!(state.balance(), deposited);
+}
+
+#[test]
+fn test_batches_over_max_vaults_fail_before_moving_lamports() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 20 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let vaults: Vec<(Pubkey, u64)> = (0..=MAX_BATCH_VAULTS as u64)
+        .map(|id| (find_vault_pda_with_id(&owner.pubkey(), id).0, id))
+        .collect();
+    for (vault, id) in &vaults {
+        send(
+            &mut svm,
+            create_deposit_instruction_with_id(owner.pubkey(), *vault, LAMPORTS_PER_SOL, *id),
+            &owner,
+        )
+        .expect("Deposit should succeed");
+    }
+
+    let too_large = Err(TransactionError::InstructionError(
+        0,
+        InstructionError::Custom(VaultError::BatchTooLarge as u32),
+    ));
+    assert_eq!(
+        send(
+            &mut svm,
+            create_withdraw_many_instruction(owner.pubkey(), &vaults),
+            &owner
+        ),
+        too_large
+    );
+    for (vault, _id) in &vaults {
+        assert_eq!(svm.get_balance(vault), Some(LAMPORTS_PER_SOL));
+    }
+
+    let deposits: Vec<(Pubkey, u64)> = (0..=MAX_BATCH_VAULTS)
+        .map(|_| (Pubkey::new_unique(), LAMPORTS_PER_SOL))
+        .collect();
+    assert_eq!(
+        send(
+            &mut svm,
+            create_deposit_many_instruction(owner.pubkey(), &deposits),
+            &owner
+        ),
+        too_large
+    );
+    for (deposit_owner, _amount) in &deposits {
+        assert!(svm.get_account(&find_vault_pda(deposit_owner).0).is_none());
+    }
+
+    // A full batch still goes through
+    send(
+        &mut svm,
+        create_withdraw_many_instruction(owner.pubkey(), &vaults[..MAX_BATCH_VAULTS]),
+        &owner,
+    )
+    .expect("WithdrawMany of MAX_BATCH_VAULTS vaults should succeed");
+}
+
+#[test]
+fn test_withdraw_all_with_tip_pays_relayer_and_owner() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    let relayer = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+    svm.airdrop(&relayer.pubkey(), LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    for instruction in [
+        create_open_vault_instruction(owner.pubkey(), vault_pda),
+        create_deposit_instruction(owner.pubkey(), vault_pda, 2 * LAMPORTS_PER_SOL),
+    ] {
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&owner.pubkey()),
+            &[&owner],
+            svm.latest_blockhash(),
+        );
+        svm.send_transaction(tx).expect("Failed to fund vault");
+    }
+
+    let mut clock = svm.get_sysvar::<Clock>();
+    clock.unix_timestamp = 1_000_000;
+    svm.set_sysvar::<Clock>(&clock);
+
+    // The approval names the relayer, so `approver` is who the owner meant to tip
+    let tip = 5_000_000;
+    let relay_until =
+        |svm: &mut LiteSVM, submitter: &Keypair, approver: &Keypair, tip: u64, expiry_ts: i64| {
+            let message = approval::tip_approval_message(
+                &vault_pda.to_bytes(),
+                tip,
+                1,
+                &approver.pubkey().to_bytes(),
+                expiry_ts,
+            );
+            let instructions = [
+                create_ed25519_verify_instruction(&owner, &message),
+                create_withdraw_all_with_tip_instruction(
+                    submitter.pubkey(),
+                    owner.pubkey(),
+                    vault_pda,
+                    tip,
+                    1,
+                    expiry_ts,
+                ),
+            ];
+
+            svm.expire_blockhash();
+            let tx = Transaction::new_signed_with_payer(
+                &instructions,
+                Some(&submitter.pubkey()),
+                &[submitter],
+                svm.latest_blockhash(),
+            );
+            let fee = total_fees([&tx]);
+            svm.send_transaction(tx).map(|_| fee).map_err(|f| f.err)
+        };
+    let relay = |svm: &mut LiteSVM, submitter: &Keypair, approver: &Keypair, tip: u64| {
+        relay_until(svm, submitter, approver, tip, 1_000_060)
+    };
+
+    // An expired approval is rejected
+    assert_eq!(
+        relay_until(&mut svm, &relayer, &relayer, tip, 999_999),
+        Err(TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(VaultError::ApprovalExpired as u32)
+        ))
+    );
+
+    // Another relayer can't take over the approval and its tip
+    let interloper = Keypair::new();
+    svm.airdrop(&interloper.pubkey(), LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+    assert_eq!(
+        relay(&mut svm, &interloper, &relayer, tip),
+        Err(TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(VaultError::InvalidApproval as u32)
+        ))
+    );
+
+    // Nor can a tip above the balance be approved into a withdrawal
+    assert_eq!(
+        relay(&mut svm, &relayer, &relayer, 3 * LAMPORTS_PER_SOL),
+        Err(TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(VaultError::InsufficientFunds as u32)
+        ))
+    );
+
+    let owner_before = svm.get_balance(&owner.pubkey()).unwrap();
+    let relayer_before = svm.get_balance(&relayer.pubkey()).unwrap();
+    let fee = relay(&mut svm, &relayer, &relayer, tip).expect("Tipped withdrawal should succeed");
+
+    assert_eq!(
+        svm.get_balance(&relayer.pubkey()).unwrap(),
+        relayer_before - fee + tip
+    );
+    assert_eq!(
+        svm.get_balance(&owner.pubkey()).unwrap(),
+        owner_before + 2 * LAMPORTS_PER_SOL - tip
+    );
+    assert_eq!(
+        svm.get_balance(&vault_pda).unwrap(),
+        svm.minimum_balance_for_rent_exemption(VaultState::LEN)
+    );
+
+    // The approval is single-use
+    assert_eq!(
+        relay(&mut svm, &relayer, &relayer, tip),
+        Err(TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(VaultError::NonceAlreadyUsed as u32)
+        ))
+    );
+}
+
+#[test]
+fn test_metadata_lives_outside_the_vault_state() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let metadata = |svm: &LiteSVM| {
+        client::fetch_vault_metadata(&|address: &Pubkey| svm.get_account(address), &vault_pda)
+    };
+    send(
+        &mut svm,
+        create_open_vault_instruction(owner.pubkey(), vault_pda),
+        &owner,
+    )
+    .expect("OpenVault should succeed");
+
+    // Nothing is allocated until metadata is first set
+    assert!(metadata(&svm).is_none());
+    let memo = "Rainy day fund, don't touch before winter".as_bytes();
+    send(
+        &mut svm,
+        create_set_metadata_instruction(owner.pubkey(), vault_pda, memo),
+        &owner,
+    )
+    .expect("SetMetadata should succeed");
+    let stored = metadata(&svm).expect("Metadata should exist");
+    assert_eq!((stored.version(), stored.memo()), (1, memo));
+    assert_eq!(
+        svm.get_account(&vault_pda).unwrap().data.len(),
+        VaultState::LEN
+    );
+
+    // Deposits and withdrawals neither need nor touch the metadata account
+    send(
+        &mut svm,
+        create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL),
+        &owner,
+    )
+    .expect("Deposit should succeed");
+    send(
+        &mut svm,
+        create_withdraw_instruction(owner.pubkey(), vault_pda),
+        &owner,
+    )
+    .expect("Withdraw should succeed");
+    assert_eq!(
+        svm.get_balance(&vault_pda).unwrap(),
+        svm.minimum_balance_for_rent_exemption(VaultState::LEN)
+    );
+    assert_eq!(
+        metadata(&svm).map(|stored| stored.memo().to_vec()),
+        Some(memo.to_vec())
+    );
+
+    // Only the owner can write it, and oversized memos are rejected
+    assert_eq!(
+        send(
+            &mut svm,
+            create_set_metadata_instruction(owner.pubkey(), vault_pda, &[b'a'; 129]),
+            &owner
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::InvalidInstructionData
+        ))
+    );
+    let stranger = Keypair::new();
+    svm.airdrop(&stranger.pubkey(), LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+    let tx = Transaction::new_signed_with_payer(
+        &[create_set_metadata_instruction(
+            stranger.pubkey(),
+            vault_pda,
+            b"mine",
+        )],
+        Some(&stranger.pubkey()),
+        &[&stranger],
+        svm.latest_blockhash(),
+    );
+    assert_eq!(
+        svm.send_transaction(tx).map(|_| ()).map_err(|f| f.err),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::InvalidAccountOwner
+        ))
+    );
+}
+
+#[test]
+fn test_deposit_with_metadata_creates_a_funded_vault_with_its_memo() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let memo = "Holiday savings".as_bytes();
+
+    // Oversized memos are rejected before anything is created
+    assert_eq!(
+        send(
+            &mut svm,
+            create_deposit_with_metadata_instruction(
+                owner.pubkey(),
+                vault_pda,
+                LAMPORTS_PER_SOL,
+                &[b'a'; 129]
+            ),
+            &owner
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::InvalidInstructionData
+        ))
+    );
+    assert!(svm.get_account(&vault_pda).is_none());
+
+    send(
+        &mut svm,
+        create_deposit_with_metadata_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL, memo),
+        &owner,
+    )
+    .expect("DepositWithMetadata should succeed");
+
+    let state = client::fetch_vault_state(
+        &|address: &Pubkey| svm.get_account(address),
+        &owner.pubkey(),
+        0,
+    )
+    .expect("Vault should be opened");
+    assert_eq!(state.balance(), LAMPORTS_PER_SOL);
+    let stored =
+        client::fetch_vault_metadata(&|address: &Pubkey| svm.get_account(address), &vault_pda)
+            .expect("Metadata should exist");
+    assert_eq!(stored.memo(), memo);
+
+    // Only a vault the deposit opens starts out with metadata
+    assert_eq!(
+        send(
+            &mut svm,
+            create_deposit_with_metadata_instruction(
+                owner.pubkey(),
+                vault_pda,
+                LAMPORTS_PER_SOL,
+                b"again"
+            ),
+            &owner
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::VaultNotEmpty as u32)
+        ))
+    );
+}
+
+#[test]
+fn test_frozen_vault_rejects_deposits_and_withdrawals_until_unfrozen() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL);
+    let withdraw_ix = create_withdraw_instruction(owner.pubkey(), vault_pda);
+
+    send(
+        &mut svm,
+        create_open_vault_instruction(owner.pubkey(), vault_pda),
+        &owner,
+    )
+    .expect("OpenVault should succeed");
+    send(&mut svm, deposit_ix.clone(), &owner).expect("Deposit should succeed");
+    send(
+        &mut svm,
+        create_set_frozen_instruction(owner.pubkey(), vault_pda, true),
+        &owner,
+    )
+    .expect("Freezing should succeed");
+
+    let frozen = Err(TransactionError::InstructionError(
+        0,
+        InstructionError::Custom(VaultError::VaultFrozen as u32),
+    ));
+    assert_eq!(send(&mut svm, deposit_ix.clone(), &owner), frozen);
+    assert_eq!(send(&mut svm, withdraw_ix.clone(), &owner), frozen);
+    let rent_floor = svm.minimum_balance_for_rent_exemption(VaultState::LEN);
+    assert_eq!(
+        svm.get_balance(&vault_pda).unwrap(),
+        rent_floor + LAMPORTS_PER_SOL
+    );
+
+    send(
+        &mut svm,
+        create_set_frozen_instruction(owner.pubkey(), vault_pda, false),
+        &owner,
+    )
+    .expect("Unfreezing should succeed");
+    send(&mut svm, deposit_ix, &owner).expect("Deposit should succeed once unfrozen");
+    send(&mut svm, withdraw_ix, &owner).expect("Withdraw should succeed once unfrozen");
+    assert_eq!(svm.get_balance(&vault_pda).unwrap(), rent_floor);
+}
+
+#[test]
+fn test_vault_admin_can_freeze_and_set_limits_but_not_withdraw() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    let admin = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+    svm.airdrop(&admin.pubkey(), LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let not_admin = Err(TransactionError::InstructionError(
+        0,
+        InstructionError::Custom(VaultError::NotVaultAdmin as u32),
+    ));
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+
+    // The owner can't be its own vault's admin
+    assert_eq!(
+        send(
+            &mut svm,
+            create_open_vault_instruction_with_admin(owner.pubkey(), vault_pda, owner.pubkey()),
+            &owner,
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::InvalidArgument
+        ))
+    );
+
+    send(
+        &mut svm,
+        create_open_vault_instruction_with_admin(owner.pubkey(), vault_pda, admin.pubkey()),
+        &owner,
+    )
+    .expect("OpenVault should succeed");
+    send(
+        &mut svm,
+        create_deposit_instruction(owner.pubkey(), vault_pda, 3 * LAMPORTS_PER_SOL),
+        &owner,
+    )
+    .expect("Deposit should succeed");
+
+    let vault_account = svm.get_account(&vault_pda).expect("Vault should exist");
+    let state = VaultState::from_bytes(&vault_account.data).expect("Vault should hold state");
+    assert_eq!(state.admin(), &admin.pubkey().to_bytes());
+
+    // Maintenance is the admin's, not the owner's
+    assert_eq!(
+        send(
+            &mut svm,
+            create_set_frozen_instruction(owner.pubkey(), vault_pda, true),
+            &owner,
+        ),
+        not_admin
+    );
+    assert_eq!(
+        send(
+            &mut svm,
+            create_set_daily_limit_instruction(owner.pubkey(), vault_pda, 0),
+            &owner,
+        ),
+        not_admin
+    );
+
+    send(
+        &mut svm,
+        create_set_frozen_instruction(admin.pubkey(), vault_pda, true),
+        &admin,
+    )
+    .expect("The admin should be able to freeze the vault");
+    assert_eq!(
+        send(
+            &mut svm,
+            create_withdraw_instruction(owner.pubkey(), vault_pda),
+            &owner,
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::VaultFrozen as u32)
+        ))
+    );
+    send(
+        &mut svm,
+        create_set_frozen_instruction(admin.pubkey(), vault_pda, false),
+        &admin,
+    )
+    .expect("The admin should be able to unfreeze the vault");
+
+    // The admin has no custody, so it can't take the lamports itself
+    let vault_lamports = svm.get_balance(&vault_pda).unwrap();
+    let mut admin_withdraw_ix =
+        create_withdraw_amount_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL, 0);
+    admin_withdraw_ix.accounts[0] = AccountMeta::new(admin.pubkey(), true);
+    assert!(send(&mut svm, admin_withdraw_ix, &admin).is_err());
+    assert_eq!(svm.get_balance(&vault_pda).unwrap(), vault_lamports);
+
+    // The owner withdraws within the limit the admin sets
+    send(
+        &mut svm,
+        create_set_daily_limit_instruction(admin.pubkey(), vault_pda, LAMPORTS_PER_SOL),
+        &admin,
+    )
+    .expect("The admin should be able to set the daily limit");
+    assert_eq!(
+        send(
+            &mut svm,
+            create_withdraw_amount_instruction(owner.pubkey(), vault_pda, 2 * LAMPORTS_PER_SOL, 0),
+            &owner,
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::DailyLimitExceeded as u32)
+        ))
+    );
+    send(
+        &mut svm,
+        create_withdraw_amount_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL, 0),
+        &owner,
+    )
+    .expect("Withdrawing within the limit should succeed");
+
+    // Locks are maintenance too, but the expiry only ever hands the vault back to its owner
+    let unlock_ts = svm.get_sysvar::<Clock>().unix_timestamp + 1_000;
+    assert_eq!(
+        send(
+            &mut svm,
+            create_extend_lock_instruction(owner.pubkey(), vault_pda, unlock_ts),
+            &owner,
+        ),
+        not_admin
+    );
+    send(
+        &mut svm,
+        create_extend_lock_instruction(admin.pubkey(), vault_pda, unlock_ts),
+        &admin,
+    )
+    .expect("The admin should be able to lock the vault");
+    send(
+        &mut svm,
+        create_set_expiry_instruction(owner.pubkey(), vault_pda, unlock_ts),
+        &owner,
+    )
+    .expect("The owner should still set the expiry");
+}
+
+#[test]
+fn test_client_create_and_deposit_opens_and_funds_in_one_transaction() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let tx = Transaction::new_signed_with_payer(
+        &client::create_and_deposit(&owner.pubkey(), 2 * LAMPORTS_PER_SOL),
+        Some(&owner.pubkey()),
+        &[&owner],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .expect("Open and deposit should succeed together");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    assert_eq!(
+        svm.get_balance(&vault_pda).unwrap(),
+        svm.minimum_balance_for_rent_exemption(VaultState::LEN) + 2 * LAMPORTS_PER_SOL
+    );
+    let state = client::fetch_vault_state(
+        &|address: &Pubkey| svm.get_account(address),
+        &owner.pubkey(),
+        0,
+    )
+    .expect("Opened vault should hold its state");
+    assert_eq!(state.balance(), 2 * LAMPORTS_PER_SOL);
+}
+
+#[test]
+fn test_failing_second_instruction_rolls_back_the_deposit_before_it() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let unlock_ts = svm.get_sysvar::<Clock>().unix_timestamp + 3_600;
+    for instruction in [
+        create_open_vault_instruction(owner.pubkey(), vault_pda),
+        create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL),
+        create_extend_lock_instruction(owner.pubkey(), vault_pda, unlock_ts),
+    ] {
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&owner.pubkey()),
+            &[&owner],
+            svm.latest_blockhash(),
+        );
+        svm.send_transaction(tx).expect("Failed to set up locked vault");
+    }
+
+    let snapshot = |svm: &LiteSVM| {
+        [vault_pda, find_stats_pda().0, find_registry_pda(&owner.pubkey()).0]
+            .map(|address| svm.get_account(&address))
+    };
+    let before = snapshot(&svm);
+    let owner_before = svm.get_balance(&owner.pubkey()).unwrap();
+
+    // The deposit succeeds on its own, but the locked vault fails the withdraw after it
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL),
+            create_withdraw_instruction(owner.pubkey(), vault_pda),
+        ],
+        Some(&owner.pubkey()),
+        &[&owner],
+        svm.latest_blockhash(),
+    );
+    let fees = total_fees([&tx]);
+    assert_eq!(
+        svm.send_transaction(tx).map(|_| ()).map_err(|f| f.err),
+        Err(TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(VaultError::VaultLocked as u32)
+        ))
+    );
+
+    // Only the fee is charged; nothing the deposit wrote survives
+    assert_eq!(snapshot(&svm), before);
+    assert_eq!(
+        svm.get_balance(&owner.pubkey()).unwrap(),
+        owner_before - fees
+    );
+}
+
+#[test]
+fn test_client_deposit_lands_in_a_durable_nonce_transaction() {
+    use solana_sdk::hash::Hash;
+    use solana_system_interface::instruction::{advance_nonce_account, create_nonce_account};
+
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    let nonce = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let tx = Transaction::new_signed_with_payer(
+        &create_nonce_account(
+            &owner.pubkey(),
+            &nonce.pubkey(),
+            &owner.pubkey(),
+            LAMPORTS_PER_SOL,
+        ),
+        Some(&owner.pubkey()),
+        &[&owner, &nonce],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .expect("Creating the nonce account should succeed");
+
+    // `[version: u32][state: u32][authority: Pubkey][durable_nonce: Hash][fee_calculator]`
+    let nonce_data = svm.get_account(&nonce.pubkey()).unwrap().data;
+    let durable_nonce = Hash::new_from_array(nonce_data[40..72].try_into().unwrap());
+
+    // Signed offline against the nonce, and submitted once the blockhash is long gone
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            advance_nonce_account(&nonce.pubkey(), &owner.pubkey()),
+            client::deposit_instruction(&owner.pubkey(), 2 * LAMPORTS_PER_SOL),
+        ],
+        Some(&owner.pubkey()),
+        &[&owner],
+        durable_nonce,
+    );
+    for _ in 0..3 {
+        svm.expire_blockhash();
+    }
+    svm.send_transaction(tx)
+        .expect("Durable nonce deposit should succeed");
+
+    assert_eq!(
+        client::vault_balance(
+            &|address: &Pubkey| svm.get_account(address),
+            &owner.pubkey()
+        ),
+        Some(2 * LAMPORTS_PER_SOL)
+    );
+    let advanced = svm.get_account(&nonce.pubkey()).unwrap().data;
+    assert_ne!(advanced[40..72], nonce_data[40..72]);
+}
+
+#[test]
+fn test_withdraw_with_approval_rejects_expired_approvals() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    let relayer = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+    svm.airdrop(&relayer.pubkey(), LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    for instruction in [
+        create_open_vault_instruction(owner.pubkey(), vault_pda),
+        create_deposit_instruction(owner.pubkey(), vault_pda, 2 * LAMPORTS_PER_SOL),
+    ] {
+        svm.expire_blockhash();
+        let recent_blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&owner.pubkey()),
+            &[&owner],
+            recent_blockhash,
+        );
+        svm.send_transaction(tx).expect("Failed to fund vault");
+    }
+
+    let amount = LAMPORTS_PER_SOL / 2;
+    let relay = |svm: &mut LiteSVM, nonce: u64, expiry_ts: i64| {
+        let message = approval::approval_message(&vault_pda.to_bytes(), amount, nonce, expiry_ts);
+        let instructions = [
+            create_ed25519_verify_instruction(&owner, &message),
+            create_withdraw_with_approval_instruction(
+                relayer.pubkey(),
+                owner.pubkey(),
+                vault_pda,
+                amount,
+                nonce,
+                expiry_ts,
+            ),
+        ];
+
+        svm.expire_blockhash();
+        let recent_blockhash = svm.latest_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&relayer.pubkey()),
+            &[&relayer],
+            recent_blockhash,
+        );
+        svm.send_transaction(tx).map(|_| ()).map_err(|f| f.err)
+    };
+
+    let mut clock = svm.get_sysvar::<Clock>();
+    clock.unix_timestamp = 1_000_000;
+    svm.set_sysvar::<Clock>(&clock);
+
+    // An approval is still good up to and including its expiry
+    relay(&mut svm, 1, 1_000_060).expect("Unexpired approval should succeed");
+    relay(&mut svm, 2, 1_000_000).expect("Approval expiring now should succeed");
+
+    // One that expired is rejected, without using up its nonce
+    let owner_before = svm.get_balance(&owner.pubkey()).unwrap();
+    assert_eq!(
+        relay(&mut svm, 3, 999_999),
+        Err(TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(VaultError::ApprovalExpired as u32)
+        ))
+    );
+    assert_eq!(svm.get_balance(&owner.pubkey()).unwrap(), owner_before);
+
+    // Nor can its expiry be pushed back without the owner signing again
+    let message = approval::approval_message(&vault_pda.to_bytes(), amount, 3, 999_999);
+    let extended = [
+        create_ed25519_verify_instruction(&owner, &message),
+        create_withdraw_with_approval_instruction(
+            relayer.pubkey(),
+            owner.pubkey(),
+            vault_pda,
+            amount,
+            3,
+            i64::MAX,
+        ),
+    ];
+    svm.expire_blockhash();
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &extended,
+        Some(&relayer.pubkey()),
+        &[&relayer],
+        recent_blockhash,
+    );
+    assert_eq!(
+        svm.send_transaction(tx).map(|_| ()).map_err(|f| f.err),
+        Err(TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(VaultError::InvalidApproval as u32)
+        ))
+    );
+
+    assert_eq!(
+        client::fetch_vault_state(
+            &|address: &Pubkey| svm.get_account(address),
+            &owner.pubkey(),
+            0
+        )
+        .map(|state| state.used_nonce()),
+        Some(2)
+    );
+}
+
+#[test]
+fn test_reconcile_recognizes_donated_lamports_in_the_balance() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let payer = Keypair::new();
+    let owner = Keypair::new();
+    svm.airdrop(&payer.pubkey(), LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let rent_floor = svm.minimum_balance_for_rent_exemption(VaultState::LEN);
+    let deposit_amount = 2 * LAMPORTS_PER_SOL;
+
+    let send = |svm: &mut LiteSVM, instruction: Instruction| {
+        send_transaction(svm, &[instruction], &[&payer, &owner]).map_err(|f| f.err)
+    };
+    let balance = |svm: &LiteSVM| {
+        client::fetch_vault_state(
+            &|address: &Pubkey| svm.get_account(address),
+            &owner.pubkey(),
+            0,
+        )
+        .map(|state| state.balance())
+    };
+
+    send(
+        &mut svm,
+        create_open_vault_instruction(owner.pubkey(), vault_pda),
+    )
+    .expect("Open should succeed");
+    send(
+        &mut svm,
+        create_deposit_instruction(owner.pubkey(), vault_pda, deposit_amount),
+    )
+    .expect("Deposit should succeed");
+
+    // A donation isn't withdrawable until the owner reconciles it
+    let donation = LAMPORTS_PER_SOL;
+    svm.airdrop(&vault_pda, donation)
+        .expect("Failed to airdrop");
+    assert_eq!(balance(&svm), Some(deposit_amount));
+
+    // Only the owner can reconcile
+    assert_eq!(
+        send(
+            &mut svm,
+            create_reconcile_instruction(payer.pubkey(), vault_pda)
+        )
+        .map(|_| ()),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::InvalidAccountOwner
+        ))
+    );
+
+    let meta = send(
+        &mut svm,
+        create_reconcile_instruction(owner.pubkey(), vault_pda),
+    )
+    .expect("Reconcile should succeed");
+    assert_eq!(
+        meta.return_data.data,
+        (deposit_amount + donation).to_le_bytes()
+    );
+    assert_eq!(balance(&svm), Some(deposit_amount + donation));
+
+    // The whole reconciled balance withdraws, and the vault keeps its rent floor
+    let owner_before = svm.get_balance(&owner.pubkey()).unwrap();
+    send(
+        &mut svm,
+        create_withdraw_instruction_with_flags(owner.pubkey(), vault_pda, 0, false),
+    )
+    .expect("Withdraw should succeed");
+    assert_eq!(
+        svm.get_balance(&owner.pubkey()).unwrap(),
+        owner_before + deposit_amount + donation
+    );
+    assert_eq!(svm.get_balance(&vault_pda), Some(rent_floor));
+
+    // A vault drained below its rent floor can't reconcile to less than nothing
+    let mut vault_account = svm.get_account(&vault_pda).unwrap();
+    vault_account.lamports = rent_floor - 1;
+    svm.set_account(vault_pda, vault_account)
+        .expect("Failed to drain vault");
+    assert_eq!(
+        send(
+            &mut svm,
+            create_reconcile_instruction(owner.pubkey(), vault_pda)
+        )
+        .map(|_| ()),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::InsufficientFunds as u32)
+        ))
+    );
+    assert_eq!(balance(&svm), Some(0));
+}
+
+#[test]
+#[allow(deprecated)] // The runtime still reports a missing account as `NotEnoughAccountKeys`
+fn test_deposit_with_missing_accounts_fails_cleanly() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    // Only `[owner, vault]`, without the system program, stats and config
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let mut instruction = create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL);
+    instruction.accounts.truncate(VaultAccounts::SYSTEM_PROGRAM);
+    assert_eq!(instruction.accounts[VaultAccounts::VAULT].pubkey, vault_pda);
+
+    let recent_blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&owner.pubkey()),
+        &[&owner],
+        recent_blockhash,
+    );
+    let failure = svm
+        .send_transaction(tx)
+        .expect_err("Deposit without its accounts should fail");
+
+    assert_eq!(
+        failure.err,
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+    assert!(
+        failure.meta.logs.iter().any(|log| log.contains(&format!(
+            "Expected at least {} accounts, got 2",
+            DepositAccounts::MIN_ACCOUNTS
+        ))),
+        "The missing accounts should be logged"
+    );
+    assert!(svm.get_account(&vault_pda).is_none());
+}
+
+#[test]
+fn test_application_seeded_vault_deposits_and_withdraws_under_its_seed() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    // Vaults namespaced by order id, passed to instructions as their salt
+    let order = blueshift_vault::pda::application_salt(b"order-1234").unwrap();
+    let other_order = blueshift_vault::pda::application_salt(b"order-1235").unwrap();
+    let (vault_pda, _bump) = client::find_seeded_vault_address(&owner.pubkey(), 0, b"order-1234")
+        .expect("Seed should fit");
+    assert_eq!(
+        vault_pda,
+        find_vault_pda_with_salt(&owner.pubkey(), &order).0
+    );
+
+    send(
+        &mut svm,
+        create_deposit_instruction_with_salt(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL, &order),
+        &owner,
+    )
+    .expect("Deposit under the application seed should succeed");
+    assert_eq!(svm.get_balance(&vault_pda), Some(LAMPORTS_PER_SOL));
+
+    // Another order's seed doesn't derive the vault
+    assert_eq!(
+        send(
+            &mut svm,
+            create_withdraw_instruction_with_salt(owner.pubkey(), vault_pda, &other_order),
+            &owner
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::InvalidPda as u32)
+        ))
+    );
+
+    send(
+        &mut svm,
+        create_withdraw_instruction_with_salt(owner.pubkey(), vault_pda, &order),
+        &owner,
+    )
+    .expect("Withdraw under the same seed should succeed");
+    assert!(svm.get_account(&vault_pda).is_none());
+}
+
+#[test]
+fn test_idempotent_withdraw_of_an_empty_vault_is_a_no_op() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let send = |svm: &mut LiteSVM, instruction: Instruction| {
+        send_transaction(svm, &[instruction], &[&owner]).map_err(|f| f.err)
+    };
+    let idempotent = client::withdraw_idempotent_instruction(&owner.pubkey());
+
+    // A never-funded vault withdraws idempotently, but still fails without the flag
+    let meta = send(&mut svm, idempotent.clone())
+        .expect("Idempotent withdraw of an empty vault should succeed");
+    assert_eq!(meta.return_data.data, 0u64.to_le_bytes());
+    assert_eq!(
+        send(&mut svm, client::withdraw_instruction(&owner.pubkey())).map(|_| ()),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::VaultEmpty as u32)
+        ))
+    );
+
+    // A funded vault withdraws and closes as usual, after which retries are no-ops
+    send(
+        &mut svm,
+        create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL),
+    )
+    .expect("Deposit should succeed");
+    let owner_before = svm.get_balance(&owner.pubkey()).unwrap();
+    send(&mut svm, idempotent.clone())
+        .expect("Idempotent withdraw of a funded vault should succeed");
+    assert!(svm.get_account(&vault_pda).is_none());
+    assert!(svm.get_balance(&owner.pubkey()).unwrap() > owner_before);
+
+    send(&mut svm, idempotent.clone()).expect("Retrying on the closed vault should succeed");
+    assert!(svm.get_account(&vault_pda).is_none());
+
+    // The owner still has to sign for it
+    let attacker = Keypair::new();
+    svm.airdrop(&attacker.pubkey(), LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+    let mut instruction = idempotent;
+    instruction.accounts[0].is_signer = false;
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&attacker.pubkey()),
+        &[&attacker],
+        svm.latest_blockhash(),
+    );
+    assert_eq!(
+        svm.send_transaction(tx).map(|_| ()).map_err(|f| f.err),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::MissingOwnerSignature as u32)
+        ))
+    );
+}
+
+#[test]
+fn test_withdraw_catches_tracked_balance_above_actual_lamports() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let deposit_amount = 2 * LAMPORTS_PER_SOL;
+
+    send(
+        &mut svm,
+        create_open_vault_instruction(owner.pubkey(), vault_pda),
+        &owner,
+    )
+    .expect("Open should succeed");
+    send(
+        &mut svm,
+        create_deposit_instruction(owner.pubkey(), vault_pda, deposit_amount),
+        &owner,
+    )
+    .expect("Deposit should succeed");
+
+    // Claim one lamport more than the vault holds above its rent floor
+    // `[.. total_deposited: 140..148][withdrawn_so_far: 148..156][balance: 156..164]`
+    let healthy = svm.get_account(&vault_pda).expect("Vault should exist");
+    let mut corrupted = healthy.clone();
+    corrupted.data[156..164].copy_from_slice(&(deposit_amount + 1).to_le_bytes());
+    assert_eq!(
+        VaultState::from_bytes(&corrupted.data).map(|state| state.balance()),
+        Some(deposit_amount + 1)
+    );
+    svm.set_account(vault_pda, corrupted)
+        .expect("Failed to corrupt vault");
+
+    for instruction in [
+        create_withdraw_instruction(owner.pubkey(), vault_pda),
+        create_withdraw_instruction_with_flags(owner.pubkey(), vault_pda, 0, false),
+    ] {
+        assert_eq!(
+            send(&mut svm, instruction, &owner),
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(VaultError::AccountingMismatch as u32)
+            ))
+        );
+    }
+    assert_eq!(
+        svm.get_account(&vault_pda).unwrap().lamports,
+        healthy.lamports
+    );
+
+    // With the accounting restored the vault withdraws as usual
+    svm.set_account(vault_pda, healthy)
+        .expect("Failed to restore vault");
+    send(
+        &mut svm,
+        create_withdraw_instruction(owner.pubkey(), vault_pda),
+        &owner,
+    )
+    .expect("Withdraw should succeed");
+    assert!(svm.get_account(&vault_pda).is_none());
+}
+
+#[test]
+fn test_client_max_deposit_amount_deposits_everything_but_fees_and_rent() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    // The owner stays rent-exempt, and the first deposit also pays for the stats PDA
+    let owner_rent = svm.minimum_balance_for_rent_exemption(0);
+    let stats_rent =
+        svm.minimum_balance_for_rent_exemption(blueshift_vault::stats::GlobalStats::LEN);
+    let rent = owner_rent + stats_rent;
+    let fee_estimate = 5_000;
+    let owner_balance = svm.get_balance(&owner.pubkey()).unwrap();
+    let amount = client::max_deposit_amount(owner_balance, fee_estimate, rent);
+    assert_eq!(amount, owner_balance - fee_estimate - rent);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[client::deposit_instruction(&owner.pubkey(), amount)],
+        Some(&owner.pubkey()),
+        &[&owner],
+        svm.latest_blockhash(),
+    );
+    let fees = total_fees([&tx]);
+    svm.send_transaction(tx)
+        .expect("Depositing the max amount should succeed");
+
+    assert_eq!(
+        client::vault_balance(
+            &|address: &Pubkey| svm.get_account(address),
+            &owner.pubkey()
+        ),
+        Some(amount)
+    );
+    assert!(fees <= fee_estimate);
+    let owner_after = svm.get_balance(&owner.pubkey()).unwrap();
+    assert_eq!(owner_after, owner_balance - amount - fees - stats_rent);
+    assert!(owner_after >= owner_rent);
+}
+
+#[test]
+fn test_deposit_rejects_program_owned_owner_account() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let payer = Keypair::new();
+    let owner = Keypair::new();
+    set_upgrade_authority(&mut svm, &owner.pubkey());
+    svm.airdrop(&payer.pubkey(), LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    // The owner still signs, but its account belongs to some program
+    svm.set_account(
+        owner.pubkey(),
+        Account {
+            lamports: 10 * LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    )
+    .expect("Failed to create program-owned owner");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let tx = Transaction::new_signed_with_payer(
+        &[create_deposit_instruction(
+            owner.pubkey(),
+            vault_pda,
+            LAMPORTS_PER_SOL,
+        )],
+        Some(&payer.pubkey()),
+        &[&payer, &owner],
+        svm.latest_blockhash(),
+    );
+    assert_eq!(
+        svm.send_transaction(tx).map(|_| ()).map_err(|f| f.err),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::InvalidOwnerAccount as u32)
+        ))
+    );
+    assert!(svm.get_account(&vault_pda).is_none());
+    assert_eq!(
+        svm.get_balance(&owner.pubkey()),
+        Some(10 * LAMPORTS_PER_SOL)
+    );
+}
+
+#[test]
+#[allow(deprecated)] // The runtime still reports a missing account as `NotEnoughAccountKeys`
+fn test_closed_vault_cannot_be_reused_until_the_cooldown_passes() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    // `[referral_bps][price_oracle][max_vaults_per_owner][max_deposit_per_tx][rent_subsidy]
+    // [dust_threshold][hook_program][reuse_cooldown_secs]`
+    let cooldown = 3_600u64;
+    let mut initialize_ix = create_initialize_instruction(owner.pubkey(), 0);
+    initialize_ix.data.extend_from_slice(&[0u8; 32]);
+    initialize_ix.data.extend_from_slice(&0u64.to_le_bytes());
+    initialize_ix.data.extend_from_slice(&0u64.to_le_bytes());
+    initialize_ix.data.extend_from_slice(&[0u8; 32]);
+    initialize_ix.data.extend_from_slice(&0u64.to_le_bytes());
+    initialize_ix.data.extend_from_slice(&[0u8; 32]);
+    initialize_ix
+        .data
+        .extend_from_slice(&cooldown.to_le_bytes());
+    send(&mut svm, initialize_ix, &owner).expect("Initialize should succeed");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let (tombstone, _bump) = client::find_tombstone_address(&vault_pda);
+    let deposit_ix = || {
+        let mut instruction =
+            create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL);
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(tombstone, false)); // tombstone
+        instruction
+    };
+    send(&mut svm, deposit_ix(), &owner).expect("First deposit should succeed");
+
+    // `[id][salt][refund][tombstone]`
+    let mut close_ix = create_close_instruction(owner.pubkey(), vault_pda, 17);
+    close_ix.data.extend_from_slice(&0u64.to_le_bytes());
+    close_ix.data.extend_from_slice(&[0u8; 32]);
+    close_ix.data.extend_from_slice(&[0, 1]);
+    close_ix
+        .accounts
+        .insert(4, AccountMeta::new(tombstone, false)); // tombstone (writable)
+    send(&mut svm, close_ix, &owner).expect("CloseAndSweep should succeed");
+    assert!(svm.get_account(&vault_pda).is_none());
+    assert!(svm.get_account(&tombstone).is_some());
+
+    // The configured cooldown needs the tombstone passed
+    assert_eq!(
+        send(
+            &mut svm,
+            create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL),
+            &owner
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::NotEnoughAccountKeys
+        ))
+    );
+    assert_eq!(
+        send(&mut svm, deposit_ix(), &owner),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::ReuseCooldown as u32)
+        ))
+    );
+
+    let mut clock = svm.get_sysvar::<Clock>();
+    clock.unix_timestamp += cooldown as i64;
+    svm.set_sysvar::<Clock>(&clock);
+
+    send(&mut svm, deposit_ix(), &owner).expect("Deposit after the cooldown should succeed");
+    assert_eq!(svm.get_balance(&vault_pda), Some(LAMPORTS_PER_SOL));
+}
+
+#[test]
+fn test_vault_harness_runs_a_deposit_withdraw_cycle() {
+    let mut harness = VaultHarness::new().expect("Failed to load program");
+    let owner = Keypair::new();
+    harness
+        .fund(&owner.pubkey(), 10.0)
+        .expect("Failed to airdrop");
+
+    harness
+        .deposit(&owner, client::sol(2.5))
+        .expect("Deposit should succeed");
+    assert_eq!(harness.balance(&owner.pubkey()), Some(client::sol(2.5)));
+
+    harness.withdraw(&owner).expect("Withdraw should succeed");
+    assert_eq!(harness.balance(&owner.pubkey()), None);
+}
+
+#[test]
+fn test_emergency_drain_moves_vault_funds_to_recovery_only_while_paused() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let admin = Keypair::new();
+    set_upgrade_authority(&mut svm, &admin.pubkey());
+    let owner = Keypair::new();
+    let recovery = Pubkey::new_unique();
+    for signer in [&admin, &owner] {
+        svm.airdrop(&signer.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop");
+    }
+
+    // `[referral_bps][price_oracle][max_vaults_per_owner][max_deposit_per_tx][rent_subsidy]
+    // [dust_threshold][hook_program][reuse_cooldown_secs][recovery_address]`
+    let mut initialize_ix = create_initialize_instruction(admin.pubkey(), 0);
+    initialize_ix.data.extend_from_slice(&[0u8; 32]);
+    initialize_ix.data.extend_from_slice(&0u64.to_le_bytes());
+    initialize_ix.data.extend_from_slice(&0u64.to_le_bytes());
+    initialize_ix.data.extend_from_slice(&[0u8; 32]);
+    initialize_ix.data.extend_from_slice(&0u64.to_le_bytes());
+    initialize_ix.data.extend_from_slice(&[0u8; 32]);
+    initialize_ix.data.extend_from_slice(&0u64.to_le_bytes());
+    initialize_ix.data.extend_from_slice(recovery.as_ref());
+    send(&mut svm, initialize_ix, &admin).expect("Initialize should succeed");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    send(
+        &mut svm,
+        create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL),
+        &owner,
+    )
+    .expect("Deposit should succeed");
+
+    let drain_ix =
+        create_emergency_drain_instruction(admin.pubkey(), owner.pubkey(), vault_pda, recovery);
+    assert_eq!(
+        send(&mut svm, drain_ix.clone(), &admin),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::NotPaused as u32)
+        ))
+    );
+
+    send(
+        &mut svm,
+        create_set_paused_instruction(admin.pubkey(), true),
+        &admin,
+    )
+    .expect("SetPaused should succeed");
+
+    // Pausing stops deposits, and only the admin can drain
+    let other = Keypair::new();
+    svm.airdrop(&other.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+    let (other_vault, _bump) = find_vault_pda(&other.pubkey());
+    assert_eq!(
+        send(
+            &mut svm,
+            create_deposit_instruction(other.pubkey(), other_vault, LAMPORTS_PER_SOL),
+            &other,
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::ProgramPaused as u32)
+        ))
+    );
+    assert_eq!(
+        send(
+            &mut svm,
+            create_emergency_drain_instruction(other.pubkey(), owner.pubkey(), vault_pda, recovery),
+            &other,
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::NotAdmin as u32)
+        ))
+    );
+
+    // Owners get the whole delay to withdraw before anything can be drained
+    assert_eq!(
+        send(&mut svm, drain_ix.clone(), &admin),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::DrainDelayNotElapsed as u32)
+        ))
+    );
+
+    let mut clock = svm.get_sysvar::<Clock>();
+    clock.unix_timestamp += 3 * 86_400;
+    svm.set_sysvar::<Clock>(&clock);
+
+    send(&mut svm, drain_ix, &admin).expect("EmergencyDrain should succeed");
+    assert!(svm.get_account(&vault_pda).is_none());
+    assert_eq!(svm.get_balance(&recovery), Some(LAMPORTS_PER_SOL));
+}
+
+#[test]
+fn test_admin_recovers_a_vault_only_after_the_owner_goes_inactive() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let admin = Keypair::new();
+    set_upgrade_authority(&mut svm, &admin.pubkey());
+    let owner = Keypair::new();
+    let recovery = Pubkey::new_unique();
+    for signer in [&admin, &owner] {
+        svm.airdrop(&signer.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop");
+    }
+
+    // `[referral_bps][price_oracle][max_vaults_per_owner][max_deposit_per_tx][rent_subsidy]
+    // [dust_threshold][hook_program][reuse_cooldown_secs][recovery_address][min_withdraw]
+    // [fee_rounding][recovery_inactivity_secs]`
+    let inactivity_secs: u64 = 365 * 86_400;
+    let mut initialize_ix = create_initialize_instruction(admin.pubkey(), 0);
+    initialize_ix.data.extend_from_slice(&[0u8; 32]);
+    initialize_ix.data.extend_from_slice(&0u64.to_le_bytes());
+    initialize_ix.data.extend_from_slice(&0u64.to_le_bytes());
+    initialize_ix.data.extend_from_slice(&[0u8; 32]);
+    initialize_ix.data.extend_from_slice(&0u64.to_le_bytes());
+    initialize_ix.data.extend_from_slice(&[0u8; 32]);
+    initialize_ix.data.extend_from_slice(&0u64.to_le_bytes());
+    initialize_ix.data.extend_from_slice(recovery.as_ref());
+    initialize_ix.data.extend_from_slice(&0u64.to_le_bytes());
+    initialize_ix.data.push(0);
+    initialize_ix
+        .data
+        .extend_from_slice(&inactivity_secs.to_le_bytes());
+    send(&mut svm, initialize_ix, &admin).expect("Initialize should succeed");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    send_all(
+        &mut svm,
+        &[
+            create_open_vault_instruction(owner.pubkey(), vault_pda),
+            create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL),
+        ],
+        &[&owner],
+    )
+    .expect("OpenVault and deposit should succeed");
+
+    let recover_ix =
+        create_admin_recover_instruction(admin.pubkey(), owner.pubkey(), vault_pda, recovery);
+
+    // The window runs from the owner's deposit, so a sweep a second short fails
+    let mut clock = svm.get_sysvar::<Clock>();
+    clock.unix_timestamp += inactivity_secs as i64 - 1;
+    svm.set_sysvar::<Clock>(&clock);
+    assert_eq!(
+        send(&mut svm, recover_ix.clone(), &admin),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::OwnerStillActive as u32)
+        ))
+    );
+
+    // Once it's up, only the admin can sweep
+    clock.unix_timestamp += 1;
+    svm.set_sysvar::<Clock>(&clock);
+    let other = Keypair::new();
+    svm.airdrop(&other.pubkey(), LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+    assert_eq!(
+        send(
+            &mut svm,
+            create_admin_recover_instruction(other.pubkey(), owner.pubkey(), vault_pda, recovery),
+            &other,
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::NotAdmin as u32)
+        ))
+    );
+
+    let rent_floor = svm.minimum_balance_for_rent_exemption(VaultState::LEN);
+    send(&mut svm, recover_ix, &admin).expect("AdminRecover should succeed");
+    assert!(svm.get_account(&vault_pda).is_none());
+    assert_eq!(
+        svm.get_balance(&recovery),
+        Some(LAMPORTS_PER_SOL + rent_floor)
+    );
+}
+
+#[test]
+fn test_withdraw_amount_below_config_minimum_fails() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    set_upgrade_authority(&mut svm, &owner.pubkey());
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    // `[referral_bps][price_oracle][max_vaults_per_owner][max_deposit_per_tx][rent_subsidy]
+    // [dust_threshold][hook_program][reuse_cooldown_secs][recovery_address][min_withdraw]`
+    let min_withdraw = LAMPORTS_PER_SOL / 10;
+    let mut initialize_ix = create_initialize_instruction(owner.pubkey(), 0);
+    initialize_ix.data.extend_from_slice(&[0u8; 32]);
+    initialize_ix.data.extend_from_slice(&0u64.to_le_bytes());
+    initialize_ix.data.extend_from_slice(&0u64.to_le_bytes());
+    initialize_ix.data.extend_from_slice(&[0u8; 32]);
+    initialize_ix.data.extend_from_slice(&0u64.to_le_bytes());
+    initialize_ix.data.extend_from_slice(&[0u8; 32]);
+    initialize_ix.data.extend_from_slice(&0u64.to_le_bytes());
+    initialize_ix.data.extend_from_slice(&[0u8; 32]);
+    initialize_ix
+        .data
+        .extend_from_slice(&min_withdraw.to_le_bytes());
+    send(&mut svm, initialize_ix, &owner).expect("Initialize should succeed");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    send(
+        &mut svm,
+        create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL),
+        &owner,
+    )
+    .expect("Deposit should succeed");
+
+    assert_eq!(
+        send(
+            &mut svm,
+            create_withdraw_amount_instruction(owner.pubkey(), vault_pda, min_withdraw - 1, 0),
+            &owner
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::WithdrawTooSmall as u32)
+        ))
+    );
+    assert_eq!(svm.get_balance(&vault_pda), Some(LAMPORTS_PER_SOL));
+
+    send(
+        &mut svm,
+        create_withdraw_amount_instruction(owner.pubkey(), vault_pda, min_withdraw, 0),
+        &owner,
+    )
+    .expect("Withdrawing the minimum should succeed");
+    assert_eq!(
+        svm.get_balance(&vault_pda),
+        Some(LAMPORTS_PER_SOL - min_withdraw)
+    );
+}
+
+
+#[test]
+fn test_proposed_admin_takes_over_once_accepted() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let admin = Keypair::new();
+    set_upgrade_authority(&mut svm, &admin.pubkey());
+    let new_admin = Keypair::new();
+    for signer in [&admin, &new_admin] {
+        svm.airdrop(&signer.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop");
+    }
+
+    send(
+        &mut svm,
+        create_initialize_instruction(admin.pubkey(), 0),
+        &admin,
+    )
+    .expect("Initialize should succeed");
+
+    send(
+        &mut svm,
+        create_propose_admin_instruction(admin.pubkey(), new_admin.pubkey()),
+        &admin,
+    )
+    .expect("ProposeAdmin should succeed");
+
+    // Proposing alone hands nothing over
+    assert_eq!(
+        send(
+            &mut svm,
+            create_set_paused_instruction(new_admin.pubkey(), true),
+            &new_admin
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::NotAdmin as u32)
+        ))
+    );
+
+    send(
+        &mut svm,
+        create_accept_admin_instruction(new_admin.pubkey()),
+        &new_admin,
+    )
+    .expect("AcceptAdmin should succeed");
+
+    send(
+        &mut svm,
+        create_set_paused_instruction(new_admin.pubkey(), true),
+        &new_admin,
+    )
+    .expect("The new admin should be able to pause");
+    assert_eq!(
+        send(
+            &mut svm,
+            create_set_paused_instruction(admin.pubkey(), false),
+            &admin
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::NotAdmin as u32)
+        ))
+    );
+
+    // The proposal is spent once accepted
+    assert_eq!(
+        send(
+            &mut svm,
+            create_accept_admin_instruction(new_admin.pubkey()),
+            &new_admin
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::NotPendingAdmin as u32)
+        ))
+    );
+}
+
+#[test]
+fn test_cancelled_admin_proposal_cannot_be_accepted() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let admin = Keypair::new();
+    set_upgrade_authority(&mut svm, &admin.pubkey());
+    let new_admin = Keypair::new();
+    for signer in [&admin, &new_admin] {
+        svm.airdrop(&signer.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop");
+    }
+
+    send(
+        &mut svm,
+        create_initialize_instruction(admin.pubkey(), 0),
+        &admin,
+    )
+    .expect("Initialize should succeed");
+
+    send(
+        &mut svm,
+        create_propose_admin_instruction(admin.pubkey(), new_admin.pubkey()),
+        &admin,
+    )
+    .expect("ProposeAdmin should succeed");
+    send(
+        &mut svm,
+        create_propose_admin_instruction(admin.pubkey(), Pubkey::default()),
+        &admin,
+    )
+    .expect("Cancelling the proposal should succeed");
+
+    assert_eq!(
+        send(
+            &mut svm,
+            create_accept_admin_instruction(new_admin.pubkey()),
+            &new_admin
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::NotPendingAdmin as u32)
+        ))
+    );
+    send(
+        &mut svm,
+        create_set_paused_instruction(admin.pubkey(), true),
+        &admin,
+    )
+    .expect("The original admin should keep the config");
+}
+
+#[test]
+fn test_accept_admin_fails_for_anyone_but_the_proposed_admin() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let admin = Keypair::new();
+    set_upgrade_authority(&mut svm, &admin.pubkey());
+    let new_admin = Keypair::new();
+    for signer in [&admin, &new_admin] {
+        svm.airdrop(&signer.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop");
+    }
+
+    send(
+        &mut svm,
+        create_initialize_instruction(admin.pubkey(), 0),
+        &admin,
+    )
+    .expect("Initialize should succeed");
+
+    let stranger = Keypair::new();
+    svm.airdrop(&stranger.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    // Only the admin can propose, so a stranger can't nominate themselves
+    assert_eq!(
+        send(
+            &mut svm,
+            create_propose_admin_instruction(stranger.pubkey(), stranger.pubkey()),
+            &stranger,
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::NotAdmin as u32)
+        ))
+    );
+
+    send(
+        &mut svm,
+        create_propose_admin_instruction(admin.pubkey(), new_admin.pubkey()),
+        &admin,
+    )
+    .expect("ProposeAdmin should succeed");
+
+    assert_eq!(
+        send(
+            &mut svm,
+            create_accept_admin_instruction(stranger.pubkey()),
+            &stranger
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::NotPendingAdmin as u32)
+        ))
+    );
+
+    // Naming the proposed admin without its signature doesn't accept either
+    let mut accept_ix = create_accept_admin_instruction(new_admin.pubkey());
+    accept_ix.accounts[0].is_signer = false;
+    assert_eq!(
+        send(&mut svm, accept_ix, &stranger),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::MissingRequiredSignature
+        ))
+    );
+
+    send(
+        &mut svm,
+        create_accept_admin_instruction(new_admin.pubkey()),
+        &new_admin,
+    )
+    .expect("The proposed admin should still be able to accept");
+}
+
+#[test]
+fn test_withdraw_to_new_account_creates_and_funds_a_fresh_wallet() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let deposit_amount = LAMPORTS_PER_SOL;
+    svm.expire_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[create_deposit_instruction(
+            owner.pubkey(),
+            vault_pda,
+            deposit_amount,
+        )],
+        Some(&owner.pubkey()),
+        &[&owner],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx).expect("Deposit should succeed");
+
+    // Without the new keypair's signature the account isn't created
+    let wallet = Keypair::new();
+    let mut unsigned_ix =
+        create_withdraw_to_new_account_instruction(owner.pubkey(), vault_pda, wallet.pubkey());
+    unsigned_ix.accounts[4].is_signer = false;
+    svm.expire_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[unsigned_ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        svm.latest_blockhash(),
+    );
+    assert_eq!(
+        svm.send_transaction(tx).map(|_| ()).map_err(|f| f.err),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::MissingRequiredSignature
+        ))
+    );
+    assert!(svm.get_account(&wallet.pubkey()).is_none());
+
+    svm.expire_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[create_withdraw_to_new_account_instruction(
+            owner.pubkey(),
+            vault_pda,
+            wallet.pubkey(),
+        )],
+        Some(&owner.pubkey()),
+        &[&owner, &wallet],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .expect("WithdrawToNewAccount should succeed");
+
+    let wallet_account = svm
+        .get_account(&wallet.pubkey())
+        .expect("The wallet should exist");
+    // Without `close` the system-owned vault keeps its rent-exempt reserve
+    let vault_after = svm.get_balance(&vault_pda).unwrap();
+    assert_eq!(wallet_account.lamports, deposit_amount - vault_after);
+    assert_eq!(wallet_account.owner, SYSTEM_PROGRAM_ID);
+    assert!(wallet_account.data.is_empty());
+}
+
+#[test]
+fn test_deposit_as_wrapped_credits_the_vault_wsol_account() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let vault_wsol = find_vault_wsol_ata(&vault_pda);
+
+    let deposit = |svm: &mut LiteSVM, amount: u64| {
+        svm.expire_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[create_deposit_as_wrapped_instruction(
+                owner.pubkey(),
+                vault_pda,
+                amount,
+            )],
+            Some(&owner.pubkey()),
+            &[&owner],
+            svm.latest_blockhash(),
+        );
+        svm.send_transaction(tx)
+            .expect("DepositAsWrapped should succeed")
+            .return_data
+            .data
+    };
+
+    // The first deposit creates the vault's wSOL account, later ones top it up
+    let return_data = deposit(&mut svm, LAMPORTS_PER_SOL);
+    assert_eq!(return_data, LAMPORTS_PER_SOL.to_le_bytes().to_vec());
+    let return_data = deposit(&mut svm, LAMPORTS_PER_SOL / 2);
+    let wrapped = LAMPORTS_PER_SOL + LAMPORTS_PER_SOL / 2;
+    assert_eq!(return_data, wrapped.to_le_bytes().to_vec());
+
+    // `[mint][owner][amount]..`
+    let wsol_account = svm
+        .get_account(&vault_wsol)
+        .expect("The vault's wSOL account should exist");
+    assert_eq!(wsol_account.owner, TOKEN_PROGRAM_ID);
+    assert_eq!(&wsol_account.data[..32], NATIVE_MINT.as_ref());
+    assert_eq!(&wsol_account.data[32..64], vault_pda.as_ref());
+    assert_eq!(token_balance(&svm, &vault_wsol), wrapped);
+
+    // The vault itself holds no bare lamports
+    assert!(svm.get_account(&vault_pda).is_none());
+}
+
+#[test]
+fn test_deposit_as_wrapped_into_an_opened_vault_is_tracked_and_respects_a_freeze() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let vault_wsol = find_vault_wsol_ata(&vault_pda);
+    send(
+        &mut svm,
+        create_open_vault_instruction(owner.pubkey(), vault_pda),
+        &owner,
+    )
+    .expect("OpenVault should succeed");
+    send(
+        &mut svm,
+        create_deposit_as_wrapped_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL),
+        &owner,
+    )
+    .expect("DepositAsWrapped should succeed");
+
+    // The wrapped SOL is tracked like a DepositToken of the native mint
+    let vault_account = svm.get_account(&vault_pda).expect("Vault should exist");
+    let state = VaultState::from_bytes(&vault_account.data).expect("Vault should hold state");
+    assert_eq!(
+        state.token_balance(&NATIVE_MINT.to_bytes()),
+        LAMPORTS_PER_SOL
+    );
+
+    // So SweepToken leaves it behind
+    let destination = Pubkey::new_unique();
+    set_wrapped_sol_account(&mut svm, destination, &owner.pubkey(), 0);
+    assert_eq!(
+        send(
+            &mut svm,
+            create_sweep_token_instruction(owner.pubkey(), vault_pda, vault_wsol, destination),
+            &owner,
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::VaultEmpty as u32)
+        ))
+    );
+    assert_eq!(token_balance(&svm, &vault_wsol), LAMPORTS_PER_SOL);
+
+    // A frozen vault takes no wrapped deposits either
+    send(
+        &mut svm,
+        create_set_frozen_instruction(owner.pubkey(), vault_pda, true),
+        &owner,
+    )
+    .expect("Freezing should succeed");
+    assert_eq!(
+        send(
+            &mut svm,
+            create_deposit_as_wrapped_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL),
+            &owner,
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::VaultFrozen as u32)
+        ))
+    );
+    assert_eq!(token_balance(&svm, &vault_wsol), LAMPORTS_PER_SOL);
+}
+
+#[test]
+fn test_get_info_many_reports_every_vault_in_simulation() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let explorer = Keypair::new();
+    svm.airdrop(&explorer.pubkey(), LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let owners = [Keypair::new(), Keypair::new(), Keypair::new()];
+    let mut vaults = Vec::new();
+    for (index, owner) in owners.iter().enumerate() {
+        svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop");
+
+        let (vault_pda, bump) = find_vault_pda(&owner.pubkey());
+        let amount = (index as u64 + 1) * LAMPORTS_PER_SOL;
+        let tx = Transaction::new_signed_with_payer(
+            &[
+                create_open_vault_instruction(owner.pubkey(), vault_pda),
+                create_deposit_instruction(owner.pubkey(), vault_pda, amount),
+            ],
+            Some(&owner.pubkey()),
+            &[owner],
+            svm.latest_blockhash(),
+        );
+        svm.send_transaction(tx).expect("Failed to set up vault");
+        vaults.push((vault_pda, bump, amount));
+    }
+
+    // A vault that was never created is reported as an empty entry
+    let missing = find_vault_pda(&Pubkey::new_unique()).0;
+    let mut addresses: Vec<Pubkey> = vaults.iter().map(|(vault, _, _)| *vault).collect();
+    addresses.insert(1, missing);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[create_get_info_many_instruction(&addresses)],
+        Some(&explorer.pubkey()),
+        &[&explorer],
+        svm.latest_blockhash(),
+    );
+    let simulated = svm
+        .simulate_transaction(tx)
+        .expect("GetInfoMany should succeed");
+
+    // `[len][balance][owner][bump][unlock_ts][flags]` per vault
+    let mut data = simulated.meta.return_data.data.as_slice();
+    let mut entries = Vec::new();
+    while let Some((&len, rest)) = data.split_first() {
+        let (info, rest) = rest.split_at(len as usize);
+        entries.push(info.to_vec());
+        data = rest;
+    }
+
+    assert_eq!(entries.len(), 4);
+    assert!(entries.remove(1).is_empty());
+    for ((info, (_, bump, amount)), owner) in entries.iter().zip(&vaults).zip(&owners) {
+        assert_eq!(info.len(), 50);
+        assert_eq!(u64::from_le_bytes(info[..8].try_into().unwrap()), *amount);
+        assert_eq!(&info[8..40], owner.pubkey().as_ref());
+        assert_eq!(info[40], *bump);
+        assert_eq!(i64::from_le_bytes(info[41..49].try_into().unwrap()), 0);
+        assert_eq!(info[49], 0);
+    }
+}
+
+#[test]
+fn test_crank_fails_before_a_drip_interval_elapsed() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    let cranker = Keypair::new();
+    for signer in [&owner, &cranker] {
+        svm.airdrop(&signer.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop");
+    }
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let recipient = Pubkey::new_unique();
+    let interval_secs = 60;
+    let drip = LAMPORTS_PER_SOL / 10;
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_open_vault_instruction(owner.pubkey(), vault_pda),
+            create_deposit_instruction(owner.pubkey(), vault_pda, 2 * LAMPORTS_PER_SOL),
+            create_set_drip_instruction(owner.pubkey(), vault_pda, interval_secs, drip, recipient),
+        ],
+        Some(&owner.pubkey()),
+        &[&owner],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .expect("Open, deposit and SetDrip should succeed");
+
+    let mut clock = svm.get_sysvar::<Clock>();
+    clock.unix_timestamp += interval_secs as i64 - 1;
+    svm.set_sysvar::<Clock>(&clock);
+
+    let vault_before = svm.get_balance(&vault_pda).unwrap();
+    assert_eq!(
+        send(
+            &mut svm,
+            create_crank_instruction(cranker.pubkey(), vault_pda, recipient),
+            &cranker
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::DripNotDue as u32)
+        ))
+    );
+    assert_eq!(svm.get_balance(&vault_pda).unwrap(), vault_before);
+    assert!(svm.get_account(&recipient).is_none());
+
+    // Clearing the schedule leaves nothing to crank, however long anyone waits
+    send(
+        &mut svm,
+        create_set_drip_instruction(owner.pubkey(), vault_pda, 0, 0, Pubkey::default()),
+        &owner,
+    )
+    .expect("Clearing the drip should succeed");
+    clock.unix_timestamp += 10 * interval_secs as i64;
+    svm.set_sysvar::<Clock>(&clock);
+    assert_eq!(
+        send(
+            &mut svm,
+            create_crank_instruction(cranker.pubkey(), vault_pda, recipient),
+            &cranker,
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::DripNotDue as u32)
+        ))
+    );
+}
+
+#[test]
+fn test_crank_releases_the_due_drip_and_tips_the_cranker() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    let cranker = Keypair::new();
+    for signer in [&owner, &cranker] {
+        svm.airdrop(&signer.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop");
+    }
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let recipient = Pubkey::new_unique();
+    let interval_secs = 60;
+    let drip = LAMPORTS_PER_SOL / 10;
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_open_vault_instruction(owner.pubkey(), vault_pda),
+            create_deposit_instruction(owner.pubkey(), vault_pda, 2 * LAMPORTS_PER_SOL),
+            create_set_drip_instruction(owner.pubkey(), vault_pda, interval_secs, drip, recipient),
+        ],
+        Some(&owner.pubkey()),
+        &[&owner],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .expect("Open, deposit and SetDrip should succeed");
+
+    // Two whole intervals elapsed, so two drips are due
+    let mut clock = svm.get_sysvar::<Clock>();
+    clock.unix_timestamp += 2 * interval_secs as i64 + 1;
+    svm.set_sysvar::<Clock>(&clock);
+
+    let vault_before = svm.get_balance(&vault_pda).unwrap();
+    let cranker_before = svm.get_balance(&cranker.pubkey()).unwrap();
+    send(
+        &mut svm,
+        create_crank_instruction(cranker.pubkey(), vault_pda, recipient),
+        &cranker,
+    )
+    .expect("Crank should succeed");
+
+    // The tip covers the cranker's signature fee
+    let tip = 5_000;
+    assert_eq!(
+        svm.get_balance(&vault_pda).unwrap(),
+        vault_before - 2 * drip
+    );
+    assert_eq!(svm.get_balance(&recipient), Some(2 * drip - tip));
+    assert_eq!(
+        svm.get_balance(&cranker.pubkey()),
+        Some(cranker_before + tip - 5_000)
+    );
+
+    let vault_account = svm.get_account(&vault_pda).unwrap();
+    let state = VaultState::from_bytes(&vault_account.data).expect("State should be readable");
+    assert_eq!(state.balance(), 2 * LAMPORTS_PER_SOL - 2 * drip);
+    assert_eq!(state.drip_last_release(), clock.unix_timestamp - 1);
+
+    let fetcher = |address: &Pubkey| svm.get_account(address);
+    let stats = client::global_stats(&fetcher).expect("Stats should exist");
+    assert_eq!(stats.lifetime_withdrawals(), 2 * drip);
+
+    // The released intervals are spent, so cranking again right away fails
+    assert_eq!(
+        send(
+            &mut svm,
+            create_crank_instruction(cranker.pubkey(), vault_pda, recipient),
+            &cranker
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::DripNotDue as u32)
+        ))
+    );
+}
+
+#[test]
+fn test_withdraw_fails_cleanly_when_owner_account_is_read_only() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let payer = Keypair::new();
+    let owner = Keypair::new();
+    svm.airdrop(&payer.pubkey(), LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let tx = Transaction::new_signed_with_payer(
+        &[create_deposit_instruction(
+            owner.pubkey(),
+            vault_pda,
+            LAMPORTS_PER_SOL,
+        )],
+        Some(&owner.pubkey()),
+        &[&owner],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx).expect("Failed to deposit");
+
+    // The owner still signs, but someone else pays so it can stay read-only
+    let mut instruction = create_withdraw_instruction(owner.pubkey(), vault_pda);
+    instruction.accounts[0] = AccountMeta::new_readonly(owner.pubkey(), true);
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &owner],
+        svm.latest_blockhash(),
+    );
+    assert_eq!(
+        svm.send_transaction(tx).map(|_| ()).map_err(|f| f.err),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::OwnerNotWritable as u32)
+        ))
+    );
+    assert_eq!(svm.get_balance(&vault_pda), Some(LAMPORTS_PER_SOL));
+}
+
+#[test]
+fn test_vaults_sharing_a_tag_are_read_back_from_its_index() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let vaults: Vec<Pubkey> = (0..3)
+        .map(|id| find_vault_pda_with_id(&owner.pubkey(), id).0)
+        .collect();
+    for (id, vault) in vaults.iter().enumerate() {
+        send(
+            &mut svm,
+            create_open_vault_instruction_with_id(owner.pubkey(), *vault, id as u64),
+            &owner,
+        )
+        .expect("OpenVault should succeed");
+    }
+
+    // Two vaults share tag 7, and tagging one of them again doesn't list it twice
+    for vault in [vaults[0], vaults[2], vaults[0]] {
+        send(
+            &mut svm,
+            create_tag_vault_instruction(owner.pubkey(), vault, 7),
+            &owner,
+        )
+        .expect("TagVault should succeed");
+    }
+    send(
+        &mut svm,
+        create_tag_vault_instruction(owner.pubkey(), vaults[1], 8),
+        &owner,
+    )
+    .expect("TagVault should succeed");
+
+    let fetcher = |address: &Pubkey| svm.get_account(address);
+    assert_eq!(
+        client::vaults_by_tag(&fetcher, &owner.pubkey(), 7),
+        Some(vec![vaults[0], vaults[2]])
+    );
+    assert_eq!(
+        client::vaults_by_tag(&fetcher, &owner.pubkey(), 8),
+        Some(vec![vaults[1]])
+    );
+    assert_eq!(client::vaults_by_tag(&fetcher, &owner.pubkey(), 9), None);
+
+    // The index is kept rent-exempt as it grows
+    let index = svm
+        .get_account(&find_tag_index_pda(&owner.pubkey(), 7).0)
+        .expect("Tag index should exist");
+    assert!(index.lamports >= svm.minimum_balance_for_rent_exemption(index.data.len()));
+}
+
+#[test]
+fn test_upgraded_multisig_vault_keeps_its_balance_and_needs_the_threshold() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    let signers = [Keypair::new(), Keypair::new(), Keypair::new()];
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+    for signer in &signers {
+        svm.airdrop(&signer.pubkey(), LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop");
+    }
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    send_all(
+        &mut svm,
+        &[create_open_vault_instruction(owner.pubkey(), vault_pda)],
+        &[&owner],
+    )
+    .expect("OpenVault should succeed");
+    let amount = LAMPORTS_PER_SOL;
+    send_all(
+        &mut svm,
+        &[create_deposit_instruction(
+            owner.pubkey(),
+            vault_pda,
+            amount,
+        )],
+        &[&owner],
+    )
+    .expect("Deposit should succeed");
+
+    let signer_keys: Vec<Pubkey> = signers.iter().map(|signer| signer.pubkey()).collect();
+
+    // The threshold can't ask for more signatures than there are signers
+    assert_eq!(
+        send_all(
+            &mut svm,
+            &[create_upgrade_to_multisig_instruction(
+                owner.pubkey(),
+                vault_pda,
+                4,
+                &signer_keys
+            )],
+            &[&owner]
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::InvalidMultisigThreshold as u32)
+        ))
+    );
+
+    let vault_before = svm.get_account(&vault_pda).expect("Vault should exist");
+    send_all(
+        &mut svm,
+        &[create_upgrade_to_multisig_instruction(
+            owner.pubkey(),
+            vault_pda,
+            2,
+            &signer_keys,
+        )],
+        &[&owner],
+    )
+    .expect("UpgradeToMultisig should succeed");
+
+    // The vault is upgraded in place, funds untouched
+    let vault_after = svm.get_account(&vault_pda).expect("Vault should exist");
+    assert_eq!(vault_after.lamports, vault_before.lamports);
+    let state = VaultState::from_bytes(&vault_after.data).expect("Vault should hold state");
+    assert_eq!(state.balance(), amount);
+    assert_eq!(state.multisig_threshold(), 2);
+    let stored: Vec<Pubkey> = state
+        .multisig_signers()
+        .iter()
+        .map(|signer| Pubkey::new_from_array(*signer))
+        .collect();
+    assert_eq!(stored, signer_keys);
+
+    // The owner alone can no longer withdraw, nor can a single signer
+    assert_eq!(
+        send_all(
+            &mut svm,
+            &[create_withdraw_instruction(owner.pubkey(), vault_pda)],
+            &[&owner]
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::MultisigThresholdNotMet as u32)
+        ))
+    );
+    assert_eq!(
+        send_all(
+            &mut svm,
+            &[create_multisig_withdraw_instruction(
+                owner.pubkey(),
+                vault_pda,
+                &[
+                    (signer_keys[0], true),
+                    (signer_keys[1], false),
+                    (signer_keys[2], false),
+                ],
+            )],
+            &[&signers[0]]
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::MultisigThresholdNotMet as u32)
+        ))
+    );
+
+    // Two of the three signers release the vault to the owner
+    let owner_before = svm.get_balance(&owner.pubkey()).unwrap();
+    send_all(
+        &mut svm,
+        &[create_multisig_withdraw_instruction(
+            owner.pubkey(),
+            vault_pda,
+            &[
+                (signer_keys[0], true),
+                (signer_keys[1], false),
+                (signer_keys[2], true),
+            ],
+        )],
+        &[&signers[0], &signers[2]],
+    )
+    .expect("Multisig withdraw should succeed");
+    assert!(svm.get_balance(&owner.pubkey()).unwrap() >= owner_before + amount);
+}
+
+#[test]
+fn test_create_multisig_opens_a_vault_that_needs_the_threshold() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    let signers = [Keypair::new(), Keypair::new(), Keypair::new()];
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+    for signer in &signers {
+        svm.airdrop(&signer.pubkey(), LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop");
+    }
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let signer_keys: Vec<Pubkey> = signers.iter().map(|signer| signer.pubkey()).collect();
+
+    // A bad threshold fails the whole instruction, leaving no vault behind
+    assert_eq!(
+        send_all(
+            &mut svm,
+            &[create_create_multisig_instruction(
+                owner.pubkey(),
+                vault_pda,
+                0,
+                &signer_keys
+            )],
+            &[&owner]
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::InvalidMultisigThreshold as u32)
+        ))
+    );
+    assert!(svm.get_account(&vault_pda).is_none());
+
+    send_all(
+        &mut svm,
+        &[create_create_multisig_instruction(
+            owner.pubkey(),
+            vault_pda,
+            2,
+            &signer_keys,
+        )],
+        &[&owner],
+    )
+    .expect("CreateMultisig should succeed");
+
+    let vault_account = svm.get_account(&vault_pda).expect("Vault should exist");
+    let state = VaultState::from_bytes(&vault_account.data).expect("Vault should hold state");
+    assert_eq!(state.owner(), &owner.pubkey().to_bytes());
+    assert_eq!(state.multisig_threshold(), 2);
+    let stored: Vec<Pubkey> = state
+        .multisig_signers()
+        .iter()
+        .map(|signer| Pubkey::new_from_array(*signer))
+        .collect();
+    assert_eq!(stored, signer_keys);
+
+    // Deposits work as usual
+    let amount = LAMPORTS_PER_SOL;
+    send_all(
+        &mut svm,
+        &[create_deposit_instruction(
+            owner.pubkey(),
+            vault_pda,
+            amount,
+        )],
+        &[&owner],
+    )
+    .expect("Deposit should succeed");
+
+    // The owner never holds sole control, and one signer isn't enough
+    assert_eq!(
+        send_all(
+            &mut svm,
+            &[create_withdraw_instruction(owner.pubkey(), vault_pda)],
+            &[&owner]
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::MultisigThresholdNotMet as u32)
+        ))
+    );
+    assert_eq!(
+        send_all(
+            &mut svm,
+            &[create_multisig_withdraw_instruction(
+                owner.pubkey(),
+                vault_pda,
+                &[
+                    (signer_keys[0], false),
+                    (signer_keys[1], true),
+                    (signer_keys[2], false),
+                ],
+            )],
+            &[&signers[1]]
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::MultisigThresholdNotMet as u32)
+        ))
+    );
+
+    // Two of the three signers release the vault to the owner
+    let owner_before = svm.get_balance(&owner.pubkey()).unwrap();
+    send_all(
+        &mut svm,
+        &[create_multisig_withdraw_instruction(
+            owner.pubkey(),
+            vault_pda,
+            &[
+                (signer_keys[0], false),
+                (signer_keys[1], true),
+                (signer_keys[2], true),
+            ],
+        )],
+        &[&signers[1], &signers[2]],
+    )
+    .expect("Multisig withdraw should succeed");
+    assert!(svm.get_balance(&owner.pubkey()).unwrap() >= owner_before + amount);
+}
+
+#[test]
+fn test_can_withdraw_reports_each_blocking_condition() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
+
+    let reason = |svm: &mut LiteSVM, vault: Pubkey| {
+        svm.expire_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[create_can_withdraw_instruction(vault)],
+            Some(&owner.pubkey()),
+            &[&owner],
+            svm.latest_blockhash(),
+        );
+        let simulated = svm
+            .simulate_transaction(tx)
+            .expect("CanWithdraw should succeed");
+        u64::from_le_bytes(simulated.meta.return_data.data.try_into().unwrap())
+    };
+
+    // A vault that holds nothing has nothing to release
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    assert_eq!(reason(&mut svm, vault_pda), VaultError::VaultEmpty as u64);
+
+    let min_age_secs = 3_600u64;
+    let flags = VaultState::FLAG_MIN_AGE;
+    let mut open_ix = create_open_vault_instruction_with_flags(owner.pubkey(), vault_pda, flags);
+    open_ix.data.extend_from_slice(&min_age_secs.to_le_bytes());
+    send(&mut svm, open_ix, &owner).expect("OpenVault with a minimum age should succeed");
+    assert_eq!(reason(&mut svm, vault_pda), VaultError::VaultEmpty as u64);
+
+    send(
+        &mut svm,
+        create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL),
+        &owner,
+    )
+    .expect("Deposit should succeed");
+    assert_eq!(
+        reason(&mut svm, vault_pda),
+        VaultError::VaultTooYoung as u64
+    );
+
+    let mut clock = svm.get_sysvar::<Clock>();
+    clock.unix_timestamp += min_age_secs as i64;
+    svm.set_sysvar::<Clock>(&clock);
+    assert_eq!(reason(&mut svm, vault_pda), 0);
+
+    send(
+        &mut svm,
+        create_extend_lock_instruction(owner.pubkey(), vault_pda, clock.unix_timestamp + 60),
+        &owner,
+    )
+    .expect("ExtendLock should succeed");
+    assert_eq!(reason(&mut svm, vault_pda), VaultError::VaultLocked as u64);
+
+    send(
+        &mut svm,
+        create_set_frozen_instruction(owner.pubkey(), vault_pda, true),
+        &owner,
+    )
+    .expect("SetFrozen should succeed");
+    assert_eq!(reason(&mut svm, vault_pda), VaultError::VaultFrozen as u64);
+
+    // An actual withdraw fails with the reported reason
+    assert_eq!(
+        send(
+            &mut svm,
+            create_withdraw_instruction(owner.pubkey(), vault_pda),
+            &owner
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::VaultFrozen as u32)
+        ))
+    );
+}
+
+#[test]
+fn test_withdraw_expired_is_permissionless_only_after_expiry_and_credits_the_owner() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    let caller = Keypair::new();
+    for signer in [&owner, &caller] {
+        svm.airdrop(&signer.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop");
+    }
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_open_vault_instruction(owner.pubkey(), vault_pda),
+            create_deposit_instruction(owner.pubkey(), vault_pda, 2 * LAMPORTS_PER_SOL),
+        ],
+        Some(&owner.pubkey()),
+        &[&owner],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .expect("OpenVault and deposit should succeed");
+
+    let not_expired = Err(TransactionError::InstructionError(
+        0,
+        InstructionError::Custom(VaultError::VaultNotExpired as u32),
+    ));
+    let withdraw_expired =
+        || create_withdraw_expired_instruction(caller.pubkey(), owner.pubkey(), vault_pda);
+
+    // Without an expiry, nobody but the owner can withdraw
+    assert_eq!(send(&mut svm, withdraw_expired(), &caller), not_expired);
+
+    let mut clock = svm.get_sysvar::<Clock>();
+    let expiry_ts = clock.unix_timestamp + 3_600;
+    send(
+        &mut svm,
+        create_set_expiry_instruction(owner.pubkey(), vault_pda, expiry_ts),
+        &owner,
+    )
+    .expect("SetExpiry should succeed");
+
+    clock.unix_timestamp = expiry_ts - 1;
+    svm.set_sysvar::<Clock>(&clock);
+    let vault_before = svm.get_balance(&vault_pda).unwrap();
+    assert_eq!(send(&mut svm, withdraw_expired(), &caller), not_expired);
+    assert_eq!(svm.get_balance(&vault_pda).unwrap(), vault_before);
+
+    // Once expired, a stranger's call sends everything to the owner, never to itself
+    clock.unix_timestamp = expiry_ts;
+    svm.set_sysvar::<Clock>(&clock);
+    let owner_before = svm.get_balance(&owner.pubkey()).unwrap();
+    let caller_before = svm.get_balance(&caller.pubkey()).unwrap();
+    send(&mut svm, withdraw_expired(), &caller)
+        .expect("WithdrawExpired should succeed after expiry");
+
+    assert_eq!(
+        svm.get_balance(&owner.pubkey()).unwrap(),
+        owner_before + vault_before
+    );
+    assert!(svm.get_balance(&caller.pubkey()).unwrap() < caller_before);
+    assert_eq!(svm.get_balance(&vault_pda).unwrap_or(0), 0);
+}
+
+#[test]
+fn test_beneficiary_claims_only_after_the_owner_goes_inactive() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    let beneficiary = Keypair::new();
+    let stranger = Keypair::new();
+    for signer in [&owner, &beneficiary, &stranger] {
+        svm.airdrop(&signer.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop");
+    }
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let inactivity_secs = 30 * 24 * 60 * 60;
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_open_vault_instruction(owner.pubkey(), vault_pda),
+            create_deposit_instruction(owner.pubkey(), vault_pda, 2 * LAMPORTS_PER_SOL),
+            create_set_beneficiary_instruction(
+                owner.pubkey(),
+                vault_pda,
+                beneficiary.pubkey(),
+                inactivity_secs,
+            ),
+        ],
+        Some(&owner.pubkey()),
+        &[&owner],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+        .expect("OpenVault, deposit and SetBeneficiary should succeed");
+
+    let still_active = Err(TransactionError::InstructionError(
+        0,
+        InstructionError::Custom(VaultError::OwnerStillActive as u32),
+    ));
+    let claim = |signer: &Keypair| {
+        create_beneficiary_claim_instruction(signer.pubkey(), owner.pubkey(), vault_pda)
+    };
+
+    // Nobody can claim while the owner is active
+    assert_eq!(
+        send(&mut svm, claim(&beneficiary), &beneficiary),
+        still_active
+    );
+
+    // A deposit just before the window closes restarts it
+    let mut clock = svm.get_sysvar::<Clock>();
+    let deadline = clock.unix_timestamp + inactivity_secs as i64;
+    clock.unix_timestamp = deadline - 1;
+    svm.set_sysvar::<Clock>(&clock);
+    send(
+        &mut svm,
+        create_deposit_instruction(owner.pubkey(), vault_pda, LAMPORTS_PER_SOL),
+        &owner,
+    )
+    .expect("Deposit should succeed");
+
+    clock.unix_timestamp = deadline;
+    svm.set_sysvar::<Clock>(&clock);
+    assert_eq!(
+        send(&mut svm, claim(&beneficiary), &beneficiary),
+        still_active
+    );
+
+    // Once the owner has been inactive long enough, only the beneficiary can claim
+    clock.unix_timestamp = deadline - 1 + inactivity_secs as i64;
+    svm.set_sysvar::<Clock>(&clock);
+    assert_eq!(send(&mut svm, claim(&stranger), &stranger), still_active);
+
+    // Lamports sent to the vault outside a deposit aren't the beneficiary's
+    let donation = LAMPORTS_PER_SOL;
+    svm.airdrop(&vault_pda, donation)
+        .expect("Failed to donate to the vault");
+
+    let rent_floor = svm.minimum_balance_for_rent_exemption(VaultState::LEN);
+    let beneficiary_before = svm.get_balance(&beneficiary.pubkey()).unwrap();
+    send(&mut svm, claim(&beneficiary), &beneficiary)
+        .expect("BeneficiaryClaim should succeed after the inactivity window");
+
+    assert_eq!(
+        svm.get_balance(&beneficiary.pubkey()).unwrap(),
+        beneficiary_before + 3 * LAMPORTS_PER_SOL - 5_000
+    );
+    assert_eq!(svm.get_balance(&vault_pda).unwrap(), rent_floor + donation);
+}
+
+#[test]
+fn test_withdraw_many_counts_the_owner_as_active_for_the_beneficiary() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let owner = Keypair::new();
+    let beneficiary = Keypair::new();
+    for signer in [&owner, &beneficiary] {
+        svm.airdrop(&signer.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop");
+    }
+
+    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let inactivity_secs = 30 * 24 * 60 * 60;
+    send_all(
+        &mut svm,
+        &[
+            create_open_vault_instruction(owner.pubkey(), vault_pda),
+            create_deposit_instruction(owner.pubkey(), vault_pda, 2 * LAMPORTS_PER_SOL),
+            create_set_beneficiary_instruction(
+                owner.pubkey(),
+                vault_pda,
+                beneficiary.pubkey(),
+                inactivity_secs,
+            ),
+        ],
+        &[&owner],
+    )
+    .expect("OpenVault, deposit and SetBeneficiary should succeed");
+
+    // A donation keeps the vault open once WithdrawMany drains its balance
+    svm.airdrop(&vault_pda, LAMPORTS_PER_SOL)
+        .expect("Failed to donate to the vault");
+
+    let mut clock = svm.get_sysvar::<Clock>();
+    let deadline = clock.unix_timestamp + inactivity_secs as i64;
+    clock.unix_timestamp = deadline - 1;
+    svm.set_sysvar::<Clock>(&clock);
+    send(
+        &mut svm,
+        create_withdraw_many_instruction(owner.pubkey(), &[(vault_pda, 0)]),
+        &owner,
+    )
+    .expect("WithdrawMany should succeed");
+
+    // The owner never deposited or used Withdraw, yet the window restarted
+    clock.unix_timestamp = deadline;
+    svm.set_sysvar::<Clock>(&clock);
+    assert_eq!(
+        send(
+            &mut svm,
+            create_beneficiary_claim_instruction(beneficiary.pubkey(), owner.pubkey(), vault_pda),
+            &beneficiary,
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::OwnerStillActive as u32),
+        ))
     );
 }
 
 #[test]
-fn test_deposit_with_zero_amount_fails() {
+fn test_relayed_withdraw_tips_from_the_gas_reserve_and_leaves_the_balance_whole() {
     let mut svm = LiteSVM::new();
-    let program_bytes =
-        std::fs::read("target/deploy/blueshift_vault.so").expect("Failed to read program file");
-    svm.add_program(PROGRAM_ID, &program_bytes);
+    load_vault_program(&mut svm).expect("Failed to load program");
 
     let owner = Keypair::new();
+    let relayer = Keypair::new();
     svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
         .expect("Failed to airdrop");
+    svm.airdrop(&relayer.pubkey(), LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
 
     let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
-
-    // Try to deposit zero lamports
-    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, 0);
-
-    let recent_blockhash = svm.latest_blockhash();
+    let deposit = 2 * LAMPORTS_PER_SOL;
+    let reserve = 10_000_000;
     let tx = Transaction::new_signed_with_payer(
-        &[deposit_ix],
+        &[
+            create_open_vault_instruction(owner.pubkey(), vault_pda),
+            create_deposit_instruction(owner.pubkey(), vault_pda, deposit),
+            create_fund_gas_reserve_instruction(owner.pubkey(), vault_pda, reserve),
+        ],
         Some(&owner.pubkey()),
         &[&owner],
-        recent_blockhash,
+        svm.latest_blockhash(),
     );
+    svm.send_transaction(tx)
+        .expect("Open, deposit and FundGasReserve should succeed");
 
-    let tx_result = svm.send_transaction(tx);
-    assert!(
-        tx_result.is_err(),
-        "Deposit with zero amount should fail"
+    let state_of = |svm: &LiteSVM| {
+        let account = svm.get_account(&vault_pda).expect("Vault should exist");
+        *VaultState::from_bytes(&account.data).expect("State should be readable")
+    };
+    let rent = svm.minimum_balance_for_rent_exemption(VaultState::LEN);
+    let funded = state_of(&svm);
+    assert_eq!(funded.gas_reserve(), reserve);
+    assert_eq!(funded.balance(), deposit);
+    assert_eq!(
+        svm.get_balance(&vault_pda).unwrap(),
+        rent + deposit + reserve
+    );
+
+    let tip = 4_000_000;
+    let message = approval::tip_approval_message(
+        &vault_pda.to_bytes(),
+        tip,
+        1,
+        &relayer.pubkey().to_bytes(),
+        i64::MAX,
+    );
+    svm.expire_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            create_ed25519_verify_instruction(&owner, &message),
+            create_withdraw_all_with_tip_instruction(
+                relayer.pubkey(),
+                owner.pubkey(),
+                vault_pda,
+                tip,
+                1,
+                i64::MAX,
+            ),
+        ],
+        Some(&relayer.pubkey()),
+        &[&relayer],
+        svm.latest_blockhash(),
+    );
+    let fee = total_fees([&tx]);
+    let owner_before = svm.get_balance(&owner.pubkey()).unwrap();
+    let relayer_before = svm.get_balance(&relayer.pubkey()).unwrap();
+    svm.send_transaction(tx)
+        .expect("Tipped withdrawal should succeed");
+
+    // The tip came out of the reserve, so the owner received every deposited lamport
+    assert_eq!(
+        svm.get_balance(&relayer.pubkey()).unwrap(),
+        relayer_before - fee + tip
     );
+    assert_eq!(
+        svm.get_balance(&owner.pubkey()).unwrap(),
+        owner_before + deposit
+    );
+    assert_eq!(state_of(&svm).gas_reserve(), reserve - tip);
+    assert_eq!(svm.get_balance(&vault_pda).unwrap(), rent + reserve - tip);
 }
 
 #[test]
-fn test_deposit_non_empty_vault_fails() {
+fn test_vault_tracks_two_mints_and_withdraws_each_independently() {
     let mut svm = LiteSVM::new();
-    let program_bytes =
-        std::fs::read("target/deploy/blueshift_vault.so").expect("Failed to read program file");
-    svm.add_program(PROGRAM_ID, &program_bytes);
+    load_vault_program(&mut svm).expect("Failed to load program");
 
     let owner = Keypair::new();
     svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
         .expect("Failed to airdrop");
 
     let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    send(
+        &mut svm,
+        create_open_vault_instruction(owner.pubkey(), vault_pda),
+        &owner,
+    )
+    .expect("OpenVault should succeed");
 
-    // First deposit - should succeed
-    let deposit_amount = 2 * LAMPORTS_PER_SOL;
-    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, deposit_amount);
+    // Each mint comes from the owner's ATA for it into the vault's ATA for it
+    let mint_a = Pubkey::new_unique();
+    let mint_b = Pubkey::new_unique();
+    let source_a = find_ata(&owner.pubkey(), &mint_a);
+    let source_b = find_ata(&owner.pubkey(), &mint_b);
+    set_token_account(&mut svm, source_a, &mint_a, &owner.pubkey(), 1_000);
+    set_token_account(&mut svm, source_b, &mint_b, &owner.pubkey(), 1_000);
+    for mint in [&mint_a, &mint_b] {
+        set_token_account(&mut svm, find_ata(&vault_pda, mint), mint, &vault_pda, 0);
+    }
 
-    let recent_blockhash = svm.latest_blockhash();
-    let tx = Transaction::new_signed_with_payer(
-        &[deposit_ix],
-        Some(&owner.pubkey()),
-        &[&owner],
-        recent_blockhash,
-    );
+    send(
+        &mut svm,
+        create_deposit_token_instruction(owner.pubkey(), vault_pda, source_a, mint_a, 700),
+        &owner,
+    )
+    .expect("Depositing mint A should succeed");
+    send(
+        &mut svm,
+        create_deposit_token_instruction(owner.pubkey(), vault_pda, source_b, mint_b, 400),
+        &owner,
+    )
+    .expect("Depositing mint B should succeed");
 
-    svm.send_transaction(tx)
-        .expect("First deposit should succeed");
+    let state_of = |svm: &LiteSVM| {
+        let account = svm.get_account(&vault_pda).expect("Vault should exist");
+        *VaultState::from_bytes(&account.data).expect("State should be readable")
+    };
+    let state = state_of(&svm);
+    assert_eq!(state.token_balance(&mint_a.to_bytes()), 700);
+    assert_eq!(state.token_balance(&mint_b.to_bytes()), 400);
+    assert_eq!(token_balance(&svm, &find_ata(&vault_pda, &mint_a)), 700);
+    assert_eq!(token_balance(&svm, &find_ata(&vault_pda, &mint_b)), 400);
 
-    // Second deposit - should fail because vault is not empty
-    let deposit_ix2 = create_deposit_instruction(owner.pubkey(), vault_pda, deposit_amount);
+    // Tracked tokens can't be swept out from under their entries
+    assert_eq!(
+        send(
+            &mut svm,
+            create_sweep_token_instruction(
+                owner.pubkey(),
+                vault_pda,
+                find_ata(&vault_pda, &mint_a),
+                source_a,
+            ),
+            &owner
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::VaultEmpty as u32)
+        ))
+    );
 
-    let recent_blockhash = svm.latest_blockhash();
-    let tx2 = Transaction::new_signed_with_payer(
-        &[deposit_ix2],
-        Some(&owner.pubkey()),
-        &[&owner],
-        recent_blockhash,
+    // Tokens only go back to the owner's ATA for the mint, even to another
+    // account the owner holds
+    let other_account = Pubkey::new_unique();
+    set_token_account(&mut svm, other_account, &mint_a, &owner.pubkey(), 0);
+    assert_eq!(
+        send(
+            &mut svm,
+            create_withdraw_token_instruction(
+                owner.pubkey(),
+                vault_pda,
+                other_account,
+                mint_a,
+                300
+            ),
+            &owner
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::InvalidDestinationAta as u32)
+        ))
     );
 
-    let tx_result = svm.send_transaction(tx2);
-    assert!(
-        tx_result.is_err(),
-        "Second deposit should fail when vault is not empty"
+    // Withdrawing one mint leaves the other untouched
+    send(
+        &mut svm,
+        create_withdraw_token_instruction(owner.pubkey(), vault_pda, source_a, mint_a, 300),
+        &owner,
+    )
+    .expect("Withdrawing part of mint A should succeed");
+    let state = state_of(&svm);
+    assert_eq!(state.token_balance(&mint_a.to_bytes()), 400);
+    assert_eq!(state.token_balance(&mint_b.to_bytes()), 400);
+    assert_eq!(token_balance(&svm, &source_a), 600);
+    assert_eq!(token_balance(&svm, &source_b), 600);
+
+    // Nor can more than mint A's entry holds
+    assert_eq!(
+        send(
+            &mut svm,
+            create_withdraw_token_instruction(owner.pubkey(), vault_pda, source_a, mint_a, 401),
+            &owner
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::InsufficientFunds as u32)
+        ))
     );
+
+    send(
+        &mut svm,
+        create_withdraw_token_instruction(owner.pubkey(), vault_pda, source_b, mint_b, 400),
+        &owner,
+    )
+    .expect("Withdrawing all of mint B should succeed");
+    let state = state_of(&svm);
+    assert_eq!(state.token_balance(&mint_a.to_bytes()), 400);
+    assert_eq!(state.token_balance(&mint_b.to_bytes()), 0);
+    assert_eq!(token_balance(&svm, &source_b), 1_000);
+    assert_eq!(token_balance(&svm, &find_ata(&vault_pda, &mint_a)), 400);
 }
 
 #[test]
-fn test_withdraw_success() {
+fn test_withdraw_rejects_a_spoofed_vault_at_a_non_pda_address() {
     let mut svm = LiteSVM::new();
-    let program_bytes =
-        std::fs::read("target/deploy/blueshift_vault.so").expect("Failed to read program file");
-    svm.add_program(PROGRAM_ID, &program_bytes);
+    load_vault_program(&mut svm).expect("Failed to load program");
 
-    let owner = Keypair::new();
-    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+    let attacker = Keypair::new();
+    svm.airdrop(&attacker.pubkey(), 10 * LAMPORTS_PER_SOL)
         .expect("Failed to airdrop");
 
-    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let withdraw = |svm: &mut LiteSVM, fake_vault: Pubkey| {
+        svm.expire_blockhash();
+        let tx = Transaction::new_signed_with_payer(
+            &[create_withdraw_instruction(attacker.pubkey(), fake_vault)],
+            Some(&attacker.pubkey()),
+            &[&attacker],
+            svm.latest_blockhash(),
+        );
+        svm.send_transaction(tx).map(|_| ()).map_err(|f| f.err)
+    };
+    let invalid_pda = Err(TransactionError::InstructionError(
+        0,
+        InstructionError::Custom(VaultError::InvalidPda as u32),
+    ));
 
-    // First, deposit some lamports
-    let deposit_amount = 2 * LAMPORTS_PER_SOL;
-    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, deposit_amount);
+    // A funded system account the attacker picked, not derived from their key
+    let fake_vault = Pubkey::new_unique();
+    assert_ne!(fake_vault, find_vault_pda(&attacker.pubkey()).0);
+    svm.airdrop(&fake_vault, 5 * LAMPORTS_PER_SOL)
+        .expect("Failed to fund the fake vault");
+    assert_eq!(withdraw(&mut svm, fake_vault), invalid_pda);
+    assert_eq!(svm.get_balance(&fake_vault).unwrap(), 5 * LAMPORTS_PER_SOL);
 
-    let recent_blockhash = svm.latest_blockhash();
-    let tx = Transaction::new_signed_with_payer(
-        &[deposit_ix],
-        Some(&owner.pubkey()),
-        &[&owner],
-        recent_blockhash,
+    // Nor does forged state naming the attacker as owner stand in for the derivation
+    let forged_vault = Pubkey::new_unique();
+    let mut data = vec![0u8; VaultState::LEN];
+    data[0] = VaultState::VERSION;
+    data[1..33].copy_from_slice(attacker.pubkey().as_ref());
+    svm.set_account(
+        forged_vault,
+        Account {
+            lamports: 5 * LAMPORTS_PER_SOL,
+            data,
+            owner: PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    )
+    .expect("Failed to set the forged vault");
+    assert_eq!(withdraw(&mut svm, forged_vault), invalid_pda);
+    assert_eq!(
+        svm.get_balance(&forged_vault).unwrap(),
+        5 * LAMPORTS_PER_SOL
     );
+}
 
-    svm.send_transaction(tx).expect("Deposit should succeed");
+#[test]
+fn test_derive_vault_returns_the_client_side_derivation() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
 
-    // Get owner balance before withdrawal
-    let owner_balance_before = svm
-        .get_account(&owner.pubkey())
-        .expect("Owner account should exist")
-        .lamports;
+    let payer = Keypair::new();
+    svm.airdrop(&payer.pubkey(), LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
 
-    // Now withdraw
-    let withdraw_ix = create_withdraw_instruction(owner.pubkey(), vault_pda);
+    let derive = |svm: &LiteSVM, instruction: Instruction| {
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&payer.pubkey()),
+            &[&payer],
+            svm.latest_blockhash(),
+        );
+        let data = svm
+            .simulate_transaction(tx)
+            .expect("DeriveVault should succeed")
+            .meta
+            .return_data
+            .data;
+        assert_eq!(data.len(), 33);
+        (Pubkey::try_from(&data[..32]).unwrap(), data[32])
+    };
 
-    let recent_blockhash = svm.latest_blockhash();
-    let tx = Transaction::new_signed_with_payer(
-        &[withdraw_ix],
-        Some(&owner.pubkey()),
-        &[&owner],
-        recent_blockhash,
+    // The owner doesn't have to sign, or even be passed as an account
+    let owner = Pubkey::new_unique();
+    let salt = [7u8; 32];
+    for (id, salt, expected) in [
+        (0, [0u8; 32], client::find_vault_address(&owner, 0)),
+        (3, [0u8; 32], client::find_vault_address(&owner, 3)),
+        (3, salt, client::find_salted_vault_address(&owner, 3, &salt)),
+    ] {
+        assert_eq!(
+            derive(&svm, create_derive_vault_instruction(owner, id, &salt)),
+            expected
+        );
+    }
+    assert_eq!(
+        client::find_vault_address(&owner, 0),
+        find_vault_pda(&owner)
     );
 
-    let tx_result = svm.send_transaction(tx);
-    assert!(tx_result.is_ok(), "Withdraw transaction should succeed");
+    // The id and salt are optional, defaulting to the owner's default vault
+    let mut default_only = create_derive_vault_instruction(owner, 0, &[0u8; 32]);
+    default_only.data.truncate(33);
+    assert_eq!(derive(&svm, default_only), find_vault_pda(&owner));
+}
 
-    // Verify vault is empty (it may still exist with 0 lamports or may not exist at all)
-    let vault_account = svm.get_account(&vault_pda);
-    if let Some(account) = vault_account {
-        assert_eq!(
-            account.lamports, 0,
-            "Vault should be empty after withdrawal"
+#[test]
+fn test_slot_lock_holds_the_vault_until_the_later_of_both_locks() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
+
+    let slot_only = Keypair::new();
+    let both = Keypair::new();
+    for owner in [&slot_only, &both] {
+        svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop");
+    }
+
+    let locked = Err(TransactionError::InstructionError(
+        0,
+        InstructionError::Custom(VaultError::VaultLocked as u32),
+    ));
+
+    let clock = svm.get_sysvar::<Clock>();
+    let (slot, now) = (clock.slot, clock.unix_timestamp);
+    let (slot_only_vault, _) = find_vault_pda(&slot_only.pubkey());
+    let (both_vault, _) = find_vault_pda(&both.pubkey());
+    for (owner, vault, locks) in [
+        (
+            &slot_only,
+            slot_only_vault,
+            vec![create_extend_slot_lock_instruction(
+                slot_only.pubkey(),
+                slot_only_vault,
+                slot + 100,
+            )],
+        ),
+        (
+            &both,
+            both_vault,
+            vec![
+                create_extend_slot_lock_instruction(both.pubkey(), both_vault, slot + 200),
+                create_extend_lock_instruction(both.pubkey(), both_vault, now + 3_600),
+            ],
+        ),
+    ] {
+        let mut instructions = vec![
+            create_open_vault_instruction(owner.pubkey(), vault),
+            create_deposit_instruction(owner.pubkey(), vault, LAMPORTS_PER_SOL),
+        ];
+        instructions.extend(locks);
+        let tx = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&owner.pubkey()),
+            &[owner],
+            svm.latest_blockhash(),
         );
+        svm.send_transaction(tx)
+            .expect("Open, deposit and lock should succeed");
     }
-    // If vault doesn't exist, that's also valid - it means all lamports were withdrawn
 
-    // Verify owner balance increased
-    let owner_balance_after = svm
-        .get_account(&owner.pubkey())
-        .expect("Owner account should exist")
-        .lamports;
+    // Like the timestamp lock, the slot lock can't be shortened
+    assert_eq!(
+        send(
+            &mut svm,
+            create_extend_slot_lock_instruction(slot_only.pubkey(), slot_only_vault, slot + 100),
+            &slot_only,
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::CannotShortenLock as u32)
+        ))
+    );
 
-    assert!(
-        owner_balance_after > owner_balance_before,
-        "Owner balance should increase after withdrawal"
+    svm.warp_to_slot(slot + 99);
+    assert_eq!(
+        send(
+            &mut svm,
+            create_withdraw_instruction(slot_only.pubkey(), slot_only_vault),
+            &slot_only,
+        ),
+        locked
     );
 
-    // Owner should have received approximately the deposited amount (minus fees)
-    assert!(
-        owner_balance_after >= owner_balance_before + deposit_amount - 10000,
-        "Owner should receive approximately the deposited amount"
+    // The slot-locked vault opens at its slot, whatever the wall clock says
+    svm.warp_to_slot(slot + 100);
+    send(
+        &mut svm,
+        create_withdraw_instruction(slot_only.pubkey(), slot_only_vault),
+        &slot_only,
+    )
+    .expect("Withdraw at the unlock slot should succeed");
+
+    // With the timestamp passed too, the later slot lock still holds the other vault
+    let mut clock = svm.get_sysvar::<Clock>();
+    clock.unix_timestamp = now + 3_600;
+    svm.set_sysvar::<Clock>(&clock);
+    assert_eq!(
+        send(
+            &mut svm,
+            create_withdraw_instruction(both.pubkey(), both_vault),
+            &both
+        ),
+        locked
     );
+
+    svm.warp_to_slot(slot + 200);
+    send(
+        &mut svm,
+        create_withdraw_instruction(both.pubkey(), both_vault),
+        &both,
+    )
+    .expect("Withdraw once both locks passed should succeed");
 }
 
 #[test]
-fn test_withdraw_empty_vault_fails() {
+fn test_delegate_withdrawals_are_capped_per_delegate_while_the_owner_is_not() {
     let mut svm = LiteSVM::new();
-    let program_bytes =
-        std::fs::read("target/deploy/blueshift_vault.so").expect("Failed to read program file");
-    svm.add_program(PROGRAM_ID, &program_bytes);
+    load_vault_program(&mut svm).expect("Failed to load program");
 
     let owner = Keypair::new();
-    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
-        .expect("Failed to airdrop");
-
-    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
-
-    // Try to withdraw from empty vault
-    let withdraw_ix = create_withdraw_instruction(owner.pubkey(), vault_pda);
+    let delegate_a = Keypair::new();
+    let stranger = Keypair::new();
+    for signer in [&owner, &delegate_a, &stranger] {
+        svm.airdrop(&signer.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop");
+    }
 
-    let recent_blockhash = svm.latest_blockhash();
+    let (vault_pda, _) = find_vault_pda(&owner.pubkey());
     let tx = Transaction::new_signed_with_payer(
-        &[withdraw_ix],
+        &[
+            create_open_vault_instruction(owner.pubkey(), vault_pda),
+            create_deposit_instruction(owner.pubkey(), vault_pda, 5 * LAMPORTS_PER_SOL),
+            create_set_delegate_instruction(
+                owner.pubkey(),
+                vault_pda,
+                delegate_a.pubkey(),
+                LAMPORTS_PER_SOL,
+            ),
+        ],
         Some(&owner.pubkey()),
         &[&owner],
-        recent_blockhash,
+        svm.latest_blockhash(),
     );
+    svm.send_transaction(tx)
+        .expect("Open, deposit and set delegate should succeed");
 
-    let tx_result = svm.send_transaction(tx);
-    assert!(
-        tx_result.is_err(),
-        "Withdraw from empty vault should fail"
+    // Delegate A can't take more than its cap in one withdrawal
+    assert_eq!(
+        send(
+            &mut svm,
+            create_delegate_withdraw_instruction(
+                delegate_a.pubkey(),
+                vault_pda,
+                2 * LAMPORTS_PER_SOL
+            ),
+            &delegate_a,
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::DelegateCapExceeded as u32)
+        ))
+    );
+
+    let before = svm.get_balance(&delegate_a.pubkey()).unwrap();
+    send(
+        &mut svm,
+        create_delegate_withdraw_instruction(delegate_a.pubkey(), vault_pda, LAMPORTS_PER_SOL),
+        &delegate_a,
+    )
+    .expect("Withdrawing up to the cap should succeed");
+    assert_eq!(
+        svm.get_balance(&delegate_a.pubkey()).unwrap(),
+        before + LAMPORTS_PER_SOL - 5_000
+    );
+    let fetcher = |address: &Pubkey| svm.get_account(address);
+    let stats = client::global_stats(&fetcher).expect("Stats should exist");
+    assert_eq!(stats.lifetime_withdrawals(), LAMPORTS_PER_SOL);
+
+    // A key the vault doesn't list can't withdraw at all
+    assert_eq!(
+        send(
+            &mut svm,
+            create_delegate_withdraw_instruction(stranger.pubkey(), vault_pda, 1),
+            &stranger,
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::NotDelegate as u32)
+        ))
+    );
+
+    // The owner withdraws past any delegate's cap
+    send(
+        &mut svm,
+        create_withdraw_amount_instruction(owner.pubkey(), vault_pda, 2 * LAMPORTS_PER_SOL, 0),
+        &owner,
+    )
+    .expect("The owner's withdrawal should not be capped");
+
+    let account = svm.get_account(&vault_pda).unwrap();
+    let state = *VaultState::from_bytes(&account.data).unwrap();
+    assert_eq!(state.balance(), 2 * LAMPORTS_PER_SOL);
+    assert_eq!(
+        state.delegate_cap(&delegate_a.pubkey().to_bytes()),
+        Some(LAMPORTS_PER_SOL)
     );
 }
 
 #[test]
-fn test_withdraw_unauthorized_fails() {
+fn test_client_migrate_moves_the_balance_to_the_new_vault_in_one_transaction() {
     let mut svm = LiteSVM::new();
-    let program_bytes =
-        std::fs::read("target/deploy/blueshift_vault.so").expect("Failed to read program file");
-    svm.add_program(PROGRAM_ID, &program_bytes);
+    load_vault_program(&mut svm).expect("Failed to load program");
 
     let owner = Keypair::new();
-    let attacker = Keypair::new();
-    
     svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
-        .expect("Failed to airdrop to owner");
-    svm.airdrop(&attacker.pubkey(), 10 * LAMPORTS_PER_SOL)
-        .expect("Failed to airdrop to attacker");
-
-    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
-
-    // Owner deposits
-    let deposit_amount = 2 * LAMPORTS_PER_SOL;
-    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, deposit_amount);
+        .expect("Failed to airdrop");
 
-    let recent_blockhash = svm.latest_blockhash();
     let tx = Transaction::new_signed_with_payer(
-        &[deposit_ix],
+        &client::create_and_deposit(&owner.pubkey(), 2 * LAMPORTS_PER_SOL),
         Some(&owner.pubkey()),
         &[&owner],
-        recent_blockhash,
+        svm.latest_blockhash(),
     );
+    svm.send_transaction(tx)
+        .expect("Open and deposit should succeed");
 
-    svm.send_transaction(tx).expect("Deposit should succeed");
-
-    // Attacker tries to withdraw from owner's vault
-    let withdraw_ix = create_withdraw_instruction(attacker.pubkey(), vault_pda);
-
-    let recent_blockhash = svm.latest_blockhash();
+    svm.expire_blockhash();
     let tx = Transaction::new_signed_with_payer(
-        &[withdraw_ix],
-        Some(&attacker.pubkey()),
-        &[&attacker],
-        recent_blockhash,
+        &client::migrate(&owner.pubkey(), 0, 7),
+        Some(&owner.pubkey()),
+        &[&owner],
+        svm.latest_blockhash(),
     );
+    svm.send_transaction(tx).expect("Migration should succeed");
 
-    let tx_result = svm.send_transaction(tx);
-    assert!(
-        tx_result.is_err(),
-        "Attacker should not be able to withdraw from owner's vault"
+    let (old_vault, _) = client::find_vault_address(&owner.pubkey(), 0);
+    let (new_vault, _) = client::find_vault_address(&owner.pubkey(), 7);
+    assert_eq!(svm.get_balance(&old_vault).unwrap_or(0), 0);
+
+    let fetcher = |address: &Pubkey| svm.get_account(address);
+    assert!(client::fetch_vault_state(&fetcher, &owner.pubkey(), 0).is_none());
+    let state = client::fetch_vault_state(&fetcher, &owner.pubkey(), 7)
+        .expect("The new vault should be opened");
+    assert_eq!(state.balance(), 2 * LAMPORTS_PER_SOL);
+    assert_eq!(
+        client::vault_registry(&fetcher, &owner.pubkey()),
+        Some(vec![new_vault])
     );
 }
 
 #[test]
-fn test_deposit_and_withdraw_full_cycle() {
+fn test_initialize_requires_the_upgrade_authority() {
     let mut svm = LiteSVM::new();
-    let program_bytes =
-        std::fs::read("target/deploy/blueshift_vault.so").expect("Failed to read program file");
-    svm.add_program(PROGRAM_ID, &program_bytes);
+    load_vault_program(&mut svm).expect("Failed to load program");
 
-    let owner = Keypair::new();
-    let initial_airdrop = 10 * LAMPORTS_PER_SOL;
-    svm.airdrop(&owner.pubkey(), initial_airdrop)
-        .expect("Failed to airdrop");
+    let deployer = Keypair::new();
+    let front_runner = Keypair::new();
+    for signer in [&deployer, &front_runner] {
+        svm.airdrop(&signer.pubkey(), LAMPORTS_PER_SOL)
+            .expect("Failed to airdrop");
+    }
 
-    let (vault_pda, _bump) = find_vault_pda(&owner.pubkey());
+    let initialize = |svm: &mut LiteSVM, signer: &Keypair| {
+        send(
+            svm,
+            create_initialize_instruction(signer.pubkey(), 250),
+            signer,
+        )
+    };
 
-    // Deposit
-    let deposit_amount = 5 * LAMPORTS_PER_SOL;
-    let deposit_ix = create_deposit_instruction(owner.pubkey(), vault_pda, deposit_amount);
+    // Without a ProgramData account nobody can create the config
+    assert_eq!(
+        initialize(&mut svm, &deployer),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::IncorrectProgramId
+        ))
+    );
 
-    let recent_blockhash = svm.latest_blockhash();
-    let tx = Transaction::new_signed_with_payer(
-        &[deposit_ix],
-        Some(&owner.pubkey()),
-        &[&owner],
-        recent_blockhash,
+    set_upgrade_authority(&mut svm, &deployer.pubkey());
+
+    // Calling first doesn't make anyone but the deployer the admin
+    assert_eq!(
+        initialize(&mut svm, &front_runner),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::NotUpgradeAuthority as u32)
+        ))
     );
+    assert!(svm.get_account(&find_config_pda().0).is_none());
 
-    svm.send_transaction(tx).expect("Deposit should succeed");
+    initialize(&mut svm, &deployer).expect("The upgrade authority should initialize");
+    let config = svm.get_account(&find_config_pda().0).unwrap();
+    assert_eq!(&config.data[..32], deployer.pubkey().as_ref());
+}
 
-    // Verify deposit
-    let vault_balance = svm
-        .get_account(&vault_pda)
-        .expect("Vault should exist")
-        .lamports;
-    assert_eq!(vault_balance, deposit_amount);
+#[test]
+fn test_split_vault_counts_against_and_carries_the_daily_limit() {
+    let mut svm = LiteSVM::new();
+    load_vault_program(&mut svm).expect("Failed to load program");
 
-    // Withdraw
-    let withdraw_ix = create_withdraw_instruction(owner.pubkey(), vault_pda);
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .expect("Failed to airdrop");
 
-    let recent_blockhash = svm.latest_blockhash();
-    let tx = Transaction::new_signed_with_payer(
-        &[withdraw_ix],
-        Some(&owner.pubkey()),
+    let (source_vault, _bump) = find_vault_pda(&owner.pubkey());
+    let (new_vault, _bump) = find_vault_pda_with_id(&owner.pubkey(), 1);
+    let daily_limit = LAMPORTS_PER_SOL;
+    let mut open_ix = create_open_vault_instruction_with_flags(
+        owner.pubkey(),
+        source_vault,
+        VaultState::FLAG_DAILY_LIMIT,
+    );
+    open_ix.data.extend_from_slice(&daily_limit.to_le_bytes());
+    send_all(
+        &mut svm,
+        &[
+            open_ix,
+            create_deposit_instruction(owner.pubkey(), source_vault, 3 * LAMPORTS_PER_SOL),
+        ],
         &[&owner],
-        recent_blockhash,
+    )
+    .expect("Failed to set up limited vault");
+
+    // More than the daily limit can't be split off in one go
+    assert_eq!(
+        send_all(
+            &mut svm,
+            &[create_split_vault_instruction(
+                owner.pubkey(),
+                2 * LAMPORTS_PER_SOL,
+                0,
+                1
+            )],
+            &[&owner]
+        ),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::DailyLimitExceeded as u32)
+        ))
     );
 
-    svm.send_transaction(tx)
-        .expect("Withdraw should succeed");
+    send_all(
+        &mut svm,
+        &[create_split_vault_instruction(
+            owner.pubkey(),
+            daily_limit / 2,
+            0,
+            1,
+        )],
+        &[&owner],
+    )
+    .expect("Splitting within the limit should succeed");
 
-    // Verify withdrawal - vault may not exist or have 0 lamports
-    let vault_balance_after = svm
-        .get_account(&vault_pda)
-        .map(|acc| acc.lamports)
-        .unwrap_or(0);
-    assert_eq!(vault_balance_after, 0, "Vault should be empty");
+    let source_account = svm.get_account(&source_vault).unwrap();
+    let source_state = VaultState::from_bytes(&source_account.data).unwrap();
+    assert_eq!(source_state.daily_withdrawn(), daily_limit / 2);
 
-    let owner_final_balance = svm
-        .get_account(&owner.pubkey())
-        .expect("Owner should exist")
-        .lamports;
+    // The new vault starts with the split already withdrawn against its limit
+    let new_account = svm.get_account(&new_vault).unwrap();
+    let new_state = VaultState::from_bytes(&new_account.data).unwrap();
+    assert_eq!(new_state.daily_limit(), daily_limit);
+    assert_eq!(new_state.daily_withdrawn(), daily_limit / 2);
 
-    // Owner should have close to the initial amount (minus transaction fees)
-    // Allow for a small margin for transaction fees
-    let expected_min_balance = initial_airdrop - 50000; // Allow up to 0.00005 SOL in fees
-    assert!(
-        owner_final_balance >= expected_min_balance,
-        "Owner should get most of their funds back (fees: {} lamports)",
-        initial_airdrop - owner_final_balance
-    );
+    for vault in [source_vault, new_vault] {
+        assert_eq!(
+            send_all(
+                &mut svm,
+                &[create_withdraw_amount_instruction(
+                    owner.pubkey(),
+                    vault,
+                    daily_limit,
+                    0
+                )],
+                &[&owner]
+            ),
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(VaultError::DailyLimitExceeded as u32)
+            ))
+        );
+    }
 }